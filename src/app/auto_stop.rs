@@ -0,0 +1,43 @@
+/// Popup shown after the `[auto_stop]` rules stopped a task that was left running.
+use chrono::{DateTime, Local};
+
+use crate::types::IntervalId;
+
+#[derive(Clone, Debug)]
+pub struct AutoStopPopup {
+    pub interval_id: IntervalId,
+    pub task: String,
+    pub start: DateTime<Local>,
+    pub stopped_at: DateTime<Local>,
+    /// New end as `HH:MM` on the day it stopped or `YYYY-MM-DD HH:MM`, prefilled with the last
+    /// key press while the task ran when there was one.
+    pub input: String,
+    pub error: Option<String>,
+}
+
+impl AutoStopPopup {
+    pub fn new(
+        interval_id: IntervalId,
+        task: String,
+        start: DateTime<Local>,
+        stopped_at: DateTime<Local>,
+        last_activity: Option<DateTime<Local>>,
+    ) -> Self {
+        let end = last_activity
+            .filter(|activity| *activity > start && *activity < stopped_at)
+            .unwrap_or(stopped_at);
+        let input = if end.date_naive() == stopped_at.date_naive() {
+            end.format("%H:%M").to_string()
+        } else {
+            end.format("%Y-%m-%d %H:%M").to_string()
+        };
+        Self {
+            interval_id,
+            task,
+            start,
+            stopped_at,
+            input,
+            error: None,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+/// Time for the live displays of running tasks, steady when the system clock is set back.
+use std::time::Instant;
+
+use chrono::{DateTime, Duration, Local};
+
+use crate::types::IntervalId;
+
+/// Wall-clock time that never runs backwards while the same interval runs. Between ticks it
+/// advances with the monotonic clock, so an NTP correction or a manual change that sets the clock
+/// back doesn't make the running timer jump back or go negative. It follows the wall clock when
+/// that is ahead, as the monotonic clock stands still while the machine sleeps, and goes back to
+/// the wall clock when another interval starts or nothing runs, as new times are stored by it.
+#[derive(Clone, Copy, Debug)]
+pub struct LiveClock {
+    /// Time shown at `instant`.
+    base: DateTime<Local>,
+    instant: Instant,
+    /// The running interval the time is held steady for.
+    interval: Option<IntervalId>,
+}
+
+impl LiveClock {
+    pub fn new() -> Self {
+        Self {
+            base: Local::now(),
+            instant: Instant::now(),
+            interval: None,
+        }
+    }
+
+    pub fn now(&self) -> DateTime<Local> {
+        self.steady().max(Local::now())
+    }
+
+    /// Moves the base along while `running` keeps running, so a clock that went ahead and came
+    /// back doesn't pull the time shown back with it. Resyncs with the wall clock otherwise.
+    pub fn tick(&mut self, running: Option<IntervalId>) {
+        self.base = if running.is_some() && running == self.interval {
+            self.now()
+        } else {
+            Local::now()
+        };
+        self.instant = Instant::now();
+        self.interval = running;
+    }
+
+    fn steady(&self) -> DateTime<Local> {
+        Duration::from_std(self.instant.elapsed())
+            .ok()
+            .and_then(|elapsed| self.base.checked_add_signed(elapsed))
+            .unwrap_or(self.base)
+    }
+}
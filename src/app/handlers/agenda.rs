@@ -0,0 +1,17 @@
+use crate::app::{Action, App};
+
+use super::ViewHandler;
+
+pub struct DayAgenda;
+
+impl ViewHandler for DayAgenda {
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::PreviousPage => app.step_agenda(-1),
+            Action::NextPage => app.step_agenda(1),
+            Action::Delete => app.delete_agenda_interval(),
+            _ => return false,
+        }
+        true
+    }
+}
@@ -0,0 +1,21 @@
+use crate::app::{Action, App};
+
+use super::ViewHandler;
+
+pub struct Calendar;
+
+impl ViewHandler for Calendar {
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::Left => app.step_calendar(-1),
+            Action::Right => app.step_calendar(1),
+            Action::Up => app.step_calendar(-7),
+            Action::Down => app.step_calendar(7),
+            Action::PreviousPage => app.step_calendar_month(-1),
+            Action::NextPage => app.step_calendar_month(1),
+            Action::Agenda => app.open_agenda(app.calendar_date),
+            _ => return false,
+        }
+        true
+    }
+}
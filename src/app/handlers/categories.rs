@@ -0,0 +1,17 @@
+use crate::app::{Action, App};
+
+use super::ViewHandler;
+
+pub struct Categories;
+
+impl ViewHandler for Categories {
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::Edit => app.open_edit_category_popup(),
+            Action::Delete => app.open_delete_category_popup(),
+            Action::New => app.open_new_category_popup(),
+            _ => return false,
+        }
+        true
+    }
+}
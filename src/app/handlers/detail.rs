@@ -0,0 +1,23 @@
+use crate::app::{Action, App};
+
+use super::ViewHandler;
+
+pub struct TickrDetail;
+
+impl ViewHandler for TickrDetail {
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::Left | Action::Right => app.toggle_detail_by_month(),
+            // Previous pages hold older intervals.
+            Action::PreviousPage => app.step_detail_page(1),
+            Action::NextPage => app.step_detail_page(-1),
+            Action::AddInterval => app.open_add_interval_popup(),
+            Action::Notes => app.open_notes_popup(),
+            Action::ToggleBillable => app.toggle_billable(),
+            Action::ToggleDone => app.toggle_done(),
+            Action::BlockedBy => app.open_blocked_by_popup(),
+            _ => return false,
+        }
+        true
+    }
+}
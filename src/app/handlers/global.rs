@@ -0,0 +1,82 @@
+use crate::app::{Action, App, AppView, FocusMode, RefreshScope};
+
+/// Keys while the tab bar has the focus. Up and Down do nothing there.
+pub fn handle_tab_bar_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Left => app.navigate_tab_left(),
+        Action::Right => app.navigate_tab_right(),
+        Action::Open => app.activate_selected_tab(),
+        Action::Up | Action::Down => {}
+        _ => return false,
+    }
+    true
+}
+
+/// Bindings that work the same in every view.
+pub fn handle_global_action(app: &mut App, action: Action) {
+    match action {
+        Action::Quit => app.quit(),
+        Action::Dashboard => app.navigate_to(AppView::Dashboard),
+        Action::Projects => app.navigate_to(AppView::Projects),
+        Action::Tasks => {
+            app.navigate_to(AppView::Tickrs);
+            app.selected_tickr = None;
+            app.selected_tickr_project_name = None;
+            app.selected_tickr_blocker = None;
+        }
+        Action::Worked => {
+            app.navigate_to(AppView::WorkedProjects);
+            app.selected_project = None;
+        }
+        Action::Timeline => app.navigate_to(AppView::Timeline),
+        Action::Calendar => app.navigate_to(AppView::Calendar),
+        Action::Categories => app.navigate_to(AppView::Categories),
+        Action::Stats => app.navigate_to(AppView::Stats),
+        Action::Legend => app.open_legend_popup(),
+        Action::ActivityLog => app.open_activity_popup(),
+        Action::Profiles => app.open_profile_popup(),
+        Action::PomodoroStart => app.start_pomodoro(),
+        Action::PomodoroSkip => app.skip_pomodoro_phase(),
+        Action::PomodoroCancel => app.cancel_pomodoro(),
+        Action::Help => {
+            if app.view == AppView::Help {
+                app.go_back();
+            } else {
+                app.navigate_to(AppView::Help);
+            }
+        }
+        Action::Capture => app.open_capture_popup(),
+        Action::Goal => app.open_goal_popup(),
+        Action::Triage => app.open_triage_popup(),
+        Action::Templates => app.open_template_popup(),
+        Action::ToggleFocus => {
+            app.focus_mode = if app.focus_mode == FocusMode::TabBar {
+                FocusMode::Content
+            } else {
+                FocusMode::TabBar
+            };
+        }
+        Action::Archive => app.toggle_archived(),
+        Action::ShowArchived => {
+            app.show_archived = !app.show_archived;
+            app.refresh(RefreshScope::CurrentView);
+        }
+        Action::Refresh => app.refresh(RefreshScope::CurrentView),
+        Action::Collapse => app.set_projects_collapsed(true, true),
+        Action::Expand => app.set_projects_collapsed(false, true),
+        Action::Up => app.move_selection_up(),
+        Action::Down => app.move_selection_down(),
+        Action::Open => app.open_selected(),
+        Action::StartStop => app.toggle_tickr(),
+        Action::Stop => app.stop_running_tickr(),
+        Action::Resume => app.resume_last_tickr(),
+        Action::Switcher => app.open_switcher_popup(),
+        Action::GoTo => app.go_to_project_from_tickr(),
+        Action::Back => app.go_back(),
+        Action::Edit => app.open_edit_popup(),
+        Action::OpenIssue => app.open_issue(),
+        Action::Delete => app.open_delete_tickr_popup(),
+        Action::NewFromGit => app.open_new_tickr_from_git(),
+        _ => {}
+    }
+}
@@ -0,0 +1,55 @@
+//! Key handling, split by what has the keys: popups first, then the current view, then the
+//! bindings every view shares. Each view's keys live in their own module behind [`ViewHandler`].
+
+mod agenda;
+mod calendar;
+mod categories;
+mod detail;
+mod global;
+mod popups;
+mod projects;
+mod tickrs;
+mod timeline;
+mod worked;
+
+use crossterm::event::KeyCode;
+
+use crate::app::{Action, App, AppView};
+
+pub use global::{handle_global_action, handle_tab_bar_action};
+pub use popups::handle_popup_key;
+
+/// The keys one view handles itself. Actions it doesn't take go on to the shared bindings.
+pub trait ViewHandler {
+    /// Plain keys the view takes before the keymap, e.g. while a search is typed.
+    fn handle_key(&self, _app: &mut App, _code: KeyCode) -> bool {
+        false
+    }
+
+    /// Runs `action` in the view. Returns false when the view has nothing of its own for it.
+    fn handle_action(&self, app: &mut App, action: Action) -> bool;
+}
+
+/// Views with nothing beyond the shared bindings.
+struct NoViewKeys;
+
+impl ViewHandler for NoViewKeys {
+    fn handle_action(&self, _app: &mut App, _action: Action) -> bool {
+        false
+    }
+}
+
+pub fn for_view(view: &AppView) -> &'static dyn ViewHandler {
+    match view {
+        AppView::Projects => &projects::Projects,
+        AppView::Tickrs => &tickrs::Tickrs,
+        AppView::ProjectTickrs => &tickrs::ProjectTickrs,
+        AppView::WorkedProjects => &worked::Worked,
+        AppView::Timeline => &timeline::Timeline,
+        AppView::Calendar => &calendar::Calendar,
+        AppView::DayAgenda => &agenda::DayAgenda,
+        AppView::Categories => &categories::Categories,
+        AppView::TickrDetail => &detail::TickrDetail,
+        AppView::Dashboard | AppView::Stats | AppView::Help => &NoViewKeys,
+    }
+}
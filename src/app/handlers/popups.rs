@@ -0,0 +1,50 @@
+use crossterm::event::KeyCode;
+
+use crate::app::{ActivePopup, App};
+
+/// Keys for the open popup, if there is one. A popup takes every key while it is open.
+pub fn handle_popup_key(app: &mut App, code: KeyCode) -> bool {
+    let Some(popup) = &app.popup else {
+        return false;
+    };
+    match popup {
+        ActivePopup::Update(_) => app.handle_update_key(code),
+        ActivePopup::IntervalError(_) => {
+            if matches!(code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                app.popup = None;
+            }
+        }
+        ActivePopup::AutoStop(_) => app.handle_auto_stop_key(code),
+        ActivePopup::Idle(_) => app.handle_idle_key(code),
+        ActivePopup::Pomodoro(_) => app.handle_pomodoro_popup_key(code),
+        ActivePopup::Legend(_) => {
+            if matches!(
+                code,
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('L') | KeyCode::Char('q')
+            ) {
+                app.popup = None;
+            }
+        }
+        ActivePopup::Activity(_) => app.handle_activity_key(code),
+        ActivePopup::Profile(_) => app.handle_profile_key(code),
+        ActivePopup::Template(_) => app.handle_template_key(code),
+        ActivePopup::Switcher(_) => app.handle_switcher_key(code),
+        ActivePopup::DeleteTickr(_) => app.handle_delete_tickr_key(code),
+        ActivePopup::Confirm(_) => app.handle_confirm_key(code),
+        ActivePopup::DeleteCategory(_) => app.handle_delete_category_key(code),
+        ActivePopup::BlockedStart(_) => app.handle_blocked_start_key(code),
+        ActivePopup::Unblock(_) => app.handle_unblock_key(code),
+        ActivePopup::BlockedBy(_) => app.handle_blocked_by_key(code),
+        ActivePopup::Edit(_) => app.handle_edit_key(code),
+        ActivePopup::AddInterval(_) => app.handle_add_interval_key(code),
+        ActivePopup::Range(_) => app.handle_range_key(code),
+        ActivePopup::Capture(_) => app.handle_capture_key(code),
+        ActivePopup::Goal(_) => app.handle_goal_key(code),
+        ActivePopup::Client(_) => app.handle_client_key(code),
+        ActivePopup::Notes(_) => app.handle_notes_key(code),
+        ActivePopup::Triage(_) => app.handle_triage_key(code),
+        ActivePopup::NewCategory(_) => app.handle_new_category_key(code),
+        ActivePopup::NewTickr(_) => app.handle_new_tickr_key(code),
+    }
+    true
+}
@@ -0,0 +1,49 @@
+use crossterm::event::KeyCode;
+
+use crate::app::{Action, App, RefreshScope};
+
+use super::ViewHandler;
+
+pub struct Projects;
+
+impl ViewHandler for Projects {
+    /// Typing into the search once `/` opened it.
+    fn handle_key(&self, app: &mut App, code: KeyCode) -> bool {
+        if !app.projects_search_active {
+            return false;
+        }
+        match code {
+            KeyCode::Esc => {
+                app.projects_search_active = false;
+                app.projects_search_query.clear();
+                app.refresh(RefreshScope::CurrentView);
+            }
+            KeyCode::Enter => {
+                app.projects_search_active = false;
+                app.refresh(RefreshScope::CurrentView);
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                app.projects_search_query.pop();
+                app.refresh(RefreshScope::CurrentView);
+            }
+            KeyCode::Char(ch) if !ch.is_control() => {
+                app.projects_search_query.push(ch);
+                app.refresh(RefreshScope::CurrentView);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::Search => app.projects_search_active = true,
+            Action::Left => app.set_projects_collapsed(true, false),
+            Action::Right => app.set_projects_collapsed(false, false),
+            Action::New => app.open_new_tickr_popup(),
+            Action::Client => app.open_client_popup(),
+            _ => return false,
+        }
+        true
+    }
+}
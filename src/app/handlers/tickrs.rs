@@ -0,0 +1,69 @@
+use crossterm::event::KeyCode;
+
+use crate::app::{Action, App, RefreshScope};
+
+use super::ViewHandler;
+
+/// The Tickrs tab.
+pub struct Tickrs;
+
+/// The tasks of one project, opened from Projects.
+pub struct ProjectTickrs;
+
+impl ViewHandler for Tickrs {
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::Sort => next_sort(app),
+            Action::Filter => {
+                app.tickr_filter = app.tickr_filter.next();
+                app.refresh(RefreshScope::CurrentView);
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl ViewHandler for ProjectTickrs {
+    /// Typing into the search once `/` opened it.
+    fn handle_key(&self, app: &mut App, code: KeyCode) -> bool {
+        if !app.project_tickrs_search_active {
+            return false;
+        }
+        match code {
+            KeyCode::Esc => {
+                app.project_tickrs_search_active = false;
+                app.project_tickrs_search_query.clear();
+            }
+            KeyCode::Enter => {
+                app.project_tickrs_search_active = false;
+                return true;
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                app.project_tickrs_search_query.pop();
+            }
+            KeyCode::Char(ch) if !ch.is_control() => app.project_tickrs_search_query.push(ch),
+            _ => return true,
+        }
+        app.selected_tickr_index = 0;
+        app.refresh(RefreshScope::CurrentView);
+        true
+    }
+
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::Search => app.project_tickrs_search_active = true,
+            Action::Filter => app.next_project_tickrs_category(),
+            Action::Sort => next_sort(app),
+            Action::New => app.open_new_tickr_popup(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn next_sort(app: &mut App) {
+    app.tickr_sort = app.tickr_sort.next();
+    let tickrs = std::mem::take(&mut app.tickrs);
+    app.set_tickrs(tickrs);
+}
@@ -0,0 +1,22 @@
+use crate::app::{Action, App};
+
+use super::ViewHandler;
+
+pub struct Timeline;
+
+impl ViewHandler for Timeline {
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::Left => app.shift_timeline(-1),
+            Action::Right => app.shift_timeline(1),
+            Action::Up => app.select_timeline_day(-1),
+            Action::Down => app.select_timeline_day(1),
+            Action::GoTo => app.open_range_popup(),
+            Action::PreviousPage => app.step_range(-1),
+            Action::NextPage => app.step_range(1),
+            Action::Agenda => app.open_agenda(app.selected_timeline_day()),
+            _ => return false,
+        }
+        true
+    }
+}
@@ -0,0 +1,20 @@
+use crate::app::{Action, App};
+
+use super::ViewHandler;
+
+pub struct Worked;
+
+impl ViewHandler for Worked {
+    fn handle_action(&self, app: &mut App, action: Action) -> bool {
+        match action {
+            Action::Left => app.set_projects_collapsed(true, false),
+            Action::Right => app.set_projects_collapsed(false, false),
+            Action::GoTo => app.open_range_popup(),
+            Action::PreviousPage => app.step_range(-1),
+            Action::NextPage => app.step_range(1),
+            Action::Agenda => app.open_agenda(chrono::Local::now().date_naive()),
+            _ => return false,
+        }
+        true
+    }
+}
@@ -0,0 +1,78 @@
+/// Keypress based idle detection for the running task.
+use chrono::{DateTime, Duration, Local};
+
+use crate::types::TickrId;
+
+#[derive(Clone, Copy, Debug)]
+pub struct IdleTracker {
+    last_activity: DateTime<Local>,
+    /// Idle time after which the user is asked; `None` disables detection.
+    threshold: Option<Duration>,
+}
+
+impl IdleTracker {
+    pub fn new(threshold: Option<Duration>) -> Self {
+        Self {
+            last_activity: Local::now(),
+            threshold,
+        }
+    }
+
+    pub fn record_activity(&mut self, now: DateTime<Local>) {
+        self.last_activity = now;
+    }
+
+    /// Last key press, or when the TUI started.
+    pub fn last_activity(&self) -> DateTime<Local> {
+        self.last_activity
+    }
+
+    /// Start of the idle period if the user has been inactive for longer than the threshold.
+    /// Time before `running_since` does not count, so a task started elsewhere is not trimmed.
+    pub fn idle_since(
+        &self,
+        running_since: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        let threshold = self.threshold?;
+        let idle_start = self.last_activity.max(running_since);
+        (now.signed_duration_since(idle_start) >= threshold).then_some(idle_start)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdleChoice {
+    Keep,
+    Discard,
+    Assign,
+}
+
+impl IdleChoice {
+    pub const ALL: [IdleChoice; 3] = [IdleChoice::Keep, IdleChoice::Discard, IdleChoice::Assign];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IdleChoice::Keep => "k: Keep the idle time",
+            IdleChoice::Discard => "d: Discard it (trim the running interval)",
+            IdleChoice::Assign => "a: Assign it to another task",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IdleTaskOption {
+    pub id: TickrId,
+    pub label: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct IdlePopup {
+    pub tickr_id: TickrId,
+    pub task: String,
+    pub idle_start: DateTime<Local>,
+    pub choice_index: usize,
+    /// Set once "assign" was chosen and the target task is being picked.
+    pub assigning: bool,
+    pub tasks: Vec<IdleTaskOption>,
+    pub task_index: usize,
+}
@@ -0,0 +1,387 @@
+/// Translates key presses into actions, with bindings that can be changed in the config file.
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Everything a key can do outside of popups and text input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Dashboard,
+    Projects,
+    #[serde(alias = "tickrs")]
+    Tasks,
+    Worked,
+    Timeline,
+    /// Month heatmap of tracked time.
+    Calendar,
+    /// Every interval of a day in the order it was tracked.
+    Agenda,
+    Categories,
+    Stats,
+    Legend,
+    /// What the TUI changed since it was opened.
+    ActivityLog,
+    /// Switch to another profile's database.
+    Profiles,
+    Help,
+    Search,
+    /// Quick capture of a note on the running task or a task in the inbox.
+    Capture,
+    /// Step through the inbox tasks to sort them.
+    Triage,
+    /// Create a task from a template and start it.
+    Templates,
+    Refresh,
+    StartStop,
+    Stop,
+    /// Restart the task that was stopped last.
+    Resume,
+    /// Switch to one of the recent tasks, filtered as you type.
+    Switcher,
+    /// Jump to a date in the timeline, to the task's project elsewhere.
+    GoTo,
+    PreviousPage,
+    /// Also read as `toggle_range`, the key that switched the Worked and Timeline ranges
+    /// before they had a range selector.
+    #[serde(alias = "toggle_range")]
+    NextPage,
+    Back,
+    Edit,
+    AddInterval,
+    /// Edit the notes of the task and its intervals.
+    Notes,
+    ToggleBillable,
+    ToggleDone,
+    BlockedBy,
+    /// Archive or unarchive the selected project or task.
+    Archive,
+    /// Show or hide archived projects and tasks.
+    ShowArchived,
+    /// Show every parent project as one row with the totals of its sub-projects.
+    Collapse,
+    /// List the sub-projects below their parents again.
+    Expand,
+    Delete,
+    New,
+    /// New task named after the current git branch, in the project named after the repository.
+    NewFromGit,
+    /// Set the hours the selected project should get each week or in total.
+    Goal,
+    /// Set the client of the selected project.
+    Client,
+    /// Cycle the order of the task list.
+    Sort,
+    /// Cycle the time range of the Tickrs tab.
+    Filter,
+    ToggleFocus,
+    Up,
+    Down,
+    Left,
+    Right,
+    Open,
+    /// Open the task's linked issue in the browser.
+    OpenIssue,
+    PomodoroStart,
+    PomodoroSkip,
+    PomodoroCancel,
+}
+
+impl Action {
+    /// Whether the action changes the database; a read-only database ignores these.
+    pub fn writes(self) -> bool {
+        matches!(
+            self,
+            Action::Capture
+                | Action::Triage
+                | Action::Templates
+                | Action::StartStop
+                | Action::Stop
+                | Action::Resume
+                | Action::Switcher
+                | Action::Edit
+                | Action::AddInterval
+                | Action::Notes
+                | Action::ToggleBillable
+                | Action::ToggleDone
+                | Action::BlockedBy
+                | Action::Archive
+                | Action::Delete
+                | Action::New
+                | Action::NewFromGit
+                | Action::Goal
+                | Action::Client
+                | Action::PomodoroStart
+                | Action::PomodoroSkip
+        )
+    }
+}
+
+const DEFAULT_BINDINGS: [(KeyCode, Action); 53] = [
+    (KeyCode::Char('q'), Action::Quit),
+    (KeyCode::Char('h'), Action::Dashboard),
+    (KeyCode::Char('p'), Action::Projects),
+    (KeyCode::Char('t'), Action::Tasks),
+    (KeyCode::Char('w'), Action::Worked),
+    (KeyCode::Char('l'), Action::Timeline),
+    (KeyCode::Char('m'), Action::Calendar),
+    (KeyCode::Char('D'), Action::Agenda),
+    (KeyCode::Char('c'), Action::Categories),
+    (KeyCode::Char('S'), Action::Stats),
+    (KeyCode::Char('L'), Action::Legend),
+    (KeyCode::Char('H'), Action::ActivityLog),
+    (KeyCode::Char('W'), Action::Profiles),
+    (KeyCode::Char('?'), Action::Help),
+    (KeyCode::Char('/'), Action::Search),
+    (KeyCode::Char('N'), Action::Capture),
+    (KeyCode::Char('I'), Action::Triage),
+    (KeyCode::Char('U'), Action::Templates),
+    (KeyCode::Char('r'), Action::Refresh),
+    (KeyCode::Char(' '), Action::StartStop),
+    (KeyCode::Char('s'), Action::Stop),
+    (KeyCode::Char('R'), Action::Resume),
+    (KeyCode::Char('g'), Action::GoTo),
+    (KeyCode::Char('['), Action::PreviousPage),
+    (KeyCode::Char(']'), Action::NextPage),
+    (KeyCode::Esc, Action::Back),
+    (KeyCode::Char('e'), Action::Edit),
+    (KeyCode::Char('a'), Action::AddInterval),
+    (KeyCode::Char('E'), Action::Notes),
+    (KeyCode::Char('b'), Action::ToggleBillable),
+    (KeyCode::Char('x'), Action::ToggleDone),
+    (KeyCode::Char('B'), Action::BlockedBy),
+    (KeyCode::Char('A'), Action::Archive),
+    (KeyCode::Char('V'), Action::ShowArchived),
+    (KeyCode::Char('-'), Action::Collapse),
+    (KeyCode::Char('+'), Action::Expand),
+    (KeyCode::Char('d'), Action::Delete),
+    (KeyCode::Char('n'), Action::New),
+    (KeyCode::Char('G'), Action::NewFromGit),
+    (KeyCode::Char('T'), Action::Goal),
+    (KeyCode::Char('C'), Action::Client),
+    (KeyCode::Char('o'), Action::Sort),
+    (KeyCode::Char('f'), Action::Filter),
+    (KeyCode::Tab, Action::ToggleFocus),
+    (KeyCode::Up, Action::Up),
+    (KeyCode::Down, Action::Down),
+    (KeyCode::Left, Action::Left),
+    (KeyCode::Right, Action::Right),
+    (KeyCode::Enter, Action::Open),
+    (KeyCode::Char('O'), Action::OpenIssue),
+    (KeyCode::Char('P'), Action::PomodoroStart),
+    (KeyCode::Char('>'), Action::PomodoroSkip),
+    (KeyCode::Char('X'), Action::PomodoroCancel),
+];
+
+/// Default keys held with Ctrl.
+const DEFAULT_CTRL_BINDINGS: [(char, Action); 1] = [('p', Action::Switcher)];
+
+/// A key with the Ctrl, Alt and Shift modifiers held with it. Shift is left out for characters,
+/// which already arrive upper case or as the shifted symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyCombo {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    /// Without Ctrl or Alt, so the key may type text.
+    pub fn is_plain(&self) -> bool {
+        !self
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl From<KeyCode> for KeyCombo {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+impl From<KeyEvent> for KeyCombo {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+/// One key or a list of keys, as written in the `[keys]` table.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBinding {
+    fn keys(&self) -> &[String] {
+        match self {
+            KeyBinding::One(key) => std::slice::from_ref(key),
+            KeyBinding::Many(keys) => keys,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    /// In the order the keys are listed, so hints show an action's first key.
+    bindings: Vec<(KeyCombo, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_BINDINGS
+                .iter()
+                .map(|(code, action)| (KeyCombo::from(*code), *action))
+                .chain(DEFAULT_CTRL_BINDINGS.iter().map(|(c, action)| {
+                    (
+                        KeyCombo::new(KeyCode::Char(*c), KeyModifiers::CONTROL),
+                        *action,
+                    )
+                }))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Default bindings with `overrides` applied. A rebound action loses its default keys,
+    /// and a key that is taken by an override no longer triggers its default action.
+    pub fn new(overrides: &HashMap<Action, KeyBinding>) -> Result<Self> {
+        let mut keymap = Self::default();
+        keymap
+            .bindings
+            .retain(|(_, action)| !overrides.contains_key(action));
+        for (action, binding) in overrides {
+            for key in binding.keys() {
+                let combo = parse_key(key)
+                    .ok_or_else(|| anyhow!("Unknown key '{key}' in the [keys] config table"))?;
+                keymap.bindings.retain(|(bound, _)| *bound != combo);
+                keymap.bindings.push((combo, *action));
+            }
+        }
+        Ok(keymap)
+    }
+
+    pub fn action(&self, key: KeyCombo) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| *action)
+    }
+
+    /// The first key bound to `action`, `None` when it has none.
+    pub fn key(&self, action: Action) -> Option<KeyCombo> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+    }
+}
+
+/// How a key is written in hints, e.g. `space`, `Enter`, `Shift+Tab` or `Ctrl+p`.
+pub fn key_label(key: KeyCombo) -> String {
+    let mut label = String::new();
+    for (modifier, name) in [
+        (KeyModifiers::CONTROL, "Ctrl+"),
+        (KeyModifiers::ALT, "Alt+"),
+        (KeyModifiers::SHIFT, "Shift+"),
+    ] {
+        if key.modifiers.contains(modifier) {
+            label.push_str(name);
+        }
+    }
+    label.push_str(&code_label(key.code));
+    label
+}
+
+fn code_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "Shift+Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Insert => "Ins".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::F(number) => format!("F{number}"),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Parses a single character or a key name like `space`, `enter` or `f5`, after any of the
+/// `ctrl+`, `alt+` and `shift+` prefixes.
+fn parse_key(value: &str) -> Option<KeyCombo> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = value;
+    while let Some((prefix, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+        modifiers |= match prefix.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => break,
+        };
+        rest = key;
+    }
+    let code = match parse_code(rest)? {
+        KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        code => code,
+    };
+    Some(KeyCombo::new(code, modifiers))
+}
+
+fn parse_code(value: &str) -> Option<KeyCode> {
+    let mut chars = value.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let code = match value.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        name => {
+            let number = name.strip_prefix('f')?.parse::<u8>().ok()?;
+            if !(1..=12).contains(&number) {
+                return None;
+            }
+            KeyCode::F(number)
+        }
+    };
+    Some(code)
+}
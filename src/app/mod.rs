@@ -1,16 +1,46 @@
+mod auto_stop;
+mod clock;
+mod handlers;
+mod idle;
+mod keymap;
+mod notes;
+mod pomodoro;
+mod project_tree;
+mod range;
 mod state;
+mod triage;
 
-use crossterm::event::KeyCode;
-
+pub use auto_stop::AutoStopPopup;
+pub use clock::LiveClock;
+pub use idle::{IdleChoice, IdlePopup, IdleTracker};
+pub use keymap::{Action, KeyBinding, KeyCombo, Keymap, key_label};
+pub use notes::NotesPopup;
+pub use pomodoro::{Pomodoro, PomodoroAlert, PomodoroPhase, PomodoroPopup, PomodoroSettings};
+pub use range::{RangeField, RangePopup, RangePreset, RangeSelector};
 pub use state::{
-    App, CategoryField, DeleteTickrPopup, EditTickrPopup, NewCategoryPopup, NewTickrField,
-    NewTickrPopup, UpdatePopup,
+    ActivePopup, ActivityPopup, AddIntervalPopup, App, BlockedByPopup, BlockedStartPopup,
+    CapturePopup, CaptureTarget, CategoryField, ClientPopup, ConfirmPopup, DeleteCategoryPopup,
+    DeleteTickrPopup, EditTickrField, EditTickrPopup, GoalPopup, IntervalErrorPopup, IntervalField,
+    LegendPopup, NewCategoryPopup, NewTickrField, NewTickrPopup, ProfilePopup, SwitcherPopup,
+    TemplatePopup, UnblockPopup, UpdatePopup,
 };
+pub use triage::{TriageField, TriagePopup, format_estimate, parse_estimate};
 
 /// Possible input events the app reacts to.
 pub enum AppEvent {
     Tick,
-    KeyPress(KeyCode),
+    KeyPress(KeyCombo),
+}
+
+/// What [`App::refresh`] reloads from the database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefreshScope {
+    /// The task list and which task runs, for the footer and live timers.
+    RunningOnly,
+    /// The running task and the data of the current view.
+    CurrentView,
+    /// Everything cached, e.g. after another process changed the database.
+    All,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -27,30 +57,90 @@ pub enum AppView {
     ProjectTickrs,
     WorkedProjects,
     Timeline,
+    Calendar,
+    /// Every interval of one day in the order it was tracked, opened from Worked, Timeline or
+    /// Calendar.
+    DayAgenda,
     Categories,
+    Stats,
     TickrDetail,
     Help,
 }
 
-const TABS: [AppView; 6] = [
+const TABS: [AppView; 8] = [
     AppView::Dashboard,
     AppView::Projects,
     AppView::Tickrs,
     AppView::WorkedProjects,
     AppView::Timeline,
+    AppView::Calendar,
     AppView::Categories,
+    AppView::Stats,
 ];
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum WorkedRange {
+/// Order of the task lists, cycled with `o`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TickrSort {
+    /// Most recently worked first, never started tasks last.
+    #[default]
+    Recent,
+    /// Most tracked time first.
+    Duration,
+    Name,
+    /// By project name, then task name.
+    Project,
+}
+
+impl TickrSort {
+    pub fn next(self) -> Self {
+        match self {
+            TickrSort::Recent => TickrSort::Duration,
+            TickrSort::Duration => TickrSort::Name,
+            TickrSort::Name => TickrSort::Project,
+            TickrSort::Project => TickrSort::Recent,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TickrSort::Recent => "recent",
+            TickrSort::Duration => "duration",
+            TickrSort::Name => "name",
+            TickrSort::Project => "project",
+        }
+    }
+}
+
+/// Time range of the Tickrs tab, cycled with `f`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TickrFilter {
+    #[default]
+    All,
+    /// Tasks with time tracked today.
     Today,
+    /// Tasks with time tracked since the start of the week.
     Week,
+    /// Tasks with time tracked on a day picked in the calendar.
+    Day(chrono::NaiveDate),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum TimelineRange {
-    Day,
-    Week,
+impl TickrFilter {
+    pub fn next(self) -> Self {
+        match self {
+            TickrFilter::All => TickrFilter::Today,
+            TickrFilter::Today => TickrFilter::Week,
+            TickrFilter::Week | TickrFilter::Day(_) => TickrFilter::All,
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            TickrFilter::All => "all time".to_string(),
+            TickrFilter::Today => "today".to_string(),
+            TickrFilter::Week => "this week".to_string(),
+            TickrFilter::Day(day) => day.format("on %a, %b %-d %Y").to_string(),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -58,4 +148,29 @@ pub struct ProjectSummary {
     pub total_seconds: i64,
     pub ended: usize,
     pub open: usize,
+    /// End of the latest interval, now while one runs.
+    pub last_worked: Option<chrono::DateTime<chrono::Local>>,
+}
+
+/// Time per day and project in the current calendar week, for the Stats view.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WeekSummary {
+    pub start: Option<chrono::NaiveDate>,
+    /// Projects with time this week, most tracked first.
+    pub projects: Vec<(crate::types::ProjectId, String)>,
+    /// Seven days of seconds per project, in the order of `projects`.
+    pub days: Vec<Vec<i64>>,
+}
+
+impl WeekSummary {
+    pub fn day_total(&self, day: usize) -> i64 {
+        self.days
+            .get(day)
+            .map(|seconds| seconds.iter().sum())
+            .unwrap_or(0)
+    }
+
+    pub fn total(&self) -> i64 {
+        (0..self.days.len()).map(|day| self.day_total(day)).sum()
+    }
 }
@@ -0,0 +1,88 @@
+/// Multi-line editor for the notes of a task and of each of its intervals.
+use crate::config::Config;
+use crate::types::{IntervalId, Tickr, TickrId};
+
+/// One set of notes the editor can switch to.
+#[derive(Clone, Debug)]
+pub struct NoteTarget {
+    /// `None` for the notes of the task itself.
+    pub interval_id: Option<IntervalId>,
+    pub label: String,
+    pub text: String,
+    original: String,
+}
+
+impl NoteTarget {
+    fn new(interval_id: Option<IntervalId>, label: String, notes: Option<&str>) -> Self {
+        let text = notes.unwrap_or_default().to_string();
+        Self {
+            interval_id,
+            label,
+            original: text.clone(),
+            text,
+        }
+    }
+
+    pub fn changed(&self) -> bool {
+        self.text.trim_end() != self.original.trim_end()
+    }
+
+    /// The text to store, `None` when nothing but whitespace is left.
+    pub fn saved_text(&self) -> Option<&str> {
+        Some(self.text.trim_end()).filter(|text| !text.trim().is_empty())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NotesPopup {
+    pub tickr_id: TickrId,
+    /// The task first, then its intervals with the latest first.
+    pub targets: Vec<NoteTarget>,
+    pub index: usize,
+    pub error: Option<String>,
+}
+
+impl NotesPopup {
+    pub fn new(tickr: &Tickr, config: &Config) -> Option<Self> {
+        let mut targets = vec![NoteTarget::new(
+            None,
+            format!("Task: {}", tickr.description),
+            tickr.notes.as_deref(),
+        )];
+        let count = tickr.intervals.len();
+        for (index, interval) in tickr.intervals.iter().enumerate().rev() {
+            targets.push(NoteTarget::new(
+                interval.id,
+                format!(
+                    "Interval {} of {count}: {}",
+                    index + 1,
+                    config.format_interval(interval.start_time, interval.end_time)
+                ),
+                interval.notes.as_deref(),
+            ));
+        }
+        Some(Self {
+            tickr_id: tickr.id?,
+            targets,
+            index: 0,
+            error: None,
+        })
+    }
+
+    pub fn current(&self) -> &NoteTarget {
+        &self.targets[self.index]
+    }
+
+    pub fn current_mut(&mut self) -> &mut NoteTarget {
+        &mut self.targets[self.index]
+    }
+
+    pub fn cycle(&mut self, forward: bool) {
+        let len = self.targets.len();
+        self.index = if forward {
+            (self.index + 1) % len
+        } else {
+            (self.index + len - 1) % len
+        };
+    }
+}
@@ -0,0 +1,81 @@
+/// Pomodoro timer: alternating work blocks and breaks on a single task.
+use chrono::{DateTime, Duration, Local};
+
+use crate::types::TickrId;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PomodoroSettings {
+    pub work: Duration,
+    pub short_break: Duration,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        Self {
+            work: Duration::minutes(25),
+            short_break: Duration::minutes(5),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PomodoroPhase {
+    Work,
+    Break,
+}
+
+#[derive(Clone, Debug)]
+pub struct Pomodoro {
+    /// Task that is tracked during work blocks.
+    pub tickr_id: TickrId,
+    pub phase: PomodoroPhase,
+    pub phase_started: DateTime<Local>,
+    /// Number of finished work blocks.
+    pub completed: u32,
+}
+
+impl Pomodoro {
+    pub fn new(tickr_id: TickrId, now: DateTime<Local>) -> Self {
+        Self {
+            tickr_id,
+            phase: PomodoroPhase::Work,
+            phase_started: now,
+            completed: 0,
+        }
+    }
+
+    pub fn remaining(&self, settings: &PomodoroSettings, now: DateTime<Local>) -> Duration {
+        let length = match self.phase {
+            PomodoroPhase::Work => settings.work,
+            PomodoroPhase::Break => settings.short_break,
+        };
+        (length - now.signed_duration_since(self.phase_started)).max(Duration::zero())
+    }
+
+    pub fn start_break(&mut self, now: DateTime<Local>) {
+        self.completed += 1;
+        self.phase = PomodoroPhase::Break;
+        self.phase_started = now;
+    }
+
+    pub fn start_work(&mut self, now: DateTime<Local>) {
+        self.phase = PomodoroPhase::Work;
+        self.phase_started = now;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PomodoroAlert {
+    /// A work block ended and its interval was stopped.
+    BreakStarted,
+    /// The break is over; the user decides whether to continue.
+    BreakOver,
+}
+
+#[derive(Clone, Debug)]
+pub struct PomodoroPopup {
+    pub alert: PomodoroAlert,
+    pub task: String,
+    pub break_minutes: i64,
+    pub completed: u32,
+}
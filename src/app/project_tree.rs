@@ -0,0 +1,66 @@
+/// Sub-projects listed below their parent project, which can be collapsed to roll-up totals.
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{Project, ProjectId};
+
+/// Ids of the sub-projects of every parent project.
+pub fn sub_projects(all: &[Project]) -> HashMap<ProjectId, Vec<ProjectId>> {
+    let ids: HashSet<ProjectId> = all.iter().filter_map(|project| project.id).collect();
+    let mut children: HashMap<ProjectId, Vec<ProjectId>> = HashMap::new();
+    for project in all {
+        if let (Some(id), Some(parent)) = (project.id, project.parent_id)
+            && ids.contains(&parent)
+        {
+            children.entry(parent).or_default().push(id);
+        }
+    }
+    children
+}
+
+/// Puts every sub-project right below its parent, keeping the order of `projects` otherwise.
+/// Parents missing from `projects` are taken from `all`, so sub-projects stay grouped (e.g. in
+/// a search). Sub-projects of `collapsed` parents are left out.
+pub fn arrange(
+    projects: Vec<Project>,
+    all: &[Project],
+    collapsed: &HashSet<ProjectId>,
+) -> Vec<Project> {
+    let by_id: HashMap<ProjectId, &Project> = all
+        .iter()
+        .filter_map(|project| project.id.map(|id| (id, project)))
+        .collect();
+    let mut children: HashMap<ProjectId, Vec<Project>> = HashMap::new();
+    let mut top_level = Vec::new();
+    for project in projects {
+        match project
+            .parent_id
+            .filter(|parent| by_id.contains_key(parent))
+        {
+            Some(parent) => {
+                if !children.contains_key(&parent) {
+                    top_level.push(parent);
+                }
+                children.entry(parent).or_default().push(project);
+            }
+            None => top_level.extend(project.id),
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut rows = Vec::new();
+    for id in top_level {
+        if !seen.insert(id) {
+            continue;
+        }
+        if let Some(project) = by_id.get(&id) {
+            rows.push(Project {
+                parent_id: None,
+                ..(*project).clone()
+            });
+        }
+        if !collapsed.contains(&id) {
+            rows.extend(children.remove(&id).unwrap_or_default());
+        }
+    }
+    rows
+}
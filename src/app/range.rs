@@ -0,0 +1,154 @@
+/// Date range picked from a row of presets with `[`/`]`, shared by the views that show a span of
+/// days. `Custom` holds dates entered in the range popup.
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::config::Config;
+
+/// Longest custom range, so a typo can't make a view lay out decades of days.
+pub const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangePreset {
+    Today,
+    Yesterday,
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    Custom,
+}
+
+impl RangePreset {
+    pub const ALL: [Self; 6] = [
+        Self::Today,
+        Self::Yesterday,
+        Self::ThisWeek,
+        Self::LastWeek,
+        Self::ThisMonth,
+        Self::Custom,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Today => "Today",
+            Self::Yesterday => "Yesterday",
+            Self::ThisWeek => "This week",
+            Self::LastWeek => "Last week",
+            Self::ThisMonth => "This month",
+            Self::Custom => "Custom",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RangeSelector {
+    pub preset: RangePreset,
+    /// First and last day of the custom range, both included.
+    custom: (NaiveDate, NaiveDate),
+}
+
+impl RangeSelector {
+    pub fn new(preset: RangePreset, today: NaiveDate) -> Self {
+        Self {
+            preset,
+            custom: (today, today),
+        }
+    }
+
+    /// First and last day of the range, both included. Weeks follow `week_start` in the config,
+    /// the last seven days without it.
+    pub fn days(&self, today: NaiveDate, config: &Config) -> (NaiveDate, NaiveDate) {
+        let week_start = config.week_start_for(today);
+        match self.preset {
+            RangePreset::Today => (today, today),
+            RangePreset::Yesterday => {
+                let yesterday = today - Duration::days(1);
+                (yesterday, yesterday)
+            }
+            RangePreset::ThisWeek => (week_start, week_start + Duration::days(6)),
+            RangePreset::LastWeek => (
+                week_start - Duration::days(7),
+                week_start - Duration::days(1),
+            ),
+            RangePreset::ThisMonth => {
+                let first = today.with_day(1).unwrap_or(today);
+                let days = crate::ui::days_in_month(today) as i64;
+                (first, first + Duration::days(days - 1))
+            }
+            RangePreset::Custom => self.custom,
+        }
+    }
+
+    /// The preset `direction` steps from the current one, `None` past either end.
+    pub fn neighbour(&self, direction: i64) -> Option<RangePreset> {
+        let index = RangePreset::ALL
+            .iter()
+            .position(|preset| *preset == self.preset)?;
+        let index = usize::try_from(index as i64 + direction).ok()?;
+        RangePreset::ALL.get(index).copied()
+    }
+
+    /// Switches to a custom range from `first` to `last`, swapped when given the wrong way round.
+    pub fn set_custom(&mut self, first: NaiveDate, last: NaiveDate) {
+        self.preset = RangePreset::Custom;
+        self.custom = (first.min(last), first.max(last));
+    }
+
+    /// Moves the range by its own length, which makes it a custom range.
+    pub fn shift(&mut self, direction: i64, today: NaiveDate, config: &Config) {
+        let (first, last) = self.days(today, config);
+        let length = Duration::days(((last - first).num_days() + 1) * direction);
+        self.set_custom(first + length, last + length);
+    }
+
+    /// Short description for headings, e.g. "this week" or the dates of a custom range.
+    pub fn describe(&self, today: NaiveDate, config: &Config) -> String {
+        if self.preset != RangePreset::Custom {
+            return self.preset.label().to_lowercase();
+        }
+        let (first, last) = self.days(today, config);
+        let format = config.date_format.as_str();
+        if first == last {
+            first.format(format).to_string()
+        } else {
+            format!("{} - {}", first.format(format), last.format(format))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RangeField {
+    #[default]
+    From,
+    To,
+}
+
+/// Popup for the dates of a custom range. An empty "from" is today and an empty "to" the same
+/// day as "from", so typing one date jumps to that day.
+#[derive(Clone, Debug)]
+pub struct RangePopup {
+    pub from: String,
+    pub to: String,
+    pub field: RangeField,
+    pub error: Option<String>,
+    /// Preset to return to when the popup is cancelled.
+    pub previous: RangePreset,
+}
+
+impl RangePopup {
+    pub fn new(previous: RangePreset) -> Self {
+        Self {
+            from: String::new(),
+            to: String::new(),
+            field: RangeField::From,
+            error: None,
+            previous,
+        }
+    }
+
+    pub fn input_mut(&mut self) -> &mut String {
+        match self.field {
+            RangeField::From => &mut self.from,
+            RangeField::To => &mut self.to,
+        }
+    }
+}
@@ -1,12 +1,47 @@
 use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
+use chrono::Datelike;
 use crossterm::event::KeyCode;
+use ratatui::widgets::TableState;
 use rusqlite::Connection;
 
-use crate::db;
-use crate::types::{CategoryId, Project, ProjectId, Tickr, TickrCategory, TickrId};
+use crate::config::Config;
+use crate::goal::GoalProgress;
+use crate::picker::PickerItem;
+use crate::types::{
+    ActivityEntry, AgendaEntry, CategoryId, ClientId, GoalPeriod, IntervalId, Project, ProjectGoal,
+    ProjectId, ReportGrouping, ReportRow, TaskTemplate, Tickr, TickrCategory, TickrId,
+};
+use crate::{daemon, db, doctor, goal};
 
-use super::{AppEvent, AppView, FocusMode, ProjectSummary, TABS, TimelineRange, WorkedRange};
+use super::handlers;
+use super::idle::IdleTaskOption;
+use super::keymap::{Action, Keymap};
+use super::notes::NotesPopup;
+use super::project_tree;
+use super::triage::{TriageField, TriagePopup, parse_estimate};
+use super::{
+    AppEvent, AppView, AutoStopPopup, FocusMode, IdleChoice, IdlePopup, IdleTracker, KeyCombo,
+    LiveClock, Pomodoro, PomodoroAlert, PomodoroPhase, PomodoroPopup, PomodoroSettings,
+    ProjectSummary, RangeField, RangePopup, RangePreset, RangeSelector, RefreshScope, TABS,
+    TickrFilter, TickrSort, WeekSummary,
+};
+
+/// How often the database is checked for changes by other processes, and how often totals that
+/// can't move on in memory with a running task may reload.
+const DATA_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the start-tracking reminder looks at the database while nothing runs.
+const REMINDER_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Length of one frame of footer animations.
+const ANIMATION_FRAME: Duration = Duration::from_millis(100);
+/// How long the footer celebrates a reached goal.
+const CELEBRATION_DURATION: Duration = Duration::from_secs(10);
+/// Shown after a warning about the interval a stop ended.
+const STOPPED_EMPTY: &str = "The task was stopped; the interval can be edited in the detail view.";
+/// Shown after a warning about the interval of the task a start ended.
+const SWITCHED_EMPTY: &str =
+    "The new task was started; the stopped interval can be edited in the detail view.";
 
 /// The top-level application state.
 pub struct App {
@@ -18,7 +53,19 @@ pub struct App {
     view_history: Vec<AppView>,
     pub projects: Vec<Project>,
     pub worked_projects: Vec<Project>,
+    /// Time per project in the range of the Worked tab.
+    pub worked_seconds: HashMap<ProjectId, i64>,
+    /// Sub-projects of every parent project.
+    pub sub_projects: HashMap<ProjectId, Vec<ProjectId>>,
+    /// Parent projects shown as one row with the totals of their sub-projects.
+    pub collapsed_projects: HashSet<ProjectId>,
+    /// Time per project since the start of the month, for the Stats view.
+    pub month_report: Vec<ReportRow>,
+    /// Time per day and project this week, for the Stats view.
+    pub week_summary: WeekSummary,
     pub tickrs: Vec<Tickr>,
+    /// Tasks with time today, whatever the current view filters out of `tickrs`.
+    pub today_tickrs: Vec<Tickr>,
     pub categories_list: Vec<TickrCategory>,
     pub status: Option<String>,
     pub selected_project_index: usize,
@@ -27,21 +74,138 @@ pub struct App {
     pub selected_tickr_index: usize,
     pub selected_tickr: Option<Tickr>,
     pub selected_tickr_project_name: Option<String>,
+    /// Description of the task blocking the selected one.
+    pub selected_tickr_blocker: Option<String>,
     pub selected_category_index: usize,
+    /// Scroll positions of the list views, kept between frames.
+    pub tickrs_table_state: TableState,
+    pub projects_table_state: TableState,
+    pub worked_projects_table_state: TableState,
+    pub agenda_table_state: TableState,
     pub tickr_detail_parent: AppView,
+    /// Page of the detail view's interval list, 0 being the newest intervals.
+    pub detail_page: usize,
+    /// Whether the detail view totals the intervals per month instead of listing them.
+    pub detail_by_month: bool,
     pub project_summaries: HashMap<ProjectId, ProjectSummary>,
+    /// Progress of the projects that have a goal.
+    pub project_goals: HashMap<ProjectId, GoalProgress>,
     pub categories: HashMap<CategoryId, TickrCategory>,
-    pub worked_range: WorkedRange,
-    pub timeline_range: TimelineRange,
+    /// Names of the clients, for the Client column of the projects table.
+    pub clients: HashMap<ClientId, String>,
+    pub worked_range: RangeSelector,
+    pub timeline_range: RangeSelector,
+    pub tickr_sort: TickrSort,
+    pub tickr_filter: TickrFilter,
+    /// Whether archived projects and tasks are listed.
+    pub show_archived: bool,
+    /// Day selected in a range of several days, see [`App::selected_timeline_day`].
+    timeline_selected: chrono::NaiveDate,
+    /// Day listed by the day agenda.
+    pub agenda_date: chrono::NaiveDate,
+    /// Intervals of `agenda_date` in the order they started.
+    pub agenda: Vec<AgendaEntry>,
+    pub selected_agenda_index: usize,
+    /// Tab the day agenda was opened from.
+    pub agenda_parent: AppView,
+    /// Selected day of the calendar, whose month is shown.
+    pub calendar_date: chrono::NaiveDate,
+    /// Tracked seconds per day of the calendar month.
+    pub calendar_days: HashMap<chrono::NaiveDate, i64>,
     pub focus_mode: FocusMode,
     pub selected_tab_index: usize,
     pub projects_search_query: String,
     pub projects_search_active: bool,
-    pub edit_popup: Option<EditTickrPopup>,
-    pub new_category_popup: Option<NewCategoryPopup>,
-    pub new_tickr_popup: Option<NewTickrPopup>,
-    pub delete_tickr_popup: Option<DeleteTickrPopup>,
-    pub update_popup: Option<UpdatePopup>,
+    /// Search and category filter of the open project's tasks, cleared when a project is opened.
+    pub project_tickrs_search_query: String,
+    pub project_tickrs_search_active: bool,
+    pub project_tickrs_category: Option<CategoryId>,
+    /// Rank of each task the search found, best first; `None` without a search.
+    project_tickrs_ranking: Option<HashMap<TickrId, usize>>,
+    /// The popup on top of the view. Only one can be open, it takes every key while it is.
+    pub popup: Option<ActivePopup>,
+    pub pomodoro: Option<Pomodoro>,
+    pub pomodoro_settings: PomodoroSettings,
+    pub idle_tracker: IdleTracker,
+    /// Plain output: no spinner and ASCII instead of box drawing characters.
+    pub plain: bool,
+    /// The database can't be written, so keys that change data do nothing.
+    pub read_only: bool,
+    /// Problems `tickr doctor` would report, counted on start and on full refreshes.
+    pub data_problems: usize,
+    /// Start of the untracked stretch while the start-tracking reminder shows.
+    pub reminder: Option<chrono::DateTime<chrono::Local>>,
+    reminder_checked: Option<Instant>,
+    /// Day the notification about going over the daily budget was sent on.
+    budget_notified: Option<chrono::NaiveDate>,
+    /// Day the every-workday templates were last checked on.
+    templates_checked: Option<chrono::NaiveDate>,
+    /// Title of the linked issue being fetched for the task whose edit popup left the name empty.
+    issue_title: Option<(TickrId, std::sync::mpsc::Receiver<anyhow::Result<String>>)>,
+    /// Newer release being looked up at startup, see [`App::start_update_check`].
+    update_check: Option<std::sync::mpsc::Receiver<Option<String>>>,
+    /// Profile whose database is open, `None` for the default database.
+    pub profile: Option<String>,
+    /// Profile picked in the profile switcher. The TUI stops and opens again on its database.
+    pub switch_profile: Option<Option<String>>,
+    /// Audit log session of this run of the TUI.
+    session: String,
+    /// Footer banner for a goal reached a moment ago, with the time it goes away.
+    pub celebration: Option<(String, Instant)>,
+    /// Day of the last check of the daily budget, and whether today's time had reached it.
+    daily_goal_checked: Option<(chrono::NaiveDate, bool)>,
+    /// Settings from the config file.
+    pub config: Config,
+    pub keymap: Keymap,
+    started_at: Instant,
+    /// Time the running timers are shown at.
+    clock: LiveClock,
+    last_data_refresh: Instant,
+    /// `PRAGMA data_version` at the last check, to notice commits of other processes.
+    data_version: i64,
+    /// Project of the running task, whose totals move on in memory while it runs.
+    running_project: Option<ProjectId>,
+    /// The running interval, to notice when another one starts.
+    running_interval: Option<IntervalId>,
+    /// Start of the running interval, for auto-stop and idle detection whatever the view shows.
+    running_since: Option<chrono::DateTime<chrono::Local>>,
+    /// Time up to which the totals of the current view count the running task: the worked time
+    /// per project, the calendar days or the stats. See [`App::advance_running`].
+    totals_counted_until: chrono::DateTime<chrono::Local>,
+    /// The same for `project_goals`.
+    goals_counted_until: chrono::DateTime<chrono::Local>,
+}
+
+/// The open popup with its state. Opening one replaces whatever was open; popups that appear on
+/// their own, like the idle question, wait until nothing else is open.
+#[derive(Clone, Debug)]
+pub enum ActivePopup {
+    Edit(EditTickrPopup),
+    NewCategory(NewCategoryPopup),
+    NewTickr(NewTickrPopup),
+    DeleteTickr(DeleteTickrPopup),
+    Confirm(ConfirmPopup),
+    BlockedBy(BlockedByPopup),
+    BlockedStart(BlockedStartPopup),
+    Unblock(UnblockPopup),
+    DeleteCategory(DeleteCategoryPopup),
+    AddInterval(AddIntervalPopup),
+    Range(RangePopup),
+    Capture(CapturePopup),
+    Goal(GoalPopup),
+    Client(ClientPopup),
+    Notes(NotesPopup),
+    Triage(TriagePopup),
+    Update(UpdatePopup),
+    IntervalError(IntervalErrorPopup),
+    Legend(LegendPopup),
+    Pomodoro(PomodoroPopup),
+    Idle(IdlePopup),
+    AutoStop(AutoStopPopup),
+    Activity(ActivityPopup),
+    Profile(ProfilePopup),
+    Template(TemplatePopup),
+    Switcher(SwitcherPopup),
 }
 
 #[derive(Clone, Debug)]
@@ -57,10 +221,19 @@ pub struct ProjectOption {
     pub name: String,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditTickrField {
+    Label,
+    Issue,
+}
+
 #[derive(Clone, Debug)]
 pub struct EditTickrPopup {
     pub tickr_id: TickrId,
     pub label: String,
+    /// Issue URL or `owner/repo#123` reference, empty for none.
+    pub issue: String,
+    pub field: EditTickrField,
     pub category_index: usize,
     pub categories: Vec<CategoryOption>,
 }
@@ -71,11 +244,213 @@ pub struct DeleteTickrPopup {
     pub label: String,
 }
 
+/// Asks before an action the `[confirm]` settings want confirmed.
+#[derive(Clone, Debug)]
+pub struct ConfirmPopup {
+    pub action: ConfirmAction,
+    pub question: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum ConfirmAction {
+    DeleteInterval {
+        interval_id: IntervalId,
+        label: String,
+    },
+    /// Stop the selected task.
+    Stop(TickrId),
+    /// Stop the running task and go to its project.
+    StopRunning(TickrId),
+    Quit,
+}
+
+impl ConfirmAction {
+    pub fn title(&self) -> &'static str {
+        match self {
+            ConfirmAction::DeleteInterval { .. } => "Delete interval",
+            ConfirmAction::Stop(_) | ConfirmAction::StopRunning(_) => "Stop task",
+            ConfirmAction::Quit => "Quit",
+        }
+    }
+
+    /// Whether the action loses data, for the color of the popup.
+    pub fn destructive(&self) -> bool {
+        matches!(self, ConfirmAction::DeleteInterval { .. })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TickrOption {
+    pub id: Option<TickrId>,
+    pub name: String,
+}
+
+/// Picks the task that blocks `tickr_id`; the first option clears the dependency.
+#[derive(Clone, Debug)]
+pub struct BlockedByPopup {
+    pub tickr_id: TickrId,
+    pub label: String,
+    pub options: Vec<TickrOption>,
+    pub option_index: usize,
+    pub error: Option<String>,
+}
+
+/// Warning shown when starting a task that is still blocked.
+#[derive(Clone, Debug)]
+pub struct BlockedStartPopup {
+    pub tickr_id: TickrId,
+    pub label: String,
+    pub blocker: String,
+}
+
+/// Offers to unblock the tasks waiting on a task that was just marked done.
+#[derive(Clone, Debug)]
+pub struct UnblockPopup {
+    pub blocker_id: TickrId,
+    pub label: String,
+    pub dependents: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DeleteCategoryPopup {
+    pub category_id: CategoryId,
+    pub name: String,
+    /// Number of tasks using the category.
+    pub usage: usize,
+    /// Where affected tasks go; the first option is "no category".
+    pub targets: Vec<CategoryOption>,
+    pub target_index: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureTarget {
+    RunningNote,
+    InboxTask,
+}
+
+#[derive(Clone, Debug)]
+pub struct CapturePopup {
+    pub input: String,
+    pub target: CaptureTarget,
+    /// Description of the running task; notes need one.
+    pub running_label: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Sets the hours a project should get; an empty input removes the goal.
+#[derive(Clone, Debug)]
+pub struct GoalPopup {
+    pub project_id: ProjectId,
+    pub project_name: String,
+    pub input: String,
+    pub period: GoalPeriod,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClientPopup {
+    pub project_id: ProjectId,
+    pub project_name: String,
+    pub input: String,
+    /// Names of the existing clients, which Tab completes to.
+    pub clients: Vec<String>,
+    pub error: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct UpdatePopup {
     pub new_version: String,
 }
 
+/// Explains why the times of an interval were refused, e.g. a stop after the clock was set back.
+#[derive(Clone, Debug)]
+pub struct IntervalErrorPopup {
+    pub error: db::IntervalError,
+    /// What became of the change, e.g. that the task keeps running.
+    pub outcome: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct LegendEntry {
+    pub name: String,
+    pub color: String,
+    pub tasks: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct LegendPopup {
+    pub entries: Vec<LegendEntry>,
+    /// True when no category is used by the visible tasks and all categories are listed.
+    pub showing_all: bool,
+}
+
+/// What the TUI changed in this session, the latest first.
+#[derive(Clone, Debug)]
+pub struct ActivityPopup {
+    pub entries: Vec<ActivityEntry>,
+    pub scroll: usize,
+}
+
+/// The default database and the profiles from the config file, to switch between.
+#[derive(Clone, Debug)]
+pub struct ProfilePopup {
+    pub options: Vec<ProfileOption>,
+    pub index: usize,
+}
+
+/// The task templates, to create a task from one and start it.
+#[derive(Clone, Debug)]
+pub struct TemplatePopup {
+    pub templates: Vec<TaskTemplate>,
+    pub index: usize,
+}
+
+/// Recent tasks filtered as you type, to switch tracking to one of them.
+#[derive(Clone, Debug)]
+pub struct SwitcherPopup {
+    pub query: String,
+    /// Open tasks, most recently worked on first, with their items for the filter.
+    pub tickrs: Vec<Tickr>,
+    pub items: Vec<PickerItem>,
+    /// Indices into `items` of the rows shown, best match first.
+    pub matches: Vec<usize>,
+    pub index: usize,
+}
+
+impl SwitcherPopup {
+    /// Rows shown at most; without a filter, the most recently worked on tasks.
+    pub const ROWS: usize = 10;
+
+    fn update_matches(&mut self) {
+        self.matches = crate::picker::filter(&self.query, &self.items);
+        self.matches.truncate(Self::ROWS);
+        self.index = 0;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProfileOption {
+    /// `None` for the default database.
+    pub name: Option<String>,
+    pub db: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntervalField {
+    Start,
+    End,
+}
+
+#[derive(Clone, Debug)]
+pub struct AddIntervalPopup {
+    pub tickr_id: TickrId,
+    pub label: String,
+    pub start: String,
+    pub end: String,
+    pub field: IntervalField,
+    pub error: Option<String>,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CategoryField {
     Name,
@@ -84,6 +459,8 @@ pub enum CategoryField {
 
 #[derive(Clone, Debug)]
 pub struct NewCategoryPopup {
+    /// Category being edited, `None` when creating a new one.
+    pub category_id: Option<CategoryId>,
     pub name: String,
     pub color: String,
     pub field: CategoryField,
@@ -109,6 +486,13 @@ pub struct NewTickrPopup {
 }
 
 impl EditTickrPopup {
+    fn input_mut(&mut self) -> &mut String {
+        match self.field {
+            EditTickrField::Label => &mut self.label,
+            EditTickrField::Issue => &mut self.issue,
+        }
+    }
+
     fn select_prev(&mut self) {
         if self.categories.is_empty() {
             return;
@@ -130,15 +514,9 @@ impl EditTickrPopup {
 
 impl App {
     pub fn new(db: Connection) -> Self {
-        let tickrs = match db::query_tickr(crate::types::TickrQuery::All, &db) {
-            Ok(tickrs) => tickrs,
-            Err(_) => Vec::new(),
-        };
-        let projects = match db::query_projects(&db) {
-            Ok(projects) => projects,
-            Err(_) => Vec::new(),
-        };
-        let running_tickr = match tickrs
+        let tickrs = db::query_tickr(crate::types::TickrQuery::All, &db).unwrap_or_default();
+        let projects = db::query_projects(&db).unwrap_or_default();
+        let running_tickr = tickrs
             .iter()
             .find(|tickr| {
                 tickr
@@ -147,11 +525,7 @@ impl App {
                     .map(|interval| interval.end_time.is_none())
                     .unwrap_or(false)
             })
-            .and_then(|tickr| tickr.id)
-        {
-            Some(id) => Some(id),
-            None => None,
-        };
+            .and_then(|tickr| tickr.id);
         let mut app = Self {
             running: true,
             pending_update: false,
@@ -161,7 +535,13 @@ impl App {
             view_history: Vec::new(),
             projects,
             worked_projects: Vec::new(),
+            worked_seconds: HashMap::new(),
+            sub_projects: HashMap::new(),
+            collapsed_projects: HashSet::new(),
+            month_report: Vec::new(),
+            week_summary: WeekSummary::default(),
             tickrs,
+            today_tickrs: Vec::new(),
             categories_list: Vec::new(),
             status: None,
             selected_project_index: 0,
@@ -170,298 +550,781 @@ impl App {
             selected_tickr_index: 0,
             selected_tickr: None,
             selected_tickr_project_name: None,
+            selected_tickr_blocker: None,
             selected_category_index: 0,
+            tickrs_table_state: TableState::default(),
+            projects_table_state: TableState::default(),
+            worked_projects_table_state: TableState::default(),
+            agenda_table_state: TableState::default(),
             tickr_detail_parent: AppView::Tickrs,
+            detail_page: 0,
+            detail_by_month: false,
             project_summaries: HashMap::new(),
+            project_goals: HashMap::new(),
             categories: HashMap::new(),
-            worked_range: WorkedRange::Today,
-            timeline_range: TimelineRange::Day,
+            clients: HashMap::new(),
+            worked_range: RangeSelector::new(RangePreset::Today, chrono::Local::now().date_naive()),
+            timeline_range: RangeSelector::new(
+                RangePreset::Today,
+                chrono::Local::now().date_naive(),
+            ),
+            tickr_sort: TickrSort::default(),
+            tickr_filter: TickrFilter::default(),
+            show_archived: false,
+            timeline_selected: chrono::Local::now().date_naive(),
+            agenda_date: chrono::Local::now().date_naive(),
+            agenda: Vec::new(),
+            selected_agenda_index: 0,
+            agenda_parent: AppView::Timeline,
+            calendar_date: chrono::Local::now().date_naive(),
+            calendar_days: HashMap::new(),
             focus_mode: FocusMode::Content,
             selected_tab_index: 0,
             projects_search_query: String::new(),
             projects_search_active: false,
-            edit_popup: None,
-            new_category_popup: None,
-            new_tickr_popup: None,
-            delete_tickr_popup: None,
-            update_popup: None,
+            project_tickrs_search_query: String::new(),
+            project_tickrs_search_active: false,
+            project_tickrs_category: None,
+            project_tickrs_ranking: None,
+            popup: None,
+            pomodoro: None,
+            pomodoro_settings: PomodoroSettings::default(),
+            idle_tracker: IdleTracker::new(None),
+            plain: false,
+            read_only: false,
+            data_problems: 0,
+            reminder: None,
+            reminder_checked: None,
+            budget_notified: None,
+            templates_checked: None,
+            issue_title: None,
+            update_check: None,
+            profile: None,
+            switch_profile: None,
+            session: format!(
+                "{}-{}",
+                chrono::Local::now().to_rfc3339(),
+                std::process::id()
+            ),
+            celebration: None,
+            daily_goal_checked: None,
+            config: Config::default(),
+            keymap: Keymap::default(),
+            started_at: Instant::now(),
+            clock: LiveClock::new(),
+            last_data_refresh: Instant::now(),
+            data_version: 0,
+            running_project: None,
+            running_interval: None,
+            running_since: None,
+            totals_counted_until: chrono::Local::now(),
+            goals_counted_until: chrono::Local::now(),
         };
+        app.data_version = db::data_version(&app.db).unwrap_or_default();
 
         // Initialize categories and project summaries
         app.refresh_categories_for_tickrs();
         app.refresh_project_summaries();
+        app.count_data_problems();
 
         app
     }
 
-    /// Central update function - process an event and mutate state.
-    pub fn update(&mut self, event: AppEvent) {
-        match event {
-            AppEvent::Tick => {
-                if self.running_tickr.is_some() {
-                    self.refresh_running_tickrs();
-                }
-            }
-            AppEvent::KeyPress(key) => self.handle_key(key),
-        }
-
-        if self.running_tickr.is_some() {
-            self.refresh_view_data();
-        }
+    fn count_data_problems(&mut self) {
+        self.data_problems = doctor::scan(&self.db, self.config.split_at_midnight)
+            .map_or(0, |findings| findings.len());
     }
 
-    fn handle_key(&mut self, key: KeyCode) {
-        if self.update_popup.is_some() {
-            self.handle_update_key(key);
+    /// Shows the start-tracking reminder once nothing ran for the configured time.
+    fn check_reminder(&mut self) {
+        if self.running_tickr.is_some() || self.read_only {
+            self.reminder = None;
             return;
         }
-        if self.delete_tickr_popup.is_some() {
-            self.handle_delete_tickr_key(key);
+        if self
+            .reminder_checked
+            .is_some_and(|checked| checked.elapsed() < REMINDER_CHECK_INTERVAL)
+        {
             return;
         }
-        if self.edit_popup.is_some() {
-            self.handle_edit_key(key);
+        self.reminder_checked = Some(Instant::now());
+        let last_tracked = db::query_last_tracked_time(&self.db).unwrap_or_default();
+        self.reminder = self
+            .config
+            .reminder
+            .overdue(last_tracked, chrono::Local::now());
+    }
+
+    /// Sends a desktop notification, once a day, when a running task takes today's time past
+    /// the daily budget.
+    fn check_budget(&mut self) {
+        if self.running_tickr.is_none() {
             return;
         }
-        if self.new_category_popup.is_some() {
-            self.handle_new_category_key(key);
+        let today = self.now().date_naive();
+        if self.budget_notified == Some(today) {
             return;
         }
-        if self.new_tickr_popup.is_some() {
-            self.handle_new_tickr_key(key);
+        let Some(left) = self.budget_left() else {
             return;
-        }
-        if self.projects_search_active {
-            self.handle_projects_search_key(key);
+        };
+        if left >= chrono::Duration::zero() {
             return;
         }
+        self.budget_notified = Some(today);
+        let budget =
+            crate::goal::format_hours(self.daily_budget().unwrap_or_default().num_seconds());
+        std::thread::spawn(move || {
+            crate::notify::send_quietly(
+                "Tickr: daily budget reached",
+                &format!("You tracked more than {budget}h today. Time to call it a day?"),
+            );
+        });
+    }
 
-        match key {
-            KeyCode::Char('q') => self.running = false,
-            KeyCode::Char('h') => {
-                self.navigate_to(AppView::Dashboard);
-                self.load_dashboard();
-            }
-            KeyCode::Char('p') => {
-                self.navigate_to(AppView::Projects);
-                self.load_projects();
-            }
-            KeyCode::Char('t') => {
-                self.navigate_to(AppView::Tickrs);
-                self.load_tickrs();
-                self.selected_tickr = None;
-                self.selected_tickr_project_name = None;
-            }
-            KeyCode::Char('w') => {
-                self.navigate_to(AppView::WorkedProjects);
-                self.load_worked_projects();
-                self.selected_project = None;
-            }
-            KeyCode::Char('l') => {
-                self.navigate_to(AppView::Timeline);
-                self.load_timeline();
-            }
-            KeyCode::Char('c') => {
-                self.navigate_to(AppView::Categories);
-                self.load_categories();
-            }
-            KeyCode::Char('?') => {
-                if self.view == AppView::Help {
-                    self.go_back();
-                } else {
-                    self.navigate_to(AppView::Help);
-                }
-            }
-            KeyCode::Char('/') => {
-                if self.view == AppView::Projects {
-                    self.projects_search_active = true;
-                }
-            }
-            KeyCode::Tab => {
-                if self.focus_mode == FocusMode::TabBar {
-                    self.focus_mode = FocusMode::Content;
-                } else {
-                    self.focus_mode = FocusMode::TabBar;
-                }
-            }
-            KeyCode::BackTab => {
-                if self.view == AppView::WorkedProjects {
-                    self.toggle_worked_range();
-                } else if self.view == AppView::Timeline {
-                    self.toggle_timeline_range();
-                }
-            }
-            KeyCode::Char('r') => match self.view {
-                AppView::Dashboard => self.load_dashboard(),
-                AppView::Projects => self.load_projects(),
-                AppView::Tickrs => self.load_tickrs(),
-                AppView::ProjectTickrs => self.load_project_tickrs(),
-                AppView::WorkedProjects => self.load_worked_projects(),
-                AppView::Timeline => self.load_timeline(),
-                AppView::Categories => self.load_categories(),
-                AppView::TickrDetail => self.refresh_tickr_detail(),
-                AppView::Help => {}
-            },
-            KeyCode::Left => {
-                if self.focus_mode == FocusMode::TabBar {
-                    self.navigate_tab_left();
-                }
-            }
-            KeyCode::Right => {
-                if self.focus_mode == FocusMode::TabBar {
-                    self.navigate_tab_right();
-                }
-            }
-            KeyCode::Up => {
-                if self.focus_mode == FocusMode::Content {
-                    self.move_selection_up();
-                }
+    /// Fills a fetched issue title into the empty name of the edit popup it was fetched for and
+    /// saves the popup. Dropped when that popup was closed in the meantime.
+    fn check_issue_title(&mut self) {
+        let Some((tickr_id, receiver)) = &self.issue_title else {
+            return;
+        };
+        let tickr_id = *tickr_id;
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err(anyhow::anyhow!("the lookup stopped"))
             }
-            KeyCode::Down => {
-                if self.focus_mode == FocusMode::Content {
-                    self.move_selection_down();
-                }
+        };
+        self.issue_title = None;
+        let Some(ActivePopup::Edit(popup)) = &mut self.popup else {
+            return;
+        };
+        if popup.tickr_id != tickr_id || !popup.label.trim().is_empty() {
+            return;
+        }
+        match result {
+            Ok(title) => {
+                popup.label = title;
+                self.apply_edit_popup();
             }
-            KeyCode::Enter => {
-                if self.focus_mode == FocusMode::TabBar {
-                    self.activate_selected_tab();
-                } else {
-                    self.open_selected();
-                }
-            }
-            KeyCode::Char(' ') => self.toggle_tickr(),
-            KeyCode::Char('s') => self.stop_running_tickr(),
-            KeyCode::Char('g') => self.go_to_project_from_tickr(),
-            KeyCode::Esc => self.go_back(),
-            KeyCode::Char('e') => self.open_edit_popup(),
-            KeyCode::Char('d') => self.open_delete_tickr_popup(),
-            KeyCode::Char('n') => match self.view {
-                AppView::Projects | AppView::ProjectTickrs => self.open_new_tickr_popup(),
-                AppView::Categories => self.open_new_category_popup(),
-                _ => {}
-            },
-            _ => {}
+            Err(err) => self.status = Some(format!("Failed to fetch the issue title: {err}")),
         }
     }
 
-    fn navigate_to(&mut self, view: AppView) {
-        if self.view != view {
-            self.view_history.push(self.view.clone());
-            self.view = view;
-            if self.view != AppView::Projects {
-                self.projects_search_active = false;
-            }
-            self.load_content_for_view();
-            // Update selected_tab_index to match the current view
-            if let Some(index) = TABS.iter().position(|v| {
-                *v == self.view
-                    || (self.view == AppView::ProjectTickrs && *v == AppView::Tickrs)
-                    || (self.view == AppView::TickrDetail && *v == AppView::Tickrs)
-            }) {
-                self.selected_tab_index = index;
+    /// Creates today's tasks of the every-workday templates, once a day.
+    fn check_templates(&mut self) {
+        let today = self.now().date_naive();
+        if self.read_only || self.templates_checked == Some(today) {
+            return;
+        }
+        self.templates_checked = Some(today);
+        let created = match db::create_due_templates(today, &self.db) {
+            Ok(created) => created,
+            Err(err) => {
+                self.status = Some(format!("Failed to create today's template tasks: {err}"));
+                return;
             }
+        };
+        if created.is_empty() {
+            return;
+        }
+        for name in &created {
+            self.log_activity(format!("Created '{name}' from its every-workday template"));
         }
+        self.refresh(RefreshScope::CurrentView);
+        self.status = Some(format!("Created today's tasks: {}", created.join(", ")));
     }
 
-    fn load_content_for_view(&mut self) {
-        match self.view {
-            AppView::Dashboard => self.load_dashboard(),
-            AppView::Projects => self.load_projects(),
-            AppView::Tickrs => self.load_tickrs(),
-            AppView::ProjectTickrs => self.load_project_tickrs(),
-            AppView::WorkedProjects => self.load_worked_projects(),
-            AppView::Timeline => self.load_timeline(),
-            AppView::Categories => self.load_categories(),
-            AppView::TickrDetail => self.refresh_tickr_detail(),
-            AppView::Help => {}
+    /// Celebrates in the footer once today's time reaches the daily budget. Reaching it counts
+    /// while Tickr runs, not when it opens on a day that is already over budget.
+    fn check_daily_goal(&mut self) {
+        let Some(left) = self.budget_left() else {
+            self.daily_goal_checked = None;
+            return;
+        };
+        let today = self.now().date_naive();
+        let reached = left <= chrono::Duration::zero();
+        if reached && self.daily_goal_checked == Some((today, false)) {
+            let budget =
+                crate::goal::format_hours(self.daily_budget().unwrap_or_default().num_seconds());
+            self.celebrate(format!("Daily goal reached: {budget}h tracked today"));
         }
+        self.daily_goal_checked = Some((today, reached));
     }
 
-    fn navigate_tab_left(&mut self) {
-        if self.selected_tab_index == 0 {
-            self.selected_tab_index = TABS.len() - 1;
-        } else {
-            self.selected_tab_index -= 1;
-        }
+    /// Shows `message` in the footer for a moment.
+    fn celebrate(&mut self, message: String) {
+        self.celebration = Some((message, Instant::now() + CELEBRATION_DURATION));
     }
 
-    fn navigate_tab_right(&mut self) {
-        self.selected_tab_index = (self.selected_tab_index + 1) % TABS.len();
+    /// The daily budget from the config, `None` while unset.
+    pub fn daily_budget(&self) -> Option<chrono::Duration> {
+        self.config
+            .daily_budget_hours
+            .filter(|hours| *hours > 0.0)
+            .map(|hours| chrono::Duration::seconds((hours * 3600.0).round() as i64))
     }
 
-    fn activate_selected_tab(&mut self) {
-        let target_view = TABS[self.selected_tab_index].clone();
-        self.navigate_to(target_view);
-        self.focus_mode = FocusMode::Content;
+    /// Time tracked today, the part after midnight of intervals started the day before included.
+    pub fn tracked_today(&self) -> chrono::Duration {
+        let now = self.now();
+        let today_start = crate::ui::local_start_of_day(now.date_naive());
+        let today_end = today_start + chrono::Duration::days(1);
+        self.today_tickrs
+            .iter()
+            .flat_map(|tickr| &tickr.intervals)
+            .fold(chrono::Duration::zero(), |total, interval| {
+                total + interval.duration_within(today_start, today_end, now)
+            })
     }
 
-    fn handle_edit_key(&mut self, key: KeyCode) {
-        match key {
-            KeyCode::Esc => {
-                self.edit_popup = None;
-                self.clear_status();
+    /// What is left of today's budget, negative once over it. `None` without a budget.
+    pub fn budget_left(&self) -> Option<chrono::Duration> {
+        self.daily_budget()
+            .map(|budget| budget - self.tracked_today())
+    }
+
+    /// Adds the time the running task tracked since the last reload to the totals in memory,
+    /// those of the current view and the goal progress, all by [`App::now`]. A new day reloads
+    /// from the database instead, when `reload` allows it; so does a goal getting reached.
+    fn advance_running(&mut self, reload: bool) {
+        let Some(project_id) = self.running_project else {
+            return;
+        };
+        let now = self.now();
+        let today = now.date_naive();
+        if self.totals_counted_until.date_naive() != today {
+            if !reload {
+                return;
             }
-            KeyCode::Enter => self.apply_edit_popup(),
-            KeyCode::Up => {
-                if let Some(popup) = self.edit_popup.as_mut() {
-                    popup.select_prev();
-                }
+            // Ranges like today move on, and intervals may need splitting at midnight.
+            self.totals_counted_until = now;
+            self.refresh(RefreshScope::CurrentView);
+            self.refresh_project_goals();
+            return;
+        }
+
+        let seconds = (now - self.totals_counted_until).num_seconds();
+        if seconds > 0 {
+            self.totals_counted_until += chrono::Duration::seconds(seconds);
+            self.advance_view_totals(project_id, seconds, today);
+        }
+
+        let seconds = (now - self.goals_counted_until).num_seconds();
+        if seconds <= 0 {
+            return;
+        }
+        self.goals_counted_until += chrono::Duration::seconds(seconds);
+        if let Some(progress) = self.project_goals.get_mut(&project_id) {
+            let target = progress.target_seconds();
+            if progress.seconds < target && progress.seconds + seconds >= target {
+                // From the database, which also celebrates the goal.
+                self.refresh_project_goals();
+            } else {
+                progress.advance(seconds, &self.config, now);
             }
-            KeyCode::Down => {
-                if let Some(popup) = self.edit_popup.as_mut() {
-                    popup.select_next();
+        }
+    }
+
+    /// Adds `seconds` of `project_id` today to the totals of the current view, with a new entry
+    /// when the project had no time yet. Money amounts in the stats stay as loaded until the next
+    /// reload, as they depend on how each interval is rounded.
+    fn advance_view_totals(
+        &mut self,
+        project_id: ProjectId,
+        seconds: i64,
+        today: chrono::NaiveDate,
+    ) {
+        match self.view {
+            AppView::WorkedProjects => {
+                let (first_day, last_day) = self.worked_range.days(today, &self.config);
+                if (first_day..=last_day).contains(&today) {
+                    *self.worked_seconds.entry(project_id).or_insert(0) += seconds;
                 }
             }
-            KeyCode::Backspace | KeyCode::Delete => {
-                if let Some(popup) = self.edit_popup.as_mut() {
-                    popup.label.pop();
+            AppView::Calendar => {
+                if let Some(day) = self.calendar_days.get_mut(&today) {
+                    *day += seconds;
                 }
             }
-            KeyCode::Char(ch) => {
-                if ch.is_control() {
-                    return;
+            AppView::Stats => {
+                let name = || {
+                    db::query_project_by_id(project_id, &self.db)
+                        .ok()
+                        .flatten()
+                        .map(|project| project.name)
+                        .unwrap_or_default()
+                };
+                let week = &mut self.week_summary;
+                let index = match week.projects.iter().position(|(id, _)| *id == project_id) {
+                    Some(index) => index,
+                    None => {
+                        week.projects.push((project_id, name()));
+                        for day in &mut week.days {
+                            day.push(0);
+                        }
+                        week.projects.len() - 1
+                    }
+                };
+                if let Some(day) = week
+                    .start
+                    .and_then(|start| usize::try_from((today - start).num_days()).ok())
+                    .and_then(|day| week.days.get_mut(day))
+                    .and_then(|day| day.get_mut(index))
+                {
+                    *day += seconds;
                 }
-                if let Some(popup) = self.edit_popup.as_mut() {
-                    popup.label.push(ch);
+                match self
+                    .month_report
+                    .iter_mut()
+                    .find(|row| row.project_id == Some(project_id))
+                {
+                    Some(row) => row.seconds += seconds,
+                    None => self.month_report.push(ReportRow {
+                        label: week.projects[index].1.clone(),
+                        project_id: Some(project_id),
+                        seconds,
+                        intervals: 1,
+                        tasks: 1,
+                        ..ReportRow::default()
+                    }),
                 }
             }
             _ => {}
         }
     }
 
-    fn handle_delete_tickr_key(&mut self, key: KeyCode) {
-        match key {
-            KeyCode::Esc | KeyCode::Char('n') => {
-                self.delete_tickr_popup = None;
-                self.clear_status();
-            }
-            KeyCode::Enter | KeyCode::Char('y') => self.apply_delete_tickr_popup(),
-            _ => {}
-        }
+    /// Whether another process, e.g. `tickr start` or the daemon, committed to the database since
+    /// the last check.
+    fn database_changed(&mut self) -> bool {
+        let Ok(version) = db::data_version(&self.db) else {
+            return false;
+        };
+        let changed = version != self.data_version;
+        self.data_version = version;
+        changed
     }
 
-    fn handle_update_key(&mut self, key: KeyCode) {
-        match key {
-            KeyCode::Esc | KeyCode::Char('n') => {
-                self.update_popup = None;
-                self.clear_status();
-            }
-            KeyCode::Enter | KeyCode::Char('y') => self.apply_update_popup(),
-            _ => {}
+    /// Reloads what `scope` covers from the database. Each scope includes the ones before it.
+    pub fn refresh(&mut self, scope: RefreshScope) {
+        // Intervals that ran past midnight since the last reload.
+        if scope != RefreshScope::RunningOnly
+            && self.config.split_at_midnight
+            && !self.read_only
+            && let Err(err) = db::split_intervals_at_midnight(&self.db)
+        {
+            self.status = Some(format!("Failed to split intervals at midnight: {err}"));
+        }
+        self.refresh_running_tickrs();
+        if scope == RefreshScope::RunningOnly {
+            return;
+        }
+        self.load_view();
+        if scope == RefreshScope::All {
+            self.categories.clear();
+            self.refresh_categories_for_tickrs();
+            self.refresh_project_summaries();
+            self.count_data_problems();
+            self.reminder_checked = None;
         }
     }
 
-    fn handle_new_category_key(&mut self, key: KeyCode) {
-        let Some(popup) = self.new_category_popup.as_mut() else {
-            return;
-        };
-        match key {
-            KeyCode::Esc => {
-                self.new_category_popup = None;
-                self.clear_status();
-            }
-            KeyCode::Enter => self.apply_new_category_popup(),
-            KeyCode::Tab => {
+    /// Central update function - process an event and mutate state.
+    pub fn update(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::Tick => {
+                self.clock.tick(self.running_interval);
+                self.update_pomodoro();
+                self.check_auto_stop();
+                self.check_idle();
+                self.check_reminder();
+                self.check_budget();
+                self.check_templates();
+                self.check_issue_title();
+                self.check_update();
+                if self
+                    .celebration
+                    .as_ref()
+                    .is_some_and(|(_, until)| Instant::now() >= *until)
+                {
+                    self.celebration = None;
+                }
+                self.check_daily_goal();
+                // Ticks arrive at animation speed; only look at the database once per interval.
+                // A running task's time is added in memory, only other processes' changes reload.
+                let due = self.last_data_refresh.elapsed() >= DATA_REFRESH_INTERVAL;
+                if due {
+                    if self.database_changed() {
+                        self.refresh(RefreshScope::All);
+                    }
+                    self.last_data_refresh = Instant::now();
+                }
+                if self.running_tickr.is_some() {
+                    self.advance_running(due);
+                }
+            }
+            AppEvent::KeyPress(key) => {
+                self.handle_key(key);
+                self.idle_tracker.record_activity(chrono::Local::now());
+            }
+        }
+    }
+
+    /// Opens `view` as the first screen, without a history entry to go back to.
+    pub fn open_start_view(&mut self, view: AppView) {
+        if let Some(index) = TABS.iter().position(|tab| *tab == view) {
+            self.selected_tab_index = index;
+        }
+        self.view = view;
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    /// The tab bar entry that is currently active.
+    pub fn current_tab(&self) -> AppView {
+        TABS[self.selected_tab_index].clone()
+    }
+
+    /// Current time for the durations of running intervals, see [`LiveClock`].
+    pub fn now(&self) -> chrono::DateTime<chrono::Local> {
+        self.clock.now()
+    }
+
+    /// Current frame of footer animations, advancing at a fixed rate regardless of redraws.
+    pub fn animation_frame(&self) -> usize {
+        (self.started_at.elapsed().as_millis() / ANIMATION_FRAME.as_millis()) as usize
+    }
+
+    /// Whether `action` does anything in the current view and focus mode. Keys of other actions
+    /// are ignored, and the footer only hints at the actions this allows.
+    pub fn action_available(&self, action: Action) -> bool {
+        use AppView::*;
+        if self.read_only && action.writes() {
+            return false;
+        }
+        let content = self.focus_mode == FocusMode::Content;
+        let view = &self.view;
+        match action {
+            Action::Search => matches!(view, Projects | ProjectTickrs),
+            Action::Sort => matches!(view, Tickrs | ProjectTickrs),
+            Action::Filter => matches!(view, Tickrs | ProjectTickrs),
+            Action::Refresh => *view != Help,
+            Action::Left | Action::Right if content && *view == TickrDetail => self
+                .selected_tickr
+                .as_ref()
+                .is_some_and(|tickr| !tickr.intervals.is_empty()),
+            Action::Left | Action::Right => {
+                !content || matches!(view, Projects | WorkedProjects | Timeline | Calendar)
+            }
+            Action::Collapse | Action::Expand => matches!(view, Projects | WorkedProjects),
+            Action::Up | Action::Down => {
+                content && !matches!(view, Dashboard | Stats | TickrDetail | Help)
+            }
+            Action::Open => {
+                !content
+                    || matches!(
+                        view,
+                        Projects
+                            | Tickrs
+                            | ProjectTickrs
+                            | WorkedProjects
+                            | Timeline
+                            | Calendar
+                            | DayAgenda
+                    )
+            }
+            Action::StartStop => matches!(view, Tickrs | ProjectTickrs | TickrDetail),
+            Action::Delete => matches!(
+                view,
+                Tickrs | ProjectTickrs | TickrDetail | Categories | DayAgenda
+            ),
+            Action::Archive => matches!(view, Projects | Tickrs | ProjectTickrs | TickrDetail),
+            Action::GoTo => matches!(view, WorkedProjects | Timeline | TickrDetail),
+            Action::PreviousPage | Action::NextPage if *view == TickrDetail => self
+                .selected_tickr
+                .as_ref()
+                .is_some_and(|tickr| crate::ui::detail_page_count(tickr, self.detail_by_month) > 1),
+            Action::PreviousPage | Action::NextPage => {
+                matches!(view, WorkedProjects | Timeline | DayAgenda | Calendar)
+            }
+            Action::Agenda => matches!(view, Timeline | Calendar | WorkedProjects),
+            Action::Back => !self.view_history.is_empty() || self.status.is_some(),
+            Action::Edit => matches!(view, Categories | TickrDetail),
+            Action::AddInterval
+            | Action::Notes
+            | Action::ToggleBillable
+            | Action::ToggleDone
+            | Action::BlockedBy => *view == TickrDetail,
+            Action::New => matches!(view, Projects | ProjectTickrs | Categories),
+            Action::Goal => *view == Projects && !self.projects.is_empty(),
+            Action::NewFromGit => matches!(view, Projects | ProjectTickrs),
+            Action::OpenIssue => {
+                *view == TickrDetail
+                    && self
+                        .selected_tickr
+                        .as_ref()
+                        .is_some_and(|tickr| tickr.issue.is_some())
+            }
+            _ => true,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyCombo) {
+        // Popups and text fields only take plain keys, so Ctrl and Alt shortcuts never type into
+        // them. They ignore `KeyCode::Null`.
+        let code = if key.is_plain() {
+            key.code
+        } else {
+            KeyCode::Null
+        };
+        if handlers::handle_popup_key(self, code) {
+            return;
+        }
+        let view = handlers::for_view(&self.view);
+        if view.handle_key(self, code) {
+            return;
+        }
+
+        let Some(action) = self.keymap.action(key) else {
+            return;
+        };
+        if !self.action_available(action) {
+            return;
+        }
+        if self.focus_mode == FocusMode::TabBar && handlers::handle_tab_bar_action(self, action) {
+            return;
+        }
+        if !view.handle_action(self, action) {
+            handlers::handle_global_action(self, action);
+        }
+    }
+
+    pub(super) fn navigate_to(&mut self, view: AppView) {
+        if self.view != view {
+            self.view_history.push(self.view.clone());
+            self.view = view;
+            if self.view != AppView::Projects {
+                self.projects_search_active = false;
+            }
+            // Coming back from a task keeps them, opening a project starts without.
+            self.project_tickrs_search_active = false;
+            if self.view == AppView::ProjectTickrs {
+                self.project_tickrs_search_query.clear();
+                self.project_tickrs_category = None;
+            }
+            if self.view == AppView::TickrDetail {
+                self.detail_page = 0;
+                self.detail_by_month = false;
+            }
+            // Update selected_tab_index to match the current view
+            if let Some(index) = TABS.iter().position(|v| {
+                *v == self.view
+                    || (self.view == AppView::ProjectTickrs && *v == AppView::Tickrs)
+                    || (self.view == AppView::TickrDetail && *v == AppView::Tickrs)
+            }) {
+                self.selected_tab_index = index;
+            }
+        }
+        // Also reloads a view that is opened again, e.g. with its tab key.
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    pub(super) fn navigate_tab_left(&mut self) {
+        if self.selected_tab_index == 0 {
+            self.selected_tab_index = TABS.len() - 1;
+        } else {
+            self.selected_tab_index -= 1;
+        }
+    }
+
+    pub(super) fn navigate_tab_right(&mut self) {
+        self.selected_tab_index = (self.selected_tab_index + 1) % TABS.len();
+    }
+
+    pub(super) fn activate_selected_tab(&mut self) {
+        let target_view = TABS[self.selected_tab_index].clone();
+        self.navigate_to(target_view);
+        self.focus_mode = FocusMode::Content;
+    }
+
+    pub(super) fn handle_edit_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Enter => self.apply_edit_popup(),
+            KeyCode::Tab | KeyCode::BackTab => {
+                if let Some(ActivePopup::Edit(popup)) = self.popup.as_mut() {
+                    popup.field = match popup.field {
+                        EditTickrField::Label => EditTickrField::Issue,
+                        EditTickrField::Issue => EditTickrField::Label,
+                    };
+                }
+            }
+            KeyCode::Up => {
+                if let Some(ActivePopup::Edit(popup)) = self.popup.as_mut() {
+                    popup.select_prev();
+                }
+            }
+            KeyCode::Down => {
+                if let Some(ActivePopup::Edit(popup)) = self.popup.as_mut() {
+                    popup.select_next();
+                }
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                if let Some(ActivePopup::Edit(popup)) = self.popup.as_mut() {
+                    popup.input_mut().pop();
+                }
+            }
+            KeyCode::Char(ch) => {
+                if ch.is_control() {
+                    return;
+                }
+                if let Some(ActivePopup::Edit(popup)) = self.popup.as_mut() {
+                    popup.input_mut().push(ch);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_delete_tickr_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('n') => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Enter | KeyCode::Char('y') => self.apply_delete_tickr_popup(),
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_confirm_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('n') => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if let Some(ActivePopup::Confirm(popup)) = self.popup.take() {
+                    self.run_confirmed(popup.action);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Asks `question` first when `ask` is set, otherwise runs `action` right away.
+    fn confirm(&mut self, ask: bool, action: ConfirmAction, question: String) {
+        if ask {
+            self.popup = Some(ActivePopup::Confirm(ConfirmPopup { action, question }));
+        } else {
+            self.run_confirmed(action);
+        }
+    }
+
+    fn run_confirmed(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::DeleteInterval { interval_id, label } => {
+                self.delete_interval(interval_id, &label)
+            }
+            ConfirmAction::Stop(id) => self.start_or_stop_tickr(id, true),
+            ConfirmAction::StopRunning(id) => self.end_running_tickr(id),
+            ConfirmAction::Quit => self.running = false,
+        }
+    }
+
+    pub(super) fn handle_delete_category_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::DeleteCategory(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('n') => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Up => {
+                popup.target_index =
+                    (popup.target_index + popup.targets.len() - 1) % popup.targets.len();
+            }
+            KeyCode::Down => {
+                popup.target_index = (popup.target_index + 1) % popup.targets.len();
+            }
+            KeyCode::Enter | KeyCode::Char('y') => self.apply_delete_category_popup(),
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_blocked_start_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('n') => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if let Some(ActivePopup::BlockedStart(popup)) = self.popup.take() {
+                    self.start_or_stop_tickr(popup.tickr_id, false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_unblock_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('n') => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Enter | KeyCode::Char('y') => self.apply_unblock_popup(),
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_blocked_by_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::BlockedBy(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Up => {
+                popup.option_index =
+                    (popup.option_index + popup.options.len() - 1) % popup.options.len();
+            }
+            KeyCode::Down => {
+                popup.option_index = (popup.option_index + 1) % popup.options.len();
+            }
+            KeyCode::Enter => self.apply_blocked_by_popup(),
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_update_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Char('n') => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Enter | KeyCode::Char('y') => self.apply_update_popup(),
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_new_category_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::NewCategory(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => {
+                self.popup = None;
+                self.clear_status();
+            }
+            KeyCode::Enter => self.apply_new_category_popup(),
+            KeyCode::Tab => {
                 popup.field = match popup.field {
                     CategoryField::Name => CategoryField::Color,
                     CategoryField::Color => CategoryField::Name,
@@ -488,13 +1351,45 @@ impl App {
         }
     }
 
-    fn handle_new_tickr_key(&mut self, key: KeyCode) {
-        let Some(popup) = self.new_tickr_popup.as_mut() else {
+    pub(super) fn handle_add_interval_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::AddInterval(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.popup = None,
+            KeyCode::Enter => self.apply_add_interval_popup(),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                popup.field = match popup.field {
+                    IntervalField::Start => IntervalField::End,
+                    IntervalField::End => IntervalField::Start,
+                };
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                match popup.field {
+                    IntervalField::Start => popup.start.pop(),
+                    IntervalField::End => popup.end.pop(),
+                };
+            }
+            KeyCode::Char(ch) => {
+                if ch.is_control() {
+                    return;
+                }
+                match popup.field {
+                    IntervalField::Start => popup.start.push(ch),
+                    IntervalField::End => popup.end.push(ch),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_new_tickr_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::NewTickr(popup)) = self.popup.as_mut() else {
             return;
         };
         match key {
             KeyCode::Esc => {
-                self.new_tickr_popup = None;
+                self.popup = None;
                 self.clear_status();
             }
             KeyCode::Enter => self.apply_new_tickr_popup(),
@@ -507,36 +1402,28 @@ impl App {
                 };
             }
             KeyCode::Up => match popup.field {
-                NewTickrField::Project => {
-                    if !popup.projects.is_empty() {
-                        if popup.project_index == 0 {
-                            popup.project_index = popup.projects.len() - 1;
-                        } else {
-                            popup.project_index -= 1;
-                        }
+                NewTickrField::Project if !popup.projects.is_empty() => {
+                    if popup.project_index == 0 {
+                        popup.project_index = popup.projects.len() - 1;
+                    } else {
+                        popup.project_index -= 1;
                     }
                 }
-                NewTickrField::Category => {
-                    if !popup.categories.is_empty() {
-                        if popup.category_index == 0 {
-                            popup.category_index = popup.categories.len() - 1;
-                        } else {
-                            popup.category_index -= 1;
-                        }
+                NewTickrField::Category if !popup.categories.is_empty() => {
+                    if popup.category_index == 0 {
+                        popup.category_index = popup.categories.len() - 1;
+                    } else {
+                        popup.category_index -= 1;
                     }
                 }
                 _ => {}
             },
             KeyCode::Down => match popup.field {
-                NewTickrField::Project => {
-                    if !popup.projects.is_empty() {
-                        popup.project_index = (popup.project_index + 1) % popup.projects.len();
-                    }
+                NewTickrField::Project if !popup.projects.is_empty() => {
+                    popup.project_index = (popup.project_index + 1) % popup.projects.len();
                 }
-                NewTickrField::Category => {
-                    if !popup.categories.is_empty() {
-                        popup.category_index = (popup.category_index + 1) % popup.categories.len();
-                    }
+                NewTickrField::Category if !popup.categories.is_empty() => {
+                    popup.category_index = (popup.category_index + 1) % popup.categories.len();
                 }
                 _ => {}
             },
@@ -547,10 +1434,8 @@ impl App {
                     popup.label.push(' ');
                 }
             }
-            KeyCode::Backspace | KeyCode::Delete => {
-                if popup.field == NewTickrField::Label {
-                    popup.label.pop();
-                }
+            KeyCode::Backspace | KeyCode::Delete if popup.field == NewTickrField::Label => {
+                popup.label.pop();
             }
             KeyCode::Char(ch) => {
                 if ch.is_control() {
@@ -564,7 +1449,8 @@ impl App {
         }
     }
 
-    fn refresh_view_data(&mut self) {
+    /// Loads the data of the current view, see [`App::refresh`].
+    fn load_view(&mut self) {
         match self.view {
             AppView::Dashboard => self.load_dashboard(),
             AppView::Projects => self.load_projects(),
@@ -572,27 +1458,48 @@ impl App {
             AppView::ProjectTickrs => self.load_project_tickrs(),
             AppView::WorkedProjects => self.load_worked_projects(),
             AppView::Timeline => self.load_timeline(),
+            AppView::Calendar => self.load_calendar(),
+            AppView::DayAgenda => self.load_agenda(),
             AppView::Categories => self.load_categories(),
+            AppView::Stats => self.load_stats(),
             AppView::TickrDetail => self.refresh_tickr_detail(),
             AppView::Help => {}
         }
     }
 
     fn refresh_running_tickrs(&mut self) {
+        let today_start = crate::ui::local_start_of_day(self.now().date_naive());
+        let today = crate::types::TickrQuery::ByTimeRange(
+            today_start,
+            today_start + chrono::Duration::days(1),
+        );
+        if let Ok(tickrs) = db::query_tickr(today, &self.db) {
+            self.today_tickrs = tickrs;
+        }
         if let Ok(tickrs) = db::query_tickr(crate::types::TickrQuery::All, &self.db) {
-            self.tickrs = tickrs;
+            self.set_tickrs(tickrs);
+            let running_before = self.running_interval;
             self.running_tickr = None;
+            self.running_project = None;
+            self.running_interval = None;
+            self.running_since = None;
             for tickr in &self.tickrs {
-                if tickr
-                    .intervals
-                    .last()
-                    .map(|interval| interval.end_time.is_none())
-                    .unwrap_or(false)
+                if let Some(interval) = tickr.intervals.last()
+                    && interval.end_time.is_none()
                 {
                     self.running_tickr = tickr.id;
+                    self.running_project = Some(tickr.project_id);
+                    self.running_interval = interval.id;
+                    self.running_since = Some(interval.start_time);
                     break;
                 }
             }
+            // Totals loaded before the start don't have the new interval's time.
+            if self.running_interval != running_before {
+                self.clock.tick(self.running_interval);
+                self.totals_counted_until = self.now();
+                self.goals_counted_until = self.now();
+            }
             if self.selected_tickr_index >= self.tickrs.len() {
                 self.selected_tickr_index = self.tickrs.len().saturating_sub(1);
             }
@@ -600,6 +1507,76 @@ impl App {
         }
     }
 
+    /// Replaces the task list in the order of `tickr_sort`, keeping the selected task selected.
+    pub(super) fn set_tickrs(&mut self, mut tickrs: Vec<Tickr>) {
+        let selected_id = self
+            .tickrs
+            .get(self.selected_tickr_index)
+            .and_then(|tickr| tickr.id);
+        match self.tickr_sort {
+            TickrSort::Recent => {
+                let now = chrono::Local::now();
+                tickrs.sort_by_cached_key(|tickr| {
+                    std::cmp::Reverse(
+                        tickr
+                            .intervals
+                            .iter()
+                            .map(|interval| interval.end_time.unwrap_or(now))
+                            .max(),
+                    )
+                });
+            }
+            TickrSort::Duration => {
+                let now = chrono::Local::now();
+                tickrs.sort_by_cached_key(|tickr| {
+                    std::cmp::Reverse(
+                        tickr
+                            .intervals
+                            .iter()
+                            .map(|interval| {
+                                (interval.end_time.unwrap_or(now) - interval.start_time)
+                                    .num_seconds()
+                            })
+                            .sum::<i64>(),
+                    )
+                });
+            }
+            TickrSort::Name => {
+                tickrs.sort_by_cached_key(|tickr| tickr.description.to_lowercase());
+            }
+            TickrSort::Project => {
+                let names: HashMap<ProjectId, String> = db::query_projects(&self.db)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|project| Some((project.id?, project.name.to_lowercase())))
+                    .collect();
+                tickrs.sort_by_cached_key(|tickr| {
+                    (
+                        names.get(&tickr.project_id).cloned().unwrap_or_default(),
+                        tickr.description.to_lowercase(),
+                    )
+                });
+            }
+        }
+        // While searching a project's tasks, the best matches come first.
+        if self.view == AppView::ProjectTickrs
+            && let Some(ranking) = &self.project_tickrs_ranking
+        {
+            tickrs.sort_by_key(|tickr| {
+                tickr
+                    .id
+                    .and_then(|id| ranking.get(&id).copied())
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        self.tickrs = tickrs;
+        if let Some(index) =
+            selected_id.and_then(|id| self.tickrs.iter().position(|tickr| tickr.id == Some(id)))
+        {
+            self.selected_tickr_index = index;
+        }
+    }
+
     fn clear_status(&mut self) {
         self.status = None;
     }
@@ -607,7 +1584,7 @@ impl App {
     fn load_dashboard(&mut self) {
         // Load all data for dashboard view
         self.load_projects();
-        self.load_tickrs();
+        self.load_tickrs_matching(crate::types::TickrQuery::All, true);
         self.load_categories();
     }
 
@@ -617,9 +1594,24 @@ impl App {
         } else {
             db::search_projects_by_name(self.projects_search_query.trim(), &self.db)
         };
+        let result = result.and_then(|projects| {
+            Ok((
+                projects,
+                db::query_projects(&self.db)?,
+                db::query_clients(&self.db)?,
+            ))
+        });
         match result {
-            Ok(projects) => {
-                self.projects = projects;
+            Ok((mut projects, all, clients)) => {
+                self.clients = clients
+                    .into_iter()
+                    .map(|client| (client.id, client.name))
+                    .collect();
+                if !self.show_archived {
+                    projects.retain(|project| !project.archived);
+                }
+                self.sub_projects = project_tree::sub_projects(&all);
+                self.projects = project_tree::arrange(projects, &all, &self.collapsed_projects);
                 self.clear_status();
                 if self.selected_project_index >= self.projects.len() {
                     self.selected_project_index = self.projects.len().saturating_sub(1);
@@ -632,44 +1624,29 @@ impl App {
         }
     }
 
-    fn handle_projects_search_key(&mut self, key: KeyCode) {
-        if self.view != AppView::Projects {
-            self.projects_search_active = false;
-            return;
-        }
-        match key {
-            KeyCode::Esc => {
-                self.projects_search_active = false;
-                self.projects_search_query.clear();
-                self.load_projects();
-            }
-            KeyCode::Enter => {
-                self.projects_search_active = false;
-                self.load_projects();
-            }
-            KeyCode::Backspace | KeyCode::Delete => {
-                self.projects_search_query.pop();
-                self.load_projects();
-            }
-            KeyCode::Char(ch) => {
-                if ch.is_control() {
-                    return;
-                }
-                self.projects_search_query.push(ch);
-                self.load_projects();
-            }
-            _ => {}
-        }
-    }
-
     fn load_worked_projects(&mut self) {
-        let result = match self.worked_range {
-            WorkedRange::Today => db::query_project_worked_on_today(&self.db),
-            WorkedRange::Week => db::query_project_worked_on_week(&self.db),
-        };
+        self.totals_counted_until = self.now();
+        let today = self.totals_counted_until.date_naive();
+        let (first_day, last_day) = self.worked_range.days(today, &self.config);
+        let result = db::query_project_worked_between(first_day, last_day, &self.db);
+        // Days still ahead have nothing tracked yet.
+        let days = ((last_day.min(today) - first_day).num_days() + 1).max(1) as usize;
+        let result = result.and_then(|projects| {
+            Ok((
+                projects,
+                db::query_projects(&self.db)?,
+                self.query_daily_project_seconds(first_day, days)?,
+            ))
+        });
         match result {
-            Ok(projects) => {
-                self.worked_projects = projects;
+            Ok((projects, all, daily)) => {
+                self.worked_seconds.clear();
+                for (project_id, seconds) in daily.into_iter().flatten() {
+                    *self.worked_seconds.entry(project_id).or_insert(0) += seconds;
+                }
+                self.sub_projects = project_tree::sub_projects(&all);
+                self.worked_projects =
+                    project_tree::arrange(projects, &all, &self.collapsed_projects);
                 self.clear_status();
                 if self.selected_worked_project_index >= self.worked_projects.len() {
                     self.selected_worked_project_index =
@@ -682,10 +1659,55 @@ impl App {
         }
     }
 
+    /// Loads the tasks of the Tickrs tab, limited to the range of `tickr_filter`.
     fn load_tickrs(&mut self) {
-        match db::query_tickr(crate::types::TickrQuery::All, &self.db) {
+        let today = chrono::Local::now().date_naive();
+        let first_day = match self.tickr_filter {
+            TickrFilter::All => {
+                self.load_tickrs_matching(crate::types::TickrQuery::All, false);
+                return;
+            }
+            TickrFilter::Today => today,
+            TickrFilter::Week => self.config.week_start_for(today),
+            TickrFilter::Day(day) => {
+                self.load_tickrs_matching(
+                    crate::types::TickrQuery::ByTimeRange(
+                        crate::ui::local_start_of_day(day),
+                        crate::ui::local_start_of_day(day + chrono::Duration::days(1)),
+                    ),
+                    false,
+                );
+                return;
+            }
+        };
+        self.load_tickrs_matching(
+            crate::types::TickrQuery::ByTimeRange(
+                crate::ui::local_start_of_day(first_day),
+                crate::ui::local_start_of_day(today + chrono::Duration::days(1)),
+            ),
+            false,
+        );
+    }
+
+    /// Loads the tasks matching `query`. Unless `include_archived` is set, archived tasks and
+    /// tasks of archived projects are left out when archived items are hidden.
+    fn load_tickrs_matching(&mut self, query: crate::types::TickrQuery, include_archived: bool) {
+        let result = db::query_tickr(query, &self.db).and_then(|mut tickrs| {
+            if !include_archived && !self.show_archived {
+                let archived_projects: HashSet<ProjectId> = db::query_projects(&self.db)?
+                    .into_iter()
+                    .filter(|project| project.archived)
+                    .filter_map(|project| project.id)
+                    .collect();
+                tickrs.retain(|tickr| {
+                    !tickr.archived && !archived_projects.contains(&tickr.project_id)
+                });
+            }
+            Ok(tickrs)
+        });
+        match result {
             Ok(tickrs) => {
-                self.tickrs = tickrs;
+                self.set_tickrs(tickrs);
                 self.clear_status();
                 if self.selected_tickr_index >= self.tickrs.len() {
                     self.selected_tickr_index = self.tickrs.len().saturating_sub(1);
@@ -699,13 +1721,219 @@ impl App {
     }
 
     fn load_timeline(&mut self) {
-        self.load_tickrs();
+        self.load_tickrs_matching(crate::types::TickrQuery::All, true);
+    }
+
+    fn load_stats(&mut self) {
+        let now = self.now();
+        self.totals_counted_until = now;
+        let today = now.date_naive();
+        let month_start = today - chrono::Duration::days(today.day0().into());
+        let report = db::query_report(
+            ReportGrouping::Project,
+            crate::ui::local_start_of_day(month_start),
+            now,
+            None,
+            &self.config.billing,
+            &self.db,
+        );
+        match report.and_then(|rows| Ok((rows, self.query_week_summary()?))) {
+            Ok((rows, week)) => {
+                self.month_report = rows;
+                self.week_summary = week;
+                self.clear_status();
+            }
+            Err(err) => {
+                self.status = Some(format!("Failed to load stats: {err}"));
+            }
+        }
+    }
+
+    /// Splits this week's intervals into days, running ones up to now.
+    fn query_week_summary(&self) -> anyhow::Result<WeekSummary> {
+        let week_start = self.config.calendar_week_start_for(self.now().date_naive());
+        let mut seconds: HashMap<ProjectId, [i64; 7]> = HashMap::new();
+        for (day, projects) in self
+            .query_daily_project_seconds(week_start, 7)?
+            .into_iter()
+            .enumerate()
+        {
+            for (project_id, day_seconds) in projects {
+                seconds.entry(project_id).or_default()[day] = day_seconds;
+            }
+        }
+        let names: HashMap<ProjectId, String> = db::query_projects(&self.db)?
+            .into_iter()
+            .filter_map(|project| project.id.map(|id| (id, project.name)))
+            .collect();
+        let mut projects: Vec<(ProjectId, [i64; 7])> = seconds.into_iter().collect();
+        projects.sort_by_key(|(id, days)| (std::cmp::Reverse(days.iter().sum::<i64>()), *id));
+        Ok(WeekSummary {
+            start: Some(week_start),
+            days: (0..7)
+                .map(|day| projects.iter().map(|(_, days)| days[day]).collect())
+                .collect(),
+            projects: projects
+                .into_iter()
+                .map(|(id, _)| (id, names.get(&id).cloned().unwrap_or_default()))
+                .collect(),
+        })
+    }
+
+    /// Time per project on each of the `days` days from `first`, running intervals up to now.
+    fn query_daily_project_seconds(
+        &self,
+        first: chrono::NaiveDate,
+        days: usize,
+    ) -> anyhow::Result<Vec<HashMap<ProjectId, i64>>> {
+        let now = self.now();
+        let day_starts: Vec<_> = (0..=days)
+            .map(|day| crate::ui::local_start_of_day(first + chrono::Duration::days(day as i64)))
+            .collect();
+        let tickrs = db::query_tickr(
+            crate::types::TickrQuery::ByTimeRange(day_starts[0], day_starts[days]),
+            &self.db,
+        )?;
+        let mut seconds = vec![HashMap::new(); days];
+        for tickr in &tickrs {
+            for interval in &tickr.intervals {
+                let end = interval.end_time.unwrap_or(now);
+                for (day, projects) in seconds.iter_mut().enumerate() {
+                    let overlap =
+                        end.min(day_starts[day + 1]) - interval.start_time.max(day_starts[day]);
+                    if overlap > chrono::Duration::zero() {
+                        *projects.entry(tickr.project_id).or_insert(0) += overlap.num_seconds();
+                    }
+                }
+            }
+        }
+        Ok(seconds)
+    }
+
+    /// Loads the tracked time per day of the month shown in the calendar.
+    fn load_calendar(&mut self) {
+        self.totals_counted_until = self.now();
+        let first = self.calendar_date.with_day(1).unwrap_or(self.calendar_date);
+        let days = crate::ui::days_in_month(first);
+        match self.query_daily_project_seconds(first, days as usize) {
+            Ok(seconds) => {
+                self.calendar_days = seconds
+                    .into_iter()
+                    .enumerate()
+                    .map(|(day, projects)| {
+                        (
+                            first + chrono::Duration::days(day as i64),
+                            projects.values().sum(),
+                        )
+                    })
+                    .collect();
+                self.clear_status();
+            }
+            Err(err) => {
+                self.status = Some(format!("Failed to load calendar: {err}"));
+            }
+        }
+    }
+
+    /// Moves the calendar selection, loading the other month when it is left.
+    pub(super) fn step_calendar(&mut self, days: i64) {
+        let month = (self.calendar_date.year(), self.calendar_date.month());
+        self.calendar_date += chrono::Duration::days(days);
+        if (self.calendar_date.year(), self.calendar_date.month()) != month {
+            self.refresh(RefreshScope::CurrentView);
+        }
+    }
+
+    /// Moves the calendar selection to the same day of the previous or next month.
+    pub(super) fn step_calendar_month(&mut self, direction: i32) {
+        let date = if direction < 0 {
+            self.calendar_date
+                .checked_sub_months(chrono::Months::new(1))
+        } else {
+            self.calendar_date
+                .checked_add_months(chrono::Months::new(1))
+        };
+        if let Some(date) = date {
+            self.calendar_date = date;
+            self.refresh(RefreshScope::CurrentView);
+        }
+    }
+
+    /// Pages the detail view's intervals towards older (positive `pages`) or newer ones.
+    pub(super) fn step_detail_page(&mut self, pages: isize) {
+        let Some(tickr) = &self.selected_tickr else {
+            return;
+        };
+        let last = crate::ui::detail_page_count(tickr, self.detail_by_month) - 1;
+        self.detail_page = self.detail_page.saturating_add_signed(pages).min(last);
+    }
+
+    /// Switches the detail view between the interval list and the totals per month.
+    pub(super) fn toggle_detail_by_month(&mut self) {
+        self.detail_by_month = !self.detail_by_month;
+        self.detail_page = 0;
+    }
+
+    /// Lists the tasks worked on the selected calendar day.
+    fn open_calendar_day(&mut self) {
+        self.tickr_filter = TickrFilter::Day(self.calendar_date);
+        self.navigate_to(AppView::Tickrs);
+        self.selected_tickr_index = 0;
+    }
+
+    /// Lists every interval of `date` from the tab that is shown.
+    pub(super) fn open_agenda(&mut self, date: chrono::NaiveDate) {
+        self.agenda_parent = self.view.clone();
+        self.agenda_date = date;
+        self.selected_agenda_index = 0;
+        self.navigate_to(AppView::DayAgenda);
+    }
+
+    fn load_agenda(&mut self) {
+        let from = crate::ui::local_start_of_day(self.agenda_date);
+        let to = crate::ui::local_start_of_day(self.agenda_date + chrono::Duration::days(1));
+        match db::query_agenda(from, to, &self.db) {
+            Ok(agenda) => {
+                self.agenda = agenda;
+                if self.selected_agenda_index >= self.agenda.len() {
+                    self.selected_agenda_index = self.agenda.len().saturating_sub(1);
+                }
+                self.clear_status();
+            }
+            Err(err) => {
+                self.status = Some(format!("Failed to load the day: {err}"));
+            }
+        }
+    }
+
+    pub(super) fn step_agenda(&mut self, days: i64) {
+        self.agenda_date += chrono::Duration::days(days);
+        self.selected_agenda_index = 0;
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    /// Opens the task of the selected agenda interval.
+    fn open_agenda_tickr(&mut self) {
+        let Some(entry) = self.agenda.get(self.selected_agenda_index) else {
+            return;
+        };
+        match db::query_tickr_by_id(entry.tickr_id, &self.db) {
+            Ok(Some(tickr)) => {
+                self.selected_tickr_project_name = self.lookup_project_name(tickr.project_id);
+                self.selected_tickr_blocker = self.lookup_tickr_description(tickr.blocked_by);
+                self.selected_tickr = Some(tickr);
+                self.tickr_detail_parent = AppView::DayAgenda;
+                self.navigate_to(AppView::TickrDetail);
+            }
+            Ok(None) => self.status = Some("Task not found.".to_string()),
+            Err(err) => self.status = Some(format!("Failed to load task: {err}")),
+        }
     }
 
     fn load_categories(&mut self) {
         match db::query_categories(&self.db) {
             Ok(mut categories) => {
-                categories.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+                categories.sort_by_key(|a| a.name.to_lowercase());
                 self.categories_list = categories;
                 self.clear_status();
                 if self.selected_category_index >= self.categories_list.len() {
@@ -728,8 +1956,35 @@ impl App {
             None => return,
         };
         match db::query_tickr(crate::types::TickrQuery::ByProjectId(project_id), &self.db) {
-            Ok(tickrs) => {
-                self.tickrs = tickrs;
+            Ok(mut tickrs) => {
+                if !self.show_archived {
+                    tickrs.retain(|tickr| !tickr.archived);
+                }
+                self.project_tickrs_ranking = None;
+                match db::search_tickrs(
+                    &self.project_tickrs_search_query,
+                    Some(project_id),
+                    &self.db,
+                ) {
+                    Ok(Some(ids)) => {
+                        let ranking: HashMap<TickrId, usize> = ids
+                            .into_iter()
+                            .enumerate()
+                            .map(|(rank, id)| (id, rank))
+                            .collect();
+                        tickrs.retain(|tickr| tickr.id.is_some_and(|id| ranking.contains_key(&id)));
+                        self.project_tickrs_ranking = Some(ranking);
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        self.status = Some(format!("Failed to search tasks: {err}"));
+                        return;
+                    }
+                }
+                if let Some(category_id) = self.project_tickrs_category {
+                    tickrs.retain(|tickr| tickr.category_id == Some(category_id));
+                }
+                self.set_tickrs(tickrs);
                 self.clear_status();
                 if self.selected_tickr_index >= self.tickrs.len() {
                     self.selected_tickr_index = self.tickrs.len().saturating_sub(1);
@@ -742,7 +1997,55 @@ impl App {
         }
     }
 
-    fn move_selection_up(&mut self) {
+    /// Whether the open project's tasks are in the order of how well they match the search.
+    pub fn project_tickrs_ranked(&self) -> bool {
+        self.project_tickrs_ranking.is_some()
+    }
+
+    /// Limits the open project's tasks to the next category they use, after the last one to
+    /// all of them again.
+    pub(super) fn next_project_tickrs_category(&mut self) {
+        let Some(project_id) = self
+            .selected_project
+            .as_ref()
+            .and_then(|project| project.id)
+        else {
+            return;
+        };
+        let loaded = db::query_tickr(crate::types::TickrQuery::ByProjectId(project_id), &self.db)
+            .and_then(|tickrs| Ok((tickrs, db::query_categories(&self.db)?)));
+        let (tickrs, categories) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                self.status = Some(format!("Failed to load categories: {err}"));
+                return;
+            }
+        };
+        let used: HashSet<CategoryId> = tickrs
+            .iter()
+            .filter_map(|tickr| tickr.category_id)
+            .collect();
+        let mut categories: Vec<TickrCategory> = categories
+            .into_iter()
+            .filter(|category| used.contains(&category.id))
+            .collect();
+        categories.sort_by_cached_key(|category| category.name.to_lowercase());
+        let next = match self.project_tickrs_category {
+            None => 0,
+            Some(current) => categories
+                .iter()
+                .position(|category| category.id == current)
+                .map_or(0, |index| index + 1),
+        };
+        self.project_tickrs_category = categories.get(next).map(|category| category.id);
+        for category in categories {
+            self.categories.insert(category.id, category);
+        }
+        self.selected_tickr_index = 0;
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    pub(super) fn move_selection_up(&mut self) {
         match self.view {
             AppView::Projects => {
                 if self.projects.is_empty() {
@@ -774,6 +2077,16 @@ impl App {
                     self.selected_worked_project_index -= 1;
                 }
             }
+            AppView::DayAgenda => {
+                if self.agenda.is_empty() {
+                    return;
+                }
+                if self.selected_agenda_index == 0 {
+                    self.selected_agenda_index = self.agenda.len() - 1;
+                } else {
+                    self.selected_agenda_index -= 1;
+                }
+            }
             AppView::Categories => {
                 if self.categories_list.is_empty() {
                     return;
@@ -788,7 +2101,7 @@ impl App {
         }
     }
 
-    fn move_selection_down(&mut self) {
+    pub(super) fn move_selection_down(&mut self) {
         match self.view {
             AppView::Projects => {
                 if self.projects.is_empty() {
@@ -810,6 +2123,12 @@ impl App {
                 self.selected_worked_project_index =
                     (self.selected_worked_project_index + 1) % self.worked_projects.len();
             }
+            AppView::DayAgenda => {
+                if self.agenda.is_empty() {
+                    return;
+                }
+                self.selected_agenda_index = (self.selected_agenda_index + 1) % self.agenda.len();
+            }
             AppView::Categories => {
                 if self.categories_list.is_empty() {
                     return;
@@ -848,25 +2167,29 @@ impl App {
         }
         let tickr = self.tickrs[self.selected_tickr_index].clone();
         self.selected_tickr_project_name = self.lookup_project_name(tickr.project_id);
+        self.selected_tickr_blocker = self.lookup_tickr_description(tickr.blocked_by);
         self.selected_tickr = Some(tickr);
         self.tickr_detail_parent = self.view.clone();
         self.navigate_to(AppView::TickrDetail);
     }
 
-    fn open_selected(&mut self) {
+    pub(super) fn open_selected(&mut self) {
         match self.view {
             AppView::Dashboard => {}
             AppView::Projects => self.open_selected_project(),
             AppView::Tickrs | AppView::ProjectTickrs => self.open_selected_tickr(),
             AppView::WorkedProjects => self.open_selected_worked_project(),
             AppView::Categories => {}
+            AppView::Stats => {}
             AppView::TickrDetail => {}
-            AppView::Timeline => {}
+            AppView::Timeline => self.open_agenda(self.selected_timeline_day()),
+            AppView::Calendar => self.open_calendar_day(),
+            AppView::DayAgenda => self.open_agenda_tickr(),
             AppView::Help => {}
         }
     }
 
-    fn open_edit_popup(&mut self) {
+    pub(super) fn open_edit_popup(&mut self) {
         if self.view != AppView::TickrDetail {
             return;
         }
@@ -886,7 +2209,7 @@ impl App {
                 return;
             }
         };
-        categories.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        categories.sort_by_key(|a| a.name.to_lowercase());
 
         let mut options = Vec::new();
         options.push(CategoryOption {
@@ -903,564 +2226,2573 @@ impl App {
         }
 
         let mut category_index = 0;
-        if let Some(current_id) = tickr.category_id {
-            if let Some(index) = options.iter().position(|opt| opt.id == Some(current_id)) {
-                category_index = index;
-            }
+        if let Some(current_id) = tickr.category_id
+            && let Some(index) = options.iter().position(|opt| opt.id == Some(current_id))
+        {
+            category_index = index;
         }
 
-        self.edit_popup = Some(EditTickrPopup {
+        self.popup = Some(ActivePopup::Edit(EditTickrPopup {
             tickr_id,
             label: tickr.description.clone(),
+            issue: tickr.issue.clone().unwrap_or_default(),
+            field: EditTickrField::Label,
             category_index,
             categories: options,
-        });
+        }));
     }
 
-    fn open_delete_tickr_popup(&mut self) {
-        if !matches!(self.view, AppView::Tickrs | AppView::ProjectTickrs | AppView::TickrDetail) {
+    pub(super) fn open_issue(&mut self) {
+        let Some(issue) = self
+            .selected_tickr
+            .as_ref()
+            .and_then(|t| t.issue.as_deref())
+        else {
             return;
-        }
-        let Some(tickr) = self.current_tickr() else {
-            self.status = Some("No task selected.".to_string());
+        };
+        let url = crate::issue::url(issue, &self.config.issue_url);
+        self.status = Some(match crate::issue::open(&url) {
+            Ok(()) => format!("Opened {url}"),
+            Err(err) => format!("{err}"),
+        });
+    }
+
+    pub(super) fn toggle_billable(&mut self) {
+        let Some(tickr) = &self.selected_tickr else {
             return;
         };
         let Some(tickr_id) = tickr.id else {
-            self.status = Some("Selected task has no id.".to_string());
             return;
         };
-
-        self.delete_tickr_popup = Some(DeleteTickrPopup {
-            tickr_id,
-            label: tickr.description.clone(),
-        });
-    }
-
-    fn open_new_category_popup(&mut self) {
-        if self.view != AppView::Categories {
+        let billable = !tickr.billable;
+        if let Err(err) = db::set_tickr_billable(tickr_id, billable, &self.db) {
+            self.status = Some(format!("Failed to update task: {err}"));
             return;
         }
-        self.new_category_popup = Some(NewCategoryPopup {
-            name: String::new(),
-            color: String::new(),
-            field: CategoryField::Name,
-        });
+        self.log_activity(format!(
+            "Marked {} {}",
+            self.tickr_name(tickr_id),
+            if billable { "billable" } else { "not billable" }
+        ));
+        self.refresh_tickr_detail();
     }
 
-    fn open_new_tickr_popup(&mut self) {
-        if self.view != AppView::Projects && self.view != AppView::ProjectTickrs {
+    pub(super) fn toggle_done(&mut self) {
+        let Some(tickr) = &self.selected_tickr else {
             return;
-        }
-
-        let projects = match db::query_projects(&self.db) {
-            Ok(projects) => projects,
-            Err(err) => {
-                self.status = Some(format!("Failed to load projects: {err}"));
-                return;
-            }
         };
-        let mut project_options = Vec::new();
-        for project in projects {
-            if let Some(id) = project.id {
-                project_options.push(ProjectOption {
-                    id,
-                    name: project.name,
-                });
+        let Some(tickr_id) = tickr.id else {
+            return;
+        };
+        let done = !tickr.done;
+        let label = tickr.description.clone();
+        let running = tickr
+            .intervals
+            .last()
+            .is_some_and(|interval| interval.end_time.is_none());
+        // A finished task stops tracking time.
+        if done && running {
+            match daemon::end_tickr(tickr_id, &self.db) {
+                Ok(warning) => self.warn_interval(warning, STOPPED_EMPTY),
+                Err(err) => {
+                    self.status = Some(format!("Failed to stop task: {err}"));
+                    return;
+                }
+            }
+            if self.running_tickr == Some(tickr_id) {
+                self.running_tickr = None;
             }
+            self.log_activity(format!("Stopped '{label}'"));
         }
-        if project_options.is_empty() {
-            self.status = Some("No projects available.".to_string());
+        if let Err(err) = db::set_tickr_done(tickr_id, done, &self.db) {
+            self.status = Some(format!("Failed to update task: {err}"));
             return;
         }
-
-        let mut categories = match db::query_categories(&self.db) {
-            Ok(categories) => categories,
-            Err(err) => {
-                self.status = Some(format!("Failed to load categories: {err}"));
-                return;
-            }
-        };
-        categories.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-
-        let mut category_options = Vec::new();
-        category_options.push(CategoryOption {
-            id: None,
-            name: "none".to_string(),
-            color: None,
-        });
-        for category in categories {
-            category_options.push(CategoryOption {
-                id: Some(category.id),
-                name: category.name,
-                color: Some(category.color),
-            });
+        self.log_activity(format!(
+            "Marked '{label}' {}",
+            if done { "done" } else { "open" }
+        ));
+        self.refresh_tickr_detail();
+        if !done {
+            return;
         }
-
-        let selected_project_id = match self.view {
-            AppView::ProjectTickrs => self.selected_project.as_ref().and_then(|p| p.id),
-            AppView::Projects => self
-                .projects
-                .get(self.selected_project_index)
-                .and_then(|project| project.id),
-            _ => None,
-        };
-        let mut project_index = 0;
-        if let Some(project_id) = selected_project_id {
-            if let Some(index) = project_options.iter().position(|opt| opt.id == project_id) {
-                project_index = index;
+        match db::query_tickrs_blocked_by(tickr_id, &self.db) {
+            Ok(dependents) if !dependents.is_empty() => {
+                self.popup = Some(ActivePopup::Unblock(UnblockPopup {
+                    blocker_id: tickr_id,
+                    label,
+                    dependents: dependents
+                        .into_iter()
+                        .map(|tickr| tickr.description)
+                        .collect(),
+                }));
             }
+            Ok(_) => {}
+            Err(err) => self.status = Some(format!("Failed to load blocked tasks: {err}")),
         }
-
-        self.new_tickr_popup = Some(NewTickrPopup {
-            label: String::new(),
-            project_index,
-            category_index: 0,
-            projects: project_options,
-            categories: category_options,
-            start_now: true,
-            field: NewTickrField::Label,
-        });
     }
 
-    fn apply_edit_popup(&mut self) {
-        let Some(popup) = self.edit_popup.take() else {
+    fn apply_unblock_popup(&mut self) {
+        let Some(ActivePopup::Unblock(popup)) = self.popup.take() else {
             return;
         };
-
-        let category_id = popup
-            .categories
-            .get(popup.category_index)
-            .and_then(|option| option.id);
-
-        if let Err(err) =
-            db::update_tickr_details(popup.tickr_id, popup.label.clone(), category_id, &self.db)
-        {
-            self.status = Some(format!("Failed to update task: {err}"));
-            self.edit_popup = Some(popup);
+        if let Err(err) = db::unblock_tickrs(popup.blocker_id, &self.db) {
+            self.status = Some(format!("Failed to unblock tasks: {err}"));
             return;
         }
-
-        self.status = Some("Task updated.".to_string());
-        self.refresh_tickr_detail();
-        self.refresh_categories_for_tickrs();
-        match self.tickr_detail_parent {
-            AppView::Tickrs => self.load_tickrs(),
-            AppView::ProjectTickrs => self.load_project_tickrs(),
-            _ => {}
-        }
+        self.log_activity(format!("Unblocked the tasks waiting on '{}'", popup.label));
+        self.refresh(RefreshScope::CurrentView);
     }
 
-    fn apply_new_category_popup(&mut self) {
-        let Some(popup) = self.new_category_popup.take() else {
+    pub(super) fn open_blocked_by_popup(&mut self) {
+        let Some(tickr) = &self.selected_tickr else {
             return;
         };
-        let name = popup.name.trim().to_string();
-        if name.is_empty() {
-            self.status = Some("Category name is required.".to_string());
-            self.new_category_popup = Some(popup);
+        let Some(tickr_id) = tickr.id else {
             return;
-        }
-
-        let color_input = popup.color.trim();
-        let color = match normalize_hex_color(color_input) {
-            Some(color) => color,
-            None => {
-                self.status = Some("Color must be a 6-digit hex value.".to_string());
-                self.new_category_popup = Some(popup);
+        };
+        let (tickrs, projects) = match (
+            db::query_tickr(crate::types::TickrQuery::All, &self.db),
+            db::query_projects(&self.db),
+        ) {
+            (Ok(tickrs), Ok(projects)) => (tickrs, projects),
+            (Err(err), _) | (_, Err(err)) => {
+                self.status = Some(format!("Failed to load tasks: {err}"));
                 return;
             }
         };
+        let project_names: HashMap<ProjectId, String> = projects
+            .into_iter()
+            .filter_map(|project| project.id.map(|id| (id, project.name)))
+            .collect();
 
-        if let Err(err) = db::create_category(name.clone(), color.clone(), &self.db) {
-            self.status = Some(format!("Failed to create category: {err}"));
-            self.new_category_popup = Some(popup);
-            return;
-        }
-
-        self.status = Some("Category created.".to_string());
-        self.load_categories();
+        let mut candidates: Vec<TickrOption> = tickrs
+            .into_iter()
+            .filter(|other| {
+                other.id != Some(tickr_id) && (!other.done || other.id == tickr.blocked_by)
+            })
+            .map(|other| TickrOption {
+                id: other.id,
+                name: match project_names.get(&other.project_id) {
+                    Some(project) => format!("{project} / {}", other.description),
+                    None => other.description,
+                },
+            })
+            .collect();
+        candidates.sort_by_key(|option| option.name.to_lowercase());
+        let mut options = vec![TickrOption {
+            id: None,
+            name: "Not blocked".to_string(),
+        }];
+        options.extend(candidates);
+        let option_index = options
+            .iter()
+            .position(|option| option.id == tickr.blocked_by)
+            .unwrap_or(0);
+
+        self.popup = Some(ActivePopup::BlockedBy(BlockedByPopup {
+            tickr_id,
+            label: tickr.description.clone(),
+            options,
+            option_index,
+            error: None,
+        }));
+    }
+
+    fn apply_blocked_by_popup(&mut self) {
+        let Some(ActivePopup::BlockedBy(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        let blocker = popup.options[popup.option_index].id;
+        let tickr_id = popup.tickr_id;
+
+        // Follow the chain of blockers to refuse dependency cycles.
+        let mut next = blocker;
+        let mut seen = HashSet::new();
+        while let Some(id) = next {
+            if id == tickr_id {
+                popup.error = Some("That task already waits on this one.".to_string());
+                return;
+            }
+            if !seen.insert(id) {
+                break;
+            }
+            next = match db::query_tickr_by_id(id, &self.db) {
+                Ok(tickr) => tickr.and_then(|tickr| tickr.blocked_by),
+                Err(err) => {
+                    popup.error = Some(format!("Failed to load task: {err}"));
+                    return;
+                }
+            };
+        }
+
+        if let Err(err) = db::set_tickr_blocked_by(tickr_id, blocker, &self.db) {
+            popup.error = Some(format!("Failed to update task: {err}"));
+            return;
+        }
+        self.popup = None;
+        self.log_activity(match blocker {
+            Some(blocker) => format!(
+                "Made {} wait on {}",
+                self.tickr_name(tickr_id),
+                self.tickr_name(blocker)
+            ),
+            None => format!("{} no longer waits on a task", self.tickr_name(tickr_id)),
+        });
+        self.refresh_tickr_detail();
+    }
+
+    pub(super) fn open_delete_tickr_popup(&mut self) {
+        if !matches!(
+            self.view,
+            AppView::Tickrs | AppView::ProjectTickrs | AppView::TickrDetail
+        ) {
+            return;
+        }
+        let Some(tickr) = self.current_tickr() else {
+            self.status = Some("No task selected.".to_string());
+            return;
+        };
+        let Some(tickr_id) = tickr.id else {
+            self.status = Some("Selected task has no id.".to_string());
+            return;
+        };
+
+        self.popup = Some(ActivePopup::DeleteTickr(DeleteTickrPopup {
+            tickr_id,
+            label: tickr.description.clone(),
+        }));
+        if !self.config.confirm.delete_task {
+            self.apply_delete_tickr_popup();
+        }
+    }
+
+    pub(super) fn open_legend_popup(&mut self) {
+        let mut counts: HashMap<CategoryId, usize> = HashMap::new();
+        for tickr in &self.tickrs {
+            if let Some(id) = tickr.category_id {
+                *counts.entry(id).or_default() += 1;
+            }
+        }
+
+        let mut entries: Vec<LegendEntry> = counts
+            .iter()
+            .filter_map(|(id, tasks)| {
+                self.categories.get(id).map(|category| LegendEntry {
+                    name: category.name.clone(),
+                    color: category.color.clone(),
+                    tasks: *tasks,
+                })
+            })
+            .collect();
+        let showing_all = entries.is_empty();
+        if showing_all {
+            entries = match db::query_categories(&self.db) {
+                Ok(categories) => categories
+                    .into_iter()
+                    .map(|category| LegendEntry {
+                        name: category.name,
+                        color: category.color,
+                        tasks: 0,
+                    })
+                    .collect(),
+                Err(err) => {
+                    self.status = Some(format!("Failed to load categories: {err}"));
+                    return;
+                }
+            };
+        }
+        entries.sort_by_key(|entry| entry.name.to_lowercase());
+
+        self.popup = Some(ActivePopup::Legend(LegendPopup {
+            entries,
+            showing_all,
+        }));
+    }
+
+    pub(super) fn open_activity_popup(&mut self) {
+        match db::query_activity(&self.session, &self.db) {
+            Ok(entries) => {
+                self.popup = Some(ActivePopup::Activity(ActivityPopup { entries, scroll: 0 }))
+            }
+            Err(err) => self.status = Some(format!("Failed to load the activity log: {err}")),
+        }
+    }
+
+    pub(super) fn handle_activity_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Activity(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('H') => {
+                self.popup = None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => popup.scroll = popup.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.scroll = (popup.scroll + 1).min(popup.entries.len().saturating_sub(1));
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn open_profile_popup(&mut self) {
+        if self.config.profiles.is_empty() {
+            self.status = Some(
+                "No profiles yet; add [profiles.<name>] tables to the config file".to_string(),
+            );
+            return;
+        }
+        let default = ProfileOption {
+            name: None,
+            db: self.config.db_path(None).unwrap_or_default(),
+        };
+        let options: Vec<ProfileOption> = std::iter::once(default)
+            .chain(
+                self.config
+                    .profiles
+                    .iter()
+                    .map(|(name, profile)| ProfileOption {
+                        name: Some(name.clone()),
+                        db: profile.db.display().to_string(),
+                    }),
+            )
+            .collect();
+        let index = options
+            .iter()
+            .position(|option| option.name == self.profile)
+            .unwrap_or(0);
+        self.popup = Some(ActivePopup::Profile(ProfilePopup { options, index }));
+    }
+
+    pub(super) fn open_template_popup(&mut self) {
+        let templates = match db::query_templates(&self.db) {
+            Ok(templates) => templates,
+            Err(err) => {
+                self.status = Some(format!("Failed to load templates: {err}"));
+                return;
+            }
+        };
+        if templates.is_empty() {
+            self.status = Some("No templates yet; add one with `tickr template add`".to_string());
+            return;
+        }
+        self.popup = Some(ActivePopup::Template(TemplatePopup {
+            templates,
+            index: 0,
+        }));
+    }
+
+    pub(super) fn handle_template_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Template(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('U') => self.popup = None,
+            KeyCode::Up | KeyCode::Char('k') => popup.index = popup.index.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.index = (popup.index + 1).min(popup.templates.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                let template = popup.templates[popup.index].clone();
+                self.popup = None;
+                match db::use_template(&template, &self.db) {
+                    Ok(id) => {
+                        self.log_activity(format!("Created '{}' from a template", template.name));
+                        self.start_or_stop_tickr(id, false);
+                    }
+                    Err(err) => {
+                        self.status = Some(format!("Failed to create task: {err}"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn handle_profile_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Profile(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('W') => self.popup = None,
+            KeyCode::Up | KeyCode::Char('k') => popup.index = popup.index.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.index = (popup.index + 1).min(popup.options.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                let name = popup.options[popup.index].name.clone();
+                self.popup = None;
+                if name != self.profile {
+                    self.switch_profile = Some(name);
+                    self.running = false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Records a change made from the TUI in the audit log, for the activity log popup.
+    fn log_activity(&self, message: String) {
+        // The change itself went through; a missing log line isn't worth an error.
+        db::record_activity(&self.session, &message, &self.db).ok();
+    }
+
+    /// Name of task `tickr_id` in quotes for the activity log.
+    fn tickr_name(&self, tickr_id: TickrId) -> String {
+        let loaded = self
+            .selected_tickr
+            .iter()
+            .chain(&self.tickrs)
+            .find(|tickr| tickr.id == Some(tickr_id))
+            .map(|tickr| tickr.description.clone());
+        match loaded.or_else(|| {
+            db::query_tickr_by_id(tickr_id, &self.db)
+                .ok()
+                .flatten()
+                .map(|tickr| tickr.description)
+        }) {
+            Some(description) => format!("'{description}'"),
+            None => format!("task #{tickr_id}"),
+        }
+    }
+
+    pub(super) fn open_add_interval_popup(&mut self) {
+        let Some(tickr) = &self.selected_tickr else {
+            return;
+        };
+        let Some(tickr_id) = tickr.id else {
+            return;
+        };
+        let now = chrono::Local::now();
+        self.popup = Some(ActivePopup::AddInterval(AddIntervalPopup {
+            tickr_id,
+            label: tickr.description.clone(),
+            start: (now - chrono::Duration::hours(1))
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            end: now.format("%Y-%m-%d %H:%M").to_string(),
+            field: IntervalField::Start,
+            error: None,
+        }));
+    }
+
+    pub(super) fn open_edit_category_popup(&mut self) {
+        let Some(category) = self.categories_list.get(self.selected_category_index) else {
+            self.status = Some("No category selected.".to_string());
+            return;
+        };
+        self.popup = Some(ActivePopup::NewCategory(NewCategoryPopup {
+            category_id: Some(category.id),
+            name: category.name.clone(),
+            color: category.color.clone(),
+            field: CategoryField::Name,
+        }));
+    }
+
+    pub(super) fn open_delete_category_popup(&mut self) {
+        let Some(category) = self.categories_list.get(self.selected_category_index) else {
+            self.status = Some("No category selected.".to_string());
+            return;
+        };
+        let usage = match db::query_category_usage(category.id, &self.db) {
+            Ok(usage) => usage,
+            Err(err) => {
+                self.status = Some(format!("Failed to load category usage: {err}"));
+                return;
+            }
+        };
+        let mut targets = vec![CategoryOption {
+            id: None,
+            name: "No category".to_string(),
+            color: None,
+        }];
+        targets.extend(
+            self.categories_list
+                .iter()
+                .filter(|other| other.id != category.id)
+                .map(|other| CategoryOption {
+                    id: Some(other.id),
+                    name: other.name.clone(),
+                    color: Some(other.color.clone()),
+                }),
+        );
+        self.popup = Some(ActivePopup::DeleteCategory(DeleteCategoryPopup {
+            category_id: category.id,
+            name: category.name.clone(),
+            usage,
+            targets,
+            target_index: 0,
+        }));
+    }
+
+    pub(super) fn open_new_category_popup(&mut self) {
+        if self.view != AppView::Categories {
+            return;
+        }
+        self.popup = Some(ActivePopup::NewCategory(NewCategoryPopup {
+            category_id: None,
+            name: String::new(),
+            color: String::new(),
+            field: CategoryField::Name,
+        }));
+    }
+
+    pub(super) fn open_new_tickr_popup(&mut self) {
+        if self.view != AppView::Projects && self.view != AppView::ProjectTickrs {
+            return;
+        }
+
+        let projects = match db::query_projects(&self.db) {
+            Ok(projects) => projects,
+            Err(err) => {
+                self.status = Some(format!("Failed to load projects: {err}"));
+                return;
+            }
+        };
+        let mut project_options = Vec::new();
+        for project in projects.into_iter().filter(|project| !project.archived) {
+            if let Some(id) = project.id {
+                project_options.push(ProjectOption {
+                    id,
+                    name: project.name,
+                });
+            }
+        }
+        if project_options.is_empty() {
+            self.status = Some("No projects available.".to_string());
+            return;
+        }
+
+        let mut categories = match db::query_categories(&self.db) {
+            Ok(categories) => categories,
+            Err(err) => {
+                self.status = Some(format!("Failed to load categories: {err}"));
+                return;
+            }
+        };
+        categories.sort_by_key(|a| a.name.to_lowercase());
+
+        let mut category_options = Vec::new();
+        category_options.push(CategoryOption {
+            id: None,
+            name: "none".to_string(),
+            color: None,
+        });
+        for category in categories {
+            category_options.push(CategoryOption {
+                id: Some(category.id),
+                name: category.name,
+                color: Some(category.color),
+            });
+        }
+
+        let selected_project_id = match self.view {
+            AppView::ProjectTickrs => self.selected_project.as_ref().and_then(|p| p.id),
+            AppView::Projects => self
+                .projects
+                .get(self.selected_project_index)
+                .and_then(|project| project.id),
+            _ => None,
+        };
+        let mut project_index = 0;
+        if let Some(project_id) = selected_project_id
+            && let Some(index) = project_options.iter().position(|opt| opt.id == project_id)
+        {
+            project_index = index;
+        }
+
+        self.popup = Some(ActivePopup::NewTickr(NewTickrPopup {
+            label: String::new(),
+            project_index,
+            category_index: 0,
+            projects: project_options,
+            categories: category_options,
+            start_now: true,
+            field: NewTickrField::Label,
+        }));
+    }
+
+    /// The new task popup filled in from the git branch Tickr was started in.
+    pub(super) fn open_new_tickr_from_git(&mut self) {
+        let Some(git) = crate::git::current() else {
+            self.status = Some("Not on a branch of a git repository.".to_string());
+            return;
+        };
+        self.open_new_tickr_popup();
+        let Some(ActivePopup::NewTickr(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        popup.label = git.task_name().to_string();
+        if let Some(repo) = &git.repo {
+            let names: Vec<&str> = popup
+                .projects
+                .iter()
+                .map(|project| project.name.as_str())
+                .collect();
+            if let Some(index) = crate::fuzzy::suggestions(repo, &names).first() {
+                popup.project_index = *index;
+            }
+        }
+        self.status = Some(format!("Task from branch '{}'.", git.branch));
+    }
+
+    fn apply_edit_popup(&mut self) {
+        let Some(ActivePopup::Edit(mut popup)) = self.popup.take() else {
+            return;
+        };
+        let issue = match popup.issue.trim() {
+            "" => None,
+            issue => match crate::issue::parse(issue) {
+                Ok(issue) => Some(issue),
+                Err(err) => {
+                    self.status = Some(format!("{err}."));
+                    popup.field = EditTickrField::Issue;
+                    self.popup = Some(ActivePopup::Edit(popup));
+                    return;
+                }
+            },
+        };
+        // An empty label takes the title of the linked issue, fetched in the background so a
+        // slow tracker doesn't freeze the screen. The popup applies again once it arrives.
+        if popup.label.trim().is_empty()
+            && let Some(issue) = issue
+        {
+            if self.issue_title.is_none() {
+                let (sender, receiver) = std::sync::mpsc::channel();
+                let template = self.config.issue_url.clone();
+                let token_hosts = self.config.issue_token_hosts.clone();
+                std::thread::spawn(move || {
+                    let _ = sender.send(crate::issue::fetch_title(&issue, &template, &token_hosts));
+                });
+                self.issue_title = Some((popup.tickr_id, receiver));
+            }
+            self.status = Some("Fetching the issue title...".to_string());
+            self.popup = Some(ActivePopup::Edit(popup));
+            return;
+        }
+
+        let category_id = popup
+            .categories
+            .get(popup.category_index)
+            .and_then(|option| option.id);
+
+        let saved =
+            db::update_tickr_details(popup.tickr_id, popup.label.clone(), category_id, &self.db)
+                .and_then(|()| db::set_tickr_issue(popup.tickr_id, issue.as_deref(), &self.db));
+        if let Err(err) = saved {
+            self.status = Some(format!("Failed to update task: {err}"));
+            self.popup = Some(ActivePopup::Edit(popup));
+            return;
+        }
+
+        self.refresh_categories_for_tickrs();
+        self.refresh(RefreshScope::CurrentView);
+        self.status = Some("Task updated.".to_string());
+        self.log_activity(format!("Edited '{}'", popup.label));
+    }
+
+    fn apply_add_interval_popup(&mut self) {
+        let Some(ActivePopup::AddInterval(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        let now = chrono::Local::now();
+        let today = now.date_naive();
+        let Some(start) = crate::ui::parse_local_datetime(&popup.start, today) else {
+            popup.error = Some("Start must be YYYY-MM-DD HH:MM or HH:MM.".to_string());
+            popup.field = IntervalField::Start;
+            return;
+        };
+        let Some(end) = crate::ui::parse_local_datetime(&popup.end, today) else {
+            popup.error = Some("End must be YYYY-MM-DD HH:MM or HH:MM.".to_string());
+            popup.field = IntervalField::End;
+            return;
+        };
+        // Unlike other ways of adding time, an empty interval is refused here, not only warned about.
+        if let Err(err) = db::check_interval(start, Some(end)) {
+            popup.error = Some(err.to_string());
+            return;
+        }
+        if end > now {
+            popup.error = Some("End lies in the future.".to_string());
+            return;
+        }
+        let existing = self
+            .selected_tickr
+            .as_ref()
+            .map(|tickr| tickr.intervals.as_slice())
+            .unwrap_or_default();
+        if let Some(overlap) = existing
+            .iter()
+            .find(|interval| interval.start_time < end && interval.end_time.unwrap_or(now) > start)
+        {
+            popup.error = Some(format!(
+                "Overlaps the interval {} - {}.",
+                overlap.start_time.format("%Y-%m-%d %H:%M"),
+                overlap
+                    .end_time
+                    .map(|end| end.format("%H:%M").to_string())
+                    .unwrap_or_else(|| "running".to_string())
+            ));
+            return;
+        }
+
+        if let Err(err) = daemon::create_interval(popup.tickr_id, start, Some(end), &self.db) {
+            popup.error = Some(format!("Failed to add interval: {err}"));
+            return;
+        }
+        let tickr_id = popup.tickr_id;
+        self.popup = None;
+        self.log_activity(format!(
+            "Added {} - {} to {}",
+            start.format("%Y-%m-%d %H:%M"),
+            end.format("%H:%M"),
+            self.tickr_name(tickr_id)
+        ));
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    fn apply_new_category_popup(&mut self) {
+        let Some(ActivePopup::NewCategory(popup)) = self.popup.take() else {
+            return;
+        };
+        let name = popup.name.trim().to_string();
+        if name.is_empty() {
+            self.status = Some("Category name is required.".to_string());
+            self.popup = Some(ActivePopup::NewCategory(popup));
+            return;
+        }
+
+        let color_input = popup.color.trim();
+        let color = match normalize_hex_color(color_input) {
+            Some(color) => color,
+            None => {
+                self.status = Some("Color must be a 6-digit hex value.".to_string());
+                self.popup = Some(ActivePopup::NewCategory(popup));
+                return;
+            }
+        };
+
+        let result = match popup.category_id {
+            Some(id) => db::update_category(id, name.clone(), color.clone(), &self.db),
+            None => db::create_category(name.clone(), color.clone(), &self.db).map(|_| ()),
+        };
+        if let Err(err) = result {
+            let action = if popup.category_id.is_some() {
+                "update"
+            } else {
+                "create"
+            };
+            self.status = Some(format!("Failed to {action} category: {err}"));
+            self.popup = Some(ActivePopup::NewCategory(popup));
+            return;
+        }
+        self.log_activity(match popup.category_id {
+            Some(_) => format!("Edited category '{name}'"),
+            None => format!("Created category '{name}'"),
+        });
+
+        if let Some(id) = popup.category_id {
+            self.categories.remove(&id);
+            self.refresh_categories_for_tickrs();
+        }
+        self.refresh(RefreshScope::CurrentView);
+        if let Some(index) = self
+            .categories_list
+            .iter()
+            .position(|category| category.name == name)
+        {
+            self.selected_category_index = index;
+        }
+    }
+
+    fn apply_new_tickr_popup(&mut self) {
+        let Some(ActivePopup::NewTickr(popup)) = self.popup.take() else {
+            return;
+        };
+
+        let label = popup.label.trim().to_string();
+        if label.is_empty() {
+            self.status = Some("Task label is required.".to_string());
+            self.popup = Some(ActivePopup::NewTickr(popup));
+            return;
+        }
+
+        let project_id = match popup.projects.get(popup.project_index) {
+            Some(project) => project.id,
+            None => {
+                self.status = Some("Project selection is required.".to_string());
+                self.popup = Some(ActivePopup::NewTickr(popup));
+                return;
+            }
+        };
+
+        let category_id = popup
+            .categories
+            .get(popup.category_index)
+            .and_then(|option| option.id);
+
+        let tickr = Tickr {
+            id: None,
+            project_id,
+            description: label.clone(),
+            category_id,
+            billable: true,
+            done: false,
+            blocked_by: None,
+            notes: None,
+            estimate_minutes: None,
+            archived: false,
+            issue: None,
+            intervals: Vec::new(),
+        };
+
+        let tickr_id = match db::create_tickr(tickr, &self.db) {
+            Ok(id) => id,
+            Err(err) => {
+                self.status = Some(format!("Failed to create task: {err}"));
+                self.popup = Some(ActivePopup::NewTickr(popup));
+                return;
+            }
+        };
+        self.log_activity(format!("Created task '{label}'"));
+
+        if popup.start_now {
+            // Starting ends the task running before.
+            let warning = match daemon::start_tickr(tickr_id, &self.db) {
+                Ok(warning) => warning,
+                Err(err) => {
+                    self.status = Some(format!("Failed to start task: {err}"));
+                    return;
+                }
+            };
+            self.log_activity(format!("Started '{label}'"));
+            self.running_tickr = Some(tickr_id);
+            self.status = Some("Task created and started.".to_string());
+            self.warn_interval(warning, SWITCHED_EMPTY);
+        } else {
+            self.status = Some("Task created.".to_string());
+        }
+
+        self.refresh_project_summaries();
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    fn apply_delete_category_popup(&mut self) {
+        let Some(ActivePopup::DeleteCategory(popup)) = self.popup.take() else {
+            return;
+        };
+        let target = popup
+            .targets
+            .get(popup.target_index)
+            .and_then(|option| option.id);
+        if let Err(err) = db::delete_category(popup.category_id, target, &self.db) {
+            self.status = Some(format!("Failed to delete category: {err}"));
+            self.popup = Some(ActivePopup::DeleteCategory(popup));
+            return;
+        }
+        self.log_activity(format!("Deleted category '{}'", popup.name));
+        self.categories.remove(&popup.category_id);
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    fn apply_delete_tickr_popup(&mut self) {
+        let Some(ActivePopup::DeleteTickr(popup)) = self.popup.take() else {
+            return;
+        };
+
+        if let Err(err) = db::delete_tickr(popup.tickr_id, &self.db) {
+            self.status = Some(format!("Failed to delete task: {err}"));
+            self.popup = Some(ActivePopup::DeleteTickr(popup));
+            return;
+        }
+        self.log_activity(format!("Deleted task '{}'", popup.label));
+
+        if self.running_tickr == Some(popup.tickr_id) {
+            self.running_tickr = None;
+        }
+
+        self.refresh_project_summaries();
+        self.selected_tickr = None;
+        self.selected_tickr_project_name = None;
+        self.selected_tickr_blocker = None;
+
+        if self.view == AppView::TickrDetail {
+            self.go_back();
+        } else {
+            self.refresh(RefreshScope::CurrentView);
+        }
+
+        self.status = Some("Task deleted.".to_string());
+    }
+
+    pub(super) fn go_back(&mut self) {
+        if let Some(prev_view) = self.view_history.pop() {
+            //Assign the new view manually (cyclic loop when using navigate_to)
+            self.view = prev_view;
+            self.refresh(RefreshScope::CurrentView);
+        }
+        self.clear_status();
+    }
+
+    pub(super) fn toggle_tickr(&mut self) {
+        let tickr = match self.current_tickr() {
+            Some(tickr) => tickr,
+            None => {
+                self.status = Some("No task selected.".to_string());
+                return;
+            }
+        };
+        let Some(id) = tickr.id else {
+            return;
+        };
+
+        let is_current_running = tickr
+            .intervals
+            .last()
+            .map(|interval| interval.end_time.is_none())
+            .unwrap_or(false)
+            && tickr.id == self.running_tickr;
+        if !is_current_running && let Some(blocker_id) = tickr.blocked_by {
+            let blocker = match db::query_tickr_by_id(blocker_id, &self.db) {
+                Ok(Some(blocker)) => blocker.description,
+                _ => format!("task #{blocker_id}"),
+            };
+            self.popup = Some(ActivePopup::BlockedStart(BlockedStartPopup {
+                tickr_id: id,
+                label: tickr.description.clone(),
+                blocker,
+            }));
+            return;
+        }
+        if is_current_running {
+            let question = format!("Stop '{}'?", tickr.description);
+            self.confirm(self.config.confirm.stop, ConfirmAction::Stop(id), question);
+        } else {
+            self.start_or_stop_tickr(id, false);
+        }
+    }
+
+    /// Stops `id` when it is `running`, otherwise starts it and stops the task running before.
+    fn start_or_stop_tickr(&mut self, id: TickrId, running: bool) {
+        let result = if running {
+            daemon::end_tickr(id, &self.db)
+                .map(|warning| self.warn_interval(warning, STOPPED_EMPTY))
+        } else {
+            daemon::start_tickr(id, &self.db)
+                .map(|warning| self.warn_interval(warning, SWITCHED_EMPTY))
+        };
+
+        if let Err(err) = result {
+            self.status = Some(format!("Failed to update task: {err}"));
+            return;
+        } else {
+            self.running_tickr = Some(id);
+        }
+        let name = self.tickr_name(id);
+        self.log_activity(if running {
+            format!("Stopped {name}")
+        } else {
+            format!("Started {name}")
+        });
+
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    fn refresh_tickr_detail(&mut self) {
+        let Some(tickr) = &self.selected_tickr else {
+            return;
+        };
+        let Some(id) = tickr.id else {
+            return;
+        };
+        match db::query_tickr_by_id(id, &self.db) {
+            Ok(Some(updated)) => {
+                self.selected_tickr_blocker = self.lookup_tickr_description(updated.blocked_by);
+                self.selected_tickr = Some(updated);
+                self.status = None;
+                self.refresh_categories_for_tickrs();
+            }
+            Ok(None) => {
+                self.status = Some("Task not found.".to_string());
+            }
+            Err(err) => {
+                self.status = Some(format!("Failed to refresh task: {err}"));
+            }
+        }
+    }
+
+    fn refresh_categories_for_tickrs(&mut self) {
+        let mut missing = HashSet::new();
+        for tickr in self.tickrs.iter().chain(&self.today_tickrs) {
+            if let Some(id) = tickr.category_id
+                && !self.categories.contains_key(&id)
+            {
+                missing.insert(id);
+            }
+        }
+        if let Some(tickr) = &self.selected_tickr
+            && let Some(id) = tickr.category_id
+            && !self.categories.contains_key(&id)
+        {
+            missing.insert(id);
+        }
+
+        for id in missing {
+            match db::query_category_by_id(id, &self.db) {
+                Ok(Some(category)) => {
+                    self.categories.insert(id, category);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    self.status = Some(format!("Failed to load categories: {err}"));
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn category_for_tickr(&self, tickr: &Tickr) -> Option<&TickrCategory> {
+        tickr.category_id.and_then(|id| self.categories.get(&id))
+    }
+
+    fn current_tickr(&self) -> Option<&Tickr> {
+        match self.view {
+            AppView::Tickrs | AppView::ProjectTickrs => self.tickrs.get(self.selected_tickr_index),
+            AppView::TickrDetail => self.selected_tickr.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn lookup_project_name(&self, project_id: u32) -> Option<String> {
+        db::query_project_by_id(project_id, &self.db)
+            .ok()
+            .flatten()
+            .map(|project| project.name)
+    }
+
+    fn lookup_tickr_description(&self, id: Option<TickrId>) -> Option<String> {
+        db::query_tickr_by_id(id?, &self.db)
+            .ok()
+            .flatten()
+            .map(|tickr| tickr.description)
+    }
+
+    pub(super) fn go_to_project_from_tickr(&mut self) {
+        if self.view != AppView::TickrDetail {
+            return;
+        }
+        let Some(tickr) = &self.selected_tickr else {
+            return;
+        };
+        self.go_to_project_by_id(tickr.project_id, tickr.id);
+    }
+
+    pub(super) fn stop_running_tickr(&mut self) {
+        self.refresh(RefreshScope::RunningOnly);
+        let running = self.tickrs.iter().find(|tickr| {
+            tickr
+                .intervals
+                .last()
+                .map(|interval| interval.end_time.is_none())
+                .unwrap_or(false)
+        });
+        let Some(tickr) = running else {
+            self.status = Some("No task running.".to_string());
+            return;
+        };
+        let Some(id) = tickr.id else {
+            self.status = Some("Running task has no id.".to_string());
+            return;
+        };
+        let question = format!("Stop '{}'?", tickr.description);
+        self.confirm(
+            self.config.confirm.stop,
+            ConfirmAction::StopRunning(id),
+            question,
+        );
+    }
+
+    /// Starts the task whose interval ended last again, from any view.
+    pub(super) fn resume_last_tickr(&mut self) {
+        let tickr = match db::query_last_stopped_tickr(&self.db) {
+            Ok(Some(tickr)) => tickr,
+            Ok(None) => {
+                self.status = Some("No stopped task to resume.".to_string());
+                return;
+            }
+            Err(err) => {
+                self.status = Some(format!("Failed to load the last task: {err}"));
+                return;
+            }
+        };
+        let Some(id) = tickr.id else {
+            return;
+        };
+        if self.running_tickr == Some(id) {
+            self.status = Some(format!("'{}' is already running.", tickr.description));
+            return;
+        }
+        self.start_unless_blocked(&tickr, id);
+    }
+
+    /// Starts `tickr`, or asks first when another task blocks it.
+    fn start_unless_blocked(&mut self, tickr: &Tickr, id: TickrId) {
+        if let Some(blocker_id) = tickr.blocked_by {
+            let blocker = match db::query_tickr_by_id(blocker_id, &self.db) {
+                Ok(Some(blocker)) => blocker.description,
+                _ => format!("task #{blocker_id}"),
+            };
+            self.popup = Some(ActivePopup::BlockedStart(BlockedStartPopup {
+                tickr_id: id,
+                label: tickr.description.clone(),
+                blocker,
+            }));
+            return;
+        }
+        self.start_or_stop_tickr(id, false);
+    }
+
+    pub(super) fn open_switcher_popup(&mut self) {
+        let (tickrs, items) = match crate::picker::switch_items(&self.config.date_format, &self.db)
+        {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                self.status = Some(format!("Failed to load tasks: {err}"));
+                return;
+            }
+        };
+        if tickrs.is_empty() {
+            self.status = Some("No open tasks to switch to.".to_string());
+            return;
+        }
+        let mut popup = SwitcherPopup {
+            query: String::new(),
+            tickrs,
+            items,
+            matches: Vec::new(),
+            index: 0,
+        };
+        popup.update_matches();
+        self.popup = Some(ActivePopup::Switcher(popup));
+    }
+
+    pub(super) fn handle_switcher_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Switcher(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.popup = None,
+            KeyCode::Up => popup.index = popup.index.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => {
+                popup.index = (popup.index + 1).min(popup.matches.len().saturating_sub(1));
+            }
+            KeyCode::Backspace => {
+                popup.query.pop();
+                popup.update_matches();
+            }
+            KeyCode::Char(c) => {
+                popup.query.push(c);
+                popup.update_matches();
+            }
+            KeyCode::Enter => {
+                let Some(tickr) = popup
+                    .matches
+                    .get(popup.index)
+                    .map(|index| popup.tickrs[*index].clone())
+                else {
+                    return;
+                };
+                self.popup = None;
+                let Some(id) = tickr.id else {
+                    return;
+                };
+                if self.running_tickr == Some(id) {
+                    self.status = Some(format!("'{}' is already running.", tickr.description));
+                    return;
+                }
+                self.start_unless_blocked(&tickr, id);
+            }
+            _ => {}
+        }
+    }
+
+    fn end_running_tickr(&mut self, id: TickrId) {
+        match daemon::end_tickr(id, &self.db) {
+            Ok(warning) => self.warn_interval(warning, STOPPED_EMPTY),
+            Err(err) => {
+                self.status = Some(format!("Failed to stop task: {err}"));
+                return;
+            }
+        }
+        self.log_activity(format!("Stopped {}", self.tickr_name(id)));
+        if let Ok(Some(tickr)) = db::query_tickr_by_id(id, &self.db) {
+            self.go_to_project_by_id(tickr.project_id, Some(id));
+        }
+    }
+
+    /// Quits, after asking when a task runs and `[confirm] quit` is on.
+    pub(super) fn quit(&mut self) {
+        let Some(id) = self.running_tickr else {
+            self.running = false;
+            return;
+        };
+        let question = format!(
+            "{} is still running and keeps running after Tickr closes. Quit?",
+            self.tickr_name(id)
+        );
+        self.confirm(self.config.confirm.quit, ConfirmAction::Quit, question);
+    }
+
+    /// Deletes the interval selected in the day agenda. A running one has to stop first.
+    pub(super) fn delete_agenda_interval(&mut self) {
+        let Some(entry) = self.agenda.get(self.selected_agenda_index) else {
+            self.status = Some("No interval selected.".to_string());
+            return;
+        };
+        let Some(end) = entry.end_time else {
+            self.status = Some("Stop the task before deleting its running interval.".to_string());
+            return;
+        };
+        let label = format!(
+            "'{}' {} - {}",
+            entry.task,
+            entry.start_time.format("%H:%M"),
+            end.format("%H:%M")
+        );
+        let action = ConfirmAction::DeleteInterval {
+            interval_id: entry.interval_id,
+            label: label.clone(),
+        };
+        self.confirm(
+            self.config.confirm.delete_interval,
+            action,
+            format!("Delete the interval {label}? This cannot be undone."),
+        );
+    }
+
+    fn delete_interval(&mut self, id: IntervalId, label: &str) {
+        if let Err(err) = daemon::delete_interval(id, &self.db) {
+            self.status = Some(format!("Failed to delete interval: {err}"));
+            return;
+        }
+        self.log_activity(format!("Deleted the interval {label}"));
+        self.status = Some(format!("Deleted the interval {label}."));
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    fn go_to_project_by_id(&mut self, project_id: u32, highlight_tickr_id: Option<u32>) {
+        let project = match db::query_project_by_id(project_id, &self.db) {
+            Ok(Some(project)) => project,
+            _ => {
+                self.status = Some("Project not found.".to_string());
+                return;
+            }
+        };
+
+        // Selected in Projects for when it is opened next.
         if let Some(index) = self
-            .categories_list
+            .projects
             .iter()
-            .position(|category| category.name == name)
+            .position(|item| item.id == Some(project_id))
         {
-            self.selected_category_index = index;
+            self.selected_project_index = index;
+        }
+        self.selected_project = Some(project);
+        self.navigate_to(AppView::ProjectTickrs);
+        if let Some(tickr_id) = highlight_tickr_id
+            && let Some(index) = self
+                .tickrs
+                .iter()
+                .position(|item| item.id == Some(tickr_id))
+        {
+            self.selected_tickr_index = index;
+        }
+        self.selected_tickr = None;
+        self.selected_tickr_project_name = None;
+        self.selected_tickr_blocker = None;
+        self.clear_status();
+    }
+
+    /// Range selector of the current view, `None` in views without one.
+    fn range_selector_mut(&mut self) -> Option<&mut RangeSelector> {
+        match self.view {
+            AppView::WorkedProjects => Some(&mut self.worked_range),
+            AppView::Timeline => Some(&mut self.timeline_range),
+            _ => None,
+        }
+    }
+
+    /// Moves the range selector to the preset `direction` steps away. Reaching the custom
+    /// preset asks for its dates.
+    pub(super) fn step_range(&mut self, direction: i64) {
+        let Some(selector) = self.range_selector_mut() else {
+            return;
+        };
+        let Some(preset) = selector.neighbour(direction) else {
+            return;
+        };
+        let previous = selector.preset;
+        selector.preset = preset;
+        if preset == RangePreset::Custom {
+            self.popup = Some(ActivePopup::Range(RangePopup::new(previous)));
+        }
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    pub(super) fn open_range_popup(&mut self) {
+        if let Some(selector) = self.range_selector_mut() {
+            self.popup = Some(ActivePopup::Range(RangePopup::new(selector.preset)));
+        }
+    }
+
+    /// Pages the timeline by the length of its range.
+    pub(super) fn shift_timeline(&mut self, direction: i64) {
+        let today = chrono::Local::now().date_naive();
+        self.timeline_range.shift(direction, today, &self.config);
+    }
+
+    /// First and last day the timeline shows.
+    pub fn timeline_days(&self) -> (chrono::NaiveDate, chrono::NaiveDate) {
+        let today = chrono::Local::now().date_naive();
+        self.timeline_range.days(today, &self.config)
+    }
+
+    /// Day the timeline opens the agenda for: the selected day of the shown range, today or the
+    /// closest shown day to it until another one is selected.
+    pub fn selected_timeline_day(&self) -> chrono::NaiveDate {
+        let (first, last) = self.timeline_days();
+        if (first..=last).contains(&self.timeline_selected) {
+            self.timeline_selected
+        } else {
+            chrono::Local::now().date_naive().clamp(first, last)
+        }
+    }
+
+    /// Moves the selected day by `direction` days, within the shown range.
+    pub(super) fn select_timeline_day(&mut self, direction: i64) {
+        let (first, last) = self.timeline_days();
+        self.timeline_selected =
+            (self.selected_timeline_day() + chrono::Duration::days(direction)).clamp(first, last);
+    }
+
+    pub(super) fn handle_range_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Range(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => {
+                let previous = popup.previous;
+                self.popup = None;
+                if let Some(selector) = self.range_selector_mut() {
+                    selector.preset = previous;
+                }
+                self.refresh(RefreshScope::CurrentView);
+            }
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                popup.field = match popup.field {
+                    RangeField::From => RangeField::To,
+                    RangeField::To => RangeField::From,
+                };
+            }
+            KeyCode::Enter => {
+                let today = chrono::Local::now().date_naive();
+                let from = parse_range_date(&popup.from, today);
+                let to = if popup.to.trim().is_empty() {
+                    from
+                } else {
+                    parse_range_date(&popup.to, today)
+                };
+                let (Some(from), Some(to)) = (from, to) else {
+                    popup.error =
+                        Some("Use YYYY-MM-DD, MM-DD, today, yesterday or -N days.".to_string());
+                    return;
+                };
+                if (to - from).num_days().abs() >= super::range::MAX_RANGE_DAYS {
+                    popup.error = Some(format!(
+                        "Pick at most {} days.",
+                        super::range::MAX_RANGE_DAYS
+                    ));
+                    return;
+                }
+                self.popup = None;
+                if let Some(selector) = self.range_selector_mut() {
+                    selector.set_custom(from, to);
+                }
+                if self.view == AppView::Timeline {
+                    self.timeline_selected = from.min(to);
+                }
+                self.refresh(RefreshScope::CurrentView);
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                popup.input_mut().pop();
+            }
+            KeyCode::Char(ch) if !ch.is_control() => popup.input_mut().push(ch),
+            _ => {}
+        }
+    }
+
+    pub(super) fn open_capture_popup(&mut self) {
+        let running_label = match db::query_running_tickr(&self.db) {
+            Ok(running) => running.map(|tickr| tickr.description),
+            Err(err) => {
+                self.status = Some(format!("Failed to load the running task: {err}"));
+                return;
+            }
+        };
+        self.popup = Some(ActivePopup::Capture(CapturePopup {
+            input: String::new(),
+            target: if running_label.is_some() {
+                CaptureTarget::RunningNote
+            } else {
+                CaptureTarget::InboxTask
+            },
+            running_label,
+            error: None,
+        }));
+    }
+
+    pub(super) fn handle_capture_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Capture(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.popup = None,
+            KeyCode::Tab | KeyCode::BackTab if popup.running_label.is_some() => {
+                popup.target = match popup.target {
+                    CaptureTarget::RunningNote => CaptureTarget::InboxTask,
+                    CaptureTarget::InboxTask => CaptureTarget::RunningNote,
+                };
+            }
+            KeyCode::Enter => self.apply_capture_popup(),
+            KeyCode::Backspace | KeyCode::Delete => {
+                popup.input.pop();
+            }
+            KeyCode::Char(ch) if !ch.is_control() => popup.input.push(ch),
+            _ => {}
         }
     }
 
-    fn apply_new_tickr_popup(&mut self) {
-        let Some(popup) = self.new_tickr_popup.take() else {
+    /// Saves the capture without touching the running timer.
+    fn apply_capture_popup(&mut self) {
+        let Some(ActivePopup::Capture(popup)) = self.popup.as_mut() else {
             return;
         };
-
-        let label = popup.label.trim().to_string();
-        if label.is_empty() {
-            self.status = Some("Task label is required.".to_string());
-            self.new_tickr_popup = Some(popup);
+        let text = popup.input.trim().to_string();
+        if text.is_empty() {
+            popup.error = Some("Type something to capture.".to_string());
             return;
         }
-
-        let project_id = match popup.projects.get(popup.project_index) {
-            Some(project) => project.id,
-            None => {
-                self.status = Some("Project selection is required.".to_string());
-                self.new_tickr_popup = Some(popup);
+        let result = match popup.target {
+            CaptureTarget::RunningNote => match db::query_running_tickr(&self.db) {
+                Ok(Some(Tickr {
+                    id: Some(id),
+                    description,
+                    ..
+                })) => db::append_tickr_note(id, &text, &self.db)
+                    .map(|()| format!("Added a note to '{description}'")),
+                Ok(_) => {
+                    popup.error = Some("No task is running anymore.".to_string());
+                    popup.running_label = None;
+                    popup.target = CaptureTarget::InboxTask;
+                    return;
+                }
+                Err(err) => Err(err),
+            },
+            CaptureTarget::InboxTask => db::inbox_project_id(&self.db).and_then(|project_id| {
+                db::create_tickr(
+                    Tickr {
+                        id: None,
+                        project_id,
+                        description: text.clone(),
+                        category_id: None,
+                        billable: true,
+                        done: false,
+                        blocked_by: None,
+                        notes: None,
+                        estimate_minutes: None,
+                        archived: false,
+                        issue: None,
+                        intervals: Vec::new(),
+                    },
+                    &self.db,
+                )
+                .map(|_| format!("Captured '{text}' in the Inbox"))
+            }),
+        };
+        let message = match result {
+            Ok(message) => message,
+            Err(err) => {
+                popup.error = Some(format!("Failed to save: {err}"));
                 return;
             }
         };
+        self.popup = None;
+        self.log_activity(message);
+        self.refresh_project_summaries();
+        self.refresh(RefreshScope::CurrentView);
+    }
 
-        let category_id = popup
-            .categories
-            .get(popup.category_index)
-            .and_then(|option| option.id);
-
-        let tickr = Tickr {
-            id: None,
+    pub(super) fn open_goal_popup(&mut self) {
+        let Some(project) = self.projects.get(self.selected_project_index) else {
+            return;
+        };
+        let Some(project_id) = project.id else {
+            return;
+        };
+        let goal = self
+            .project_goals
+            .get(&project_id)
+            .map(|progress| progress.goal);
+        self.popup = Some(ActivePopup::Goal(GoalPopup {
             project_id,
-            description: label.clone(),
-            category_id,
-            intervals: Vec::new(),
+            project_name: project.name.clone(),
+            input: goal
+                .map(|goal| goal::format_hours(i64::from(goal.minutes) * 60))
+                .unwrap_or_default(),
+            period: goal.map_or(GoalPeriod::Week, |goal| goal.period),
+            error: None,
+        }));
+    }
+
+    pub(super) fn handle_goal_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Goal(popup)) = self.popup.as_mut() else {
+            return;
         };
+        match key {
+            KeyCode::Esc => self.popup = None,
+            KeyCode::Tab | KeyCode::BackTab => {
+                popup.period = match popup.period {
+                    GoalPeriod::Week => GoalPeriod::Total,
+                    GoalPeriod::Total => GoalPeriod::Week,
+                };
+            }
+            KeyCode::Enter => self.apply_goal_popup(),
+            KeyCode::Backspace | KeyCode::Delete => {
+                popup.input.pop();
+            }
+            KeyCode::Char(ch) if !ch.is_control() => popup.input.push(ch),
+            _ => {}
+        }
+    }
 
-        let tickr_id = match db::create_tickr(tickr, &self.db) {
-            Ok(id) => id,
+    fn apply_goal_popup(&mut self) {
+        let Some(ActivePopup::Goal(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        let goal = if popup.input.trim().is_empty() {
+            None
+        } else {
+            match goal::parse_hours(&popup.input).filter(|minutes| *minutes > 0) {
+                Some(minutes) => Some(ProjectGoal {
+                    period: popup.period,
+                    minutes,
+                }),
+                None => {
+                    popup.error = Some("Enter hours like 20, 7.5 or 1h30m.".to_string());
+                    return;
+                }
+            }
+        };
+        if let Err(err) = db::set_project_goal(popup.project_id, goal, &self.db) {
+            popup.error = Some(format!("Failed to save the goal: {err}"));
+            return;
+        }
+        let message = match &goal {
+            Some(goal) => format!(
+                "Set the goal of '{}' to {}",
+                popup.project_name,
+                goal::describe(goal)
+            ),
+            None => format!("Removed the goal of '{}'", popup.project_name),
+        };
+        self.popup = None;
+        self.log_activity(message);
+        self.refresh_project_summaries();
+    }
+
+    pub(super) fn open_client_popup(&mut self) {
+        let Some(project) = self.projects.get(self.selected_project_index) else {
+            return;
+        };
+        let Some(project_id) = project.id else {
+            return;
+        };
+        let clients = match db::query_clients(&self.db) {
+            Ok(clients) => clients.into_iter().map(|client| client.name).collect(),
             Err(err) => {
-                self.status = Some(format!("Failed to create task: {err}"));
-                self.new_tickr_popup = Some(popup);
+                self.status = Some(format!("Failed to load clients: {err}"));
                 return;
             }
         };
+        self.popup = Some(ActivePopup::Client(ClientPopup {
+            project_id,
+            project_name: project.name.clone(),
+            input: project
+                .client_id
+                .and_then(|id| self.clients.get(&id).cloned())
+                .unwrap_or_default(),
+            clients,
+            error: None,
+        }));
+    }
 
-        if popup.start_now {
-            if let Some(running_id) = self.running_tickr {
-                if let Err(err) = db::end_tickr(running_id, &self.db) {
-                    self.status = Some(format!("Failed to stop running task: {err}"));
-                    return;
+    pub(super) fn handle_client_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Client(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.popup = None,
+            KeyCode::Tab => {
+                // The first client starting with the input, then the ones after it.
+                let input = popup.input.trim().to_lowercase();
+                let next = match popup
+                    .clients
+                    .iter()
+                    .position(|name| name.to_lowercase() == input)
+                {
+                    Some(index) => Some((index + 1) % popup.clients.len()),
+                    None => popup
+                        .clients
+                        .iter()
+                        .position(|name| name.to_lowercase().starts_with(&input)),
+                };
+                if let Some(index) = next {
+                    popup.input = popup.clients[index].clone();
                 }
-                self.running_tickr = None;
             }
-            if let Err(err) = db::start_tickr(tickr_id, &self.db) {
-                self.status = Some(format!("Failed to start task: {err}"));
-                return;
+            KeyCode::Enter => self.apply_client_popup(),
+            KeyCode::Backspace | KeyCode::Delete => {
+                popup.input.pop();
             }
-            self.running_tickr = Some(tickr_id);
-            self.status = Some("Task created and started.".to_string());
-        } else {
-            self.status = Some("Task created.".to_string());
+            KeyCode::Char(ch) if !ch.is_control() => popup.input.push(ch),
+            _ => {}
         }
+    }
 
-        self.refresh_project_summaries();
-        match self.view {
-            AppView::Projects => self.load_projects(),
-            AppView::ProjectTickrs => self.load_project_tickrs(),
-            AppView::Tickrs => self.load_tickrs(),
+    /// Sets the typed client, created when it's new; an empty name removes the client.
+    fn apply_client_popup(&mut self) {
+        let Some(ActivePopup::Client(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        let project_id = popup.project_id;
+        let name = popup.input.trim();
+        let result = if name.is_empty() {
+            db::set_project_client(project_id, None, &self.db).map(|()| None)
+        } else {
+            db::query_client_by_name(name, &self.db)
+                .and_then(|existing| match existing {
+                    Some(client) => Ok((client.id, client.name)),
+                    None => Ok((db::create_client(name, &self.db)?, name.to_string())),
+                })
+                .and_then(|(id, name)| {
+                    db::set_project_client(project_id, Some(id), &self.db)?;
+                    Ok(Some(name))
+                })
+        };
+        let message = match result {
+            Ok(Some(client)) => format!("Set the client of '{}' to {client}", popup.project_name),
+            Ok(None) => format!("Removed the client of '{}'", popup.project_name),
+            Err(err) => {
+                popup.error = Some(format!("Failed to save the client: {err}"));
+                return;
+            }
+        };
+        self.popup = None;
+        self.log_activity(message);
+        self.refresh(RefreshScope::CurrentView);
+    }
+
+    pub(super) fn open_notes_popup(&mut self) {
+        let Some(tickr) = &self.selected_tickr else {
+            return;
+        };
+        self.popup = NotesPopup::new(tickr, &self.config).map(ActivePopup::Notes);
+    }
+
+    pub(super) fn handle_notes_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Notes(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => self.save_notes_popup(),
+            KeyCode::Tab => popup.cycle(true),
+            KeyCode::BackTab => popup.cycle(false),
+            KeyCode::Enter => popup.current_mut().text.push('\n'),
+            KeyCode::Backspace | KeyCode::Delete => {
+                popup.current_mut().text.pop();
+            }
+            KeyCode::Char(ch) if !ch.is_control() => popup.current_mut().text.push(ch),
             _ => {}
         }
     }
 
-    fn apply_delete_tickr_popup(&mut self) {
-        let Some(popup) = self.delete_tickr_popup.take() else {
+    /// Stores every changed note and closes the editor.
+    fn save_notes_popup(&mut self) {
+        let Some(ActivePopup::Notes(popup)) = self.popup.as_mut() else {
             return;
         };
+        for target in popup.targets.iter().filter(|target| target.changed()) {
+            let result = match target.interval_id {
+                Some(id) => db::set_interval_notes(id, target.saved_text(), &self.db),
+                None => db::set_tickr_notes(popup.tickr_id, target.saved_text(), &self.db),
+            };
+            if let Err(err) = result {
+                popup.error = Some(format!("Failed to save the notes: {err}"));
+                return;
+            }
+        }
+        let tickr_id = popup.tickr_id;
+        let changed = popup.targets.iter().any(|target| target.changed());
+        self.popup = None;
+        if changed {
+            self.log_activity(format!("Edited the notes of {}", self.tickr_name(tickr_id)));
+        }
+        self.refresh_tickr_detail();
+    }
 
-        if let Err(err) = db::delete_tickr(popup.tickr_id, &self.db) {
-            self.status = Some(format!("Failed to delete task: {err}"));
-            self.delete_tickr_popup = Some(popup);
+    pub(super) fn open_triage_popup(&mut self) {
+        let loaded = db::inbox_project_id(&self.db).and_then(|inbox_id| {
+            let tickrs =
+                db::query_tickr(crate::types::TickrQuery::ByProjectId(inbox_id), &self.db)?;
+            let projects = db::query_projects(&self.db)?;
+            let categories = db::query_categories(&self.db)?;
+            Ok((inbox_id, tickrs, projects, categories))
+        });
+        let (inbox_id, tickrs, projects, mut categories) = match loaded {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                self.status = Some(format!("Failed to load the inbox: {err}"));
+                return;
+            }
+        };
+        let tickrs: Vec<Tickr> = tickrs.into_iter().filter(|tickr| !tickr.done).collect();
+        if tickrs.is_empty() {
+            self.status = Some(format!(
+                "Nothing to triage, {} is empty.",
+                db::INBOX_PROJECT
+            ));
+            return;
+        }
+        let mut projects: Vec<ProjectOption> = projects
+            .into_iter()
+            .filter(|project| !project.archived)
+            .filter_map(|project| {
+                let id = project.id.filter(|id| *id != inbox_id)?;
+                Some(ProjectOption {
+                    id,
+                    name: project.name,
+                })
+            })
+            .collect();
+        if projects.is_empty() {
+            self.status = Some("Create a project to move inbox tasks to first.".to_string());
             return;
         }
+        projects.sort_by_key(|project| project.name.to_lowercase());
+        categories.sort_by_key(|category| category.name.to_lowercase());
+        let mut options = vec![CategoryOption {
+            id: None,
+            name: "none".to_string(),
+            color: None,
+        }];
+        options.extend(categories.into_iter().map(|category| CategoryOption {
+            id: Some(category.id),
+            name: category.name,
+            color: Some(category.color),
+        }));
 
-        if self.running_tickr == Some(popup.tickr_id) {
+        let mut popup = TriagePopup {
+            tickrs,
+            index: 0,
+            projects,
+            project_index: 0,
+            categories: options,
+            category_index: 0,
+            estimate: String::new(),
+            field: TriageField::Project,
+            error: None,
+        };
+        popup.show(0);
+        self.popup = Some(ActivePopup::Triage(popup));
+    }
+
+    pub(super) fn handle_triage_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Triage(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => {
+                self.popup = None;
+                self.refresh_project_summaries();
+                self.refresh(RefreshScope::CurrentView);
+            }
+            KeyCode::Tab => popup.field = popup.field.next(),
+            KeyCode::BackTab => popup.field = popup.field.prev(),
+            KeyCode::Up => popup.cycle_option(false),
+            KeyCode::Down => popup.cycle_option(true),
+            KeyCode::Left => popup.show(popup.index + popup.tickrs.len() - 1),
+            KeyCode::Right => popup.show(popup.index + 1),
+            KeyCode::Enter => self.apply_triage(),
+            KeyCode::Delete => self.delete_triaged_tickr(),
+            KeyCode::Backspace if popup.field == TriageField::Estimate => {
+                popup.estimate.pop();
+            }
+            KeyCode::Char(ch) if popup.field == TriageField::Estimate && !ch.is_control() => {
+                popup.estimate.push(ch);
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the shown task out of the inbox and shows the next one.
+    fn apply_triage(&mut self) {
+        let Some(ActivePopup::Triage(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        let Some(tickr) = popup.current() else {
+            return;
+        };
+        let Some(tickr_id) = tickr.id else {
+            return;
+        };
+        let estimate = if popup.estimate.trim().is_empty() {
+            None
+        } else {
+            match parse_estimate(&popup.estimate) {
+                Some(minutes) => Some(minutes),
+                None => {
+                    popup.error = Some("Estimate like 45m, 2h, 1h30 or 1.5h.".to_string());
+                    return;
+                }
+            }
+        };
+        let project_id = popup.projects[popup.project_index].id;
+        let category_id = popup.categories[popup.category_index].id;
+        let result = db::set_tickr_project(tickr_id, project_id, &self.db)
+            .and_then(|_| {
+                db::update_tickr_details(tickr_id, tickr.description.clone(), category_id, &self.db)
+            })
+            .and_then(|_| db::set_tickr_estimate(tickr_id, estimate, &self.db));
+        if let Err(err) = result {
+            popup.error = Some(format!("Failed to update task: {err}"));
+            return;
+        }
+        let message = format!(
+            "Moved '{}' from the Inbox to '{}'",
+            tickr.description, popup.projects[popup.project_index].name
+        );
+        self.log_activity(message);
+        self.remove_triaged_tickr();
+    }
+
+    fn delete_triaged_tickr(&mut self) {
+        let Some(ActivePopup::Triage(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        let Some(tickr) = popup.current() else {
+            return;
+        };
+        let Some(tickr_id) = tickr.id else {
+            return;
+        };
+        let message = format!("Deleted '{}' from the Inbox", tickr.description);
+        if let Err(err) = db::delete_tickr(tickr_id, &self.db) {
+            popup.error = Some(format!("Failed to delete task: {err}"));
+            return;
+        }
+        self.log_activity(message);
+        if self.running_tickr == Some(tickr_id) {
             self.running_tickr = None;
         }
+        self.remove_triaged_tickr();
+    }
 
-        self.refresh_project_summaries();
-        self.selected_tickr = None;
-        self.selected_tickr_project_name = None;
+    /// Drops the shown task from the triage, closing it once the inbox is empty.
+    fn remove_triaged_tickr(&mut self) {
+        let Some(ActivePopup::Triage(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        popup.tickrs.remove(popup.index);
+        if popup.tickrs.is_empty() {
+            self.popup = None;
+            self.refresh_project_summaries();
+            self.refresh(RefreshScope::CurrentView);
+            return;
+        }
+        popup.show(popup.index);
+    }
 
-        match self.view {
-            AppView::TickrDetail => self.go_back(),
-            AppView::Tickrs => self.load_tickrs(),
-            AppView::ProjectTickrs => self.load_project_tickrs(),
-            _ => self.refresh_view_data(),
+    /// Archives or unarchives the selected project or task.
+    pub(super) fn toggle_archived(&mut self) {
+        let result = match self.view {
+            AppView::Projects => {
+                let Some(project) = self.projects.get(self.selected_project_index) else {
+                    return;
+                };
+                let Some(id) = project.id else {
+                    return;
+                };
+                let verb = if project.archived {
+                    "Unarchived"
+                } else {
+                    "Archived"
+                };
+                db::set_project_archived(id, !project.archived, &self.db)
+                    .map(|()| format!("{verb} project '{}'", project.name))
+            }
+            AppView::Tickrs | AppView::ProjectTickrs | AppView::TickrDetail => {
+                let tickr = if self.view == AppView::TickrDetail {
+                    self.selected_tickr.as_ref()
+                } else {
+                    self.tickrs.get(self.selected_tickr_index)
+                };
+                let Some(tickr) = tickr else {
+                    return;
+                };
+                let Some(id) = tickr.id else {
+                    return;
+                };
+                let verb = if tickr.archived {
+                    "Unarchived"
+                } else {
+                    "Archived"
+                };
+                db::set_tickr_archived(id, !tickr.archived, &self.db)
+                    .map(|()| format!("{verb} '{}'", tickr.description))
+            }
+            _ => return,
+        };
+        match result {
+            Ok(message) => self.log_activity(message),
+            Err(err) => {
+                self.status = Some(format!("Failed to archive: {err}"));
+                return;
+            }
         }
+        self.refresh(RefreshScope::CurrentView);
+    }
 
-        self.status = Some("Task deleted.".to_string());
+    /// Collapses (`collapse`) or expands the group of the selected project in the Projects or
+    /// Worked tab, or every group when `all` is set. The group's parent stays selected.
+    pub(super) fn set_projects_collapsed(&mut self, collapse: bool, all: bool) {
+        let list = match self.view {
+            AppView::Projects => &self.projects,
+            AppView::WorkedProjects => &self.worked_projects,
+            _ => return,
+        };
+        let index = match self.view {
+            AppView::Projects => self.selected_project_index,
+            _ => self.selected_worked_project_index,
+        };
+        let selected = list.get(index).cloned();
+        let parent = selected
+            .as_ref()
+            .and_then(|project| project.parent_id.or(project.id))
+            .filter(|id| self.sub_projects.contains_key(id));
+        if all {
+            if collapse {
+                self.collapsed_projects = self.sub_projects.keys().copied().collect();
+            } else {
+                self.collapsed_projects.clear();
+            }
+        } else if let Some(parent) = parent {
+            if collapse {
+                self.collapsed_projects.insert(parent);
+            } else {
+                self.collapsed_projects.remove(&parent);
+            }
+        } else {
+            return;
+        }
+
+        let keep = if collapse {
+            parent
+        } else {
+            selected.and_then(|project| project.id)
+        };
+        self.refresh(RefreshScope::CurrentView);
+        match self.view {
+            AppView::Projects => {
+                if let Some(index) = self
+                    .projects
+                    .iter()
+                    .position(|p| p.id == keep && keep.is_some())
+                {
+                    self.selected_project_index = index;
+                }
+            }
+            _ => {
+                if let Some(index) = self
+                    .worked_projects
+                    .iter()
+                    .position(|p| p.id == keep && keep.is_some())
+                {
+                    self.selected_worked_project_index = index;
+                }
+            }
+        }
     }
 
-    fn go_back(&mut self) {
-        if let Some(prev_view) = self.view_history.pop() {
-            //Assign the new view manually (cyclic loop when using navigate_to)
-            self.view = prev_view;
-            self.load_content_for_view();
+    fn refresh_project_summaries(&mut self) {
+        match db::query_tickr(crate::types::TickrQuery::All, &self.db) {
+            Ok(tickrs) => {
+                let mut summaries: HashMap<ProjectId, ProjectSummary> = HashMap::new();
+                let now = chrono::Local::now();
+                for tickr in tickrs {
+                    let entry = summaries.entry(tickr.project_id).or_default();
+                    let last_interval = tickr.intervals.last();
+                    let is_running = last_interval
+                        .map(|interval| interval.end_time.is_none())
+                        .unwrap_or(false);
+                    if is_running || tickr.intervals.is_empty() {
+                        entry.open += 1;
+                    } else {
+                        entry.ended += 1;
+                    }
+                    for interval in &tickr.intervals {
+                        let end = interval.end_time.unwrap_or(now);
+                        entry.last_worked = entry.last_worked.max(Some(end));
+                        if let Some(end_time) = interval.end_time {
+                            let seconds = end_time
+                                .signed_duration_since(interval.start_time)
+                                .num_seconds();
+                            if seconds > 0 {
+                                entry.total_seconds += seconds;
+                            }
+                        }
+                    }
+                }
+                self.project_summaries = summaries;
+            }
+            Err(err) => {
+                self.status = Some(format!("Failed to load project summaries: {err}"));
+            }
         }
-        self.clear_status();
+        self.refresh_project_goals();
     }
 
-    fn toggle_tickr(&mut self) {
-        let tickr = match self.current_tickr() {
-            Some(tickr) => tickr,
-            None => {
-                self.status = Some("No task selected.".to_string());
+    /// Reloads the progress of project goals and celebrates those reached since the last load.
+    fn refresh_project_goals(&mut self) {
+        let now = self.now();
+        self.goals_counted_until = now;
+        let goals = match goal::progress(&self.config, now, &self.db) {
+            Ok(goals) => goals,
+            Err(err) => {
+                self.status = Some(format!("Failed to load project goals: {err}"));
                 return;
             }
         };
-        let Some(id) = tickr.id else {
-            return;
-        };
-
-        let is_current_running = tickr
-            .intervals
-            .last()
-            .map(|interval| interval.end_time.is_none())
-            .unwrap_or(false)
-            && tickr.id == self.running_tickr;
-        let result = if is_current_running {
-            db::end_tickr(id, &self.db)
-        } else {
-            if self.running_tickr.is_some() {
-                // Stop currently running tickr if any
-                if let Some(running_id) = self.running_tickr {
-                    if let Err(err) = db::end_tickr(running_id, &self.db) {
-                        self.status = Some(format!("Failed to stop currently running task: {err}"));
-                        return;
-                    }
-                }
-                self.running_tickr = None;
+        let reached: Vec<(ProjectId, GoalProgress)> = goals
+            .iter()
+            .filter(|(project_id, progress)| {
+                progress.seconds >= progress.target_seconds()
+                    && self.project_goals.get(project_id).is_some_and(|previous| {
+                        previous.goal == progress.goal
+                            && previous.seconds < previous.target_seconds()
+                    })
+            })
+            .map(|(project_id, progress)| (*project_id, *progress))
+            .collect();
+        self.project_goals = goals;
+        for (project_id, progress) in reached {
+            let name = self
+                .projects
+                .iter()
+                .find(|project| project.id == Some(project_id))
+                .map_or("A project", |project| project.name.as_str());
+            let message = format!(
+                "{name} reached its goal of {}",
+                goal::describe(&progress.goal)
+            );
+            if self.config.goal_notifications {
+                let body = message.clone();
+                std::thread::spawn(move || {
+                    crate::notify::send_quietly("Tickr: goal reached", &body);
+                });
             }
-            db::start_tickr(id, &self.db)
-        };
+            self.celebrate(message);
+        }
+    }
 
-        if let Err(err) = result {
-            self.status = Some(format!("Failed to update task: {err}"));
+    pub(super) fn start_pomodoro(&mut self) {
+        if self.pomodoro.is_some() {
+            self.status = Some("Pomodoro already running. X cancels it.".to_string());
             return;
-        } else {
-            self.running_tickr = Some(id);
         }
+        let Some(tickr_id) = self.running_tickr else {
+            self.status = Some("Start a task before starting a pomodoro.".to_string());
+            return;
+        };
+        self.pomodoro = Some(Pomodoro::new(tickr_id, self.now()));
+        self.status = Some(format!(
+            "Pomodoro started: {} minutes of work.",
+            self.pomodoro_settings.work.num_minutes()
+        ));
+    }
+
+    pub(super) fn skip_pomodoro_phase(&mut self) {
+        match self.pomodoro.as_ref().map(|pomodoro| pomodoro.phase) {
+            Some(PomodoroPhase::Work) => self.finish_work_block(),
+            Some(PomodoroPhase::Break) => self.begin_work_block(),
+            None => self.status = Some("No pomodoro running.".to_string()),
+        }
+    }
 
-        match self.view {
-            AppView::Tickrs => self.load_tickrs(),
-            AppView::ProjectTickrs => self.load_project_tickrs(),
-            AppView::TickrDetail => self.refresh_tickr_detail(),
-            _ => {}
+    pub(super) fn cancel_pomodoro(&mut self) {
+        if self.pomodoro.take().is_some() {
+            self.status = Some("Pomodoro cancelled.".to_string());
         }
     }
 
-    fn refresh_tickr_detail(&mut self) {
-        let Some(tickr) = &self.selected_tickr else {
+    /// Advances the pomodoro when the current phase ran out.
+    fn update_pomodoro(&mut self) {
+        let Some(pomodoro) = self.pomodoro.as_ref().filter(|_| !self.read_only) else {
             return;
         };
-        let Some(id) = tickr.id else {
+        let now = self.now();
+        if pomodoro.remaining(&self.pomodoro_settings, now) > chrono::Duration::zero() {
             return;
-        };
-        match db::query_tickr_by_id(id, &self.db) {
-            Ok(Some(updated)) => {
-                self.selected_tickr = Some(updated);
-                self.status = None;
-                self.refresh_categories_for_tickrs();
-            }
-            Ok(None) => {
-                self.status = Some("Task not found.".to_string());
-            }
-            Err(err) => {
-                self.status = Some(format!("Failed to refresh task: {err}"));
+        }
+        match pomodoro.phase {
+            PomodoroPhase::Work => self.finish_work_block(),
+            PomodoroPhase::Break if self.popup.is_none() => {
+                self.popup = Some(ActivePopup::Pomodoro(
+                    self.pomodoro_popup(PomodoroAlert::BreakOver),
+                ));
             }
+            PomodoroPhase::Break => {}
         }
     }
 
-    fn refresh_categories_for_tickrs(&mut self) {
-        let mut missing = HashSet::new();
-        for tickr in &self.tickrs {
-            if let Some(id) = tickr.category_id {
-                if !self.categories.contains_key(&id) {
-                    missing.insert(id);
+    /// Stops the pomodoro task and starts the break.
+    fn finish_work_block(&mut self) {
+        let Some(tickr_id) = self.pomodoro.as_ref().map(|pomodoro| pomodoro.tickr_id) else {
+            return;
+        };
+        if self.running_tickr == Some(tickr_id) {
+            match daemon::end_tickr(tickr_id, &self.db) {
+                Ok(warning) => {
+                    self.log_activity(format!(
+                        "Stopped {} for a pomodoro break",
+                        self.tickr_name(tickr_id)
+                    ));
+                    self.warn_interval(warning, STOPPED_EMPTY);
                 }
+                Err(err) => self.status = Some(format!("Failed to stop task: {err}")),
             }
         }
-        if let Some(tickr) = &self.selected_tickr {
-            if let Some(id) = tickr.category_id {
-                if !self.categories.contains_key(&id) {
-                    missing.insert(id);
-                }
-            }
+        let now = self.now();
+        if let Some(pomodoro) = self.pomodoro.as_mut() {
+            pomodoro.start_break(now);
+        }
+        self.refresh(RefreshScope::CurrentView);
+        // The break starts on time either way; a popup the user is busy with stays open.
+        if self.popup.is_none() {
+            self.popup = Some(ActivePopup::Pomodoro(
+                self.pomodoro_popup(PomodoroAlert::BreakStarted),
+            ));
+        } else {
+            self.status = Some(format!(
+                "Time for a break: {} minutes.",
+                self.pomodoro_settings.short_break.num_minutes()
+            ));
         }
+    }
 
-        for id in missing {
-            match db::query_category_by_id(id, &self.db) {
-                Ok(Some(category)) => {
-                    self.categories.insert(id, category);
-                }
-                Ok(None) => {}
+    /// Restarts the pomodoro task, stopping whatever else is running.
+    fn begin_work_block(&mut self) {
+        let Some(tickr_id) = self.pomodoro.as_ref().map(|pomodoro| pomodoro.tickr_id) else {
+            return;
+        };
+        if self.running_tickr != Some(tickr_id) {
+            match daemon::start_tickr(tickr_id, &self.db) {
+                Ok(warning) => self.warn_interval(warning, SWITCHED_EMPTY),
                 Err(err) => {
-                    self.status = Some(format!("Failed to load categories: {err}"));
+                    self.status = Some(format!("Failed to start task: {err}"));
                     return;
                 }
             }
+            self.log_activity(format!(
+                "Started {} after a pomodoro break",
+                self.tickr_name(tickr_id)
+            ));
+        }
+        let now = self.now();
+        if let Some(pomodoro) = self.pomodoro.as_mut() {
+            pomodoro.start_work(now);
         }
+        self.refresh(RefreshScope::CurrentView);
+        self.status = Some(format!(
+            "Back to work: {} minutes.",
+            self.pomodoro_settings.work.num_minutes()
+        ));
     }
 
-    pub fn category_for_tickr(&self, tickr: &Tickr) -> Option<&TickrCategory> {
-        tickr.category_id.and_then(|id| self.categories.get(&id))
+    fn pomodoro_popup(&self, alert: PomodoroAlert) -> PomodoroPopup {
+        let tickr_id = self.pomodoro.as_ref().map(|pomodoro| pomodoro.tickr_id);
+        let task = self
+            .tickrs
+            .iter()
+            .find(|tickr| tickr.id.is_some() && tickr.id == tickr_id)
+            .map(|tickr| tickr.description.clone())
+            .unwrap_or_default();
+        PomodoroPopup {
+            alert,
+            task,
+            break_minutes: self.pomodoro_settings.short_break.num_minutes(),
+            completed: self
+                .pomodoro
+                .as_ref()
+                .map(|pomodoro| pomodoro.completed)
+                .unwrap_or(0),
+        }
     }
 
-    fn current_tickr(&self) -> Option<&Tickr> {
-        match self.view {
-            AppView::Tickrs | AppView::ProjectTickrs => self.tickrs.get(self.selected_tickr_index),
-            AppView::TickrDetail => self.selected_tickr.as_ref(),
-            _ => None,
+    pub(super) fn handle_pomodoro_popup_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Pomodoro(popup)) = &self.popup else {
+            return;
+        };
+        match (popup.alert, key) {
+            (PomodoroAlert::BreakStarted, KeyCode::Enter | KeyCode::Esc) => {
+                self.popup = None;
+            }
+            (PomodoroAlert::BreakOver, KeyCode::Enter) => {
+                self.popup = None;
+                self.begin_work_block();
+            }
+            (PomodoroAlert::BreakOver, KeyCode::Esc) => {
+                self.popup = None;
+                self.cancel_pomodoro();
+            }
+            _ => {}
         }
     }
 
-    fn lookup_project_name(&self, project_id: u32) -> Option<String> {
-        db::query_project_by_id(project_id, &self.db)
-            .ok()
-            .flatten()
-            .map(|project| project.name)
+    /// Asks what to do with the idle time once the user stopped typing for too long.
+    /// Stops the running task once the `[auto_stop]` rules say so, at the time they give, and
+    /// asks whether to move the end back to the last key press.
+    pub fn check_auto_stop(&mut self) {
+        if self.read_only || self.popup.is_some() {
+            return;
+        }
+        let (Some(tickr_id), Some(interval_id), Some(start)) = (
+            self.running_tickr,
+            self.running_interval,
+            self.running_since,
+        ) else {
+            return;
+        };
+        let Some(stop) = self.config.auto_stop.stop_time(start, chrono::Local::now()) else {
+            return;
+        };
+        let task = self
+            .lookup_tickr_description(Some(tickr_id))
+            .unwrap_or_default();
+        if let Err(err) = db::stop_tickr_at(tickr_id, stop, &self.db) {
+            self.status = Some(format!("Failed to stop task: {err}"));
+            return;
+        }
+        self.log_activity(format!(
+            "Auto-stopped '{task}' at {}",
+            stop.format("%Y-%m-%d %H:%M")
+        ));
+        self.running_tickr = None;
+        self.popup = Some(ActivePopup::AutoStop(AutoStopPopup::new(
+            interval_id,
+            task,
+            start,
+            stop,
+            Some(self.idle_tracker.last_activity()),
+        )));
+        self.refresh(RefreshScope::CurrentView);
     }
 
-    fn go_to_project_from_tickr(&mut self) {
-        if self.view != AppView::TickrDetail {
+    pub(super) fn handle_auto_stop_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::AutoStop(popup)) = self.popup.as_mut() else {
             return;
+        };
+        match key {
+            KeyCode::Esc => self.popup = None,
+            KeyCode::Enter => self.apply_auto_stop_popup(),
+            KeyCode::Backspace | KeyCode::Delete => {
+                popup.input.pop();
+            }
+            KeyCode::Char(ch) if !ch.is_control() => popup.input.push(ch),
+            _ => {}
         }
-        let Some(tickr) = &self.selected_tickr else {
+    }
+
+    fn apply_auto_stop_popup(&mut self) {
+        let Some(ActivePopup::AutoStop(popup)) = self.popup.as_mut() else {
             return;
         };
-        self.go_to_project_by_id(tickr.project_id, tickr.id);
+        let Some(end) =
+            crate::ui::parse_local_datetime(&popup.input, popup.stopped_at.date_naive())
+        else {
+            popup.error = Some("End must be YYYY-MM-DD HH:MM or HH:MM.".to_string());
+            return;
+        };
+        if end <= popup.start || end > popup.stopped_at {
+            popup.error = Some(format!(
+                "Pick a time after {} and no later than {}.",
+                popup.start.format("%Y-%m-%d %H:%M"),
+                popup.stopped_at.format("%Y-%m-%d %H:%M")
+            ));
+            return;
+        }
+        if end < popup.stopped_at {
+            if let Err(err) = daemon::set_interval_end(popup.interval_id, end, &self.db) {
+                popup.error = Some(format!("Failed to trim the interval: {err}"));
+                return;
+            }
+            let message = format!("Trimmed '{}' to end at {}", popup.task, end.format("%H:%M"));
+            self.status = Some(format!("{message}."));
+            self.log_activity(message);
+            self.refresh(RefreshScope::CurrentView);
+        }
+        self.popup = None;
     }
 
-    fn stop_running_tickr(&mut self) {
-        self.refresh_running_tickrs();
-        let running = self.tickrs.iter().find(|tickr| {
-            tickr
-                .intervals
-                .last()
-                .map(|interval| interval.end_time.is_none())
-                .unwrap_or(false)
-        });
-        let Some(tickr) = running else {
-            self.status = Some("No task running.".to_string());
+    fn check_idle(&mut self) {
+        if self.read_only || self.popup.is_some() {
+            return;
+        }
+        let (Some(tickr_id), Some(running_since)) = (self.running_tickr, self.running_since) else {
             return;
         };
-        let Some(id) = tickr.id else {
-            self.status = Some("Running task has no id.".to_string());
+        let Some(idle_start) = self
+            .idle_tracker
+            .idle_since(running_since, chrono::Local::now())
+        else {
             return;
         };
+        let tasks = self
+            .tickrs
+            .iter()
+            .filter(|candidate| candidate.id != Some(tickr_id))
+            .filter_map(|candidate| {
+                let project = self
+                    .projects
+                    .iter()
+                    .find(|project| project.id == Some(candidate.project_id))
+                    .map(|project| project.name.as_str())
+                    .unwrap_or("Unknown project");
+                Some(IdleTaskOption {
+                    id: candidate.id?,
+                    label: format!("{project} > {}", candidate.description),
+                })
+            })
+            .collect();
+        self.popup = Some(ActivePopup::Idle(IdlePopup {
+            tickr_id,
+            task: self
+                .lookup_tickr_description(Some(tickr_id))
+                .unwrap_or_default(),
+            idle_start,
+            choice_index: 0,
+            assigning: false,
+            tasks,
+            task_index: 0,
+        }));
+    }
 
-        if let Err(err) = db::end_tickr(id, &self.db) {
-            self.status = Some(format!("Failed to stop task: {err}"));
+    pub(super) fn handle_idle_key(&mut self, key: KeyCode) {
+        let Some(ActivePopup::Idle(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        if popup.assigning {
+            match key {
+                KeyCode::Esc => popup.assigning = false,
+                KeyCode::Up if !popup.tasks.is_empty() => {
+                    popup.task_index =
+                        (popup.task_index + popup.tasks.len() - 1) % popup.tasks.len();
+                }
+                KeyCode::Down if !popup.tasks.is_empty() => {
+                    popup.task_index = (popup.task_index + 1) % popup.tasks.len();
+                }
+                KeyCode::Enter => self.apply_idle_choice(IdleChoice::Assign),
+                _ => {}
+            }
             return;
         }
+        match key {
+            KeyCode::Up => {
+                popup.choice_index =
+                    (popup.choice_index + IdleChoice::ALL.len() - 1) % IdleChoice::ALL.len();
+            }
+            KeyCode::Down => {
+                popup.choice_index = (popup.choice_index + 1) % IdleChoice::ALL.len();
+            }
+            KeyCode::Enter => {
+                let choice = IdleChoice::ALL[popup.choice_index];
+                self.select_idle_choice(choice);
+            }
+            KeyCode::Esc | KeyCode::Char('k') => self.apply_idle_choice(IdleChoice::Keep),
+            KeyCode::Char('d') => self.apply_idle_choice(IdleChoice::Discard),
+            KeyCode::Char('a') => self.select_idle_choice(IdleChoice::Assign),
+            _ => {}
+        }
+    }
 
-        self.go_to_project_by_id(tickr.project_id, Some(id));
+    fn select_idle_choice(&mut self, choice: IdleChoice) {
+        let Some(ActivePopup::Idle(popup)) = self.popup.as_mut() else {
+            return;
+        };
+        if choice != IdleChoice::Assign {
+            self.apply_idle_choice(choice);
+        } else if popup.tasks.is_empty() {
+            self.status = Some("No other task to assign the idle time to.".to_string());
+        } else {
+            popup.assigning = true;
+        }
     }
 
-    fn go_to_project_by_id(&mut self, project_id: u32, highlight_tickr_id: Option<u32>) {
-        let project = match db::query_project_by_id(project_id, &self.db) {
-            Ok(Some(project)) => project,
-            _ => {
-                self.status = Some("Project not found.".to_string());
+    fn apply_idle_choice(&mut self, choice: IdleChoice) {
+        let Some(ActivePopup::Idle(popup)) = self.popup.take() else {
+            return;
+        };
+        let now = chrono::Local::now();
+        let idle = crate::ui::format_duration(now.signed_duration_since(popup.idle_start));
+        let result = match choice {
+            IdleChoice::Keep => {
+                self.status = Some(format!("Kept {idle} of idle time on {}.", popup.task));
                 return;
             }
+            IdleChoice::Discard => {
+                daemon::split_running_interval(popup.tickr_id, popup.idle_start, now, &self.db)
+                    .map(|_| format!("Discarded {idle} of idle time."))
+            }
+            IdleChoice::Assign => {
+                let Some(target) = popup.tasks.get(popup.task_index) else {
+                    return;
+                };
+                daemon::split_running_interval(popup.tickr_id, popup.idle_start, now, &self.db)
+                    .and_then(|()| {
+                        daemon::create_interval(target.id, popup.idle_start, Some(now), &self.db)
+                    })
+                    .map(|_| format!("Assigned {idle} of idle time to {}.", target.label))
+            }
         };
-
-        self.load_projects();
-        if let Some(index) = self
-            .projects
-            .iter()
-            .position(|item| item.id == Some(project_id))
-        {
-            self.selected_project_index = index;
-        }
-        self.selected_project = Some(project);
-        self.navigate_to(AppView::ProjectTickrs);
-        self.load_project_tickrs();
-        if let Some(tickr_id) = highlight_tickr_id {
-            if let Some(index) = self
-                .tickrs
-                .iter()
-                .position(|item| item.id == Some(tickr_id))
-            {
-                self.selected_tickr_index = index;
+        match result {
+            Ok(message) => {
+                self.log_activity(format!(
+                    "{} from '{}'",
+                    message.trim_end_matches('.'),
+                    popup.task
+                ));
+                self.status = Some(message);
+            }
+            Err(err) => {
+                if !self.show_interval_error(&err, "The idle time was kept.") {
+                    self.status = Some(format!("Failed to update idle time: {err}"));
+                }
             }
         }
-        self.selected_tickr = None;
-        self.selected_tickr_project_name = None;
-        self.clear_status();
+        self.refresh(RefreshScope::CurrentView);
     }
 
-    fn toggle_worked_range(&mut self) {
-        self.worked_range = match self.worked_range {
-            WorkedRange::Today => WorkedRange::Week,
-            WorkedRange::Week => WorkedRange::Today,
+    /// Shows the [`db::IntervalError`] behind `err` in a popup. Returns false for other errors,
+    /// and while another popup is open, which the caller reports itself.
+    fn show_interval_error(&mut self, err: &anyhow::Error, outcome: &str) -> bool {
+        let Some(error) = err.downcast_ref::<db::IntervalError>() else {
+            return false;
         };
-        if self.view == AppView::WorkedProjects {
-            self.load_worked_projects();
+        if self.popup.is_some() {
+            return false;
         }
+        self.popup = Some(ActivePopup::IntervalError(IntervalErrorPopup {
+            error: *error,
+            outcome: outcome.to_string(),
+        }));
+        true
     }
 
-    fn toggle_timeline_range(&mut self) {
-        self.timeline_range = match self.timeline_range {
-            TimelineRange::Day => TimelineRange::Week,
-            TimelineRange::Week => TimelineRange::Day,
+    /// Shows a warning about an interval that was stored anyway, in a popup unless another one is
+    /// open.
+    fn warn_interval(&mut self, warning: Option<db::IntervalError>, outcome: &str) {
+        let Some(warning) = warning else {
+            return;
         };
-        if self.view == AppView::Timeline {
-            self.load_timeline();
+        if self.popup.is_some() {
+            self.status = Some(warning.to_string());
+            return;
         }
+        self.popup = Some(ActivePopup::IntervalError(IntervalErrorPopup {
+            error: warning,
+            outcome: outcome.to_string(),
+        }));
     }
 
-    pub fn project_summary_for(&self, project: &Project) -> ProjectSummary {
-        project
-            .id
-            .and_then(|id| self.project_summaries.get(&id).copied())
-            .unwrap_or_default()
+    /// Looks for a newer release on a background thread, so a slow network doesn't hold up the
+    /// start. The update popup shows once the answer is in.
+    pub fn start_update_check(&mut self) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            sender
+                .send(crate::updater::check_for_updates().ok().flatten())
+                .ok();
+        });
+        self.update_check = Some(receiver);
     }
 
-    fn refresh_project_summaries(&mut self) {
-        match db::query_tickr(crate::types::TickrQuery::All, &self.db) {
-            Ok(tickrs) => {
-                let mut summaries: HashMap<ProjectId, ProjectSummary> = HashMap::new();
-                for tickr in tickrs {
-                    let entry = summaries
-                        .entry(tickr.project_id)
-                        .or_insert(ProjectSummary::default());
-                    let last_interval = tickr.intervals.last();
-                    let is_running = last_interval
-                        .map(|interval| interval.end_time.is_none())
-                        .unwrap_or(false);
-                    if is_running || tickr.intervals.is_empty() {
-                        entry.open += 1;
-                    } else {
-                        entry.ended += 1;
-                    }
-                    for interval in &tickr.intervals {
-                        if let Some(end_time) = interval.end_time {
-                            let seconds = end_time
-                                .signed_duration_since(interval.start_time)
-                                .num_seconds();
-                            if seconds > 0 {
-                                entry.total_seconds += seconds;
-                            }
-                        }
-                    }
-                }
-                self.project_summaries = summaries;
-            }
-            Err(err) => {
-                self.status = Some(format!("Failed to load project summaries: {err}"));
-            }
+    fn check_update(&mut self) {
+        let Some(receiver) = &self.update_check else {
+            return;
+        };
+        let new_version = match receiver.try_recv() {
+            Ok(new_version) => new_version,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => None,
+        };
+        self.update_check = None;
+        let Some(new_version) = new_version else {
+            return;
+        };
+        // A popup the user is busy with stays open.
+        if self.popup.is_none() {
+            self.show_update_popup(new_version);
+        } else {
+            self.status = Some(format!(
+                "Tickr {new_version} is available, start Tickr again to update."
+            ));
         }
     }
 
     pub fn show_update_popup(&mut self, new_version: String) {
-        self.update_popup = Some(UpdatePopup { new_version });
+        self.popup = Some(ActivePopup::Update(UpdatePopup { new_version }));
     }
 
     fn apply_update_popup(&mut self) {
-        self.update_popup = None;
+        self.popup = None;
         self.pending_update = true;
         self.running = false;
     }
 }
 
+/// Parses a date of the range popup relative to `today`.
+fn parse_range_date(input: &str, today: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+    let input = input.trim().to_lowercase();
+    match input.as_str() {
+        "" | "today" => return Some(today),
+        "yesterday" => return today.pred_opt(),
+        _ => {}
+    }
+    if let Some(days) = input.strip_prefix('-')
+        && let Ok(days) = days.parse::<i64>()
+    {
+        return Some(today - chrono::Duration::days(days));
+    }
+    chrono::NaiveDate::parse_from_str(&input, "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(
+                &format!("{}-{input}", today.format("%Y")),
+                "%Y-%m-%d",
+            )
+            .ok()
+        })
+}
+
 fn normalize_hex_color(value: &str) -> Option<String> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
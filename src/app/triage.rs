@@ -0,0 +1,119 @@
+/// Step-by-step sorting of the tasks collected in the inbox project.
+use crate::types::Tickr;
+
+use super::state::{CategoryOption, ProjectOption};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriageField {
+    Project,
+    Category,
+    Estimate,
+}
+
+impl TriageField {
+    pub fn next(self) -> Self {
+        match self {
+            TriageField::Project => TriageField::Category,
+            TriageField::Category => TriageField::Estimate,
+            TriageField::Estimate => TriageField::Project,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            TriageField::Project => TriageField::Estimate,
+            TriageField::Category => TriageField::Project,
+            TriageField::Estimate => TriageField::Category,
+        }
+    }
+}
+
+/// Shows one inbox task at a time with the project, category and estimate to give it.
+#[derive(Clone, Debug)]
+pub struct TriagePopup {
+    /// Inbox tasks still to sort.
+    pub tickrs: Vec<Tickr>,
+    pub index: usize,
+    /// Every project except the inbox.
+    pub projects: Vec<ProjectOption>,
+    pub project_index: usize,
+    /// Categories with "none" first.
+    pub categories: Vec<CategoryOption>,
+    pub category_index: usize,
+    pub estimate: String,
+    pub field: TriageField,
+    pub error: Option<String>,
+}
+
+impl TriagePopup {
+    pub fn current(&self) -> Option<&Tickr> {
+        self.tickrs.get(self.index)
+    }
+
+    /// Shows task `index`, prefilled with its category and estimate.
+    pub fn show(&mut self, index: usize) {
+        self.index = if self.tickrs.is_empty() {
+            0
+        } else {
+            index % self.tickrs.len()
+        };
+        self.error = None;
+        let Some(tickr) = self.tickrs.get(self.index) else {
+            return;
+        };
+        self.category_index = self
+            .categories
+            .iter()
+            .position(|option| option.id == tickr.category_id)
+            .unwrap_or(0);
+        self.estimate = tickr
+            .estimate_minutes
+            .map(format_estimate)
+            .unwrap_or_default();
+    }
+
+    pub fn cycle_option(&mut self, forward: bool) {
+        let (index, len) = match self.field {
+            TriageField::Project => (&mut self.project_index, self.projects.len()),
+            TriageField::Category => (&mut self.category_index, self.categories.len()),
+            TriageField::Estimate => return,
+        };
+        if len == 0 {
+            return;
+        }
+        *index = if forward {
+            (*index + 1) % len
+        } else {
+            (*index + len - 1) % len
+        };
+    }
+}
+
+/// Parses an estimate such as `90`, `45m`, `2h`, `1h30` or `1.5h` into minutes.
+pub fn parse_estimate(input: &str) -> Option<u32> {
+    let input = input.trim().to_lowercase().replace(' ', "");
+    if input.is_empty() {
+        return None;
+    }
+    if let Some((hours, minutes)) = input.split_once('h') {
+        let hours: f64 = hours.parse().ok()?;
+        let minutes: u32 = match minutes.trim_end_matches('m') {
+            "" => 0,
+            minutes => minutes.parse().ok()?,
+        };
+        if !hours.is_finite() || hours < 0.0 {
+            return None;
+        }
+        return Some((hours * 60.0).round() as u32 + minutes);
+    }
+    input.trim_end_matches('m').parse().ok()
+}
+
+/// Formats minutes the way [`parse_estimate`] reads them, e.g. `1h30m`.
+pub fn format_estimate(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h{minutes}m"),
+    }
+}
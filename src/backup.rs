@@ -0,0 +1,102 @@
+//! Copies of the database taken before commands that change a lot at once: imports, syncs,
+//! `doctor` repairs and schema upgrades. They go to a `backups` directory next to the database,
+//! at most one every few minutes, and only the newest ones are kept.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use rusqlite::Connection;
+
+/// A backup this recent covers the next command too, e.g. several imports in a row.
+const MIN_GAP: Duration = Duration::from_secs(10 * 60);
+/// Older backups of the same database are removed.
+const KEEP: usize = 20;
+
+pub enum Backup {
+    Created(PathBuf),
+    /// Taken less than `MIN_GAP` ago, with its age.
+    Recent(PathBuf, Duration),
+}
+
+impl Backup {
+    /// The line that tells where the backup is.
+    pub fn describe(&self) -> String {
+        match self {
+            Backup::Created(path) => format!("Backed up the database to {}", path.display()),
+            Backup::Recent(path, age) => {
+                let taken = Local::now() - chrono::Duration::from_std(*age).unwrap_or_default();
+                format!(
+                    "The database was backed up at {} to {}",
+                    taken.format("%H:%M"),
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+/// Backs up the database behind `conn` before `reason` (a short word for the file name), unless
+/// one was taken in the last minutes. `None` for in-memory databases.
+pub fn before(reason: &str, conn: &Connection) -> Result<Option<Backup>> {
+    let Some(db_path) = conn.path().filter(|path| !path.is_empty()).map(Path::new) else {
+        return Ok(None);
+    };
+    let Some(stem) = db_path.file_stem().map(|stem| stem.to_string_lossy()) else {
+        return Ok(None);
+    };
+    let dir = db_path.parent().unwrap_or(Path::new(".")).join("backups");
+    let prefix = format!("{stem}-");
+
+    let mut backups = existing(&dir, &prefix);
+    if let Some(newest) = backups.last()
+        && let Ok(age) = std::fs::metadata(newest)
+            .and_then(|meta| meta.modified())
+            .map(|modified| {
+                SystemTime::now()
+                    .duration_since(modified)
+                    .unwrap_or_default()
+            })
+        && age < MIN_GAP
+    {
+        return Ok(Some(Backup::Recent(newest.clone(), age)));
+    }
+
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Cannot create the backup directory {}", dir.display()))?;
+    let path = dir.join(format!(
+        "{prefix}{}-{reason}.db",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    // Unlike a file copy, this gives a consistent database while other processes write.
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])
+        .with_context(|| format!("Cannot back up the database to {}", path.display()))?;
+    backups.push(path.clone());
+
+    let excess = backups.len().saturating_sub(KEEP);
+    for old in &backups[..excess] {
+        std::fs::remove_file(old).ok();
+    }
+    Ok(Some(Backup::Created(path)))
+}
+
+/// Backups of one database, oldest first; the time in their names sorts them.
+fn existing(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "db")
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.strip_prefix(prefix))
+                    .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .collect();
+    backups.sort();
+    backups
+}
@@ -0,0 +1,259 @@
+/// Scripted data entry for `tickr batch`.
+///
+/// A script holds one operation per line, with shell-style quoting:
+///
+/// ```text
+/// # comments and blank lines are ignored
+/// project add "Client A"
+/// category add Meetings "#3366ff"
+/// task add "Client A" "Weekly sync" --category Meetings
+/// interval add "Client A" "Weekly sync" "2026-02-02 09:00" "2026-02-02 09:45"
+/// ```
+///
+/// Colors have to be quoted or written without `#`, an unquoted `#` starts a comment.
+/// The whole script runs in one transaction, so a failing line leaves the database untouched.
+use std::io::BufRead;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Local, NaiveTime};
+use rusqlite::Connection;
+
+use crate::{db, types};
+
+#[derive(Debug)]
+enum Operation {
+    Project {
+        name: String,
+    },
+    Category {
+        name: String,
+        color: Option<String>,
+    },
+    Task {
+        project: String,
+        description: String,
+        category: Option<String>,
+    },
+    Interval {
+        project: String,
+        task: String,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct BatchSummary {
+    pub projects: usize,
+    pub categories: usize,
+    pub tasks: usize,
+    pub intervals: usize,
+    /// Projects, categories and tasks that already existed.
+    pub existing: usize,
+}
+
+/// Parses the whole script, then executes it in a single transaction.
+/// With `dry_run` the transaction is rolled back after all operations succeeded.
+pub fn run(input: impl BufRead, dry_run: bool, conn: &Connection) -> Result<BatchSummary> {
+    let mut operations = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        let line_no = index + 1;
+        if let Some(operation) =
+            parse_line(&line).with_context(|| format!("line {line_no}: {}", line.trim()))?
+        {
+            operations.push((line_no, operation));
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let mut summary = BatchSummary::default();
+    for (line_no, operation) in operations {
+        execute(operation, &mut summary, &tx).with_context(|| format!("line {line_no}"))?;
+    }
+    if !dry_run {
+        tx.commit()?;
+    }
+    Ok(summary)
+}
+
+fn parse_line(line: &str) -> Result<Option<Operation>> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return Ok(None);
+    }
+    let words = shlex::split(trimmed).ok_or_else(|| anyhow!("unbalanced quotes"))?;
+    let (kind, args) = match words.as_slice() {
+        [kind, action, args @ ..] if action == "add" => (kind.as_str(), args),
+        _ => bail!("expected '<project|category|task|interval> add ...'"),
+    };
+
+    let operation = match (kind, args) {
+        ("project", [name]) => Operation::Project { name: name.clone() },
+        ("category", [name]) => Operation::Category {
+            name: name.clone(),
+            color: None,
+        },
+        ("category", [name, color]) => {
+            let color = format!("#{}", color.trim_start_matches('#'));
+            if !crate::color::is_valid_hex(&color) {
+                bail!("invalid color '{color}', expected #RRGGBB");
+            }
+            Operation::Category {
+                name: name.clone(),
+                color: Some(color),
+            }
+        }
+        ("task", [project, description]) => Operation::Task {
+            project: project.clone(),
+            description: description.clone(),
+            category: None,
+        },
+        ("task", [project, description, flag, category]) if flag == "--category" => {
+            Operation::Task {
+                project: project.clone(),
+                description: description.clone(),
+                category: Some(category.clone()),
+            }
+        }
+        ("interval", [project, task, start, end]) => {
+            let start = parse_datetime(start)?;
+            let end = parse_datetime(end)?;
+            if end <= start {
+                bail!("interval ends before it starts");
+            }
+            Operation::Interval {
+                project: project.clone(),
+                task: task.clone(),
+                start,
+                end,
+            }
+        }
+        ("project", _) => bail!("usage: project add <name>"),
+        ("category", _) => bail!("usage: category add <name> [#RRGGBB]"),
+        ("task", _) => bail!("usage: task add <project> <description> [--category <name>]"),
+        ("interval", _) => bail!("usage: interval add <project> <task> <start> <end>"),
+        (other, _) => bail!("unknown record type '{other}'"),
+    };
+    Ok(Some(operation))
+}
+
+fn execute(operation: Operation, summary: &mut BatchSummary, conn: &Connection) -> Result<()> {
+    match operation {
+        Operation::Project { name } => {
+            if db::check_project_exists(&name, conn)? {
+                summary.existing += 1;
+                return Ok(());
+            }
+            db::create_project(
+                types::Project {
+                    id: None,
+                    name,
+                    created_at: Local::now(),
+                    archived: false,
+                    parent_id: None,
+                    client_id: None,
+                },
+                conn,
+            )?;
+            summary.projects += 1;
+        }
+        Operation::Category { name, color } => {
+            if db::query_category_id(&name, conn)?.is_some() {
+                summary.existing += 1;
+                return Ok(());
+            }
+            let color = color.unwrap_or_else(crate::color::random_color);
+            db::create_category(name, color, conn)?;
+            summary.categories += 1;
+        }
+        Operation::Task {
+            project,
+            description,
+            category,
+        } => {
+            let project_id = find_project(&project, conn)?;
+            if find_task(project_id, &description, conn)?.is_some() {
+                summary.existing += 1;
+                return Ok(());
+            }
+            let category_id = match category {
+                Some(name) => Some(db::query_category_id(&name, conn)?.ok_or_else(|| {
+                    anyhow!("category '{name}' not found, add it with 'category add' first")
+                })?),
+                None => None,
+            };
+            db::create_tickr(
+                types::Tickr {
+                    id: None,
+                    project_id,
+                    description,
+                    category_id,
+                    billable: true,
+                    done: false,
+                    blocked_by: None,
+                    notes: None,
+                    estimate_minutes: None,
+                    archived: false,
+                    issue: None,
+                    intervals: Vec::new(),
+                },
+                conn,
+            )?;
+            summary.tasks += 1;
+        }
+        Operation::Interval {
+            project,
+            task,
+            start,
+            end,
+        } => {
+            let project_id = find_project(&project, conn)?;
+            let tickr_id = find_task(project_id, &task, conn)?.ok_or_else(|| {
+                anyhow!("task '{task}' not found in '{project}', add it with 'task add' first")
+            })?;
+            db::create_interval(
+                types::Interval {
+                    id: None,
+                    entry_id: tickr_id,
+                    start_time: start,
+                    end_time: Some(end),
+                    notes: None,
+                },
+                conn,
+            )?;
+            summary.intervals += 1;
+        }
+    }
+    Ok(())
+}
+
+fn find_project(name: &str, conn: &Connection) -> Result<types::ProjectId> {
+    db::query_project(types::ProjectQuery::ByName(name.to_string()), conn)?
+        .into_iter()
+        .chain(db::query_project_by_alias(name, conn)?)
+        .find_map(|project| project.id)
+        .ok_or_else(|| anyhow!("project '{name}' not found, add it with 'project add' first"))
+}
+
+fn find_task(
+    project_id: types::ProjectId,
+    description: &str,
+    conn: &Connection,
+) -> Result<Option<types::TickrId>> {
+    Ok(
+        db::query_tickr(types::TickrQuery::ByProjectId(project_id), conn)?
+            .into_iter()
+            .find(|tickr| tickr.description == description)
+            .and_then(|tickr| tickr.id),
+    )
+}
+
+/// Accepts RFC 3339 timestamps as well as local "YYYY-MM-DD HH:MM" values.
+fn parse_datetime(value: &str) -> Result<DateTime<Local>> {
+    if NaiveTime::parse_from_str(value.trim(), "%H:%M").is_ok() {
+        bail!("'{value}' has no date, expected 'YYYY-MM-DD HH:MM' or RFC 3339");
+    }
+    crate::ui::parse_local_datetime(value, Local::now().date_naive())
+        .ok_or_else(|| anyhow!("invalid time '{value}', expected 'YYYY-MM-DD HH:MM' or RFC 3339"))
+}
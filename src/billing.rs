@@ -0,0 +1,196 @@
+/// Hourly rates and rounding of billable time.
+///
+/// Rules come from the `[billing]` table of the config file and can be overridden per project
+/// with `tickr project set`. Rounding is applied to every interval on its own.
+use std::collections::BTreeMap;
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    #[default]
+    Up,
+    Nearest,
+    Down,
+}
+
+impl RoundingMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoundingMode::Up => "up",
+            RoundingMode::Nearest => "nearest",
+            RoundingMode::Down => "down",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::from_str(value, true).ok()
+    }
+}
+
+/// ISO 4217 currency code such as `EUR`, stored as three uppercase letters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub fn parse(value: &str) -> Option<Self> {
+        let code: [u8; 3] = value
+            .trim()
+            .to_ascii_uppercase()
+            .as_bytes()
+            .try_into()
+            .ok()?;
+        code.iter()
+            .all(u8::is_ascii_uppercase)
+            .then_some(Currency(code))
+    }
+
+    /// `parse` for clap arguments.
+    pub fn parse_arg(value: &str) -> Result<Self, String> {
+        Self::parse(value).ok_or_else(|| format!("'{value}' is not a three-letter currency code"))
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).unwrap_or("???")
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse_arg(&value)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.to_string()
+    }
+}
+
+/// Earnings summed per currency, so amounts in different currencies are never added up.
+/// Amounts without a currency are kept apart as well.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Amounts(BTreeMap<Option<Currency>, f64>);
+
+impl Amounts {
+    pub fn add(&mut self, currency: Option<Currency>, amount: f64) {
+        *self.0.entry(currency).or_insert(0.0) += amount;
+    }
+
+    pub fn merge(&mut self, other: &Amounts) {
+        for (currency, amount) in &other.0 {
+            self.add(*currency, *amount);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Every amount multiplied by `factor`, e.g. to extrapolate earnings.
+    pub fn scaled(&self, factor: f64) -> Amounts {
+        Amounts(
+            self.0
+                .iter()
+                .map(|(currency, amount)| (*currency, amount * factor))
+                .collect(),
+        )
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Option<Currency>, f64)> + '_ {
+        self.0.iter().map(|(currency, amount)| (*currency, *amount))
+    }
+}
+
+impl fmt::Display for Amounts {
+    /// `1250.00 EUR + 80.00 USD`, amounts without a currency as bare numbers.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (currency, amount)) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" + ")?;
+            }
+            match currency {
+                Some(currency) => write!(f, "{amount:.2} {currency}")?,
+                None => write!(f, "{amount:.2}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Billing settings where every field is optional, so project rules can fall back to the
+/// global ones field by field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BillingRules {
+    pub hourly_rate: Option<f64>,
+    /// Size of the rounding step in minutes; 0 turns rounding off.
+    pub rounding_minutes: Option<u32>,
+    pub rounding: Option<RoundingMode>,
+    /// Currency of the hourly rate, e.g. `EUR`.
+    pub currency: Option<Currency>,
+}
+
+impl BillingRules {
+    /// Fills the fields not set in `self` from `fallback`.
+    pub fn or(self, fallback: &BillingRules) -> BillingRules {
+        BillingRules {
+            hourly_rate: self.hourly_rate.or(fallback.hourly_rate),
+            rounding_minutes: self.rounding_minutes.or(fallback.rounding_minutes),
+            rounding: self.rounding.or(fallback.rounding),
+            currency: self.currency.or(fallback.currency),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == BillingRules::default()
+    }
+
+    /// Rounds a billable duration to the configured step.
+    pub fn round(&self, seconds: i64) -> i64 {
+        let step = i64::from(self.rounding_minutes.unwrap_or(0)) * 60;
+        if step == 0 || seconds <= 0 {
+            return seconds;
+        }
+        let steps = match self.rounding.unwrap_or_default() {
+            RoundingMode::Up => (seconds + step - 1) / step,
+            RoundingMode::Nearest => (seconds + step / 2) / step,
+            RoundingMode::Down => seconds / step,
+        };
+        steps * step
+    }
+
+    /// Amount for `billed_seconds`, `None` without an hourly rate.
+    pub fn amount(&self, billed_seconds: i64) -> Option<f64> {
+        self.hourly_rate
+            .map(|rate| rate * billed_seconds as f64 / 3600.0)
+    }
+
+    pub fn describe(&self) -> String {
+        let rate = match (self.hourly_rate, self.currency) {
+            (Some(rate), Some(currency)) => format!("{rate:.2} {currency}/h"),
+            (Some(rate), None) => format!("{rate:.2}/h"),
+            (None, _) => "no rate".to_string(),
+        };
+        let rounding = match self.rounding_minutes {
+            Some(minutes) if minutes > 0 => format!(
+                "rounded {} to {minutes} min",
+                self.rounding.unwrap_or_default().as_str()
+            ),
+            _ => "not rounded".to_string(),
+        };
+        format!("{rate}, {rounding}")
+    }
+}
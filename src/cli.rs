@@ -1,10 +1,23 @@
 /// CLI argument parsing and command handling.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, BufRead, IsTerminal, Write};
+
 use anyhow::Result;
-use chrono::{DateTime, Local};
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use rusqlite::Connection;
 
-use crate::{db, types};
+use crate::billing::{Amounts, BillingRules, Currency, RoundingMode};
+use crate::config::{self, Config};
+use crate::export::svg::TimelineColor;
+use crate::export::{self, ExportFormat, ExportRecord};
+use crate::import::{self, ImportSource};
+use crate::picker;
+use crate::progress::Progress;
+use crate::status::{self, StatusOutput};
+use crate::table::{self, Cell, Paint, Table};
+use crate::types::{self, Tickr};
+use crate::{daemon, db, debug, doctor, goal, serve, sync};
 
 #[derive(Parser)]
 #[command(
@@ -13,6 +26,27 @@ use crate::{db, types};
     about = "Tickr - A terminal-based time tracker"
 )]
 pub struct Cli {
+    /// Path to the SQLite database (overrides TICKR_DB and the default location).
+    #[arg(long, global = true, value_name = "PATH")]
+    pub db: Option<String>,
+    /// Use the database of a profile from the config file's `[profiles]` tables.
+    #[arg(long, global = true, value_name = "NAME", conflicts_with = "db")]
+    pub profile: Option<String>,
+    /// Use a line-based prompt instead of the full-screen interface (screen reader friendly).
+    #[arg(long)]
+    pub no_tui: bool,
+    /// Disable the spinner and draw the interface with ASCII characters only.
+    #[arg(long)]
+    pub plain: bool,
+    /// Length of a pomodoro work block in minutes.
+    #[arg(long, value_name = "MINUTES", default_value_t = 25)]
+    pub pomodoro_work: u32,
+    /// Length of a pomodoro break in minutes.
+    #[arg(long, value_name = "MINUTES", default_value_t = 5)]
+    pub pomodoro_break: u32,
+    /// Ask what to do with idle time after this many minutes without a keypress (0 disables).
+    #[arg(long, value_name = "MINUTES", default_value_t = 10)]
+    pub idle_minutes: u32,
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -23,19 +57,467 @@ pub enum Command {
         #[command(subcommand)]
         command: ProjectCommand,
     },
+    /// Customers that projects are grouped under, for per-client reports and invoices.
+    Client {
+        #[command(subcommand)]
+        command: ClientCommand,
+    },
     Task {
         #[command(subcommand)]
         command: TaskCommand,
     },
+    /// Tasks created in one step, e.g. "Daily standup", optionally every workday on their own.
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+    /// Pick one of the recent tasks with a fuzzy filter and switch to it.
+    Switch,
+    /// Start the task that was stopped last again.
+    Resume,
     Category {
         name: String,
         color_opt: Option<String>,
     },
+    /// Lock reviewed weeks so their intervals can't change, or unlock them again.
+    Week {
+        #[command(subcommand)]
+        command: WeekCommand,
+    },
+    /// Freeze the totals per day, project and category of a range, e.g. the numbers reported
+    /// for a quarter, so later edits and deletions of intervals don't change them.
+    Snapshot {
+        #[command(subcommand)]
+        command: SnapshotCommand,
+    },
+    /// Set up the config file.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Print the currently running task.
+    Status {
+        /// Print the status as a JSON object.
+        #[arg(long, conflicts_with_all = ["format", "waybar"])]
+        json: bool,
+        /// Print a Waybar custom module object (text, tooltip, class). --format sets the text.
+        #[arg(long)]
+        waybar: bool,
+        /// Custom output, e.g. "{project} {task} {elapsed}".
+        /// Placeholders: {project}, {task}, {category}, {elapsed}, {started}.
+        #[arg(long)]
+        format: Option<String>,
+        /// Read the status cached on the last start/stop instead of the database, for status
+        /// bars that poll every few seconds.
+        #[arg(long)]
+        fast: bool,
+        /// Keep running and print the status again whenever it changes, for status bars that
+        /// read a stream of lines.
+        #[arg(long, conflicts_with = "fast")]
+        watch: bool,
+    },
+    /// Run a script of project/category/task/interval additions in one transaction.
+    Batch {
+        /// Script to read, stdin when omitted or "-".
+        file: Option<String>,
+        /// Check the script against the database without saving anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export tasks and intervals as CSV or JSON.
+    Export {
+        /// Options saved in an `[export.profiles.<name>]` table of the config file, used where
+        /// none are given here.
+        profile: Option<String>,
+        /// CSV unless the profile says otherwise.
+        #[arg(long, value_enum)]
+        format: Option<ExportFormat>,
+        /// Write a zip with the JSON export, a weekly report and an invoice draft to the
+        /// --output file. Covers last week unless --from is given.
+        #[arg(long, conflicts_with = "format")]
+        bundle: bool,
+        /// Write to this file instead of stdout.
+        #[arg(short = 'o', long)]
+        output: Option<String>,
+        /// Only export intervals from this day on (YYYY-MM-DD).
+        #[arg(long)]
+        from: Option<String>,
+        /// Only export intervals up to this day, inclusive (YYYY-MM-DD).
+        #[arg(long)]
+        to: Option<String>,
+        /// Only export intervals from locked (approved) weeks.
+        #[arg(long)]
+        approved: bool,
+        /// Only export the projects of this client, e.g. for its invoice.
+        #[arg(long)]
+        client: Option<String>,
+    },
+    /// Import a Tickr CSV/JSON export or another tool's data.
+    Import(ImportArgs),
+    /// List intervals added, removed or changed between two CSV/JSON exports, or between an
+    /// export and the database.
+    Diff {
+        /// Earlier export.
+        old: String,
+        /// Later export, the database when omitted.
+        new: Option<String>,
+        /// Only compare intervals from this day on (YYYY-MM-DD). Against the database, the days
+        /// covered by the export are compared by default.
+        #[arg(long)]
+        from: Option<String>,
+        /// Only compare intervals up to this day, inclusive (YYYY-MM-DD).
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Print tracked time totals grouped by project, category or task.
+    Report {
+        /// What to group the totals by.
+        #[arg(long, value_enum, default_value_t = ReportBy::Project)]
+        by: ReportBy,
+        /// How to print the report.
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+        format: ReportFormat,
+        /// First day of the report (YYYY-MM-DD), defaults to six days ago.
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day of the report, inclusive (YYYY-MM-DD), defaults to today.
+        #[arg(long)]
+        to: Option<String>,
+        /// Only count the projects of this client.
+        #[arg(long)]
+        client: Option<String>,
+    },
+    /// Draw the timeline of a day or week as an SVG image, e.g. for retros or reports.
+    Timeline {
+        /// File to write the SVG to.
+        #[arg(long, value_name = "FILE")]
+        svg: String,
+        /// First day (YYYY-MM-DD), defaults to the start of this week.
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day, inclusive (YYYY-MM-DD), defaults to six days after --from.
+        #[arg(long)]
+        to: Option<String>,
+        /// What the blocks are colored by.
+        #[arg(long, value_enum, default_value_t = TimelineColor::Project)]
+        color_by: TimelineColor,
+    },
+    /// Help with bug reports.
+    Debug {
+        #[command(subcommand)]
+        command: DebugCommand,
+    },
+    /// Check the intervals for overlaps, several running at once, ends before starts and
+    /// timestamps that can't be read. Offers to repair each problem when run on a terminal.
+    Doctor {
+        /// Repair every problem without asking.
+        #[arg(long)]
+        fix: bool,
+        /// Also offer to split intervals that span midnight into one per day, as the
+        /// `split_at_midnight` setting does.
+        #[arg(long)]
+        split_midnight: bool,
+    },
+    /// Run headless and suggest logging untracked active time.
+    Daemon {
+        /// Minutes without keyboard/mouse input after which you count as idle.
+        #[arg(long, default_value_t = 5)]
+        idle_minutes: u64,
+        /// Shortest untracked active stretch (minutes) worth a suggestion.
+        #[arg(long, default_value_t = 15)]
+        min_block_minutes: u64,
+        /// Seconds between activity checks.
+        #[arg(long, default_value_t = 30)]
+        poll_seconds: u64,
+    },
+    /// Serve projects, tasks, intervals and reports as JSON over HTTP, and start or stop tasks.
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to listen on; use 0.0.0.0 to reach the API from other devices.
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Require this token as `Authorization: Bearer <token>` on every request.
+        #[arg(long)]
+        token: String,
+    },
+    /// Exchange projects, categories, tasks and intervals with the `tickr serve` of another
+    /// database, e.g. on a home server. Changes made on both sides since the last sync are merged.
+    Sync {
+        /// Address of the server, e.g. http://192.168.1.5:8080, or https:// behind a TLS proxy.
+        #[arg(long)]
+        server: String,
+        /// Token the server was started with.
+        #[arg(long)]
+        token: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    pub file: String,
+    /// Format of the file, detected from the extension and contents when omitted.
+    #[arg(long = "from", value_enum)]
+    pub source: Option<ImportSource>,
+    /// Project that receives ActivityWatch intervals (created if missing).
+    #[arg(short = 'p', long)]
+    pub project: Option<String>,
+    /// ActivityWatch: only import activity from this day (YYYY-MM-DD).
+    #[arg(long)]
+    pub date: Option<String>,
+    /// ActivityWatch: merge activity of the same app separated by at most this many minutes.
+    #[arg(long, default_value_t = 5)]
+    pub gap: i64,
+    /// ActivityWatch: drop suggestions shorter than this many minutes.
+    #[arg(long, default_value_t = 10)]
+    pub min: i64,
+    /// ActivityWatch: accept every suggestion without prompting.
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+    /// How to treat intervals that duplicate existing ones of the same task.
+    #[arg(long, value_enum, default_value_t = import::DuplicatePolicy::Skip)]
+    pub duplicates: import::DuplicatePolicy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportBy {
+    Project,
+    Client,
+    Category,
+    Task,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Totals with billing, as an aligned table.
+    Table,
+    /// Timesheet with a column per day, to paste into wikis or standup notes.
+    Markdown,
+}
+
+impl Command {
+    /// Whether the command changes the database, which a read-only database refuses.
+    pub fn writes(&self) -> bool {
+        !matches!(
+            self,
+            Command::Config { .. }
+                | Command::Status { .. }
+                | Command::Export { .. }
+                | Command::Diff { .. }
+                | Command::Report { .. }
+                | Command::Timeline { .. }
+                | Command::Debug { .. }
+                | Command::Doctor { fix: false, .. }
+                | Command::Daemon { .. }
+                | Command::Serve { .. }
+                | Command::Week {
+                    command: WeekCommand::List
+                }
+                | Command::Client {
+                    command: ClientCommand::List
+                }
+                | Command::Template {
+                    command: TemplateCommand::List
+                }
+                | Command::Snapshot {
+                    command: SnapshotCommand::List | SnapshotCommand::Open { .. }
+                }
+                | Command::Project {
+                    command: ProjectCommand::Alias {
+                        project: None,
+                        remove: false,
+                        ..
+                    }
+                }
+                | Command::Project {
+                    command: ProjectCommand::Goal {
+                        hours: None,
+                        clear: false,
+                        ..
+                    }
+                }
+        )
+    }
 }
 
 #[derive(Subcommand, Debug)]
 pub enum ProjectCommand {
-    Add { name: String },
+    #[command(visible_alias = "new")]
+    Add {
+        name: String,
+        /// Start with the tasks, categories and billing of a `[project_templates.<name>]` table
+        /// from the config file.
+        #[arg(long)]
+        template: Option<String>,
+    },
+    /// Override the hourly rate and rounding of the `[billing]` config for one project.
+    Set {
+        name: String,
+        /// Hourly rate for billable time.
+        #[arg(long)]
+        rate: Option<f64>,
+        /// Round every billable interval to this many minutes (0 disables rounding).
+        #[arg(long, value_name = "MINUTES")]
+        rounding: Option<u32>,
+        /// Direction of the rounding.
+        #[arg(long, value_enum)]
+        rounding_mode: Option<RoundingMode>,
+        /// Currency of the rate, e.g. EUR.
+        #[arg(long, value_parser = Currency::parse_arg)]
+        currency: Option<Currency>,
+        /// Remove all overrides, so the config file applies again.
+        #[arg(long, conflicts_with_all = ["rate", "rounding", "rounding_mode", "currency"])]
+        reset: bool,
+    },
+    /// Group a project under PARENT as a sub-project, or make it top-level again.
+    Parent {
+        name: String,
+        /// Parent project, omit to remove the current parent.
+        parent: Option<String>,
+    },
+    /// Give a project a short name that works wherever a project name does, e.g.
+    /// `project alias aw "Acme Website"`. Lists the aliases without arguments and shows the
+    /// project of an alias without a project.
+    Alias {
+        alias: Option<String>,
+        /// Project the alias names.
+        #[arg(requires = "alias")]
+        project: Option<String>,
+        /// Remove the alias instead.
+        #[arg(long, requires = "alias", conflicts_with = "project")]
+        remove: bool,
+    },
+    /// Put a project under CLIENT, created when it doesn't exist yet, or take it away from its
+    /// client.
+    Client {
+        name: String,
+        /// Client of the project, omit to remove the current client.
+        client: Option<String>,
+    },
+    /// Set the hours a project should get each week, e.g. `project goal "Acme Website" 20`.
+    /// Lists the goals and their progress without arguments and shows one project's without
+    /// hours.
+    Goal {
+        project: Option<String>,
+        /// Hours like 20, 7.5 or 1h30m.
+        #[arg(requires = "project")]
+        hours: Option<String>,
+        /// The hours are for all time rather than each week.
+        #[arg(long, requires = "hours")]
+        total: bool,
+        /// Remove the goal instead.
+        #[arg(long, requires = "project", conflicts_with = "hours")]
+        clear: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ClientCommand {
+    Add {
+        name: String,
+    },
+    /// List the clients with their projects.
+    List,
+    Rename {
+        name: String,
+        new_name: String,
+    },
+    /// Delete a client. Its projects stay, without a client.
+    Delete {
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Write a config file with every setting commented out at its default.
+    Init {
+        /// Replace an existing config file.
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DebugCommand {
+    /// Zip platform info, the config with personal values redacted and database statistics
+    /// (counts and schema, no contents) to attach to a bug report.
+    Bundle {
+        /// Zip file to write, tickr-debug-<date>.zip by default.
+        #[arg(short = 'o', long)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WeekCommand {
+    /// Lock the week containing DATE (YYYY-MM-DD), last week when omitted.
+    Lock { date: Option<String> },
+    /// Unlock the week containing DATE (YYYY-MM-DD) so it can be changed again.
+    Unlock { date: String },
+    /// List the locked weeks.
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Add a template for tasks named NAME in PROJECT.
+    Add {
+        project: String,
+        name: String,
+        #[arg(short = 'c', long = "category")]
+        category: Option<String>,
+        /// Tasks from it aren't billable to the client.
+        #[arg(long)]
+        non_billable: bool,
+        /// Create a task from it on its own every Monday to Friday, when the TUI opens or
+        /// `template` runs.
+        #[arg(long)]
+        every_workday: bool,
+    },
+    /// List the templates.
+    List,
+    /// Create a task from a template.
+    Use {
+        name: String,
+        /// Start the new task right away.
+        #[arg(long)]
+        start: bool,
+    },
+    Delete {
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommand {
+    /// Store the totals under NAME. Covers everything tracked up to today unless --from or --to
+    /// are given.
+    Create {
+        name: String,
+        /// First day (YYYY-MM-DD), the first tracked day when omitted.
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day, inclusive (YYYY-MM-DD), defaults to today.
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// List the snapshots with their ranges and totals.
+    List,
+    /// Print the totals of a snapshot as they were when it was created.
+    Open {
+        name: String,
+        /// What to group the totals by.
+        #[arg(long, value_enum, default_value_t = SnapshotBy::Project)]
+        by: SnapshotBy,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SnapshotBy {
+    Project,
+    Category,
+    Day,
 }
 
 #[derive(Subcommand, Debug)]
@@ -49,23 +531,94 @@ pub enum TaskCommand {
         end: Option<String>,
         #[arg(short = 'c', long = "category")]
         category: Option<String>,
+        /// Mark the task as not billable to the client.
+        #[arg(long)]
+        non_billable: bool,
     },
     Switch {
         project: String,
         description: String,
     },
     Start {
+        /// Project of the task; with --from-git, the repository's name when omitted.
+        #[arg(required_unless_present = "from_git")]
+        project: Option<String>,
+        #[arg(required_unless_present = "from_git", conflicts_with = "from_git")]
+        description: Option<String>,
+        /// Name the task after the current git branch (the part after the last '/') and create
+        /// it when it doesn't exist yet.
+        #[arg(long)]
+        from_git: bool,
+    },
+    /// Link a task to an issue, or print the linked issue's address.
+    Issue {
         project: String,
         description: String,
+        /// Issue URL or a reference like owner/repo#123.
+        issue: Option<String>,
+        /// Rename the task to the issue's title.
+        #[arg(long)]
+        title: bool,
+        /// Open the issue in the browser.
+        #[arg(long)]
+        open: bool,
+        /// Remove the link.
+        #[arg(long, conflicts_with_all = ["issue", "title", "open"])]
+        clear: bool,
     },
 }
 
 /// Execute a CLI command (project, task, or category).
-pub fn run(command: Command, conn: &Connection) -> Result<()> {
+pub fn run(command: Command, config: &Config, conn: &Connection) -> Result<()> {
     match command {
         Command::Project {
-            command: ProjectCommand::Add { name },
-        } => handle_project_add(name, conn)?,
+            command: ProjectCommand::Add { name, template },
+        } => match template {
+            Some(template) => handle_project_from_template(name, &template, config, conn)?,
+            None => handle_project_add(name, conn)?,
+        },
+        Command::Project {
+            command:
+                ProjectCommand::Set {
+                    name,
+                    rate,
+                    rounding,
+                    rounding_mode,
+                    currency,
+                    reset,
+                },
+        } => {
+            let overrides = BillingRules {
+                hourly_rate: rate,
+                rounding_minutes: rounding,
+                rounding: rounding_mode,
+                currency,
+            };
+            handle_project_set(name, overrides, reset, &config.billing, conn)?
+        }
+        Command::Project {
+            command: ProjectCommand::Parent { name, parent },
+        } => handle_project_parent(name, parent, conn)?,
+        Command::Project {
+            command: ProjectCommand::Client { name, client },
+        } => handle_project_client(name, client, conn)?,
+        Command::Project {
+            command:
+                ProjectCommand::Alias {
+                    alias,
+                    project,
+                    remove,
+                },
+        } => handle_project_alias(alias, project, remove, conn)?,
+        Command::Project {
+            command:
+                ProjectCommand::Goal {
+                    project,
+                    hours,
+                    total,
+                    clear,
+                },
+        } => handle_project_goal(project, hours, total, clear, config, conn)?,
         Command::Task {
             command:
                 TaskCommand::Add {
@@ -74,8 +627,17 @@ pub fn run(command: Command, conn: &Connection) -> Result<()> {
                     start,
                     end,
                     category,
+                    non_billable,
                 },
-        } => handle_task_add(project, description, start, end, category, conn)?,
+        } => handle_task_add(
+            project,
+            description,
+            start,
+            end,
+            category,
+            !non_billable,
+            conn,
+        )?,
         Command::Task {
             command:
                 TaskCommand::Switch {
@@ -88,9 +650,131 @@ pub fn run(command: Command, conn: &Connection) -> Result<()> {
                 TaskCommand::Start {
                     project,
                     description,
+                    from_git,
                 },
-        } => handle_task_switch(project, description, conn)?, // Starting a task is the same as switching to it if no other is currently running
+        } => match (project, description) {
+            (project, _) if from_git => handle_task_start_from_git(project, conn)?,
+            // Starting a task is the same as switching to it if no other is currently running
+            (Some(project), Some(description)) => handle_task_switch(project, description, conn)?,
+            _ => {}
+        },
+        Command::Task {
+            command:
+                TaskCommand::Issue {
+                    project,
+                    description,
+                    issue,
+                    title,
+                    open,
+                    clear,
+                },
+        } => {
+            let options = IssueOptions { title, open, clear };
+            handle_task_issue(project, description, issue, options, config, conn)?
+        }
+        Command::Client { command } => handle_client(command, conn)?,
+        Command::Template { command } => handle_template(command, conn)?,
+        Command::Switch => handle_switch(config, conn)?,
+        Command::Resume => handle_resume(conn)?,
         Command::Category { name, color_opt } => handle_category_add(name, color_opt, conn)?,
+        Command::Week { command } => handle_week(command, config, conn)?,
+        Command::Snapshot { command } => handle_snapshot(command, conn)?,
+        Command::Config { command } => handle_config(&command)?,
+        Command::Status {
+            json,
+            waybar,
+            format,
+            fast,
+            watch,
+        } => {
+            if fast {
+                // Nothing cached yet, start the cache for the next call.
+                status::write_cache(conn).ok();
+            }
+            let style = StatusStyle {
+                json,
+                waybar,
+                format,
+            };
+            if watch {
+                watch_status(&style, conn)?
+            } else {
+                print_status(status::query_status(conn)?, &style)?
+            }
+        }
+        Command::Batch { file, dry_run } => handle_batch(file, dry_run, conn)?,
+        Command::Export {
+            profile,
+            format,
+            bundle,
+            output,
+            from,
+            to,
+            approved,
+            client,
+        } => {
+            let saved = match profile {
+                Some(name) => export_profile(&name, config)?.clone(),
+                None => config::ExportProfileConfig::default(),
+            };
+            let filter = RecordFilter {
+                approved: approved || saved.approved,
+                client: client.or(saved.client),
+            };
+            let output = output.or(saved.output);
+            if bundle {
+                handle_export_bundle(output, from, to, &filter, config, conn)?
+            } else {
+                let format = format.or(saved.format).unwrap_or(ExportFormat::Csv);
+                handle_export(format, output, from, to, &filter, &config.billing, conn)?
+            }
+        }
+        Command::Import(args) => handle_import(args, conn)?,
+        Command::Diff { old, new, from, to } => {
+            handle_diff(old, new, from, to, &config.billing, conn)?
+        }
+        Command::Report {
+            by,
+            format,
+            from,
+            to,
+            client,
+        } => handle_report(by, format, from, to, client, &config.billing, conn)?,
+        Command::Timeline {
+            svg,
+            from,
+            to,
+            color_by,
+        } => handle_timeline(svg, from, to, color_by, config, conn)?,
+        Command::Debug {
+            command: DebugCommand::Bundle { output },
+        } => handle_debug_bundle(output, conn)?,
+        Command::Doctor {
+            fix,
+            split_midnight,
+        } => handle_doctor(fix, split_midnight || config.split_at_midnight, conn)?,
+        Command::Daemon {
+            idle_minutes,
+            min_block_minutes,
+            poll_seconds,
+        } => daemon::run(
+            daemon::DaemonOptions {
+                poll: std::time::Duration::from_secs(poll_seconds.max(1)),
+                idle_threshold: std::time::Duration::from_secs(idle_minutes * 60),
+                min_block: std::time::Duration::from_secs(min_block_minutes * 60),
+                reminder: config.reminder.clone(),
+            },
+            conn,
+        )?,
+        Command::Serve { port, host, token } => serve::run(
+            serve::ServeOptions { host, port, token },
+            &config.billing,
+            conn,
+        )?,
+        Command::Sync { server, token } => {
+            announce_backup("sync", conn)?;
+            sync::run(&server, token.as_deref(), conn)?
+        }
     }
     Ok(())
 }
@@ -105,128 +789,2037 @@ fn handle_project_add(name: String, conn: &Connection) -> Result<()> {
             id: None,
             name,
             created_at: Local::now(),
+            archived: false,
+            parent_id: None,
+            client_id: None,
         },
         conn,
     )?;
     Ok(())
 }
 
-fn handle_task_add(
-    project: String,
-    description: String,
-    start: Option<String>,
-    end: Option<String>,
-    category: Option<String>,
+fn handle_project_from_template(
+    name: String,
+    template_name: &str,
+    config: &Config,
     conn: &Connection,
 ) -> Result<()> {
-    let projects = db::query_project(types::ProjectQuery::ByName(project.clone()), conn)?;
-    if projects.is_empty() {
-        println!("Project '{project}' not found");
+    let Some(template) = config.project_templates.get(template_name) else {
+        if config.project_templates.is_empty() {
+            println!(
+                "No project template '{template_name}': the config file has no \
+                 [project_templates.{template_name}] table."
+            );
+        } else {
+            let names: Vec<&str> = config
+                .project_templates
+                .keys()
+                .map(String::as_str)
+                .collect();
+            println!(
+                "No project template '{template_name}', the config file has {}.",
+                names.join(", ")
+            );
+        }
         return Ok(());
-    }
-    if projects.len() > 1 {
-        println!("Multiple projects found with the same name, cannot determine which one to use");
+    };
+    if db::check_project_exists(&name, conn)? {
+        println!("Project '{name}' already exists.");
         return Ok(());
     }
-    let project_id = projects[0].id.unwrap();
-
-    let start_time = parse_optional_datetime(start)?;
-    let end_time = parse_optional_datetime(end)?;
-    if start_time.is_none() && end_time.is_some() {
-        println!("End time requires a start time.");
+    if let Some(rate) = template.billing.hourly_rate
+        && (!rate.is_finite() || rate < 0.0)
+    {
+        println!("The rate of template '{template_name}' must be a positive number.");
         return Ok(());
     }
 
-    let category_id = if let Some(cat_name) = category {
-        match db::query_category_id(&cat_name, conn)? {
-            Some(id) => Some(id),
-            None => {
-                println!("Category '{cat_name}' not found, creating it with a random color.");
-                let color = crate::color::random_color();
-                Some(db::create_category(cat_name, color, conn)?)
-            }
-        }
-    } else {
-        None
-    };
-
-    let tickr_id = db::create_tickr(
-        types::Tickr {
+    let tx = conn.unchecked_transaction()?;
+    let project_id = db::create_project(
+        types::Project {
             id: None,
-            project_id,
-            description,
-            category_id,
-            intervals: Vec::new(), // Intervals will be created separately based on start/end times
+            name: name.clone(),
+            created_at: Local::now(),
+            archived: false,
+            parent_id: None,
+            client_id: None,
         },
-        conn,
+        &tx,
     )?;
-    if let Some(start_time) = start_time {
-        db::create_interval(
-            types::Interval {
+    for category in &template.categories {
+        category_or_create(category.clone(), &tx)?;
+    }
+    for task in &template.tasks {
+        let category_id = task
+            .category
+            .clone()
+            .map(|category| category_or_create(category, &tx))
+            .transpose()?;
+        db::create_tickr(
+            Tickr {
                 id: None,
-                entry_id: tickr_id,
-                start_time,
-                end_time,
+                project_id,
+                description: task.name.clone(),
+                category_id,
+                billable: task.billable,
+                done: false,
+                blocked_by: None,
+                notes: None,
+                estimate_minutes: None,
+                archived: false,
+                issue: None,
+                intervals: Vec::new(),
             },
-            conn,
+            &tx,
         )?;
     }
+    if !template.billing.is_empty() {
+        db::set_project_billing(project_id, &template.billing, &tx)?;
+        db::record_project_rate(project_id, None, template.billing.hourly_rate, &tx)?;
+    }
+    tx.commit()?;
+
+    let plural = if template.tasks.len() == 1 { "" } else { "s" };
+    println!(
+        "Created project '{name}' from template '{template_name}' with {} task{plural}.",
+        template.tasks.len()
+    );
+    println!(
+        "{name}: {}",
+        template.billing.or(&config.billing).describe()
+    );
     Ok(())
 }
 
-fn handle_task_switch(project: String, description: String, conn: &Connection) -> Result<()> {
-    let projects = db::query_project(types::ProjectQuery::ByName(project.clone()), conn)?;
-    if projects.is_empty() {
-        println!("Project '{project}' not found");
+fn handle_project_set(
+    name: String,
+    overrides: BillingRules,
+    reset: bool,
+    global: &BillingRules,
+    conn: &Connection,
+) -> Result<()> {
+    let Some(project_id) = resolve_project(&name, conn)?.and_then(|project| project.id) else {
+        return Ok(());
+    };
+    let current = db::query_project_billing(project_id, conn)?;
+    let rules = if reset {
+        BillingRules::default()
+    } else {
+        overrides.or(&current)
+    };
+    if let Some(rate) = rules.hourly_rate
+        && (!rate.is_finite() || rate < 0.0)
+    {
+        println!("The rate must be a positive number.");
         return Ok(());
     }
-    if projects.len() > 1 {
-        println!("Multiple projects found with the same name, cannot determine which one to use");
+    db::set_project_billing(project_id, &rules, conn)?;
+    db::record_project_rate(project_id, current.hourly_rate, rules.hourly_rate, conn)?;
+    println!("{name}: {}", rules.or(global).describe());
+    Ok(())
+}
+
+fn handle_project_parent(name: String, parent: Option<String>, conn: &Connection) -> Result<()> {
+    let project_id = |name: &str| -> Result<Option<types::ProjectId>> {
+        Ok(resolve_project(name, conn)?.and_then(|project| project.id))
+    };
+    let Some(id) = project_id(&name)? else {
+        return Ok(());
+    };
+    let Some(parent) = parent else {
+        db::set_project_parent(id, None, conn)?;
+        println!("{name} is a top-level project again.");
+        return Ok(());
+    };
+    let Some(parent_id) = project_id(&parent)? else {
+        return Ok(());
+    };
+    db::set_project_parent(id, Some(parent_id), conn)?;
+    println!("{name} is now a sub-project of {parent}.");
+    Ok(())
+}
+
+fn handle_project_client(name: String, client: Option<String>, conn: &Connection) -> Result<()> {
+    let Some(project) = resolve_project(&name, conn)? else {
+        return Ok(());
+    };
+    let Some(id) = project.id else {
+        return Ok(());
+    };
+    let Some(client) = client else {
+        db::set_project_client(id, None, conn)?;
+        println!("{} has no client anymore.", project.name);
+        return Ok(());
+    };
+    let client = client.trim();
+    if client.is_empty() {
+        println!("A client name can't be empty.");
         return Ok(());
     }
-    let project_id = projects[0].id.unwrap();
-    let tickrs = db::query_tickr(types::TickrQuery::ByProjectId(project_id), conn)?;
-    let mut tickr = None;
-    for tickr_candidate in tickrs {
-        if tickr_candidate.description == description {
-            println!("Switching to task '{}'", description);
-            tickr = Some(tickr_candidate);
-            break;
+    let client_id = match db::query_client_by_name(client, conn)? {
+        Some(existing) => existing.id,
+        None => {
+            println!("Created client '{client}'.");
+            db::create_client(client, conn)?
+        }
+    };
+    db::set_project_client(id, Some(client_id), conn)?;
+    println!("{} is now a project of {client}.", project.name);
+    Ok(())
+}
+
+fn handle_client(command: ClientCommand, conn: &Connection) -> Result<()> {
+    match command {
+        ClientCommand::Add { name } => {
+            let name = name.trim();
+            if name.is_empty() {
+                println!("A client name can't be empty.");
+                return Ok(());
+            }
+            if db::query_client_by_name(name, conn)?.is_some() {
+                println!("Client '{name}' already exists.");
+                return Ok(());
+            }
+            db::create_client(name, conn)?;
+            println!("Added client '{name}'.");
         }
+        ClientCommand::List => {
+            let clients = db::query_clients(conn)?;
+            if clients.is_empty() {
+                println!("No clients.");
+                return Ok(());
+            }
+            let projects = db::query_projects(conn)?;
+            let mut table = Table::new(&["Client", "Projects"]);
+            for client in clients {
+                let mut names: Vec<&str> = projects
+                    .iter()
+                    .filter(|project| project.client_id == Some(client.id))
+                    .map(|project| project.name.as_str())
+                    .collect();
+                names.sort_unstable();
+                table.row([
+                    Cell::from(client.name).paint(Paint::Cyan),
+                    Cell::from(names.join(", ")),
+                ]);
+            }
+            table.print();
+        }
+        ClientCommand::Rename { name, new_name } => {
+            let Some(client) = resolve_client(&name, conn)? else {
+                return Ok(());
+            };
+            let new_name = new_name.trim();
+            if new_name.is_empty() {
+                println!("A client name can't be empty.");
+                return Ok(());
+            }
+            db::rename_client(client.id, new_name, conn)?;
+            println!("Renamed client '{}' to '{new_name}'.", client.name);
+        }
+        ClientCommand::Delete { name } => {
+            let Some(client) = resolve_client(&name, conn)? else {
+                return Ok(());
+            };
+            db::delete_client(client.id, conn)?;
+            let projects = match client.projects {
+                0 => String::new(),
+                1 => ", its project has no client now".to_string(),
+                count => format!(", its {count} projects have no client now"),
+            };
+            println!("Deleted client '{}'{projects}.", client.name);
+        }
+    }
+    Ok(())
+}
+
+fn handle_project_alias(
+    alias: Option<String>,
+    project: Option<String>,
+    remove: bool,
+    conn: &Connection,
+) -> Result<()> {
+    let Some(alias) = alias else {
+        let aliases = db::query_project_aliases(conn)?;
+        if aliases.is_empty() {
+            println!("No project aliases.");
+            return Ok(());
+        }
+        let projects = db::query_projects(conn)?;
+        let mut table = Table::new(&["Alias", "Project"]);
+        for (alias, project_id) in aliases {
+            if let Some(project) = projects.iter().find(|p| p.id == Some(project_id)) {
+                table.row([
+                    Cell::from(alias).paint(Paint::Cyan),
+                    project.name.as_str().into(),
+                ]);
+            }
+        }
+        table.print();
+        return Ok(());
+    };
+    if remove {
+        if !db::remove_project_alias(&alias, conn)? {
+            println!("No alias '{alias}'.");
+        }
+        return Ok(());
     }
-    if tickr.is_none() {
-        println!("Task '{}' not found in project '{}'", description, project);
+    let Some(project) = project else {
+        match db::query_project_by_alias(&alias, conn)? {
+            Some(project) => println!("{alias} -> {}", project.name),
+            None => println!("No alias '{alias}'."),
+        }
+        return Ok(());
+    };
+    if alias.trim().is_empty() {
+        println!("An alias can't be empty.");
         return Ok(());
     }
-    let tickr = tickr.unwrap();
-    let tickr_to_stop = db::query_tickr(types::TickrQuery::ByProjectId(project_id), conn)?
-        .into_iter()
-        .find(|t| t.intervals.iter().any(|i| i.end_time.is_none()));
-    if let Some(old_tickr) = tickr_to_stop {
-        println!(
-            "Stopping currently running task '{}'",
-            old_tickr.description
-        );
-        db::end_tickr(old_tickr.id.unwrap(), conn)?;
+    // A project's own name always wins, so such an alias would never be used.
+    if db::check_project_exists(&alias, conn)? {
+        println!("'{alias}' is already the name of a project.");
+        return Ok(());
     }
-    db::start_tickr(tickr.id.unwrap(), conn)?;
+    let Some(project) = resolve_project(&project, conn)? else {
+        return Ok(());
+    };
+    let Some(project_id) = project.id else {
+        return Ok(());
+    };
+    db::set_project_alias(&alias, project_id, conn)?;
+    println!("'{alias}' now names project '{}'", project.name);
     Ok(())
 }
 
-fn handle_category_add(name: String, color_opt: Option<String>, conn: &Connection) -> Result<()> {
-    let color = if let Some(c) = color_opt {
-        if !crate::color::is_valid_hex(&c) {
-            println!("Invalid color format. Please provide a hex code like #RRGGBB.");
+fn handle_project_goal(
+    project: Option<String>,
+    hours: Option<String>,
+    total: bool,
+    clear: bool,
+    config: &Config,
+    conn: &Connection,
+) -> Result<()> {
+    let Some(name) = project else {
+        let progress = goal::progress(config, Local::now(), conn)?;
+        if progress.is_empty() {
+            println!("No project goals.");
             return Ok(());
         }
-        c
-    } else {
-        crate::color::random_color()
+        let projects = db::query_projects(conn)?;
+        let mut table = Table::new(&["Project", "Goal", "Tracked", "Progress", ""]);
+        for project in &projects {
+            let Some(progress) = project.id.and_then(|id| progress.get(&id)) else {
+                continue;
+            };
+            table.row(goal_cells(&project.name, progress));
+        }
+        table.print();
+        return Ok(());
     };
-    db::create_category(name, color, conn)?;
+    let Some(project) = resolve_project(&name, conn)? else {
+        return Ok(());
+    };
+    let Some(project_id) = project.id else {
+        return Ok(());
+    };
+    if clear {
+        db::set_project_goal(project_id, None, conn)?;
+        println!("Removed the goal of '{}'", project.name);
+        return Ok(());
+    }
+    let Some(hours) = hours else {
+        match goal::progress(config, Local::now(), conn)?.get(&project_id) {
+            Some(progress) => {
+                let mut table = Table::new(&["Project", "Goal", "Tracked", "Progress", ""]);
+                table.row(goal_cells(&project.name, progress));
+                table.print();
+            }
+            None => println!("'{}' has no goal.", project.name),
+        }
+        return Ok(());
+    };
+    let Some(minutes) = goal::parse_hours(&hours).filter(|minutes| *minutes > 0) else {
+        println!("'{hours}' is not a number of hours, e.g. 20, 7.5 or 1h30m.");
+        return Ok(());
+    };
+    let goal = types::ProjectGoal {
+        period: if total {
+            types::GoalPeriod::Total
+        } else {
+            types::GoalPeriod::Week
+        },
+        minutes,
+    };
+    db::set_project_goal(project_id, Some(goal), conn)?;
+    println!("'{}' now aims for {}", project.name, goal::describe(&goal));
     Ok(())
 }
 
+/// A row of the goal tables: project, goal, tracked time, bar and state.
+fn goal_cells(project: &str, progress: &goal::GoalProgress) -> Vec<Cell> {
+    let paint = match progress.state {
+        goal::GoalState::Behind => Paint::Yellow,
+        goal::GoalState::Over => Paint::Red,
+        goal::GoalState::OnTrack | goal::GoalState::Reached => Paint::Green,
+    };
+    let tracked = match progress.goal.period {
+        types::GoalPeriod::Week => "this week",
+        types::GoalPeriod::Total => "in total",
+    };
+    vec![
+        project.into(),
+        goal::describe(&progress.goal).into(),
+        format!("{}h {tracked}", goal::format_hours(progress.seconds)).into(),
+        Cell::from(format!(
+            "[{}] {:.0}%",
+            progress.bar(20),
+            progress.fraction() * 100.0
+        ))
+        .paint(paint),
+        Cell::from(progress.state.label()).paint(paint),
+    ]
+}
+
+fn handle_task_add(
+    project: String,
+    description: String,
+    start: Option<String>,
+    end: Option<String>,
+    category: Option<String>,
+    billable: bool,
+    conn: &Connection,
+) -> Result<()> {
+    let Some(project_id) = resolve_project(&project, conn)?.and_then(|project| project.id) else {
+        return Ok(());
+    };
+
+    let start_time = parse_optional_datetime(start)?;
+    let end_time = parse_optional_datetime(end)?;
+    if start_time.is_none() && end_time.is_some() {
+        println!("End time requires a start time.");
+        return Ok(());
+    }
+    if let Some(start_time) = start_time {
+        match db::check_interval(start_time, end_time) {
+            Err(err) if err.is_warning() => eprintln!("Warning: {err}"),
+            result => result?,
+        }
+        db::ensure_unlocked(start_time, end_time, conn)?;
+    }
+
+    let category_id = category
+        .map(|name| category_or_create(name, conn))
+        .transpose()?;
+
+    let tickr_id = db::create_tickr(
+        types::Tickr {
+            id: None,
+            project_id,
+            description,
+            category_id,
+            billable,
+            done: false,
+            blocked_by: None,
+            notes: None,
+            estimate_minutes: None,
+            archived: false,
+            issue: None,
+            intervals: Vec::new(), // Intervals will be created separately based on start/end times
+        },
+        conn,
+    )?;
+    if let Some(start_time) = start_time {
+        daemon::create_interval(tickr_id, start_time, end_time, conn)?;
+    }
+    Ok(())
+}
+
+/// Category named `name`, created with a random color when there is none.
+fn category_or_create(name: String, conn: &Connection) -> Result<types::CategoryId> {
+    match db::query_category_id(&name, conn)? {
+        Some(id) => Ok(id),
+        None => {
+            println!("Category '{name}' not found, creating it with a random color.");
+            let color = crate::color::random_color();
+            db::create_category(name, color, conn)
+        }
+    }
+}
+
+fn handle_task_switch(project: String, description: String, conn: &Connection) -> Result<()> {
+    match resolve_tickr(&project, &description, conn)? {
+        Some(tickr) => switch_to_tickr(&tickr, conn),
+        None => Ok(()),
+    }
+}
+
+/// What `task issue` does besides linking.
+struct IssueOptions {
+    title: bool,
+    open: bool,
+    clear: bool,
+}
+
+fn handle_task_issue(
+    project: String,
+    description: String,
+    issue: Option<String>,
+    options: IssueOptions,
+    config: &Config,
+    conn: &Connection,
+) -> Result<()> {
+    let Some(tickr) = resolve_tickr(&project, &description, conn)? else {
+        return Ok(());
+    };
+    let Some(id) = tickr.id else {
+        return Ok(());
+    };
+    if options.clear {
+        db::set_tickr_issue(id, None, conn)?;
+        println!("Removed the issue of '{}'", tickr.description);
+        return Ok(());
+    }
+    let linking = issue.is_some();
+    let issue = match issue {
+        Some(issue) => {
+            let issue = match crate::issue::parse(&issue) {
+                Ok(issue) => issue,
+                Err(err) => {
+                    println!("{err}");
+                    return Ok(());
+                }
+            };
+            db::set_tickr_issue(id, Some(&issue), conn)?;
+            println!("Linked '{}' to {issue}", tickr.description);
+            issue
+        }
+        None => match tickr.issue {
+            Some(issue) => issue,
+            None => {
+                println!("'{}' has no issue", tickr.description);
+                return Ok(());
+            }
+        },
+    };
+    let url = crate::issue::url(&issue, &config.issue_url);
+    if options.title {
+        let title =
+            crate::issue::fetch_title(&issue, &config.issue_url, &config.issue_token_hosts)?;
+        db::update_tickr_details(id, title.clone(), tickr.category_id, conn)?;
+        println!("Renamed '{}' to '{title}'", tickr.description);
+    }
+    if options.open {
+        crate::issue::open(&url)?;
+    } else if !options.title && !linking {
+        println!("{url}");
+    }
+    Ok(())
+}
+
+/// The task called `description` in the project called `project`, offering close spellings of
+/// both like [`resolve_project`]; `None` after printing why when none is picked.
+fn resolve_tickr(project: &str, description: &str, conn: &Connection) -> Result<Option<Tickr>> {
+    let Some(project) = resolve_project(project, conn)? else {
+        return Ok(None);
+    };
+    let Some(project_id) = project.id else {
+        return Ok(None);
+    };
+    let mut tickrs = db::query_tickr(types::TickrQuery::ByProjectId(project_id), conn)?;
+    let index = match tickrs
+        .iter()
+        .position(|tickr| tickr.description == description)
+    {
+        Some(index) => Some(index),
+        None => {
+            let names: Vec<&str> = tickrs
+                .iter()
+                .map(|tickr| tickr.description.as_str())
+                .collect();
+            let not_found = format!(
+                "Task '{description}' not found in project '{}'",
+                project.name
+            );
+            confirm_suggestion(description, &not_found, &names)?
+        }
+    };
+    Ok(index.map(|index| tickrs.swap_remove(index)))
+}
+
+/// Starts the task named after the current git branch in `project`, or the project named after
+/// the repository, creating the task when needed.
+fn handle_task_start_from_git(project: Option<String>, conn: &Connection) -> Result<()> {
+    let Some(git) = crate::git::current() else {
+        println!("Not on a branch of a git repository");
+        return Ok(());
+    };
+    let project = match (project, &git.repo) {
+        (Some(project), _) => resolve_project(&project, conn)?,
+        // Repositories are often named in lower case, so any case of the name matches.
+        (None, Some(repo)) => match db::query_projects(conn)?
+            .into_iter()
+            .find(|project| project.name.eq_ignore_ascii_case(repo))
+        {
+            Some(project) => Some(project),
+            None => resolve_project(repo, conn)?,
+        },
+        (None, None) => {
+            println!("Could not tell the repository's name, pass the project to start the task in");
+            return Ok(());
+        }
+    };
+    let Some(project) = project else {
+        return Ok(());
+    };
+    let Some(project_id) = project.id else {
+        return Ok(());
+    };
+    let description = git.task_name().to_string();
+    let tickrs = db::query_tickr(types::TickrQuery::ByProjectId(project_id), conn)?;
+    let tickr = match tickrs
+        .into_iter()
+        .find(|tickr| tickr.description == description)
+    {
+        Some(tickr) => tickr,
+        None => {
+            let tickr = Tickr {
+                id: None,
+                project_id,
+                description,
+                category_id: None,
+                billable: true,
+                done: false,
+                blocked_by: None,
+                notes: None,
+                estimate_minutes: None,
+                archived: false,
+                issue: None,
+                intervals: Vec::new(),
+            };
+            let id = db::create_tickr(tickr.clone(), conn)?;
+            println!(
+                "Created task '{}' in project '{}'",
+                tickr.description, project.name
+            );
+            Tickr {
+                id: Some(id),
+                ..tickr
+            }
+        }
+    };
+    switch_to_tickr(&tickr, conn)
+}
+
+/// Lists the open tasks, most recently worked on first, and switches to the one picked.
+fn handle_switch(config: &Config, conn: &Connection) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        println!("tickr switch needs a terminal, use 'tickr task switch <project> <task>' instead");
+        return Ok(());
+    }
+    let (tickrs, items) = picker::switch_items(&config.date_format, conn)?;
+    if tickrs.is_empty() {
+        println!("No open tasks to switch to");
+        return Ok(());
+    }
+    match picker::pick("Switch to: ", &items)? {
+        Some(index) => switch_to_tickr(&tickrs[index], conn),
+        None => Ok(()),
+    }
+}
+
+/// Starts `tickr`, ending the running task, and says so.
+fn handle_resume(conn: &Connection) -> Result<()> {
+    let Some(tickr) = db::query_last_stopped_tickr(conn)? else {
+        println!("No stopped task to resume");
+        return Ok(());
+    };
+    let running = db::query_running_tickr(conn)?;
+    if running.is_some_and(|running| running.id == tickr.id) {
+        println!("'{}' is already running", tickr.description);
+        return Ok(());
+    }
+    switch_to_tickr(&tickr, conn)
+}
+
+fn switch_to_tickr(tickr: &Tickr, conn: &Connection) -> Result<()> {
+    let description = &tickr.description;
+    println!("Switching to task '{description}'");
+    if let Some(blocker_id) = tickr.blocked_by {
+        match db::query_tickr_by_id(blocker_id, conn)? {
+            Some(blocker) => eprintln!(
+                "Warning: '{}' is blocked by '{}'",
+                description, blocker.description
+            ),
+            None => eprintln!("Warning: '{description}' is blocked by task #{blocker_id}"),
+        }
+    }
+    // Starting ends the running task.
+    if let Some(old_tickr) = db::query_running_tickr(conn)?.filter(|t| t.id != tickr.id) {
+        println!(
+            "Stopping currently running task '{}'",
+            old_tickr.description
+        );
+    }
+    if let Some(warning) = daemon::start_tickr(tickr.id.unwrap(), conn)? {
+        eprintln!("Warning: {warning}");
+    }
+    Ok(())
+}
+
+fn handle_category_add(name: String, color_opt: Option<String>, conn: &Connection) -> Result<()> {
+    let color = if let Some(c) = color_opt {
+        if !crate::color::is_valid_hex(&c) {
+            println!("Invalid color format. Please provide a hex code like #RRGGBB.");
+            return Ok(());
+        }
+        c
+    } else {
+        crate::color::random_color()
+    };
+    db::create_category(name, color, conn)?;
+    Ok(())
+}
+
+/// How `tickr status` prints the running task.
+pub struct StatusStyle {
+    pub json: bool,
+    pub waybar: bool,
+    pub format: Option<String>,
+}
+
+/// How often `status --watch` looks at the database.
+const STATUS_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// `status` as `tickr status` prints it, `None` when there is nothing to print.
+fn format_status(status: &StatusOutput, style: &StatusStyle) -> Result<Option<String>> {
+    if style.waybar {
+        let output = status.waybar(style.format.as_deref());
+        return Ok(Some(serde_json::to_string(&output)?));
+    }
+    if style.json {
+        return Ok(Some(serde_json::to_string(status)?));
+    }
+    if !status.running {
+        // Custom formats are meant for prompts and status bars, print nothing there.
+        return Ok(style
+            .format
+            .is_none()
+            .then(|| "No task running".to_string()));
+    }
+    if let Some(template) = &style.format {
+        return Ok(Some(status.fill(template)));
+    }
+    // Only the default line is colored; custom formats go into prompts with colors of their own.
+    let color = table::color_enabled();
+    let category = match status.category {
+        Some(_) => format!(" [{}]", table::paint("{category}", Paint::Cyan, color)),
+        None => String::new(),
+    };
+    let template = format!(
+        "{} > {}{category} > Running {} (since {{started}})",
+        table::paint("{project}", Paint::Bold, color),
+        table::paint("{task}", Paint::Bold, color),
+        table::paint("{elapsed}", Paint::Green, color),
+    );
+    Ok(Some(status.fill(&template)))
+}
+
+/// Prints `status` as `tickr status` does, shared with `status --fast` in `main`.
+pub fn print_status(status: StatusOutput, style: &StatusStyle) -> Result<()> {
+    if let Some(line) = format_status(&status, style)? {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Prints the status, then again every time the printed line would change. Runs until killed or
+/// until stdout is closed by the status bar.
+fn watch_status(style: &StatusStyle, conn: &Connection) -> Result<()> {
+    let mut last = None;
+    loop {
+        // An empty line clears the bar when a custom format has nothing to show.
+        let line = format_status(&status::query_status(conn)?, style)?.unwrap_or_default();
+        if last.as_ref() != Some(&line) {
+            let mut stdout = io::stdout().lock();
+            match writeln!(stdout, "{line}").and_then(|()| stdout.flush()) {
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+                result => result?,
+            }
+            last = Some(line);
+        }
+        std::thread::sleep(STATUS_WATCH_INTERVAL);
+    }
+}
+
+fn handle_report(
+    by: ReportBy,
+    format: ReportFormat,
+    from: Option<String>,
+    to: Option<String>,
+    client: Option<String>,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<()> {
+    let today = Local::now().date_naive();
+    let from_date = match from {
+        Some(value) => parse_date(&value)?,
+        None => today - Duration::days(6),
+    };
+    let client = match client {
+        Some(name) => match resolve_client(&name, conn)? {
+            Some(client) => Some(client),
+            None => return Ok(()),
+        },
+        None => None,
+    };
+    let to_date = match to {
+        Some(value) => parse_date(&value)?,
+        None => today,
+    };
+    if to_date < from_date {
+        println!("--to must not be before --from.");
+        return Ok(());
+    }
+
+    let (grouping, heading) = match by {
+        ReportBy::Project => (types::ReportGrouping::Project, "Project"),
+        ReportBy::Client => (types::ReportGrouping::Client, "Client"),
+        ReportBy::Category => (types::ReportGrouping::Category, "Category"),
+        ReportBy::Task => (types::ReportGrouping::Task, "Task"),
+    };
+    if format == ReportFormat::Markdown {
+        let timesheet = markdown_timesheet(
+            grouping,
+            heading,
+            from_date,
+            to_date,
+            client.as_ref(),
+            billing,
+            conn,
+        )?;
+        print!("{timesheet}");
+        return Ok(());
+    }
+    let rows = db::query_report(
+        grouping,
+        crate::ui::local_start_of_day(from_date),
+        crate::ui::local_start_of_day(to_date + Duration::days(1)),
+        client.as_ref().map(|client| client.id),
+        billing,
+        conn,
+    )?;
+
+    let for_client = client
+        .map(|client| format!(" for {}", client.name))
+        .unwrap_or_default();
+    println!(
+        "Report by {}{for_client} from {from_date} to {to_date}",
+        heading.to_lowercase()
+    );
+    println!();
+    if rows.is_empty() {
+        println!("No time tracked in this range.");
+        return Ok(());
+    }
+
+    let total_seconds: i64 = rows.iter().map(|row| row.seconds).sum();
+    let with_amount = rows.iter().any(|row| !row.amounts.is_empty());
+    let duration = |seconds: i64| crate::ui::format_duration(Duration::seconds(seconds));
+    let goals = if grouping == types::ReportGrouping::Project {
+        report_goals(&rows, from_date, to_date, conn)?
+    } else {
+        HashMap::new()
+    };
+    let cells = |label: String, row: &types::ReportRow| {
+        let percent = if total_seconds > 0 {
+            row.seconds as f64 * 100.0 / total_seconds as f64
+        } else {
+            0.0
+        };
+        let mut cells = vec![
+            label,
+            duration(row.billable_seconds),
+            duration(row.billed_seconds),
+            duration(row.seconds - row.billable_seconds),
+            duration(row.seconds),
+            format!("{percent:.1}%"),
+            row.intervals.to_string(),
+            row.tasks.to_string(),
+        ];
+        if with_amount {
+            cells.push(row.amounts.to_string());
+        }
+        if !goals.is_empty() {
+            cells.push(goals.get(&row.label).cloned().unwrap_or_default());
+        }
+        cells
+    };
+
+    let mut headings = vec![
+        heading,
+        "Billable",
+        "Billed",
+        "Non-billable",
+        "Total",
+        "%",
+        "Intervals",
+        "Tasks",
+    ];
+    if with_amount {
+        headings.push("Amount");
+    }
+    if !goals.is_empty() {
+        headings.push("Goal");
+    }
+    let mut table = Table::new(&headings).right(&(1..headings.len()).collect::<Vec<_>>());
+    for row in &rows {
+        table.row(cells(
+            crate::ui::clamp_name(&row.label, 40).trim_end().to_string(),
+            row,
+        ));
+    }
+    let mut amounts = Amounts::default();
+    for row in &rows {
+        amounts.merge(&row.amounts);
+    }
+    let total = types::ReportRow {
+        label: "Total".to_string(),
+        project_id: None,
+        seconds: total_seconds,
+        billable_seconds: rows.iter().map(|row| row.billable_seconds).sum(),
+        billed_seconds: rows.iter().map(|row| row.billed_seconds).sum(),
+        amounts,
+        intervals: rows.iter().map(|row| row.intervals).sum(),
+        backwards: rows.iter().map(|row| row.backwards).sum(),
+        tasks: rows.iter().map(|row| row.tasks).sum(),
+    };
+    table.total(cells(total.label.clone(), &total));
+    table.print();
+    if total.backwards > 0 {
+        println!();
+        println!(
+            "{}",
+            table::paint(
+                &backwards_note(total.backwards),
+                Paint::Yellow,
+                table::color_enabled()
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Progress towards their goals of the projects in a report by project, by project name. Weekly
+/// goals are scaled to the days of the range, total goals count all time up to its end.
+fn report_goals(
+    rows: &[types::ReportRow],
+    from: NaiveDate,
+    to: NaiveDate,
+    conn: &Connection,
+) -> Result<HashMap<String, String>> {
+    let goals = db::query_project_goals(conn)?;
+    if goals.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let end = crate::ui::local_start_of_day(to + Duration::days(1)).min(Local::now());
+    let all_time = db::query_project_seconds(None, end, conn)?;
+    let days = (to - from).num_days() + 1;
+    let mut cells = HashMap::new();
+    for project in db::query_projects(conn)? {
+        let Some(id) = project.id else {
+            continue;
+        };
+        let Some(goal) = goals.get(&id) else {
+            continue;
+        };
+        let Some(row) = rows.iter().find(|row| row.label == project.name) else {
+            continue;
+        };
+        let target = i64::from(goal.minutes) * 60;
+        let (seconds, target, suffix) = match goal.period {
+            types::GoalPeriod::Week => (row.seconds, target * days / 7, ""),
+            types::GoalPeriod::Total => (all_time.get(&id).copied().unwrap_or(0), target, " total"),
+        };
+        let percent = if target > 0 {
+            seconds as f64 * 100.0 / target as f64
+        } else {
+            100.0
+        };
+        cells.insert(
+            project.name,
+            format!(
+                "{percent:.0}% of {}{suffix}",
+                crate::ui::format_duration(Duration::seconds(target))
+            ),
+        );
+    }
+    Ok(cells)
+}
+
+/// Points out `count` intervals that end before they start.
+fn backwards_note(count: usize) -> String {
+    if count == 1 {
+        "1 interval ends before it starts, probably after the clock was set back, and counts as \
+         00:00:00."
+            .to_string()
+    } else {
+        format!(
+            "{count} intervals end before they start, probably after the clock was set back, and \
+             count as 00:00:00."
+        )
+    }
+}
+
+/// Markdown table of the time per group (rows) and day (columns) from `from` to `to`, with the
+/// totals of each group in the last column and of each day in the last row.
+fn markdown_timesheet(
+    grouping: types::ReportGrouping,
+    heading: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    client: Option<&types::Client>,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<String> {
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+    // Seconds per group label and day, from a report per day so intervals split at midnight.
+    let mut groups: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+    let mut backwards = 0;
+    for (index, day) in days.iter().enumerate() {
+        let rows = db::query_report(
+            grouping,
+            crate::ui::local_start_of_day(*day),
+            crate::ui::local_start_of_day(*day + Duration::days(1)),
+            client.map(|client| client.id),
+            billing,
+            conn,
+        )?;
+        for row in rows {
+            backwards += row.backwards;
+            groups
+                .entry(row.label)
+                .or_insert_with(|| vec![0; days.len()])[index] += row.seconds;
+        }
+    }
+
+    let mut out = match client {
+        Some(client) => format!("## Timesheet {} {from} to {to}\n\n", client.name),
+        None => format!("## Timesheet {from} to {to}\n\n"),
+    };
+    if groups.is_empty() {
+        out.push_str("No time tracked in this range.\n");
+        return Ok(out);
+    }
+    let duration = |seconds: i64| crate::ui::format_duration(Duration::seconds(seconds));
+    let cell = |seconds: i64| {
+        if seconds > 0 {
+            duration(seconds)
+        } else {
+            String::new()
+        }
+    };
+    let mut header = vec![heading.to_string()];
+    header.extend(days.iter().map(|day| day.format("%a %m-%d").to_string()));
+    header.push("Total".to_string());
+    let mut lines = vec![
+        markdown_row(&header),
+        format!("|---|{}", "--:|".repeat(days.len() + 1)),
+    ];
+    let mut day_totals = vec![0; days.len()];
+    for (label, seconds) in &groups {
+        let mut cells = vec![markdown_escape(label)];
+        cells.extend(seconds.iter().map(|seconds| cell(*seconds)));
+        cells.push(duration(seconds.iter().sum()));
+        lines.push(markdown_row(&cells));
+        for (total, seconds) in day_totals.iter_mut().zip(seconds) {
+            *total += seconds;
+        }
+    }
+    let mut cells = vec!["**Total**".to_string()];
+    cells.extend(
+        day_totals
+            .iter()
+            .map(|seconds| format!("**{}**", duration(*seconds))),
+    );
+    cells.push(format!("**{}**", duration(day_totals.iter().sum())));
+    lines.push(markdown_row(&cells));
+
+    for line in lines {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    if backwards > 0 {
+        out.push_str(&format!("\n> {}\n", backwards_note(backwards)));
+    }
+    Ok(out)
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    format!("| {} |", cells.join(" | "))
+}
+
+/// `text` safe to put in a markdown table cell.
+fn markdown_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('\n', " ")
+}
+
+fn handle_batch(file: Option<String>, dry_run: bool, conn: &Connection) -> Result<()> {
+    let summary = match file.as_deref() {
+        None | Some("-") => crate::batch::run(io::stdin().lock(), dry_run, conn)?,
+        Some(path) => crate::batch::run(
+            io::BufReader::new(std::fs::File::open(path)?),
+            dry_run,
+            conn,
+        )?,
+    };
+    println!(
+        "{} {} projects, {} categories, {} tasks and {} intervals ({} already existed).",
+        if dry_run { "Would add" } else { "Added" },
+        summary.projects,
+        summary.categories,
+        summary.tasks,
+        summary.intervals,
+        summary.existing
+    );
+    Ok(())
+}
+
+/// Runs before the config is loaded and the database is opened, see `main`.
+pub fn handle_config(command: &ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Init { force } => {
+            let path = config::write_sample(*force)?;
+            println!("Wrote {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn handle_week(command: WeekCommand, config: &Config, conn: &Connection) -> Result<()> {
+    match command {
+        WeekCommand::Lock { date } => {
+            let date = match date {
+                Some(date) => parse_date(&date)?,
+                None => Local::now().date_naive() - Duration::days(7),
+            };
+            let week_start = config.calendar_week_start_for(date);
+            if let Some(locked) = db::locked_week_containing(date, conn)? {
+                println!("The week of {locked} is already locked.");
+                return Ok(());
+            }
+            db::lock_week(week_start, conn)?;
+            println!(
+                "Locked the week of {week_start} to {}.",
+                week_start + Duration::days(6)
+            );
+        }
+        WeekCommand::Unlock { date } => match db::unlock_week(parse_date(&date)?, conn)? {
+            Some(week_start) => println!("Unlocked the week of {week_start}."),
+            None => println!("The week containing {date} is not locked."),
+        },
+        WeekCommand::List => {
+            let weeks = db::query_locked_weeks(conn)?;
+            if weeks.is_empty() {
+                println!("No locked weeks.");
+                return Ok(());
+            }
+            let mut table = Table::new(&["From", "To", "Locked"]);
+            for (week_start, locked_at) in weeks {
+                table.row([
+                    Cell::from(week_start.to_string()),
+                    Cell::from((week_start + Duration::days(6)).to_string()),
+                    Cell::from(locked_at.format("%Y-%m-%d %H:%M").to_string()).paint(Paint::Dim),
+                ]);
+            }
+            table.print();
+        }
+    }
+    Ok(())
+}
+
+fn handle_template(command: TemplateCommand, conn: &Connection) -> Result<()> {
+    if !matches!(command, TemplateCommand::List) {
+        for name in db::create_due_templates(Local::now().date_naive(), conn)? {
+            println!("Created today's '{name}' task.");
+        }
+    }
+    match command {
+        TemplateCommand::Add {
+            project,
+            name,
+            category,
+            non_billable,
+            every_workday,
+        } => {
+            let name = name.trim();
+            if name.is_empty() {
+                println!("A template name can't be empty.");
+                return Ok(());
+            }
+            if db::query_template_by_name(name, conn)?.is_some() {
+                println!("Template '{name}' already exists.");
+                return Ok(());
+            }
+            let Some(project) = resolve_project(&project, conn)? else {
+                return Ok(());
+            };
+            let Some(project_id) = project.id else {
+                return Ok(());
+            };
+            let category_id = category
+                .map(|name| category_or_create(name, conn))
+                .transpose()?;
+            db::create_template(
+                &types::TaskTemplate {
+                    id: None,
+                    name: name.to_string(),
+                    project_id,
+                    project: project.name.clone(),
+                    category_id,
+                    billable: !non_billable,
+                    every_workday,
+                    last_created: None,
+                },
+                conn,
+            )?;
+            let every = if every_workday {
+                ", a task is created from it every workday"
+            } else {
+                ""
+            };
+            println!("Added template '{name}' in {}{every}.", project.name);
+        }
+        TemplateCommand::List => {
+            let templates = db::query_templates(conn)?;
+            if templates.is_empty() {
+                println!("No templates.");
+                return Ok(());
+            }
+            let categories: HashMap<types::CategoryId, String> = db::query_categories(conn)?
+                .into_iter()
+                .map(|category| (category.id, category.name))
+                .collect();
+            let mut table = Table::new(&["Template", "Project", "Category", "Billable", "Repeats"]);
+            for template in templates {
+                table.row([
+                    Cell::from(template.name).paint(Paint::Cyan),
+                    Cell::from(template.project),
+                    Cell::from(
+                        template
+                            .category_id
+                            .and_then(|id| categories.get(&id).cloned())
+                            .unwrap_or_default(),
+                    ),
+                    Cell::from(if template.billable { "yes" } else { "no" }),
+                    Cell::from(if template.every_workday {
+                        "every workday"
+                    } else {
+                        ""
+                    }),
+                ]);
+            }
+            table.print();
+        }
+        TemplateCommand::Use { name, start } => {
+            let Some(template) = resolve_template(&name, conn)? else {
+                return Ok(());
+            };
+            let tickr_id = db::use_template(&template, conn)?;
+            println!("Created task '{}' in {}.", template.name, template.project);
+            if start && let Some(tickr) = db::query_tickr_by_id(tickr_id, conn)? {
+                switch_to_tickr(&tickr, conn)?;
+            }
+        }
+        TemplateCommand::Delete { name } => {
+            let Some(template) = resolve_template(&name, conn)? else {
+                return Ok(());
+            };
+            if let Some(id) = template.id {
+                db::delete_template(id, conn)?;
+            }
+            println!("Deleted template '{}'.", template.name);
+        }
+    }
+    Ok(())
+}
+
+fn handle_snapshot(command: SnapshotCommand, conn: &Connection) -> Result<()> {
+    let duration = |seconds: i64| crate::ui::format_duration(Duration::seconds(seconds));
+    match command {
+        SnapshotCommand::Create { name, from, to } => {
+            let name = name.trim();
+            if name.is_empty() {
+                println!("A snapshot name can't be empty.");
+                return Ok(());
+            }
+            if db::query_snapshot_by_name(name, conn)?.is_some() {
+                println!("Snapshot '{name}' already exists.");
+                return Ok(());
+            }
+            let from = from.as_deref().map(parse_date).transpose()?;
+            let to = match to {
+                Some(value) => parse_date(&value)?,
+                None => Local::now().date_naive(),
+            };
+            if from.is_some_and(|from| to < from) {
+                println!("--to must not be before --from.");
+                return Ok(());
+            }
+            let mut progress = Progress::new("Adding up", 0);
+            db::create_snapshot(name, from, to, &mut progress, conn)?;
+            drop(progress);
+            if let Some(snapshot) = db::query_snapshot_by_name(name, conn)? {
+                println!(
+                    "Saved snapshot '{}' of {} to {}: {}",
+                    snapshot.name,
+                    snapshot.from,
+                    snapshot.to,
+                    duration(snapshot.seconds)
+                );
+            }
+        }
+        SnapshotCommand::List => {
+            let snapshots = db::query_snapshots(conn)?;
+            if snapshots.is_empty() {
+                println!("No snapshots.");
+                return Ok(());
+            }
+            let mut table = Table::new(&["Snapshot", "From", "To", "Total", "Created"]).right(&[3]);
+            for snapshot in snapshots {
+                table.row([
+                    Cell::from(snapshot.name).paint(Paint::Cyan),
+                    Cell::from(snapshot.from.to_string()),
+                    Cell::from(snapshot.to.to_string()),
+                    Cell::from(duration(snapshot.seconds)),
+                    Cell::from(snapshot.created_at.format("%Y-%m-%d %H:%M").to_string())
+                        .paint(Paint::Dim),
+                ]);
+            }
+            table.print();
+        }
+        SnapshotCommand::Open { name, by } => {
+            let mut snapshots = db::query_snapshots(conn)?;
+            let names: Vec<&str> = snapshots
+                .iter()
+                .map(|snapshot| snapshot.name.as_str())
+                .collect();
+            let index = match names
+                .iter()
+                .position(|item| item.eq_ignore_ascii_case(&name))
+            {
+                Some(index) => Some(index),
+                None => confirm_suggestion(&name, &format!("Snapshot '{name}' not found"), &names)?,
+            };
+            let Some(index) = index else {
+                return Ok(());
+            };
+            let snapshot = snapshots.swap_remove(index);
+            let rows = db::query_snapshot_rows(snapshot.id, conn)?;
+
+            let heading = match by {
+                SnapshotBy::Project => "Project",
+                SnapshotBy::Category => "Category",
+                SnapshotBy::Day => "Day",
+            };
+            println!(
+                "Snapshot '{}' by {} from {} to {}, taken {}",
+                snapshot.name,
+                heading.to_lowercase(),
+                snapshot.from,
+                snapshot.to,
+                snapshot.created_at.format("%Y-%m-%d %H:%M")
+            );
+            println!();
+            if rows.is_empty() {
+                println!("No time tracked in this range.");
+                return Ok(());
+            }
+            // Days sort by date, the others by time, most first.
+            let mut groups: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+            for row in &rows {
+                let label = match by {
+                    SnapshotBy::Project => row.project.clone(),
+                    SnapshotBy::Category => row
+                        .category
+                        .clone()
+                        .unwrap_or_else(|| "No category".to_string()),
+                    SnapshotBy::Day => row.day.format("%Y-%m-%d %a").to_string(),
+                };
+                let group = groups.entry(label).or_default();
+                group.0 += row.seconds;
+                group.1 += row.billable_seconds;
+            }
+            let mut groups: Vec<(String, (i64, i64))> = groups.into_iter().collect();
+            if by != SnapshotBy::Day {
+                groups.sort_by_key(|(_, (seconds, _))| -seconds);
+            }
+            let total: i64 = groups.iter().map(|(_, (seconds, _))| seconds).sum();
+            let billable: i64 = groups.iter().map(|(_, (_, billable))| billable).sum();
+            let mut table = Table::new(&[heading, "Billable", "Non-billable", "Total", "%"])
+                .right(&[1, 2, 3, 4]);
+            for (label, (seconds, billable_seconds)) in groups {
+                let percent = if total > 0 {
+                    seconds as f64 * 100.0 / total as f64
+                } else {
+                    0.0
+                };
+                table.row([
+                    Cell::from(label).paint(Paint::Cyan),
+                    Cell::from(duration(billable_seconds)),
+                    Cell::from(duration(seconds - billable_seconds)),
+                    Cell::from(duration(seconds)),
+                    Cell::from(format!("{percent:.1}%")),
+                ]);
+            }
+            table.total([
+                "Total".to_string(),
+                duration(billable),
+                duration(total - billable),
+                duration(total),
+                "100.0%".to_string(),
+            ]);
+            table.print();
+        }
+    }
+    Ok(())
+}
+
+/// Which of the collected records `export` keeps.
+struct RecordFilter {
+    /// Only intervals from locked weeks.
+    approved: bool,
+    /// Only the projects of this client.
+    client: Option<String>,
+}
+
+impl RecordFilter {
+    /// Drops the records the filter leaves out. Returns false when the client doesn't exist,
+    /// after saying so.
+    fn apply(&self, records: &mut Vec<ExportRecord>, conn: &Connection) -> Result<bool> {
+        if self.approved {
+            export::retain_approved(records, conn)?;
+        }
+        if let Some(name) = &self.client {
+            let Some(client) = resolve_client(name, conn)? else {
+                return Ok(false);
+            };
+            records.retain(|record| record.client.as_ref() == Some(&client.name));
+        }
+        Ok(true)
+    }
+}
+
+/// The `[export.profiles.<name>]` table of the config file.
+fn export_profile<'a>(name: &str, config: &'a Config) -> Result<&'a config::ExportProfileConfig> {
+    let profiles = &config.export.profiles;
+    let Some(profile) = profiles.get(name) else {
+        if profiles.is_empty() {
+            anyhow::bail!(
+                "No export profile '{name}': the config file has no [export.profiles.{name}] table"
+            );
+        }
+        let names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        anyhow::bail!(
+            "No export profile '{name}', the config file has {}",
+            names.join(", ")
+        );
+    };
+    Ok(profile)
+}
+
+fn handle_export(
+    format: ExportFormat,
+    output: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    filter: &RecordFilter,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<()> {
+    let from_date = from.as_deref().map(parse_date).transpose()?;
+    let to_date = to.as_deref().map(parse_date).transpose()?;
+    let mut records = export::collect_records(
+        from_date.map(crate::ui::local_start_of_day),
+        to_date.map(|date| crate::ui::local_start_of_day(date + Duration::days(1))),
+        billing,
+        conn,
+    )?;
+    if !filter.apply(&mut records, conn)? {
+        return Ok(());
+    }
+    match output {
+        Some(path) => {
+            let mut file = std::fs::File::create(&path)?;
+            export::write_records(&records, format, from_date, to_date, &mut file)?;
+            println!("Exported {} records to {path}.", records.len());
+        }
+        None => export::write_records(
+            &records,
+            format,
+            from_date,
+            to_date,
+            &mut io::stdout().lock(),
+        )?,
+    }
+    Ok(())
+}
+
+fn handle_timeline(
+    path: String,
+    from: Option<String>,
+    to: Option<String>,
+    color_by: TimelineColor,
+    config: &Config,
+    conn: &Connection,
+) -> Result<()> {
+    if !path.to_lowercase().ends_with(".svg") {
+        anyhow::bail!(
+            "Only SVG images can be written, e.g. `--svg timeline.svg`; convert it to PNG with \
+             a tool like rsvg-convert or Inkscape"
+        );
+    }
+    let from_date = match from {
+        Some(value) => parse_date(&value)?,
+        None => config.calendar_week_start_for(Local::now().date_naive()),
+    };
+    let to_date = match to {
+        Some(value) => parse_date(&value)?,
+        None => from_date + Duration::days(6),
+    };
+    if to_date < from_date {
+        println!("--to must not be before --from.");
+        return Ok(());
+    }
+    let records = export::collect_records(
+        Some(crate::ui::local_start_of_day(from_date)),
+        Some(crate::ui::local_start_of_day(to_date + Duration::days(1))),
+        &config.billing,
+        conn,
+    )?;
+    let mut file = std::fs::File::create(&path)?;
+    export::svg::write_timeline(&records, from_date, to_date, color_by, &mut file)?;
+    let days = (to_date - from_date).num_days() + 1;
+    println!(
+        "Wrote the timeline of {days} day{} to {path}.",
+        if days == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+fn handle_debug_bundle(output: Option<String>, conn: &Connection) -> Result<()> {
+    let path = output
+        .unwrap_or_else(|| format!("tickr-debug-{}.zip", Local::now().format("%Y-%m-%d-%H%M%S")));
+    debug::write_bundle(conn, std::fs::File::create(&path)?)?;
+    println!("Wrote {path}, check it before attaching it to a bug report.");
+    Ok(())
+}
+
+fn handle_export_bundle(
+    output: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    filter: &RecordFilter,
+    config: &Config,
+    conn: &Connection,
+) -> Result<()> {
+    let Some(path) = output else {
+        anyhow::bail!("--bundle needs --output");
+    };
+    let last_week = config.calendar_week_start_for(Local::now().date_naive() - Duration::days(7));
+    let from_date = match &from {
+        Some(value) => parse_date(value)?,
+        None => last_week,
+    };
+    let to_date = match (&from, &to) {
+        (_, Some(value)) => parse_date(value)?,
+        (Some(_), None) => Local::now().date_naive(),
+        (None, None) => last_week + Duration::days(6),
+    };
+    if to_date < from_date {
+        anyhow::bail!("--to must not be before --from.");
+    }
+    let mut records = export::collect_records(
+        Some(crate::ui::local_start_of_day(from_date)),
+        Some(crate::ui::local_start_of_day(to_date + Duration::days(1))),
+        &config.billing,
+        conn,
+    )?;
+    if !filter.apply(&mut records, conn)? {
+        return Ok(());
+    }
+    export::bundle::write_bundle(
+        &records,
+        from_date,
+        to_date,
+        config,
+        std::fs::File::create(&path)?,
+    )?;
+    println!(
+        "Bundled {} records from {from_date} to {to_date} into {path}.",
+        records.len()
+    );
+    Ok(())
+}
+
+fn handle_import(args: ImportArgs, conn: &Connection) -> Result<()> {
+    let source = match args.source {
+        Some(source) => source,
+        None => import::detect_source(&args.file)?,
+    };
+    let records = match source {
+        ImportSource::Csv => import::native::load_csv(&args.file)?,
+        ImportSource::Json => import::native::load_json(&args.file)?,
+        ImportSource::Activitywatch => {
+            let Some(records) = review_activitywatch(&args)? else {
+                println!("Nothing imported.");
+                return Ok(());
+            };
+            records
+        }
+    };
+    if !records.is_empty() {
+        announce_backup("import", conn)?;
+    }
+    let mut progress = Progress::new("Importing", records.len());
+    let summary = import::commit_records(&records, args.duplicates, &mut progress, conn)?;
+    drop(progress);
+    print_import_summary(&summary);
+    Ok(())
+}
+
+fn handle_diff(
+    old: String,
+    new: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<()> {
+    let mut from_date = from.as_deref().map(parse_date).transpose()?;
+    let mut to_date = to.as_deref().map(parse_date).transpose()?;
+    let old_records = load_export(&old)?;
+    let new_records = match &new {
+        Some(path) => load_export(path)?,
+        None => {
+            if from_date.is_none() && to_date.is_none() {
+                let days = old_records
+                    .iter()
+                    .filter_map(|record| record.start.map(|start| start.date_naive()));
+                from_date = days.clone().min();
+                to_date = days.max();
+            }
+            export::collect_records(
+                from_date.map(crate::ui::local_start_of_day),
+                to_date.map(|date| crate::ui::local_start_of_day(date + Duration::days(1))),
+                billing,
+                conn,
+            )?
+        }
+    };
+    let in_range = |record: &export::ExportRecord| match record.start {
+        Some(start) => {
+            let day = start.date_naive();
+            from_date.is_none_or(|from| day >= from) && to_date.is_none_or(|to| day <= to)
+        }
+        None => from_date.is_none() && to_date.is_none(),
+    };
+    let old_records: Vec<_> = old_records.into_iter().filter(in_range).collect();
+    let new_records: Vec<_> = new_records.into_iter().filter(in_range).collect();
+
+    let diff = export::diff::diff_records(&old_records, &new_records);
+    let new_name = new.as_deref().unwrap_or("the database");
+    if diff.is_empty() {
+        println!(
+            "No differences between {old} and {new_name} ({} records).",
+            old_records.len()
+        );
+        return Ok(());
+    }
+    for (heading, records, paint) in [
+        ("Removed", &diff.removed, Paint::Red),
+        ("Added", &diff.added, Paint::Green),
+    ] {
+        if records.is_empty() {
+            continue;
+        }
+        println!("{heading} {}:", records.len());
+        let mut table = record_table(&[]);
+        for (index, record) in records.iter().enumerate() {
+            table.row(record_cells(index, record, paint));
+        }
+        table.print();
+        println!();
+    }
+    if !diff.changed.is_empty() {
+        println!("Changed {}:", diff.changed.len());
+        let mut table = record_table(&["Field", "Was", "Is"]);
+        for (index, (before, after)) in diff.changed.iter().enumerate() {
+            let mut record = record_cells(index, before, Paint::Plain);
+            for (field, was, is) in export::diff::changed_fields(before, after) {
+                // The record's own columns only on its first changed field.
+                let mut cells = std::mem::take(&mut record);
+                cells.resize_with(RECORD_COLUMNS.len(), Cell::default);
+                cells.extend([
+                    Cell::from(field),
+                    Cell::from(was).paint(Paint::Red),
+                    Cell::from(is).paint(Paint::Green),
+                ]);
+                table.row(cells);
+            }
+        }
+        table.print();
+        println!();
+    }
+    println!(
+        "{} removed, {} added, {} changed between {old} and {new_name}.",
+        diff.removed.len(),
+        diff.added.len(),
+        diff.changed.len()
+    );
+    Ok(())
+}
+
+/// Records of a file written by `tickr export --format csv` or `--format json`.
+fn load_export(path: &str) -> Result<Vec<export::ExportRecord>> {
+    match import::detect_source(path) {
+        Ok(ImportSource::Csv) => import::native::load_csv(path),
+        Ok(ImportSource::Json) => import::native::load_json(path),
+        Ok(ImportSource::Activitywatch) | Err(_) => {
+            anyhow::bail!("'{path}' is not a Tickr CSV or JSON export")
+        }
+    }
+}
+
+/// Loads ActivityWatch suggestions and lets the user pick which ones to keep.
+fn review_activitywatch(args: &ImportArgs) -> Result<Option<Vec<export::ExportRecord>>> {
+    let Some(project) = args.project.as_deref() else {
+        anyhow::bail!("ActivityWatch imports need --project");
+    };
+    let date = match &args.date {
+        Some(value) => Some(parse_date(value)?),
+        None => None,
+    };
+    let suggestions = import::activitywatch::load_suggestions(
+        &args.file,
+        &import::activitywatch::Options {
+            date,
+            merge_gap: Duration::minutes(args.gap.max(0)),
+            min_duration: Duration::minutes(args.min.max(0)),
+        },
+    )?;
+    if suggestions.is_empty() {
+        println!("No activity long enough to suggest intervals.");
+        return Ok(None);
+    }
+
+    println!("{} suggested intervals for '{project}':", suggestions.len());
+    let accepted = if args.yes {
+        for (index, suggestion) in suggestions.iter().enumerate() {
+            println!("{}", describe_suggestion(index, suggestion));
+        }
+        suggestions
+    } else {
+        review_suggestions(suggestions)?
+    };
+    if accepted.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        accepted
+            .into_iter()
+            .map(|suggestion| suggestion.into_record(project))
+            .collect(),
+    ))
+}
+
+fn print_import_summary(summary: &import::ImportSummary) {
+    println!(
+        "Imported {} intervals ({} new projects, {} new categories, {} new tasks).",
+        summary.imported,
+        summary.created_projects,
+        summary.created_categories,
+        summary.created_tasks
+    );
+    let describe = |conflict: import::Conflict| match conflict {
+        import::Conflict::Exact => "exact duplicate",
+        import::Conflict::Overlap => "overlaps existing interval",
+        import::Conflict::Running => "open interval while another task is running",
+        import::Conflict::EndsBeforeStart => "ends before it starts",
+    };
+    for (heading, records) in [
+        ("Skipped", &summary.skipped),
+        ("Imported possible duplicates,", &summary.flagged),
+    ] {
+        if records.is_empty() {
+            continue;
+        }
+        println!("{heading} {}:", records.len());
+        let mut table = record_table(&["Conflict"]);
+        for (index, (record, conflict)) in records.iter().enumerate() {
+            let mut cells = record_cells(index, record, Paint::Plain);
+            cells.push(Cell::from(describe(*conflict)).paint(Paint::Yellow));
+            table.row(cells);
+        }
+        table.print();
+    }
+}
+
+/// Columns that [`record_cells`] fills.
+const RECORD_COLUMNS: [&str; 5] = ["#", "Date", "Time", "Project", "Task"];
+
+/// Table of export records with `extra` columns after the record's own.
+fn record_table(extra: &[&str]) -> Table {
+    let headings: Vec<&str> = RECORD_COLUMNS.iter().chain(extra).copied().collect();
+    Table::new(&headings).right(&[0])
+}
+
+/// Cells of an export record, numbered from 1, with the project and task in `paint`.
+fn record_cells(index: usize, record: &export::ExportRecord, paint: Paint) -> Vec<Cell> {
+    let (date, time) = match (record.start, record.end) {
+        (Some(start), Some(end)) => (
+            start.format("%Y-%m-%d").to_string(),
+            format!("{}-{}", start.format("%H:%M"), end.format("%H:%M")),
+        ),
+        (Some(start), None) => (
+            start.format("%Y-%m-%d").to_string(),
+            format!("{}-open", start.format("%H:%M")),
+        ),
+        _ => ("not started".to_string(), String::new()),
+    };
+    vec![
+        Cell::from(format!("{})", index + 1)).paint(Paint::Dim),
+        date.into(),
+        time.into(),
+        Cell::from(record.project.as_str()).paint(paint),
+        Cell::from(record.task.as_str()).paint(paint),
+    ]
+}
+
+/// Backs up the database before a command that changes a lot at once and says where to.
+fn announce_backup(reason: &str, conn: &Connection) -> Result<()> {
+    if let Some(backup) = crate::backup::before(reason, conn)? {
+        println!("{}", backup.describe());
+    }
+    Ok(())
+}
+
+fn handle_doctor(fix: bool, split_at_midnight: bool, conn: &Connection) -> Result<()> {
+    let findings = doctor::scan(conn, split_at_midnight)?;
+    if findings.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+    let plural = if findings.len() == 1 { "" } else { "s" };
+    println!("Found {} problem{plural}:", findings.len());
+    for (index, finding) in findings.iter().enumerate() {
+        println!("{:>3}) {}", index + 1, finding.description);
+        println!("     Repair: {}", finding.repair.describe());
+    }
+    let ask = !fix && io::stdin().is_terminal() && !db::is_read_only(conn);
+    if !fix && !ask {
+        let split = if split_at_midnight {
+            " --split-midnight"
+        } else {
+            ""
+        };
+        println!("Run `tickr doctor{split} --fix` to repair them.");
+        return Ok(());
+    }
+
+    println!();
+    announce_backup("doctor", conn)?;
+    // Repairs change what the other checks find, so the database is scanned again after each.
+    // Problems that were skipped, failed or came back are not offered a second time.
+    let mut done: HashSet<String> = HashSet::new();
+    let mut repaired = 0;
+    let mut fix_rest = fix;
+    let mut progress = Progress::new("Repairing", findings.len());
+    while let Some(finding) = doctor::scan(conn, split_at_midnight)?
+        .into_iter()
+        .find(|finding| !done.contains(&finding.description))
+    {
+        done.insert(finding.description.clone());
+        if fix_rest {
+            progress.set(done.len() - 1);
+        } else {
+            println!("{}", finding.description);
+            let question = format!(
+                "    Repair: {}? [y]es / [N]o / [a]ll remaining / [q]uit: ",
+                finding.repair.describe()
+            );
+            match prompt(&question)?.as_str() {
+                "y" | "yes" => {}
+                "a" | "all" => fix_rest = true,
+                "q" | "quit" => break,
+                _ => continue,
+            }
+        }
+        let result = finding.repair.apply(conn);
+        progress.clear();
+        match result {
+            Ok(()) => {
+                repaired += 1;
+                if fix {
+                    println!("Repaired: {}", finding.repair.describe());
+                }
+            }
+            Err(err) => println!("Could not {}: {err}", finding.repair.describe()),
+        }
+    }
+    drop(progress);
+    let left = doctor::scan(conn, split_at_midnight)?.len();
+    println!(
+        "Repaired {repaired} problem{}, {left} left.",
+        if repaired == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+fn review_suggestions(
+    suggestions: Vec<import::SuggestedInterval>,
+) -> Result<Vec<import::SuggestedInterval>> {
+    let mut accepted = Vec::new();
+    let mut accept_rest = false;
+    for (index, suggestion) in suggestions.into_iter().enumerate() {
+        println!("{}", describe_suggestion(index, &suggestion));
+        if accept_rest {
+            accepted.push(suggestion);
+            continue;
+        }
+        match prompt("    Keep? [Y]es / [n]o / [a]ll remaining / [q]uit: ")?.as_str() {
+            "" | "y" | "yes" => accepted.push(suggestion),
+            "a" | "all" => {
+                accept_rest = true;
+                accepted.push(suggestion);
+            }
+            "q" | "quit" => return Ok(Vec::new()),
+            _ => {}
+        }
+    }
+    Ok(accepted)
+}
+
+fn describe_suggestion(index: usize, suggestion: &import::SuggestedInterval) -> String {
+    let duration = crate::ui::format_duration(suggestion.end - suggestion.start);
+    let detail = suggestion
+        .detail
+        .as_deref()
+        .map(|title| {
+            let short: String = title.chars().take(60).collect();
+            format!("  {short}")
+        })
+        .unwrap_or_default();
+    format!(
+        "{:>3}) {} {}-{} ({duration})  {}{detail}",
+        index + 1,
+        suggestion.start.format("%Y-%m-%d"),
+        suggestion.start.format("%H:%M"),
+        suggestion.end.format("%H:%M"),
+        suggestion.task,
+    )
+}
+
+/// The project called `name`. When there is none, offers the closest spelling on a terminal and
+/// lists close matches otherwise; `None` after printing why when no project is picked.
+fn resolve_project(name: &str, conn: &Connection) -> Result<Option<types::Project>> {
+    let mut projects = db::query_projects(conn)?;
+    let exact: Vec<usize> = (0..projects.len())
+        .filter(|index| projects[*index].name == name)
+        .collect();
+    if exact.is_empty()
+        && let Some(project) = db::query_project_by_alias(name, conn)?
+    {
+        return Ok(Some(project));
+    }
+    let index = match exact.as_slice() {
+        [index] => Some(*index),
+        [] => {
+            let names: Vec<&str> = projects
+                .iter()
+                .map(|project| project.name.as_str())
+                .collect();
+            confirm_suggestion(name, &format!("Project '{name}' not found"), &names)?
+        }
+        _ => {
+            println!(
+                "Multiple projects found with the same name, cannot determine which one to use"
+            );
+            None
+        }
+    };
+    Ok(index.map(|index| projects.swap_remove(index)))
+}
+
+/// The client called `name` in any case, offering the closest spelling like
+/// [`resolve_project`]; `None` after printing why when no client is picked.
+fn resolve_client(name: &str, conn: &Connection) -> Result<Option<types::Client>> {
+    let mut clients = db::query_clients(conn)?;
+    if let Some(index) = clients
+        .iter()
+        .position(|client| client.name.eq_ignore_ascii_case(name))
+    {
+        return Ok(Some(clients.swap_remove(index)));
+    }
+    let names: Vec<&str> = clients.iter().map(|client| client.name.as_str()).collect();
+    let index = confirm_suggestion(name, &format!("Client '{name}' not found"), &names)?;
+    Ok(index.map(|index| clients.swap_remove(index)))
+}
+
+fn resolve_template(name: &str, conn: &Connection) -> Result<Option<types::TaskTemplate>> {
+    let mut templates = db::query_templates(conn)?;
+    if let Some(index) = templates
+        .iter()
+        .position(|template| template.name.eq_ignore_ascii_case(name))
+    {
+        return Ok(Some(templates.swap_remove(index)));
+    }
+    let names: Vec<&str> = templates
+        .iter()
+        .map(|template| template.name.as_str())
+        .collect();
+    let index = confirm_suggestion(name, &format!("Template '{name}' not found"), &names)?;
+    Ok(index.map(|index| templates.swap_remove(index)))
+}
+
+/// After `not_found`, asks whether the one of `names` closest to `name` was meant and returns
+/// its index when confirmed. Without a terminal to ask on, the close matches are only listed.
+fn confirm_suggestion(name: &str, not_found: &str, names: &[&str]) -> Result<Option<usize>> {
+    let suggestions = crate::fuzzy::suggestions(name, names);
+    let Some(&best) = suggestions.first() else {
+        println!("{not_found}");
+        return Ok(None);
+    };
+    if !io::stdin().is_terminal() {
+        let close: Vec<String> = suggestions
+            .iter()
+            .take(3)
+            .map(|index| format!("'{}'", names[*index]))
+            .collect();
+        println!("{not_found}, did you mean {}?", close.join(" or "));
+        return Ok(None);
+    }
+    let answer = prompt(&format!(
+        "{not_found}, did you mean '{}'? [y/N] ",
+        names[best]
+    ))?;
+    Ok(matches!(answer.as_str(), "y" | "yes").then_some(best))
+}
+
+fn prompt(question: &str) -> Result<String> {
+    print!("{question}");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_lowercase())
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| anyhow::anyhow!("Invalid date '{value}', expected YYYY-MM-DD"))
+}
+
 fn parse_optional_datetime(value: Option<String>) -> Result<Option<DateTime<Local>>> {
     match value {
         Some(s) => {
@@ -0,0 +1,793 @@
+/// User configuration loaded from `config.toml` in the Tickr config directory.
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Weekday};
+use serde::Deserialize;
+
+use crate::app::{Action, AppView, KeyBinding};
+use crate::billing::BillingRules;
+use crate::export::ExportFormat;
+
+/// Environment variable that points to a config file in a non-default location.
+pub const CONFIG_PATH_ENV: &str = "TICKR_CONFIG";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// View the TUI opens into.
+    pub default_view: StartView,
+    /// Milliseconds between redraws of the TUI.
+    pub tick_rate_ms: u64,
+    /// chrono format string for dates shown in the TUI.
+    pub date_format: String,
+    /// Show interval times with the weekday, as "today" for today and without the date for the end
+    /// of an interval on the day it started.
+    pub smart_dates: bool,
+    /// First day of the week; unset means weeks are the last seven days.
+    pub week_start: Option<Weekday>,
+    /// Database file, used unless `--db` or `TICKR_DB` is given.
+    pub db: Option<PathBuf>,
+    /// Color theme of the TUI.
+    pub theme: ThemeName,
+    /// Header of PDF timesheets (`[timesheet]` table).
+    pub timesheet: TimesheetConfig,
+    /// Saved options of `tickr export <profile>` (`[export.profiles.<name>]` tables).
+    pub export: ExportConfig,
+    /// Default rate and rounding (`[billing]` table), overridable per project.
+    pub billing: BillingRules,
+    /// Hours you aim to track per month, compared with the forecast in the Stats view.
+    pub monthly_goal_hours: Option<f64>,
+    /// Hours you aim to work per day; the TUI shows what is left of them and warns once over.
+    pub daily_budget_hours: Option<f64>,
+    /// Send a desktop notification when a project goal is reached, besides the footer banner.
+    pub goal_notifications: bool,
+    /// Split ended intervals at local midnight, so each one lies on a single day.
+    pub split_at_midnight: bool,
+    /// Web address of linked `owner/repo#123` issues, with `{repo}` and `{number}` placeholders.
+    pub issue_url: String,
+    /// Self-hosted GitHub Enterprise or GitLab servers that may get `GITHUB_TOKEN` or
+    /// `GITLAB_TOKEN` when looking up titles, besides github.com and gitlab.com.
+    pub issue_token_hosts: Vec<String>,
+    /// Reminder to start tracking during working hours (`[reminder]` table).
+    pub reminder: ReminderConfig,
+    /// When the TUI stops tasks left running (`[auto_stop]` table).
+    pub auto_stop: AutoStopConfig,
+    /// Actions the TUI asks about before doing them (`[confirm]` table).
+    pub confirm: ConfirmConfig,
+    /// Hidden columns and column widths of the Projects and Tickrs tables (`[columns]` table).
+    pub columns: ColumnsConfig,
+    /// Commands run when tasks start, stop or switch (`[hooks]` table).
+    pub hooks: HooksConfig,
+    /// Key bindings that replace the defaults (`[keys]` table).
+    pub keys: HashMap<Action, KeyBinding>,
+    /// Named databases chosen with `--profile` or in the TUI (`[profiles.<name>]` tables).
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Structures for `tickr project add --template` (`[project_templates.<name>]` tables).
+    pub project_templates: BTreeMap<String, ProjectTemplateConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_view: StartView::default(),
+            tick_rate_ms: 100,
+            date_format: "%Y-%m-%d".to_string(),
+            smart_dates: true,
+            week_start: None,
+            db: None,
+            theme: ThemeName::default(),
+            timesheet: TimesheetConfig::default(),
+            export: ExportConfig::default(),
+            billing: BillingRules::default(),
+            monthly_goal_hours: None,
+            daily_budget_hours: None,
+            goal_notifications: false,
+            split_at_midnight: false,
+            issue_url: crate::issue::DEFAULT_ISSUE_URL.to_string(),
+            issue_token_hosts: Vec::new(),
+            reminder: ReminderConfig::default(),
+            auto_stop: AutoStopConfig::default(),
+            confirm: ConfirmConfig::default(),
+            columns: ColumnsConfig::default(),
+            hooks: HooksConfig::default(),
+            keys: HashMap::new(),
+            profiles: BTreeMap::new(),
+            project_templates: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Database of profile `name`, or without one the `db` setting unless `TICKR_DB` is set,
+    /// else the default location.
+    pub fn db_path(&self, profile: Option<&str>) -> Result<String> {
+        if let Some(name) = profile {
+            let Some(profile) = self.profiles.get(name) else {
+                if self.profiles.is_empty() {
+                    bail!("No profile '{name}': the config file has no [profiles.{name}] table");
+                }
+                let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                bail!(
+                    "No profile '{name}', the config file has {}",
+                    names.join(", ")
+                );
+            };
+            return Ok(profile.db.to_string_lossy().into_owned());
+        }
+        let env_set =
+            std::env::var(crate::db::DB_PATH_ENV).is_ok_and(|value| !value.trim().is_empty());
+        Ok(match &self.db {
+            Some(path) if !env_set => path.to_string_lossy().into_owned(),
+            _ => crate::db::default_db_path(),
+        })
+    }
+}
+
+impl Config {
+    pub fn tick_rate(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.tick_rate_ms.clamp(10, 1000))
+    }
+
+    /// First day of the week that ends with or contains `date`.
+    pub fn week_start_for(&self, date: NaiveDate) -> NaiveDate {
+        match self.week_start {
+            Some(start) => date - Duration::days(date.weekday().days_since(start).into()),
+            None => date - Duration::days(6),
+        }
+    }
+
+    /// A time in lists of intervals, e.g. "Mon 2024-05-13 09:10" or "today 09:10".
+    pub fn format_moment(&self, time: DateTime<Local>) -> String {
+        if !self.smart_dates {
+            return time
+                .format(&format!("{} %H:%M", self.date_format))
+                .to_string();
+        }
+        if time.date_naive() == Local::now().date_naive() {
+            return time.format("today %H:%M").to_string();
+        }
+        time.format(&format!("%a {} %H:%M", self.date_format))
+            .to_string()
+    }
+
+    /// An interval from `start` to `end`, e.g. "Mon 2024-05-13 09:10 → 11:45"; "open" while it
+    /// runs.
+    pub fn format_interval(&self, start: DateTime<Local>, end: Option<DateTime<Local>>) -> String {
+        let end = match end {
+            None => "open".to_string(),
+            Some(end) if !self.smart_dates => self.format_moment(end),
+            Some(end) if end.date_naive() == start.date_naive() => end.format("%H:%M").to_string(),
+            Some(end) => self.format_moment(end),
+        };
+        let arrow = if self.smart_dates { "→" } else { "->" };
+        format!("{} {arrow} {end}", self.format_moment(start))
+    }
+
+    /// First day of the calendar week containing `date`, Monday unless `week_start` says otherwise.
+    pub fn calendar_week_start_for(&self, date: NaiveDate) -> NaiveDate {
+        let start = self.week_start.unwrap_or(Weekday::Mon);
+        date - Duration::days(date.weekday().days_since(start).into())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// The terminal's own text color and background, with its colors for the rest.
+    #[default]
+    Terminal,
+    /// White text for dark backgrounds, whatever the terminal uses.
+    Dark,
+    /// Darker colors for terminals with a light background.
+    Light,
+    /// Only black, white and bold, for monochrome displays.
+    Mono,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[cfg_attr(not(feature = "pdf"), allow(dead_code))]
+pub struct TimesheetConfig {
+    /// Name printed in the header, e.g. yours or your company's.
+    pub name: Option<String>,
+    /// JPEG image printed in the top left corner.
+    pub logo: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReminderConfig {
+    /// Minutes without a running task before the reminder shows; unset turns it off.
+    pub after_minutes: Option<u32>,
+    /// Start of working hours as `HH:MM`.
+    pub start: String,
+    /// End of working hours as `HH:MM`.
+    pub end: String,
+    /// Working days.
+    pub days: Vec<Weekday>,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            after_minutes: None,
+            start: "09:00".to_string(),
+            end: "17:00".to_string(),
+            days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+        }
+    }
+}
+
+/// Stops tasks left running, e.g. overnight. Off while neither rule is set.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AutoStopConfig {
+    /// Time of day as `HH:MM` by which a running task stops.
+    pub at: Option<String>,
+    /// Hours a task may run in one go before it stops.
+    pub after_hours: Option<f64>,
+}
+
+impl AutoStopConfig {
+    /// When the rules stop a task running since `start`: the first `at` after it started or
+    /// `after_hours` later, whichever comes first. `None` until that moment has passed at `now`.
+    pub fn stop_time(
+        &self,
+        start: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        let at = self.at.as_deref().and_then(|at| {
+            let day = start.date_naive();
+            match crate::ui::parse_local_datetime(at, day)? {
+                same_day if same_day > start => Some(same_day),
+                _ => crate::ui::parse_local_datetime(at, day.succ_opt()?),
+            }
+        });
+        let after = self
+            .after_hours
+            .filter(|hours| *hours > 0.0)
+            .map(|hours| start + Duration::seconds((hours * 3600.0).round() as i64));
+        let stop = at.into_iter().chain(after).min()?;
+        (stop <= now).then_some(stop)
+    }
+}
+
+/// Which actions ask first. Deleting does by default, stopping and quitting don't.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfirmConfig {
+    pub delete_task: bool,
+    /// Deleting an interval in the day agenda.
+    pub delete_interval: bool,
+    /// Stopping the running task with `space` or `s`.
+    pub stop: bool,
+    /// Quitting while a task runs; it keeps running either way.
+    pub quit: bool,
+}
+
+impl Default for ConfirmConfig {
+    fn default() -> Self {
+        Self {
+            delete_task: true,
+            delete_interval: true,
+            stop: false,
+            quit: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    pub profiles: BTreeMap<String, ExportProfileConfig>,
+}
+
+/// Options of `tickr export` saved under a name, used where the command line gives none.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportProfileConfig {
+    pub format: Option<ExportFormat>,
+    /// File written instead of stdout.
+    pub output: Option<String>,
+    /// Only intervals from locked weeks.
+    pub approved: bool,
+    /// Only the projects of this client.
+    pub client: Option<String>,
+}
+
+/// A named database, e.g. for work and personal time.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub db: PathBuf,
+}
+
+/// What a new project made from a template starts with.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectTemplateConfig {
+    /// Created when they don't exist yet, besides the categories of the tasks.
+    pub categories: Vec<String>,
+    pub tasks: Vec<ProjectTemplateTask>,
+    /// Rate and rounding of the project, as `tickr project set` gives them.
+    pub billing: BillingRules,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectTemplateTask {
+    pub name: String,
+    pub category: Option<String>,
+    #[serde(default = "billable_default")]
+    pub billable: bool,
+}
+
+fn billable_default() -> bool {
+    true
+}
+
+/// Shell commands run in the background, see [`crate::hooks`].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    pub on_start: Option<String>,
+    pub on_stop: Option<String>,
+    /// Runs instead of `on_stop` and `on_start` when one task replaces another.
+    pub on_switch: Option<String>,
+}
+
+impl HooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self.on_start.is_none() && self.on_stop.is_none() && self.on_switch.is_none()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColumnsConfig {
+    pub projects: TableColumns<ProjectColumn>,
+    pub tickrs: TableColumns<TickrColumn>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectColumn {
+    Project,
+    Total,
+    End,
+    Open,
+    /// Progress towards the project's goal.
+    Goal,
+    Client,
+}
+
+impl ProjectColumn {
+    pub const ALL: [Self; 6] = [
+        Self::Project,
+        Self::Total,
+        Self::End,
+        Self::Open,
+        Self::Goal,
+        Self::Client,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TickrColumn {
+    Task,
+    State,
+    Category,
+    Intervals,
+    /// When the task was last worked on, e.g. "2h ago".
+    Last,
+    Time,
+}
+
+impl TickrColumn {
+    pub const ALL: [Self; 6] = [
+        Self::Task,
+        Self::State,
+        Self::Category,
+        Self::Intervals,
+        Self::Last,
+        Self::Time,
+    ];
+}
+
+/// Columns of one table to hide, and widths that replace the default of a column.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableColumns<C: Eq + std::hash::Hash> {
+    pub hide: Vec<C>,
+    pub widths: HashMap<C, u16>,
+}
+
+impl<C: Eq + std::hash::Hash> Default for TableColumns<C> {
+    fn default() -> Self {
+        Self {
+            hide: Vec::new(),
+            widths: HashMap::new(),
+        }
+    }
+}
+
+impl<C: Copy + Eq + std::hash::Hash> TableColumns<C> {
+    pub fn shows(&self, column: C) -> bool {
+        !self.hide.contains(&column)
+    }
+
+    pub fn width(&self, column: C) -> Option<u16> {
+        self.widths.get(&column).copied()
+    }
+}
+
+impl ReminderConfig {
+    /// Start of the untracked stretch when it is time to remind at `now`: the later of
+    /// `last_tracked` and the start of today's working hours. `None` while the reminder is off,
+    /// outside of working hours or before `after_minutes` have passed.
+    pub fn overdue(
+        &self,
+        last_tracked: Option<DateTime<Local>>,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        let after = Duration::minutes(self.after_minutes.filter(|minutes| *minutes > 0)?.into());
+        if !self.days.contains(&now.weekday()) {
+            return None;
+        }
+        let today = now.date_naive();
+        let start = crate::ui::parse_local_datetime(&self.start, today)?;
+        let end = crate::ui::parse_local_datetime(&self.end, today)?;
+        if now < start || now >= end {
+            return None;
+        }
+        let since = last_tracked.map_or(start, |last| last.max(start));
+        (now - since >= after).then_some(since)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartView {
+    #[default]
+    Dashboard,
+    Projects,
+    #[serde(alias = "tasks")]
+    Tickrs,
+    Worked,
+    Timeline,
+    Calendar,
+    Categories,
+    Stats,
+    /// The tab that was active when Tickr was last closed.
+    Last,
+}
+
+impl StartView {
+    const TABS: [(StartView, &'static str, AppView); 8] = [
+        (StartView::Dashboard, "dashboard", AppView::Dashboard),
+        (StartView::Projects, "projects", AppView::Projects),
+        (StartView::Tickrs, "tickrs", AppView::Tickrs),
+        (StartView::Worked, "worked", AppView::WorkedProjects),
+        (StartView::Timeline, "timeline", AppView::Timeline),
+        (StartView::Calendar, "calendar", AppView::Calendar),
+        (StartView::Categories, "categories", AppView::Categories),
+        (StartView::Stats, "stats", AppView::Stats),
+    ];
+
+    /// Resolves the view to open, reading the remembered tab for `Last`.
+    pub fn resolve(self) -> AppView {
+        let start = match self {
+            StartView::Last => load_last_view().unwrap_or_default(),
+            other => other,
+        };
+        Self::TABS
+            .iter()
+            .find(|(view, _, _)| *view == start)
+            .map(|(_, _, app_view)| app_view.clone())
+            .unwrap_or(AppView::Dashboard)
+    }
+}
+
+/// Written by `tickr config init`: every setting, commented out at its default.
+const SAMPLE_CONFIG: &str = r#"# Tickr configuration. Every setting is optional and shown at its default value;
+# uncomment a line to change it.
+
+# View the TUI opens into: dashboard, projects, tickrs (or tasks), worked, timeline, calendar,
+# categories, stats, or last to reopen the tab that was active when Tickr was closed.
+# default_view = "dashboard"
+
+# Hours to track per month; the Stats tab compares its forecast with this goal.
+# monthly_goal_hours = 120
+
+# Hours to work per day; the Dashboard and the footer show what is left of them today or the
+# overtime, and a desktop notification comes when a running task goes past them.
+# daily_budget_hours = 8
+
+# The footer celebrates for a moment when today's time reaches the daily budget or a project
+# reaches its goal. Also send a desktop notification for project goals (the daily budget has its
+# own notification).
+# goal_notifications = false
+
+# Split intervals that run past midnight into one interval per day once they end, e.g. for
+# timesheets that expect every interval on a single day. Intervals in locked weeks stay as they
+# are. `tickr doctor --split-midnight` splits existing ones without turning this on.
+# split_at_midnight = false
+
+# Milliseconds between redraws (10-1000).
+# tick_rate_ms = 100
+
+# chrono format for dates in the detail and timeline views.
+# date_format = "%Y-%m-%d"
+
+# Show interval times as "Mon 2024-05-13 09:10 → 11:45": with the weekday, "today" instead of
+# today's date and no date for an end on the day the interval started.
+# smart_dates = true
+
+# First day of the week for the timeline and the worked "this week" range.
+# Without it, a week is the last seven days (and locked weeks start on Monday).
+# week_start = "monday"
+
+# Database file, used unless --db or TICKR_DB is given.
+# db = "/path/to/tickr.db"
+
+# Color theme: terminal (its own text color and background), dark (white text for dark
+# backgrounds), light (for light terminal backgrounds) or mono.
+# theme = "terminal"
+
+# Where issue references like owner/repo#123 on tasks point. For GitLab use
+# "https://gitlab.com/{repo}/-/issues/{number}". Full issue URLs open as they are.
+# issue_url = "https://github.com/{repo}/issues/{number}"
+
+# Titles of issues are looked up with GITHUB_TOKEN or GITLAB_TOKEN from the environment, which
+# only go to github.com and gitlab.com unless their own servers are listed here.
+# issue_token_hosts = ["git.example.com"]
+
+# Reminder to start tracking: when no task has run for after_minutes during working hours,
+# the TUI shows a banner and `tickr daemon` sends a desktop notification. Off while unset.
+[reminder]
+# after_minutes = 15
+# start = "09:00"
+# end = "17:00"
+# days = ["mon", "tue", "wed", "thu", "fri"]
+
+# Stops a task left running, e.g. overnight: at a time of day, or once it ran for after_hours in
+# one go. The TUI applies it when it starts and while it runs, and offers to move the end back to
+# the last key press. Off while unset.
+[auto_stop]
+# at = "23:59"
+# after_hours = 12
+
+# Actions the TUI asks about first. Turn the deletions off to skip their popups, or turn stop
+# and quit on for a popup before stopping the running task or quitting while it runs.
+[confirm]
+# delete_task = true
+# delete_interval = true
+# stop = false
+# quit = false
+
+# Columns of the Projects and Tickrs tables. `hide` removes columns, `widths` gives a column a
+# fixed width; the name column otherwise takes the space the others leave.
+# Projects columns: project, total, end, open, goal (only while a project has a goal), client
+# (only while a project has a client).
+# Tickrs columns: task, state, category, intervals, last, time.
+[columns.projects]
+# hide = ["end", "open"]
+# widths = { project = 40 }
+
+[columns.tickrs]
+# hide = ["intervals"]
+# widths = { category = 24 }
+
+# Shell commands run when a task starts or stops. They get TICKR_EVENT, TICKR_TASK, TICKR_TASK_ID,
+# TICKR_PROJECT and TICKR_DURATION_SECONDS (of the stopped interval), and the same as JSON on
+# stdin. on_switch runs instead of on_stop and on_start when one task replaces another, with
+# TICKR_PREVIOUS_TASK and TICKR_PREVIOUS_PROJECT as well. Output is discarded.
+[hooks]
+# on_start = "notify-send \"Tracking $TICKR_TASK\""
+# on_stop = "~/bin/log-time.sh"
+# on_switch = "~/bin/set-chat-status.sh"
+
+# Default hourly rate and rounding of billable time. Every interval is rounded on its own,
+# up, down or to the nearest step. Override them per project with `tickr project set`.
+[billing]
+# hourly_rate = 80.0
+# rounding_minutes = 0
+# rounding = "up"
+# Three-letter currency code of the rate; projects can bill in another one.
+# currency = "EUR"
+
+# Key bindings, e.g. for non-QWERTY layouts. A rebound action loses its default key, and a
+# list binds several keys: start_stop = ["space", "enter"]. Keys are single characters or
+# names: space, enter, esc, tab, backtab, backspace, delete, insert, up, down, left, right,
+# home, end, pageup, pagedown, f1-f12, each optionally after ctrl+, alt+ or shift+, e.g.
+# "ctrl+p". Popups and text fields keep their keys and ignore Ctrl and Alt combinations.
+[keys]
+# quit = "q"
+# dashboard = "h"
+# projects = "p"
+# tasks = "t"
+# worked = "w"
+# timeline = "l"
+# calendar = "m"
+# agenda = "D"
+# categories = "c"
+# stats = "S"
+# legend = "L"
+# activity_log = "H"
+# profiles = "W"
+# help = "?"
+# search = "/"
+# capture = "N"
+# triage = "I"
+# templates = "U"
+# refresh = "r"
+# start_stop = "space"
+# stop = "s"
+# resume = "R"
+# switcher = "ctrl+p"
+# go_to = "g"
+# previous_page = "["
+# next_page = "]"
+# back = "esc"
+# edit = "e"
+# add_interval = "a"
+# notes = "E"
+# toggle_billable = "b"
+# toggle_done = "x"
+# blocked_by = "B"
+# archive = "A"
+# show_archived = "V"
+# collapse = "-"
+# expand = "+"
+# delete = "d"
+# new = "n"
+# new_from_git = "G"
+# goal = "T"
+# client = "C"
+# sort = "o"
+# filter = "f"
+# toggle_focus = "tab"
+# up = "up"
+# down = "down"
+# left = "left"
+# right = "right"
+# open = "enter"
+# open_issue = "O"
+# pomodoro_start = "P"
+# pomodoro_skip = ">"
+# pomodoro_cancel = "X"
+
+# Header of PDF timesheets.
+[timesheet]
+# name = "Jane Doe Consulting"
+# logo = "/path/to/logo.jpg"   # JPEG only
+
+# Saved export options: `tickr export acme` writes this client's approved weeks as a PDF to
+# the file below. Options given on the command line win over the profile's.
+# [export.profiles.acme]
+# format = "pdf"
+# output = "/path/to/acme-timesheet.pdf"
+# approved = true
+# client = "Acme Corp"
+
+# Named databases, e.g. to keep work and personal time apart. `tickr --profile work` opens one
+# and W switches between them in the TUI; without a profile the `db` setting applies.
+# [profiles.work]
+# db = "/path/to/work.db"
+# [profiles.personal]
+# db = "/path/to/personal.db"
+
+# Projects made with `tickr project new <name> --template consulting` get these tasks and
+# categories (created when missing) and this rate and rounding.
+# [project_templates.consulting]
+# categories = ["Meetings", "Development"]
+# tasks = [
+#     { name = "Kickoff", category = "Meetings" },
+#     { name = "Development", category = "Development" },
+#     { name = "Travel", billable = false },
+# ]
+# billing = { hourly_rate = 120.0, rounding_minutes = 15, rounding = "up" }
+"#;
+
+pub fn config_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(CONFIG_PATH_ENV)
+        && !path.trim().is_empty()
+    {
+        return Some(PathBuf::from(path));
+    }
+    dirs::config_dir().map(|dir| dir.join("tickr").join("config.toml"))
+}
+
+/// Loads the config file, falling back to defaults when it does not exist.
+pub fn load() -> Result<Config> {
+    let Some(path) = config_path() else {
+        return Ok(Config::default());
+    };
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let raw = std::fs::read_to_string(&path)?;
+    let config: Config =
+        toml::from_str(&raw).with_context(|| format!("Invalid config file {}", path.display()))?;
+    if StrftimeItems::new(&config.date_format).any(|item| item == Item::Error) {
+        bail!(
+            "Invalid config file {}: date_format '{}' is not a valid chrono format",
+            path.display(),
+            config.date_format
+        );
+    }
+    for time in [&config.reminder.start, &config.reminder.end] {
+        if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+            bail!(
+                "Invalid config file {}: reminder time '{time}' is not HH:MM",
+                path.display()
+            );
+        }
+    }
+    if let Some(time) = &config.auto_stop.at
+        && NaiveTime::parse_from_str(time, "%H:%M").is_err()
+    {
+        bail!(
+            "Invalid config file {}: auto_stop time '{time}' is not HH:MM",
+            path.display()
+        );
+    }
+    Ok(config)
+}
+
+/// Writes the commented sample config to the config path and returns that path. An existing
+/// file is only replaced with `force`.
+pub fn write_sample(force: bool) -> Result<PathBuf> {
+    let Some(path) = config_path() else {
+        bail!("No config directory found, set {CONFIG_PATH_ENV} to choose a file");
+    };
+    if path.exists() && !force {
+        bail!(
+            "{} already exists, use --force to replace it",
+            path.display()
+        );
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, SAMPLE_CONFIG)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn last_view_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("tickr").join("last_view"))
+}
+
+fn load_last_view() -> Option<StartView> {
+    let raw = std::fs::read_to_string(last_view_path()?).ok()?;
+    StartView::TABS
+        .iter()
+        .find(|(_, name, _)| *name == raw.trim())
+        .map(|(view, _, _)| *view)
+}
+
+/// Remembers the tab `view` belongs to for `default_view = "last"`.
+pub fn save_last_view(view: &AppView) -> Result<()> {
+    let Some(path) = last_view_path() else {
+        return Ok(());
+    };
+    let Some((_, name, _)) = StartView::TABS.iter().find(|(_, _, tab)| tab == view) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, name)?;
+    Ok(())
+}
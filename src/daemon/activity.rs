@@ -0,0 +1,38 @@
+/// System-wide user activity detection.
+use std::time::Duration;
+
+/// Returns how long the user has been idle (no keyboard/mouse input), if the platform
+/// exposes it. Linux uses `xprintidle`, macOS reads `HIDIdleTime` from `ioreg`.
+pub fn system_idle_time() -> Option<Duration> {
+    platform_idle_time()
+}
+
+#[cfg(target_os = "linux")]
+fn platform_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("xprintidle").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let millis = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_millis(millis))
+}
+
+#[cfg(target_os = "macos")]
+fn platform_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let line = text.lines().find(|line| line.contains("\"HIDIdleTime\""))?;
+    let nanos = line.rsplit('=').next()?.trim().parse::<u64>().ok()?;
+    Some(Duration::from_nanos(nanos))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn platform_idle_time() -> Option<Duration> {
+    None
+}
@@ -0,0 +1,305 @@
+/// Headless background mode.
+///
+/// The daemon watches system-wide activity and, when the user comes back after a
+/// stretch of untracked active time, suggests logging that block retroactively. With a
+/// `[reminder]` configured it also reminds to start tracking during working hours.
+/// On Unix it serves a local socket that writes intervals for other Tickr processes.
+mod activity;
+mod request;
+#[cfg(unix)]
+mod socket;
+
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use rusqlite::Connection;
+
+use crate::config::ReminderConfig;
+use crate::db;
+use crate::hooks::{self, HookTask};
+use crate::types::{IntervalId, TickrId};
+use request::Request;
+
+#[derive(Clone, Debug)]
+pub struct DaemonOptions {
+    /// How often activity and the running task are checked.
+    pub poll: Duration,
+    /// Input-free time after which the user counts as idle.
+    pub idle_threshold: Duration,
+    /// Shortest untracked active stretch worth a suggestion.
+    pub min_block: Duration,
+    pub reminder: ReminderConfig,
+}
+
+/// Starts a task and ends whatever else runs, through the daemon while one runs for the
+/// database and directly otherwise. Then runs the start or switch hook. Returns a warning when
+/// the interval of the task that was running ends up holding no time.
+pub fn start_tickr(id: TickrId, conn: &Connection) -> Result<Option<db::IntervalError>> {
+    let now = chrono::Local::now();
+    let mut warning = None;
+    for other in running_tickrs(conn)?
+        .into_iter()
+        .filter(|other| *other != id)
+    {
+        warning = warning.or(db::running_end(other, now, conn)?.1);
+    }
+    let previous = if hooks::enabled() {
+        running_tickrs(conn)?
+            .into_iter()
+            .find(|other| *other != id)
+            .and_then(|other| HookTask::load(other, conn))
+    } else {
+        None
+    };
+    request::send(Request::Start { tickr_id: id }, conn)?;
+    if hooks::enabled() {
+        hooks::started(HookTask::load(id, conn), previous);
+    }
+    Ok(warning)
+}
+
+/// Stops a task, through the daemon while one runs, then runs the stop hook. Returns a warning
+/// when the interval ends up holding no time.
+pub fn end_tickr(id: TickrId, conn: &Connection) -> Result<Option<db::IntervalError>> {
+    let stopped = if hooks::enabled() {
+        HookTask::load(id, conn).filter(|task| task.duration_seconds.is_some())
+    } else {
+        None
+    };
+    // The daemon only answers with errors, so the warning comes from here.
+    let (_, warning) = db::running_end(id, chrono::Local::now(), conn)?;
+    request::send(Request::Stop { tickr_id: id }, conn)?;
+    hooks::stopped(stopped.as_ref());
+    Ok(warning)
+}
+
+/// Adds an interval to a task, running when it has no end, through the daemon while one runs.
+pub fn create_interval(
+    tickr_id: TickrId,
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    conn: &Connection,
+) -> Result<()> {
+    let request = Request::AddInterval {
+        tickr_id,
+        start,
+        end,
+    };
+    request::send(request, conn)
+}
+
+/// Moves the end of an interval, through the daemon while one runs.
+pub fn set_interval_end(id: IntervalId, end: DateTime<Local>, conn: &Connection) -> Result<()> {
+    let request = Request::EndInterval {
+        interval_id: id,
+        end,
+    };
+    request::send(request, conn)
+}
+
+/// Replaces the times of an interval, through the daemon while one runs.
+pub fn set_interval_times(
+    id: IntervalId,
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    conn: &Connection,
+) -> Result<()> {
+    let request = Request::SetIntervalTimes {
+        interval_id: id,
+        start,
+        end,
+    };
+    request::send(request, conn)
+}
+
+/// Deletes an interval, through the daemon while one runs.
+pub fn delete_interval(id: IntervalId, conn: &Connection) -> Result<()> {
+    request::send(Request::DeleteInterval { interval_id: id }, conn)
+}
+
+/// Cuts `[gap_start, gap_end)` out of the running interval of a task, through the daemon while
+/// one runs.
+pub fn split_running_interval(
+    tickr_id: TickrId,
+    gap_start: DateTime<Local>,
+    gap_end: DateTime<Local>,
+    conn: &Connection,
+) -> Result<()> {
+    let request = Request::CutRunning {
+        tickr_id,
+        gap_start,
+        gap_end,
+    };
+    request::send(request, conn)
+}
+
+/// Starts `id` and ends every other running task; a task that already runs keeps running.
+fn switch_to(id: TickrId, conn: &Connection) -> Result<()> {
+    let running = running_tickrs(conn)?;
+    for other in running.iter().filter(|other| **other != id) {
+        db::end_tickr(*other, conn)?;
+    }
+    if !running.contains(&id) {
+        db::start_tickr(id, conn)?;
+    }
+    Ok(())
+}
+
+fn running_tickrs(conn: &Connection) -> Result<Vec<TickrId>> {
+    Ok(conn
+        .prepare("SELECT DISTINCT entry_id FROM intervals WHERE end_time IS NULL")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?)
+}
+
+/// An untracked stretch of activity between `start` and `end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct UntrackedBlock {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+}
+
+/// Follows active/idle transitions while no task is running.
+#[derive(Default)]
+struct IdleWatcher {
+    untracked_since: Option<DateTime<Local>>,
+    pending: Option<UntrackedBlock>,
+    was_idle: bool,
+}
+
+impl IdleWatcher {
+    /// Feeds one observation and returns a block to suggest once the user returns from idle.
+    fn observe(
+        &mut self,
+        now: DateTime<Local>,
+        idle: Duration,
+        tracking: bool,
+        options: &DaemonOptions,
+    ) -> Option<UntrackedBlock> {
+        let is_idle = idle >= options.idle_threshold;
+        let last_input = now - chrono::Duration::from_std(idle).unwrap_or_default();
+        let mut suggestion = None;
+
+        if tracking {
+            self.untracked_since = None;
+            self.pending = None;
+        } else if is_idle {
+            if let Some(start) = self.untracked_since.take() {
+                let min_block = chrono::Duration::from_std(options.min_block).unwrap_or_default();
+                if last_input - start >= min_block {
+                    self.pending = Some(UntrackedBlock {
+                        start,
+                        end: last_input,
+                    });
+                }
+            }
+        } else {
+            if self.was_idle {
+                suggestion = self.pending.take();
+            }
+            if self.untracked_since.is_none() {
+                self.untracked_since = Some(last_input);
+            }
+        }
+
+        self.was_idle = is_idle;
+        suggestion
+    }
+}
+
+/// Runs the daemon loop until the process is terminated.
+pub fn run(options: DaemonOptions, conn: &Connection) -> Result<()> {
+    if activity::system_idle_time().is_none() {
+        println!(
+            "Idle detection is not available on this system (Linux needs `xprintidle`); \
+             untracked time suggestions are disabled."
+        );
+    }
+    #[cfg(unix)]
+    if let Some(db_path) = conn.path().filter(|path| !path.is_empty()) {
+        let socket = socket::serve(db_path)?;
+        println!("Writing intervals through {}.", socket.display());
+    }
+    println!("tickr daemon running, press Ctrl+C to stop.");
+
+    let mut watcher = IdleWatcher::default();
+    let mut last_reminder = None;
+    loop {
+        // Keeps `status --fast` right after changes made without Tickr, e.g. a synced database.
+        crate::status::write_cache(conn).ok();
+        let tracking = db::query_running_tickr(conn)?.is_some();
+        if let Some(idle) = activity::system_idle_time()
+            && let Some(block) = watcher.observe(Local::now(), idle, tracking, &options)
+        {
+            suggest_block(block, conn)?;
+        }
+        if !tracking {
+            // Repeats every `after_minutes` until a task runs.
+            let last_tracked = db::query_last_tracked_time(conn)?.max(last_reminder);
+            if let Some(since) = options.reminder.overdue(last_tracked, Local::now()) {
+                remind(since, conn)?;
+                last_reminder = Some(Local::now());
+            }
+        }
+        thread::sleep(options.poll);
+    }
+}
+
+fn remind(since: DateTime<Local>, conn: &Connection) -> Result<()> {
+    let last = match db::query_last_stopped_tickr(conn)? {
+        Some(tickr) => format!("\nLast task: {}", tickr.description),
+        None => String::new(),
+    };
+    crate::notify::send(
+        "Tickr: nothing tracked",
+        &format!(
+            "No task has been running since {}. What are you working on?{last}",
+            since.format("%H:%M")
+        ),
+    );
+    println!(
+        "{} reminded to start tracking",
+        Local::now().format("%H:%M")
+    );
+    Ok(())
+}
+
+fn suggest_block(block: UntrackedBlock, conn: &Connection) -> Result<()> {
+    let (project, task) = match db::query_last_stopped_tickr(conn)? {
+        Some(tickr) => {
+            let project = db::query_project_by_id(tickr.project_id, conn)?
+                .map(|project| project.name)
+                .unwrap_or_else(|| "<project>".to_string());
+            (project, tickr.description)
+        }
+        None => ("<project>".to_string(), "<task>".to_string()),
+    };
+    let duration = crate::ui::format_duration(block.end - block.start);
+    let command = format!(
+        "tickr task add {} {} --start {} --end {}",
+        shell_quote(&project),
+        shell_quote(&task),
+        block.start.to_rfc3339(),
+        block.end.to_rfc3339()
+    );
+    let body = format!(
+        "You were active {}-{} ({duration}) without tracking.\nLog it with:\n{command}",
+        block.start.format("%H:%M"),
+        block.end.format("%H:%M"),
+    );
+    crate::notify::send("Tickr: untracked time", &body);
+    println!(
+        "{} untracked block: {command}",
+        Local::now().format("%H:%M")
+    );
+    Ok(())
+}
+
+/// `value` as one shell word, in single quotes when it needs any.
+fn shell_quote(value: &str) -> String {
+    // A NUL byte is the only thing that can't be quoted, and no shell takes one anyway.
+    let value = value.replace('\0', "");
+    shlex::try_quote(&value).map(String::from).unwrap_or(value)
+}
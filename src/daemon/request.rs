@@ -0,0 +1,120 @@
+/// Writes to intervals that go through the daemon while one runs for the database, so one
+/// process puts them in order. Without a daemon, and off Unix, they are applied right away.
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::db;
+use crate::types::{Interval, IntervalId, TickrId};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub(super) enum Request {
+    /// Start a task, ending whatever else runs.
+    Start {
+        tickr_id: TickrId,
+    },
+    Stop {
+        tickr_id: TickrId,
+    },
+    /// Add an interval to a task, running when it has no end.
+    AddInterval {
+        tickr_id: TickrId,
+        start: DateTime<Local>,
+        end: Option<DateTime<Local>>,
+    },
+    EndInterval {
+        interval_id: IntervalId,
+        end: DateTime<Local>,
+    },
+    SetIntervalTimes {
+        interval_id: IntervalId,
+        start: DateTime<Local>,
+        end: Option<DateTime<Local>>,
+    },
+    DeleteInterval {
+        interval_id: IntervalId,
+    },
+    /// Cut `[gap_start, gap_end)` out of the running interval of a task.
+    CutRunning {
+        tickr_id: TickrId,
+        gap_start: DateTime<Local>,
+        gap_end: DateTime<Local>,
+    },
+}
+
+impl Request {
+    pub(super) fn apply(&self, conn: &Connection) -> Result<()> {
+        match *self {
+            Request::Start { tickr_id } => super::switch_to(tickr_id, conn),
+            Request::Stop { tickr_id } => db::end_tickr(tickr_id, conn).map(|_| ()),
+            Request::AddInterval {
+                tickr_id,
+                start,
+                end,
+            } => {
+                let interval = Interval {
+                    id: None,
+                    entry_id: tickr_id,
+                    start_time: start,
+                    end_time: end,
+                    notes: None,
+                };
+                db::create_interval(interval, conn).map(|_| ())
+            }
+            Request::EndInterval { interval_id, end } => {
+                db::set_interval_end(interval_id, end, conn)
+            }
+            Request::SetIntervalTimes {
+                interval_id,
+                start,
+                end,
+            } => db::set_interval_times(interval_id, start, end, conn),
+            Request::DeleteInterval { interval_id } => db::delete_interval(interval_id, conn),
+            Request::CutRunning {
+                tickr_id,
+                gap_start,
+                gap_end,
+            } => db::split_running_interval(tickr_id, gap_start, gap_end, conn),
+        }
+    }
+
+    /// What the request did, for the log of the daemon.
+    pub(super) fn describe(&self) -> String {
+        let time = |time: DateTime<Local>| time.format("%Y-%m-%d %H:%M").to_string();
+        match *self {
+            Request::Start { tickr_id } => format!("started task {tickr_id}"),
+            Request::Stop { tickr_id } => format!("stopped task {tickr_id}"),
+            Request::AddInterval {
+                tickr_id, start, ..
+            } => format!("added an interval from {} to task {tickr_id}", time(start)),
+            Request::EndInterval { interval_id, end } => {
+                format!("ended interval {interval_id} at {}", time(end))
+            }
+            Request::SetIntervalTimes { interval_id, .. } => {
+                format!("changed the times of interval {interval_id}")
+            }
+            Request::DeleteInterval { interval_id } => format!("deleted interval {interval_id}"),
+            Request::CutRunning {
+                tickr_id,
+                gap_start,
+                gap_end,
+            } => format!(
+                "cut {} - {} out of task {tickr_id}",
+                time(gap_start),
+                gap_end.format("%H:%M")
+            ),
+        }
+    }
+}
+
+/// Sends `request` to the daemon of the database behind `conn`, or applies it here when none
+/// runs.
+pub(super) fn send(request: Request, conn: &Connection) -> Result<()> {
+    #[cfg(unix)]
+    if let Some(sent) = super::socket::send(&request, conn) {
+        return sent;
+    }
+    request.apply(conn)
+}
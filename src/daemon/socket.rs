@@ -0,0 +1,96 @@
+/// Local socket of the daemon. While a daemon runs for a database, the TUI, the shell, the CLI
+/// and `doctor --fix` write intervals through it: starting and stopping tasks, adding, ending,
+/// changing and deleting intervals, and cutting idle time out of a running one. One process
+/// then decides which task runs, and two clients can't leave conflicting running intervals.
+/// Without a daemon they write the database directly.
+///
+/// Tasks, projects, notes and settings, splitting ended intervals at midnight, and the bulk
+/// writes of imports, batches and sync still go straight to the database, where SQLite's
+/// locking keeps the writes apart but not in the order the daemon would put them.
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use chrono::Local;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::request::Request;
+use crate::db;
+
+/// How long a client waits for the daemon before giving up.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Response {
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Socket of the daemon for the database at `db_path`.
+fn socket_path(db_path: &Path) -> PathBuf {
+    crate::lock::runtime_path(db_path, "sock")
+}
+
+fn database_path(conn: &Connection) -> Option<PathBuf> {
+    let path = conn.path().filter(|path| !path.is_empty())?;
+    std::fs::canonicalize(path).ok()
+}
+
+/// Listens on the socket of the database at `db_path` in a background thread with a
+/// connection of its own. Fails when another daemon already serves the database.
+pub fn serve(db_path: &str) -> Result<PathBuf> {
+    let path = socket_path(&std::fs::canonicalize(db_path)?);
+    if UnixStream::connect(&path).is_ok() {
+        bail!("Another tickr daemon already runs for {db_path}");
+    }
+    // A socket file left behind by a daemon that was killed.
+    std::fs::remove_file(&path).ok();
+    let listener = UnixListener::bind(&path)?;
+    let conn = db::init(db_path)?;
+    std::thread::spawn(move || {
+        // One request at a time, so writes never interleave.
+        for stream in listener.incoming().flatten() {
+            handle_client(stream, &conn).ok();
+        }
+    });
+    Ok(path)
+}
+
+fn handle_client(mut stream: UnixStream, conn: &Connection) -> Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let result = serde_json::from_str::<Request>(&line)
+        .map_err(anyhow::Error::from)
+        .and_then(|request| {
+            request.apply(conn)?;
+            println!("{} {}", Local::now().format("%H:%M"), request.describe());
+            Ok(())
+        });
+    let response = Response {
+        error: result.err().map(|err| format!("{err:#}")),
+    };
+    writeln!(stream, "{}", serde_json::to_string(&response)?)?;
+    Ok(())
+}
+
+/// Sends `request` to the daemon of the database behind `conn`, `None` when none runs.
+pub(super) fn send(request: &Request, conn: &Connection) -> Option<Result<()>> {
+    let stream = UnixStream::connect(socket_path(&database_path(conn)?)).ok()?;
+    Some(exchange(stream, request))
+}
+
+fn exchange(mut stream: UnixStream, request: &Request) -> Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let response: Response = serde_json::from_str(&line)?;
+    match response.error {
+        Some(error) => bail!("The daemon could not do that: {error}"),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,35 @@
+/// Audit log of what the TUI changed, one row per change with the session it was made in.
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use rusqlite::{Connection, params};
+
+use crate::types::ActivityEntry;
+
+pub fn record_activity(session: &str, message: &str, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT INTO audit_log (session, at, message) VALUES (?1, ?2, ?3)",
+        params![session, Local::now().to_rfc3339(), message],
+    )?;
+    Ok(())
+}
+
+/// Changes recorded in `session`, the latest first.
+pub fn query_activity(session: &str, conn: &Connection) -> Result<Vec<ActivityEntry>> {
+    let mut stmt =
+        conn.prepare("SELECT at, message FROM audit_log WHERE session = ?1 ORDER BY id DESC")?;
+    let rows = stmt.query_map([session], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut entries = Vec::new();
+    for row in rows {
+        let (at, message) = row?;
+        let Ok(at) = DateTime::parse_from_rfc3339(&at) else {
+            continue;
+        };
+        entries.push(ActivityEntry {
+            at: at.with_timezone(&Local),
+            message,
+        });
+    }
+    Ok(entries)
+}
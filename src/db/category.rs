@@ -52,3 +52,46 @@ pub fn query_categories(conn: &Connection) -> Result<Vec<TickrCategory>> {
     }
     Ok(categories)
 }
+
+pub fn update_category(
+    id: CategoryId,
+    name: String,
+    color: String,
+    conn: &Connection,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE categories SET name = ?1, color = ?2 WHERE id = ?3",
+        (name, color, id),
+    )?;
+    Ok(())
+}
+
+/// Number of tasks that use the category.
+pub fn query_category_usage(id: CategoryId, conn: &Connection) -> Result<usize> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM entries WHERE category_id = ?1",
+        [id],
+        |row| row.get(0),
+    )?;
+    Ok(count as usize)
+}
+
+/// Deletes a category, moving its tasks to `reassign_to` or leaving them without a category.
+pub fn delete_category(
+    id: CategoryId,
+    reassign_to: Option<CategoryId>,
+    conn: &Connection,
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE entries SET category_id = ?1 WHERE category_id = ?2",
+        (reassign_to, id),
+    )?;
+    tx.execute(
+        "UPDATE templates SET category_id = ?1 WHERE category_id = ?2",
+        (reassign_to, id),
+    )?;
+    tx.execute("DELETE FROM categories WHERE id = ?1", [id])?;
+    tx.commit()?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+/// Client database queries.
+use anyhow::{Result, bail};
+use chrono::Local;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::types::{Client, ClientId, ProjectId};
+
+pub fn create_client(name: &str, conn: &Connection) -> Result<ClientId> {
+    if query_client_by_name(name, conn)?.is_some() {
+        bail!("A client named '{name}' already exists.");
+    }
+    conn.execute(
+        "INSERT INTO clients (name, created_at) VALUES (?1, ?2)",
+        (name, Local::now().to_rfc3339()),
+    )?;
+    Ok(conn.last_insert_rowid() as ClientId)
+}
+
+/// Every client with the number of its projects, by name.
+pub fn query_clients(conn: &Connection) -> Result<Vec<Client>> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT c.id, c.name, COUNT(p.id)
+        FROM clients c
+        LEFT JOIN projects p ON p.client_id = c.id
+        GROUP BY c.id
+        ORDER BY c.name COLLATE NOCASE",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Client {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            projects: row.get::<_, i64>(2)? as usize,
+        })
+    })?;
+    let mut clients = Vec::new();
+    for row in rows {
+        clients.push(row?);
+    }
+    Ok(clients)
+}
+
+/// Client named `name`; names match in any case, as the unique index compares them.
+pub fn query_client_by_name(name: &str, conn: &Connection) -> Result<Option<Client>> {
+    Ok(query_clients(conn)?
+        .into_iter()
+        .find(|client| client.name.eq_ignore_ascii_case(name)))
+}
+
+pub fn rename_client(id: ClientId, name: &str, conn: &Connection) -> Result<()> {
+    let taken: Option<ClientId> = conn
+        .query_row(
+            "SELECT id FROM clients WHERE name = ?1 AND id != ?2",
+            (name, id),
+            |row| row.get(0),
+        )
+        .optional()?;
+    if taken.is_some() {
+        bail!("A client named '{name}' already exists.");
+    }
+    conn.execute("UPDATE clients SET name = ?1 WHERE id = ?2", (name, id))?;
+    Ok(())
+}
+
+/// Deletes a client; its projects stay, without a client.
+pub fn delete_client(id: ClientId, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE projects SET client_id = NULL WHERE client_id = ?1",
+        [id],
+    )?;
+    conn.execute("DELETE FROM clients WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Puts a project under `client`, or takes it away from its client.
+pub fn set_project_client(
+    project_id: ProjectId,
+    client: Option<ClientId>,
+    conn: &Connection,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE projects SET client_id = ?1 WHERE id = ?2",
+        (client, project_id),
+    )?;
+    Ok(())
+}
@@ -1,8 +1,113 @@
+use std::fmt;
+
 use anyhow::Result;
 use chrono::{DateTime, Local};
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::types::{AgendaEntry, Interval, IntervalId, RawInterval, TickrCategory, TickrId};
+
+/// Times an interval can't be stored with. Functions writing intervals return it inside their
+/// `anyhow::Error`, so callers can tell it apart with `downcast_ref`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntervalError {
+    /// The end lies before the start, e.g. because the clock was set back while a task ran.
+    EndsBeforeStart {
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    },
+    /// Start and end are the same moment. Such intervals are stored, this only warns about them.
+    ZeroLength { at: DateTime<Local> },
+    /// A running interval was stopped at `end`, before its start, so it ended at its start.
+    EndMovedToStart {
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    },
+}
+
+impl IntervalError {
+    /// Whether the interval may be stored anyway.
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            IntervalError::ZeroLength { .. } | IntervalError::EndMovedToStart { .. }
+        )
+    }
+}
+
+impl fmt::Display for IntervalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalError::EndsBeforeStart { start, end } => write!(
+                f,
+                "The interval would end at {} before it starts at {}.",
+                end.format("%Y-%m-%d %H:%M:%S"),
+                start.format("%Y-%m-%d %H:%M:%S")
+            ),
+            IntervalError::ZeroLength { at } => write!(
+                f,
+                "The interval starts and ends at {}, so it holds no time.",
+                at.format("%Y-%m-%d %H:%M:%S")
+            ),
+            IntervalError::EndMovedToStart { start, end } => write!(
+                f,
+                "The clock shows {}, before the interval started at {}; it ends at its start and \
+                 holds no time. Was the clock set back?",
+                end.format("%Y-%m-%d %H:%M:%S"),
+                start.format("%Y-%m-%d %H:%M:%S")
+            ),
+        }
+    }
+}
 
-use crate::types::Interval;
+impl std::error::Error for IntervalError {}
+
+/// Checks the times of an interval; running intervals (without an end) always pass.
+pub fn check_interval(
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+) -> Result<(), IntervalError> {
+    match end {
+        Some(end) if end < start => Err(IntervalError::EndsBeforeStart { start, end }),
+        Some(end) if end == start => Err(IntervalError::ZeroLength { at: start }),
+        _ => Ok(()),
+    }
+}
+
+/// Like [`check_interval`], but lets zero-length intervals through for the caller to warn about.
+fn ensure_valid(start: DateTime<Local>, end: Option<DateTime<Local>>) -> Result<()> {
+    match check_interval(start, end) {
+        Err(err) if !err.is_warning() => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Where the running interval of a task ends when it is stopped at `end`: there, or at its start
+/// when that is later, e.g. after the clock was set back, so the task can always be stopped. With
+/// a warning about that and about intervals that hold no time.
+pub fn running_end(
+    tickr_id: TickrId,
+    end: DateTime<Local>,
+    conn: &Connection,
+) -> Result<(DateTime<Local>, Option<IntervalError>)> {
+    let start: Option<Option<String>> = conn
+        .query_row(
+            "SELECT start_time FROM intervals WHERE entry_id = ?1 AND end_time IS NULL",
+            [tickr_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(start) = start.flatten() else {
+        return Ok((end, None));
+    };
+    let start = parse_required_datetime(Some(start))?;
+    Ok(match check_interval(start, Some(end)) {
+        Ok(()) => (end, None),
+        Err(IntervalError::EndsBeforeStart { start, end }) => {
+            (start, Some(IntervalError::EndMovedToStart { start, end }))
+        }
+        Err(warning) => (end, Some(warning)),
+    })
+}
 
 pub fn query_intervals_by_tickr_id(tickr_id: u32, conn: &Connection) -> Result<Vec<Interval>> {
     let intervals = conn.prepare("SELECT * FROM intervals WHERE entry_id = ?1")?;
@@ -11,8 +116,9 @@ pub fn query_intervals_by_tickr_id(tickr_id: u32, conn: &Connection) -> Result<V
         Ok(Interval {
             id: Some(row.get(0)?),
             entry_id: row.get(1)?,
-            start_time: parse_required_datetime(row.get(2)?).expect("Expecting parsing of start datetime to succeed, all Db entries should be parsable."),
+            start_time: parse_required_datetime(row.get(2)?).map_err(unreadable_column(2))?,
             end_time: parse_optional_datetime(row.get(3)?),
+            notes: row.get("notes")?,
         })
     })?;
     let mut result = Vec::new();
@@ -22,22 +128,34 @@ pub fn query_intervals_by_tickr_id(tickr_id: u32, conn: &Connection) -> Result<V
     Ok(result)
 }
 
+/// Intervals overlapping `[from, to)`, running ones included.
 pub fn query_intervals_by_time_range(
     from: DateTime<Local>,
     to: DateTime<Local>,
     conn: &Connection,
 ) -> Result<Vec<Interval>, rusqlite::Error> {
-    let intervals =
-        conn.prepare("SELECT * FROM intervals WHERE start_time >= ?1 AND end_time <= ?2")?;
+    let intervals = conn.prepare(
+        "SELECT * FROM intervals
+         WHERE julianday(start_time) < julianday(?2)
+           AND julianday(COALESCE(end_time, ?3)) > julianday(?1)",
+    )?;
     let mut stmt = intervals;
-    let rows = stmt.query_map([from.to_rfc3339(), to.to_rfc3339()], |row| {
-        Ok(Interval {
-            id: Some(row.get(0)?),
-            entry_id: row.get(1)?,
-            start_time: parse_required_datetime(row.get(2)?).expect("Expecting parsing of start datetime to succeed, all Db entries should be parsable."),
-            end_time: parse_optional_datetime(row.get(3)?),
-        })
-    })?;
+    let rows = stmt.query_map(
+        [
+            from.to_rfc3339(),
+            to.to_rfc3339(),
+            Local::now().to_rfc3339(),
+        ],
+        |row| {
+            Ok(Interval {
+                id: Some(row.get(0)?),
+                entry_id: row.get(1)?,
+                start_time: parse_required_datetime(row.get(2)?).map_err(unreadable_column(2))?,
+                end_time: parse_optional_datetime(row.get(3)?),
+                notes: row.get("notes")?,
+            })
+        },
+    )?;
     let mut result = Vec::new();
     for row in rows {
         result.push(row?);
@@ -45,13 +163,78 @@ pub fn query_intervals_by_time_range(
     Ok(result)
 }
 
+/// Intervals overlapping `[from, to)` with their task, project and category, in the order they
+/// started. Running intervals are included.
+pub fn query_agenda(
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    conn: &Connection,
+) -> Result<Vec<AgendaEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.entry_id, i.start_time, i.end_time, e.description, p.name,
+                c.id, c.name, c.color, i.notes, i.id
+         FROM intervals i
+         JOIN entries e ON e.id = i.entry_id
+         JOIN projects p ON p.id = e.project_id
+         LEFT JOIN categories c ON c.id = e.category_id
+         WHERE julianday(i.start_time) < julianday(?2)
+           AND julianday(COALESCE(i.end_time, ?3)) > julianday(?1)
+         ORDER BY julianday(i.start_time), i.id",
+    )?;
+    let rows = stmt.query_map(
+        [
+            from.to_rfc3339(),
+            to.to_rfc3339(),
+            Local::now().to_rfc3339(),
+        ],
+        |row| {
+            let category = match row.get::<_, Option<u32>>(5)? {
+                Some(id) => Some(TickrCategory {
+                    id,
+                    name: row.get(6)?,
+                    color: row.get(7)?,
+                }),
+                None => None,
+            };
+            Ok((
+                row.get(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get(4)?,
+                category,
+                row.get::<_, Option<String>>(8)?,
+                row.get(9)?,
+            ))
+        },
+    )?;
+    let mut result = Vec::new();
+    for row in rows {
+        let (tickr_id, start, end, task, project, category, notes, interval_id) = row?;
+        result.push(AgendaEntry {
+            interval_id,
+            tickr_id,
+            task: task.unwrap_or_default(),
+            project,
+            category,
+            start_time: parse_required_datetime(start)?,
+            end_time: parse_optional_datetime(end),
+            notes,
+        });
+    }
+    Ok(result)
+}
+
 pub fn create_interval(interval: Interval, conn: &Connection) -> Result<Interval> {
+    ensure_valid(interval.start_time, interval.end_time)?;
+    super::ensure_unlocked(interval.start_time, interval.end_time, conn)?;
     conn.execute(
-        "INSERT INTO intervals (entry_id, start_time, end_time) VALUES (?1, ?2, ?3)",
+        "INSERT INTO intervals (entry_id, start_time, end_time, notes) VALUES (?1, ?2, ?3, ?4)",
         rusqlite::params![
             interval.entry_id,
             interval.start_time.to_rfc3339(),
             interval.end_time.map(|dt| dt.to_rfc3339()),
+            interval.notes,
         ],
     )?;
     let id = conn.last_insert_rowid() as u32;
@@ -61,6 +244,234 @@ pub fn create_interval(interval: Interval, conn: &Connection) -> Result<Interval
     })
 }
 
+/// Replaces the notes of an interval, `None` clears them.
+pub fn set_interval_notes(id: IntervalId, notes: Option<&str>, conn: &Connection) -> Result<()> {
+    ensure_stored_unlocked(id, conn)?;
+    conn.execute(
+        "UPDATE intervals SET notes = ?1 WHERE id = ?2",
+        rusqlite::params![notes, id],
+    )?;
+    Ok(())
+}
+
+/// Ends the running interval of a task at `end` instead of now, see [`running_end`].
+pub fn end_running_interval_at(
+    tickr_id: TickrId,
+    end: DateTime<Local>,
+    conn: &Connection,
+) -> Result<Option<IntervalError>> {
+    let (end, warning) = running_end(tickr_id, end, conn)?;
+    conn.execute(
+        "UPDATE intervals SET end_time = ?1 WHERE entry_id = ?2 AND end_time IS NULL",
+        rusqlite::params![end.to_rfc3339(), tickr_id],
+    )?;
+    Ok(warning)
+}
+
+/// Stops the running interval of a task at `end`, e.g. when it was left running overnight.
+pub fn stop_tickr_at(
+    tickr_id: TickrId,
+    end: DateTime<Local>,
+    conn: &Connection,
+) -> Result<Option<IntervalError>> {
+    let warning = end_running_interval_at(tickr_id, end, conn)?;
+    crate::status::write_cache(conn).ok();
+    Ok(warning)
+}
+
+/// Moves the end of a finished interval.
+pub fn set_interval_end(id: IntervalId, end: DateTime<Local>, conn: &Connection) -> Result<()> {
+    let start: String = conn.query_row(
+        "SELECT start_time FROM intervals WHERE id = ?1",
+        [id],
+        |row| row.get(0),
+    )?;
+    let start = parse_required_datetime(Some(start))?;
+    ensure_valid(start, Some(end))?;
+    ensure_stored_unlocked(id, conn)?;
+    super::ensure_unlocked(start, Some(end), conn)?;
+    conn.execute(
+        "UPDATE intervals SET end_time = ?1 WHERE id = ?2",
+        rusqlite::params![end.to_rfc3339(), id],
+    )?;
+    Ok(())
+}
+
+/// Every interval with its task and project, the times as they are stored.
+pub fn query_raw_intervals(conn: &Connection) -> Result<Vec<RawInterval>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.id, e.description, p.name, i.start_time, i.end_time
+         FROM intervals i
+         JOIN entries e ON e.id = i.entry_id
+         LEFT JOIN projects p ON p.id = e.project_id
+         ORDER BY i.id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(RawInterval {
+            id: row.get(0)?,
+            task: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            project: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            start_time: row.get(3)?,
+            end_time: row.get(4)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Replaces the start and end of an interval, `None` leaves it running.
+pub fn set_interval_times(
+    id: IntervalId,
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    conn: &Connection,
+) -> Result<()> {
+    ensure_valid(start, end)?;
+    ensure_stored_unlocked(id, conn)?;
+    super::ensure_unlocked(start, end, conn)?;
+    conn.execute(
+        "UPDATE intervals SET start_time = ?1, end_time = ?2 WHERE id = ?3",
+        rusqlite::params![start.to_rfc3339(), end.map(|end| end.to_rfc3339()), id],
+    )?;
+    crate::status::write_cache(conn).ok();
+    Ok(())
+}
+
+pub fn delete_interval(id: IntervalId, conn: &Connection) -> Result<()> {
+    ensure_stored_unlocked(id, conn)?;
+    conn.execute("DELETE FROM intervals WHERE id = ?1", [id])?;
+    crate::status::write_cache(conn).ok();
+    Ok(())
+}
+
+/// Fails when the interval as it is stored lies in a locked week. Times that can't be read
+/// belong to no week, so they don't count as locked.
+fn ensure_stored_unlocked(id: IntervalId, conn: &Connection) -> Result<()> {
+    let (start, end): (Option<String>, Option<String>) = conn.query_row(
+        "SELECT start_time, end_time FROM intervals WHERE id = ?1",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let Some(start) = parse_optional_datetime(start) else {
+        return Ok(());
+    };
+    super::ensure_unlocked(start, parse_optional_datetime(end), conn)
+}
+
+/// Cuts `[gap_start, gap_end)` out of the running interval of a task: the interval ends at
+/// `gap_start` and a new running interval starts at `gap_end`.
+pub fn split_running_interval(
+    tickr_id: TickrId,
+    gap_start: DateTime<Local>,
+    gap_end: DateTime<Local>,
+    conn: &Connection,
+) -> Result<()> {
+    ensure_valid(gap_start, Some(gap_end))?;
+    super::ensure_unlocked(gap_start, Some(gap_end), conn)?;
+    let tx = conn.unchecked_transaction()?;
+    end_running_interval_at(tickr_id, gap_start, &tx)?;
+    create_interval(
+        Interval {
+            id: None,
+            entry_id: tickr_id,
+            start_time: gap_end,
+            end_time: None,
+            notes: None,
+        },
+        &tx,
+    )?;
+    tx.commit()?;
+    crate::status::write_cache(conn).ok();
+    Ok(())
+}
+
+/// Splits an ended interval at each local midnight it spans, into one interval per day. The
+/// notes stay with the first part. Returns how many intervals were added.
+pub fn split_interval_at_midnight(id: IntervalId, conn: &Connection) -> Result<usize> {
+    let (tickr_id, start, end): (TickrId, Option<String>, Option<String>) = conn.query_row(
+        "SELECT entry_id, start_time, end_time FROM intervals WHERE id = ?1",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    let start = parse_required_datetime(start)?;
+    let Some(end) = parse_optional_datetime(end).filter(|end| *end > start) else {
+        return Ok(0);
+    };
+    let cuts: Vec<DateTime<Local>> = midnights(start, end).collect();
+    let Some(first_cut) = cuts.first() else {
+        return Ok(0);
+    };
+    super::ensure_unlocked(start, Some(end), conn)?;
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE intervals SET end_time = ?1 WHERE id = ?2",
+        rusqlite::params![first_cut.to_rfc3339(), id],
+    )?;
+    let ends = cuts.iter().skip(1).copied().chain(std::iter::once(end));
+    for (part_start, part_end) in cuts.iter().zip(ends) {
+        create_interval(
+            Interval {
+                id: None,
+                entry_id: tickr_id,
+                start_time: *part_start,
+                end_time: Some(part_end),
+                notes: None,
+            },
+            &tx,
+        )?;
+    }
+    tx.commit()?;
+    crate::status::write_cache(conn).ok();
+    Ok(cuts.len())
+}
+
+/// Splits every ended interval that spans midnight, leaving out those in locked weeks. Returns
+/// how many intervals were split.
+pub fn split_intervals_at_midnight(conn: &Connection) -> Result<usize> {
+    let mut stmt = conn.prepare(
+        "SELECT id, start_time, end_time FROM intervals WHERE end_time IS NOT NULL ORDER BY id",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, IntervalId>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut split = 0;
+    for (id, start, end) in rows {
+        // Unreadable times are left to `tickr doctor`.
+        let (Ok(start), Some(end)) = (parse_required_datetime(start), parse_optional_datetime(end))
+        else {
+            continue;
+        };
+        if end <= start
+            || midnights(start, end).next().is_none()
+            || super::ensure_unlocked(start, Some(end), conn).is_err()
+        {
+            continue;
+        }
+        if split_interval_at_midnight(id, conn)? > 0 {
+            split += 1;
+        }
+    }
+    Ok(split)
+}
+
+/// The local midnights after `start` and before `end`.
+fn midnights(
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> impl Iterator<Item = DateTime<Local>> {
+    start
+        .date_naive()
+        .iter_days()
+        .skip(1)
+        .map(crate::ui::local_start_of_day)
+        .take_while(move |midnight| *midnight < end)
+}
+
 fn parse_required_datetime(value: Option<String>) -> Result<DateTime<Local>> {
     value
         .and_then(|raw| {
@@ -68,7 +479,16 @@ fn parse_required_datetime(value: Option<String>) -> Result<DateTime<Local>> {
                 .ok()
                 .map(|dt| dt.with_timezone(&Local))
         })
-        .ok_or_else(|| anyhow::anyhow!("Failed to parse datetime"))
+        .ok_or_else(|| {
+            anyhow::anyhow!("Failed to parse datetime, `tickr doctor` can repair the interval")
+        })
+}
+
+/// Reports a value of column `index` that couldn't be read from within a row mapping.
+fn unreadable_column(index: usize) -> impl Fn(anyhow::Error) -> rusqlite::Error {
+    move |err| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, err.into())
+    }
 }
 
 fn parse_optional_datetime(value: Option<String>) -> Option<DateTime<Local>> {
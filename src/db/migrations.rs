@@ -2,6 +2,24 @@
 use anyhow::Result;
 use rusqlite::Connection;
 
+/// Raised with every change to `run_migrations`. Databases below it are backed up before they are
+/// upgraded; it is kept in `PRAGMA user_version`.
+const SCHEMA_VERSION: i64 = 2;
+
+/// Whether an existing database is from before the current schema. New, empty ones aren't.
+pub fn needs_upgrade(conn: &Connection) -> Result<bool> {
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version >= SCHEMA_VERSION {
+        return Ok(false);
+    }
+    let has_data: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'entries')",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(has_data)
+}
+
 /// Creates the initial schema if it doesn't exist yet.
 pub fn run_migrations(conn: &Connection) -> Result<()> {
     conn.execute_batch(
@@ -9,6 +27,15 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
         CREATE TABLE IF NOT EXISTS projects (
             id          INTEGER PRIMARY KEY AUTOINCREMENT,
             name        TEXT    NOT NULL UNIQUE,
+            created_at  TEXT    NOT NULL,
+            archived    INTEGER NOT NULL DEFAULT 0,
+            parent_id   INTEGER,
+            client_id   INTEGER REFERENCES clients(id) ON DELETE SET NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS clients (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            name        TEXT    NOT NULL UNIQUE COLLATE NOCASE,
             created_at  TEXT    NOT NULL
         );
 
@@ -17,8 +44,16 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
             project_id  INTEGER NOT NULL,
             description TEXT,
             category_id INTEGER,
+            billable    INTEGER NOT NULL DEFAULT 1,
+            done        INTEGER NOT NULL DEFAULT 0,
+            blocked_by  INTEGER,
+            notes       TEXT,
+            estimate_minutes INTEGER,
+            archived    INTEGER NOT NULL DEFAULT 0,
+            issue       TEXT,
             FOREIGN KEY (project_id) REFERENCES projects(id),
-            FOREIGN KEY (category_id) REFERENCES categories(id)
+            FOREIGN KEY (category_id) REFERENCES categories(id),
+            FOREIGN KEY (blocked_by) REFERENCES entries(id)
         );
 
         CREATE TABLE IF NOT EXISTS categories (
@@ -32,12 +67,102 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
             entry_id   INTEGER NOT NULL,
             start_time TEXT    NOT NULL,
             end_time   TEXT,
+            notes      TEXT,
             FOREIGN KEY (entry_id) REFERENCES entries(id) ON DELETE CASCADE
         );
+
+        CREATE TABLE IF NOT EXISTS project_billing (
+            project_id       INTEGER PRIMARY KEY,
+            hourly_rate      REAL,
+            rounding_minutes INTEGER,
+            rounding         TEXT,
+            currency         TEXT,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS rate_history (
+            id             INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id     INTEGER,
+            hourly_rate    REAL,
+            effective_from TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS locked_weeks (
+            week_start TEXT PRIMARY KEY,
+            locked_at  TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS project_aliases (
+            alias      TEXT    PRIMARY KEY COLLATE NOCASE,
+            project_id INTEGER NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS project_goals (
+            project_id INTEGER PRIMARY KEY,
+            period     TEXT    NOT NULL,
+            minutes    INTEGER NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id      INTEGER PRIMARY KEY AUTOINCREMENT,
+            session TEXT NOT NULL,
+            at      TEXT NOT NULL,
+            message TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS audit_log_session ON audit_log (session);
+
+        CREATE TABLE IF NOT EXISTS templates (
+            id            INTEGER PRIMARY KEY AUTOINCREMENT,
+            name          TEXT    NOT NULL UNIQUE COLLATE NOCASE,
+            project_id    INTEGER NOT NULL,
+            category_id   INTEGER,
+            billable      INTEGER NOT NULL DEFAULT 1,
+            every_workday INTEGER NOT NULL DEFAULT 0,
+            last_created  TEXT,
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+            FOREIGN KEY (category_id) REFERENCES categories(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS snapshots (
+            id         INTEGER PRIMARY KEY AUTOINCREMENT,
+            name       TEXT    NOT NULL UNIQUE COLLATE NOCASE,
+            from_date  TEXT    NOT NULL,
+            to_date    TEXT    NOT NULL,
+            created_at TEXT    NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS snapshot_rows (
+            snapshot_id      INTEGER NOT NULL,
+            day              TEXT    NOT NULL,
+            project          TEXT    NOT NULL,
+            category         TEXT,
+            seconds          INTEGER NOT NULL,
+            billable_seconds INTEGER NOT NULL,
+            FOREIGN KEY (snapshot_id) REFERENCES snapshots(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS snapshot_rows_snapshot ON snapshot_rows (snapshot_id);
         ",
     )?;
     migrate_entries_nullable(conn)?;
     migrate_entries_add_category(conn)?;
+    migrate_entries_add_billable(conn)?;
+    migrate_entries_add_dependencies(conn)?;
+    migrate_entries_add_notes(conn)?;
+    migrate_intervals_add_notes(conn)?;
+    migrate_entries_add_estimate(conn)?;
+    migrate_add_archived(conn)?;
+    migrate_projects_add_parent(conn)?;
+    migrate_projects_add_client(conn)?;
+    migrate_project_billing_add_currency(conn)?;
+    migrate_entries_add_issue(conn)?;
+    migrate_add_sync(conn)?;
+    migrate_add_search(conn)?;
+    // A read-only database that needed no changes keeps working with the old version number.
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .ok();
     Ok(())
 }
 
@@ -101,3 +226,332 @@ fn migrate_entries_add_category(conn: &Connection) -> Result<()> {
     conn.execute("ALTER TABLE entries ADD COLUMN category_id INTEGER", [])?;
     Ok(())
 }
+
+fn migrate_entries_add_billable(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(entries)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "billable" {
+            return Ok(());
+        }
+    }
+
+    conn.execute(
+        "ALTER TABLE entries ADD COLUMN billable INTEGER NOT NULL DEFAULT 1",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_entries_add_dependencies(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(entries)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "blocked_by" {
+            return Ok(());
+        }
+    }
+
+    conn.execute_batch(
+        "
+        ALTER TABLE entries ADD COLUMN done INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE entries ADD COLUMN blocked_by INTEGER REFERENCES entries(id);
+        ",
+    )?;
+    Ok(())
+}
+
+fn migrate_entries_add_notes(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(entries)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "notes" {
+            return Ok(());
+        }
+    }
+
+    conn.execute("ALTER TABLE entries ADD COLUMN notes TEXT", [])?;
+    Ok(())
+}
+
+fn migrate_intervals_add_notes(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(intervals)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "notes" {
+            return Ok(());
+        }
+    }
+
+    conn.execute("ALTER TABLE intervals ADD COLUMN notes TEXT", [])?;
+    Ok(())
+}
+
+fn migrate_project_billing_add_currency(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(project_billing)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "currency" {
+            return Ok(());
+        }
+    }
+
+    conn.execute("ALTER TABLE project_billing ADD COLUMN currency TEXT", [])?;
+    Ok(())
+}
+
+/// Adds the `archived` flag to both projects and entries.
+fn migrate_add_archived(conn: &Connection) -> Result<()> {
+    for table in ["projects", "entries"] {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let rows = stmt.query_map([], |row| {
+            let name: String = row.get(1)?;
+            Ok(name)
+        })?;
+        let mut has_column = false;
+        for row in rows {
+            if row? == "archived" {
+                has_column = true;
+            }
+        }
+        if !has_column {
+            conn.execute(
+                &format!("ALTER TABLE {table} ADD COLUMN archived INTEGER NOT NULL DEFAULT 0"),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn migrate_projects_add_parent(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(projects)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "parent_id" {
+            return Ok(());
+        }
+    }
+    conn.execute("ALTER TABLE projects ADD COLUMN parent_id INTEGER", [])?;
+    Ok(())
+}
+
+fn migrate_projects_add_client(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(projects)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "client_id" {
+            return Ok(());
+        }
+    }
+    conn.execute(
+        "ALTER TABLE projects ADD COLUMN client_id INTEGER \
+         REFERENCES clients(id) ON DELETE SET NULL",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_entries_add_estimate(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(entries)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "estimate_minutes" {
+            return Ok(());
+        }
+    }
+
+    conn.execute(
+        "ALTER TABLE entries ADD COLUMN estimate_minutes INTEGER",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_entries_add_issue(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("PRAGMA table_info(entries)")?;
+    let rows = stmt.query_map([], |row| {
+        let name: String = row.get(1)?;
+        Ok(name)
+    })?;
+    for row in rows {
+        if row? == "issue" {
+            return Ok(());
+        }
+    }
+
+    conn.execute("ALTER TABLE entries ADD COLUMN issue TEXT", [])?;
+    Ok(())
+}
+
+/// Tables `tickr sync` shares, with the kind their deletions are recorded as.
+const SYNCED_TABLES: [(&str, &str); 4] = [
+    ("projects", "project"),
+    ("categories", "category"),
+    ("entries", "task"),
+    ("intervals", "interval"),
+];
+
+/// Gives every synced row a `uid` that is the same on all machines, the time it last changed and
+/// the `sync_version` it changed at. Triggers keep them up to date and record deletions, so the
+/// queries that write these tables don't have to know about sync.
+fn migrate_add_sync(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS sync_clock (
+            version  INTEGER NOT NULL,
+            -- Set while `tickr sync` applies changes, which keep their modified_at: 1 for
+            -- changes pushed to this database, 2 for changes pulled by it, which also keep
+            -- their sync_version so they aren't sent back.
+            applying INTEGER NOT NULL DEFAULT 0
+        );
+        INSERT INTO sync_clock (version) SELECT 0 WHERE NOT EXISTS (SELECT 1 FROM sync_clock);
+
+        CREATE TABLE IF NOT EXISTS sync_tombstones (
+            uid          TEXT    PRIMARY KEY,
+            kind         TEXT    NOT NULL,
+            deleted_at   TEXT    NOT NULL,
+            sync_version INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS sync_servers (
+            server          TEXT    PRIMARY KEY,
+            pushed_version  INTEGER NOT NULL,
+            pulled_version  INTEGER NOT NULL,
+            synced_at       TEXT    NOT NULL
+        );
+        ",
+    )?;
+    for (table, kind) in SYNCED_TABLES {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let has_uid = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .iter()
+            .any(|name| name == "uid");
+        if !has_uid {
+            // Rows from before sync count as changed now, so the first sync sends them.
+            conn.execute_batch(&format!(
+                "
+                ALTER TABLE {table} ADD COLUMN uid TEXT;
+                ALTER TABLE {table} ADD COLUMN modified_at TEXT;
+                ALTER TABLE {table} ADD COLUMN sync_version INTEGER NOT NULL DEFAULT 0;
+                UPDATE {table} SET uid = lower(hex(randomblob(16))), modified_at = {SYNC_NOW},
+                    sync_version = 1;
+                UPDATE sync_clock SET version = max(version, 1);
+                "
+            ))?;
+        }
+        conn.execute_batch(&format!(
+            "
+            CREATE UNIQUE INDEX IF NOT EXISTS {table}_uid ON {table} (uid);
+
+            CREATE TRIGGER IF NOT EXISTS {table}_sync_insert AFTER INSERT ON {table}
+            BEGIN
+                UPDATE sync_clock SET version = version + 1 WHERE applying < 2;
+                UPDATE {table} SET
+                    uid = coalesce(NEW.uid, lower(hex(randomblob(16)))),
+                    modified_at = coalesce(NEW.modified_at, {SYNC_NOW}),
+                    sync_version = CASE WHEN (SELECT applying FROM sync_clock) < 2
+                        THEN (SELECT version FROM sync_clock) ELSE NEW.sync_version END
+                WHERE rowid = NEW.rowid;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS {table}_sync_update AFTER UPDATE ON {table}
+            WHEN NEW.sync_version IS OLD.sync_version AND (SELECT applying FROM sync_clock) < 2
+            BEGIN
+                UPDATE sync_clock SET version = version + 1;
+                UPDATE {table} SET
+                    modified_at = CASE WHEN (SELECT applying FROM sync_clock)
+                        THEN NEW.modified_at ELSE {SYNC_NOW} END,
+                    sync_version = (SELECT version FROM sync_clock)
+                WHERE rowid = NEW.rowid;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS {table}_sync_delete AFTER DELETE ON {table}
+            WHEN OLD.uid IS NOT NULL AND (SELECT applying FROM sync_clock) < 2
+            BEGIN
+                UPDATE sync_clock SET version = version + 1;
+                INSERT OR REPLACE INTO sync_tombstones (uid, kind, deleted_at, sync_version)
+                VALUES (OLD.uid, '{kind}', {SYNC_NOW}, (SELECT version FROM sync_clock));
+            END;
+            "
+        ))?;
+    }
+    Ok(())
+}
+
+/// Current time in UTC with milliseconds, which sorts like the time it stands for.
+const SYNC_NOW: &str = "strftime('%Y-%m-%dT%H:%M:%fZ', 'now')";
+
+/// Full-text index of the task names and notes for searching, kept up to date by triggers. It
+/// only stores the index; the text stays in `entries`.
+fn migrate_add_search(conn: &Connection) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'entries_search')",
+        [],
+        |row| row.get(0),
+    )?;
+    conn.execute_batch(
+        "
+        CREATE VIRTUAL TABLE IF NOT EXISTS entries_search USING fts5(
+            description, notes,
+            content = 'entries', content_rowid = 'id',
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS entries_search_insert AFTER INSERT ON entries
+        BEGIN
+            INSERT INTO entries_search (rowid, description, notes)
+            VALUES (NEW.id, NEW.description, NEW.notes);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS entries_search_delete AFTER DELETE ON entries
+        BEGIN
+            INSERT INTO entries_search (entries_search, rowid, description, notes)
+            VALUES ('delete', OLD.id, OLD.description, OLD.notes);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS entries_search_update
+        AFTER UPDATE OF description, notes ON entries
+        BEGIN
+            INSERT INTO entries_search (entries_search, rowid, description, notes)
+            VALUES ('delete', OLD.id, OLD.description, OLD.notes);
+            INSERT INTO entries_search (rowid, description, notes)
+            VALUES (NEW.id, NEW.description, NEW.notes);
+        END;
+        ",
+    )?;
+    if !exists {
+        // Tasks from before the index.
+        conn.execute(
+            "INSERT INTO entries_search (entries_search) VALUES ('rebuild')",
+            [],
+        )?;
+    }
+    Ok(())
+}
@@ -1,36 +1,129 @@
 /// Database module with project, tickr, category queries and migrations.
+mod audit;
 mod category;
+mod client;
 mod intervals;
 mod migrations;
 mod project;
+mod rates;
+mod report;
+mod search;
+mod snapshot;
+mod sync;
+mod template;
 mod tickr;
+mod week_lock;
 
-use anyhow::Result;
-use rusqlite::Connection;
+use anyhow::{Context, Result};
+use rusqlite::{Connection, DatabaseName, ErrorCode};
 
 // Re-export all public functions
-pub use category::{create_category, query_categories, query_category_by_id, query_category_id};
-pub use intervals::create_interval;
+pub use audit::{query_activity, record_activity};
+pub use category::{
+    create_category, delete_category, query_categories, query_category_by_id, query_category_id,
+    query_category_usage, update_category,
+};
+pub use client::{
+    create_client, delete_client, query_client_by_name, query_clients, rename_client,
+    set_project_client,
+};
+pub use intervals::{
+    IntervalError, check_interval, create_interval, delete_interval, query_agenda,
+    query_raw_intervals, running_end, set_interval_end, set_interval_notes, set_interval_times,
+    split_interval_at_midnight, split_intervals_at_midnight, split_running_interval, stop_tickr_at,
+};
 pub use project::{
-    check_project_exists, create_project, query_project, query_project_by_id,
-    query_project_worked_on_today, query_project_worked_on_week, query_projects,
-    search_projects_by_name,
+    INBOX_PROJECT, check_project_exists, create_project, inbox_project_id,
+    query_all_project_billing, query_project, query_project_aliases, query_project_billing,
+    query_project_by_alias, query_project_by_id, query_project_goals, query_project_worked_between,
+    query_projects, remove_project_alias, search_projects_by_name, set_project_alias,
+    set_project_archived, set_project_billing, set_project_goal, set_project_parent,
+};
+pub use rates::{query_rate_history, record_global_rate, record_project_rate};
+pub use report::{query_project_seconds, query_report};
+pub use search::search_tickrs;
+pub use snapshot::{create_snapshot, query_snapshot_by_name, query_snapshot_rows, query_snapshots};
+pub use sync::{apply_changes, query_changes, query_sync_cursor, set_sync_cursor, sync_version};
+pub use template::{
+    create_due_templates, create_template, delete_template, query_template_by_name,
+    query_templates, use_template,
 };
 pub use tickr::{
-    create_tickr, delete_tickr, end_tickr, query_tickr, query_tickr_by_id, start_tickr,
-    update_tickr_details,
+    append_tickr_note, create_tickr, delete_tickr, end_tickr, query_last_stopped_tickr,
+    query_last_tracked_time, query_running_tickr, query_tickr, query_tickr_by_id,
+    query_tickrs_blocked_by, set_tickr_archived, set_tickr_billable, set_tickr_blocked_by,
+    set_tickr_done, set_tickr_estimate, set_tickr_issue, set_tickr_notes, set_tickr_project,
+    start_tickr, unblock_tickrs, update_tickr_details,
+};
+pub use week_lock::{
+    ensure_tickr_unlocked, ensure_unlocked, lock_week, locked_week_containing, query_locked_weeks,
+    unlock_week,
 };
 
-/// Opens (or creates) the SQLite database and runs migrations.
+/// Opens (or creates) the SQLite database and runs migrations. A write-protected database
+/// opens as long as it needs no migration, see [`is_read_only`].
 pub fn init(db_path: &str) -> Result<Connection> {
-    let conn = Connection::open(db_path)?;
-    migrations::run_migrations(&conn)?;
+    let conn = Connection::open(db_path).with_context(|| {
+        format!("Cannot open the database at {db_path}, check that it or its directory is writable")
+    })?;
+    // The daemon and other Tickr processes may write at the same moment.
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+    if migrations::needs_upgrade(&conn)? && !is_read_only(&conn) {
+        // Without the upgrade Tickr can't open the database, so a failed backup only warns.
+        match crate::backup::before("upgrade", &conn) {
+            Ok(Some(backup)) => eprintln!("{}", backup.describe()),
+            Ok(None) => {}
+            Err(err) => eprintln!("Warning: {err:#}"),
+        }
+    }
+    if let Err(err) = migrations::run_migrations(&conn) {
+        if is_read_only(&conn) {
+            anyhow::bail!(
+                "The database at {db_path} is read-only and needs an upgrade to this version of \
+                 Tickr. Open it once with write access."
+            );
+        }
+        return Err(err);
+    }
     Ok(conn)
 }
 
+/// Whether writes to the database fail, because the file, its directory or the file system is
+/// write-protected. A database locked by another process still counts as writable.
+pub fn is_read_only(conn: &Connection) -> bool {
+    if conn.is_readonly(DatabaseName::Main).unwrap_or(false) {
+        return true;
+    }
+    // A write-protected directory only shows once SQLite creates its journal, so try a write.
+    let probe = conn
+        .execute_batch("BEGIN IMMEDIATE; CREATE TABLE tickr_write_probe (id INTEGER); ROLLBACK;");
+    let Err(err) = probe else {
+        return false;
+    };
+    conn.execute_batch("ROLLBACK").ok();
+    !matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+/// Counter that changes whenever another connection, e.g. a CLI command or the daemon, commits
+/// to the database. Commits through `conn` itself leave it as it is.
+pub fn data_version(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("PRAGMA data_version", [], |row| row.get(0))?)
+}
+
+/// Environment variable that overrides the default database location.
+pub const DB_PATH_ENV: &str = "TICKR_DB";
+
 /// Returns the default database path inside the user's data directory.
-/// Falls back to `./tickr.db` when no data dir is found.
+/// `TICKR_DB` takes precedence when set, and `./tickr.db` is used when no data dir is found.
 pub fn default_db_path() -> String {
+    if let Ok(path) = std::env::var(DB_PATH_ENV)
+        && !path.trim().is_empty()
+    {
+        return path;
+    }
     if let Some(data_dir) = dirs::data_local_dir() {
         let tickr_dir = data_dir.join("tickr");
         std::fs::create_dir_all(&tickr_dir).ok();
@@ -1,17 +1,42 @@
 /// Project-related database queries.
-use anyhow::Result;
+use std::collections::HashMap;
+
+use anyhow::{Result, bail};
 use chrono::DateTime;
 use chrono::Local;
-use rusqlite::Connection;
+use chrono::NaiveDate;
+use rusqlite::{Connection, OptionalExtension};
 
-use crate::types::{Project, ProjectQuery};
+use crate::billing::{BillingRules, Currency, RoundingMode};
+use crate::types::{GoalPeriod, Project, ProjectGoal, ProjectId, ProjectQuery};
 
-pub fn create_project(arg: Project, conn: &Connection) -> Result<()> {
+pub fn create_project(arg: Project, conn: &Connection) -> Result<ProjectId> {
     conn.execute(
         "INSERT INTO projects (name, created_at) VALUES (?1, ?2)",
         (&arg.name, arg.created_at.to_rfc3339()),
     )?;
-    Ok(())
+    Ok(conn.last_insert_rowid() as ProjectId)
+}
+
+/// Project that collects quick captures until they are sorted.
+pub const INBOX_PROJECT: &str = "Inbox";
+
+/// Returns the id of the inbox project, creating it on first use.
+pub fn inbox_project_id(conn: &Connection) -> Result<ProjectId> {
+    if let Some(id) = query_project_by_name(INBOX_PROJECT.to_string(), conn)?.and_then(|p| p.id) {
+        return Ok(id);
+    }
+    create_project(
+        Project {
+            id: None,
+            name: INBOX_PROJECT.to_string(),
+            created_at: Local::now(),
+            archived: false,
+            parent_id: None,
+            client_id: None,
+        },
+        conn,
+    )
 }
 
 pub fn query_projects(conn: &Connection) -> Result<Vec<Project>> {
@@ -23,6 +48,9 @@ pub fn query_projects(conn: &Connection) -> Result<Vec<Project>> {
             created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
                 .unwrap()
                 .with_timezone(&Local),
+            archived: row.get("archived")?,
+            parent_id: row.get("parent_id")?,
+            client_id: row.get("client_id")?,
         })
     })?;
     let mut projects = Vec::new();
@@ -51,6 +79,9 @@ pub fn query_project_by_name(name: String, conn: &Connection) -> Result<Option<P
             created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
                 .unwrap()
                 .with_timezone(&Local),
+            archived: row.get("archived")?,
+            parent_id: row.get("parent_id")?,
+            client_id: row.get("client_id")?,
         }))
     } else {
         Ok(None)
@@ -67,57 +98,50 @@ pub fn query_project_by_id(id: u32, conn: &Connection) -> Result<Option<Project>
             created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
                 .unwrap()
                 .with_timezone(&Local),
+            archived: row.get("archived")?,
+            parent_id: row.get("parent_id")?,
+            client_id: row.get("client_id")?,
         }))
     } else {
         Ok(None)
     }
 }
 
-pub fn query_project_worked_on_today(conn: &Connection) -> Result<Vec<Project>> {
+/// Projects with time tracked between `first` and `last`, both days included. Intervals that run
+/// into the range from the day before count, and running ones up to now.
+pub fn query_project_worked_between(
+    first: NaiveDate,
+    last: NaiveDate,
+    conn: &Connection,
+) -> Result<Vec<Project>> {
     let mut stmt = conn.prepare(
         "
-        SELECT DISTINCT p.id, p.name, p.created_at
+        SELECT DISTINCT p.id, p.name, p.created_at, p.archived, p.parent_id, p.client_id
         FROM projects p
         JOIN entries e ON e.project_id = p.id
         JOIN intervals i ON i.entry_id = e.id
-        WHERE i.start_time >= date('now', 'localtime') || 'T00:00:00'
-        AND i.start_time <  date('now', 'localtime', '+1 day') || 'T00:00:00';",
+        WHERE julianday(i.start_time) < julianday(?2)
+          AND julianday(COALESCE(i.end_time, ?3)) > julianday(?1);",
     )?;
-    let rows = stmt.query_map([], |row| {
-        Ok(Project {
-            id: Some(row.get(0)?),
-            name: row.get(1)?,
-            created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
-                .unwrap()
-                .with_timezone(&Local),
-        })
-    })?;
-    let mut projects = Vec::new();
-    for row in rows {
-        projects.push(row?);
-    }
-    Ok(projects)
-}
-
-pub fn query_project_worked_on_week(conn: &Connection) -> Result<Vec<Project>> {
-    let mut stmt = conn.prepare(
-        "
-        SELECT DISTINCT p.id, p.name, p.created_at
-        FROM projects p
-        JOIN entries e ON e.project_id = p.id
-        JOIN intervals i ON i.entry_id = e.id
-        WHERE i.start_time >= date('now', 'localtime', '-6 day') || 'T00:00:00'
-        AND i.start_time <  date('now', 'localtime', '+1 day') || 'T00:00:00';",
+    let rows = stmt.query_map(
+        [
+            crate::ui::local_start_of_day(first).to_rfc3339(),
+            crate::ui::local_start_of_day(last + chrono::Duration::days(1)).to_rfc3339(),
+            Local::now().to_rfc3339(),
+        ],
+        |row| {
+            Ok(Project {
+                id: Some(row.get(0)?),
+                name: row.get(1)?,
+                created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
+                    .unwrap()
+                    .with_timezone(&Local),
+                archived: row.get("archived")?,
+                parent_id: row.get("parent_id")?,
+                client_id: row.get("client_id")?,
+            })
+        },
     )?;
-    let rows = stmt.query_map([], |row| {
-        Ok(Project {
-            id: Some(row.get(0)?),
-            name: row.get(1)?,
-            created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
-                .unwrap()
-                .with_timezone(&Local),
-        })
-    })?;
     let mut projects = Vec::new();
     for row in rows {
         projects.push(row?);
@@ -140,7 +164,8 @@ pub fn search_projects_by_name(query: &str, conn: &Connection) -> Result<Vec<Pro
         .replace('%', "\\%")
         .replace('_', "\\_");
 
-    let mut stmt = conn.prepare("SELECT * FROM projects WHERE name LIKE ?1 ESCAPE '\\' ORDER BY name ASC")?;
+    let mut stmt =
+        conn.prepare("SELECT * FROM projects WHERE name LIKE ?1 ESCAPE '\\' ORDER BY name ASC")?;
     let pattern = format!("%{}%", escaped);
     let rows = stmt.query_map([pattern], |row| {
         Ok(Project {
@@ -149,6 +174,9 @@ pub fn search_projects_by_name(query: &str, conn: &Connection) -> Result<Vec<Pro
             created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(2)?)
                 .unwrap()
                 .with_timezone(&Local),
+            archived: row.get("archived")?,
+            parent_id: row.get("parent_id")?,
+            client_id: row.get("client_id")?,
         })
     })?;
     let mut projects = Vec::new();
@@ -157,3 +185,193 @@ pub fn search_projects_by_name(query: &str, conn: &Connection) -> Result<Vec<Pro
     }
     Ok(projects)
 }
+
+pub fn set_project_archived(id: ProjectId, archived: bool, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE projects SET archived = ?1 WHERE id = ?2",
+        (archived, id),
+    )?;
+    Ok(())
+}
+
+/// Groups a project under `parent`, or makes it top-level again. Only one level of nesting is
+/// allowed, so the parent must be top-level and the project must not have sub-projects.
+pub fn set_project_parent(
+    id: ProjectId,
+    parent: Option<ProjectId>,
+    conn: &Connection,
+) -> Result<()> {
+    if let Some(parent) = parent {
+        if parent == id {
+            bail!("A project can't be its own parent.");
+        }
+        let parent_of_parent: Option<ProjectId> = conn.query_row(
+            "SELECT parent_id FROM projects WHERE id = ?1",
+            [parent],
+            |row| row.get(0),
+        )?;
+        if parent_of_parent.is_some() {
+            bail!("The parent is itself a sub-project, only one level of nesting is supported.");
+        }
+        let children: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM projects WHERE parent_id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+        if children > 0 {
+            bail!(
+                "The project has sub-projects of its own, only one level of nesting is supported."
+            );
+        }
+    }
+    conn.execute(
+        "UPDATE projects SET parent_id = ?1 WHERE id = ?2",
+        (parent, id),
+    )?;
+    Ok(())
+}
+
+/// Rate and rounding overrides of a project; unset fields fall back to the config file.
+pub fn query_project_billing(project_id: ProjectId, conn: &Connection) -> Result<BillingRules> {
+    Ok(query_all_project_billing(conn)?
+        .remove(&project_id)
+        .unwrap_or_default())
+}
+
+pub fn query_all_project_billing(conn: &Connection) -> Result<HashMap<ProjectId, BillingRules>> {
+    let mut stmt = conn.prepare(
+        "SELECT project_id, hourly_rate, rounding_minutes, rounding, currency FROM project_billing",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let rounding: Option<String> = row.get(3)?;
+        let currency: Option<String> = row.get(4)?;
+        Ok((
+            row.get(0)?,
+            BillingRules {
+                hourly_rate: row.get(1)?,
+                rounding_minutes: row.get(2)?,
+                rounding: rounding.as_deref().and_then(RoundingMode::parse),
+                currency: currency.as_deref().and_then(Currency::parse),
+            },
+        ))
+    })?;
+    let mut rules = HashMap::new();
+    for row in rows {
+        let (project_id, project_rules) = row?;
+        rules.insert(project_id, project_rules);
+    }
+    Ok(rules)
+}
+
+/// Replaces the overrides of a project; empty `rules` remove them.
+pub fn set_project_billing(
+    project_id: ProjectId,
+    rules: &BillingRules,
+    conn: &Connection,
+) -> Result<()> {
+    if rules.is_empty() {
+        conn.execute(
+            "DELETE FROM project_billing WHERE project_id = ?1",
+            [project_id],
+        )?;
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO project_billing (project_id, hourly_rate, rounding_minutes, rounding, currency)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(project_id) DO UPDATE SET
+             hourly_rate = excluded.hourly_rate,
+             rounding_minutes = excluded.rounding_minutes,
+             rounding = excluded.rounding,
+             currency = excluded.currency",
+        rusqlite::params![
+            project_id,
+            rules.hourly_rate,
+            rules.rounding_minutes,
+            rules.rounding.map(|mode| mode.as_str()),
+            rules.currency.map(String::from),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Makes `alias` another name of the project, taking it over from the project it named before.
+pub fn set_project_alias(alias: &str, project_id: ProjectId, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT INTO project_aliases (alias, project_id) VALUES (?1, ?2)
+         ON CONFLICT(alias) DO UPDATE SET alias = excluded.alias, project_id = excluded.project_id",
+        (alias, project_id),
+    )?;
+    Ok(())
+}
+
+/// Removes `alias`, returns whether there was one.
+pub fn remove_project_alias(alias: &str, conn: &Connection) -> Result<bool> {
+    Ok(conn.execute("DELETE FROM project_aliases WHERE alias = ?1", [alias])? > 0)
+}
+
+/// Every alias with the project it names, by alias.
+pub fn query_project_aliases(conn: &Connection) -> Result<Vec<(String, ProjectId)>> {
+    let mut stmt = conn.prepare("SELECT alias, project_id FROM project_aliases ORDER BY alias")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    let mut aliases = Vec::new();
+    for row in rows {
+        aliases.push(row?);
+    }
+    Ok(aliases)
+}
+
+/// Project that `alias` names; aliases match in any case.
+pub fn query_project_by_alias(alias: &str, conn: &Connection) -> Result<Option<Project>> {
+    let project_id: Option<ProjectId> = conn
+        .query_row(
+            "SELECT project_id FROM project_aliases WHERE alias = ?1",
+            [alias],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match project_id {
+        Some(id) => query_project_by_id(id, conn),
+        None => Ok(None),
+    }
+}
+
+/// Goals of every project that has one.
+pub fn query_project_goals(conn: &Connection) -> Result<HashMap<ProjectId, ProjectGoal>> {
+    let mut stmt = conn.prepare("SELECT project_id, period, minutes FROM project_goals")?;
+    let rows = stmt.query_map([], |row| {
+        let period: String = row.get(1)?;
+        Ok((row.get(0)?, period, row.get(2)?))
+    })?;
+    let mut goals = HashMap::new();
+    for row in rows {
+        let (project_id, period, minutes) = row?;
+        // A period written by a newer version is left alone rather than misread.
+        if let Some(period) = GoalPeriod::parse(&period) {
+            goals.insert(project_id, ProjectGoal { period, minutes });
+        }
+    }
+    Ok(goals)
+}
+
+/// Sets the goal of a project; `None` removes it.
+pub fn set_project_goal(
+    project_id: ProjectId,
+    goal: Option<ProjectGoal>,
+    conn: &Connection,
+) -> Result<()> {
+    match goal {
+        Some(goal) => conn.execute(
+            "INSERT INTO project_goals (project_id, period, minutes) VALUES (?1, ?2, ?3)
+             ON CONFLICT(project_id) DO UPDATE SET
+                 period = excluded.period,
+                 minutes = excluded.minutes",
+            rusqlite::params![project_id, goal.period.as_str(), goal.minutes],
+        )?,
+        None => conn.execute(
+            "DELETE FROM project_goals WHERE project_id = ?1",
+            [project_id],
+        )?,
+    };
+    Ok(())
+}
@@ -0,0 +1,132 @@
+/// History of hourly rates, so time keeps the rate that applied when it was tracked.
+///
+/// Every change of a project's rate (`tickr project set`) and of the `[billing]` rate in the
+/// config file is stored with the time it took effect. The first change also stores the rate
+/// before it, effective since forever. Rows without a project hold the config rate.
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::types::ProjectId;
+
+/// `effective_from` of the rate that applied before the first recorded change.
+const SINCE_FOREVER: &str = "1970-01-01T00:00:00+00:00";
+
+/// When a rate took effect and the rate, `None` for no rate.
+type RateChange = (DateTime<Local>, Option<f64>);
+
+/// All recorded rate changes, oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct RateHistory {
+    changes: HashMap<Option<ProjectId>, Vec<RateChange>>,
+}
+
+impl RateHistory {
+    /// Hourly rate of a project at `time`. `project_rate` and `global_rate` are the current
+    /// rates, used when no change was recorded before `time`. A project without its own rate
+    /// falls back to the global one.
+    pub fn rate_at(
+        &self,
+        project_id: ProjectId,
+        time: DateTime<Local>,
+        project_rate: Option<f64>,
+        global_rate: Option<f64>,
+    ) -> Option<f64> {
+        let global = || self.recorded_at(None, time).unwrap_or(global_rate);
+        match self.recorded_at(Some(project_id), time) {
+            Some(rate) => rate.or_else(global),
+            None => project_rate.or_else(global),
+        }
+    }
+
+    fn recorded_at(
+        &self,
+        project_id: Option<ProjectId>,
+        time: DateTime<Local>,
+    ) -> Option<Option<f64>> {
+        self.changes
+            .get(&project_id)?
+            .iter()
+            .rev()
+            .find(|(effective_from, _)| *effective_from <= time)
+            .map(|(_, rate)| *rate)
+    }
+}
+
+pub fn query_rate_history(conn: &Connection) -> Result<RateHistory> {
+    let mut stmt = conn.prepare(
+        "SELECT project_id, hourly_rate, effective_from FROM rate_history
+         ORDER BY julianday(effective_from), id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Option<ProjectId>>(0)?,
+            row.get::<_, Option<f64>>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut history = RateHistory::default();
+    for row in rows {
+        let (project_id, rate, effective_from) = row?;
+        let Ok(effective_from) = DateTime::parse_from_rfc3339(&effective_from) else {
+            continue;
+        };
+        history
+            .changes
+            .entry(project_id)
+            .or_default()
+            .push((effective_from.with_timezone(&Local), rate));
+    }
+    Ok(history)
+}
+
+/// Records a change of a project's own rate from `old` to `new`, effective now.
+pub fn record_project_rate(
+    project_id: ProjectId,
+    old: Option<f64>,
+    new: Option<f64>,
+    conn: &Connection,
+) -> Result<()> {
+    if old == new {
+        return Ok(());
+    }
+    if latest_rate(Some(project_id), conn)?.is_none() {
+        insert_rate(Some(project_id), old, SINCE_FOREVER, conn)?;
+    }
+    insert_rate(Some(project_id), new, &Local::now().to_rfc3339(), conn)
+}
+
+/// Records the config rate when it differs from the last one seen, effective now.
+pub fn record_global_rate(rate: Option<f64>, conn: &Connection) -> Result<()> {
+    match latest_rate(None, conn)? {
+        None => insert_rate(None, rate, SINCE_FOREVER, conn),
+        Some(latest) if latest != rate => insert_rate(None, rate, &Local::now().to_rfc3339(), conn),
+        Some(_) => Ok(()),
+    }
+}
+
+fn latest_rate(project_id: Option<ProjectId>, conn: &Connection) -> Result<Option<Option<f64>>> {
+    Ok(conn
+        .query_row(
+            "SELECT hourly_rate FROM rate_history WHERE project_id IS ?1
+             ORDER BY julianday(effective_from) DESC, id DESC LIMIT 1",
+            [project_id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+fn insert_rate(
+    project_id: Option<ProjectId>,
+    rate: Option<f64>,
+    effective_from: &str,
+    conn: &Connection,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO rate_history (project_id, hourly_rate, effective_from) VALUES (?1, ?2, ?3)",
+        rusqlite::params![project_id, rate, effective_from],
+    )?;
+    Ok(())
+}
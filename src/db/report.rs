@@ -0,0 +1,161 @@
+/// Aggregation queries for reports.
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use rusqlite::Connection;
+
+use super::project::query_all_project_billing;
+use super::rates::query_rate_history;
+use crate::billing::BillingRules;
+use crate::types::{ClientId, ProjectId, ReportGrouping, ReportRow, TickrId};
+
+/// Sums tracked time per group for intervals overlapping `[from, to)`.
+/// Intervals are clipped to the range and running intervals count up to now. Intervals that end
+/// before they start, as a clock set back while they ran can leave them, count as no time.
+/// Billable time is rounded per interval with the project's rules, falling back to `billing`,
+/// and paid at the rate that applied when the interval started. With `client`, only that client's
+/// projects count.
+pub fn query_report(
+    grouping: ReportGrouping,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    client: Option<ClientId>,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<Vec<ReportRow>> {
+    let label = match grouping {
+        ReportGrouping::Project => "p.name",
+        ReportGrouping::Client => "COALESCE(cl.name, '(no client)')",
+        ReportGrouping::Category => "COALESCE(c.name, '(none)')",
+        ReportGrouping::Task => "p.name || ' / ' || COALESCE(e.description, '')",
+    };
+    let sql = format!(
+        "
+        SELECT {label} AS label,
+               MAX(0,
+                   MIN(julianday(COALESCE(i.end_time, ?3)), julianday(?2))
+                   - MAX(julianday(i.start_time), julianday(?1))
+               ) * 86400.0 AS seconds,
+               e.billable,
+               e.id,
+               p.id,
+               i.start_time,
+               julianday(i.end_time) < julianday(i.start_time)
+        FROM intervals i
+        JOIN entries e ON e.id = i.entry_id
+        JOIN projects p ON p.id = e.project_id
+        LEFT JOIN categories c ON c.id = e.category_id
+        LEFT JOIN clients cl ON cl.id = p.client_id
+        WHERE julianday(i.start_time) < julianday(?2)
+          AND julianday(COALESCE(i.end_time, ?3)) > julianday(?1)
+          AND (?4 IS NULL OR p.client_id = ?4);"
+    );
+    let project_rules = query_all_project_billing(conn)?;
+    let rate_history = query_rate_history(conn)?;
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        rusqlite::params![
+            from.to_rfc3339(),
+            to.to_rfc3339(),
+            Local::now().to_rfc3339(),
+            client,
+        ],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?.round() as i64,
+                row.get::<_, bool>(2)?,
+                row.get::<_, TickrId>(3)?,
+                row.get::<_, ProjectId>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, Option<bool>>(6)?.unwrap_or(false),
+            ))
+        },
+    )?;
+
+    let mut groups: BTreeMap<String, (ReportRow, HashSet<TickrId>)> = BTreeMap::new();
+    for row in rows {
+        let (label, seconds, billable, tickr_id, project_id, start, backwards) = row?;
+        let (report_row, tasks) = groups.entry(label.clone()).or_insert_with(|| {
+            (
+                ReportRow {
+                    label,
+                    project_id: (grouping == ReportGrouping::Project).then_some(project_id),
+                    ..ReportRow::default()
+                },
+                HashSet::new(),
+            )
+        });
+        report_row.seconds += seconds;
+        report_row.intervals += 1;
+        if backwards {
+            report_row.backwards += 1;
+        }
+        tasks.insert(tickr_id);
+        if billable {
+            let project = project_rules.get(&project_id).copied().unwrap_or_default();
+            let mut rules = project.or(billing);
+            if let Ok(start) = DateTime::parse_from_rfc3339(&start) {
+                rules.hourly_rate = rate_history.rate_at(
+                    project_id,
+                    start.with_timezone(&Local),
+                    project.hourly_rate,
+                    billing.hourly_rate,
+                );
+            }
+            let billed = rules.round(seconds);
+            report_row.billable_seconds += seconds;
+            report_row.billed_seconds += billed;
+            if let Some(amount) = rules.amount(billed) {
+                report_row.amounts.add(rules.currency, amount);
+            }
+        }
+    }
+
+    let mut result: Vec<ReportRow> = groups
+        .into_values()
+        .map(|(row, tasks)| ReportRow {
+            tasks: tasks.len(),
+            ..row
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.seconds
+            .cmp(&a.seconds)
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    Ok(result)
+}
+
+/// Seconds tracked per project on its own tasks from `from` (or the first interval) up to `to`.
+/// Intervals are clipped to the range and running intervals count up to `to`.
+pub fn query_project_seconds(
+    from: Option<DateTime<Local>>,
+    to: DateTime<Local>,
+    conn: &Connection,
+) -> Result<HashMap<ProjectId, i64>> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT e.project_id,
+               SUM(MAX(0,
+                   MIN(julianday(COALESCE(i.end_time, ?2)), julianday(?2))
+                   - MAX(julianday(i.start_time), julianday(COALESCE(?1, i.start_time)))
+               )) * 86400.0
+        FROM intervals i
+        JOIN entries e ON e.id = i.entry_id
+        WHERE julianday(i.start_time) < julianday(?2)
+          AND (?1 IS NULL OR julianday(COALESCE(i.end_time, ?2)) > julianday(?1))
+        GROUP BY e.project_id",
+    )?;
+    let rows = stmt.query_map(
+        rusqlite::params![from.map(|from| from.to_rfc3339()), to.to_rfc3339()],
+        |row| Ok((row.get(0)?, row.get::<_, f64>(1)?.round() as i64)),
+    )?;
+    let mut seconds = HashMap::new();
+    for row in rows {
+        let (project_id, project_seconds) = row?;
+        seconds.insert(project_id, project_seconds);
+    }
+    Ok(seconds)
+}
@@ -0,0 +1,43 @@
+/// Full-text search over task names and notes, through the `entries_search` index.
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::types::{ProjectId, TickrId};
+
+/// Tasks whose name or notes contain a word starting with each word of `query`, best match
+/// first; matches in the name count more than in the notes. Limited to one project when
+/// `project_id` is given. `None` when the query has no words to search for.
+pub fn search_tickrs(
+    query: &str,
+    project_id: Option<ProjectId>,
+    conn: &Connection,
+) -> Result<Option<Vec<TickrId>>> {
+    let Some(expression) = match_expression(query) else {
+        return Ok(None);
+    };
+    let mut stmt = conn.prepare(
+        "
+        SELECT entries.id
+        FROM entries_search
+        JOIN entries ON entries.id = entries_search.rowid
+        WHERE entries_search MATCH ?1 AND (?2 IS NULL OR entries.project_id = ?2)
+        ORDER BY bm25(entries_search, 10.0, 1.0)",
+    )?;
+    let rows = stmt.query_map((expression, project_id), |row| row.get(0))?;
+    let mut ids = Vec::new();
+    for row in rows {
+        ids.push(row?);
+    }
+    Ok(Some(ids))
+}
+
+/// The words of `query` as an FTS5 query. Each word is quoted, so punctuation can't break the
+/// syntax, and matches as a prefix, so results show up while a word is still being typed.
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
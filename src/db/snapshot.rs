@@ -0,0 +1,197 @@
+/// Snapshots: totals per day, project and category copied out of the intervals, so numbers
+/// reported once stay the same after the intervals change or are deleted.
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use rusqlite::Connection;
+
+use crate::progress::Progress;
+use crate::types::{Snapshot, SnapshotId, SnapshotRow, TickrQuery};
+
+/// Stores the totals of the days `from` (the first tracked day when `None`) to `to`, inclusive,
+/// under `name`. Running intervals count up to now. `progress` counts the tasks added up.
+pub fn create_snapshot(
+    name: &str,
+    from: Option<NaiveDate>,
+    to: NaiveDate,
+    progress: &mut Progress,
+    conn: &Connection,
+) -> Result<SnapshotId> {
+    if query_snapshot_by_name(name, conn)?.is_some() {
+        bail!("A snapshot named '{name}' already exists.");
+    }
+    let rows = day_totals(from, to, progress, conn)?;
+    let from = from.or(rows.first().map(|row| row.day)).unwrap_or(to);
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO snapshots (name, from_date, to_date, created_at) VALUES (?1, ?2, ?3, ?4)",
+        (
+            name,
+            from.to_string(),
+            to.to_string(),
+            Local::now().to_rfc3339(),
+        ),
+    )?;
+    let id = tx.last_insert_rowid() as SnapshotId;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO snapshot_rows
+                 (snapshot_id, day, project, category, seconds, billable_seconds)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for row in &rows {
+            insert.execute((
+                id,
+                row.day.to_string(),
+                &row.project,
+                &row.category,
+                row.seconds,
+                row.billable_seconds,
+            ))?;
+        }
+    }
+    tx.commit()?;
+    Ok(id)
+}
+
+/// Every snapshot with its total, oldest first.
+pub fn query_snapshots(conn: &Connection) -> Result<Vec<Snapshot>> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT s.id, s.name, s.from_date, s.to_date, s.created_at, COALESCE(SUM(r.seconds), 0)
+        FROM snapshots s
+        LEFT JOIN snapshot_rows r ON r.snapshot_id = s.id
+        GROUP BY s.id
+        ORDER BY s.created_at, s.id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, SnapshotId>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, i64>(5)?,
+        ))
+    })?;
+    let mut snapshots = Vec::new();
+    for row in rows {
+        let (id, name, from, to, created_at, seconds) = row?;
+        snapshots.push(Snapshot {
+            id,
+            name,
+            from: from.parse()?,
+            to: to.parse()?,
+            created_at: DateTime::parse_from_rfc3339(&created_at)?.with_timezone(&Local),
+            seconds,
+        });
+    }
+    Ok(snapshots)
+}
+
+/// Snapshot named `name`, in any case.
+pub fn query_snapshot_by_name(name: &str, conn: &Connection) -> Result<Option<Snapshot>> {
+    Ok(query_snapshots(conn)?
+        .into_iter()
+        .find(|snapshot| snapshot.name.eq_ignore_ascii_case(name)))
+}
+
+/// Rows of a snapshot by day, project and category.
+pub fn query_snapshot_rows(id: SnapshotId, conn: &Connection) -> Result<Vec<SnapshotRow>> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT day, project, category, seconds, billable_seconds
+        FROM snapshot_rows
+        WHERE snapshot_id = ?1
+        ORDER BY day, project, category",
+    )?;
+    let rows = stmt.query_map([id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, i64>(4)?,
+        ))
+    })?;
+    let mut result = Vec::new();
+    for row in rows {
+        let (day, project, category, seconds, billable_seconds) = row?;
+        result.push(SnapshotRow {
+            day: day.parse()?,
+            project,
+            category,
+            seconds,
+            billable_seconds,
+        });
+    }
+    Ok(result)
+}
+
+/// Time per day, project and category from the intervals, split at local midnight.
+fn day_totals(
+    from: Option<NaiveDate>,
+    to: NaiveDate,
+    progress: &mut Progress,
+    conn: &Connection,
+) -> Result<Vec<SnapshotRow>> {
+    let projects: HashMap<_, _> = super::query_projects(conn)?
+        .into_iter()
+        .filter_map(|project| Some((project.id?, project.name)))
+        .collect();
+    let categories: HashMap<_, _> = super::query_categories(conn)?
+        .into_iter()
+        .map(|category| (category.id, category.name))
+        .collect();
+    let range_start = from.map(crate::ui::local_start_of_day);
+    let range_end = crate::ui::local_start_of_day(to + Duration::days(1));
+    let now = Local::now();
+
+    let mut totals: BTreeMap<(NaiveDate, String, Option<String>), (i64, i64)> = BTreeMap::new();
+    let tickrs = super::query_tickr(TickrQuery::All, conn)?;
+    progress.set_total(tickrs.len());
+    for (done, tickr) in tickrs.into_iter().enumerate() {
+        progress.set(done);
+        let project = projects
+            .get(&tickr.project_id)
+            .cloned()
+            .unwrap_or_else(|| "Unknown project".to_string());
+        let category = tickr
+            .category_id
+            .and_then(|id| categories.get(&id).cloned());
+        for interval in &tickr.intervals {
+            let mut start = match range_start {
+                Some(range_start) => interval.start_time.max(range_start),
+                None => interval.start_time,
+            };
+            let end = interval.end_time.unwrap_or(now).min(range_end);
+            while start < end {
+                let day = start.date_naive();
+                let day_end = crate::ui::local_start_of_day(day + Duration::days(1)).min(end);
+                let seconds = (day_end - start).num_seconds();
+                let total = totals
+                    .entry((day, project.clone(), category.clone()))
+                    .or_default();
+                total.0 += seconds;
+                if tickr.billable {
+                    total.1 += seconds;
+                }
+                start = day_end;
+            }
+        }
+    }
+    Ok(totals
+        .into_iter()
+        .filter(|(_, (seconds, _))| *seconds > 0)
+        .map(
+            |((day, project, category), (seconds, billable_seconds))| SnapshotRow {
+                day,
+                project,
+                category,
+                seconds,
+                billable_seconds,
+            },
+        )
+        .collect())
+}
@@ -0,0 +1,540 @@
+//! Changes for `tickr sync`: what changed after a sync version, and applying changes from
+//! another database. Rows are matched by `uid`, projects and categories also by name. The later
+//! `modified_at` wins; deletions win over changes made before them.
+
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::sync::{
+    CategoryChange, ChangeSet, Deletion, IntervalChange, ProjectChange, SyncKind, TaskChange,
+};
+use crate::types::{CategoryId, ProjectId, TickrId};
+
+/// Current sync version; every change to a synced row raises it.
+pub fn sync_version(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("SELECT version FROM sync_clock", [], |row| row.get(0))?)
+}
+
+/// Everything that changed after `since`, with the version it is complete up to.
+pub fn query_changes(since: i64, conn: &Connection) -> Result<ChangeSet> {
+    let tx = conn.unchecked_transaction()?;
+    let version = sync_version(&tx)?;
+
+    let mut stmt = tx.prepare(
+        "SELECT p.uid, p.name, parent.uid, parent.name, p.archived, p.created_at, p.modified_at
+         FROM projects p LEFT JOIN projects parent ON parent.id = p.parent_id
+         WHERE p.sync_version > ?1 ORDER BY p.sync_version",
+    )?;
+    let projects = stmt
+        .query_map([since], |row| {
+            Ok(ProjectChange {
+                uid: row.get(0)?,
+                name: row.get(1)?,
+                parent_uid: row.get(2)?,
+                parent: row.get(3)?,
+                archived: row.get(4)?,
+                created_at: row.get(5)?,
+                modified_at: row.get(6)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = tx.prepare(
+        "SELECT uid, name, color, modified_at FROM categories
+         WHERE sync_version > ?1 ORDER BY sync_version",
+    )?;
+    let categories = stmt
+        .query_map([since], |row| {
+            Ok(CategoryChange {
+                uid: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                modified_at: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = tx.prepare(
+        "SELECT e.uid, p.uid, p.name, e.description, c.uid, c.name, e.billable, e.done,
+                blocker.uid, e.notes, e.estimate_minutes, e.archived, e.issue, e.modified_at
+         FROM entries e
+         JOIN projects p ON p.id = e.project_id
+         LEFT JOIN categories c ON c.id = e.category_id
+         LEFT JOIN entries blocker ON blocker.id = e.blocked_by
+         WHERE e.sync_version > ?1 ORDER BY e.sync_version",
+    )?;
+    let tasks = stmt
+        .query_map([since], |row| {
+            Ok(TaskChange {
+                uid: row.get(0)?,
+                project_uid: row.get(1)?,
+                project: row.get(2)?,
+                description: row.get(3)?,
+                category_uid: row.get(4)?,
+                category: row.get(5)?,
+                billable: row.get(6)?,
+                done: row.get(7)?,
+                blocked_by_uid: row.get(8)?,
+                notes: row.get(9)?,
+                estimate_minutes: row.get(10)?,
+                archived: row.get(11)?,
+                issue: row.get(12)?,
+                modified_at: row.get(13)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = tx.prepare(
+        "SELECT i.uid, e.uid, i.start_time, i.end_time, i.notes, i.modified_at
+         FROM intervals i JOIN entries e ON e.id = i.entry_id
+         WHERE i.sync_version > ?1 ORDER BY i.sync_version",
+    )?;
+    let intervals = stmt
+        .query_map([since], |row| {
+            Ok(IntervalChange {
+                uid: row.get(0)?,
+                task_uid: row.get(1)?,
+                start: row.get(2)?,
+                end: row.get(3)?,
+                notes: row.get(4)?,
+                modified_at: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = tx.prepare(
+        "SELECT kind, uid, deleted_at FROM sync_tombstones
+         WHERE sync_version > ?1 ORDER BY sync_version",
+    )?;
+    let deletions = stmt
+        .query_map([since], |row| {
+            Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .filter_map(|row| {
+            let (kind, uid, deleted_at) = match row {
+                Ok(row) => row,
+                Err(err) => return Some(Err(err)),
+            };
+            let kind = SyncKind::parse(&kind)?;
+            Some(Ok(Deletion {
+                kind,
+                uid,
+                deleted_at,
+            }))
+        })
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(ChangeSet {
+        version,
+        projects,
+        categories,
+        tasks,
+        intervals,
+        deletions,
+    })
+}
+
+/// Applies `changes` in one transaction. Returns how many of them changed this database; the
+/// others were older than what it has, or refer to tasks it doesn't know. `pulled` changes came
+/// from the server and keep their sync version, so the next push doesn't send them back; pushed
+/// ones get a new one, so the server's other clients pull them.
+pub fn apply_changes(changes: &ChangeSet, pulled: bool, conn: &Connection) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE sync_clock SET applying = ?1",
+        [if pulled { 2 } else { 1 }],
+    )?;
+    let mut applied = 0;
+
+    for project in &changes.projects {
+        applied += usize::from(apply_project(project, &tx)?);
+    }
+    // Parents once all projects of the change set exist.
+    for project in &changes.projects {
+        let Some(id) = row_by_uid("projects", &project.uid, &tx)?.map(|(id, _)| id) else {
+            continue;
+        };
+        let parent = match (&project.parent_uid, &project.parent) {
+            (Some(uid), name) => resolve_named("projects", uid, name.as_deref(), &tx)?,
+            _ => None,
+        };
+        tx.execute(
+            "UPDATE projects SET parent_id = ?1, modified_at = ?2
+             WHERE id = ?3 AND modified_at = ?2 AND parent_id IS NOT ?1",
+            params![parent, project.modified_at, id],
+        )?;
+    }
+
+    for category in &changes.categories {
+        applied += usize::from(apply_category(category, &tx)?);
+    }
+    for task in &changes.tasks {
+        applied += usize::from(apply_task(task, &tx)?);
+    }
+    for task in &changes.tasks {
+        let Some(id) = row_by_uid("entries", &task.uid, &tx)?.map(|(id, _)| id) else {
+            continue;
+        };
+        let blocker = match &task.blocked_by_uid {
+            Some(uid) => row_by_uid("entries", uid, &tx)?.map(|(id, _)| id),
+            None => None,
+        };
+        tx.execute(
+            "UPDATE entries SET blocked_by = ?1, modified_at = ?2
+             WHERE id = ?3 AND modified_at = ?2 AND blocked_by IS NOT ?1",
+            params![blocker, task.modified_at, id],
+        )?;
+    }
+    for interval in &changes.intervals {
+        applied += usize::from(apply_interval(interval, &tx)?);
+    }
+    for deletion in &changes.deletions {
+        applied += usize::from(apply_deletion(deletion, &tx)?);
+    }
+
+    tx.execute("UPDATE sync_clock SET applying = 0", [])?;
+    tx.commit()?;
+    if applied > 0 {
+        crate::status::write_cache(conn).ok();
+    }
+    Ok(applied)
+}
+
+/// Sync versions of the last sync with `server`: up to which local changes were sent, and up to
+/// which of the server's changes were received.
+pub fn query_sync_cursor(server: &str, conn: &Connection) -> Result<(i64, i64)> {
+    Ok(conn
+        .query_row(
+            "SELECT pushed_version, pulled_version FROM sync_servers WHERE server = ?1",
+            [server],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .unwrap_or((0, 0)))
+}
+
+pub fn set_sync_cursor(server: &str, pushed: i64, pulled: i64, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT INTO sync_servers (server, pushed_version, pulled_version, synced_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(server) DO UPDATE SET pushed_version = ?2, pulled_version = ?3,
+             synced_at = ?4",
+        params![server, pushed, pulled, chrono::Local::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Local id and `modified_at` of the row of `table` with `uid`.
+fn row_by_uid(table: &str, uid: &str, conn: &Connection) -> Result<Option<(u32, String)>> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT id, modified_at FROM {table} WHERE uid = ?1"),
+            [uid],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?)
+}
+
+/// Id of the project or category with `uid`, or else with `name`.
+fn resolve_named(
+    table: &str,
+    uid: &str,
+    name: Option<&str>,
+    conn: &Connection,
+) -> Result<Option<u32>> {
+    if let Some((id, _)) = row_by_uid(table, uid, conn)? {
+        return Ok(Some(id));
+    }
+    let Some(name) = name else {
+        return Ok(None);
+    };
+    Ok(conn
+        .query_row(
+            &format!("SELECT id FROM {table} WHERE name = ?1"),
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Id and `modified_at` of the project or category with `uid`, or else with `name`. One with the
+/// same name but another uid was created on two machines: it takes over the incoming uid, so
+/// they end up with the same one.
+fn match_named(
+    table: &str,
+    uid: &str,
+    name: &str,
+    conn: &Connection,
+) -> Result<Option<(u32, String)>> {
+    if let Some(row) = row_by_uid(table, uid, conn)? {
+        return Ok(Some(row));
+    }
+    let Some((id, modified_at)) = conn
+        .query_row(
+            &format!("SELECT id, modified_at FROM {table} WHERE name = ?1"),
+            [name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    conn.execute(
+        &format!("UPDATE {table} SET uid = ?1 WHERE id = ?2"),
+        params![uid, id],
+    )?;
+    Ok(Some((id, modified_at)))
+}
+
+/// The name `name` for row `id` of `table`, or its current name while another row has `name`.
+fn free_name(table: &str, id: u32, name: &str, conn: &Connection) -> Result<Option<String>> {
+    let taken: bool = conn.query_row(
+        &format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE name = ?1 AND id != ?2)"),
+        params![name, id],
+        |row| row.get(0),
+    )?;
+    Ok((!taken).then(|| name.to_string()))
+}
+
+fn apply_project(project: &ProjectChange, conn: &Connection) -> Result<bool> {
+    match match_named("projects", &project.uid, &project.name, conn)? {
+        Some((_, modified_at)) if modified_at >= project.modified_at => Ok(false),
+        Some((id, _)) => {
+            let name = free_name("projects", id, &project.name, conn)?;
+            conn.execute(
+                "UPDATE projects SET name = coalesce(?1, name), archived = ?2, modified_at = ?3
+                 WHERE id = ?4",
+                params![name, project.archived, project.modified_at, id],
+            )?;
+            Ok(true)
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO projects (name, created_at, archived, uid, modified_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    project.name,
+                    project.created_at,
+                    project.archived,
+                    project.uid,
+                    project.modified_at
+                ],
+            )?;
+            Ok(true)
+        }
+    }
+}
+
+fn apply_category(category: &CategoryChange, conn: &Connection) -> Result<bool> {
+    match match_named("categories", &category.uid, &category.name, conn)? {
+        Some((_, modified_at)) if modified_at >= category.modified_at => Ok(false),
+        Some((id, _)) => {
+            let name = free_name("categories", id, &category.name, conn)?;
+            conn.execute(
+                "UPDATE categories SET name = coalesce(?1, name), color = ?2, modified_at = ?3
+                 WHERE id = ?4",
+                params![name, category.color, category.modified_at, id],
+            )?;
+            Ok(true)
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO categories (name, color, uid, modified_at) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    category.name,
+                    category.color,
+                    category.uid,
+                    category.modified_at
+                ],
+            )?;
+            Ok(true)
+        }
+    }
+}
+
+fn apply_task(task: &TaskChange, conn: &Connection) -> Result<bool> {
+    let local = row_by_uid("entries", &task.uid, conn)?;
+    if local
+        .as_ref()
+        .is_some_and(|(_, modified_at)| *modified_at >= task.modified_at)
+    {
+        return Ok(false);
+    }
+    let Some(project_id): Option<ProjectId> =
+        resolve_named("projects", &task.project_uid, Some(&task.project), conn)?
+    else {
+        return Ok(false);
+    };
+    let category_id: Option<CategoryId> = match &task.category_uid {
+        Some(uid) => resolve_named("categories", uid, task.category.as_deref(), conn)?,
+        None => None,
+    };
+    match local {
+        Some((id, _)) => {
+            conn.execute(
+                "UPDATE entries SET project_id = ?1, description = ?2, category_id = ?3,
+                     billable = ?4, done = ?5, notes = ?6, estimate_minutes = ?7, archived = ?8,
+                     issue = ?9, modified_at = ?10
+                 WHERE id = ?11",
+                params![
+                    project_id,
+                    task.description,
+                    category_id,
+                    task.billable,
+                    task.done,
+                    task.notes,
+                    task.estimate_minutes,
+                    task.archived,
+                    task.issue,
+                    task.modified_at,
+                    id
+                ],
+            )?;
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO entries (project_id, description, category_id, billable, done, notes,
+                     estimate_minutes, archived, issue, uid, modified_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    project_id,
+                    task.description,
+                    category_id,
+                    task.billable,
+                    task.done,
+                    task.notes,
+                    task.estimate_minutes,
+                    task.archived,
+                    task.issue,
+                    task.uid,
+                    task.modified_at
+                ],
+            )?;
+        }
+    }
+    Ok(true)
+}
+
+/// Intervals in locked weeks stay as they are here, like edits from the TUI or CLI.
+fn apply_interval(interval: &IntervalChange, conn: &Connection) -> Result<bool> {
+    let local = row_by_uid("intervals", &interval.uid, conn)?;
+    if local
+        .as_ref()
+        .is_some_and(|(_, modified_at)| *modified_at >= interval.modified_at)
+    {
+        return Ok(false);
+    }
+    let Some((tickr_id, _)): Option<(TickrId, String)> =
+        row_by_uid("entries", &interval.task_uid, conn)?
+    else {
+        return Ok(false);
+    };
+    if in_locked_week(&interval.start, interval.end.as_deref(), conn)? {
+        return Ok(false);
+    }
+    match local {
+        Some((id, _)) => {
+            if interval_in_locked_week(id, conn)? {
+                return Ok(false);
+            }
+            conn.execute(
+                "UPDATE intervals SET entry_id = ?1, start_time = ?2, end_time = ?3, notes = ?4,
+                     modified_at = ?5
+                 WHERE id = ?6",
+                params![
+                    tickr_id,
+                    interval.start,
+                    interval.end,
+                    interval.notes,
+                    interval.modified_at,
+                    id
+                ],
+            )?;
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO intervals (entry_id, start_time, end_time, notes, uid, modified_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    tickr_id,
+                    interval.start,
+                    interval.end,
+                    interval.notes,
+                    interval.uid,
+                    interval.modified_at
+                ],
+            )?;
+        }
+    }
+    Ok(true)
+}
+
+fn apply_deletion(deletion: &Deletion, conn: &Connection) -> Result<bool> {
+    let table = deletion.kind.table();
+    let Some((id, modified_at)) = row_by_uid(table, &deletion.uid, conn)? else {
+        return Ok(false);
+    };
+    // Changed here after it was deleted there.
+    if modified_at > deletion.deleted_at {
+        return Ok(false);
+    }
+    match deletion.kind {
+        SyncKind::Project => {
+            let used: bool = conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM entries WHERE project_id = ?1)",
+                [id],
+                |row| row.get(0),
+            )?;
+            if used {
+                return Ok(false);
+            }
+            conn.execute("DELETE FROM projects WHERE id = ?1", [id])?;
+        }
+        SyncKind::Category => {
+            conn.execute(
+                "UPDATE entries SET category_id = NULL WHERE category_id = ?1",
+                [id],
+            )?;
+            conn.execute("DELETE FROM categories WHERE id = ?1", [id])?;
+        }
+        SyncKind::Task => {
+            if super::ensure_tickr_unlocked(id, conn).is_err() {
+                return Ok(false);
+            }
+            super::unblock_tickrs(id, conn)?;
+            conn.execute("DELETE FROM intervals WHERE entry_id = ?1", [id])?;
+            conn.execute("DELETE FROM entries WHERE id = ?1", [id])?;
+        }
+        SyncKind::Interval => {
+            if interval_in_locked_week(id, conn)? {
+                return Ok(false);
+            }
+            conn.execute("DELETE FROM intervals WHERE id = ?1", [id])?;
+        }
+    }
+    Ok(true)
+}
+
+fn interval_in_locked_week(id: u32, conn: &Connection) -> Result<bool> {
+    let (start, end): (String, Option<String>) = conn.query_row(
+        "SELECT start_time, end_time FROM intervals WHERE id = ?1",
+        [id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    in_locked_week(&start, end.as_deref(), conn)
+}
+
+/// Whether stored times fall in a locked week. Times that can't be read are left to
+/// `tickr doctor`.
+fn in_locked_week(start: &str, end: Option<&str>, conn: &Connection) -> Result<bool> {
+    let parse = |value: &str| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|time| time.with_timezone(&chrono::Local))
+    };
+    let Some(start) = parse(start) else {
+        return Ok(false);
+    };
+    let end = end.and_then(parse);
+    Ok(super::ensure_unlocked(start, end, conn).is_err())
+}
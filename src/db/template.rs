@@ -0,0 +1,114 @@
+/// Task templates and the tasks created from them.
+use anyhow::{Result, bail};
+use chrono::{Datelike, NaiveDate, Weekday};
+use rusqlite::Connection;
+
+use crate::types::{TaskTemplate, TemplateId, Tickr, TickrId};
+
+pub fn create_template(template: &TaskTemplate, conn: &Connection) -> Result<TemplateId> {
+    if query_template_by_name(&template.name, conn)?.is_some() {
+        bail!("A template named '{}' already exists.", template.name);
+    }
+    conn.execute(
+        "INSERT INTO templates (name, project_id, category_id, billable, every_workday)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        (
+            &template.name,
+            template.project_id,
+            template.category_id,
+            template.billable,
+            template.every_workday,
+        ),
+    )?;
+    Ok(conn.last_insert_rowid() as TemplateId)
+}
+
+/// Every template with the name of its project, by name.
+pub fn query_templates(conn: &Connection) -> Result<Vec<TaskTemplate>> {
+    let mut stmt = conn.prepare(
+        "
+        SELECT t.id, t.name, t.project_id, p.name, t.category_id, t.billable, t.every_workday,
+               t.last_created
+        FROM templates t
+        JOIN projects p ON p.id = t.project_id
+        ORDER BY t.name COLLATE NOCASE",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(TaskTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            project_id: row.get(2)?,
+            project: row.get(3)?,
+            category_id: row.get(4)?,
+            billable: row.get(5)?,
+            every_workday: row.get(6)?,
+            last_created: row
+                .get::<_, Option<String>>(7)?
+                .and_then(|day| day.parse().ok()),
+        })
+    })?;
+    let mut templates = Vec::new();
+    for row in rows {
+        templates.push(row?);
+    }
+    Ok(templates)
+}
+
+/// Template named `name`, in any case.
+pub fn query_template_by_name(name: &str, conn: &Connection) -> Result<Option<TaskTemplate>> {
+    Ok(query_templates(conn)?
+        .into_iter()
+        .find(|template| template.name.eq_ignore_ascii_case(name)))
+}
+
+pub fn delete_template(id: TemplateId, conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM templates WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Creates a task from `template` in its project.
+pub fn use_template(template: &TaskTemplate, conn: &Connection) -> Result<TickrId> {
+    super::create_tickr(
+        Tickr {
+            id: None,
+            project_id: template.project_id,
+            description: template.name.clone(),
+            category_id: template.category_id,
+            billable: template.billable,
+            done: false,
+            blocked_by: None,
+            notes: None,
+            estimate_minutes: None,
+            archived: false,
+            issue: None,
+            intervals: Vec::new(),
+        },
+        conn,
+    )
+}
+
+/// On Monday to Friday, creates a task from every `every_workday` template that has none from
+/// `today` yet. Returns the names of the created tasks.
+pub fn create_due_templates(today: NaiveDate, conn: &Connection) -> Result<Vec<String>> {
+    if matches!(today.weekday(), Weekday::Sat | Weekday::Sun) {
+        return Ok(Vec::new());
+    }
+    let mut created = Vec::new();
+    for template in query_templates(conn)? {
+        let Some(id) = template.id else {
+            continue;
+        };
+        if !template.every_workday || template.last_created >= Some(today) {
+            continue;
+        }
+        let tx = conn.unchecked_transaction()?;
+        use_template(&template, &tx)?;
+        tx.execute(
+            "UPDATE templates SET last_created = ?1 WHERE id = ?2",
+            (today.to_string(), id),
+        )?;
+        tx.commit()?;
+        created.push(template.name);
+    }
+    Ok(created)
+}
@@ -5,13 +5,26 @@ use rusqlite::Connection;
 
 use crate::{
     db::intervals::{query_intervals_by_tickr_id, query_intervals_by_time_range},
-    types::{CategoryId, Tickr, TickrId, TickrQuery},
+    types::{CategoryId, ProjectId, Tickr, TickrId, TickrQuery},
 };
 
 pub fn create_tickr(arg: Tickr, conn: &Connection) -> Result<TickrId> {
     conn.execute(
-        "INSERT INTO entries (project_id, description, category_id) VALUES (?1, ?2, ?3)",
-        (&arg.project_id, &arg.description, &arg.category_id),
+        "INSERT INTO entries
+            (project_id, description, category_id, billable, done, blocked_by, notes,
+             estimate_minutes, issue)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        (
+            &arg.project_id,
+            &arg.description,
+            &arg.category_id,
+            &arg.billable,
+            &arg.done,
+            &arg.blocked_by,
+            &arg.notes,
+            &arg.estimate_minutes,
+            &arg.issue,
+        ),
     )?;
     Ok(conn.last_insert_rowid() as TickrId)
 }
@@ -34,6 +47,13 @@ pub fn query_tickr_all(conn: &Connection) -> Result<Vec<Tickr>> {
             project_id: row.get(1)?,
             description: row.get(2)?,
             category_id: row.get(3)?,
+            billable: row.get("billable")?,
+            done: row.get("done")?,
+            blocked_by: row.get("blocked_by")?,
+            notes: row.get("notes")?,
+            estimate_minutes: row.get("estimate_minutes")?,
+            archived: row.get("archived")?,
+            issue: row.get("issue")?,
             intervals: Vec::new(),
         })
     })?;
@@ -63,6 +83,13 @@ pub fn query_tickr_by_project(project: String, conn: &Connection) -> Result<Vec<
                 project_id: row.get(1)?,
                 description: row.get(2)?,
                 category_id: row.get(3)?,
+                billable: row.get("billable")?,
+                done: row.get("done")?,
+                blocked_by: row.get("blocked_by")?,
+                notes: row.get("notes")?,
+                estimate_minutes: row.get("estimate_minutes")?,
+                archived: row.get("archived")?,
+                issue: row.get("issue")?,
                 intervals: Vec::new(),
             })
         })?;
@@ -89,6 +116,13 @@ pub fn query_tickr_by_project_id(project_id: u32, conn: &Connection) -> Result<V
             project_id: row.get(1)?,
             description: row.get(2)?,
             category_id: row.get(3)?,
+            billable: row.get("billable")?,
+            done: row.get("done")?,
+            blocked_by: row.get("blocked_by")?,
+            notes: row.get("notes")?,
+            estimate_minutes: row.get("estimate_minutes")?,
+            archived: row.get("archived")?,
+            issue: row.get("issue")?,
             intervals: Vec::new(),
         })
     })?;
@@ -110,8 +144,12 @@ pub fn query_tickr_by_time_range(
     conn: &Connection,
 ) -> Result<Vec<Tickr>> {
     let mut result = Vec::new();
+    let mut seen = std::collections::HashSet::new();
     let candiate_intervals = query_intervals_by_time_range(from, to, conn)?;
     for interval in &candiate_intervals {
+        if !seen.insert(interval.entry_id) {
+            continue;
+        }
         let tickr = query_tickr_by_id(interval.entry_id, conn)?.ok_or_else(|| {
             anyhow::anyhow!(
                 "Interval with entry_id {} has no corresponding tickr entry",
@@ -132,6 +170,13 @@ pub fn query_tickr_by_id(id: TickrId, conn: &Connection) -> Result<Option<Tickr>
             project_id: row.get(1)?,
             description: row.get(2)?,
             category_id: row.get(3)?,
+            billable: row.get("billable")?,
+            done: row.get("done")?,
+            blocked_by: row.get("blocked_by")?,
+            notes: row.get("notes")?,
+            estimate_minutes: row.get("estimate_minutes")?,
+            archived: row.get("archived")?,
+            issue: row.get("issue")?,
             intervals: Vec::new(),
         };
         if let Some(id) = tickr.id {
@@ -143,22 +188,62 @@ pub fn query_tickr_by_id(id: TickrId, conn: &Connection) -> Result<Option<Tickr>
     }
 }
 
+pub fn query_running_tickr(conn: &Connection) -> Result<Option<Tickr>> {
+    let mut stmt = conn.prepare(
+        "SELECT entry_id FROM intervals WHERE end_time IS NULL ORDER BY start_time DESC LIMIT 1",
+    )?;
+    let mut rows = stmt.query([])?;
+    if let Some(row) = rows.next()? {
+        query_tickr_by_id(row.get(0)?, conn)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the task whose interval ended most recently.
+pub fn query_last_stopped_tickr(conn: &Connection) -> Result<Option<Tickr>> {
+    let mut stmt = conn.prepare(
+        "SELECT entry_id FROM intervals WHERE end_time IS NOT NULL
+         ORDER BY julianday(end_time) DESC LIMIT 1",
+    )?;
+    let mut rows = stmt.query([])?;
+    if let Some(row) = rows.next()? {
+        query_tickr_by_id(row.get(0)?, conn)
+    } else {
+        Ok(None)
+    }
+}
+
+/// End of the interval that ended most recently, `None` when nothing was tracked yet.
+pub fn query_last_tracked_time(conn: &Connection) -> Result<Option<DateTime<Local>>> {
+    let mut stmt = conn.prepare(
+        "SELECT end_time FROM intervals WHERE end_time IS NOT NULL
+         ORDER BY julianday(end_time) DESC LIMIT 1",
+    )?;
+    let mut rows = stmt.query([])?;
+    let Some(row) = rows.next()? else {
+        return Ok(None);
+    };
+    let end: String = row.get(0)?;
+    Ok(DateTime::parse_from_rfc3339(&end)
+        .ok()
+        .map(|end| end.with_timezone(&Local)))
+}
+
 pub fn start_tickr(id: TickrId, conn: &Connection) -> Result<()> {
+    super::ensure_unlocked(Local::now(), None, conn)?;
     let now = Local::now().to_rfc3339();
     conn.execute(
         "INSERT INTO intervals (entry_id, start_time) VALUES (?1, ?2)",
         rusqlite::params![id, now],
     )?;
+    crate::status::write_cache(conn).ok();
     Ok(())
 }
 
-pub fn end_tickr(id: TickrId, conn: &Connection) -> Result<()> {
-    let now = Local::now().to_rfc3339();
-    conn.execute(
-        "UPDATE intervals SET end_time = ?1 WHERE entry_id = ?2 AND end_time IS NULL",
-        rusqlite::params![now, id],
-    )?;
-    Ok(())
+/// Ends the running interval of a task now, with a warning when it holds no time.
+pub fn end_tickr(id: TickrId, conn: &Connection) -> Result<Option<super::IntervalError>> {
+    super::stop_tickr_at(id, Local::now(), conn)
 }
 
 pub fn update_tickr_details(
@@ -171,11 +256,117 @@ pub fn update_tickr_details(
         "UPDATE entries SET description = ?1, category_id = ?2 WHERE id = ?3",
         (description, category_id, id),
     )?;
+    crate::status::write_cache(conn).ok();
+    Ok(())
+}
+
+pub fn set_tickr_archived(id: TickrId, archived: bool, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE entries SET archived = ?1 WHERE id = ?2",
+        (archived, id),
+    )?;
+    Ok(())
+}
+
+pub fn set_tickr_billable(id: TickrId, billable: bool, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE entries SET billable = ?1 WHERE id = ?2",
+        (billable, id),
+    )?;
+    Ok(())
+}
+
+pub fn set_tickr_project(id: TickrId, project_id: ProjectId, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE entries SET project_id = ?1 WHERE id = ?2",
+        (project_id, id),
+    )?;
+    Ok(())
+}
+
+pub fn set_tickr_estimate(
+    id: TickrId,
+    estimate_minutes: Option<u32>,
+    conn: &Connection,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE entries SET estimate_minutes = ?1 WHERE id = ?2",
+        (estimate_minutes, id),
+    )?;
+    Ok(())
+}
+
+pub fn set_tickr_done(id: TickrId, done: bool, conn: &Connection) -> Result<()> {
+    conn.execute("UPDATE entries SET done = ?1 WHERE id = ?2", (done, id))?;
+    Ok(())
+}
+
+pub fn set_tickr_blocked_by(
+    id: TickrId,
+    blocked_by: Option<TickrId>,
+    conn: &Connection,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE entries SET blocked_by = ?1 WHERE id = ?2",
+        (blocked_by, id),
+    )?;
+    Ok(())
+}
+
+/// Adds `note` as a new line to the notes of a task.
+pub fn append_tickr_note(id: TickrId, note: &str, conn: &Connection) -> Result<()> {
+    super::ensure_tickr_unlocked(id, conn)?;
+    conn.execute(
+        "UPDATE entries
+         SET notes = CASE WHEN notes IS NULL OR notes = '' THEN ?1 ELSE notes || char(10) || ?1 END
+         WHERE id = ?2",
+        (note, id),
+    )?;
+    Ok(())
+}
+
+/// Links a task to an issue, `None` removes the link.
+pub fn set_tickr_issue(id: TickrId, issue: Option<&str>, conn: &Connection) -> Result<()> {
+    conn.execute("UPDATE entries SET issue = ?1 WHERE id = ?2", (issue, id))?;
+    Ok(())
+}
+
+/// Replaces the notes of a task, `None` clears them.
+pub fn set_tickr_notes(id: TickrId, notes: Option<&str>, conn: &Connection) -> Result<()> {
+    super::ensure_tickr_unlocked(id, conn)?;
+    conn.execute("UPDATE entries SET notes = ?1 WHERE id = ?2", (notes, id))?;
+    Ok(())
+}
+
+/// Returns the tasks waiting on `blocker_id`.
+pub fn query_tickrs_blocked_by(blocker_id: TickrId, conn: &Connection) -> Result<Vec<Tickr>> {
+    let ids = conn
+        .prepare("SELECT id FROM entries WHERE blocked_by = ?1")?
+        .query_map([blocker_id], |row| row.get(0))?
+        .collect::<Result<Vec<TickrId>, _>>()?;
+    let mut tickrs = Vec::new();
+    for id in ids {
+        if let Some(tickr) = query_tickr_by_id(id, conn)? {
+            tickrs.push(tickr);
+        }
+    }
+    Ok(tickrs)
+}
+
+/// Clears the dependency of every task waiting on `blocker_id`.
+pub fn unblock_tickrs(blocker_id: TickrId, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "UPDATE entries SET blocked_by = NULL WHERE blocked_by = ?1",
+        [blocker_id],
+    )?;
     Ok(())
 }
 
 pub fn delete_tickr(id: TickrId, conn: &Connection) -> Result<()> {
+    super::ensure_tickr_unlocked(id, conn)?;
+    unblock_tickrs(id, conn)?;
     conn.execute("DELETE FROM intervals WHERE entry_id = ?1", [id])?;
     conn.execute("DELETE FROM entries WHERE id = ?1", [id])?;
+    crate::status::write_cache(conn).ok();
     Ok(())
 }
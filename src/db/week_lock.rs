@@ -0,0 +1,119 @@
+/// Locked (approved) weeks, whose intervals can't change until the week is unlocked.
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::types::TickrId;
+
+/// A week counts as locked for the seven days starting at its first day.
+const WEEK_DAYS: i64 = 7;
+
+/// Locks the seven days starting at `week_start`. Fails while a running task overlaps them.
+pub fn lock_week(week_start: NaiveDate, conn: &Connection) -> Result<()> {
+    let week_end = week_start + Duration::days(WEEK_DAYS);
+    let running_since: Option<String> = conn
+        .query_row(
+            "SELECT start_time FROM intervals WHERE end_time IS NULL ORDER BY start_time LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(start) = running_since
+        .and_then(|raw| DateTime::parse_from_rfc3339(&raw).ok())
+        .map(|start| start.with_timezone(&Local).date_naive())
+        && start < week_end
+        && Local::now().date_naive() >= week_start
+    {
+        bail!("A task is still running in the week of {week_start}, stop it before locking.");
+    }
+    conn.execute(
+        "INSERT OR IGNORE INTO locked_weeks (week_start, locked_at) VALUES (?1, ?2)",
+        (
+            week_start.format("%Y-%m-%d").to_string(),
+            Local::now().to_rfc3339(),
+        ),
+    )?;
+    Ok(())
+}
+
+/// Unlocks the week containing `date`. Returns the start of the unlocked week, if any was.
+pub fn unlock_week(date: NaiveDate, conn: &Connection) -> Result<Option<NaiveDate>> {
+    let Some(week_start) = locked_week_containing(date, conn)? else {
+        return Ok(None);
+    };
+    conn.execute(
+        "DELETE FROM locked_weeks WHERE week_start = ?1",
+        [week_start.format("%Y-%m-%d").to_string()],
+    )?;
+    Ok(Some(week_start))
+}
+
+/// First days of the locked weeks with the time they were locked, oldest first.
+pub fn query_locked_weeks(conn: &Connection) -> Result<Vec<(NaiveDate, DateTime<Local>)>> {
+    let mut stmt =
+        conn.prepare("SELECT week_start, locked_at FROM locked_weeks ORDER BY week_start")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut weeks = Vec::new();
+    for row in rows {
+        let (week_start, locked_at) = row?;
+        let Ok(week_start) = NaiveDate::parse_from_str(&week_start, "%Y-%m-%d") else {
+            continue;
+        };
+        let locked_at = DateTime::parse_from_rfc3339(&locked_at)
+            .map(|dt| dt.with_timezone(&Local))
+            .unwrap_or_else(|_| Local::now());
+        weeks.push((week_start, locked_at));
+    }
+    Ok(weeks)
+}
+
+/// Start of the locked week that contains `date`.
+pub fn locked_week_containing(date: NaiveDate, conn: &Connection) -> Result<Option<NaiveDate>> {
+    locked_week_overlapping(date, date, conn)
+}
+
+fn locked_week_overlapping(
+    first: NaiveDate,
+    last: NaiveDate,
+    conn: &Connection,
+) -> Result<Option<NaiveDate>> {
+    let week_start: Option<String> = conn
+        .query_row(
+            "SELECT week_start FROM locked_weeks WHERE week_start > ?1 AND week_start <= ?2
+             ORDER BY week_start LIMIT 1",
+            (
+                (first - Duration::days(WEEK_DAYS))
+                    .format("%Y-%m-%d")
+                    .to_string(),
+                last.format("%Y-%m-%d").to_string(),
+            ),
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(week_start.and_then(|raw| NaiveDate::parse_from_str(&raw, "%Y-%m-%d").ok()))
+}
+
+/// Fails when the time from `start` to `end` (now while running) touches a locked week.
+pub fn ensure_unlocked(
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    conn: &Connection,
+) -> Result<()> {
+    let end = end.unwrap_or_else(Local::now).max(start);
+    if let Some(week_start) = locked_week_overlapping(start.date_naive(), end.date_naive(), conn)? {
+        bail!(
+            "The week of {week_start} is locked, unlock it with `tickr week unlock {week_start}` first."
+        );
+    }
+    Ok(())
+}
+
+/// Fails when any interval of the task lies in a locked week.
+pub fn ensure_tickr_unlocked(tickr_id: TickrId, conn: &Connection) -> Result<()> {
+    for interval in super::intervals::query_intervals_by_tickr_id(tickr_id, conn)? {
+        ensure_unlocked(interval.start_time, interval.end_time, conn)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,183 @@
+/// Zip bundle for bug reports: platform info, the config with personal values redacted and
+/// database statistics. Only counts and the schema are read from the database, never its rows.
+use std::fmt::Write as _;
+use std::io::{Seek, Write};
+
+use anyhow::Result;
+use rusqlite::Connection;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::config;
+
+/// Config keys whose values are replaced, because they name people, files or credentials.
+const REDACTED_KEYS: [&str; 3] = ["db", "name", "logo"];
+/// Parts of key names that mark a value as a credential.
+const SECRET_KEY_PARTS: [&str; 4] = ["token", "secret", "password", "api_key"];
+
+/// Writes the bundle for the database behind `conn` to `out`.
+pub fn write_bundle<W: Write + Seek>(conn: &Connection, out: W) -> Result<()> {
+    let mut zip = ZipWriter::new(out);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("system.txt", options)?;
+    zip.write_all(system_info(conn.path().unwrap_or_default()).as_bytes())?;
+
+    zip.start_file("config.toml", options)?;
+    zip.write_all(redacted_config().as_bytes())?;
+
+    zip.start_file("database.txt", options)?;
+    zip.write_all(database_stats(conn)?.as_bytes())?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn system_info(db_path: &str) -> String {
+    let mut info = String::new();
+    let _ = writeln!(info, "Tickr {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        info,
+        "Platform: {} {} ({})",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::FAMILY
+    );
+    let _ = writeln!(
+        info,
+        "Features: {}",
+        if cfg!(feature = "pdf") { "pdf" } else { "none" }
+    );
+    for variable in ["TERM", "COLORTERM", "TERM_PROGRAM", "LANG"] {
+        let value = std::env::var(variable).unwrap_or_default();
+        let _ = writeln!(info, "{variable}: {value}");
+    }
+    if let Ok((columns, rows)) = crossterm::terminal::size() {
+        let _ = writeln!(info, "Terminal size: {columns}x{rows}");
+    }
+    let config_path = config::config_path();
+    let _ = writeln!(
+        info,
+        "Config file: {}",
+        match &config_path {
+            Some(path) if path.exists() => "present",
+            Some(_) => "missing, defaults apply",
+            None => "no config directory",
+        }
+    );
+    let _ = writeln!(
+        info,
+        "Config file set by {}: {}",
+        config::CONFIG_PATH_ENV,
+        std::env::var_os(config::CONFIG_PATH_ENV).is_some()
+    );
+    let db_size = std::fs::metadata(db_path).map(|meta| meta.len()).ok();
+    let _ = writeln!(
+        info,
+        "Database file size: {}",
+        db_size.map_or("unknown".to_string(), |size| format!("{size} bytes"))
+    );
+    let _ = writeln!(info, "Log file: none, Tickr does not keep a log");
+    info
+}
+
+/// The config file with the values of personal keys replaced, plus whether it loads.
+fn redacted_config() -> String {
+    let Some(path) = config::config_path().filter(|path| path.exists()) else {
+        return "# No config file, defaults apply.\n".to_string();
+    };
+    let raw = match std::fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) => return format!("# Config file could not be read: {err}\n"),
+    };
+    let mut redacted = match config::load() {
+        Ok(_) => "# Config file loads without errors.\n".to_string(),
+        Err(err) => {
+            let mut message = String::from("# Config file does not load:\n");
+            for line in format!("{err:#}").lines() {
+                let _ = writeln!(message, "# {line}");
+            }
+            message
+        }
+    };
+    for line in raw.lines() {
+        redacted.push_str(&redact_line(line));
+        redacted.push('\n');
+    }
+    redacted
+}
+
+fn redact_line(line: &str) -> String {
+    let Some((key, _)) = line.split_once('=') else {
+        return line.to_string();
+    };
+    let name = key.trim().trim_matches('"').to_lowercase();
+    let secret = REDACTED_KEYS.contains(&name.as_str())
+        || SECRET_KEY_PARTS.iter().any(|part| name.contains(part));
+    if secret && !line.trim_start().starts_with('#') {
+        format!("{key}= \"<redacted>\"")
+    } else {
+        line.to_string()
+    }
+}
+
+/// SQLite version, schema, size and row counts of every table.
+fn database_stats(conn: &Connection) -> Result<String> {
+    let mut stats = String::new();
+    let version: String = conn.query_row("SELECT sqlite_version()", [], |row| row.get(0))?;
+    let _ = writeln!(stats, "SQLite {version}");
+    for pragma in [
+        "user_version",
+        "page_size",
+        "page_count",
+        "freelist_count",
+        "journal_mode",
+    ] {
+        let value: String = conn.query_row(&format!("PRAGMA {pragma}"), [], |row| {
+            row.get::<_, rusqlite::types::Value>(0)
+                .map(|value| match value {
+                    rusqlite::types::Value::Integer(number) => number.to_string(),
+                    rusqlite::types::Value::Text(text) => text,
+                    other => format!("{other:?}"),
+                })
+        })?;
+        let _ = writeln!(stats, "{pragma}: {value}");
+    }
+    let check: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+    let _ = writeln!(stats, "quick_check: {check}");
+
+    let mut stmt = conn.prepare(
+        "SELECT name, sql FROM sqlite_master
+         WHERE type IN ('table', 'index') AND name NOT LIKE 'sqlite_%' AND sql IS NOT NULL
+         ORDER BY type DESC, name",
+    )?;
+    let objects = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let _ = writeln!(stats, "\nRow counts");
+    for (name, sql) in &objects {
+        if !sql.starts_with("CREATE TABLE") {
+            continue;
+        }
+        let count: i64 =
+            conn.query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+                row.get(0)
+            })?;
+        let _ = writeln!(stats, "  {name}: {count}");
+    }
+    let running: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM intervals WHERE end_time IS NULL",
+        [],
+        |row| row.get(0),
+    )?;
+    let _ = writeln!(stats, "  running intervals: {running}");
+
+    let _ = writeln!(stats, "\nSchema");
+    for (_, sql) in &objects {
+        let _ = writeln!(stats, "{sql};");
+    }
+    Ok(stats)
+}
@@ -0,0 +1,247 @@
+//! Consistency checks of the stored intervals for `tickr doctor`: overlaps, more than one running
+//! interval, intervals that end before they start and timestamps that can't be read, and on
+//! request intervals that span midnight. Each finding comes with the repair `tickr doctor` offers
+//! for it.
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use rusqlite::Connection;
+
+use crate::types::{IntervalId, RawInterval};
+use crate::ui::local_start_of_day;
+use crate::{daemon, db};
+
+/// Formats besides RFC 3339 that timestamps written by hand or by other tools tend to use. They
+/// are read as local time.
+const LENIENT_FORMATS: [&str; 3] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Repair {
+    Delete(IntervalId),
+    SetTimes {
+        id: IntervalId,
+        start: DateTime<Local>,
+        end: Option<DateTime<Local>>,
+    },
+    /// One interval per day the interval spans.
+    SplitAtMidnight(IntervalId),
+}
+
+impl Repair {
+    pub fn describe(&self) -> String {
+        match self {
+            Repair::Delete(id) => format!("delete interval #{id}"),
+            Repair::SetTimes { id, start, end } => {
+                format!("set interval #{id} to {}", span(*start, *end))
+            }
+            Repair::SplitAtMidnight(id) => format!("split interval #{id} at midnight"),
+        }
+    }
+
+    pub fn apply(&self, conn: &Connection) -> Result<()> {
+        match self {
+            Repair::Delete(id) => daemon::delete_interval(*id, conn),
+            Repair::SetTimes { id, start, end } => {
+                daemon::set_interval_times(*id, *start, *end, conn)
+            }
+            Repair::SplitAtMidnight(id) => db::split_interval_at_midnight(*id, conn).map(|_| ()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub description: String,
+    pub repair: Repair,
+}
+
+/// An interval whose times could be read.
+struct Checked {
+    raw: RawInterval,
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+}
+
+impl Checked {
+    fn label(&self) -> String {
+        format!(
+            "#{} {} > {} {}",
+            self.raw.id,
+            self.raw.project,
+            self.raw.task,
+            span(self.start, self.end)
+        )
+    }
+}
+
+/// Every problem in the database, in the order the checks run. Ended intervals that span
+/// midnight only count with `split_at_midnight`.
+pub fn scan(conn: &Connection, split_at_midnight: bool) -> Result<Vec<Finding>> {
+    let now = Local::now();
+    let mut findings = Vec::new();
+    let mut checked = Vec::new();
+    for raw in db::query_raw_intervals(conn)? {
+        let start = raw.start_time.as_deref().and_then(parse_stored);
+        let end = raw.end_time.as_deref().map(parse_stored);
+        match (start, end) {
+            (Some(start), None) => checked.push(Checked {
+                raw,
+                start,
+                end: None,
+            }),
+            (Some(start), Some(Some(end))) => checked.push(Checked {
+                raw,
+                start,
+                end: Some(end),
+            }),
+            _ => findings.push(unreadable(&raw)),
+        }
+    }
+
+    // The other checks run on these once they are stored as RFC 3339.
+    checked.retain(|interval| {
+        let stored_start = interval.raw.start_time.as_deref().unwrap_or_default();
+        let stored_end = interval.raw.end_time.as_deref();
+        let normalized = DateTime::parse_from_rfc3339(stored_start).is_ok()
+            && stored_end.is_none_or(|end| DateTime::parse_from_rfc3339(end).is_ok());
+        if !normalized {
+            findings.push(Finding {
+                description: format!(
+                    "#{} {} > {} has timestamps in an unexpected format ({} - {})",
+                    interval.raw.id,
+                    interval.raw.project,
+                    interval.raw.task,
+                    stored_start,
+                    stored_end.unwrap_or("running")
+                ),
+                repair: Repair::SetTimes {
+                    id: interval.raw.id,
+                    start: interval.start,
+                    end: interval.end,
+                },
+            });
+        }
+        normalized
+    });
+
+    checked.retain(|interval| match interval.end {
+        Some(end) if end < interval.start => {
+            findings.push(Finding {
+                description: format!(
+                    "{} ends before it starts, which counts as no time",
+                    interval.label()
+                ),
+                repair: Repair::Delete(interval.raw.id),
+            });
+            false
+        }
+        _ => true,
+    });
+    checked.sort_by_key(|interval| (interval.start, interval.raw.id));
+
+    let open: Vec<&Checked> = checked
+        .iter()
+        .filter(|interval| interval.end.is_none())
+        .collect();
+    for pair in open.windows(2) {
+        let (earlier, later) = (pair[0], pair[1]);
+        let repair = if later.start > earlier.start {
+            Repair::SetTimes {
+                id: earlier.raw.id,
+                start: earlier.start,
+                end: Some(later.start),
+            }
+        } else {
+            Repair::Delete(earlier.raw.id)
+        };
+        findings.push(Finding {
+            description: format!(
+                "{} is running at the same time as {}",
+                earlier.label(),
+                later.label()
+            ),
+            repair,
+        });
+    }
+
+    // Running intervals count up to now. Overlaps between two of them are reported above.
+    let mut latest: Option<(&Checked, DateTime<Local>)> = None;
+    for interval in &checked {
+        let end = interval.end.unwrap_or(now);
+        if let Some((previous, previous_end)) = latest
+            && interval.start < previous_end
+            && !(previous.end.is_none() && interval.end.is_none())
+        {
+            // A running interval was most likely forgotten when the next one started.
+            let repair = if previous.end.is_none() {
+                Repair::SetTimes {
+                    id: previous.raw.id,
+                    start: previous.start,
+                    end: Some(interval.start),
+                }
+            } else if end <= previous_end {
+                Repair::Delete(interval.raw.id)
+            } else {
+                Repair::SetTimes {
+                    id: interval.raw.id,
+                    start: previous_end,
+                    end: interval.end,
+                }
+            };
+            findings.push(Finding {
+                description: format!("{} overlaps {}", interval.label(), previous.label()),
+                repair,
+            });
+        }
+        if latest.is_none_or(|(_, previous_end)| end > previous_end) {
+            latest = Some((interval, end));
+        }
+    }
+
+    if split_at_midnight {
+        for interval in &checked {
+            let Some(end) = interval.end else {
+                continue;
+            };
+            if end > local_start_of_day(interval.start.date_naive() + chrono::Duration::days(1)) {
+                findings.push(Finding {
+                    description: format!("{} spans midnight", interval.label()),
+                    repair: Repair::SplitAtMidnight(interval.raw.id),
+                });
+            }
+        }
+    }
+    Ok(findings)
+}
+
+fn unreadable(raw: &RawInterval) -> Finding {
+    let start = raw.start_time.as_deref().unwrap_or("(none)");
+    let end = raw.end_time.as_deref().unwrap_or("running");
+    Finding {
+        description: format!(
+            "#{} {} > {} has timestamps that can't be read ({start} - {end})",
+            raw.id, raw.project, raw.task
+        ),
+        repair: Repair::Delete(raw.id),
+    }
+}
+
+/// A stored timestamp: RFC 3339 as Tickr writes it, or one of [`LENIENT_FORMATS`].
+fn parse_stored(value: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Local));
+    }
+    LENIENT_FORMATS.iter().find_map(|format| {
+        let time = NaiveDateTime::parse_from_str(value, format).ok()?;
+        Local.from_local_datetime(&time).earliest()
+    })
+}
+
+fn span(start: DateTime<Local>, end: Option<DateTime<Local>>) -> String {
+    let end = match end {
+        Some(end) if end.date_naive() == start.date_naive() => end.format("%H:%M").to_string(),
+        Some(end) => end.format("%Y-%m-%d %H:%M").to_string(),
+        None => "running".to_string(),
+    };
+    format!("{} - {end}", start.format("%Y-%m-%d %H:%M"))
+}
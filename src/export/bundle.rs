@@ -0,0 +1,210 @@
+/// Zip bundle of everything a project manager needs for a range: the JSON export, a weekly
+/// report and an invoice draft (plus the PDF timesheet when built with PDF support).
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::io::{Seek, Write};
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate, Timelike};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use super::{ExportFormat, ExportRecord};
+use crate::billing::Amounts;
+use crate::config::Config;
+use crate::ui::format_duration;
+
+/// Time and earnings of one project in a week or in the whole range.
+#[derive(Default)]
+struct Totals {
+    seconds: i64,
+    billable_seconds: i64,
+    billed_seconds: i64,
+    amounts: Amounts,
+}
+
+impl Totals {
+    fn add(&mut self, record: &ExportRecord, seconds: i64) {
+        self.seconds += seconds;
+        if record.billable {
+            self.billable_seconds += seconds;
+        }
+        self.billed_seconds += record.billed_seconds.unwrap_or(0);
+        if let Some(amount) = record.amount {
+            self.amounts.add(record.currency, amount);
+        }
+    }
+
+    fn merge(&mut self, other: &Totals) {
+        self.seconds += other.seconds;
+        self.billable_seconds += other.billable_seconds;
+        self.billed_seconds += other.billed_seconds;
+        self.amounts.merge(&other.amounts);
+    }
+}
+
+/// Writes the bundle for the records collected from `from` to `to`, inclusive.
+pub fn write_bundle<W: Write + Seek>(
+    records: &[ExportRecord],
+    from: NaiveDate,
+    to: NaiveDate,
+    config: &Config,
+    out: W,
+) -> Result<()> {
+    let mut zip = ZipWriter::new(out);
+    let now = Local::now();
+    let mut options = SimpleFileOptions::default();
+    if let Ok(modified) = zip::DateTime::from_date_and_time(
+        now.year().clamp(1980, 2107) as u16,
+        now.month() as u8,
+        now.day() as u8,
+        now.hour() as u8,
+        now.minute() as u8,
+        now.second() as u8,
+    ) {
+        options = options.last_modified_time(modified);
+    }
+
+    zip.start_file("tickr-export.json", options)?;
+    super::write_records(records, ExportFormat::Json, Some(from), Some(to), &mut zip)?;
+
+    zip.start_file("weekly-report.txt", options)?;
+    zip.write_all(weekly_report(records, from, to, config).as_bytes())?;
+
+    zip.start_file("invoice-draft.txt", options)?;
+    zip.write_all(invoice_draft(records, from, to).as_bytes())?;
+
+    #[cfg(feature = "pdf")]
+    {
+        zip.start_file("timesheet.pdf", options)?;
+        super::pdf::write_timesheet(records, Some(from), Some(to), &config.timesheet, &mut zip)?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn record_seconds(record: &ExportRecord) -> Option<i64> {
+    let start = record.start?;
+    Some(
+        record
+            .duration_seconds
+            .unwrap_or_else(|| (Local::now() - start).num_seconds())
+            .max(0),
+    )
+}
+
+/// Totals per calendar week and project.
+fn weekly_report(
+    records: &[ExportRecord],
+    from: NaiveDate,
+    to: NaiveDate,
+    config: &Config,
+) -> String {
+    let mut weeks: BTreeMap<NaiveDate, BTreeMap<&str, Totals>> = BTreeMap::new();
+    for record in records {
+        let (Some(start), Some(seconds)) = (record.start, record_seconds(record)) else {
+            continue;
+        };
+        weeks
+            .entry(config.calendar_week_start_for(start.date_naive()))
+            .or_default()
+            .entry(&record.project)
+            .or_default()
+            .add(record, seconds);
+    }
+
+    let mut report = format!("Weekly report from {from} to {to}\n");
+    if weeks.is_empty() {
+        report.push_str("\nNo time tracked in this range.\n");
+        return report;
+    }
+    let duration = |seconds: i64| format_duration(Duration::seconds(seconds));
+    let mut total = Totals::default();
+    for (week_start, projects) in &weeks {
+        let _ = writeln!(
+            report,
+            "\nWeek of {week_start} to {}",
+            *week_start + Duration::days(6)
+        );
+        let width = projects.keys().map(|name| name.chars().count()).max();
+        let width = width.unwrap_or(0).max("Total".len());
+        let mut week = Totals::default();
+        for (project, totals) in projects {
+            let _ = write!(
+                report,
+                "  {project:<width$}  {}  billable {}  billed {}",
+                duration(totals.seconds),
+                duration(totals.billable_seconds),
+                duration(totals.billed_seconds)
+            );
+            if !totals.amounts.is_empty() {
+                let _ = write!(report, "  {}", totals.amounts);
+            }
+            report.push('\n');
+            week.merge(totals);
+        }
+        let _ = writeln!(report, "  {:<width$}  {}", "Total", duration(week.seconds));
+        total.merge(&week);
+    }
+    let _ = writeln!(
+        report,
+        "\nTotal {}, billable {}, billed {}",
+        duration(total.seconds),
+        duration(total.billable_seconds),
+        duration(total.billed_seconds)
+    );
+    if !total.amounts.is_empty() {
+        let _ = writeln!(report, "Amount {}", total.amounts);
+    }
+    report
+}
+
+/// Billed time and amounts per project, ready to copy into an invoice. It is addressed to the
+/// client when all the billable work is for one.
+fn invoice_draft(records: &[ExportRecord], from: NaiveDate, to: NaiveDate) -> String {
+    let mut projects: BTreeMap<&str, Totals> = BTreeMap::new();
+    let mut clients: BTreeSet<Option<&str>> = BTreeSet::new();
+    for record in records.iter().filter(|record| record.billable) {
+        clients.insert(record.client.as_deref());
+        if let Some(seconds) = record_seconds(record) {
+            projects
+                .entry(&record.project)
+                .or_default()
+                .add(record, seconds);
+        }
+    }
+
+    let mut draft = match clients.into_iter().collect::<Vec<_>>().as_slice() {
+        [Some(client)] => format!("Invoice draft for {client}, {from} to {to}\n\n"),
+        _ => format!("Invoice draft for {from} to {to}\n\n"),
+    };
+    if projects.is_empty() {
+        draft.push_str("No billable time in this range.\n");
+        return draft;
+    }
+    let width = projects.keys().map(|name| name.chars().count()).max();
+    let width = width.unwrap_or(0).max("Total".len());
+    let mut total = Totals::default();
+    for (project, totals) in &projects {
+        let hours = totals.billed_seconds as f64 / 3600.0;
+        let _ = write!(draft, "{project:<width$}  {hours:>8.2} h");
+        if !totals.amounts.is_empty() {
+            let _ = write!(draft, "  {}", totals.amounts);
+        }
+        draft.push('\n');
+        total.merge(totals);
+    }
+    let hours = total.billed_seconds as f64 / 3600.0;
+    let _ = write!(
+        draft,
+        "{}\n{:<width$}  {hours:>8.2} h",
+        "-".repeat(width + 12),
+        "Total"
+    );
+    if !total.amounts.is_empty() {
+        let _ = write!(draft, "  {}", total.amounts);
+    }
+    draft.push('\n');
+    draft
+}
@@ -0,0 +1,156 @@
+/// Differences between two sets of export records, e.g. an export sent earlier and the database
+/// after edits, merges or a sync.
+use std::fmt::Display;
+
+use chrono::{DateTime, Local};
+
+use super::ExportRecord;
+
+#[derive(Debug, Default)]
+pub struct RecordDiff {
+    pub added: Vec<ExportRecord>,
+    pub removed: Vec<ExportRecord>,
+    /// Old and new version of intervals that were edited.
+    pub changed: Vec<(ExportRecord, ExportRecord)>,
+}
+
+impl RecordDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares `old` with `new`. Exports carry no interval ids, so records are paired by project,
+/// task and start first. Of the rest, a record is taken as edited when another one has the same
+/// start and end (a renamed or moved task), or belongs to the same task and overlaps it (a moved
+/// start). Unstarted tasks are paired by project and task.
+pub fn diff_records(old: &[ExportRecord], new: &[ExportRecord]) -> RecordDiff {
+    let mut unmatched: Vec<Option<&ExportRecord>> = new.iter().map(Some).collect();
+    let mut leftover = Vec::new();
+    let mut diff = RecordDiff::default();
+
+    for record in old {
+        let found = take_match(&mut unmatched, |other| {
+            other.project == record.project
+                && other.task == record.task
+                && other.start == record.start
+        });
+        match found {
+            Some(other) if other == record => {}
+            Some(other) => diff.changed.push((record.clone(), other.clone())),
+            None => leftover.push(record),
+        }
+    }
+    for record in leftover {
+        let found = take_match(&mut unmatched, |other| {
+            record.start.is_some()
+                && ((other.start == record.start && other.end == record.end)
+                    || (other.project == record.project
+                        && other.task == record.task
+                        && overlaps(record, other)))
+        });
+        match found {
+            Some(other) => diff.changed.push((record.clone(), other.clone())),
+            None => diff.removed.push(record.clone()),
+        }
+    }
+    diff.added = unmatched.into_iter().flatten().cloned().collect();
+    diff
+}
+
+fn take_match<'a>(
+    candidates: &mut [Option<&'a ExportRecord>],
+    matches: impl Fn(&ExportRecord) -> bool,
+) -> Option<&'a ExportRecord> {
+    candidates
+        .iter_mut()
+        .find(|candidate| candidate.is_some_and(&matches))?
+        .take()
+}
+
+fn overlaps(a: &ExportRecord, b: &ExportRecord) -> bool {
+    let now = Local::now();
+    match (a.start, b.start) {
+        (Some(a_start), Some(b_start)) => {
+            a_start < b.end.unwrap_or(now) && b_start < a.end.unwrap_or(now)
+        }
+        _ => false,
+    }
+}
+
+/// The fields that differ between two versions of a record, as `(name, old, new)`.
+pub fn changed_fields(
+    old: &ExportRecord,
+    new: &ExportRecord,
+) -> Vec<(&'static str, String, String)> {
+    let mut fields = Vec::new();
+    let mut compare = |name: &'static str, old: String, new: String| {
+        if old != new {
+            fields.push((name, old, new));
+        }
+    };
+    compare("project", old.project.clone(), new.project.clone());
+    compare("task", old.task.clone(), new.task.clone());
+    compare("category", optional(&old.category), optional(&new.category));
+    compare(
+        "category color",
+        optional(&old.category_color),
+        optional(&new.category_color),
+    );
+    compare(
+        "billable",
+        old.billable.to_string(),
+        new.billable.to_string(),
+    );
+    compare("start", time(old.start), time(new.start));
+    compare("end", time(old.end), time(new.end));
+    compare(
+        "duration",
+        seconds(old.duration_seconds),
+        seconds(new.duration_seconds),
+    );
+    compare(
+        "billed",
+        seconds(old.billed_seconds),
+        seconds(new.billed_seconds),
+    );
+    compare(
+        "rate",
+        optional(&old.hourly_rate),
+        optional(&new.hourly_rate),
+    );
+    compare("amount", optional(&old.amount), optional(&new.amount));
+    compare("currency", optional(&old.currency), optional(&new.currency));
+    compare("task notes", notes(&old.task_notes), notes(&new.task_notes));
+    compare("notes", notes(&old.notes), notes(&new.notes));
+    compare("client", optional(&old.client), optional(&new.client));
+    fields
+}
+
+fn optional<T: Display>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| "none".to_string(), |value| value.to_string())
+}
+
+/// Notes on one line, so every changed field takes a single line.
+fn notes(value: &Option<String>) -> String {
+    match value.as_deref().filter(|notes| !notes.is_empty()) {
+        Some(notes) => format!("\"{}\"", notes.lines().collect::<Vec<_>>().join(" / ")),
+        None => "none".to_string(),
+    }
+}
+
+fn time(value: Option<DateTime<Local>>) -> String {
+    value.map_or_else(
+        || "none".to_string(),
+        |time| time.format("%Y-%m-%d %H:%M:%S").to_string(),
+    )
+}
+
+fn seconds(value: Option<i64>) -> String {
+    value.map_or_else(
+        || "none".to_string(),
+        |seconds| crate::ui::format_duration(chrono::Duration::seconds(seconds)),
+    )
+}
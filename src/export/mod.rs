@@ -0,0 +1,237 @@
+/// Export of tracked data as CSV, JSON, a PDF timesheet or an SVG timeline.
+///
+/// All formats share [`ExportRecord`]: one record per interval, plus one record without
+/// times for tasks that were never started. `tickr import` reads CSV and JSON files back.
+pub mod bundle;
+pub mod diff;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod svg;
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use clap::ValueEnum;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::billing::{BillingRules, Currency};
+use crate::{db, types};
+
+/// Version of the JSON document layout.
+pub const JSON_EXPORT_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    /// Timesheet for clients, see `[timesheet]` in the config file.
+    #[cfg(feature = "pdf")]
+    Pdf,
+}
+
+/// A single exported interval (or an unstarted task when `start` is empty).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportRecord {
+    pub project: String,
+    pub task: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub category_color: Option<String>,
+    /// Files written before the billable flag existed count as billable.
+    #[serde(default = "default_billable")]
+    pub billable: bool,
+    #[serde(default)]
+    pub start: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub end: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub duration_seconds: Option<i64>,
+    /// Billable duration after the project's rounding rules, empty for non-billable work.
+    #[serde(default)]
+    pub billed_seconds: Option<i64>,
+    #[serde(default)]
+    pub hourly_rate: Option<f64>,
+    #[serde(default)]
+    pub amount: Option<f64>,
+    /// Currency of `hourly_rate` and `amount`.
+    #[serde(default)]
+    pub currency: Option<Currency>,
+    /// Notes of the task, repeated on each of its intervals.
+    #[serde(default)]
+    pub task_notes: Option<String>,
+    /// Notes of the interval.
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Client of the project. Last, so that columns of older CSV files keep their place.
+    #[serde(default)]
+    pub client: Option<String>,
+}
+
+fn default_billable() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonExport {
+    pub version: u32,
+    pub exported_at: DateTime<Local>,
+    pub records: Vec<ExportRecord>,
+}
+
+/// Collects records for every task, keeping only intervals overlapping `[from, to)` when given.
+/// Billed time and amounts use the project's rules, falling back to `billing`, with the rate
+/// that applied when each interval started.
+pub fn collect_records(
+    from: Option<DateTime<Local>>,
+    to: Option<DateTime<Local>>,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<Vec<ExportRecord>> {
+    let clients: HashMap<types::ClientId, String> = db::query_clients(conn)?
+        .into_iter()
+        .map(|client| (client.id, client.name))
+        .collect();
+    let projects: HashMap<types::ProjectId, (String, Option<String>)> = db::query_projects(conn)?
+        .into_iter()
+        .filter_map(|project| {
+            let client = project.client_id.and_then(|id| clients.get(&id).cloned());
+            project.id.map(|id| (id, (project.name, client)))
+        })
+        .collect();
+    let categories: HashMap<types::CategoryId, types::TickrCategory> = db::query_categories(conn)?
+        .into_iter()
+        .map(|category| (category.id, category))
+        .collect();
+    let project_rules = db::query_all_project_billing(conn)?;
+    let rate_history = db::query_rate_history(conn)?;
+    let ranged = from.is_some() || to.is_some();
+    let now = Local::now();
+
+    let mut records = Vec::new();
+    for tickr in db::query_tickr(types::TickrQuery::All, conn)? {
+        let (project, client) = projects.get(&tickr.project_id).cloned().unwrap_or_default();
+        let category = tickr.category_id.and_then(|id| categories.get(&id));
+        let project_billing = project_rules
+            .get(&tickr.project_id)
+            .copied()
+            .unwrap_or_default();
+        let rules = project_billing.or(billing);
+        let base = ExportRecord {
+            project,
+            task: tickr.description.clone(),
+            category: category.map(|category| category.name.clone()),
+            category_color: category.map(|category| category.color.clone()),
+            billable: tickr.billable,
+            start: None,
+            end: None,
+            duration_seconds: None,
+            billed_seconds: None,
+            hourly_rate: None,
+            amount: None,
+            currency: None,
+            task_notes: tickr.notes.clone(),
+            notes: None,
+            client,
+        };
+        if tickr.intervals.is_empty() {
+            if !ranged {
+                records.push(base);
+            }
+            continue;
+        }
+        for interval in &tickr.intervals {
+            let end = interval.end_time.unwrap_or(now);
+            if from.is_some_and(|from| end <= from)
+                || to.is_some_and(|to| interval.start_time >= to)
+            {
+                continue;
+            }
+            let duration_seconds = interval
+                .end_time
+                .map(|end| (end - interval.start_time).num_seconds());
+            let rules = BillingRules {
+                hourly_rate: rate_history.rate_at(
+                    tickr.project_id,
+                    interval.start_time,
+                    project_billing.hourly_rate,
+                    billing.hourly_rate,
+                ),
+                ..rules
+            };
+            let billed_seconds = duration_seconds
+                .filter(|_| tickr.billable)
+                .map(|seconds| rules.round(seconds));
+            records.push(ExportRecord {
+                start: Some(interval.start_time),
+                end: interval.end_time,
+                duration_seconds,
+                billed_seconds,
+                hourly_rate: rules.hourly_rate.filter(|_| tickr.billable),
+                amount: billed_seconds.and_then(|seconds| rules.amount(seconds)),
+                currency: rules.currency.filter(|_| tickr.billable),
+                notes: interval.notes.clone(),
+                ..base.clone()
+            });
+        }
+    }
+    records.sort_by(|a, b| (a.start, &a.project, &a.task).cmp(&(b.start, &b.project, &b.task)));
+    Ok(records)
+}
+
+/// Keeps only intervals that started in a locked (approved) week.
+pub fn retain_approved(records: &mut Vec<ExportRecord>, conn: &Connection) -> Result<()> {
+    let weeks: Vec<NaiveDate> = db::query_locked_weeks(conn)?
+        .into_iter()
+        .map(|(week_start, _)| week_start)
+        .collect();
+    records.retain(|record| {
+        record.start.is_some_and(|start| {
+            let day = start.date_naive();
+            weeks
+                .iter()
+                .any(|week_start| *week_start <= day && day < *week_start + Duration::days(7))
+        })
+    });
+    Ok(())
+}
+
+/// Writes `records` in the requested format.
+/// `from` and `to` are the days the records were collected for, shown in PDF timesheets.
+#[cfg_attr(not(feature = "pdf"), allow(unused_variables))]
+pub fn write_records(
+    records: &[ExportRecord],
+    format: ExportFormat,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    out: &mut dyn Write,
+) -> Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for record in records {
+                writer.serialize(record)?;
+            }
+            writer.flush()?;
+        }
+        ExportFormat::Json => {
+            let document = JsonExport {
+                version: JSON_EXPORT_VERSION,
+                exported_at: Local::now(),
+                records: records.to_vec(),
+            };
+            serde_json::to_writer_pretty(&mut *out, &document)?;
+            writeln!(out)?;
+        }
+        #[cfg(feature = "pdf")]
+        ExportFormat::Pdf => {
+            let config = crate::config::load()?;
+            pdf::write_timesheet(records, from, to, &config.timesheet, out)?;
+        }
+    }
+    Ok(())
+}
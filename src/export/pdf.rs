@@ -0,0 +1,393 @@
+/// Client-facing timesheet export as a PDF document.
+///
+/// The document only uses the standard Helvetica fonts, so no font files or external tools are
+/// needed. Text is encoded as WinAnsi; characters outside of it are printed as `?`.
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use chrono::{Duration, Local, NaiveDate};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+
+use super::ExportRecord;
+use crate::billing::Amounts;
+use crate::config::TimesheetConfig;
+use crate::ui::format_duration;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const ROW_HEIGHT: f32 = 15.0;
+const FONT_SIZE: f32 = 9.0;
+const LOGO_HEIGHT: f32 = 48.0;
+
+/// Left edges of the date, project, task and time columns; durations are right aligned.
+const COLUMNS: [f32; 4] = [MARGIN, 120.0, 230.0, 410.0];
+const DURATION_RIGHT: f32 = PAGE_WIDTH - MARGIN;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+const LOGO: Name = Name(b"Im1");
+
+/// Writes a timesheet with one section per day and billable/non-billable totals.
+/// Billable rows show the rounded duration. Records without a start (tasks that were never
+/// worked on) are left out.
+pub fn write_timesheet(
+    records: &[ExportRecord],
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    settings: &TimesheetConfig,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let now = Local::now();
+    let mut days: BTreeMap<NaiveDate, Vec<&ExportRecord>> = BTreeMap::new();
+    for record in records {
+        if let Some(start) = record.start {
+            days.entry(start.date_naive()).or_default().push(record);
+        }
+    }
+    let duration = |record: &ExportRecord| match (record.billed_seconds, record.start) {
+        (Some(billed), _) => Duration::seconds(billed),
+        (None, Some(start)) => record.end.unwrap_or(now).signed_duration_since(start),
+        (None, None) => Duration::zero(),
+    };
+    let mut amounts = Amounts::default();
+    for record in records {
+        if let Some(amount) = record.amount {
+            amounts.add(record.currency, amount);
+        }
+    }
+
+    let logo = match &settings.logo {
+        Some(path) => Some(load_jpeg(path)?),
+        None => None,
+    };
+
+    let mut layout = Layout::new();
+    layout.header(settings, logo.as_ref(), from, to, &days);
+    let mut billable = Duration::zero();
+    let mut non_billable = Duration::zero();
+    for (day, entries) in &days {
+        layout.ensure_space(ROW_HEIGHT * 3.0);
+        layout.text(BOLD, COLUMNS[0], &day.format("%a %Y-%m-%d").to_string());
+        let mut day_total = Duration::zero();
+        for (index, record) in entries.iter().enumerate() {
+            if index > 0 {
+                layout.ensure_space(ROW_HEIGHT);
+            }
+            let worked = duration(record);
+            day_total += worked;
+            if record.billable {
+                billable += worked;
+            } else {
+                non_billable += worked;
+            }
+            let task = if record.billable {
+                record.task.clone()
+            } else {
+                format!("{} (not billable)", record.task)
+            };
+            let time = match (record.start, record.end) {
+                (Some(start), Some(end)) => {
+                    format!("{} - {}", start.format("%H:%M"), end.format("%H:%M"))
+                }
+                (Some(start), None) => format!("{} - running", start.format("%H:%M")),
+                _ => String::new(),
+            };
+            layout.text(REGULAR, COLUMNS[1], &truncate(&record.project, 20));
+            layout.text(REGULAR, COLUMNS[2], &truncate(&task, 32));
+            layout.text(REGULAR, COLUMNS[3], &time);
+            layout.text_right(REGULAR, DURATION_RIGHT, &format_duration(worked));
+            layout.advance(ROW_HEIGHT);
+        }
+        layout.rule(COLUMNS[3], 0.5);
+        layout.text_right(BOLD, DURATION_RIGHT, &format_duration(day_total));
+        layout.advance(ROW_HEIGHT * 1.5);
+    }
+
+    if days.is_empty() {
+        layout.text(REGULAR, MARGIN, "No time tracked in this period.");
+        layout.advance(ROW_HEIGHT * 2.0);
+    }
+    layout.ensure_space(ROW_HEIGHT * 5.0);
+    layout.rule(COLUMNS[3], 1.0);
+    for (label, total, font) in [
+        ("Billable", billable, REGULAR),
+        ("Non-billable", non_billable, REGULAR),
+        ("Total", billable + non_billable, BOLD),
+    ] {
+        layout.text(font, COLUMNS[3], label);
+        layout.text_right(font, DURATION_RIGHT, &format_duration(total));
+        layout.advance(ROW_HEIGHT);
+    }
+    for (index, (currency, amount)) in amounts.iter().enumerate() {
+        layout.ensure_space(ROW_HEIGHT);
+        if index == 0 {
+            layout.text(BOLD, COLUMNS[3], "Amount");
+        }
+        let amount = match currency {
+            Some(currency) => format!("{amount:.2} {currency}"),
+            None => format!("{amount:.2}"),
+        };
+        layout.text_right(BOLD, DURATION_RIGHT, &amount);
+        layout.advance(ROW_HEIGHT);
+    }
+
+    out.write_all(&layout.finish(logo.as_ref()))?;
+    Ok(())
+}
+
+/// Positions rows top to bottom and starts a new page when one is full.
+struct Layout {
+    pages: Vec<Content>,
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self {
+            pages: vec![Content::new()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn page(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    fn header(
+        &mut self,
+        settings: &TimesheetConfig,
+        logo: Option<&Jpeg>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        days: &BTreeMap<NaiveDate, Vec<&ExportRecord>>,
+    ) {
+        let mut text_x = MARGIN;
+        if let Some(logo) = logo {
+            let width = LOGO_HEIGHT * logo.width as f32 / logo.height as f32;
+            let y = self.y - LOGO_HEIGHT;
+            self.page()
+                .save_state()
+                .transform([width, 0.0, 0.0, LOGO_HEIGHT, MARGIN, y])
+                .x_object(LOGO)
+                .restore_state();
+            text_x += width + 16.0;
+        }
+        self.advance(18.0);
+        self.text_sized(BOLD, text_x, 18.0, "Timesheet");
+        self.advance(18.0);
+        if let Some(name) = &settings.name {
+            self.text_sized(REGULAR, text_x, 12.0, name);
+        }
+        self.advance(16.0);
+        let first = from.or_else(|| days.keys().next().copied());
+        let last = to.or_else(|| days.keys().next_back().copied());
+        let period = match (first, last) {
+            (Some(first), Some(last)) => format!("Period: {first} to {last}"),
+            _ => "Period: all time".to_string(),
+        };
+        self.text(REGULAR, text_x, &period);
+        if logo.is_some() {
+            self.y = self.y.min(PAGE_HEIGHT - MARGIN - LOGO_HEIGHT);
+        }
+        self.advance(ROW_HEIGHT * 2.0);
+        self.column_headings();
+    }
+
+    fn column_headings(&mut self) {
+        for (x, heading) in COLUMNS.iter().zip(["Date", "Project", "Task", "Time"]) {
+            self.text(BOLD, *x, heading);
+        }
+        self.text_right(BOLD, DURATION_RIGHT, "Duration");
+        self.advance(ROW_HEIGHT);
+        self.rule(MARGIN, 1.0);
+        self.advance(4.0);
+    }
+
+    /// Starts a new page unless `height` fits above the bottom margin.
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height >= MARGIN + ROW_HEIGHT {
+            return;
+        }
+        self.pages.push(Content::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.advance(ROW_HEIGHT);
+        self.column_headings();
+    }
+
+    fn advance(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn text(&mut self, font: Name<'static>, x: f32, text: &str) {
+        self.text_sized(font, x, FONT_SIZE, text);
+    }
+
+    fn text_sized(&mut self, font: Name<'static>, x: f32, size: f32, text: &str) {
+        let y = self.y;
+        self.page()
+            .begin_text()
+            .set_font(font, size)
+            .next_line(x, y)
+            .show(Str(&encode(text)))
+            .end_text();
+    }
+
+    fn text_right(&mut self, font: Name<'static>, right: f32, text: &str) {
+        self.text(font, right - text_width(text, FONT_SIZE), text);
+    }
+
+    /// Horizontal line from `x` to the right margin, just above the current row.
+    fn rule(&mut self, x: f32, width: f32) {
+        let y = self.y + ROW_HEIGHT - 4.0;
+        self.page()
+            .set_line_width(width)
+            .set_stroke_gray(0.6)
+            .move_to(x, y)
+            .line_to(DURATION_RIGHT, y)
+            .stroke();
+    }
+
+    /// Numbers the pages and assembles the document.
+    fn finish(mut self, logo: Option<&Jpeg>) -> Vec<u8> {
+        let count = self.pages.len();
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            let label = format!("Page {} of {count}", index + 1);
+            page.begin_text()
+                .set_font(REGULAR, 8.0)
+                .next_line(DURATION_RIGHT - text_width(&label, 8.0), MARGIN / 2.0)
+                .show(Str(&encode(&label)))
+                .end_text();
+        }
+
+        let catalog_id = Ref::new(1);
+        let tree_id = Ref::new(2);
+        let regular_id = Ref::new(3);
+        let bold_id = Ref::new(4);
+        let logo_id = Ref::new(5);
+        let page_ids: Vec<Ref> = (0..count).map(|i| Ref::new(6 + 2 * i as i32)).collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(tree_id);
+        pdf.pages(tree_id)
+            .kids(page_ids.iter().copied())
+            .count(count as i32);
+        pdf.type1_font(regular_id)
+            .base_font(Name(b"Helvetica"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+        pdf.type1_font(bold_id)
+            .base_font(Name(b"Helvetica-Bold"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+        if let Some(logo) = logo {
+            let mut image = pdf.image_xobject(logo_id, &logo.data);
+            image.filter(Filter::DctDecode);
+            image.width(logo.width as i32);
+            image.height(logo.height as i32);
+            image.bits_per_component(8);
+            match logo.components {
+                1 => image.color_space().device_gray(),
+                4 => image.color_space().device_cmyk(),
+                _ => image.color_space().device_rgb(),
+            }
+            image.finish();
+        }
+
+        for (page_id, content) in page_ids.iter().zip(self.pages) {
+            let content_id = Ref::new(page_id.get() + 1);
+            let mut page = pdf.page(*page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+            page.parent(tree_id);
+            page.contents(content_id);
+            let mut resources = page.resources();
+            resources
+                .fonts()
+                .pair(REGULAR, regular_id)
+                .pair(BOLD, bold_id);
+            if logo.is_some() {
+                resources.x_objects().pair(LOGO, logo_id);
+            }
+            resources.finish();
+            page.finish();
+            pdf.stream(content_id, &content.finish());
+        }
+        pdf.finish()
+    }
+}
+
+struct Jpeg {
+    data: Vec<u8>,
+    width: u16,
+    height: u16,
+    components: u8,
+}
+
+/// Reads a JPEG file and its dimensions; PDF viewers decode the data themselves.
+fn load_jpeg(path: &Path) -> Result<Jpeg> {
+    let data =
+        std::fs::read(path).with_context(|| format!("Failed to read logo {}", path.display()))?;
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        bail!("Logo {} is not a JPEG image", path.display());
+    }
+    let mut pos = 2;
+    while pos + 9 < data.len() {
+        if data[pos] != 0xFF {
+            break;
+        }
+        let marker = data[pos + 1];
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        // Start-of-frame markers, except DHT (C4), JPG (C8) and DAC (CC).
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let height = u16::from_be_bytes([data[pos + 5], data[pos + 6]]);
+            let width = u16::from_be_bytes([data[pos + 7], data[pos + 8]]);
+            let components = data[pos + 9];
+            if width == 0 || height == 0 {
+                break;
+            }
+            return Ok(Jpeg {
+                data,
+                width,
+                height,
+                components,
+            });
+        }
+        pos += 2 + length;
+    }
+    bail!("Could not read the size of logo {}", path.display())
+}
+
+/// Encodes `text` for the WinAnsi encoded standard fonts.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '\u{20}'..='\u{7E}' | '\u{A0}'..='\u{FF}' => c as u8,
+            '\u{2013}' | '\u{2014}' => b'-',
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Approximate Helvetica width, exact for durations and page numbers.
+fn text_width(text: &str, size: f32) -> f32 {
+    let units: f32 = text
+        .chars()
+        .map(|c| match c {
+            '0'..='9' => 556.0,
+            ':' | ' ' => 278.0,
+            'i' | 'l' | 'f' | 't' => 278.0,
+            'm' | 'w' | 'D' | 'M' | 'W' => 833.0,
+            _ => 556.0,
+        })
+        .sum();
+    units * size / 1000.0
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max - 3).collect();
+    short.push_str("...");
+    short
+}
@@ -0,0 +1,272 @@
+//! The timeline as an SVG image: one row per day with a block for every interval, colored by
+//! project or category, and a legend with the totals. Browsers, wikis and most slide tools show
+//! SVG files directly.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveDate, Timelike};
+use clap::ValueEnum;
+
+use super::ExportRecord;
+use crate::ui::{format_duration, local_start_of_day};
+
+const WIDTH: f64 = 1000.0;
+const MARGIN: f64 = 20.0;
+const LABEL_WIDTH: f64 = 100.0;
+const TOTAL_WIDTH: f64 = 70.0;
+const TITLE_HEIGHT: f64 = 40.0;
+const AXIS_HEIGHT: f64 = 20.0;
+const ROW_HEIGHT: f64 = 28.0;
+const BAR_HEIGHT: f64 = 20.0;
+const LEGEND_ROW_HEIGHT: f64 = 20.0;
+const FONT: &str = "font-family=\"sans-serif\" font-size=\"12\"";
+const TITLE_FONT: &str = "font-family=\"sans-serif\" font-size=\"16\" font-weight=\"bold\"";
+
+/// Projects get these in the order of their names.
+const PROJECT_COLORS: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+/// Tasks without a category, or with a color that isn't `#rrggbb`.
+const NO_CATEGORY_COLOR: &str = "#9e9e9e";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TimelineColor {
+    Project,
+    /// The colors of the categories as set in Tickr.
+    Category,
+}
+
+/// What a block is colored by, with its color.
+struct Group {
+    color: String,
+    total: Duration,
+}
+
+/// Writes the timeline of the days `from` to `to` (inclusive). Only the hours with tracked time
+/// are drawn; records without a start are left out and running intervals end now.
+pub fn write_timeline(
+    records: &[ExportRecord],
+    from: NaiveDate,
+    to: NaiveDate,
+    color_by: TimelineColor,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let now = Local::now();
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+    let range_start = local_start_of_day(from);
+    let range_end = local_start_of_day(to + Duration::days(1));
+    let intervals: Vec<(&ExportRecord, DateTime<Local>, DateTime<Local>)> = records
+        .iter()
+        .filter_map(|record| {
+            let start = record.start?.max(range_start);
+            let end = record.end.unwrap_or(now).min(range_end);
+            (end > start).then_some((record, start, end))
+        })
+        .collect();
+
+    let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+    for (record, start, end) in &intervals {
+        let name = group_name(record, color_by);
+        let color = match color_by {
+            TimelineColor::Project => String::new(),
+            TimelineColor::Category => record
+                .category_color
+                .as_deref()
+                .filter(|color| is_hex_color(color))
+                .unwrap_or(NO_CATEGORY_COLOR)
+                .to_string(),
+        };
+        groups
+            .entry(name)
+            .or_insert(Group {
+                color,
+                total: Duration::zero(),
+            })
+            .total += *end - *start;
+    }
+    if color_by == TimelineColor::Project {
+        for (index, group) in groups.values_mut().enumerate() {
+            group.color = PROJECT_COLORS[index % PROJECT_COLORS.len()].to_string();
+        }
+    }
+
+    // Whole hours from the earliest start to the latest end on any day, a working day when
+    // nothing was tracked.
+    let (first_hour, last_hour) = hours_with_time(&intervals).unwrap_or((8, 18));
+    let hours = f64::from(last_hour - first_hour);
+    let chart_left = MARGIN + LABEL_WIDTH;
+    let chart_width = WIDTH - chart_left - TOTAL_WIDTH - MARGIN;
+    let x_of = |day_start: DateTime<Local>, time: DateTime<Local>| {
+        let hour = (time - day_start).num_seconds() as f64 / 3600.0;
+        chart_left + (hour - f64::from(first_hour)).clamp(0.0, hours) / hours * chart_width
+    };
+    let rows_top = MARGIN + TITLE_HEIGHT + AXIS_HEIGHT;
+    let legend_top = rows_top + days.len() as f64 * ROW_HEIGHT + LEGEND_ROW_HEIGHT;
+    let height = legend_top + groups.len().max(1) as f64 * LEGEND_ROW_HEIGHT + MARGIN;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{height}\" \
+         viewBox=\"0 0 {WIDTH} {height}\">"
+    )?;
+    writeln!(
+        svg,
+        "<rect width=\"{WIDTH}\" height=\"{height}\" fill=\"#ffffff\"/>"
+    )?;
+    let title = if from == to {
+        format!("Tickr timeline {}", from.format("%a %Y-%m-%d"))
+    } else {
+        format!(
+            "Tickr timeline {} to {}",
+            from.format("%Y-%m-%d"),
+            to.format("%Y-%m-%d")
+        )
+    };
+    writeln!(
+        svg,
+        "<text x=\"{MARGIN}\" y=\"{}\" {TITLE_FONT}>{}</text>",
+        MARGIN + 16.0,
+        escape(&title)
+    )?;
+
+    // Hour lines with their labels above the first row, every other hour on long days.
+    let label_every = if last_hour - first_hour > 12 { 2 } else { 1 };
+    let grid_bottom = rows_top + days.len() as f64 * ROW_HEIGHT;
+    for hour in first_hour..=last_hour {
+        let x = chart_left + f64::from(hour - first_hour) / hours * chart_width;
+        writeln!(
+            svg,
+            "<line x1=\"{x:.1}\" y1=\"{}\" x2=\"{x:.1}\" y2=\"{grid_bottom}\" stroke=\"#dddddd\"/>",
+            rows_top - 4.0
+        )?;
+        if (hour - first_hour) % label_every == 0 {
+            writeln!(
+                svg,
+                "<text x=\"{x:.1}\" y=\"{}\" {FONT} fill=\"#666666\" text-anchor=\"middle\">{hour:02}:00</text>",
+                rows_top - 8.0
+            )?;
+        }
+    }
+
+    for (row, day) in days.iter().enumerate() {
+        let top = rows_top + row as f64 * ROW_HEIGHT;
+        let text_y = top + ROW_HEIGHT / 2.0 + 4.0;
+        let day_start = local_start_of_day(*day);
+        let day_end = local_start_of_day(*day + Duration::days(1));
+        writeln!(
+            svg,
+            "<text x=\"{MARGIN}\" y=\"{text_y}\" {FONT}>{}</text>",
+            day.format("%a %Y-%m-%d")
+        )?;
+        let mut day_total = Duration::zero();
+        for (record, start, end) in &intervals {
+            let (start, end) = ((*start).max(day_start), (*end).min(day_end));
+            if end <= start {
+                continue;
+            }
+            day_total += end - start;
+            let x = x_of(day_start, start);
+            let width = (x_of(day_start, end) - x).max(1.0);
+            let color = &groups[&group_name(record, color_by)].color;
+            writeln!(
+                svg,
+                "<rect x=\"{x:.1}\" y=\"{:.1}\" width=\"{width:.1}\" height=\"{BAR_HEIGHT}\" \
+                 fill=\"{color}\"><title>{}</title></rect>",
+                top + (ROW_HEIGHT - BAR_HEIGHT) / 2.0,
+                escape(&format!(
+                    "{} > {} {} - {}",
+                    record.project,
+                    record.task,
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                ))
+            )?;
+        }
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{text_y}\" {FONT} text-anchor=\"end\">{}</text>",
+            WIDTH - MARGIN,
+            format_duration(day_total)
+        )?;
+    }
+
+    if groups.is_empty() {
+        writeln!(
+            svg,
+            "<text x=\"{MARGIN}\" y=\"{}\" {FONT} fill=\"#666666\">Nothing tracked</text>",
+            legend_top + 14.0
+        )?;
+    }
+    // Most tracked first.
+    let mut legend: Vec<(&String, &Group)> = groups.iter().collect();
+    legend.sort_by_key(|(_, group)| -group.total);
+    for (row, (name, group)) in legend.into_iter().enumerate() {
+        let top = legend_top + row as f64 * LEGEND_ROW_HEIGHT;
+        writeln!(
+            svg,
+            "<rect x=\"{MARGIN}\" y=\"{}\" width=\"14\" height=\"14\" fill=\"{}\"/>",
+            top + 2.0,
+            group.color
+        )?;
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" {FONT}>{}  {}</text>",
+            MARGIN + 22.0,
+            top + 14.0,
+            escape(name),
+            format_duration(group.total)
+        )?;
+    }
+    writeln!(svg, "</svg>")?;
+    out.write_all(svg.as_bytes())?;
+    Ok(())
+}
+
+fn group_name(record: &ExportRecord, color_by: TimelineColor) -> String {
+    match color_by {
+        TimelineColor::Project => record.project.clone(),
+        TimelineColor::Category => record
+            .category
+            .clone()
+            .unwrap_or_else(|| "No category".to_string()),
+    }
+}
+
+/// First and last hour of the day with tracked time, the last one rounded up.
+fn hours_with_time(
+    intervals: &[(&ExportRecord, DateTime<Local>, DateTime<Local>)],
+) -> Option<(u32, u32)> {
+    let mut hours: Option<(u32, u32)> = None;
+    for (_, start, end) in intervals {
+        let next_midnight = local_start_of_day(start.date_naive() + Duration::days(1));
+        let (first, last) = if *end > next_midnight {
+            // Part of it is on the next day, from midnight on.
+            (0, 24)
+        } else if *end == next_midnight {
+            (start.hour(), 24)
+        } else {
+            let rounded_up = u32::from(end.minute() > 0 || end.second() > 0);
+            (start.hour(), end.hour() + rounded_up)
+        };
+        hours = Some(hours.map_or((first, last), |(earliest, latest)| {
+            (earliest.min(first), latest.max(last))
+        }));
+    }
+    hours.map(|(first, last)| (first, last.max(first + 1)))
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
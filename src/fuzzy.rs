@@ -0,0 +1,79 @@
+//! Close spellings of names, for suggesting a project or task when a typed name doesn't match,
+//! and the scoring of the `tickr switch` filter. Comparisons ignore case.
+
+/// Edit distance between `a` and `b`: the insertions, deletions, substitutions and swaps of
+/// neighbouring characters that turn one into the other.
+pub fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Rows for the prefixes of `a` one and two characters shorter than the current one.
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 0..a.len() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for j in 0..b.len() {
+            let substitution = previous[j] + usize::from(a[i] != b[j]);
+            let mut edits = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                edits = edits.min(before_previous[j - 1] + 1);
+            }
+            current[j + 1] = edits;
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+/// Indices of the `candidates` that look like a misspelling of `query`, closest first. A
+/// candidate qualifies when it contains the query or is at most a third of its length of edits
+/// away.
+pub fn suggestions(query: &str, candidates: &[&str]) -> Vec<usize> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut scored: Vec<(usize, usize, usize)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(index, candidate)| {
+            let candidate = candidate.to_lowercase();
+            let edits = distance(&query, &candidate);
+            let allowed = (query.chars().count().max(candidate.chars().count()) / 3).max(1);
+            let score = if candidate.contains(&query) {
+                edits.min(1)
+            } else if edits <= allowed {
+                edits
+            } else {
+                return None;
+            };
+            Some((score, candidate.chars().count(), index))
+        })
+        .collect();
+    scored.sort();
+    scored.into_iter().map(|(_, _, index)| index).collect()
+}
+
+/// How well `query` matches `candidate` while filtering a list as you type: every character of
+/// the query, spaces aside, has to appear in the candidate in order. Higher is better; runs of
+/// consecutive characters and matches at the start of a word count more, gaps less. `None` when
+/// the candidate doesn't match.
+pub fn filter_score(query: &str, candidate: &str) -> Option<i64> {
+    let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (next..chars.len()).find(|index| chars[*index] == wanted)?;
+        score += 1;
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        if let Some(previous) = previous {
+            let gap = found - previous - 1;
+            score += if gap == 0 { 5 } else { -(gap.min(5) as i64) };
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
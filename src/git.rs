@@ -0,0 +1,53 @@
+//! Branch and repository of the git checkout Tickr runs in, for naming a task after the ticket
+//! branch being worked on.
+
+/// The checked-out branch and the name of its repository.
+pub struct GitContext {
+    /// Name of the `origin` remote's repository, the checkout's directory without a remote.
+    pub repo: Option<String>,
+    pub branch: String,
+}
+
+impl GitContext {
+    /// Task name for the branch: the part after the last `/`, so `feature/ABC-12-login` becomes
+    /// `ABC-12-login`.
+    pub fn task_name(&self) -> &str {
+        self.branch.rsplit('/').next().unwrap_or(&self.branch)
+    }
+}
+
+/// Branch and repository of the current directory, `None` outside a repository, without git or
+/// on a detached HEAD.
+pub fn current() -> Option<GitContext> {
+    // Fails on a detached HEAD, and unlike `rev-parse` works before the first commit.
+    let branch = git(&["symbolic-ref", "--short", "HEAD"])?;
+    let repo = git(&["remote", "get-url", "origin"])
+        .or_else(|| git(&["rev-parse", "--show-toplevel"]))
+        .and_then(|path| repo_name(&path));
+    Some(GitContext { repo, branch })
+}
+
+/// Last path segment of a remote URL or directory, without `.git`.
+fn repo_name(path: &str) -> Option<String> {
+    let name = path
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()?
+        .trim_end_matches(".git");
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Trimmed output of a git command, `None` when it fails or prints nothing.
+fn git(args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
@@ -0,0 +1,160 @@
+//! Hours a project should get per week or in total, and how far it got. Only time tracked on the
+//! project's own tasks counts, like in `tickr report --by project`.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+use rusqlite::Connection;
+
+use crate::config::Config;
+use crate::db;
+use crate::types::{GoalPeriod, ProjectGoal, ProjectId};
+
+/// How the time tracked compares with the goal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoalState {
+    /// Less than an even spread over the week would have by now.
+    Behind,
+    OnTrack,
+    Reached,
+    /// More than the goal.
+    Over,
+}
+
+impl GoalState {
+    pub fn label(self) -> &'static str {
+        match self {
+            GoalState::Behind => "behind",
+            GoalState::OnTrack => "on track",
+            GoalState::Reached => "reached",
+            GoalState::Over => "over",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GoalProgress {
+    pub goal: ProjectGoal,
+    /// Time tracked this week for weekly goals, all time for total goals.
+    pub seconds: i64,
+    pub state: GoalState,
+}
+
+impl GoalProgress {
+    pub fn target_seconds(&self) -> i64 {
+        i64::from(self.goal.minutes) * 60
+    }
+
+    /// Share of the goal tracked, 1.0 once reached and more when over.
+    pub fn fraction(&self) -> f64 {
+        if self.goal.minutes == 0 {
+            return 1.0;
+        }
+        self.seconds as f64 / self.target_seconds() as f64
+    }
+
+    /// Tracked and target hours, e.g. `12.5/20h`.
+    pub fn hours(&self) -> String {
+        format!(
+            "{}/{}h",
+            format_hours(self.seconds),
+            format_hours(self.target_seconds())
+        )
+    }
+
+    /// Adds time tracked since the progress was computed, e.g. by the running task, up to `now`.
+    pub fn advance(&mut self, seconds: i64, config: &Config, now: DateTime<Local>) {
+        self.seconds += seconds;
+        self.state = state(&self.goal, self.seconds, week_passed(config, now));
+    }
+
+    /// `width` characters of `#` for the tracked share and `-` for the rest.
+    pub fn bar(&self, width: usize) -> String {
+        let filled = ((self.fraction().min(1.0) * width as f64).round() as usize).min(width);
+        format!("{}{}", "#".repeat(filled), "-".repeat(width - filled))
+    }
+}
+
+/// Progress of every project with a goal at `now`.
+pub fn progress(
+    config: &Config,
+    now: DateTime<Local>,
+    conn: &Connection,
+) -> Result<HashMap<ProjectId, GoalProgress>> {
+    let goals = db::query_project_goals(conn)?;
+    if goals.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let week_start = week_start(config, now);
+    let week = db::query_project_seconds(Some(week_start), now, conn)?;
+    let total = db::query_project_seconds(None, now, conn)?;
+    let week_passed = week_passed(config, now);
+
+    Ok(goals
+        .into_iter()
+        .map(|(project_id, goal)| {
+            let tracked = match goal.period {
+                GoalPeriod::Week => &week,
+                GoalPeriod::Total => &total,
+            };
+            let seconds = tracked.get(&project_id).copied().unwrap_or(0);
+            let state = state(&goal, seconds, week_passed);
+            (
+                project_id,
+                GoalProgress {
+                    goal,
+                    seconds,
+                    state,
+                },
+            )
+        })
+        .collect())
+}
+
+fn week_start(config: &Config, now: DateTime<Local>) -> DateTime<Local> {
+    crate::ui::local_start_of_day(config.calendar_week_start_for(now.date_naive()))
+}
+
+/// Share of the week that has passed, for the pace of weekly goals.
+fn week_passed(config: &Config, now: DateTime<Local>) -> f64 {
+    (now - week_start(config, now)).num_seconds() as f64 / Duration::days(7).num_seconds() as f64
+}
+
+fn state(goal: &ProjectGoal, seconds: i64, week_passed: f64) -> GoalState {
+    let target = i64::from(goal.minutes) * 60;
+    if seconds > target {
+        GoalState::Over
+    } else if seconds == target {
+        GoalState::Reached
+    } else if goal.period == GoalPeriod::Week && (seconds as f64) < target as f64 * week_passed {
+        GoalState::Behind
+    } else {
+        GoalState::OnTrack
+    }
+}
+
+/// Reads a goal in hours: `20`, `7.5`, `1h30m` or `90m`.
+pub fn parse_hours(input: &str) -> Option<u32> {
+    let input = input.trim();
+    match input.parse::<f64>() {
+        Ok(hours) if hours.is_finite() && hours >= 0.0 => Some((hours * 60.0).round() as u32),
+        Ok(_) => None,
+        Err(_) => crate::app::parse_estimate(input),
+    }
+}
+
+/// Hours with one decimal, leaving out `.0`.
+pub fn format_hours(seconds: i64) -> String {
+    let hours = format!("{:.1}", seconds as f64 / 3600.0);
+    hours.trim_end_matches(".0").to_string()
+}
+
+/// The goal as it is set, e.g. `20h a week` or `100h total`.
+pub fn describe(goal: &ProjectGoal) -> String {
+    let hours = format_hours(i64::from(goal.minutes) * 60);
+    match goal.period {
+        GoalPeriod::Week => format!("{hours}h a week"),
+        GoalPeriod::Total => format!("{hours}h total"),
+    }
+}
@@ -0,0 +1,145 @@
+/// Commands from the `[hooks]` config that run when a task starts, stops or replaces another,
+/// e.g. to set a chat status or switch a light. A hook gets the task in `TICKR_*` environment
+/// variables and as JSON on stdin. It runs in the background with its output discarded, so a
+/// slow or failing hook never holds up tracking.
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use chrono::Local;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::config::HooksConfig;
+use crate::db;
+use crate::types::TickrId;
+
+static HOOKS: OnceLock<HooksConfig> = OnceLock::new();
+
+/// Sets the hooks for the rest of the process, once at startup.
+pub fn init(hooks: HooksConfig) {
+    HOOKS.get_or_init(|| hooks);
+}
+
+/// Whether any hook is configured, so callers can skip looking up tasks otherwise.
+pub fn enabled() -> bool {
+    HOOKS.get().is_some_and(|hooks| !hooks.is_empty())
+}
+
+/// A task as hooks see it.
+#[derive(Debug, Serialize)]
+pub struct HookTask {
+    pub id: TickrId,
+    pub task: String,
+    pub project: String,
+    /// Length of the running interval, `None` when the task is not running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<i64>,
+}
+
+impl HookTask {
+    /// `None` when the task can't be read; hooks are not worth failing a start or stop for.
+    pub fn load(id: TickrId, conn: &Connection) -> Option<Self> {
+        let tickr = db::query_tickr_by_id(id, conn).ok()??;
+        let project = db::query_project_by_id(tickr.project_id, conn)
+            .ok()
+            .flatten()
+            .map(|project| project.name)
+            .unwrap_or_default();
+        let duration_seconds = tickr
+            .intervals
+            .iter()
+            .find(|interval| interval.end_time.is_none())
+            .map(|interval| (Local::now() - interval.start_time).num_seconds().max(0));
+        Some(Self {
+            id,
+            task: tickr.description,
+            project,
+            duration_seconds,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: &'static str,
+    task: &'a HookTask,
+    /// The task that was stopped by a switch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous: Option<&'a HookTask>,
+}
+
+/// Runs the hooks for `task` having started, after `previous` when it replaced that task.
+pub fn started(task: Option<HookTask>, previous: Option<HookTask>) {
+    let (Some(hooks), Some(task)) = (HOOKS.get(), task) else {
+        return;
+    };
+    match (&previous, &hooks.on_switch) {
+        (Some(previous), Some(command)) => run(command, "switch", &task, Some(previous)),
+        _ => {
+            if let Some(previous) = &previous {
+                stopped(Some(previous));
+            }
+            if let Some(command) = &hooks.on_start {
+                run(command, "start", &task, None);
+            }
+        }
+    }
+}
+
+/// Runs the stop hook for `task`, loaded while it was still running.
+pub fn stopped(task: Option<&HookTask>) {
+    if let (Some(hooks), Some(task)) = (HOOKS.get(), task)
+        && let Some(command) = &hooks.on_stop
+    {
+        run(command, "stop", task, None);
+    }
+}
+
+fn run(command: &str, event: &'static str, task: &HookTask, previous: Option<&HookTask>) {
+    let payload = Payload {
+        event,
+        task,
+        previous,
+    };
+    let Ok(json) = serde_json::to_string(&payload) else {
+        return;
+    };
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .env("TICKR_EVENT", event)
+        .env("TICKR_TASK_ID", task.id.to_string())
+        .env("TICKR_TASK", &task.task)
+        .env("TICKR_PROJECT", &task.project)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(seconds) = task.duration_seconds {
+        shell.env("TICKR_DURATION_SECONDS", seconds.to_string());
+    }
+    if let Some(previous) = previous {
+        shell
+            .env("TICKR_PREVIOUS_TASK", &previous.task)
+            .env("TICKR_PREVIOUS_PROJECT", &previous.project);
+        if let Some(seconds) = previous.duration_seconds {
+            shell.env("TICKR_PREVIOUS_DURATION_SECONDS", seconds.to_string());
+        }
+    }
+    let Ok(mut child) = shell.spawn() else {
+        return;
+    };
+    // The payload fits the pipe buffer, so this returns before the hook reads it.
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(json.as_bytes()).ok();
+    }
+    std::thread::spawn(move || child.wait());
+}
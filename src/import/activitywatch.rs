@@ -0,0 +1,142 @@
+/// ActivityWatch export parsing.
+///
+/// Window events (`currentwindow` buckets) are merged per application into
+/// contiguous blocks, AFK periods are dropped, and blocks that are too short
+/// to be meaningful are discarded.
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate};
+use serde::Deserialize;
+
+use super::SuggestedInterval;
+
+#[derive(Deserialize)]
+struct Export {
+    buckets: HashMap<String, Bucket>,
+}
+
+#[derive(Deserialize)]
+struct Bucket {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+#[derive(Deserialize)]
+struct Event {
+    timestamp: DateTime<FixedOffset>,
+    duration: f64,
+    #[serde(default)]
+    data: HashMap<String, serde_json::Value>,
+}
+
+impl Event {
+    fn start(&self) -> DateTime<Local> {
+        self.timestamp.with_timezone(&Local)
+    }
+
+    fn end(&self) -> DateTime<Local> {
+        self.start() + Duration::milliseconds((self.duration * 1000.0) as i64)
+    }
+
+    fn field(&self, key: &str) -> Option<&str> {
+        self.data.get(key).and_then(|value| value.as_str())
+    }
+}
+
+/// Options controlling how raw events are condensed into suggestions.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Only keep events starting on this local date.
+    pub date: Option<NaiveDate>,
+    /// Events of the same app closer than this are merged into one block.
+    pub merge_gap: Duration,
+    /// Blocks shorter than this are dropped.
+    pub min_duration: Duration,
+}
+
+struct Block {
+    app: String,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    titles: HashMap<String, f64>,
+}
+
+/// Reads an ActivityWatch JSON export and returns suggested intervals in chronological order.
+pub fn load_suggestions(path: &str, options: &Options) -> Result<Vec<SuggestedInterval>> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+    let export: Export = serde_json::from_str(&raw).context("Not a valid ActivityWatch export")?;
+
+    let mut windows = Vec::new();
+    let mut afk = Vec::new();
+    for bucket in export.buckets.into_values() {
+        match bucket.kind.as_str() {
+            "currentwindow" => windows.extend(bucket.events),
+            "afkstatus" => afk.extend(
+                bucket
+                    .events
+                    .into_iter()
+                    .filter(|event| event.field("status") == Some("afk"))
+                    .map(|event| (event.start(), event.end())),
+            ),
+            _ => {}
+        }
+    }
+    if windows.is_empty() {
+        anyhow::bail!("Export contains no window events (currentwindow buckets)");
+    }
+
+    windows.retain(|event| {
+        let start = event.start();
+        let in_range = options.date.is_none_or(|date| start.date_naive() == date);
+        let midpoint = start + (event.end() - start) / 2;
+        let away = afk
+            .iter()
+            .any(|(afk_start, afk_end)| midpoint >= *afk_start && midpoint < *afk_end);
+        in_range && event.duration > 0.0 && !away
+    });
+    windows.sort_by_key(|event| event.timestamp);
+
+    let mut blocks: Vec<Block> = Vec::new();
+    for event in &windows {
+        let app = event.field("app").unwrap_or("Unknown").to_string();
+        let title = event.field("title").unwrap_or_default().to_string();
+        let (start, end) = (event.start(), event.end());
+        let extend = blocks
+            .last()
+            .is_some_and(|block| block.app == app && start - block.end <= options.merge_gap);
+        if extend {
+            let block = blocks.last_mut().expect("checked above");
+            block.end = block.end.max(end);
+            *block.titles.entry(title).or_default() += event.duration;
+        } else {
+            blocks.push(Block {
+                app,
+                start,
+                end,
+                titles: HashMap::from([(title, event.duration)]),
+            });
+        }
+    }
+
+    Ok(blocks
+        .into_iter()
+        .filter(|block| block.end - block.start >= options.min_duration)
+        .map(|block| {
+            let detail = block
+                .titles
+                .into_iter()
+                .filter(|(title, _)| !title.is_empty())
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(title, _)| title);
+            SuggestedInterval {
+                task: block.app,
+                detail,
+                start: block.start,
+                end: block.end,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,284 @@
+/// Importers that turn external data into Tickr projects, tasks and intervals.
+pub mod activitywatch;
+pub mod native;
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use rusqlite::Connection;
+
+use crate::export::ExportRecord;
+use crate::progress::Progress;
+use crate::{db, types};
+
+/// Share of the shorter interval that must be covered for two intervals to count as duplicates.
+const DUPLICATE_OVERLAP_RATIO: f64 = 0.8;
+
+/// File formats `tickr import` understands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ImportSource {
+    /// CSV written by `tickr export --format csv`.
+    Csv,
+    /// JSON written by `tickr export --format json`.
+    Json,
+    /// ActivityWatch JSON export (window and AFK buckets).
+    Activitywatch,
+}
+
+/// Guesses the source format from the file extension and, for JSON, its top-level keys.
+pub fn detect_source(path: &str) -> Result<ImportSource> {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("csv") => Ok(ImportSource::Csv),
+        Some("json") => {
+            let raw = std::fs::read_to_string(path)?;
+            let value: serde_json::Value = serde_json::from_str(&raw)?;
+            if value.get("buckets").is_some() {
+                Ok(ImportSource::Activitywatch)
+            } else {
+                Ok(ImportSource::Json)
+            }
+        }
+        _ => anyhow::bail!("Cannot tell the format of '{path}', pass --from"),
+    }
+}
+
+/// A proposed interval that still has to be confirmed before it is stored.
+#[derive(Clone, Debug)]
+pub struct SuggestedInterval {
+    pub task: String,
+    pub detail: Option<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl SuggestedInterval {
+    pub fn into_record(self, project: &str) -> ExportRecord {
+        ExportRecord {
+            project: project.to_string(),
+            task: self.task,
+            category: None,
+            category_color: None,
+            billable: true,
+            start: Some(self.start),
+            end: Some(self.end),
+            duration_seconds: Some((self.end - self.start).num_seconds()),
+            billed_seconds: None,
+            hourly_rate: None,
+            amount: None,
+            currency: None,
+            task_notes: None,
+            notes: None,
+            client: None,
+        }
+    }
+}
+
+/// What to do with an imported interval that duplicates an existing one of the same task.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum DuplicatePolicy {
+    /// Leave the duplicate out.
+    #[default]
+    Skip,
+    /// Import it anyway but list it in the summary.
+    Flag,
+}
+
+/// Why an imported interval was skipped or flagged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// Same start and end as an existing interval of the task.
+    Exact,
+    /// Mostly covered by an existing interval of the task.
+    Overlap,
+    /// An open interval while another task is already running; always skipped.
+    Running,
+    /// Ends before it starts; always skipped.
+    EndsBeforeStart,
+}
+
+/// Outcome of an import run.
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub created_projects: usize,
+    pub created_categories: usize,
+    pub created_tasks: usize,
+    pub skipped: Vec<(ExportRecord, Conflict)>,
+    pub flagged: Vec<(ExportRecord, Conflict)>,
+}
+
+/// Checks whether `[start, end)` matches or substantially overlaps one of `existing`.
+pub fn find_duplicate(
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    existing: &[types::Interval],
+) -> Option<Conflict> {
+    let now = Local::now();
+    let mut overlap_found = false;
+    for interval in existing {
+        let other_end = interval.end_time.unwrap_or(now);
+        if interval.start_time == start && interval.end_time.unwrap_or(now) == end {
+            return Some(Conflict::Exact);
+        }
+        let overlap = end.min(other_end) - start.max(interval.start_time);
+        let shorter = (end - start).min(other_end - interval.start_time);
+        if shorter.num_seconds() > 0
+            && overlap.num_seconds() as f64
+                >= shorter.num_seconds() as f64 * DUPLICATE_OVERLAP_RATIO
+        {
+            overlap_found = true;
+        }
+    }
+    overlap_found.then_some(Conflict::Overlap)
+}
+
+/// Stores `records`, creating missing projects, categories and tasks on the way.
+/// Everything is written in a single transaction, so an import cancelled halfway leaves the
+/// database as it was; intervals duplicating existing ones of the same task are handled
+/// according to `policy`.
+pub fn commit_records(
+    records: &[ExportRecord],
+    policy: DuplicatePolicy,
+    progress: &mut Progress,
+    conn: &Connection,
+) -> Result<ImportSummary> {
+    let tx = conn.unchecked_transaction()?;
+    let mut summary = ImportSummary::default();
+    let mut projects: HashMap<String, types::ProjectId> = db::query_projects(&tx)?
+        .into_iter()
+        .filter_map(|project| project.id.map(|id| (project.name, id)))
+        .collect();
+    let mut categories: HashMap<String, types::CategoryId> = db::query_categories(&tx)?
+        .into_iter()
+        .map(|category| (category.name, category.id))
+        .collect();
+    let mut tickrs = db::query_tickr(types::TickrQuery::All, &tx)?;
+    let mut running = db::query_running_tickr(&tx)?.is_some();
+
+    for (done, record) in records.iter().enumerate() {
+        progress.set(done);
+        let project_id = match projects.get(&record.project) {
+            Some(id) => *id,
+            None => {
+                let id = db::create_project(
+                    types::Project {
+                        id: None,
+                        name: record.project.clone(),
+                        created_at: Local::now(),
+                        archived: false,
+                        parent_id: None,
+                        client_id: None,
+                    },
+                    &tx,
+                )?;
+                if let Some(client) = record.client.as_deref().map(str::trim)
+                    && !client.is_empty()
+                {
+                    let client_id = match db::query_client_by_name(client, &tx)? {
+                        Some(client) => client.id,
+                        None => db::create_client(client, &tx)?,
+                    };
+                    db::set_project_client(id, Some(client_id), &tx)?;
+                }
+                projects.insert(record.project.clone(), id);
+                summary.created_projects += 1;
+                id
+            }
+        };
+
+        let category_id = match record.category.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => match categories.get(name) {
+                Some(id) => Some(*id),
+                None => {
+                    let color = record
+                        .category_color
+                        .clone()
+                        .filter(|color| crate::color::is_valid_hex(color))
+                        .unwrap_or_else(crate::color::random_color);
+                    let id = db::create_category(name.to_string(), color, &tx)?;
+                    categories.insert(name.to_string(), id);
+                    summary.created_categories += 1;
+                    Some(id)
+                }
+            },
+            _ => None,
+        };
+
+        let index = match tickrs
+            .iter()
+            .position(|tickr| tickr.project_id == project_id && tickr.description == record.task)
+        {
+            Some(index) => index,
+            None => {
+                let tickr = types::Tickr {
+                    id: None,
+                    project_id,
+                    description: record.task.clone(),
+                    category_id,
+                    billable: record.billable,
+                    done: false,
+                    blocked_by: None,
+                    notes: record.task_notes.clone(),
+                    estimate_minutes: None,
+                    archived: false,
+                    issue: None,
+                    intervals: Vec::new(),
+                };
+                let id = db::create_tickr(tickr.clone(), &tx)?;
+                tickrs.push(types::Tickr {
+                    id: Some(id),
+                    ..tickr
+                });
+                summary.created_tasks += 1;
+                tickrs.len() - 1
+            }
+        };
+
+        let Some(start) = record.start else {
+            continue;
+        };
+        if record.end.is_none() && running {
+            summary.skipped.push((record.clone(), Conflict::Running));
+            continue;
+        }
+        if db::check_interval(start, record.end).is_err_and(|err| !err.is_warning()) {
+            summary
+                .skipped
+                .push((record.clone(), Conflict::EndsBeforeStart));
+            continue;
+        }
+        let end = record.end.unwrap_or_else(Local::now);
+        if let Some(conflict) = find_duplicate(start, end, &tickrs[index].intervals) {
+            match policy {
+                DuplicatePolicy::Skip => {
+                    summary.skipped.push((record.clone(), conflict));
+                    continue;
+                }
+                DuplicatePolicy::Flag => summary.flagged.push((record.clone(), conflict)),
+            }
+        }
+
+        let tickr = &mut tickrs[index];
+        let interval = db::create_interval(
+            types::Interval {
+                id: None,
+                entry_id: tickr.id.expect("stored tasks have an id"),
+                start_time: start,
+                end_time: record.end,
+                notes: record.notes.clone(),
+            },
+            &tx,
+        )?;
+        running |= interval.end_time.is_none();
+        tickr.intervals.push(interval);
+        summary.imported += 1;
+    }
+    tx.commit()?;
+    Ok(summary)
+}
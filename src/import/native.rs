@@ -0,0 +1,28 @@
+/// Reading files written by `tickr export`.
+use anyhow::{Context, Result};
+
+use crate::export::{ExportRecord, JSON_EXPORT_VERSION, JsonExport};
+
+pub fn load_csv(path: &str) -> Result<Vec<ExportRecord>> {
+    let mut reader =
+        csv::Reader::from_path(path).with_context(|| format!("Failed to read {path}"))?;
+    let mut records = Vec::new();
+    for (index, row) in reader.deserialize().enumerate() {
+        // Row 1 is the header, so data starts on line 2.
+        records.push(row.with_context(|| format!("Invalid record on line {}", index + 2))?);
+    }
+    Ok(records)
+}
+
+pub fn load_json(path: &str) -> Result<Vec<ExportRecord>> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+    let document: JsonExport =
+        serde_json::from_str(&raw).context("Not a valid Tickr JSON export")?;
+    if document.version > JSON_EXPORT_VERSION {
+        anyhow::bail!(
+            "Export version {} is newer than supported version {JSON_EXPORT_VERSION}",
+            document.version
+        );
+    }
+    Ok(document.records)
+}
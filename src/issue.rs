@@ -0,0 +1,133 @@
+//! Issues linked to tasks: a full URL or an `owner/repo#123` reference, which `issue_url` in the
+//! config turns into a URL (GitHub by default). Titles come from the GitHub and GitLab APIs.
+
+use anyhow::{Result, anyhow, bail};
+use reqwest::header;
+
+/// Where references point when the config doesn't set `issue_url`.
+pub const DEFAULT_ISSUE_URL: &str = "https://github.com/{repo}/issues/{number}";
+
+/// Keep the TUI responsive when the issue tracker is slow or unreachable.
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// `input` trimmed when it is an http(s) URL or an `owner/repo#123` reference.
+pub fn parse(input: &str) -> Result<String> {
+    let input = input.trim();
+    if input.starts_with("https://") || input.starts_with("http://") {
+        return Ok(input.to_string());
+    }
+    if reference(input).is_some() {
+        return Ok(input.to_string());
+    }
+    bail!("'{input}' is neither a URL nor a reference like owner/repo#123")
+}
+
+/// Repository and number of an `owner/repo#123` reference. GitLab groups may nest, so the
+/// repository can have more than one `/`.
+fn reference(input: &str) -> Option<(&str, u64)> {
+    let (repo, number) = input.rsplit_once('#')?;
+    let number = number.parse().ok()?;
+    let valid = repo.contains('/')
+        && repo.split('/').all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    valid.then_some((repo, number))
+}
+
+/// Web address of `issue`, filling references into `template`.
+pub fn url(issue: &str, template: &str) -> String {
+    match reference(issue) {
+        Some((repo, number)) => template
+            .replace("{repo}", repo)
+            .replace("{number}", &number.to_string()),
+        None => issue.to_string(),
+    }
+}
+
+/// Opens `url` in the default browser without waiting for it.
+pub fn open(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        // Unlike `cmd /C start`, hands the URL over without a shell parsing `&` and `^` in it.
+        let mut command = std::process::Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    // The browser's output would end up over the TUI.
+    command
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|err| anyhow!("Could not open {url}: {err}"))?;
+    Ok(())
+}
+
+/// API address of the issue at `url`, with its host and the environment variable holding a
+/// token for it. Knows GitHub (`/owner/repo/issues/1` or `/pull/1`) and GitLab
+/// (`/group/project/-/issues/1`).
+fn api_url(url: &str) -> Option<(String, &str, &'static str)> {
+    let rest = url.split_once("://")?.1;
+    let (host, path) = rest.split_once('/')?;
+    let path = path.trim_end_matches('/');
+    if let Some((project, number)) = path.split_once("/-/issues/") {
+        let project = project.replace('/', "%2F");
+        let api = format!("https://{host}/api/v4/projects/{project}/issues/{number}");
+        return Some((api, host, "GITLAB_TOKEN"));
+    }
+    let (repo, number) = path
+        .split_once("/issues/")
+        .or_else(|| path.split_once("/pull/"))?;
+    let api = if host == "github.com" {
+        format!("https://api.github.com/repos/{repo}/issues/{number}")
+    } else {
+        // GitHub Enterprise serves the API below its own host.
+        format!("https://{host}/api/v3/repos/{repo}/issues/{number}")
+    };
+    Some((api, host, "GITHUB_TOKEN"))
+}
+
+/// Title of `issue` as its tracker shows it. Private repositories need `GITHUB_TOKEN` or
+/// `GITLAB_TOKEN` in the environment, which are sent to github.com, gitlab.com and
+/// `token_hosts` only, so a link to another server can't collect them.
+pub fn fetch_title(issue: &str, template: &str, token_hosts: &[String]) -> Result<String> {
+    let url = url(issue, template);
+    let (api, host, token_var) =
+        api_url(&url).ok_or_else(|| anyhow!("Can't look up titles of issues at {url}"))?;
+    let trusted = match token_var {
+        "GITLAB_TOKEN" => host == "gitlab.com",
+        _ => host == "github.com",
+    } || token_hosts
+        .iter()
+        .any(|trusted| trusted.eq_ignore_ascii_case(host));
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("tickr/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let mut request = client.get(&api).header(header::ACCEPT, "application/json");
+    if trusted
+        && let Ok(token) = std::env::var(token_var)
+        && !token.trim().is_empty()
+    {
+        request = request.bearer_auth(token.trim());
+    }
+    let response = request.send()?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("{url} responded with {status}");
+    }
+    let issue: serde_json::Value = serde_json::from_str(&response.text()?)?;
+    issue
+        .get("title")
+        .and_then(|title| title.as_str())
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .ok_or_else(|| anyhow!("{url} has no title"))
+}
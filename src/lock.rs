@@ -0,0 +1,46 @@
+/// Files of one database in the runtime directory: the lock that keeps a second TUI from opening
+/// the database, and the socket of its daemon.
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+/// File with `extension` for the database at `db_path`. Named by a hash of the path, because
+/// socket paths are limited to about 100 bytes.
+pub fn runtime_path(db_path: &Path, extension: &str) -> PathBuf {
+    // FNV-1a, stable across builds so every process agrees on the name.
+    let hash = db_path
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("tickr-{hash:016x}.{extension}"))
+}
+
+/// Held by the TUI while it runs. The operating system releases it when the file is dropped or
+/// the process dies, so a crash never leaves a stale lock behind.
+pub struct InstanceLock {
+    _file: File,
+}
+
+/// Takes the TUI lock of the database at `db_path`, failing while another TUI holds it. `None`
+/// when no lock file can be made, e.g. for a database that does not exist as a file.
+pub fn acquire(db_path: &str) -> Result<Option<InstanceLock>> {
+    let Ok(canonical) = std::fs::canonicalize(db_path) else {
+        return Ok(None);
+    };
+    let Ok(file) = File::create(runtime_path(&canonical, "lock")) else {
+        return Ok(None);
+    };
+    match file.try_lock() {
+        Ok(()) => Ok(Some(InstanceLock { _file: file })),
+        Err(TryLockError::WouldBlock) => bail!(
+            "Tickr is already open on {db_path} in another terminal. Commands like `tickr start` \
+             still work; the open Tickr picks up their changes."
+        ),
+        Err(TryLockError::Error(_)) => Ok(None),
+    }
+}
@@ -1,8 +1,31 @@
 mod app;
+mod backup;
+mod batch;
+mod billing;
 mod cli;
 mod color;
+mod config;
+mod daemon;
 mod db;
+mod debug;
+mod doctor;
 mod event;
+mod export;
+mod fuzzy;
+mod git;
+mod goal;
+mod hooks;
+mod import;
+mod issue;
+mod lock;
+mod notify;
+mod picker;
+mod progress;
+mod serve;
+mod shell;
+mod status;
+mod sync;
+mod table;
 mod tui;
 mod types;
 mod ui;
@@ -12,25 +35,99 @@ use anyhow::Result;
 use clap::Parser;
 
 fn main() -> Result<()> {
-    let db_path = db::default_db_path();
-    let conn = db::init(&db_path)?;
     let cli_opts = cli::Cli::parse();
+    // `config init` has to work while the config file is missing or broken.
+    if let Some(cli::Command::Config { command }) = &cli_opts.command {
+        return cli::handle_config(command);
+    }
+    let config = match config::load() {
+        Ok(config) => config,
+        // The debug bundle reports the broken config file instead of failing on it.
+        Err(_) if matches!(cli_opts.command, Some(cli::Command::Debug { .. })) => {
+            config::Config::default()
+        }
+        Err(err) => return Err(err),
+    };
+    let mut db_path = match &cli_opts.db {
+        Some(path) => path.clone(),
+        None => config.db_path(cli_opts.profile.as_deref())?,
+    };
+    if let Some(cli::Command::Status {
+        json,
+        waybar,
+        format,
+        fast: true,
+        ..
+    }) = &cli_opts.command
+        && let Some(status) = status::read_cache(&db_path)
+    {
+        let style = cli::StatusStyle {
+            json: *json,
+            waybar: *waybar,
+            format: format.clone(),
+        };
+        return cli::print_status(status, &style);
+    }
+    hooks::init(config.hooks.clone());
+    let (conn, read_only) = open_database(&db_path, &config)?;
     if let Some(command) = cli_opts.command {
-        return cli::run(command, &conn);
+        if read_only && command.writes() {
+            anyhow::bail!("The database at {db_path} is read-only, this command can't change it.");
+        }
+        return cli::run(command, &config, &conn);
     }
-
-    let mut app = app::App::new(conn);
-    
-    // Check for updates at startup
-    if let Ok(Some(new_version)) = updater::check_for_updates() {
-        app.show_update_popup(new_version);
+    if cli_opts.no_tui {
+        return shell::run(&conn, read_only);
     }
-    
+
+    let mut lock = lock::acquire(&db_path)?;
+    ui::Theme::init(config.theme);
+    let mut app = new_app(
+        conn,
+        read_only,
+        cli_opts.profile.clone(),
+        &cli_opts,
+        &config,
+    )?;
+
+    app.start_update_check();
+
     let mut terminal = tui::init()?;
-    let mut event_handler = event::EventHandler::new();
-    let result = event_handler.run(&mut app, &mut terminal);
+    let mut event_handler = event::EventHandler::new(app.config.tick_rate());
+    let result = loop {
+        let result = event_handler.run(&mut app, &mut terminal);
+        let Some(profile) = app.switch_profile.take() else {
+            break result;
+        };
+        if result.is_err() {
+            break result;
+        }
+        // The profile's database replaces the open one, with a fresh TUI on it. The lock goes
+        // first, for a profile that points at the open database.
+        drop(lock.take());
+        let opened = config.db_path(profile.as_deref()).and_then(|path| {
+            let (conn, read_only) = open_database(&path, &config)?;
+            let new_lock = lock::acquire(&path)?;
+            let new = new_app(conn, read_only, profile.clone(), &cli_opts, &config)?;
+            Ok((path, new_lock, new))
+        });
+        match opened {
+            Ok((path, new_lock, new)) => {
+                db_path = path;
+                lock = new_lock;
+                app = new;
+            }
+            Err(err) => {
+                // Back to the database that was open.
+                lock = lock::acquire(&db_path).ok().flatten();
+                app.running = true;
+                app.status = Some(format!("Can't switch profiles: {err:#}"));
+            }
+        }
+    };
 
     tui::restore()?;
+    config::save_last_view(&app.current_tab()).ok();
 
     // Perform update after TUI is restored if user accepted
     if app.pending_update {
@@ -40,3 +137,45 @@ fn main() -> Result<()> {
 
     result
 }
+
+/// Opens the database at `db_path` and brings it in line with the config file. Returns whether
+/// it is read-only.
+fn open_database(db_path: &str, config: &config::Config) -> Result<(rusqlite::Connection, bool)> {
+    let conn = db::init(db_path)?;
+    let read_only = db::is_read_only(&conn);
+    if !read_only {
+        db::record_global_rate(config.billing.hourly_rate, &conn)?;
+        if config.split_at_midnight {
+            db::split_intervals_at_midnight(&conn)?;
+        }
+    }
+    Ok((conn, read_only))
+}
+
+/// The TUI on `conn`, set up from the command line and the config file.
+fn new_app(
+    conn: rusqlite::Connection,
+    read_only: bool,
+    profile: Option<String>,
+    cli_opts: &cli::Cli,
+    config: &config::Config,
+) -> Result<app::App> {
+    let mut app = app::App::new(conn);
+    app.read_only = read_only;
+    app.profile = profile;
+    app.open_start_view(config.default_view.resolve());
+    app.plain = cli_opts.plain;
+    app.pomodoro_settings = app::PomodoroSettings {
+        work: chrono::Duration::minutes(cli_opts.pomodoro_work.max(1).into()),
+        short_break: chrono::Duration::minutes(cli_opts.pomodoro_break.max(1).into()),
+    };
+    app.idle_tracker = app::IdleTracker::new(
+        (cli_opts.idle_minutes > 0 && !read_only)
+            .then(|| chrono::Duration::minutes(cli_opts.idle_minutes.into())),
+    );
+    app.keymap = app::Keymap::new(&config.keys)?;
+    app.config = config.clone();
+    // A task left running overnight stops before the first frame.
+    app.check_auto_stop();
+    Ok(app)
+}
@@ -0,0 +1,51 @@
+//! Desktop notifications via the platform's notification tool.
+
+/// Shows a desktop notification. Falls back to printing when no notifier is available.
+pub fn send(title: &str, body: &str) {
+    if !try_send(title, body) {
+        println!("[{title}] {body}");
+    }
+}
+
+/// Shows a desktop notification if a notifier is available, without printing anything, e.g.
+/// while the TUI draws on the terminal.
+pub fn send_quietly(title: &str, body: &str) {
+    try_send(title, body);
+}
+
+#[cfg(target_os = "linux")]
+fn try_send(title: &str, body: &str) -> bool {
+    std::process::Command::new("notify-send")
+        .args(["--app-name", "tickr", title, body])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn try_send(title: &str, body: &str) -> bool {
+    let script = format!(
+        "display notification {} with title {}",
+        apple_script_string(body),
+        apple_script_string(title)
+    );
+    std::process::Command::new("osascript")
+        .args(["-e", &script])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn apple_script_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn try_send(_title: &str, _body: &str) -> bool {
+    false
+}
@@ -0,0 +1,215 @@
+/// A list to pick from below the command line, filtered as you type, for choosing a task without
+/// the full-screen interface. It draws in place of the lines under the prompt and clears them
+/// when done, so the shell scrollback keeps only what the command prints afterwards.
+use std::io::{self, Write};
+
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, queue};
+use rusqlite::Connection;
+use unicode_width::UnicodeWidthStr;
+
+use crate::db;
+use crate::types::{Tickr, TickrQuery};
+
+/// Rows of matches shown under the prompt.
+const VISIBLE_ROWS: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct PickerItem {
+    /// Text the filter matches against.
+    pub label: String,
+    /// Other texts that match the item, e.g. the label with a project alias for the name.
+    pub also_matches: Vec<String>,
+    /// Dimmed text after the label, e.g. when the task was last worked on.
+    pub detail: String,
+}
+
+/// Leaves raw mode however the picker ends.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+/// Lets the user narrow `items` down by typing and pick one with Enter. Returns the index into
+/// `items`, `None` when cancelled with Esc or Ctrl+C. Items keep their order among equal matches.
+pub fn pick(prompt: &str, items: &[PickerItem]) -> Result<Option<usize>> {
+    let _raw = RawMode::enable()?;
+    let mut stdout = io::stdout();
+    let mut query = String::new();
+    let mut selected = 0;
+    let picked = loop {
+        let matches = filter(&query, items);
+        selected = selected.min(matches.len().saturating_sub(1));
+        draw(&mut stdout, prompt, &query, items, &matches, selected)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => break None,
+            KeyCode::Char('c') if ctrl => break None,
+            KeyCode::Enter => break matches.get(selected).copied(),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => selected += 1,
+            KeyCode::Char('n') if ctrl => selected += 1,
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    };
+    queue!(
+        stdout,
+        cursor::MoveToColumn(0),
+        terminal::Clear(ClearType::FromCursorDown)
+    )?;
+    stdout.flush()?;
+    Ok(picked)
+}
+
+/// The open tasks of open projects, most recently worked on first, with their items for
+/// switching to them.
+pub fn switch_items(date_format: &str, conn: &Connection) -> Result<(Vec<Tickr>, Vec<PickerItem>)> {
+    let projects = db::query_projects(conn)?;
+    let aliases = db::query_project_aliases(conn)?;
+    let running_id = db::query_running_tickr(conn)?.and_then(|tickr| tickr.id);
+    let mut tickrs: Vec<(Tickr, &str)> = db::query_tickr(TickrQuery::All, conn)?
+        .into_iter()
+        .filter(|tickr| !tickr.done && !tickr.archived)
+        .filter_map(|tickr| {
+            let project = projects
+                .iter()
+                .find(|project| project.id == Some(tickr.project_id) && !project.archived)?;
+            Some((tickr, project.name.as_str()))
+        })
+        .collect();
+    // Never worked on sorts last.
+    tickrs.sort_by_key(|(tickr, _)| std::cmp::Reverse(crate::ui::last_worked(tickr)));
+    let items = tickrs
+        .iter()
+        .map(|(tickr, project)| PickerItem {
+            label: format!("{project} / {}", tickr.description),
+            also_matches: aliases
+                .iter()
+                .filter(|(_, project_id)| *project_id == tickr.project_id)
+                .map(|(alias, _)| format!("{alias} / {}", tickr.description))
+                .collect(),
+            detail: if tickr.id.is_some() && tickr.id == running_id {
+                "running".to_string()
+            } else {
+                crate::ui::last_worked(tickr).map_or("never".to_string(), |then| {
+                    crate::ui::format_relative(then, date_format)
+                })
+            },
+        })
+        .collect();
+    Ok((tickrs.into_iter().map(|(tickr, _)| tickr).collect(), items))
+}
+
+/// Indices of the items matching `query`, best matches first.
+pub fn filter(query: &str, items: &[PickerItem]) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            std::iter::once(&item.label)
+                .chain(&item.also_matches)
+                .filter_map(|text| crate::fuzzy::filter_score(query, text))
+                .max()
+                .map(|score| (-score, index))
+        })
+        .collect();
+    scored.sort();
+    scored.into_iter().map(|(_, index)| index).collect()
+}
+
+fn draw(
+    stdout: &mut io::Stdout,
+    prompt: &str,
+    query: &str,
+    items: &[PickerItem],
+    matches: &[usize],
+    selected: usize,
+) -> Result<()> {
+    // Lines longer than the terminal would wrap and throw off the way back up to the prompt.
+    let width = terminal::size().map_or(80, |(columns, _)| columns as usize);
+    let line_width = width.saturating_sub(1);
+    // Scroll the list so the selected row stays visible.
+    let first = selected.saturating_sub(VISIBLE_ROWS - 1);
+    let rows = matches.iter().skip(first).take(VISIBLE_ROWS);
+    queue!(
+        stdout,
+        cursor::MoveToColumn(0),
+        terminal::Clear(ClearType::FromCursorDown)
+    )?;
+    let mut lines = 0;
+    for (offset, index) in rows.enumerate() {
+        let item = &items[*index];
+        let is_selected = first + offset == selected;
+        let marker = if is_selected { "> " } else { "  " };
+        let label_width = item
+            .label
+            .width()
+            .min(line_width.saturating_sub(marker.len()));
+        let label = crate::ui::clamp_name(&item.label, label_width);
+        let detail_width = line_width.saturating_sub(marker.len() + label_width);
+        let detail = crate::ui::clamp_name(&format!("  {}", item.detail), detail_width);
+        queue!(stdout, Print("\r\n"), Print(marker))?;
+        if is_selected {
+            queue!(
+                stdout,
+                SetAttribute(Attribute::Bold),
+                Print(label),
+                SetAttribute(Attribute::Reset)
+            )?;
+        } else {
+            queue!(stdout, Print(label))?;
+        }
+        queue!(
+            stdout,
+            SetAttribute(Attribute::Dim),
+            Print(detail.trim_end()),
+            SetAttribute(Attribute::Reset)
+        )?;
+        lines += 1;
+    }
+    if matches.is_empty() {
+        queue!(stdout, Print("\r\n  No matches"))?;
+        lines += 1;
+    }
+    if lines > 0 {
+        queue!(stdout, cursor::MoveUp(lines))?;
+    }
+    let input = crate::ui::clamp_name(&format!("{prompt}{query}"), line_width);
+    let input = input.trim_end();
+    queue!(
+        stdout,
+        cursor::MoveToColumn(0),
+        Print(input),
+        cursor::MoveToColumn(input.width() as u16)
+    )?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,109 @@
+//! Progress bar for CLI commands that work through many records. It is drawn on stderr, so it
+//! stays out of piped output, and only once the work takes long enough to be worth watching.
+
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Work finished sooner than this never shows a bar.
+const SHOW_AFTER: Duration = Duration::from_millis(300);
+/// Least time between two redraws.
+const REDRAW_EVERY: Duration = Duration::from_millis(100);
+const BAR_WIDTH: usize = 24;
+
+pub struct Progress {
+    label: String,
+    total: usize,
+    done: usize,
+    started: Instant,
+    last_draw: Option<Instant>,
+    /// Whether stderr is a terminal to draw on.
+    enabled: bool,
+}
+
+impl Progress {
+    pub fn new(label: &str, total: usize) -> Self {
+        Self {
+            label: label.to_string(),
+            total,
+            done: 0,
+            started: Instant::now(),
+            last_draw: None,
+            enabled: std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// For work that only learns how much there is once it started.
+    pub fn set_total(&mut self, total: usize) {
+        self.total = total;
+    }
+
+    /// Records that `done` of the items are finished.
+    pub fn set(&mut self, done: usize) {
+        self.done = done.min(self.total);
+        if !self.enabled || self.started.elapsed() < SHOW_AFTER {
+            return;
+        }
+        if self
+            .last_draw
+            .is_some_and(|last| last.elapsed() < REDRAW_EVERY)
+        {
+            return;
+        }
+        self.last_draw = Some(Instant::now());
+        let line = self.line();
+        let mut stderr = std::io::stderr();
+        write!(stderr, "\r{line}\x1b[K").ok();
+        stderr.flush().ok();
+    }
+
+    /// Removes the bar until the next [`Progress::set`], for printing a line of its own.
+    pub fn clear(&mut self) {
+        if self.last_draw.take().is_some() {
+            let mut stderr = std::io::stderr();
+            write!(stderr, "\r\x1b[K").ok();
+            stderr.flush().ok();
+        }
+    }
+
+    fn line(&self) -> String {
+        let fraction = if self.total == 0 {
+            1.0
+        } else {
+            self.done as f64 / self.total as f64
+        };
+        let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+        let eta = if self.done > 0 && self.done < self.total {
+            let per_item = self.started.elapsed().as_secs_f64() / self.done as f64;
+            let seconds = (per_item * (self.total - self.done) as f64).ceil() as i64;
+            format!(
+                "  {} left",
+                crate::ui::format_duration(chrono::Duration::seconds(seconds))
+            )
+        } else {
+            String::new()
+        };
+        let line = format!(
+            "{} [{}{}] {}/{}{eta}  Ctrl+C cancels",
+            self.label,
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            self.done,
+            self.total,
+        );
+        // A line that wraps can't be redrawn in place.
+        let width = crossterm::terminal::size()
+            .ok()
+            .filter(|(columns, _)| *columns > 0)
+            .map_or(80, |(columns, _)| columns as usize);
+        crate::ui::clamp_name(&line, width.saturating_sub(1))
+            .trim_end()
+            .to_string()
+    }
+}
+
+impl Drop for Progress {
+    /// Removes the bar, so the summary printed next starts on a clean line.
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -0,0 +1,681 @@
+/// `tickr serve`: a small JSON API over HTTP, so browser dashboards, phone shortcuts and launcher
+/// extensions can read tracked time and start or stop tasks. Each connection is read on its own
+/// thread, so a slow client doesn't hold up the others; the database work happens one request at
+/// a time on the thread that owns the connection. Every request needs the token and the server's
+/// own address as its `Host`, checked before a body is read.
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::billing::BillingRules;
+use crate::types::{Interval, ReportGrouping, Tickr, TickrId, TickrQuery};
+use crate::{daemon, db, export, status, sync};
+
+/// How long a client may take to send its request.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Requests are small; anything bigger is refused.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+/// Changes sent by `tickr sync`, which can be a whole history the first time.
+const MAX_SYNC_BODY_BYTES: usize = 64 * 1024 * 1024;
+/// Longest request line or header line.
+const MAX_LINE_BYTES: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+/// Connections served at once; more are turned away until one finishes.
+const MAX_CLIENTS: usize = 16;
+
+pub struct ServeOptions {
+    pub host: String,
+    pub port: u16,
+    /// Required as `Authorization: Bearer <token>` on every request.
+    pub token: String,
+}
+
+/// A request read by a client thread, with where to send its answer.
+type Job = (Request, mpsc::Sender<(u16, Option<Value>)>);
+
+/// What a client thread checks before it reads a request's body.
+struct Access {
+    token: String,
+    /// Host name given with `--host`.
+    host: String,
+    /// Address the server listens on.
+    address: SocketAddr,
+}
+
+impl Access {
+    /// Whether `host`, a `Host` header, names the server rather than another site, which a web
+    /// page could point at it through DNS rebinding. A server on all interfaces takes any IP
+    /// address.
+    fn allows_host(&self, host: &str) -> bool {
+        let (name, port) = match host.strip_prefix('[') {
+            Some(rest) => match rest.split_once(']') {
+                Some((name, rest)) => (name, rest.strip_prefix(':')),
+                None => return false,
+            },
+            None => match host.split_once(':') {
+                Some((name, port)) => (name, Some(port)),
+                None => (host, None),
+            },
+        };
+        if port.is_some_and(|port| port.parse() != Ok(self.address.port())) {
+            return false;
+        }
+        if name.eq_ignore_ascii_case(&self.host) {
+            return true;
+        }
+        match name.parse::<IpAddr>() {
+            Ok(ip) => self.address.ip().is_unspecified() || ip == self.address.ip(),
+            Err(_) => name.eq_ignore_ascii_case("localhost") && self.address.ip().is_loopback(),
+        }
+    }
+}
+
+/// Counts a connection while it is served.
+struct ClientSlot(Arc<AtomicUsize>);
+
+impl ClientSlot {
+    fn take(clients: &Arc<AtomicUsize>) -> Option<Self> {
+        if clients.fetch_add(1, Ordering::SeqCst) < MAX_CLIENTS {
+            Some(Self(clients.clone()))
+        } else {
+            clients.fetch_sub(1, Ordering::SeqCst);
+            None
+        }
+    }
+}
+
+impl Drop for ClientSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn date(&self, name: &str) -> Result<Option<NaiveDate>, HttpError> {
+        self.query
+            .get(name)
+            .map(|value| {
+                NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                    HttpError::bad_request(format!("Invalid {name} '{value}', expected YYYY-MM-DD"))
+                })
+            })
+            .transpose()
+    }
+}
+
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: 404,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for HttpError {
+    fn from(err: anyhow::Error) -> Self {
+        Self {
+            status: 500,
+            message: format!("{err:#}"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ProjectOutput {
+    id: Option<u32>,
+    name: String,
+    parent_id: Option<u32>,
+    archived: bool,
+    created_at: DateTime<Local>,
+}
+
+#[derive(Serialize)]
+struct TaskOutput {
+    id: Option<TickrId>,
+    project_id: u32,
+    description: String,
+    category: Option<String>,
+    billable: bool,
+    done: bool,
+    archived: bool,
+    running: bool,
+    tracked_seconds: i64,
+    notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    intervals: Option<Vec<IntervalOutput>>,
+}
+
+#[derive(Serialize)]
+struct IntervalOutput {
+    id: Option<u32>,
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    notes: Option<String>,
+}
+
+impl From<&Interval> for IntervalOutput {
+    fn from(interval: &Interval) -> Self {
+        Self {
+            id: interval.id,
+            start: interval.start_time,
+            end: interval.end_time,
+            notes: interval.notes.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ReportRowOutput {
+    label: String,
+    seconds: i64,
+    billable_seconds: i64,
+    billed_seconds: i64,
+    amounts: Vec<AmountOutput>,
+    intervals: usize,
+    tasks: usize,
+}
+
+#[derive(Serialize)]
+struct AmountOutput {
+    currency: Option<String>,
+    amount: f64,
+}
+
+/// Serves the API until the process is stopped.
+pub fn run(options: ServeOptions, billing: &BillingRules, conn: &Connection) -> Result<()> {
+    let listener = TcpListener::bind((options.host.as_str(), options.port))?;
+    let read_only = db::is_read_only(conn);
+    let access = Arc::new(Access {
+        token: options.token,
+        host: options.host,
+        address: listener.local_addr()?,
+    });
+    println!(
+        "Serving the Tickr API on http://{}{}",
+        access.address,
+        if read_only { " (read-only)" } else { "" }
+    );
+    let (jobs, queue) = mpsc::channel::<Job>();
+    std::thread::spawn(move || {
+        let clients = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming().flatten() {
+            let Some(slot) = ClientSlot::take(&clients) else {
+                let busy = json!({ "error": "Too many connections, try again shortly" });
+                write_response(&stream, 503, Some(&busy)).ok();
+                continue;
+            };
+            let jobs = jobs.clone();
+            let access = access.clone();
+            std::thread::spawn(move || {
+                handle_client(stream, &access, &jobs).ok();
+                drop(slot);
+            });
+        }
+    });
+    for (request, reply) in queue {
+        reply.send(respond(&request, read_only, billing, conn)).ok();
+    }
+    Ok(())
+}
+
+/// Reads one request and writes its answer, leaving the database work to the thread of `run`.
+fn handle_client(stream: TcpStream, access: &Access, jobs: &mpsc::Sender<Job>) -> Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let (status, body) = match read_request(&mut reader, access) {
+        // Browsers ask before sending the token from another origin.
+        Ok(request) if request.method == "OPTIONS" => (204, None),
+        Ok(request) => {
+            let (reply, answer) = mpsc::channel();
+            jobs.send((request, reply))?;
+            answer.recv()?
+        }
+        Err(err) => (err.status, Some(json!({ "error": err.message }))),
+    };
+    write_response(&stream, status, body.as_ref())
+}
+
+fn respond(
+    request: &Request,
+    read_only: bool,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> (u16, Option<Value>) {
+    let result = route(request, read_only, billing, conn);
+    println!(
+        "{} {} {}",
+        Local::now().format("%H:%M:%S"),
+        request.method,
+        request.path
+    );
+    match result {
+        Ok(value) => (200, Some(value)),
+        Err(err) => (err.status, Some(json!({ "error": err.message }))),
+    }
+}
+
+/// Reads a request, refusing it once its headers show no access, before the body is read.
+fn read_request(reader: &mut BufReader<&TcpStream>, access: &Access) -> Result<Request, HttpError> {
+    let malformed = || HttpError::bad_request("Malformed HTTP request");
+    let line = read_line(reader)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(malformed());
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect();
+
+    let mut headers = HashMap::new();
+    loop {
+        let line = read_line(reader)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(HttpError::bad_request("Too many headers"));
+        }
+        let (name, value) = line.split_once(':').ok_or_else(malformed)?;
+        headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+    }
+    authorize(method, &headers, access)?;
+
+    // Only `POST /sync` reads a body, but one that was sent has to be consumed before answering.
+    let length: usize = headers
+        .get("content-length")
+        .map_or(Ok(0), |value| value.parse())
+        .map_err(|_| malformed())?;
+    let path = path.trim_end_matches('/');
+    let limit = if path == "/sync" {
+        MAX_SYNC_BODY_BYTES
+    } else {
+        MAX_BODY_BYTES
+    };
+    if length > limit {
+        return Err(HttpError::bad_request("Request body too large"));
+    }
+    let mut body = Vec::new();
+    reader
+        .take(length as u64)
+        .read_to_end(&mut body)
+        .map_err(|_| malformed())?;
+    if body.len() != length {
+        return Err(malformed());
+    }
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query,
+        body,
+    })
+}
+
+/// One line of the request head, at most [`MAX_LINE_BYTES`] long.
+fn read_line(reader: &mut BufReader<&TcpStream>) -> Result<String, HttpError> {
+    let mut line = String::new();
+    reader
+        .take(MAX_LINE_BYTES)
+        .read_line(&mut line)
+        .map_err(|_| HttpError::bad_request("Malformed HTTP request"))?;
+    if !line.ends_with('\n') {
+        return Err(HttpError::bad_request("Request line or header too long"));
+    }
+    Ok(line)
+}
+
+/// A query string value with `+` and `%XX` escapes decoded; broken escapes stay as they are.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' if let Some(byte) = value
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()) =>
+            {
+                decoded.push(byte);
+                index += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Checks the `Host` of every request and the token of all but a browser's preflight `OPTIONS`,
+/// which can't carry it.
+fn authorize(
+    method: &str,
+    headers: &HashMap<String, String>,
+    access: &Access,
+) -> Result<(), HttpError> {
+    if !headers
+        .get("host")
+        .is_some_and(|host| access.allows_host(host))
+    {
+        return Err(HttpError {
+            status: 403,
+            message: "Unknown Host".to_string(),
+        });
+    }
+    if method == "OPTIONS" {
+        return Ok(());
+    }
+    let given = headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if constant_time_eq(given.as_bytes(), access.token.as_bytes()) {
+        Ok(())
+    } else {
+        Err(HttpError {
+            status: 401,
+            message: "Missing or wrong token".to_string(),
+        })
+    }
+}
+
+/// Compares without stopping at the first difference, so the time taken doesn't tell how much of
+/// a guessed token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut difference = a.len() ^ b.len();
+    for (index, byte) in b.iter().enumerate() {
+        difference |= usize::from(a.get(index).copied().unwrap_or(0) ^ byte);
+    }
+    difference == 0
+}
+
+fn write_response(mut stream: &TcpStream, status: u16, body: Option<&Value>) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let body = body.map(Value::to_string).unwrap_or_default();
+    // Browser dashboards are served from another origin; every answer needs the token, which a
+    // web page the user happens to have open doesn't know.
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Authorization, Content-Type\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn route(
+    request: &Request,
+    read_only: bool,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<Value, HttpError> {
+    let segments: Vec<&str> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+    if request.method == "POST" && read_only {
+        return Err(HttpError {
+            status: 403,
+            message: "The database is read-only".to_string(),
+        });
+    }
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["status"]) => Ok(json!(status::query_status(conn)?)),
+        ("GET", ["projects"]) => projects(conn),
+        ("GET", ["tasks"]) => tasks(request, conn),
+        ("GET", ["tasks", id]) => task(parse_id(id)?, conn),
+        ("POST", ["tasks", id, "start"]) => start(parse_id(id)?, conn),
+        ("POST", ["tasks", id, "stop"]) => stop(Some(parse_id(id)?), conn),
+        ("POST", ["stop"]) => stop(None, conn),
+        ("GET", ["intervals"]) => intervals(request, billing, conn),
+        ("GET", ["report"]) => report(request, billing, conn),
+        ("GET", ["sync"]) => sync_changes(request, conn),
+        ("POST", ["sync"]) => sync_apply(request, conn),
+        (_, ["status" | "projects" | "tasks" | "intervals" | "report" | "stop" | "sync"])
+        | (_, ["tasks", _] | ["tasks", _, "start" | "stop"]) => Err(HttpError {
+            status: 405,
+            message: format!("{} is not allowed here", request.method),
+        }),
+        _ => Err(HttpError::not_found(format!(
+            "No endpoint {}",
+            request.path
+        ))),
+    }
+}
+
+fn parse_id(value: &str) -> Result<TickrId, HttpError> {
+    value
+        .parse()
+        .map_err(|_| HttpError::bad_request(format!("Invalid task id '{value}'")))
+}
+
+fn projects(conn: &Connection) -> Result<Value, HttpError> {
+    let projects: Vec<ProjectOutput> = db::query_projects(conn)?
+        .into_iter()
+        .map(|project| ProjectOutput {
+            id: project.id,
+            name: project.name,
+            parent_id: project.parent_id,
+            archived: project.archived,
+            created_at: project.created_at,
+        })
+        .collect();
+    Ok(json!(projects))
+}
+
+fn task_output(tickr: &Tickr, with_intervals: bool, conn: &Connection) -> Result<TaskOutput> {
+    let now = Local::now();
+    let category = match tickr.category_id {
+        Some(id) => db::query_category_by_id(id, conn)?.map(|category| category.name),
+        None => None,
+    };
+    Ok(TaskOutput {
+        id: tickr.id,
+        project_id: tickr.project_id,
+        description: tickr.description.clone(),
+        category,
+        billable: tickr.billable,
+        done: tickr.done,
+        archived: tickr.archived,
+        running: tickr.intervals.iter().any(|i| i.end_time.is_none()),
+        tracked_seconds: tickr
+            .intervals
+            .iter()
+            .map(|i| (i.end_time.unwrap_or(now) - i.start_time).num_seconds())
+            .sum(),
+        notes: tickr.notes.clone(),
+        intervals: with_intervals.then(|| tickr.intervals.iter().map(Into::into).collect()),
+    })
+}
+
+fn tasks(request: &Request, conn: &Connection) -> Result<Value, HttpError> {
+    let query = match request.query.get("project") {
+        Some(id) => TickrQuery::ByProjectId(
+            id.parse()
+                .map_err(|_| HttpError::bad_request(format!("Invalid project id '{id}'")))?,
+        ),
+        None => TickrQuery::All,
+    };
+    let tasks = db::query_tickr(query, conn)?
+        .iter()
+        .map(|tickr| task_output(tickr, false, conn))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(json!(tasks))
+}
+
+fn task(id: TickrId, conn: &Connection) -> Result<Value, HttpError> {
+    let tickr = db::query_tickr_by_id(id, conn)?
+        .ok_or_else(|| HttpError::not_found(format!("No task {id}")))?;
+    Ok(json!(task_output(&tickr, true, conn)?))
+}
+
+/// Starts a task and ends whatever else runs, then answers with the new status.
+fn start(id: TickrId, conn: &Connection) -> Result<Value, HttpError> {
+    db::query_tickr_by_id(id, conn)?
+        .ok_or_else(|| HttpError::not_found(format!("No task {id}")))?;
+    let warning = daemon::start_tickr(id, conn)?;
+    status_after(warning, conn)
+}
+
+/// Stops task `id`, or the running task when `None`, then answers with the new status.
+fn stop(id: Option<TickrId>, conn: &Connection) -> Result<Value, HttpError> {
+    let id = match id {
+        Some(id) => {
+            db::query_tickr_by_id(id, conn)?
+                .ok_or_else(|| HttpError::not_found(format!("No task {id}")))?;
+            Some(id)
+        }
+        None => db::query_running_tickr(conn)?.and_then(|tickr| tickr.id),
+    };
+    let warning = match id {
+        Some(id) => daemon::end_tickr(id, conn)?,
+        None => None,
+    };
+    status_after(warning, conn)
+}
+
+/// The status after a start or stop, with a `warning` when an interval ended up holding no time.
+fn status_after(warning: Option<db::IntervalError>, conn: &Connection) -> Result<Value, HttpError> {
+    let mut status = json!(status::query_status(conn)?);
+    if let Some(warning) = warning {
+        status["warning"] = json!(warning.to_string());
+    }
+    Ok(status)
+}
+
+/// Intervals as in a JSON export, limited by the optional `from` and `to` days.
+fn intervals(
+    request: &Request,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<Value, HttpError> {
+    let from = request.date("from")?;
+    let to = request.date("to")?;
+    let records = export::collect_records(
+        from.map(crate::ui::local_start_of_day),
+        to.map(|date| crate::ui::local_start_of_day(date + chrono::Duration::days(1))),
+        billing,
+        conn,
+    )?;
+    Ok(json!(records))
+}
+
+/// Totals like `tickr report`, by `by` (project, client, category or task) over the last seven days
+/// unless `from` and `to` say otherwise.
+fn report(
+    request: &Request,
+    billing: &BillingRules,
+    conn: &Connection,
+) -> Result<Value, HttpError> {
+    let today = Local::now().date_naive();
+    let from = request
+        .date("from")?
+        .unwrap_or(today - chrono::Duration::days(6));
+    let to = request.date("to")?.unwrap_or(today);
+    let grouping = match request.query.get("by").map(String::as_str) {
+        None | Some("project") => ReportGrouping::Project,
+        Some("client") => ReportGrouping::Client,
+        Some("category") => ReportGrouping::Category,
+        Some("task") => ReportGrouping::Task,
+        Some(other) => {
+            return Err(HttpError::bad_request(format!(
+                "Invalid by '{other}', expected project, client, category or task"
+            )));
+        }
+    };
+    let rows: Vec<ReportRowOutput> = db::query_report(
+        grouping,
+        crate::ui::local_start_of_day(from),
+        crate::ui::local_start_of_day(to + chrono::Duration::days(1)),
+        None,
+        billing,
+        conn,
+    )?
+    .into_iter()
+    .map(|row| ReportRowOutput {
+        amounts: row
+            .amounts
+            .iter()
+            .map(|(currency, amount)| AmountOutput {
+                currency: currency.map(|currency| currency.to_string()),
+                amount,
+            })
+            .collect(),
+        label: row.label,
+        seconds: row.seconds,
+        billable_seconds: row.billable_seconds,
+        billed_seconds: row.billed_seconds,
+        intervals: row.intervals,
+        tasks: row.tasks,
+    })
+    .collect();
+    Ok(json!({ "from": from, "to": to, "rows": rows }))
+}
+
+/// Changes after the sync version `since` (0 for everything), for `tickr sync` to pull.
+fn sync_changes(request: &Request, conn: &Connection) -> Result<Value, HttpError> {
+    let since = match request.query.get("since") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| HttpError::bad_request(format!("Invalid since '{value}'")))?,
+        None => 0,
+    };
+    Ok(json!(db::query_changes(since, conn)?))
+}
+
+/// Applies changes pushed by `tickr sync`.
+fn sync_apply(request: &Request, conn: &Connection) -> Result<Value, HttpError> {
+    let changes: sync::ChangeSet = serde_json::from_slice(&request.body)
+        .map_err(|err| HttpError::bad_request(format!("Invalid changes: {err}")))?;
+    let applied = db::apply_changes(&changes, false, conn)?;
+    Ok(json!(sync::PushResult {
+        applied,
+        version: db::sync_version(conn)?,
+    }))
+}
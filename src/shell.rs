@@ -0,0 +1,318 @@
+/// Line-based interactive interface used with `--no-tui`.
+///
+/// Every piece of output is a plain line of text without colors, box drawing or animation,
+/// so it works with screen readers and terminals that cannot render the TUI.
+use std::io::{self, BufRead, Write};
+
+use anyhow::Result;
+use chrono::{Duration, Local};
+use rusqlite::Connection;
+
+use crate::billing::BillingRules;
+use crate::types::{self, ReportGrouping, Tickr};
+use crate::{daemon, db, ui};
+
+const HELP: &str = "Commands:
+  status           Show the running task
+  projects         List projects
+  tasks [project]  List tasks, optionally only those of one project (name or number)
+  start <number>   Start a task from the last task list, stopping the running one
+  stop             Stop the running task
+  new              Create a task, answering one question per line
+  today            Show time worked today per project
+  help             Show this list
+  quit             Leave Tickr";
+
+/// Runs the prompt loop until the user quits or stdin is closed. A `read_only` database can
+/// only be browsed.
+pub fn run(conn: &Connection, read_only: bool) -> Result<()> {
+    let mut shell = Shell {
+        conn,
+        projects: Vec::new(),
+        tasks: Vec::new(),
+    };
+    println!("Tickr interactive mode. Type help for a list of commands.");
+    if read_only {
+        println!("The database is read-only, tasks can't be started, stopped or created.");
+    }
+    shell.status()?;
+    while let Some(line) = read_line("tickr> ")? {
+        let (command, argument) = match line.split_once(' ') {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line.as_str(), ""),
+        };
+        let result = match command.to_lowercase().as_str() {
+            "" => Ok(()),
+            "help" | "?" => {
+                println!("{HELP}");
+                Ok(())
+            }
+            "start" | "stop" | "new" if read_only => {
+                println!("The database is read-only, tasks can't be started, stopped or created.");
+                Ok(())
+            }
+            "status" => shell.status(),
+            "projects" => shell.list_projects(),
+            "tasks" => shell.list_tasks(argument),
+            "start" => shell.start(argument),
+            "stop" => shell.stop(),
+            "new" => shell.new_task(),
+            "today" => shell.today(),
+            "quit" | "exit" | "q" => break,
+            other => {
+                println!("Unknown command '{other}'. Type help for a list of commands.");
+                Ok(())
+            }
+        };
+        if let Err(err) = result {
+            println!("Error: {err}");
+        }
+    }
+    Ok(())
+}
+
+struct Shell<'a> {
+    conn: &'a Connection,
+    /// Projects as numbered by the last `projects` command.
+    projects: Vec<types::Project>,
+    /// Tasks as numbered by the last `tasks` command.
+    tasks: Vec<Tickr>,
+}
+
+impl Shell<'_> {
+    fn status(&self) -> Result<()> {
+        let Some(tickr) = db::query_running_tickr(self.conn)? else {
+            println!("No task running.");
+            return Ok(());
+        };
+        let started = tickr
+            .intervals
+            .iter()
+            .find(|interval| interval.end_time.is_none())
+            .map(|interval| interval.start_time);
+        let project = self.project_name(tickr.project_id)?;
+        match started {
+            Some(start) => println!(
+                "Running: {} in project {project}, started at {}, elapsed {}.",
+                tickr.description,
+                start.format("%H:%M"),
+                ui::format_duration(Local::now().signed_duration_since(start))
+            ),
+            None => println!("Running: {} in project {project}.", tickr.description),
+        }
+        Ok(())
+    }
+
+    fn list_projects(&mut self) -> Result<()> {
+        self.projects = db::query_projects(self.conn)?;
+        if self.projects.is_empty() {
+            println!("No projects yet.");
+            return Ok(());
+        }
+        println!("{} projects:", self.projects.len());
+        for (index, project) in self.projects.iter().enumerate() {
+            println!("{}. {}", index + 1, project.name);
+        }
+        Ok(())
+    }
+
+    fn list_tasks(&mut self, project: &str) -> Result<()> {
+        let tasks = if project.is_empty() {
+            db::query_tickr(types::TickrQuery::All, self.conn)?
+        } else {
+            let Some(project) = self.find_project(project)? else {
+                println!("Project '{project}' not found.");
+                return Ok(());
+            };
+            db::query_tickr(
+                types::TickrQuery::ByProjectId(project.id.unwrap_or_default()),
+                self.conn,
+            )?
+        };
+        self.tasks = tasks;
+        if self.tasks.is_empty() {
+            println!("No tasks.");
+            return Ok(());
+        }
+        println!("{} tasks:", self.tasks.len());
+        for (index, tickr) in self.tasks.iter().enumerate() {
+            let running = tickr.intervals.iter().any(|i| i.end_time.is_none());
+            let total = tickr
+                .intervals
+                .iter()
+                .map(|interval| interval.duration(Local::now()))
+                .fold(Duration::zero(), |sum, duration| sum + duration);
+            println!(
+                "{}. {} in project {}, total {}{}",
+                index + 1,
+                tickr.description,
+                self.project_name(tickr.project_id)?,
+                ui::format_duration(total),
+                if running { ", running" } else { "" }
+            );
+        }
+        Ok(())
+    }
+
+    fn start(&mut self, argument: &str) -> Result<()> {
+        if self.tasks.is_empty() {
+            println!("List tasks first with the tasks command.");
+            return Ok(());
+        }
+        let Some(tickr) = argument
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| self.tasks.get(index))
+        else {
+            println!(
+                "Give a task number between 1 and {}, for example: start 1",
+                self.tasks.len()
+            );
+            return Ok(());
+        };
+        let (Some(id), description) = (tickr.id, tickr.description.clone()) else {
+            return Ok(());
+        };
+        if let Some(running) = db::query_running_tickr(self.conn)? {
+            if running.id == Some(id) {
+                println!("{description} is already running.");
+                return Ok(());
+            }
+            // Starting ends it.
+            println!("Stopped {}.", running.description);
+        }
+        if let Some(warning) = daemon::start_tickr(id, self.conn)? {
+            eprintln!("Warning: {warning}");
+        }
+        println!("Started {description}.");
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<()> {
+        match db::query_running_tickr(self.conn)? {
+            Some(Tickr {
+                id: Some(id),
+                description,
+                ..
+            }) => {
+                if let Some(warning) = daemon::end_tickr(id, self.conn)? {
+                    eprintln!("Warning: {warning}");
+                }
+                println!("Stopped {description}.");
+            }
+            _ => println!("No task running."),
+        }
+        Ok(())
+    }
+
+    fn new_task(&mut self) -> Result<()> {
+        let Some(project_name) = read_line("Project name or number: ")? else {
+            return Ok(());
+        };
+        let Some(project) = self.find_project(&project_name)? else {
+            println!("Project '{project_name}' not found. Create it with: tickr project add");
+            return Ok(());
+        };
+        let Some(description) = read_line("Task description: ")? else {
+            return Ok(());
+        };
+        if description.is_empty() {
+            println!("Cancelled, the description is empty.");
+            return Ok(());
+        }
+        let start_now = read_line("Start it now? yes or no: ")?
+            .map(|answer| matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+            .unwrap_or(false);
+
+        let id = db::create_tickr(
+            Tickr {
+                id: None,
+                project_id: project.id.unwrap_or_default(),
+                description: description.clone(),
+                category_id: None,
+                billable: true,
+                done: false,
+                blocked_by: None,
+                notes: None,
+                estimate_minutes: None,
+                archived: false,
+                issue: None,
+                intervals: Vec::new(),
+            },
+            self.conn,
+        )?;
+        println!("Created {description} in project {}.", project.name);
+        if start_now {
+            if let Some(running) = db::query_running_tickr(self.conn)? {
+                println!("Stopped {}.", running.description);
+            }
+            if let Some(warning) = daemon::start_tickr(id, self.conn)? {
+                eprintln!("Warning: {warning}");
+            }
+            println!("Started {description}.");
+        }
+        Ok(())
+    }
+
+    fn today(&self) -> Result<()> {
+        let from = ui::local_start_of_day(Local::now().date_naive());
+        let rows = db::query_report(
+            ReportGrouping::Project,
+            from,
+            Local::now(),
+            None,
+            &BillingRules::default(),
+            self.conn,
+        )?;
+        if rows.is_empty() {
+            println!("Nothing tracked today.");
+            return Ok(());
+        }
+        let total: i64 = rows.iter().map(|row| row.seconds).sum();
+        println!(
+            "Worked today: {}",
+            ui::format_duration(Duration::seconds(total))
+        );
+        for row in rows {
+            println!(
+                "{}: {}",
+                row.label,
+                ui::format_duration(Duration::seconds(row.seconds))
+            );
+        }
+        Ok(())
+    }
+
+    /// Resolves a project by number from the last `projects` listing or by name.
+    fn find_project(&self, value: &str) -> Result<Option<types::Project>> {
+        if let Ok(number) = value.parse::<usize>()
+            && let Some(project) = number
+                .checked_sub(1)
+                .and_then(|index| self.projects.get(index))
+        {
+            return Ok(Some(project.clone()));
+        }
+        Ok(db::query_projects(self.conn)?
+            .into_iter()
+            .find(|project| project.name.eq_ignore_ascii_case(value)))
+    }
+
+    fn project_name(&self, id: types::ProjectId) -> Result<String> {
+        Ok(db::query_project_by_id(id, self.conn)?
+            .map(|project| project.name)
+            .unwrap_or_else(|| "Unknown project".to_string()))
+    }
+}
+
+/// Prints `question` and reads one trimmed line, returning `None` once stdin is closed.
+fn read_line(question: &str) -> Result<Option<String>> {
+    print!("{question}");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        println!();
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
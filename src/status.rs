@@ -0,0 +1,180 @@
+/// The running task as printed by `tickr status`, and a small cache of it for status bars that
+/// poll every few seconds: `status --fast` reads the cache instead of opening the database.
+/// `status --waybar` prints it as the JSON object of a Waybar custom module.
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::db;
+
+#[derive(Serialize, Deserialize)]
+pub struct StatusOutput {
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started: Option<DateTime<Local>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_seconds: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed: Option<String>,
+}
+
+/// Output of `status --waybar`, one line per update for a custom module with `return-type`
+/// `json`. Polybar and i3status wrappers can pick the fields they need with `jq`.
+#[derive(Serialize)]
+pub struct WaybarOutput {
+    pub text: String,
+    pub tooltip: String,
+    /// `running` or `idle`, for styling the module.
+    pub class: &'static str,
+}
+
+/// Text of the Waybar module unless `--format` sets another.
+const WAYBAR_FORMAT: &str = "{task} {elapsed}";
+
+impl StatusOutput {
+    /// `template` with the placeholders replaced: `{project}`, `{task}`, `{category}`,
+    /// `{elapsed}` and `{started}`.
+    pub fn fill(&self, template: &str) -> String {
+        let started = self
+            .started
+            .map(|dt| dt.format("%H:%M").to_string())
+            .unwrap_or_default();
+        template
+            .replace("{project}", self.project.as_deref().unwrap_or_default())
+            .replace("{task}", self.task.as_deref().unwrap_or_default())
+            .replace("{category}", self.category.as_deref().unwrap_or_default())
+            .replace("{elapsed}", self.elapsed.as_deref().unwrap_or_default())
+            .replace("{started}", &started)
+    }
+
+    /// The status for Waybar, with `format` as the text when given. The text is empty while
+    /// nothing runs, which hides the module.
+    pub fn waybar(&self, format: Option<&str>) -> WaybarOutput {
+        if !self.running {
+            return WaybarOutput {
+                text: String::new(),
+                tooltip: "No task running".to_string(),
+                class: "idle",
+            };
+        }
+        let tooltip = match &self.category {
+            Some(_) => {
+                self.fill("{project} > {task} [{category}]\nRunning {elapsed} since {started}")
+            }
+            None => self.fill("{project} > {task}\nRunning {elapsed} since {started}"),
+        };
+        WaybarOutput {
+            text: self.fill(format.unwrap_or(WAYBAR_FORMAT)),
+            tooltip,
+            class: "running",
+        }
+    }
+
+    /// Fills in the elapsed time up to now.
+    fn with_elapsed(mut self) -> Self {
+        if let Some(started) = self.started {
+            let elapsed = Local::now().signed_duration_since(started);
+            self.elapsed_seconds = Some(elapsed.num_seconds().max(0));
+            self.elapsed = Some(crate::ui::format_duration(elapsed));
+        }
+        self
+    }
+}
+
+pub fn query_status(conn: &Connection) -> Result<StatusOutput> {
+    let running = db::query_running_tickr(conn)?;
+    let Some((tickr, interval)) = running.as_ref().and_then(|tickr| {
+        tickr
+            .intervals
+            .iter()
+            .find(|i| i.end_time.is_none())
+            .map(|interval| (tickr, interval))
+    }) else {
+        return Ok(StatusOutput {
+            running: false,
+            project: None,
+            task: None,
+            category: None,
+            started: None,
+            elapsed_seconds: None,
+            elapsed: None,
+        });
+    };
+
+    let project = db::query_project_by_id(tickr.project_id, conn)?
+        .map(|project| project.name)
+        .unwrap_or_else(|| "Unknown project".to_string());
+    let category = match tickr.category_id {
+        Some(id) => db::query_category_by_id(id, conn)?.map(|category| category.name),
+        None => None,
+    };
+    Ok(StatusOutput {
+        running: true,
+        project: Some(project),
+        task: Some(tickr.description.clone()),
+        category,
+        started: Some(interval.start_time),
+        elapsed_seconds: None,
+        elapsed: None,
+    }
+    .with_elapsed())
+}
+
+/// Cache file of the database at `db_path`, one per database so `--db` and profiles don't mix.
+fn cache_path(db_path: &Path) -> Option<PathBuf> {
+    let name: String = db_path
+        .to_string_lossy()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dirs::cache_dir().map(|dir| {
+        dir.join("tickr")
+            .join("status")
+            .join(format!("{name}.json"))
+    })
+}
+
+/// Rewrites the cache of the database behind `conn`. Called whenever the running task changes
+/// and periodically by the daemon; in-memory databases have no cache.
+pub fn write_cache(conn: &Connection) -> Result<()> {
+    let Some(db_path) = conn.path().filter(|path| !path.is_empty()) else {
+        return Ok(());
+    };
+    let Some(path) = cache_path(Path::new(db_path)) else {
+        return Ok(());
+    };
+    let mut status = query_status(conn)?;
+    status.elapsed_seconds = None;
+    status.elapsed = None;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write next to the cache and rename, so a poller never reads half a file.
+    let partial = path.with_extension("json.partial");
+    std::fs::write(&partial, serde_json::to_string(&status)?)?;
+    std::fs::rename(partial, path)?;
+    Ok(())
+}
+
+/// The cached status of the database at `db_path`, `None` until the cache has been written.
+pub fn read_cache(db_path: &str) -> Option<StatusOutput> {
+    let db_path = std::fs::canonicalize(db_path).ok()?;
+    let raw = std::fs::read_to_string(cache_path(&db_path)?).ok()?;
+    let status: StatusOutput = serde_json::from_str(&raw).ok()?;
+    Some(status.with_elapsed())
+}
@@ -0,0 +1,198 @@
+//! `tickr sync`: keeps two databases in step through `tickr serve` on one of them. Both sides
+//! count changes with a sync version; the client sends what changed here since its last push and
+//! applies what changed on the server since its last pull. Rows are identified by a uid that
+//! stays the same across machines, deletions travel as tombstones. Only projects, categories,
+//! tasks and intervals sync; rates, billing settings, goals, aliases, clients, templates,
+//! snapshots and locked weeks belong to each database.
+
+use anyhow::{Result, bail};
+use reqwest::header;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::db;
+use crate::progress::Progress;
+
+/// A whole history can take a while to send the first time.
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Rows changed after a sync version, complete up to `version`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChangeSet {
+    pub version: i64,
+    #[serde(default)]
+    pub projects: Vec<ProjectChange>,
+    #[serde(default)]
+    pub categories: Vec<CategoryChange>,
+    #[serde(default)]
+    pub tasks: Vec<TaskChange>,
+    #[serde(default)]
+    pub intervals: Vec<IntervalChange>,
+    #[serde(default)]
+    pub deletions: Vec<Deletion>,
+}
+
+impl ChangeSet {
+    pub fn len(&self) -> usize {
+        self.projects.len()
+            + self.categories.len()
+            + self.tasks.len()
+            + self.intervals.len()
+            + self.deletions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Parents and categories come with their names as well, for databases that created the same
+/// ones on their own.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProjectChange {
+    pub uid: String,
+    pub name: String,
+    pub parent_uid: Option<String>,
+    pub parent: Option<String>,
+    pub archived: bool,
+    pub created_at: String,
+    pub modified_at: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CategoryChange {
+    pub uid: String,
+    pub name: String,
+    pub color: String,
+    pub modified_at: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskChange {
+    pub uid: String,
+    pub project_uid: String,
+    pub project: String,
+    pub description: Option<String>,
+    pub category_uid: Option<String>,
+    pub category: Option<String>,
+    pub billable: bool,
+    pub done: bool,
+    pub blocked_by_uid: Option<String>,
+    pub notes: Option<String>,
+    pub estimate_minutes: Option<u32>,
+    pub archived: bool,
+    pub issue: Option<String>,
+    pub modified_at: String,
+}
+
+/// Times as stored, RFC 3339.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IntervalChange {
+    pub uid: String,
+    pub task_uid: String,
+    pub start: String,
+    pub end: Option<String>,
+    pub notes: Option<String>,
+    pub modified_at: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Deletion {
+    pub kind: SyncKind,
+    pub uid: String,
+    pub deleted_at: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncKind {
+    Project,
+    Category,
+    Task,
+    Interval,
+}
+
+impl SyncKind {
+    /// The kind as stored in `sync_tombstones`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "project" => Some(SyncKind::Project),
+            "category" => Some(SyncKind::Category),
+            "task" => Some(SyncKind::Task),
+            "interval" => Some(SyncKind::Interval),
+            _ => None,
+        }
+    }
+
+    pub fn table(self) -> &'static str {
+        match self {
+            SyncKind::Project => "projects",
+            SyncKind::Category => "categories",
+            SyncKind::Task => "entries",
+            SyncKind::Interval => "intervals",
+        }
+    }
+}
+
+/// Answer of `POST /sync`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushResult {
+    pub applied: usize,
+    pub version: i64,
+}
+
+/// Sends local changes to the `tickr serve` at `server` and applies the ones made there.
+pub fn run(server: &str, token: Option<&str>, conn: &Connection) -> Result<()> {
+    let server = server.trim_end_matches('/');
+    if !server.starts_with("http://") && !server.starts_with("https://") {
+        bail!("'{server}' is not a server address like http://host:8080");
+    }
+    let url = format!("{server}/sync");
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("tickr/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let authorized = |request: reqwest::blocking::RequestBuilder| match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    };
+
+    // Sending, receiving and applying, most of it spent waiting on the network.
+    let mut progress = Progress::new("Syncing", 3);
+    let (pushed, pulled) = db::query_sync_cursor(server, conn)?;
+    let outgoing = db::query_changes(pushed, conn)?;
+    let mut sent = 0;
+    if !outgoing.is_empty() {
+        let response = authorized(client.post(&url))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&outgoing)?)
+            .send()?;
+        let result: PushResult = serde_json::from_str(&read_body(response, server)?)?;
+        sent = result.applied;
+    }
+
+    progress.set(1);
+    let response = authorized(client.get(format!("{url}?since={pulled}"))).send()?;
+    let incoming: ChangeSet = serde_json::from_str(&read_body(response, server)?)?;
+    progress.set(2);
+    let received = db::apply_changes(&incoming, true, conn)?;
+    // Changes made here during the sync come after `outgoing.version` and go with the next push.
+    db::set_sync_cursor(server, outgoing.version, incoming.version, conn)?;
+    drop(progress);
+
+    println!("Synced with {server}: {sent} changes sent, {received} received");
+    Ok(())
+}
+
+fn read_body(response: reqwest::blocking::Response, server: &str) -> Result<String> {
+    let status = response.status();
+    let body = response.text()?;
+    if !status.is_success() {
+        let message = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|value| value.get("error")?.as_str().map(str::to_string))
+            .unwrap_or(body);
+        bail!("{server} responded with {status}: {message}");
+    }
+    Ok(body)
+}
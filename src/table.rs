@@ -0,0 +1,189 @@
+//! Aligned columns for the lists and reports the CLI prints. Colors only show on a terminal and
+//! follow the `NO_COLOR` convention, so output that is piped or redirected stays plain text.
+
+use std::io::IsTerminal;
+
+use unicode_width::UnicodeWidthStr;
+
+/// Space between two columns.
+const COLUMN_GAP: &str = "  ";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Paint {
+    #[default]
+    Plain,
+    Bold,
+    Dim,
+    Red,
+    Green,
+    Yellow,
+    Cyan,
+}
+
+impl Paint {
+    fn code(self) -> Option<&'static str> {
+        match self {
+            Paint::Plain => None,
+            Paint::Bold => Some("1"),
+            Paint::Dim => Some("2"),
+            Paint::Red => Some("31"),
+            Paint::Green => Some("32"),
+            Paint::Yellow => Some("33"),
+            Paint::Cyan => Some("36"),
+        }
+    }
+}
+
+/// Whether what goes to stdout may be colored.
+pub fn color_enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::io::stdout().is_terminal()
+}
+
+/// `text` in `paint`, unchanged without `color`.
+pub fn paint(text: &str, paint: Paint, color: bool) -> String {
+    match paint.code() {
+        Some(code) if color && !text.is_empty() => format!("\x1b[{code}m{text}\x1b[0m"),
+        _ => text.to_string(),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Cell {
+    text: String,
+    paint: Paint,
+}
+
+impl Cell {
+    pub fn paint(mut self, paint: Paint) -> Self {
+        self.paint = paint;
+        self
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            paint: Paint::Plain,
+        }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+/// Rows under a heading, each column as wide as its widest cell. Columns are left-aligned unless
+/// set with [`Table::right`].
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    headings: Vec<String>,
+    right: Vec<bool>,
+    rows: Vec<Vec<Cell>>,
+    /// Last row, set apart by a line and in bold.
+    total: Option<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new(headings: &[&str]) -> Self {
+        Self {
+            headings: headings.iter().map(|heading| heading.to_string()).collect(),
+            right: vec![false; headings.len()],
+            ..Self::default()
+        }
+    }
+
+    /// Aligns `columns` to the right, for numbers and durations.
+    pub fn right(mut self, columns: &[usize]) -> Self {
+        for column in columns {
+            if let Some(right) = self.right.get_mut(*column) {
+                *right = true;
+            }
+        }
+        self
+    }
+
+    /// Adds a row; missing cells stay empty and extra ones are dropped.
+    pub fn row<C: Into<Cell>>(&mut self, cells: impl IntoIterator<Item = C>) {
+        self.rows.push(self.fit(cells));
+    }
+
+    /// Sets the closing row, e.g. the sums of the columns.
+    pub fn total<C: Into<Cell>>(&mut self, cells: impl IntoIterator<Item = C>) {
+        self.total = Some(self.fit(cells));
+    }
+
+    fn fit<C: Into<Cell>>(&self, cells: impl IntoIterator<Item = C>) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = cells.into_iter().map(Into::into).collect();
+        cells.resize_with(self.headings.len(), Cell::default);
+        cells
+    }
+
+    /// The table's lines, colored when `color` is set.
+    pub fn render(&self, color: bool) -> Vec<String> {
+        let headings: Vec<Cell> = self
+            .headings
+            .iter()
+            .map(|heading| Cell::from(heading.as_str()).paint(Paint::Bold))
+            .collect();
+        let widths: Vec<usize> = (0..self.headings.len())
+            .map(|column| {
+                std::iter::once(&headings)
+                    .chain(&self.rows)
+                    .chain(&self.total)
+                    .map(|cells| cells[column].text.width())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let separator = paint(
+            &widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<_>>()
+                .join(COLUMN_GAP),
+            Paint::Dim,
+            color,
+        );
+        let mut lines = vec![self.line(&headings, &widths, color), separator.clone()];
+        lines.extend(self.rows.iter().map(|row| self.line(row, &widths, color)));
+        if let Some(total) = &self.total {
+            let total: Vec<Cell> = total
+                .iter()
+                .map(|cell| cell.clone().paint(Paint::Bold))
+                .collect();
+            lines.push(separator);
+            lines.push(self.line(&total, &widths, color));
+        }
+        lines
+    }
+
+    fn line(&self, cells: &[Cell], widths: &[usize], color: bool) -> String {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .zip(&self.right)
+            .map(|((cell, width), right)| {
+                // Padded by display width, as names may hold wide characters.
+                let padding = " ".repeat(width - cell.text.width());
+                let text = paint(&cell.text, cell.paint, color);
+                if *right {
+                    format!("{padding}{text}")
+                } else {
+                    format!("{text}{padding}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(COLUMN_GAP);
+        line.trim_end().to_string()
+    }
+
+    pub fn print(&self) {
+        for line in self.render(color_enabled()) {
+            println!("{line}");
+        }
+    }
+}
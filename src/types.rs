@@ -1,19 +1,36 @@
-use std::u32;
-
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Duration, Local, NaiveDate};
 
 pub type TickrId = u32;
 pub type ProjectId = u32;
 pub type CategoryId = u32;
 pub type IntervalId = u32;
+pub type ClientId = u32;
+pub type SnapshotId = u32;
+pub type TemplateId = u32;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Project {
     pub id: Option<ProjectId>,
     pub name: String,
     pub created_at: DateTime<Local>,
+    /// Hidden from lists unless archived items are shown.
+    pub archived: bool,
+    /// Project this one is a sub-project of.
+    pub parent_id: Option<ProjectId>,
+    /// Customer the project is done for.
+    pub client_id: Option<ClientId>,
+}
+
+/// Customer that projects are grouped under for reports and invoices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Client {
+    pub id: ClientId,
+    pub name: String,
+    /// Number of projects of the client.
+    pub projects: usize,
 }
 
+#[allow(dead_code)]
 pub(crate) enum ProjectQuery {
     All,
     ByName(String),
@@ -26,9 +43,23 @@ pub(crate) struct Tickr {
     pub project_id: ProjectId,
     pub description: String,
     pub category_id: Option<CategoryId>,
+    /// Whether time on this task can be billed to the client.
+    pub billable: bool,
+    pub done: bool,
+    /// Task that has to be done before this one can start.
+    pub blocked_by: Option<TickrId>,
+    /// Free-text notes, one captured note per line.
+    pub notes: Option<String>,
+    /// Expected effort in minutes.
+    pub estimate_minutes: Option<u32>,
+    /// Hidden from lists unless archived items are shown.
+    pub archived: bool,
+    /// Linked issue, a URL or an `owner/repo#123` reference.
+    pub issue: Option<String>,
     pub intervals: Vec<Interval>,
 }
 
+#[allow(dead_code)]
 pub(crate) enum TickrQuery {
     All,
     ByProject(String),
@@ -48,4 +79,163 @@ pub(crate) struct Interval {
     pub entry_id: TickrId,
     pub start_time: DateTime<Local>,
     pub end_time: Option<DateTime<Local>>,
+    /// What was done during this interval.
+    pub notes: Option<String>,
+}
+
+impl Interval {
+    /// Length up to `now` while it runs. Never negative, though the clock being set back while
+    /// it ran can leave the end before the start.
+    pub fn duration(&self, now: DateTime<Local>) -> Duration {
+        self.end_time
+            .unwrap_or(now)
+            .signed_duration_since(self.start_time)
+            .max(Duration::zero())
+    }
+
+    /// The part of the interval within `[from, to)`, running intervals up to `now`.
+    pub fn duration_within(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Duration {
+        let start = self.start_time.max(from);
+        let end = self.end_time.unwrap_or(now).min(to);
+        end.signed_duration_since(start).max(Duration::zero())
+    }
+
+    /// Whether it ends before it starts, e.g. after the clock was set back.
+    pub fn is_backwards(&self) -> bool {
+        self.end_time.is_some_and(|end| end < self.start_time)
+    }
+}
+
+/// Whether a project's goal is for every week or for all time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GoalPeriod {
+    /// The calendar week, starting on the configured `week_start`.
+    Week,
+    Total,
+}
+
+impl GoalPeriod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GoalPeriod::Week => "week",
+            GoalPeriod::Total => "total",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "week" => Some(GoalPeriod::Week),
+            "total" => Some(GoalPeriod::Total),
+            _ => None,
+        }
+    }
+}
+
+/// Time a project should get per week or in total.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ProjectGoal {
+    pub period: GoalPeriod,
+    pub minutes: u32,
+}
+
+/// An interval as stored, with its times unparsed, for `tickr doctor` to check.
+#[derive(Clone, Debug)]
+pub(crate) struct RawInterval {
+    pub id: IntervalId,
+    pub task: String,
+    pub project: String,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+}
+
+/// A change the TUI made, from the audit log.
+#[derive(Clone, Debug)]
+pub(crate) struct ActivityEntry {
+    pub at: DateTime<Local>,
+    pub message: String,
+}
+
+/// One interval of a day's agenda with the task, project and category it was tracked on.
+pub(crate) struct AgendaEntry {
+    pub interval_id: IntervalId,
+    pub tickr_id: TickrId,
+    pub task: String,
+    pub project: String,
+    pub category: Option<TickrCategory>,
+    pub start_time: DateTime<Local>,
+    /// `None` while the interval is running.
+    pub end_time: Option<DateTime<Local>>,
+    pub notes: Option<String>,
+}
+
+/// Task that `tickr template use` or the TUI creates in one step, e.g. "Daily standup".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TaskTemplate {
+    pub id: Option<TemplateId>,
+    /// Description of the tasks created from it.
+    pub name: String,
+    pub project_id: ProjectId,
+    /// Name of the project, filled in by queries.
+    pub project: String,
+    pub category_id: Option<CategoryId>,
+    pub billable: bool,
+    /// Create a task from it on its own every Monday to Friday.
+    pub every_workday: bool,
+    /// Day a task was last created from it on its own.
+    pub last_created: Option<NaiveDate>,
+}
+
+/// Totals of a range frozen under a name, see `tickr snapshot`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Snapshot {
+    pub id: SnapshotId,
+    pub name: String,
+    pub from: NaiveDate,
+    /// Last day, inclusive.
+    pub to: NaiveDate,
+    pub created_at: DateTime<Local>,
+    pub seconds: i64,
+}
+
+/// Time of one project and category on one day of a snapshot. Names are stored as they were,
+/// later renames don't change them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SnapshotRow {
+    pub day: NaiveDate,
+    pub project: String,
+    pub category: Option<String>,
+    pub seconds: i64,
+    pub billable_seconds: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ReportGrouping {
+    Project,
+    Client,
+    Category,
+    Task,
+}
+
+/// Aggregated tracked time for one group of a report.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ReportRow {
+    pub label: String,
+    /// The project of the group when grouped by project.
+    pub project_id: Option<ProjectId>,
+    pub seconds: i64,
+    /// Part of `seconds` tracked on billable tasks.
+    pub billable_seconds: i64,
+    /// `billable_seconds` after rounding each interval.
+    pub billed_seconds: i64,
+    /// Earnings of `billed_seconds` per currency, empty when no rate applies.
+    pub amounts: crate::billing::Amounts,
+    pub intervals: usize,
+    /// Intervals that end before they start, counted as no time.
+    pub backwards: usize,
+    pub tasks: usize,
 }
@@ -0,0 +1,185 @@
+use chrono::{DateTime, Duration, Local};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Cell, Paragraph, Row, Table},
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::helpers::{format_duration, hex_to_color, local_start_of_day};
+use super::theme::Theme;
+use super::{render_scrolling_table, right_cell, table_header};
+use crate::app::App;
+use crate::types::AgendaEntry;
+
+/// Below this width the category column is left out.
+const WIDE_LAYOUT_WIDTH: u16 = 72;
+const MAX_PROJECT_WIDTH: usize = 20;
+
+pub fn render_day_agenda(frame: &mut Frame, area: Rect, app: &mut App) {
+    if let Some(text) = agenda_placeholder(app) {
+        frame.render_widget(Paragraph::new(text), area);
+        return;
+    }
+    let mut state = std::mem::take(&mut app.agenda_table_state);
+    state.select(Some(app.selected_agenda_index));
+    render_scrolling_table(
+        frame,
+        area,
+        agenda_heading(app),
+        agenda_table(app, area.width >= WIDE_LAYOUT_WIDTH),
+        app.agenda.len(),
+        &mut state,
+    );
+    app.agenda_table_state = state;
+}
+
+fn agenda_placeholder(app: &App) -> Option<Text<'_>> {
+    if let Some(status) = &app.status {
+        return Some(Text::from(status.as_str()));
+    }
+    if app.agenda.is_empty() {
+        return Some(Text::from(format!(
+            "No time tracked on {}. Press '[' or ']' for another day.",
+            app.agenda_date.format("%a, %b %-d %Y")
+        )));
+    }
+    None
+}
+
+fn agenda_heading(app: &App) -> Vec<Line<'static>> {
+    let now = app.now();
+    let total: i64 = app
+        .agenda
+        .iter()
+        .map(|entry| seconds_on_day(app, entry, now))
+        .sum();
+    let count = app.agenda.len();
+    vec![
+        Line::from(vec![
+            Span::styled(
+                "  Total: ",
+                Style::default()
+                    .fg(Theme::secondary())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format_duration(Duration::seconds(total)),
+                Style::default()
+                    .fg(Theme::accent())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" in {count} interval{}", if count == 1 { "" } else { "s" }),
+                Style::default().fg(Theme::dim()),
+            ),
+        ]),
+        Line::from(""),
+    ]
+}
+
+fn agenda_table(app: &App, wide: bool) -> Table<'_> {
+    let now = app.now();
+    let project_width = app
+        .agenda
+        .iter()
+        .map(|entry| entry.project.width())
+        .max()
+        .unwrap_or(0)
+        .clamp("Project".len(), MAX_PROJECT_WIDTH) as u16;
+
+    let mut titles = vec![
+        Cell::from("Time"),
+        right_cell("Duration"),
+        Cell::from("Task"),
+        Cell::from("Project"),
+    ];
+    let mut widths = vec![
+        Constraint::Length(13),
+        Constraint::Length(8),
+        Constraint::Fill(1),
+        Constraint::Length(project_width),
+    ];
+    if wide {
+        titles.push(Cell::from("Category"));
+        widths.push(Constraint::Length(16));
+    }
+
+    let rows = app.agenda.iter().map(|entry| {
+        let end = match entry.end_time {
+            Some(end) => clock(app, end),
+            None => "now".to_string(),
+        };
+        let time = format!("{}-{end}", clock(app, entry.start_time));
+        let time_style = if entry.end_time.is_none() {
+            Style::default().fg(Theme::active())
+        } else {
+            Style::default().fg(Theme::dim())
+        };
+        let duration = format_duration(Duration::seconds(seconds_on_day(app, entry, now)));
+        let mut cells = vec![
+            Cell::from(Span::styled(time, time_style)),
+            right_cell(Span::styled(duration, Style::default().fg(Theme::accent()))),
+            task_cell(entry),
+            Cell::from(Span::styled(
+                entry.project.as_str(),
+                Style::default().fg(Theme::secondary()),
+            )),
+        ];
+        if wide {
+            cells.push(match &entry.category {
+                Some(category) => {
+                    let color = hex_to_color(&category.color).unwrap_or(Color::Magenta);
+                    Cell::from(Span::styled(
+                        category.name.as_str(),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ))
+                }
+                None => Cell::from(""),
+            });
+        }
+        Row::new(cells)
+    });
+    Table::new(rows, widths).header(table_header(titles))
+}
+
+/// The task with the first line of the interval's notes.
+fn task_cell(entry: &AgendaEntry) -> Cell<'_> {
+    let mut spans = vec![Span::raw(entry.task.as_str())];
+    if let Some(note) = entry
+        .notes
+        .as_deref()
+        .and_then(|notes| notes.lines().next())
+        .filter(|note| !note.is_empty())
+    {
+        spans.push(Span::styled(
+            format!(" - {note}"),
+            Style::default().fg(Theme::dim()),
+        ));
+    }
+    Cell::from(Line::from(spans))
+}
+
+/// `HH:MM`, or `<HH:MM`/`>HH:MM` for times before or after the agenda day.
+fn clock(app: &App, time: DateTime<Local>) -> String {
+    let date = time.date_naive();
+    let marker = if date < app.agenda_date {
+        "<"
+    } else if date > app.agenda_date {
+        ">"
+    } else {
+        ""
+    };
+    format!("{marker}{}", time.format("%H:%M"))
+}
+
+/// Part of the interval that falls on the agenda day, running intervals up to `now`.
+fn seconds_on_day(app: &App, entry: &AgendaEntry, now: DateTime<Local>) -> i64 {
+    let day_start = local_start_of_day(app.agenda_date);
+    let day_end = local_start_of_day(app.agenda_date + Duration::days(1));
+    let start = entry.start_time.max(day_start);
+    let end = entry.end_time.unwrap_or(now).min(day_end);
+    (end - start).num_seconds().max(0)
+}
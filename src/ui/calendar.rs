@@ -0,0 +1,114 @@
+use chrono::{Datelike, Duration, Local};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+};
+
+use super::helpers::format_duration;
+use super::theme::Theme;
+use super::view_model;
+use crate::app::App;
+
+/// Month grid with one cell per day, shaded by the time tracked that day relative to the
+/// busiest day of the month.
+pub fn build_calendar_text(app: &App) -> Text<'_> {
+    if let Some(status) = &app.status {
+        return Text::from(status.as_str());
+    }
+    let month = view_model::calendar_month(
+        app.calendar_date,
+        &app.calendar_days,
+        &app.config,
+        Local::now().date_naive(),
+    );
+
+    let mut lines = vec![Line::from(
+        month
+            .weekdays
+            .iter()
+            .map(|weekday| {
+                Span::styled(
+                    format!("  {weekday:<10}"),
+                    Style::default().fg(Theme::dim()),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )];
+
+    for week in &month.weeks {
+        let mut spans = Vec::new();
+        for day in week {
+            spans.push(Span::raw("  "));
+            let Some(day) = day else {
+                spans.push(Span::raw(" ".repeat(10)));
+                continue;
+            };
+            let duration = if day.seconds > 0 {
+                format!("{}:{:02}", day.seconds / 3600, day.seconds % 3600 / 60)
+            } else {
+                String::new()
+            };
+            let mut style = if day.selected {
+                Style::default()
+                    .fg(Theme::badge_text())
+                    .bg(Theme::highlight())
+                    .add_modifier(Modifier::BOLD)
+            } else if day.heat > 0 {
+                let (background, text) = Theme::heat(day.heat);
+                Style::default().fg(text).bg(background)
+            } else {
+                Style::default().fg(Theme::dim())
+            };
+            if day.today {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            spans.push(Span::styled(
+                format!(" {:>2} {duration:>5} ", day.date.day()),
+                style,
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let value = |seconds: i64| {
+        Span::styled(
+            format_duration(Duration::seconds(seconds)),
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(
+            format!("  {}  ", month.selected.format("%A, %B %-d")),
+            Style::default()
+                .fg(Theme::secondary())
+                .add_modifier(Modifier::BOLD),
+        ),
+        value(month.selected_seconds),
+    ]));
+    lines.push(Line::from(vec![
+        Span::styled(
+            format!("  {} total  ", month.first.format("%B")),
+            Style::default().fg(Theme::dim()),
+        ),
+        value(month.total_seconds),
+        Span::styled(
+            format!(" on {} of {} days", month.tracked_days, month.days_in_month),
+            Style::default().fg(Theme::dim()),
+        ),
+    ]));
+    let mut legend = vec![Span::styled("  Less ", Style::default().fg(Theme::dim()))];
+    for level in 1..=4 {
+        legend.push(Span::styled(
+            "  ",
+            Style::default().bg(Theme::heat(level).0),
+        ));
+        legend.push(Span::raw(" "));
+    }
+    legend.push(Span::styled("More", Style::default().fg(Theme::dim())));
+    lines.push(Line::from(legend));
+
+    Text::from(lines)
+}
@@ -42,7 +42,7 @@ pub fn build_categories_text(app: &App) -> Text<'_> {
 
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        "n: New category   esc: Back",
+        "n: New category   e: Edit   d: Delete   esc: Back",
         Style::default().fg(Theme::dim()),
     )));
 
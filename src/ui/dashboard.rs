@@ -1,18 +1,33 @@
-use chrono::{Duration, Local};
 use ratatui::{
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
 };
 
+use super::budget_span;
 use super::helpers::{clamp_name, format_duration, hex_to_color};
+use super::projects::goal_color;
+use super::relative::format_relative;
 use super::theme::Theme;
+use super::view_model;
 use crate::app::App;
+use crate::types::GoalPeriod;
 
 pub fn build_dashboard_text(app: &App) -> Text<'_> {
+    let data = view_model::DashboardData {
+        projects: &app.projects,
+        tree: super::projects::project_tree(app),
+        project_summaries: &app.project_summaries,
+        project_goals: &app.project_goals,
+        tickrs: &app.tickrs,
+        categories: &app.categories,
+        daily_budget: app.daily_budget(),
+    };
+    let dashboard = view_model::dashboard(data, app.now());
+    let date_format = app.config.date_format.as_str();
     let mut lines = Vec::new();
 
     // Welcome section
-    let now = Local::now();
+    let now = dashboard.now;
     lines.push(Line::from(Span::styled(
         format!("  Welcome to Tickr - {}", now.format("%A, %B %e, %Y")),
         Style::default()
@@ -33,45 +48,33 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
         Style::default().fg(Theme::dim()),
     )));
 
-    let mut running_found = false;
-    for tickr in &app.tickrs {
-        if let Some(interval) = tickr.intervals.iter().find(|i| i.end_time.is_none()) {
-            let project_name = app
-                .projects
-                .iter()
-                .find(|project| project.id == Some(tickr.project_id))
-                .map(|project| project.name.as_str())
-                .unwrap_or("Unknown");
-            let duration = format_duration(now.signed_duration_since(interval.start_time));
-
-            lines.push(Line::from(vec![
-                Span::styled(
-                    "  ● ",
-                    Style::default()
-                        .fg(Theme::active())
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(
-                    &tickr.description,
-                    Style::default()
-                        .fg(Theme::text())
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]));
-            lines.push(Line::from(vec![
-                Span::raw("    "),
-                Span::styled("Project: ", Style::default().fg(Theme::dim())),
-                Span::styled(project_name, Style::default().fg(Theme::highlight())),
-                Span::raw("  "),
-                Span::styled("Time: ", Style::default().fg(Theme::dim())),
-                Span::styled(duration, Style::default().fg(Theme::active())),
-            ]));
-            running_found = true;
-            break;
-        }
-    }
-
-    if !running_found {
+    if let Some(running) = &dashboard.running {
+        lines.push(Line::from(vec![
+            Span::styled(
+                "  ● ",
+                Style::default()
+                    .fg(Theme::active())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                running.task,
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+        lines.push(Line::from(vec![
+            Span::raw("    "),
+            Span::styled("Project: ", Style::default().fg(Theme::dim())),
+            Span::styled(running.project, Style::default().fg(Theme::highlight())),
+            Span::raw("  "),
+            Span::styled("Time: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                format_duration(running.elapsed),
+                Style::default().fg(Theme::active()),
+            ),
+        ]));
+    } else {
         lines.push(Line::from(Span::styled(
             "  No task currently running",
             Style::default().fg(Theme::dim()),
@@ -91,57 +94,42 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
         Style::default().fg(Theme::dim()),
     )));
 
-    let today_start = now
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_local_timezone(Local)
-        .unwrap();
-    let today_tickrs: Vec<_> = app
-        .tickrs
-        .iter()
-        .filter(|tickr| {
-            tickr
-                .intervals
-                .iter()
-                .any(|interval| interval.start_time >= today_start)
-        })
-        .collect();
-
-    let today_duration = today_tickrs
-        .iter()
-        .fold(Duration::seconds(0), |acc, tickr| {
-            let tickr_duration =
-                tickr
-                    .intervals
-                    .iter()
-                    .fold(Duration::seconds(0), |acc2, interval| {
-                        if interval.start_time >= today_start {
-                            let end_time = interval.end_time.unwrap_or(now);
-                            acc2 + end_time.signed_duration_since(interval.start_time)
-                        } else {
-                            acc2
-                        }
-                    });
-            acc + tickr_duration
-        });
-
-    let today_projects: std::collections::HashSet<_> =
-        today_tickrs.iter().map(|tickr| tickr.project_id).collect();
-
+    let today = &dashboard.today;
     lines.push(Line::from(vec![
         Span::styled("  Total time: ", Style::default().fg(Theme::dim())),
         Span::styled(
-            format_duration(today_duration),
+            format_duration(today.total()),
             Style::default()
                 .fg(Theme::accent())
                 .add_modifier(Modifier::BOLD),
         ),
     ]));
+    if let (Some(budget), Some(left)) = (today.budget, today.budget_left()) {
+        lines.push(Line::from(vec![
+            Span::styled("  Daily budget: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                format!("{}h", crate::goal::format_hours(budget.num_seconds())),
+                Style::default().fg(Theme::accent()),
+            ),
+            budget_span(left, "  "),
+        ]));
+    }
+    lines.push(Line::from(vec![
+        Span::styled("  Billable: ", Style::default().fg(Theme::dim())),
+        Span::styled(
+            format_duration(today.billable),
+            Style::default().fg(Theme::accent()),
+        ),
+        Span::styled("  Non-billable: ", Style::default().fg(Theme::dim())),
+        Span::styled(
+            format_duration(today.non_billable),
+            Style::default().fg(Theme::dim()),
+        ),
+    ]));
     lines.push(Line::from(vec![
         Span::styled("  Tasks worked: ", Style::default().fg(Theme::dim())),
         Span::styled(
-            format!("{}", today_tickrs.len()),
+            today.tasks.to_string(),
             Style::default()
                 .fg(Theme::success())
                 .add_modifier(Modifier::BOLD),
@@ -150,7 +138,7 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
     lines.push(Line::from(vec![
         Span::styled("  Projects: ", Style::default().fg(Theme::dim())),
         Span::styled(
-            format!("{}", today_projects.len()),
+            today.projects.to_string(),
             Style::default()
                 .fg(Theme::highlight())
                 .add_modifier(Modifier::BOLD),
@@ -158,6 +146,41 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
     ]));
     lines.push(Line::from(""));
 
+    if !dashboard.goals.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  Goals",
+            Style::default()
+                .fg(Theme::secondary())
+                .add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Line::from(Span::styled(
+            "  ──────",
+            Style::default().fg(Theme::dim()),
+        )));
+        for (project, progress) in &dashboard.goals {
+            let color = goal_color(progress.state);
+            let period = match progress.goal.period {
+                GoalPeriod::Week => "this week",
+                GoalPeriod::Total => "in total",
+            };
+            lines.push(Line::from(vec![
+                Span::styled("  • ", Style::default().fg(Theme::dim())),
+                Span::styled(clamp_name(project, 30), Style::default().fg(Theme::text())),
+                Span::raw(" "),
+                Span::styled(progress.bar(20), Style::default().fg(color)),
+                Span::styled(
+                    format!(" {} {period}", progress.hours()),
+                    Style::default().fg(Theme::accent()),
+                ),
+                Span::styled(
+                    format!("  {}", progress.state.label()),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+            ]));
+        }
+        lines.push(Line::from(""));
+    }
+
     // Quick Stats section
     lines.push(Line::from(Span::styled(
         "  Quick Stats",
@@ -173,7 +196,7 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
     lines.push(Line::from(vec![
         Span::styled("  Total Projects: ", Style::default().fg(Theme::dim())),
         Span::styled(
-            format!("{}", app.projects.len()),
+            dashboard.project_count.to_string(),
             Style::default()
                 .fg(Theme::text())
                 .add_modifier(Modifier::BOLD),
@@ -181,7 +204,7 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
         Span::raw("  "),
         Span::styled("Total Tasks: ", Style::default().fg(Theme::dim())),
         Span::styled(
-            format!("{}", app.tickrs.len()),
+            dashboard.task_count.to_string(),
             Style::default()
                 .fg(Theme::text())
                 .add_modifier(Modifier::BOLD),
@@ -189,7 +212,7 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
         Span::raw("  "),
         Span::styled("Categories: ", Style::default().fg(Theme::dim())),
         Span::styled(
-            format!("{}", app.categories.len()),
+            dashboard.category_count.to_string(),
             Style::default()
                 .fg(Theme::text())
                 .add_modifier(Modifier::BOLD),
@@ -209,24 +232,29 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
         Style::default().fg(Theme::dim()),
     )));
 
-    if app.projects.is_empty() {
+    if dashboard.recent_projects.is_empty() {
         lines.push(Line::from(Span::styled(
             "  No projects yet",
             Style::default().fg(Theme::dim()),
         )));
     } else {
-        let recent_projects = app.projects.iter().take(5);
-        for project in recent_projects {
-            let summary = app.project_summary_for(project);
-            let name = clamp_name(&project.name, 30);
-            let total = format_duration(Duration::seconds(summary.total_seconds.max(0)));
+        for project in &dashboard.recent_projects {
+            let name = clamp_name(project.name, 30);
+            let total = format_duration(project.total);
 
-            lines.push(Line::from(vec![
+            let mut spans = vec![
                 Span::styled("  • ", Style::default().fg(Theme::dim())),
                 Span::styled(name, Style::default().fg(Theme::text())),
                 Span::raw(" "),
                 Span::styled(format!("[{}]", total), Style::default().fg(Theme::accent())),
-            ]));
+            ];
+            if let Some(last_worked) = project.last_worked {
+                spans.push(Span::styled(
+                    format!("  {}", format_relative(last_worked, date_format)),
+                    Style::default().fg(Theme::dim()),
+                ));
+            }
+            lines.push(Line::from(spans));
         }
     }
     lines.push(Line::from(""));
@@ -243,26 +271,16 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
         Style::default().fg(Theme::dim()),
     )));
 
-    if app.tickrs.is_empty() {
+    if dashboard.recent_tasks.is_empty() {
         lines.push(Line::from(Span::styled(
             "  No tasks yet",
             Style::default().fg(Theme::dim()),
         )));
     } else {
-        let recent_tickrs = app.tickrs.iter().take(5);
-        for tickr in recent_tickrs {
-            let total_duration =
-                tickr
-                    .intervals
-                    .iter()
-                    .fold(Duration::seconds(0), |acc, interval| {
-                        let end_time = interval.end_time.unwrap_or(now);
-                        acc + end_time.signed_duration_since(interval.start_time)
-                    });
-
+        for task in &dashboard.recent_tasks {
             let mut spans = vec![Span::styled("  • ", Style::default().fg(Theme::dim()))];
 
-            if let Some(category) = app.category_for_tickr(tickr) {
+            if let Some(category) = task.category {
                 let cat_color = hex_to_color(&category.color).unwrap_or(Color::Magenta);
                 spans.push(Span::styled(
                     format!("[{}] ", category.name),
@@ -270,16 +288,22 @@ pub fn build_dashboard_text(app: &App) -> Text<'_> {
                 ));
             }
 
-            let description = clamp_name(&tickr.description, 35);
+            let description = clamp_name(task.description, 35);
             spans.push(Span::styled(
                 description,
                 Style::default().fg(Theme::text()),
             ));
             spans.push(Span::raw(" "));
             spans.push(Span::styled(
-                format!("[{}]", format_duration(total_duration)),
+                format!("[{}]", format_duration(task.total)),
                 Style::default().fg(Theme::accent()),
             ));
+            if let Some(last_worked) = task.last_worked {
+                spans.push(Span::styled(
+                    format!("  {}", format_relative(last_worked, date_format)),
+                    Style::default().fg(Theme::dim()),
+                ));
+            }
 
             lines.push(Line::from(spans));
         }
@@ -1,15 +1,58 @@
-use chrono::{Duration, Local};
+use chrono::Local;
 use ratatui::{
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
 };
 
 use super::theme::Theme;
-use crate::app::App;
+use crate::app::{App, format_estimate};
+use crate::types::Tickr;
 
 use super::helpers::{format_duration, hex_to_color};
+use super::relative::format_relative;
+use super::timeline::task_day_lines;
+use super::view_model::{self, TaskStatus};
 
-pub fn build_tickr_detail_text(app: &App) -> Text<'_> {
+/// Days shown in the time of day bars.
+const TIME_OF_DAY_DAYS: usize = 7;
+/// Intervals listed per page; `[` and `]` page through the rest.
+const INTERVALS_PER_PAGE: usize = 10;
+/// Months listed per page of the by month rollup.
+const MONTHS_PER_PAGE: usize = 12;
+
+/// Number of pages of the interval list of `tickr`, or of its by month rollup.
+pub fn detail_page_count(tickr: &Tickr, by_month: bool) -> usize {
+    let (rows, per_page) = if by_month {
+        (
+            view_model::month_totals(tickr, Local::now()).len(),
+            MONTHS_PER_PAGE,
+        )
+    } else {
+        (tickr.intervals.len(), INTERVALS_PER_PAGE)
+    };
+    rows.div_ceil(per_page).max(1)
+}
+
+/// Section heading, with the page shown when there is more than one.
+fn section_heading(title: String, page: usize, pages: usize) -> Line<'static> {
+    let mut spans = vec![Span::styled(
+        title,
+        Style::default()
+            .fg(Theme::accent())
+            .add_modifier(Modifier::BOLD),
+    )];
+    if pages > 1 {
+        spans.push(Span::styled(
+            format!("  page {} of {pages}", page + 1),
+            Style::default().fg(Theme::dim()),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Builds the detail body; `width` is the inner width of the content area and decides the
+/// resolution of the time of day bars.
+pub fn build_tickr_detail_text(app: &App, width: u16) -> Text<'_> {
     if let Some(status) = &app.status {
         return Text::from(status.as_str());
     }
@@ -17,7 +60,7 @@ pub fn build_tickr_detail_text(app: &App) -> Text<'_> {
         return Text::from("No task selected.");
     };
 
-    const LABEL_WIDTH: usize = 11;
+    const LABEL_WIDTH: usize = 12;
     let label_style = Style::default().fg(Theme::dim());
     let label = |name: &str| {
         let label_text = format!("{name}:");
@@ -45,43 +88,26 @@ pub fn build_tickr_detail_text(app: &App) -> Text<'_> {
         Line::from(vec![label("Category"), value("none")])
     };
 
-    let first_start = tickr
-        .intervals
-        .first()
-        .map(|i| i.start_time.format("%Y-%m-%d %H:%M").to_string())
+    let now = app.now();
+    let detail = view_model::task_detail(tickr, now);
+    let first_start = detail
+        .first_start
+        .map(|start| app.config.format_moment(start))
         .unwrap_or_else(|| "pending".to_string());
-    let last_end = tickr
-        .intervals
-        .last()
-        .and_then(|i| i.end_time)
-        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string());
-
-    let status = if tickr.intervals.is_empty() {
-        "Not started"
-    } else if tickr.intervals.last().unwrap().end_time.is_none() {
-        "Running"
-    } else {
-        "Ended"
-    };
-    let status_color = match status {
-        "Running" => Theme::active(),
-        "Ended" => Theme::ended(),
-        _ => Theme::warn(),
-    };
-
-    let now = Local::now();
-    let total_duration = tickr
-        .intervals
-        .iter()
-        .fold(Duration::seconds(0), |acc, interval| {
-            let end_time = interval.end_time.unwrap_or(now);
-            acc + end_time.signed_duration_since(interval.start_time)
-        });
-    let elapsed = if tickr.intervals.is_empty() {
-        "--:--:--".to_string()
-    } else {
-        format_duration(total_duration)
+    let last_end = detail.last_end.map(|end| app.config.format_moment(end));
+    let last_worked_ago = detail
+        .last_end
+        .map(|end| format!(" ({})", format_relative(end, &app.config.date_format)))
+        .unwrap_or_default();
+    let status_color = match detail.status {
+        TaskStatus::Running => Theme::active(),
+        TaskStatus::Done => Theme::success(),
+        TaskStatus::Ended => Theme::ended(),
+        TaskStatus::NotStarted => Theme::warn(),
     };
+    let elapsed = detail
+        .total
+        .map_or_else(|| "--:--:--".to_string(), format_duration);
 
     let mut lines = vec![
         Line::from(vec![
@@ -100,65 +126,163 @@ pub fn build_tickr_detail_text(app: &App) -> Text<'_> {
         Line::from("----------------------------------------"),
         Line::from(vec![label("Project"), value(project)]),
         category_line,
+        Line::from(vec![
+            label("Billable"),
+            value(if tickr.billable { "yes" } else { "no" }),
+        ]),
+        Line::from(vec![
+            label("Blocked by"),
+            match (&app.selected_tickr_blocker, tickr.blocked_by) {
+                (Some(blocker), _) => Span::styled(
+                    blocker.as_str(),
+                    Style::default()
+                        .fg(Theme::warn())
+                        .add_modifier(Modifier::BOLD),
+                ),
+                (None, Some(id)) => {
+                    Span::styled(format!("task #{id}"), Style::default().fg(Theme::warn()))
+                }
+                (None, None) => value("nothing"),
+            },
+        ]),
         Line::from(vec![
             label("Status"),
             Span::styled(
-                status,
+                detail.status.label(),
                 Style::default()
                     .fg(status_color)
                     .add_modifier(Modifier::BOLD),
             ),
+            Span::styled(
+                if tickr.archived { ", archived" } else { "" },
+                Style::default().fg(Theme::dim()),
+            ),
         ]),
         Line::from(vec![label("First"), value(&first_start)]),
         Line::from(vec![
             label("Last"),
             value(&last_end.clone().unwrap_or_else(|| "open".to_string())),
+            Span::styled(last_worked_ago, label_style),
         ]),
         Line::from(vec![label("Elapsed"), value(&elapsed)]),
+        Line::from(vec![
+            label("Estimate"),
+            match tickr.estimate_minutes {
+                Some(minutes) => value(&format_estimate(minutes)),
+                None => value("none"),
+            },
+        ]),
+        Line::from(vec![
+            label("Issue"),
+            match &tickr.issue {
+                Some(issue) => Span::styled(
+                    issue.as_str(),
+                    Style::default()
+                        .fg(Theme::accent())
+                        .add_modifier(Modifier::UNDERLINED),
+                ),
+                None => value("none"),
+            },
+        ]),
         Line::from(""),
-        Line::from(vec![Span::styled(
-            format!("Intervals ({})", tickr.intervals.len()),
-            Style::default()
-                .fg(Theme::accent())
-                .add_modifier(Modifier::BOLD),
-        )]),
     ];
 
-    if tickr.intervals.is_empty() {
-        lines.push(Line::from(vec![Span::styled("  none", label_style)]));
-    } else {
-        for (index, interval) in tickr.intervals.iter().enumerate() {
-            let start = interval.start_time.format("%Y-%m-%d %H:%M").to_string();
-            let (end, duration) = if let Some(end_time) = interval.end_time {
-                let end = end_time.format("%Y-%m-%d %H:%M").to_string();
-                let duration = format_duration(end_time.signed_duration_since(interval.start_time));
-                (end, duration)
+    // Page 0 holds the newest rows, so a long-lived task opens on its latest work.
+    let pages = detail_page_count(tickr, app.detail_by_month);
+    let page = app.detail_page.min(pages - 1);
+    if app.detail_by_month {
+        let months = view_model::month_totals(tickr, now);
+        lines.push(section_heading(
+            format!("By month ({})", months.len()),
+            page,
+            pages,
+        ));
+        let shown = months
+            .iter()
+            .skip(page * MONTHS_PER_PAGE)
+            .take(MONTHS_PER_PAGE);
+        for total in shown {
+            let intervals = if total.intervals == 1 {
+                "1 interval".to_string()
             } else {
-                let end = "open".to_string();
-                let duration = format_duration(now.signed_duration_since(interval.start_time));
-                (end, duration)
+                format!("{} intervals", total.intervals)
             };
-            if tickr.intervals.len() > 5 {
-                if index < 2 || index >= tickr.intervals.len() - 2 {
-                    lines.push(Line::from(vec![
-                        Span::raw(format!("  {:>2}) {start} -> {end} ", index + 1)),
-                        Span::styled(format!("({duration})"), Style::default().fg(Theme::dim())),
-                    ]));
-                } else if index == 2 {
-                    lines.push(Line::from(vec![Span::raw("     ...")]));
-                }
-            } else {
-                lines.push(Line::from(vec![
-                    Span::raw(format!("  {:>2}) {start} -> {end} ", index + 1)),
-                    Span::styled(format!("({duration})"), Style::default().fg(Theme::dim())),
-                ]));
+            lines.push(Line::from(vec![
+                Span::raw(format!(
+                    "  {}  {}  ",
+                    total.month.format("%Y-%m"),
+                    format_duration(total.duration)
+                )),
+                Span::styled(intervals, Style::default().fg(Theme::dim())),
+            ]));
+        }
+    } else {
+        lines.push(section_heading(
+            format!("Intervals ({})", tickr.intervals.len()),
+            page,
+            pages,
+        ));
+    }
+
+    if tickr.intervals.is_empty() {
+        lines.push(Line::from(vec![Span::styled("  none", label_style)]));
+    } else if !app.detail_by_month {
+        // Oldest first within the page, like the whole list reads.
+        let shown = view_model::newest_first_page(tickr.intervals.len(), page, INTERVALS_PER_PAGE);
+        for (index, interval) in tickr
+            .intervals
+            .iter()
+            .enumerate()
+            .take(shown.end)
+            .skip(shown.start)
+        {
+            let times = app
+                .config
+                .format_interval(interval.start_time, interval.end_time);
+            let duration = format_duration(interval.duration(now));
+            let mut spans = vec![
+                Span::raw(format!("  {:>2}) {times} ", index + 1)),
+                Span::styled(format!("({duration})"), Style::default().fg(Theme::dim())),
+            ];
+            if interval.is_backwards() {
+                spans.push(Span::styled(
+                    "  ends before it starts, counted as 0",
+                    Style::default().fg(Theme::warn()),
+                ));
+            }
+            lines.push(Line::from(spans));
+            for note in interval.notes.iter().flat_map(|notes| notes.lines()) {
+                lines.push(Line::from(Span::styled(
+                    format!("      {note}"),
+                    Style::default().fg(Theme::secondary()),
+                )));
             }
         }
     }
 
-    lines.push(Line::from(""));
-    lines.push(Line::from(
-        "space: Start/End   s: Stop running   g: Project   e: Edit   d: Delete   esc: Back",
-    ));
+    if !tickr.intervals.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![Span::styled(
+            "Time of day",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )]));
+        lines.extend(task_day_lines(tickr, width, TIME_OF_DAY_DAYS));
+    }
+
+    if let Some(notes) = tickr.notes.as_deref().filter(|notes| !notes.is_empty()) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![Span::styled(
+            "Notes",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )]));
+        for note in notes.lines() {
+            lines.push(Line::from(format!("  {note}")));
+        }
+    }
+
     Text::from(lines)
 }
@@ -24,49 +24,118 @@ pub fn build_help_text(_app: &App) -> Text<'_> {
         "Tab: Toggle focus (tab bar / content)",
         "Left/Right: Navigate tabs (tab bar focus)",
         "Enter: Activate tab (tab bar focus)",
-        "h/p/t/w/l/c: Quick nav",
+        "h/p/t/w/l/m/c: Quick nav",
+        "S: Stats with this week's bars and the month forecast",
         "r: Refresh current view",
+        "L: Category color legend",
+        "H: Activity log of what this session changed",
+        "W: Switch profiles",
+        "N: Quick capture (note on the running task or Inbox task)",
+        "I: Triage the Inbox (project, category, estimate)",
+        "U: Create and start a task from a template",
         "esc: Back",
     ]));
 
     lines.push(Line::from(""));
     lines.push(section_title("Lists"));
-    lines.extend(section_lines(&[
-        "Up/Down: Move selection",
-        "Enter: Open",
-    ]));
+    lines.extend(section_lines(&["Up/Down: Move selection", "Enter: Open"]));
 
     lines.push(Line::from(""));
     lines.push(section_title("Projects"));
-    lines.extend(section_lines(&["/: Search projects"]));
+    lines.extend(section_lines(&[
+        "/: Search projects",
+        "Left/Right: Collapse/expand sub-projects (projects/worked)",
+        "-/+: Collapse/expand all sub-projects (projects/worked)",
+    ]));
 
     lines.push(Line::from(""));
     lines.push(section_title("Tickrs"));
     lines.extend(section_lines(&[
         "space: Start/End task",
         "s: Stop running task",
+        "R: Resume the task stopped last",
+        "Ctrl+p: Switch to a recent task, filtered as you type",
+        "o: Sort by recent, duration, name or project",
+        "f: Show tasks worked on today, this week or all (Tickrs tab)",
+        "/: Search the names and notes of the open project's tasks, f: Filter them by category",
+        "A: Archive/unarchive the selected project or task",
+        "V: Show/hide archived projects and tasks",
         "g: Go to project (detail)",
         "e: Edit task (detail)",
+        "a: Add a past interval (detail)",
+        "E: Edit the notes of the task and its intervals (detail)",
+        "b: Toggle billable (detail)",
+        "x: Mark done / not done (detail)",
+        "B: Set the task it is blocked by (detail)",
+        "O: Open the linked issue in the browser (detail)",
+        "[/]: Older/newer intervals (detail)",
+        "Left/Right: Switch between the intervals and their totals per month (detail)",
         "d: Delete task",
     ]));
 
+    lines.push(Line::from(""));
+    lines.push(section_title("Pomodoro"));
+    lines.extend(section_lines(&[
+        "P: Start a pomodoro on the running task",
+        ">: Skip to the break / next work block",
+        "X: Cancel the pomodoro",
+    ]));
+
     lines.push(Line::from(""));
     lines.push(section_title("Create"));
     lines.extend(section_lines(&[
         "n: New task (projects/tickrs) or new category (categories)",
+        "G: New task named after the git branch, in the repository's project",
+        "T: Set the hours the selected project should get a week or in total (projects)",
+        "C: Set the client of the selected project (projects)",
+        "e/d: Edit/delete the selected category (categories)",
     ]));
 
     lines.push(Line::from(""));
     lines.push(section_title("Worked/Timeline"));
-    lines.extend(section_lines(&["Shift+Tab: Toggle day/week range"]));
+    lines.extend(section_lines(&[
+        "[/]: Range: today, yesterday, this/last week, this month or custom",
+        "g: Custom range, one date to show a single day",
+        "Left/Right: Previous/next range of the same length (timeline)",
+        "Up/Down: Select a day of the range (timeline)",
+        "Enter: Intervals of the selected day (timeline)",
+        "D: Intervals of the day (today in worked, selected day in calendar)",
+    ]));
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Day"));
+    lines.extend(section_lines(&[
+        "Up/Down: Select interval",
+        "[/]: Previous/next day",
+        "Enter: Open the task",
+        "d: Delete the selected interval",
+    ]));
+
+    lines.push(Line::from(""));
+    lines.push(section_title("Calendar"));
+    lines.extend(section_lines(&[
+        "m: Month heatmap of tracked time",
+        "Arrows: Select day",
+        "[/]: Previous/next month",
+        "Enter: Tasks worked on the selected day",
+    ]));
 
     lines.push(Line::from(""));
     lines.push(section_title("Popups"));
     lines.extend(section_lines(&[
         "Edit task: Up/Down change category, Enter save, Esc cancel",
-        "New category: Tab switch field, Enter save, Esc cancel",
+        "New/edit category: Tab switch field, Enter save, Esc cancel",
+        "Delete category: Up/Down pick where its tasks go, Enter/Y confirm, Esc/N cancel",
         "New task: Tab switch field, Up/Down select, Space toggle start, Enter save, Esc cancel",
         "Delete task: Enter/Y confirm, Esc/N cancel",
+        "Confirm (see [confirm] in the config): Enter/Y yes, Esc/N cancel",
+        "Recent tasks: type to filter, Up/Down select, Enter switch, Esc close",
+        "Templates: Up/Down select, Enter create and start, Esc close",
+        "Triage: Tab switch field, Up/Down change, Enter move, Left/Right skip, Del delete, Esc close",
+        "Add interval: Tab switch field, Enter save, Esc cancel",
+        "Notes: Enter new line, Tab switch between task and intervals, Esc save and close",
+        "Pomodoro: Enter continue, Esc close or end pomodoro",
+        "Idle: k keep, d discard, a assign to another task, Esc keep",
     ]));
 
     Text::from(lines)
@@ -1,5 +1,10 @@
-use chrono::Duration;
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+};
 use ratatui::style::Color;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::types::Tickr;
 
 pub fn format_duration(duration: Duration) -> String {
     let total_seconds = duration.num_seconds().max(0);
@@ -9,16 +14,56 @@ pub fn format_duration(duration: Duration) -> String {
     format!("{hours:02}:{minutes:02}:{seconds:02}")
 }
 
+/// `value` padded or cut to exactly `width` terminal cells, ending in an ellipsis when cut. Wide
+/// characters such as CJK and most emoji take two cells.
 pub fn clamp_name(value: &str, width: usize) -> String {
-    let value_len = value.chars().count();
-    if value_len <= width {
-        return format!("{value:<width$}", width = width);
+    let value_width = value.width();
+    if value_width <= width {
+        return format!("{value}{}", " ".repeat(width - value_width));
+    }
+    if width == 0 {
+        return String::new();
     }
-    let trimmed = value
-        .chars()
-        .take(width.saturating_sub(2))
-        .collect::<String>();
-    format!("{trimmed}..")
+    let mut trimmed = String::new();
+    let mut used = 0;
+    for c in value.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > width - 1 {
+            break;
+        }
+        trimmed.push(c);
+        used += char_width;
+    }
+    // A wide character that did not fit leaves one cell to pad.
+    format!("{trimmed}…{}", " ".repeat(width - 1 - used))
+}
+
+/// End of the latest interval of `tickr`, now while one runs.
+pub fn last_worked(tickr: &Tickr) -> Option<DateTime<Local>> {
+    let now = Local::now();
+    tickr
+        .intervals
+        .iter()
+        .map(|interval| interval.end_time.unwrap_or(now))
+        .max()
+}
+
+/// ASCII replacement for box drawing and decorative symbols, used by plain output mode.
+pub fn ascii_fallback(symbol: &str) -> Option<&'static str> {
+    let ascii = match symbol {
+        "─" | "━" | "═" | "╌" => "-",
+        "│" | "┃" | "║" => "|",
+        "┌" | "┐" | "└" | "┘" | "╭" | "╮" | "╰" | "╯" | "┏" | "┓" | "┗" | "┛" | "╔" | "╗" | "╚"
+        | "╝" | "├" | "┤" | "┬" | "┴" | "┼" => "+",
+        "•" | "●" => "*",
+        "█" | "▇" | "▆" | "▅" | "▄" | "▃" | "▂" | "▁" | "■" => "#",
+        "▾" => "v",
+        "←" => "<",
+        "→" | "▸" => ">",
+        "…" => ".",
+        _ => return None,
+    };
+    Some(ascii)
 }
 
 pub fn hex_to_color(value: &str) -> Option<Color> {
@@ -31,3 +76,38 @@ pub fn hex_to_color(value: &str) -> Option<Color> {
     let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
     Some(Color::Rgb(r, g, b))
 }
+
+/// Number of days in the month of `date`.
+pub fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).unwrap_or(date);
+    first
+        .checked_add_months(Months::new(1))
+        .map(|next| (next - first).num_days() as u32)
+        .unwrap_or(31)
+}
+
+/// Midnight of `date` in the local timezone, resolving DST gaps and overlaps.
+pub fn local_start_of_day(date: NaiveDate) -> DateTime<Local> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("valid time");
+    // Where the clocks skip midnight, the day starts at the first minute after the gap.
+    (0..24 * 60)
+        .map(|minutes| midnight + chrono::Duration::minutes(minutes))
+        .find_map(|naive| Local.from_local_datetime(&naive).earliest())
+        .unwrap_or_else(|| Local.from_utc_datetime(&midnight))
+}
+
+/// Parses user input as a local datetime: RFC 3339, "YYYY-MM-DD HH:MM", or "HH:MM" on `date`.
+pub fn parse_local_datetime(value: &str, date: NaiveDate) -> Option<DateTime<Local>> {
+    let value = value.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.with_timezone(&Local));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            NaiveTime::parse_from_str(value, "%H:%M")
+                .ok()
+                .map(|time| date.and_time(time))
+        })?;
+    Local.from_local_datetime(&naive).earliest()
+}
@@ -1,46 +1,51 @@
+mod agenda;
+mod calendar;
 mod categories;
 mod dashboard;
 mod detail;
 mod help;
 mod helpers;
 mod projects;
+mod relative;
+mod stats;
 mod theme;
-mod timeline;
 mod tickrs;
+mod timeline;
+mod view_model;
 
 use chrono::Local;
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     prelude::Alignment,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    widgets::{
+        Block, BorderType, Borders, Cell, Clear, HighlightSpacing, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Table, TableState,
+    },
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::{
+    Action, ActivePopup, App, AppView, RangeField, RangePreset, RangeSelector, key_label,
 };
+pub(crate) use theme::Theme;
 
-use crate::app::{App, AppView};
-use theme::Theme;
+pub(crate) use detail::detail_page_count;
+use helpers::{ascii_fallback, hex_to_color};
+pub(crate) use helpers::{
+    clamp_name, days_in_month, format_duration, last_worked, local_start_of_day,
+    parse_local_datetime,
+};
+pub(crate) use relative::format_relative;
 
-use helpers::{format_duration, hex_to_color};
+/// Cells of the header strip showing today's time per category.
+const CATEGORY_STRIP_WIDTH: usize = 20;
 
 /// Renders the entire UI for a single frame.
-pub fn draw(frame: &mut Frame, app: &App) {
+pub fn draw(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
-    let (title, body_text) = match app.view {
-        AppView::Dashboard => (" Dashboard ", dashboard::build_dashboard_text(app)),
-        AppView::Projects => (" Projects ", projects::build_projects_text(app)),
-        AppView::Tickrs => (" Tickrs ", tickrs::build_tickrs_text(app, true)),
-        AppView::ProjectTickrs => (
-            projects::build_project_tickr_title(app),
-            tickrs::build_tickrs_text(app, true),
-        ),
-        AppView::WorkedProjects => (" Worked ", projects::build_worked_projects_text(app)),
-        AppView::Timeline => (" Timeline ", timeline::build_timeline_text(app)),
-        AppView::Categories => (" Categories ", categories::build_categories_text(app)),
-        AppView::TickrDetail => (" Task ", detail::build_tickr_detail_text(app)),
-        AppView::Help => (" Help ", help::build_help_text(app)),
-    };
-
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -50,10 +55,12 @@ pub fn draw(frame: &mut Frame, app: &App) {
         ])
         .split(area);
 
-    let header_lines = vec![Line::from(vec![
+    let mut header_spans = vec![
         Span::styled(
             "  Tickr  ",
-            Style::default().fg(Color::Black).bg(Theme::primary()),
+            Style::default()
+                .fg(Theme::badge_text())
+                .bg(Theme::primary()),
         ),
         Span::raw(" "),
         Span::styled(
@@ -62,45 +69,171 @@ pub fn draw(frame: &mut Frame, app: &App) {
                 .fg(Theme::secondary())
                 .add_modifier(Modifier::BOLD),
         ),
-    ])];
-    let header = Paragraph::new(Text::from(header_lines))
-        .alignment(Alignment::Left)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .style(Style::default().fg(Theme::secondary())),
-        );
-    frame.render_widget(header, layout[0]);
+    ];
+    if let Some(profile) = &app.profile {
+        header_spans.push(Span::raw("   "));
+        header_spans.push(Span::styled(
+            format!(" {profile} "),
+            Style::default().fg(Theme::badge_text()).bg(Theme::accent()),
+        ));
+    }
+    if app.read_only {
+        header_spans.push(Span::raw("   "));
+        header_spans.push(Span::styled(
+            " READ-ONLY ",
+            Style::default().fg(Theme::badge_text()).bg(Theme::warn()),
+        ));
+        header_spans.push(Span::styled(
+            " the database can't be written, changes are disabled",
+            Style::default().fg(Theme::warn()),
+        ));
+    }
+    if app.data_problems > 0 {
+        header_spans.push(Span::raw("   "));
+        header_spans.push(Span::styled(
+            format!(
+                " {} DATA PROBLEM{} ",
+                app.data_problems,
+                if app.data_problems == 1 { "" } else { "S" }
+            ),
+            Style::default().fg(Theme::badge_text()).bg(Theme::warn()),
+        ));
+        header_spans.push(Span::styled(
+            " run `tickr doctor` to repair",
+            Style::default().fg(Theme::warn()),
+        ));
+    }
+    if let Some(since) = app.reminder {
+        header_spans.push(Span::styled(
+            format!(
+                "   Nothing tracked since {}, start a task?",
+                since.format("%H:%M")
+            ),
+            Style::default().fg(Theme::warn()),
+        ));
+    }
+    let header_block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Theme::secondary()));
+    let header_area = header_block.inner(layout[0]);
+    frame.render_widget(header_block, layout[0]);
+    let header_line = Line::from(header_spans);
+    let strip = category_strip(app)
+        .filter(|strip| header_line.width() + strip.width() + 2 <= header_area.width as usize);
+    let header_columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(strip.as_ref().map_or(0, |strip| strip.width() as u16)),
+        ])
+        .split(header_area);
+    frame.render_widget(
+        Paragraph::new(header_line).alignment(Alignment::Left),
+        header_columns[0],
+    );
+    if let Some(strip) = strip {
+        frame.render_widget(Paragraph::new(strip), header_columns[1]);
+    }
+
+    // The body takes arrow keys while its content is focused: a heavy accent border then, a dim
+    // one while the tab bar is focused. The border type tells them apart without colors too.
+    let (body_border, body_border_color) = match app.focus_mode {
+        crate::app::FocusMode::Content => (BorderType::Thick, Theme::accent()),
+        crate::app::FocusMode::TabBar => (BorderType::Rounded, Theme::dim()),
+    };
+    let body_block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(body_border)
+        .border_style(Style::default().fg(body_border_color))
+        .style(Style::default().fg(Theme::secondary()));
+    let body_area = body_block.inner(layout[1]);
+    frame.render_widget(body_block, layout[1]);
 
-    let mut body_lines = vec![
-        tabs_line(app),
+    let mut footer_lines = vec![
         Line::from(""),
         Line::from(Span::styled(
-            format!("  {title}"),
-            Style::default()
-                .fg(Theme::accent())
-                .add_modifier(Modifier::BOLD),
+            "----------------------------------------",
+            Style::default().fg(Theme::dim()),
         )),
-        Line::from(""),
     ];
-    body_lines.extend(body_text.lines);
-    body_lines.push(Line::from(""));
-    body_lines.push(Line::from(Span::styled(
-        "----------------------------------------",
-        Style::default().fg(Theme::dim()),
-    )));
-    body_lines.extend(keybinds_lines(app));
-    let body = Paragraph::new(Text::from(body_lines))
-        .style(Style::default().fg(Theme::text()))
-        .alignment(Alignment::Left)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .style(Style::default().fg(Theme::secondary())),
-        );
-    frame.render_widget(body, layout[1]);
+    footer_lines.extend(keybinds_lines(app));
+    let body_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Min(1),
+            Constraint::Length(footer_lines.len() as u16),
+        ])
+        .split(body_area);
+
+    let title = match app.view {
+        AppView::Dashboard => " Dashboard ",
+        AppView::Projects => " Projects ",
+        AppView::Tickrs => " Tickrs ",
+        AppView::ProjectTickrs => projects::build_project_tickr_title(app),
+        AppView::WorkedProjects => " Worked ",
+        AppView::Timeline => " Timeline ",
+        AppView::Calendar => " Calendar ",
+        AppView::DayAgenda => " Day ",
+        AppView::Categories => " Categories ",
+        AppView::Stats => " Stats ",
+        AppView::TickrDetail => " Task ",
+        AppView::Help => " Help ",
+    };
+    let mut title_spans = vec![Span::styled(
+        format!("  {title}"),
+        Style::default()
+            .fg(Theme::accent())
+            .add_modifier(Modifier::BOLD),
+    )];
+    let list_state = match app.view {
+        AppView::Tickrs => Some(format!(
+            "  {}, sorted by {}",
+            app.tickr_filter.label(),
+            app.tickr_sort.label()
+        )),
+        AppView::ProjectTickrs if app.project_tickrs_ranked() => {
+            Some("  sorted by relevance".to_string())
+        }
+        AppView::ProjectTickrs => Some(format!("  sorted by {}", app.tickr_sort.label())),
+        AppView::Calendar => Some(format!("  {}", app.calendar_date.format("%B %Y"))),
+        AppView::DayAgenda => Some(format!("  {}", app.agenda_date.format("%A, %B %-d %Y"))),
+        _ => None,
+    };
+    if let Some(list_state) = list_state {
+        title_spans.push(Span::styled(list_state, Style::default().fg(Theme::dim())));
+    }
+    let heading_lines = vec![tabs_line(app), Line::from(""), Line::from(title_spans)];
+    render_text(frame, body_layout[0], Text::from(heading_lines));
+
+    let content_area = body_layout[1];
+    match app.view {
+        AppView::Projects => projects::render_projects(frame, content_area, app),
+        AppView::Tickrs | AppView::ProjectTickrs => tickrs::render_tickrs(frame, content_area, app),
+        AppView::WorkedProjects => projects::render_worked_projects(frame, content_area, app),
+        AppView::Dashboard => {
+            render_text(frame, content_area, dashboard::build_dashboard_text(app))
+        }
+        AppView::Timeline => render_text(
+            frame,
+            content_area,
+            timeline::build_timeline_text(app, content_area.width),
+        ),
+        AppView::Calendar => render_text(frame, content_area, calendar::build_calendar_text(app)),
+        AppView::DayAgenda => agenda::render_day_agenda(frame, content_area, app),
+        AppView::Categories => {
+            render_text(frame, content_area, categories::build_categories_text(app))
+        }
+        AppView::Stats => stats::render_stats(frame, content_area, app),
+        AppView::TickrDetail => render_text(
+            frame,
+            content_area,
+            detail::build_tickr_detail_text(app, content_area.width),
+        ),
+        AppView::Help => render_text(frame, content_area, help::build_help_text(app)),
+    }
+    render_text(frame, body_layout[2], Text::from(footer_lines));
 
     let footer = Paragraph::new(Text::from(running_task_line(app)))
         .alignment(Alignment::Left)
@@ -112,49 +245,186 @@ pub fn draw(frame: &mut Frame, app: &App) {
         );
     frame.render_widget(footer, layout[2]);
 
-    if let Some(popup) = &app.edit_popup {
-        render_edit_popup(frame, popup);
+    if let Some(popup) = &app.popup {
+        match popup {
+            ActivePopup::Edit(popup) => render_edit_popup(frame, popup),
+            ActivePopup::NewCategory(popup) => render_new_category_popup(frame, popup),
+            ActivePopup::NewTickr(popup) => render_new_tickr_popup(frame, popup),
+            ActivePopup::DeleteTickr(popup) => render_delete_tickr_popup(frame, popup),
+            ActivePopup::Confirm(popup) => render_confirm_popup(frame, popup),
+            ActivePopup::DeleteCategory(popup) => render_delete_category_popup(frame, popup),
+            ActivePopup::BlockedBy(popup) => render_blocked_by_popup(frame, popup),
+            ActivePopup::BlockedStart(popup) => render_blocked_start_popup(frame, popup),
+            ActivePopup::Unblock(popup) => render_unblock_popup(frame, popup),
+            ActivePopup::AddInterval(popup) => render_add_interval_popup(frame, popup),
+            ActivePopup::Range(popup) => render_range_popup(frame, popup),
+            ActivePopup::Capture(popup) => render_capture_popup(frame, popup),
+            ActivePopup::Goal(popup) => render_goal_popup(frame, popup),
+            ActivePopup::Client(popup) => render_client_popup(frame, popup),
+            ActivePopup::Notes(popup) => render_notes_popup(frame, popup),
+            ActivePopup::Triage(popup) => render_triage_popup(frame, popup),
+            ActivePopup::Legend(popup) => render_legend_popup(frame, popup),
+            ActivePopup::Activity(popup) => render_activity_popup(frame, popup),
+            ActivePopup::Profile(popup) => {
+                render_profile_popup(frame, popup, app.profile.as_deref())
+            }
+            ActivePopup::Template(popup) => render_template_popup(frame, popup),
+            ActivePopup::Switcher(popup) => render_switcher_popup(frame, popup),
+            ActivePopup::Pomodoro(popup) => render_pomodoro_popup(frame, popup),
+            ActivePopup::Idle(popup) => render_idle_popup(frame, popup),
+            ActivePopup::AutoStop(popup) => render_auto_stop_popup(frame, popup),
+            ActivePopup::IntervalError(popup) => render_interval_error_popup(frame, popup),
+            ActivePopup::Update(popup) => render_update_popup(frame, popup),
+        }
     }
-    if let Some(popup) = &app.new_category_popup {
-        render_new_category_popup(frame, popup);
+
+    if app.plain {
+        for cell in frame.buffer_mut().content.iter_mut() {
+            if let Some(ascii) = ascii_fallback(cell.symbol()) {
+                cell.set_symbol(ascii);
+            }
+        }
     }
-    if let Some(popup) = &app.new_tickr_popup {
-        render_new_tickr_popup(frame, popup);
+}
+
+/// Today's time as a bar split by category colors, with the total, e.g. `today ████▒▒ 03:15:00 `.
+/// `None` before anything is tracked today.
+fn category_strip(app: &App) -> Option<Line<'static>> {
+    let shares = view_model::today_by_category(&app.today_tickrs, &app.categories, app.now());
+    if shares.is_empty() {
+        return None;
     }
-    if let Some(popup) = &app.delete_tickr_popup {
-        render_delete_tickr_popup(frame, popup);
+    let mut spans = vec![Span::styled("today ", Style::default().fg(Theme::dim()))];
+    let cells = view_model::strip_cells(&shares, CATEGORY_STRIP_WIDTH);
+    for (share, cells) in shares.iter().zip(cells) {
+        let color = share
+            .category
+            .and_then(|category| hex_to_color(&category.color))
+            .unwrap_or(Theme::dim());
+        spans.push(Span::styled("█".repeat(cells), Style::default().fg(color)));
     }
-    if let Some(popup) = &app.update_popup {
-        render_update_popup(frame, popup);
+    let total = shares
+        .iter()
+        .fold(chrono::Duration::zero(), |sum, share| sum + share.duration);
+    spans.push(Span::styled(
+        format!(" {} ", format_duration(total)),
+        Style::default().fg(Theme::accent()),
+    ));
+    Some(Line::from(spans))
+}
+
+/// Renders view content inside the body block.
+fn render_text(frame: &mut Frame, area: Rect, text: Text) {
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(Theme::text()))
+        .alignment(Alignment::Left);
+    frame.render_widget(paragraph, area);
+}
+
+/// Renders `table` below the `heading` lines, scrolled so the selected row stays visible, with a
+/// scrollbar once the rows no longer fit. The table is expected to have a one-line header.
+fn render_scrolling_table(
+    frame: &mut Frame,
+    area: Rect,
+    heading: Vec<Line>,
+    table: Table,
+    row_count: usize,
+    state: &mut TableState,
+) {
+    let [heading_area, table_area] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(heading.len() as u16), Constraint::Min(0)])
+        .areas(area);
+    render_text(frame, heading_area, Text::from(heading));
+
+    let visible = usize::from(table_area.height.saturating_sub(1));
+    let table = table
+        .style(Style::default().fg(Theme::text()))
+        .row_highlight_style(
+            Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(Span::styled(
+            "> ",
+            Style::default().fg(Theme::selection_marker()),
+        ))
+        .highlight_spacing(HighlightSpacing::Always);
+    if row_count <= visible {
+        *state.offset_mut() = 0;
+        frame.render_stateful_widget(table, table_area, state);
+        return;
     }
+    let [rows_area, scrollbar_area] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .areas(table_area);
+    frame.render_stateful_widget(table, rows_area, state);
+    let mut scrollbar_state =
+        ScrollbarState::new(row_count.saturating_sub(visible)).position(state.offset());
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_symbol(Some("│"))
+        .thumb_symbol("█")
+        .style(Style::default().fg(Theme::dim()));
+    // Next to the rows only, below the header.
+    let scrollbar_area = Rect {
+        y: scrollbar_area.y + 1,
+        height: scrollbar_area.height.saturating_sub(1),
+        ..scrollbar_area
+    };
+    frame.render_stateful_widget(scrollbar, scrollbar_area, &mut scrollbar_state);
+}
+
+/// Header row of a view table.
+fn table_header<'a>(titles: impl IntoIterator<Item = Cell<'a>>) -> Row<'a> {
+    Row::new(titles).style(
+        Style::default()
+            .fg(Theme::secondary())
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Table cell with right-aligned text, for numbers and durations.
+fn right_cell<'a>(text: impl Into<Line<'a>>) -> Cell<'a> {
+    Cell::from(text.into().alignment(Alignment::Right))
 }
 
 fn render_edit_popup(frame: &mut Frame, popup: &crate::app::EditTickrPopup) {
+    use crate::app::EditTickrField;
+
     let area = centered_rect(70, 70, frame.area());
     frame.render_widget(Clear, area);
+    let field_style = |field: EditTickrField| {
+        if popup.field == field {
+            Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        }
+    };
 
-    let mut lines = Vec::new();
-    lines.push(Line::from(Span::styled(
-        "Edit task",
-        Style::default()
-            .fg(Theme::accent())
-            .add_modifier(Modifier::BOLD),
-    )));
-    lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled("Label: ", Style::default().fg(Theme::dim())),
-        Span::styled(
-            popup.label.as_str(),
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Edit task",
             Style::default()
-                .fg(Theme::text())
+                .fg(Theme::accent())
                 .add_modifier(Modifier::BOLD),
-        ),
-    ]));
-    lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(
-        "Category",
-        Style::default().fg(Theme::dim()),
-    )));
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Label: ", Style::default().fg(Theme::dim())),
+            Span::styled(popup.label.as_str(), field_style(EditTickrField::Label)),
+        ]),
+        Line::from(vec![
+            Span::styled("Issue: ", Style::default().fg(Theme::dim())),
+            Span::styled(popup.issue.as_str(), field_style(EditTickrField::Issue)),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled("Category", Style::default().fg(Theme::dim()))),
+    ];
 
     for (index, option) in popup.categories.iter().enumerate() {
         let selected = index == popup.category_index;
@@ -181,7 +451,8 @@ fn render_edit_popup(frame: &mut Frame, popup: &crate::app::EditTickrPopup) {
 
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        "Type to edit label. Up/Down: category. Enter: save. Esc: cancel.",
+        "Tab: label/issue (URL or owner/repo#123; an empty label takes the issue's title). \
+         Up/Down: category. Enter: save. Esc: cancel.",
         Style::default().fg(Theme::dim()),
     )));
 
@@ -216,9 +487,14 @@ fn render_new_category_popup(frame: &mut Frame, popup: &crate::app::NewCategoryP
         Style::default().fg(Theme::text())
     };
 
+    let (heading, title) = if popup.category_id.is_some() {
+        ("Edit category", " Edit Category ")
+    } else {
+        ("New category", " New Category ")
+    };
     let mut lines = Vec::new();
     lines.push(Line::from(Span::styled(
-        "New category",
+        heading,
         Style::default()
             .fg(Theme::accent())
             .add_modifier(Modifier::BOLD),
@@ -252,183 +528,1111 @@ fn render_new_category_popup(frame: &mut Frame, popup: &crate::app::NewCategoryP
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .style(Style::default().fg(Theme::secondary()))
-                .title(" New Category "),
+                .title(title),
         );
     frame.render_widget(popup_widget, area);
 }
 
-fn render_new_tickr_popup(frame: &mut Frame, popup: &crate::app::NewTickrPopup) {
-    let area = centered_rect(70, 75, frame.area());
+fn render_range_popup(frame: &mut Frame, popup: &crate::app::RangePopup) {
+    let area = centered_rect(50, 30, frame.area());
     frame.render_widget(Clear, area);
 
-    let label_active = popup.field == crate::app::NewTickrField::Label;
-    let project_active = popup.field == crate::app::NewTickrField::Project;
-    let category_active = popup.field == crate::app::NewTickrField::Category;
-    let start_active = popup.field == crate::app::NewTickrField::StartNow;
-
-    let arrow_style = Style::default()
-        .fg(Theme::selection_marker())
-        .add_modifier(Modifier::BOLD);
-    let label_style = if label_active {
-        Style::default()
-            .fg(Theme::highlight())
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Theme::text())
-    };
-    let label_title_style = if label_active {
-        Style::default()
-            .fg(Theme::highlight())
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Theme::dim())
-    };
-    let project_title_style = if project_active {
-        Style::default()
-            .fg(Theme::highlight())
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Theme::dim())
-    };
-    let category_title_style = if category_active {
-        Style::default()
-            .fg(Theme::highlight())
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Theme::dim())
-    };
-    let start_style = if start_active {
-        Style::default()
-            .fg(Theme::highlight())
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Theme::text())
-    };
-    let start_title_style = if start_active {
-        Style::default()
-            .fg(Theme::highlight())
-            .add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Theme::dim())
-    };
-
-    let mut lines = Vec::new();
-    lines.push(Line::from(Span::styled(
-        "New task",
-        Style::default()
-            .fg(Theme::accent())
-            .add_modifier(Modifier::BOLD),
-    )));
-    lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled(if label_active { "> " } else { "  " }, arrow_style),
-        Span::styled("Label: ", label_title_style),
-        Span::styled(popup.label.as_str(), label_style),
-    ]));
-    lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled(if project_active { "> " } else { "  " }, arrow_style),
-        Span::styled("Project", project_title_style),
-    ]));
-    for (index, option) in popup.projects.iter().enumerate() {
-        let selected = index == popup.project_index;
-        let marker_style = if selected {
-            arrow_style
-        } else {
-            Style::default().fg(Theme::dim())
-        };
-        let name_style = if selected || project_active {
+    let field_line = |label: &'static str, value: &str, field: RangeField| {
+        let style = if popup.field == field {
             Style::default()
-                .fg(Theme::text())
+                .fg(Theme::highlight())
                 .add_modifier(Modifier::BOLD)
         } else {
             Style::default().fg(Theme::text())
         };
-        lines.push(Line::from(vec![
-            Span::styled(if selected { "> " } else { "  " }, marker_style),
-            Span::styled(option.name.as_str(), name_style),
-        ]));
-    }
-    lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled(if category_active { "> " } else { "  " }, arrow_style),
-        Span::styled("Category", category_title_style),
-    ]));
-    for (index, option) in popup.categories.iter().enumerate() {
-        let selected = index == popup.category_index;
-        let marker_style = if selected {
-            arrow_style
-        } else {
-            Style::default().fg(Theme::dim())
-        };
-        let mut name_style = if let Some(color) = option.color.as_deref().and_then(hex_to_color) {
-            Style::default().fg(color).add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Theme::text())
-        };
-        if selected || category_active {
-            name_style = name_style.add_modifier(Modifier::BOLD);
-        }
-        lines.push(Line::from(vec![
-            Span::styled(if selected { "> " } else { "  " }, marker_style),
-            Span::styled(option.name.as_str(), name_style),
-        ]));
+        Line::from(vec![
+            Span::styled(label, Style::default().fg(Theme::dim())),
+            Span::styled(value.to_string(), style),
+        ])
+    };
+    let mut lines = vec![
+        field_line("From: ", &popup.from, RangeField::From),
+        field_line("To:   ", &popup.to, RangeField::To),
+        Line::from(""),
+    ];
+    if let Some(error) = &popup.error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+        lines.push(Line::from(""));
     }
-    lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled(if start_active { "> " } else { "  " }, arrow_style),
-        Span::styled("Start now: ", start_title_style),
-        Span::styled(if popup.start_now { "yes" } else { "no" }, start_style),
-    ]));
-    lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        "Type to edit label. Tab: switch field. Up/Down: select. Space: toggle start. Enter: save. Esc: cancel.",
+        "YYYY-MM-DD, MM-DD, today, yesterday or -N. An empty From is today, an empty To the same day. Tab: switch field. Enter: show. Esc: cancel.",
         Style::default().fg(Theme::dim()),
     )));
 
     let popup_widget = Paragraph::new(Text::from(lines))
         .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .style(Style::default().fg(Theme::secondary()))
-                .title(" New Task "),
+                .title(" Custom range "),
         );
     frame.render_widget(popup_widget, area);
 }
 
-fn render_delete_tickr_popup(frame: &mut Frame, popup: &crate::app::DeleteTickrPopup) {
-    let area = centered_rect(60, 35, frame.area());
+fn render_notes_popup(frame: &mut Frame, popup: &crate::app::NotesPopup) {
+    let area = centered_rect(70, 60, frame.area());
     frame.render_widget(Clear, area);
 
-    let mut lines = Vec::new();
-    lines.push(Line::from(Span::styled(
-        "Delete task",
-        Style::default()
-            .fg(Theme::danger())
-            .add_modifier(Modifier::BOLD),
-    )));
+    let target = popup.current();
+    let mut header = vec![
+        Line::from(vec![
+            Span::styled(
+                target.label.as_str(),
+                Style::default()
+                    .fg(Theme::highlight())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  ({}/{})", popup.index + 1, popup.targets.len()),
+                Style::default().fg(Theme::dim()),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    if let Some(error) = &popup.error {
+        header.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+        header.push(Line::from(""));
+    }
+    let footer = Line::from(Span::styled(
+        "Enter: new line  Tab: task/intervals  Esc: save and close",
+        Style::default().fg(Theme::dim()),
+    ));
+
+    // Keep the end of long notes, where typing happens, in view.
+    let width = area.width.saturating_sub(2).max(1) as usize;
+    let room = (area.height as usize).saturating_sub(2 + header.len() + 2);
+    let mut text_lines: Vec<&str> = target.text.split('\n').collect();
+    let rows = |line: &str| line.width().div_ceil(width).max(1);
+    while text_lines.len() > 1 && text_lines.iter().map(|line| rows(line)).sum::<usize>() > room {
+        text_lines.remove(0);
+    }
+    let last = text_lines.len() - 1;
+    let text_style = Style::default().fg(Theme::text());
+    let mut lines = header;
+    for (index, line) in text_lines.into_iter().enumerate() {
+        let mut spans = vec![Span::styled(line, text_style)];
+        if index == last {
+            spans.push(Span::styled(
+                "_",
+                Style::default().fg(Theme::selection_marker()),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
     lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled("Task: ", Style::default().fg(Theme::dim())),
-        Span::styled(
-            popup.label.as_str(),
+    lines.push(footer);
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Notes "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_goal_popup(frame: &mut Frame, popup: &crate::app::GoalPopup) {
+    use crate::types::GoalPeriod;
+
+    let area = centered_rect(60, 35, frame.area());
+    frame.render_widget(Clear, area);
+
+    let period_style = |period| {
+        if popup.period == period {
             Style::default()
-                .fg(Theme::text())
-                .add_modifier(Modifier::BOLD),
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::dim())
+        }
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            popup.project_name.as_str(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Hours ", Style::default().fg(Theme::dim())),
+            Span::styled("each week", period_style(GoalPeriod::Week)),
+            Span::raw("  "),
+            Span::styled("in total", period_style(GoalPeriod::Total)),
+        ]),
+        Line::from(vec![
+            Span::styled("> ", Style::default().fg(Theme::selection_marker())),
+            Span::styled(
+                popup.input.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    if let Some(error) = &popup.error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "Leave empty to remove the goal.",
+        Style::default().fg(Theme::dim()),
+    )));
+    lines.push(Line::from(Span::styled(
+        "Enter: save  Tab: week/total  Esc: cancel",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Goal "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_client_popup(frame: &mut Frame, popup: &crate::app::ClientPopup) {
+    let area = centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            popup.project_name.as_str(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled("Client", Style::default().fg(Theme::dim()))),
+        Line::from(vec![
+            Span::styled("> ", Style::default().fg(Theme::selection_marker())),
+            Span::styled(
+                popup.input.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    if !popup.clients.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("Clients: ", Style::default().fg(Theme::dim())),
+            Span::styled(popup.clients.join(", "), Style::default().fg(Theme::text())),
+        ]));
+        lines.push(Line::from(""));
+    }
+    if let Some(error) = &popup.error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "A new name adds the client, an empty one removes it.",
+        Style::default().fg(Theme::dim()),
+    )));
+    lines.push(Line::from(Span::styled(
+        "Enter: save  Tab: complete  Esc: cancel",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Client "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_capture_popup(frame: &mut Frame, popup: &crate::app::CapturePopup) {
+    use crate::app::CaptureTarget;
+
+    let area = centered_rect(60, 35, frame.area());
+    frame.render_widget(Clear, area);
+
+    let target_style = |target| {
+        if popup.target == target {
+            Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::dim())
+        }
+    };
+    let mut targets = Vec::new();
+    if let Some(running) = &popup.running_label {
+        targets.push(Span::styled(
+            format!("Note on {running}"),
+            target_style(CaptureTarget::RunningNote),
+        ));
+        targets.push(Span::raw("  "));
+    }
+    targets.push(Span::styled(
+        format!("New task in {}", crate::db::INBOX_PROJECT),
+        target_style(CaptureTarget::InboxTask),
+    ));
+
+    let mut lines = vec![
+        Line::from(targets),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("> ", Style::default().fg(Theme::selection_marker())),
+            Span::styled(
+                popup.input.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    if let Some(error) = &popup.error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        if popup.running_label.is_some() {
+            "Enter: save  Tab: note/task  Esc: cancel"
+        } else {
+            "Enter: save  Esc: cancel"
+        },
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Capture "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_triage_popup(frame: &mut Frame, popup: &crate::app::TriagePopup) {
+    use crate::app::TriageField;
+
+    let area = centered_rect(60, 45, frame.area());
+    frame.render_widget(Clear, area);
+
+    let Some(tickr) = popup.current() else {
+        return;
+    };
+    let field_line = |field, label: &str, value: String| {
+        let active = popup.field == field;
+        let style = if active {
+            Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        };
+        let value = if active && field != TriageField::Estimate {
+            format!("< {value} >")
+        } else {
+            value
+        };
+        Line::from(vec![
+            Span::styled(format!("{label:<10}"), Style::default().fg(Theme::dim())),
+            Span::styled(value, style),
+        ])
+    };
+    let estimate = if popup.field == TriageField::Estimate {
+        format!("{}_", popup.estimate)
+    } else if popup.estimate.is_empty() {
+        "none".to_string()
+    } else {
+        popup.estimate.clone()
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!("{}/{}  ", popup.index + 1, popup.tickrs.len()),
+                Style::default().fg(Theme::dim()),
+            ),
+            Span::styled(
+                tickr.description.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+        field_line(
+            TriageField::Project,
+            "Project",
+            popup.projects[popup.project_index].name.clone(),
         ),
-    ]));
-    lines.push(Line::from(""));
+        field_line(
+            TriageField::Category,
+            "Category",
+            popup.categories[popup.category_index].name.clone(),
+        ),
+        field_line(TriageField::Estimate, "Estimate", estimate),
+        Line::from(""),
+    ];
+    if let Some(error) = &popup.error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "Tab: field  Up/Down: change  Enter: move  Left/Right: skip  Del: delete  Esc: done",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(format!(" Triage {} ", crate::db::INBOX_PROJECT)),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_add_interval_popup(frame: &mut Frame, popup: &crate::app::AddIntervalPopup) {
+    let area = centered_rect(60, 45, frame.area());
+    frame.render_widget(Clear, area);
+
+    let field_style = |field| {
+        if popup.field == field {
+            Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        }
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Add interval",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Task: ", Style::default().fg(Theme::dim())),
+            Span::styled(popup.label.as_str(), Style::default().fg(Theme::text())),
+        ]),
+        Line::from(vec![
+            Span::styled("Start: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.start.as_str(),
+                field_style(crate::app::IntervalField::Start),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("End:   ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.end.as_str(),
+                field_style(crate::app::IntervalField::End),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    if let Some(error) = &popup.error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "YYYY-MM-DD HH:MM or HH:MM (today). Tab: switch field. Enter: save. Esc: cancel.",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Add Interval "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_new_tickr_popup(frame: &mut Frame, popup: &crate::app::NewTickrPopup) {
+    let area = centered_rect(70, 75, frame.area());
+    frame.render_widget(Clear, area);
+
+    let label_active = popup.field == crate::app::NewTickrField::Label;
+    let project_active = popup.field == crate::app::NewTickrField::Project;
+    let category_active = popup.field == crate::app::NewTickrField::Category;
+    let start_active = popup.field == crate::app::NewTickrField::StartNow;
+
+    let arrow_style = Style::default()
+        .fg(Theme::selection_marker())
+        .add_modifier(Modifier::BOLD);
+    let label_style = if label_active {
+        Style::default()
+            .fg(Theme::highlight())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Theme::text())
+    };
+    let label_title_style = if label_active {
+        Style::default()
+            .fg(Theme::highlight())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Theme::dim())
+    };
+    let project_title_style = if project_active {
+        Style::default()
+            .fg(Theme::highlight())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Theme::dim())
+    };
+    let category_title_style = if category_active {
+        Style::default()
+            .fg(Theme::highlight())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Theme::dim())
+    };
+    let start_style = if start_active {
+        Style::default()
+            .fg(Theme::highlight())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Theme::text())
+    };
+    let start_title_style = if start_active {
+        Style::default()
+            .fg(Theme::highlight())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Theme::dim())
+    };
+
+    let mut lines = Vec::new();
     lines.push(Line::from(Span::styled(
-        "This cannot be undone.",
+        "New task",
         Style::default()
-            .fg(Theme::danger())
+            .fg(Theme::accent())
             .add_modifier(Modifier::BOLD),
     )));
     lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(if label_active { "> " } else { "  " }, arrow_style),
+        Span::styled("Label: ", label_title_style),
+        Span::styled(popup.label.as_str(), label_style),
+    ]));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(if project_active { "> " } else { "  " }, arrow_style),
+        Span::styled("Project", project_title_style),
+    ]));
+    for (index, option) in popup.projects.iter().enumerate() {
+        let selected = index == popup.project_index;
+        let marker_style = if selected {
+            arrow_style
+        } else {
+            Style::default().fg(Theme::dim())
+        };
+        let name_style = if selected || project_active {
+            Style::default()
+                .fg(Theme::text())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        };
+        lines.push(Line::from(vec![
+            Span::styled(if selected { "> " } else { "  " }, marker_style),
+            Span::styled(option.name.as_str(), name_style),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(if category_active { "> " } else { "  " }, arrow_style),
+        Span::styled("Category", category_title_style),
+    ]));
+    for (index, option) in popup.categories.iter().enumerate() {
+        let selected = index == popup.category_index;
+        let marker_style = if selected {
+            arrow_style
+        } else {
+            Style::default().fg(Theme::dim())
+        };
+        let mut name_style = if let Some(color) = option.color.as_deref().and_then(hex_to_color) {
+            Style::default().fg(color).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        };
+        if selected || category_active {
+            name_style = name_style.add_modifier(Modifier::BOLD);
+        }
+        lines.push(Line::from(vec![
+            Span::styled(if selected { "> " } else { "  " }, marker_style),
+            Span::styled(option.name.as_str(), name_style),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(if start_active { "> " } else { "  " }, arrow_style),
+        Span::styled("Start now: ", start_title_style),
+        Span::styled(if popup.start_now { "yes" } else { "no" }, start_style),
+    ]));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Type to edit label. Tab: switch field. Up/Down: select. Space: toggle start. Enter: save. Esc: cancel.",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" New Task "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_delete_category_popup(frame: &mut Frame, popup: &crate::app::DeleteCategoryPopup) {
+    let area = centered_rect(60, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Delete category",
+            Style::default()
+                .fg(Theme::danger())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Category: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.name.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    if popup.usage == 0 {
+        lines.push(Line::from(Span::styled(
+            "No task uses this category.",
+            Style::default().fg(Theme::dim()),
+        )));
+    } else {
+        lines.push(Line::from(Span::styled(
+            format!("Move its {} tasks to:", popup.usage),
+            Style::default().fg(Theme::text()),
+        )));
+        for (index, target) in popup.targets.iter().enumerate() {
+            let selected = index == popup.target_index;
+            let marker = if selected { "> " } else { "  " };
+            let color = target
+                .color
+                .as_deref()
+                .and_then(hex_to_color)
+                .unwrap_or(Theme::text());
+            let style = if selected {
+                Style::default().fg(color).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(color)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(marker, Style::default().fg(Theme::selection_marker())),
+                Span::styled(target.name.as_str(), style),
+            ]));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        if popup.usage == 0 {
+            "Enter/Y: delete  Esc/N: cancel"
+        } else {
+            "Up/Down: Choose target  Enter/Y: delete  Esc/N: cancel"
+        },
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::danger()))
+                .title(" Delete "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_delete_tickr_popup(frame: &mut Frame, popup: &crate::app::DeleteTickrPopup) {
+    let area = centered_rect(60, 35, frame.area());
+    frame.render_widget(Clear, area);
+
+    let lines = vec![
+        Line::from(Span::styled(
+            "Delete task",
+            Style::default()
+                .fg(Theme::danger())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Task: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.label.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "This cannot be undone.",
+            Style::default()
+                .fg(Theme::danger())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter/Y: delete  Esc/N: cancel",
+            Style::default().fg(Theme::dim()),
+        )),
+    ];
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::danger()))
+                .title(" Delete "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_confirm_popup(frame: &mut Frame, popup: &crate::app::ConfirmPopup) {
+    let area = centered_rect(60, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let color = if popup.action.destructive() {
+        Theme::danger()
+    } else {
+        Theme::accent()
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            popup.action.title(),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            popup.question.as_str(),
+            Style::default().fg(Theme::text()),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter/Y: yes  Esc/N: cancel",
+            Style::default().fg(Theme::dim()),
+        )),
+    ];
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(color))
+                .title(format!(" {} ", popup.action.title())),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_blocked_by_popup(frame: &mut Frame, popup: &crate::app::BlockedByPopup) {
+    let area = centered_rect(70, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Blocked by",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Task: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.label.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    // Borders plus the lines above and the error and hint lines below.
+    let visible = usize::from(area.height)
+        .saturating_sub(lines.len() + 6)
+        .max(1);
+    let first = (popup.option_index + 1)
+        .saturating_sub(visible)
+        .min(popup.options.len().saturating_sub(visible));
+    for (index, option) in popup.options.iter().enumerate().skip(first).take(visible) {
+        let selected = index == popup.option_index;
+        let marker = if selected { "> " } else { "  " };
+        let style = if selected {
+            Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        };
+        lines.push(Line::from(vec![
+            Span::styled(marker, Style::default().fg(Theme::selection_marker())),
+            Span::styled(option.name.as_str(), style),
+        ]));
+    }
+    lines.push(Line::from(""));
+    if let Some(error) = &popup.error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+    }
+    lines.push(Line::from(Span::styled(
+        "Up/Down: Choose task  Enter: Save  Esc: Cancel",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Dependency "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_blocked_start_popup(frame: &mut Frame, popup: &crate::app::BlockedStartPopup) {
+    let area = centered_rect(60, 35, frame.area());
+    frame.render_widget(Clear, area);
+
+    let lines = vec![
+        Line::from(Span::styled(
+            "Task is blocked",
+            Style::default()
+                .fg(Theme::warn())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Task: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.label.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Waiting on: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.blocker.as_str(),
+                Style::default()
+                    .fg(Theme::warn())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter/Y: start anyway  Esc/N: cancel",
+            Style::default().fg(Theme::dim()),
+        )),
+    ];
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::warn()))
+                .title(" Blocked "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_unblock_popup(frame: &mut Frame, popup: &crate::app::UnblockPopup) {
+    let area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Unblock waiting tasks?",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                popup.label.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                " is done. These tasks wait on it:",
+                Style::default().fg(Theme::dim()),
+            ),
+        ]),
+    ];
+    for dependent in &popup.dependents {
+        lines.push(Line::from(Span::styled(
+            format!("  {dependent}"),
+            Style::default().fg(Theme::warn()),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter/Y: unblock  Esc/N: keep blocked",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Done "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_legend_popup(frame: &mut Frame, popup: &crate::app::LegendPopup) {
+    let area = centered_rect(50, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Category colors",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            if popup.showing_all {
+                "All categories"
+            } else {
+                "Used by the tasks in this view"
+            },
+            Style::default().fg(Theme::dim()),
+        )),
+        Line::from(""),
+    ];
+
+    if popup.entries.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No categories yet.",
+            Style::default().fg(Theme::dim()),
+        )));
+    }
+    for entry in &popup.entries {
+        let color = hex_to_color(&entry.color).unwrap_or(Theme::text());
+        let mut spans = vec![
+            Span::styled("██ ", Style::default().fg(color)),
+            Span::styled(
+                clamp_name(&entry.name, 20),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" "),
+            Span::styled(entry.color.as_str(), Style::default().fg(Theme::dim())),
+        ];
+        if entry.tasks > 0 {
+            let label = if entry.tasks == 1 { "task" } else { "tasks" };
+            spans.push(Span::styled(
+                format!("  {} {label}", entry.tasks),
+                Style::default().fg(Theme::accent()),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "L/Esc: close",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Legend "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_activity_popup(frame: &mut Frame, popup: &crate::app::ActivityPopup) {
+    let area = centered_rect(70, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Changes made in this session",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            "Latest first, as stored in the audit log",
+            Style::default().fg(Theme::dim()),
+        )),
+        Line::from(""),
+    ];
+    if popup.entries.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Nothing changed yet.",
+            Style::default().fg(Theme::dim()),
+        )));
+    }
+    // Title, blank line and the hint at the bottom, inside the border.
+    let visible = (area.height as usize).saturating_sub(lines.len() + 4);
+    for entry in popup.entries.iter().skip(popup.scroll).take(visible) {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{}  ", entry.at.format("%H:%M:%S")),
+                Style::default().fg(Theme::dim()),
+            ),
+            Span::styled(entry.message.clone(), Style::default().fg(Theme::text())),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Up/Down: scroll  H/Esc: close",
+        Style::default().fg(Theme::dim()),
+    )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Activity "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_profile_popup(
+    frame: &mut Frame,
+    popup: &crate::app::ProfilePopup,
+    current: Option<&str>,
+) {
+    let area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Switch to another database",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for (index, option) in popup.options.iter().enumerate() {
+        let selected = index == popup.index;
+        let marker = if selected { "> " } else { "  " };
+        let style = if selected {
+            Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        };
+        let name = option.name.as_deref().unwrap_or("default");
+        let open = if option.name.as_deref() == current {
+            " (open)"
+        } else {
+            ""
+        };
+        lines.push(Line::from(vec![
+            Span::styled(marker, Style::default().fg(Theme::selection_marker())),
+            Span::styled(format!("{name}{open}"), style),
+            Span::styled(
+                format!("  {}", option.db),
+                Style::default().fg(Theme::dim()),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        "Enter/Y: delete  Esc/N: cancel",
+        "Up/Down: Choose  Enter: Switch  Esc: Cancel",
         Style::default().fg(Theme::dim()),
     )));
 
@@ -438,53 +1642,371 @@ fn render_delete_tickr_popup(frame: &mut Frame, popup: &crate::app::DeleteTickrP
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .style(Style::default().fg(Theme::danger()))
-                .title(" Delete "),
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Profiles "),
         );
     frame.render_widget(popup_widget, area);
 }
 
-fn render_update_popup(frame: &mut Frame, popup: &crate::app::UpdatePopup) {
-    let area = centered_rect(60, 35, frame.area());
+fn render_switcher_popup(frame: &mut Frame, popup: &crate::app::SwitcherPopup) {
+    let area = centered_rect(60, 50, frame.area());
     frame.render_widget(Clear, area);
 
-    let mut lines = Vec::new();
-    lines.push(Line::from(Span::styled(
-        "Update Available",
-        Style::default()
-            .fg(Theme::success())
-            .add_modifier(Modifier::BOLD),
-    )));
-    lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled("New version: ", Style::default().fg(Theme::dim())),
-        Span::styled(
-            popup.new_version.as_str(),
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Switch to: ", Style::default().fg(Theme::accent())),
+            Span::styled(
+                popup.query.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    for (row, index) in popup.matches.iter().enumerate() {
+        let item = &popup.items[*index];
+        let selected = row == popup.index;
+        let marker = if selected { "> " } else { "  " };
+        let style = if selected {
             Style::default()
-                .fg(Theme::active())
-                .add_modifier(Modifier::BOLD),
-        ),
-    ]));
-    lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(
-        "Would you like to update now?",
-        Style::default().fg(Theme::text()),
-    )));
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        };
+        lines.push(Line::from(vec![
+            Span::styled(marker, Style::default().fg(Theme::selection_marker())),
+            Span::styled(item.label.clone(), style),
+            Span::styled(
+                format!("  {}", item.detail),
+                Style::default().fg(Theme::dim()),
+            ),
+        ]));
+    }
+    if popup.matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No matches",
+            Style::default().fg(Theme::dim()),
+        )));
+    }
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        "The application will download and install the update,",
+        "Type: Filter  Up/Down: Choose  Enter: Switch  Esc: Cancel",
         Style::default().fg(Theme::dim()),
     )));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Recent tasks "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_template_popup(frame: &mut Frame, popup: &crate::app::TemplatePopup) {
+    let area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Create a task from a template and start it",
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for (index, template) in popup.templates.iter().enumerate() {
+        let selected = index == popup.index;
+        let marker = if selected { "> " } else { "  " };
+        let style = if selected {
+            Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Theme::text())
+        };
+        let every_workday = if template.every_workday {
+            ", every workday"
+        } else {
+            ""
+        };
+        lines.push(Line::from(vec![
+            Span::styled(marker, Style::default().fg(Theme::selection_marker())),
+            Span::styled(template.name.clone(), style),
+            Span::styled(
+                format!("  {}{every_workday}", template.project),
+                Style::default().fg(Theme::dim()),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        "then exit. Please restart after the update completes.",
+        "Up/Down: Choose  Enter: Create and start  Esc: Cancel",
         Style::default().fg(Theme::dim()),
     )));
-    lines.push(Line::from(""));
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::secondary()))
+                .title(" Templates "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_auto_stop_popup(frame: &mut Frame, popup: &crate::app::AutoStopPopup) {
+    let area = centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Left running?",
+            Style::default()
+                .fg(Theme::warn())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(popup.task.as_str(), Style::default().fg(Theme::active())),
+            Span::styled(" ran since ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.start.format("%Y-%m-%d %H:%M").to_string(),
+                Style::default().fg(Theme::text()),
+            ),
+            Span::styled(" and was stopped at ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.stopped_at.format("%Y-%m-%d %H:%M").to_string(),
+                Style::default().fg(Theme::text()),
+            ),
+            Span::styled(".", Style::default().fg(Theme::dim())),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "End the interval at:",
+            Style::default().fg(Theme::text()),
+        )),
+        Line::from(vec![
+            Span::styled("> ", Style::default().fg(Theme::selection_marker())),
+            Span::styled(
+                popup.input.as_str(),
+                Style::default()
+                    .fg(Theme::text())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    if let Some(error) = &popup.error {
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Theme::danger()),
+        )));
+        lines.push(Line::from(""));
+    }
     lines.push(Line::from(Span::styled(
-        "Enter/Y: update  Esc/N: skip",
+        "Enter: trim to this time  Esc: keep the stop time",
         Style::default().fg(Theme::dim()),
     )));
 
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::warn()))
+                .title(" Auto-stop "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_idle_popup(frame: &mut Frame, popup: &crate::app::IdlePopup) {
+    let area = centered_rect(60, 55, frame.area());
+    frame.render_widget(Clear, area);
+
+    let idle = format_duration(Local::now().signed_duration_since(popup.idle_start));
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Were you away?",
+            Style::default()
+                .fg(Theme::warn())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("No input since ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.idle_start.format("%H:%M").to_string(),
+                Style::default().fg(Theme::text()),
+            ),
+            Span::styled(" (", Style::default().fg(Theme::dim())),
+            Span::styled(idle, Style::default().fg(Theme::accent())),
+            Span::styled(") while running ", Style::default().fg(Theme::dim())),
+            Span::styled(popup.task.as_str(), Style::default().fg(Theme::active())),
+        ]),
+        Line::from(""),
+    ];
+
+    if popup.assigning {
+        lines.push(Line::from(Span::styled(
+            "Assign the idle time to:",
+            Style::default().fg(Theme::text()),
+        )));
+        for (index, task) in popup.tasks.iter().enumerate() {
+            let selected = index == popup.task_index;
+            let style = if selected {
+                Style::default()
+                    .fg(Theme::highlight())
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Theme::text())
+            };
+            let marker = if selected { "> " } else { "  " };
+            lines.push(Line::from(Span::styled(
+                format!("{marker}{}", task.label),
+                style,
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Up/Down: Select  Enter: Assign  Esc: Back",
+            Style::default().fg(Theme::dim()),
+        )));
+    } else {
+        for (index, choice) in crate::app::IdleChoice::ALL.iter().enumerate() {
+            let selected = index == popup.choice_index;
+            let style = if selected {
+                Style::default()
+                    .fg(Theme::highlight())
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Theme::text())
+            };
+            let marker = if selected { "> " } else { "  " };
+            lines.push(Line::from(Span::styled(
+                format!("{marker}{}", choice.label()),
+                style,
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Up/Down: Select  Enter: Confirm  Esc: Keep",
+            Style::default().fg(Theme::dim()),
+        )));
+    }
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::warn()))
+                .title(" Idle "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_pomodoro_popup(frame: &mut Frame, popup: &crate::app::PomodoroPopup) {
+    let area = centered_rect(50, 35, frame.area());
+    frame.render_widget(Clear, area);
+
+    let (title, message, hint) = match popup.alert {
+        crate::app::PomodoroAlert::BreakStarted => (
+            "Time for a break",
+            format!(
+                "Work block #{} on '{}' is done and the task was stopped.",
+                popup.completed, popup.task
+            ),
+            format!(
+                "Step away for {} minutes.  Enter/Esc: close",
+                popup.break_minutes
+            ),
+        ),
+        crate::app::PomodoroAlert::BreakOver => (
+            "Break is over",
+            format!("Ready for another block on '{}'?", popup.task),
+            "Enter: start work block  Esc: end pomodoro".to_string(),
+        ),
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(Theme::warn())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(message, Style::default().fg(Theme::text()))),
+        Line::from(""),
+        Line::from(Span::styled(hint, Style::default().fg(Theme::dim()))),
+    ];
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Theme::warn()))
+                .title(" Pomodoro "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
+fn render_update_popup(frame: &mut Frame, popup: &crate::app::UpdatePopup) {
+    let area = centered_rect(60, 35, frame.area());
+    frame.render_widget(Clear, area);
+
+    let lines = vec![
+        Line::from(Span::styled(
+            "Update Available",
+            Style::default()
+                .fg(Theme::success())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("New version: ", Style::default().fg(Theme::dim())),
+            Span::styled(
+                popup.new_version.as_str(),
+                Style::default()
+                    .fg(Theme::active())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Would you like to update now?",
+            Style::default().fg(Theme::text()),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "The application will download and install the update,",
+            Style::default().fg(Theme::dim()),
+        )),
+        Line::from(Span::styled(
+            "then exit. Please restart after the update completes.",
+            Style::default().fg(Theme::dim()),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter/Y: update  Esc/N: skip",
+            Style::default().fg(Theme::dim()),
+        )),
+    ];
+
     let popup_widget = Paragraph::new(Text::from(lines))
         .alignment(Alignment::Left)
         .block(
@@ -497,6 +2019,50 @@ fn render_update_popup(frame: &mut Frame, popup: &crate::app::UpdatePopup) {
     frame.render_widget(popup_widget, area);
 }
 
+fn render_interval_error_popup(frame: &mut Frame, popup: &crate::app::IntervalErrorPopup) {
+    let area = centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let (heading, color) = if popup.error.is_warning() {
+        ("Empty interval", Theme::warn())
+    } else {
+        ("Interval ends before it starts", Theme::danger())
+    };
+    let lines = vec![
+        Line::from(Span::styled(
+            heading,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            popup.error.to_string(),
+            Style::default().fg(Theme::text()),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            popup.outcome.as_str(),
+            Style::default().fg(Theme::dim()),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter/Esc: close",
+            Style::default().fg(Theme::dim()),
+        )),
+    ];
+
+    let popup_widget = Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(color))
+                .title(" Interval "),
+        );
+    frame.render_widget(popup_widget, area);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -530,7 +2096,9 @@ fn tabs_line(app: &App) -> Line<'_> {
         ("Tickrs", AppView::Tickrs),
         ("Worked", AppView::WorkedProjects),
         ("Timeline", AppView::Timeline),
+        ("Calendar", AppView::Calendar),
         ("Categories", AppView::Categories),
+        ("Stats", AppView::Stats),
     ];
 
     let mut spans = Vec::new();
@@ -541,31 +2109,59 @@ fn tabs_line(app: &App) -> Line<'_> {
         let active = match app.view {
             AppView::ProjectTickrs => *view == AppView::Tickrs,
             AppView::TickrDetail => *view == AppView::Tickrs,
+            AppView::DayAgenda => *view == app.agenda_parent,
             AppView::WorkedProjects => *view == AppView::WorkedProjects,
             _ => *view == app.view,
         };
-        let focused =
-            app.focus_mode == crate::app::FocusMode::TabBar && app.selected_tab_index == index;
-        let style = if active {
+        let tab_bar_focused = app.focus_mode == crate::app::FocusMode::TabBar;
+        let focused = tab_bar_focused && app.selected_tab_index == index;
+        // While the content is focused the bar is dimmed, with only the active tab marked.
+        let style = match (active, focused, tab_bar_focused) {
+            (true, _, true) => Style::default()
+                .fg(Theme::badge_text())
+                .bg(Theme::highlight())
+                .add_modifier(Modifier::BOLD),
+            (true, _, false) => Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD),
+            (false, true, _) => Style::default()
+                .fg(Theme::highlight())
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            (false, false, true) => Style::default().fg(Theme::text()),
+            (false, false, false) => Style::default()
+                .fg(Theme::dim())
+                .add_modifier(Modifier::DIM),
+        };
+        spans.push(Span::styled(format!(" {name} "), style));
+    }
+
+    Line::from(spans)
+}
+
+/// The presets of a range selector in a row, the selected one marked like the active tab.
+fn range_selector_line(selector: &RangeSelector) -> Line<'static> {
+    let mut spans = vec![Span::styled("  Range:", Style::default().fg(Theme::dim()))];
+    for preset in RangePreset::ALL {
+        spans.push(Span::raw(" "));
+        let style = if preset == selector.preset {
             Style::default()
-                .fg(Color::Black)
+                .fg(Theme::badge_text())
                 .bg(Theme::highlight())
                 .add_modifier(Modifier::BOLD)
-        } else if focused {
-            Style::default()
-                .fg(Theme::highlight())
-                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
         } else {
             Style::default().fg(Theme::dim())
         };
-        spans.push(Span::styled(format!(" {name} "), style));
+        spans.push(Span::styled(format!(" {} ", preset.label()), style));
     }
-
     Line::from(spans)
 }
 
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+/// Sparkles around the banner of a reached goal, a few frames each.
+const CONFETTI_FRAMES: [&str; 4] = ["✦ ✧ ✦", "✧ ✦ ✧", "★ ✧ ★", "✧ ★ ✧"];
+
 fn running_task_line(app: &App) -> Line<'_> {
-    let now = Local::now();
+    let now = app.now();
     let mut running: Option<(&crate::types::Tickr, &crate::types::Interval)> = None;
     for tickr in &app.tickrs {
         if let Some(interval) = tickr.intervals.iter().find(|i| i.end_time.is_none()) {
@@ -581,74 +2177,304 @@ fn running_task_line(app: &App) -> Line<'_> {
             .find(|project| project.id == Some(tickr.project_id))
             .map(|project| project.name.as_str())
             .unwrap_or("Unknown project");
-        let duration = format_duration(now.signed_duration_since(interval.start_time));
-        format!(
+        let duration = format_duration(interval.duration(now));
+        let line = format!(
             "{project_name} > {} > Running {duration}",
             tickr.description
-        )
+        );
+        if app.plain {
+            line
+        } else {
+            let spinner = SPINNER_FRAMES[app.animation_frame() % SPINNER_FRAMES.len()];
+            format!("{spinner} {line}")
+        }
     } else {
         "No task running".to_string()
     };
 
-    Line::from(Span::styled(
+    let mut spans = vec![Span::styled(
         text,
         Style::default()
             .fg(Theme::active())
             .add_modifier(Modifier::BOLD),
-    ))
+    )];
+    if let Some((message, _)) = &app.celebration {
+        let confetti = if app.plain {
+            "***"
+        } else {
+            CONFETTI_FRAMES[app.animation_frame() / 3 % CONFETTI_FRAMES.len()]
+        };
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!(" {confetti} {message} {confetti} "),
+            Style::default()
+                .fg(Theme::badge_text())
+                .bg(Theme::success())
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(left) = app.budget_left() {
+        spans.push(budget_span(left, "  "));
+    }
+    if let Some(pomodoro) = &app.pomodoro {
+        let remaining = pomodoro
+            .remaining(&app.pomodoro_settings, now)
+            .num_seconds();
+        let phase = match pomodoro.phase {
+            crate::app::PomodoroPhase::Work => "Pomodoro",
+            crate::app::PomodoroPhase::Break => "Break",
+        };
+        spans.push(Span::styled(
+            format!(
+                "  {phase} {:02}:{:02} left (#{})",
+                remaining / 60,
+                remaining % 60,
+                pomodoro.completed + 1
+            ),
+            Style::default().fg(Theme::warn()),
+        ));
+    }
+    Line::from(spans)
 }
 
-fn keybinds_lines(app: &App) -> Vec<Line<'static>> {
-    let focus_hint = if app.focus_mode == crate::app::FocusMode::TabBar {
-        "Tab: Switch to content  ←/→: Navigate tabs  Enter: Select"
+/// What is `left` of the daily budget, or the overtime once it is negative.
+pub(crate) fn budget_span(left: chrono::Duration, prefix: &str) -> Span<'static> {
+    if left < chrono::Duration::zero() {
+        Span::styled(
+            format!("{prefix}{} overtime today", format_duration(-left)),
+            Style::default()
+                .fg(Theme::danger())
+                .add_modifier(Modifier::BOLD),
+        )
     } else {
-        "Tab: Switch to tab bar  h/p/t/w/l/c: Quick nav  ?: Help"
-    };
+        Span::styled(
+            format!("{prefix}{} left today", format_duration(left)),
+            Style::default().fg(Theme::dim()),
+        )
+    }
+}
 
-    let (primary, secondary) = match app.view {
+/// Actions whose keys a footer hint shows joined by "/", and what they do.
+type Hint = (&'static [Action], &'static str);
+
+const TAB_BAR_HINTS: &[Hint] = &[
+    (&[Action::ToggleFocus], "Switch to content"),
+    (&[Action::Left, Action::Right], "Navigate tabs"),
+    (&[Action::Open], "Select"),
+];
+
+const CONTENT_HINTS: &[Hint] = &[
+    (&[Action::ToggleFocus], "Switch to tab bar"),
+    (
+        &[
+            Action::Dashboard,
+            Action::Projects,
+            Action::Tasks,
+            Action::Worked,
+            Action::Timeline,
+            Action::Calendar,
+            Action::Categories,
+        ],
+        "Quick nav",
+    ),
+    (&[Action::Help], "Help"),
+];
+
+const COMMON_HINTS: &[Hint] = &[
+    (&[Action::Back], "Back"),
+    (&[Action::Refresh], "Refresh"),
+    (&[Action::Quit], "Quit"),
+];
+
+/// Hints of the view, the most used ones first.
+fn view_hints(view: &AppView) -> (&'static [Hint], &'static [Hint]) {
+    match view {
         AppView::Dashboard => (
-            "h: Home  p: Projects  t: Tasks  w: Worked  l: Timeline  c: Categories",
-            "r: Refresh  ?: Help  q: Quit",
+            &[
+                (&[Action::Capture], "Capture"),
+                (&[Action::Triage], "Triage inbox"),
+                (&[Action::Stop], "Stop running"),
+                (&[Action::Resume], "Resume last"),
+                (&[Action::Switcher], "Switch task"),
+                (&[Action::PomodoroStart], "Pomodoro"),
+            ],
+            &[
+                (&[Action::Stats], "Stats"),
+                (&[Action::Legend], "Legend"),
+                (&[Action::ActivityLog], "Activity"),
+                (&[Action::Profiles], "Profiles"),
+                (&[Action::Templates], "Templates"),
+            ],
         ),
         AppView::Projects => (
-            "Up/Down: Select  Enter: Open  n: New task  /: Search",
-            "r: Refresh  ?: Help  q: Quit",
+            &[
+                (&[Action::Up, Action::Down], "Select"),
+                (&[Action::Open], "Open"),
+                (&[Action::New], "New task"),
+                (&[Action::Search], "Search"),
+            ],
+            &[
+                (&[Action::Left, Action::Right], "Collapse/Expand"),
+                (&[Action::Collapse, Action::Expand], "All"),
+                (&[Action::NewFromGit], "Task from branch"),
+                (&[Action::Goal], "Goal"),
+                (&[Action::Client], "Client"),
+                (&[Action::Archive], "Archive"),
+                (&[Action::ShowArchived], "Show archived"),
+            ],
         ),
         AppView::Tickrs => (
-            "Up/Down: Select  Enter: Detail  space: Start/End  d: Delete",
-            "r: Refresh  ?: Help  q: Quit",
+            &[
+                (&[Action::Up, Action::Down], "Select"),
+                (&[Action::Open], "Detail"),
+                (&[Action::StartStop], "Start/End"),
+                (&[Action::Delete], "Delete"),
+            ],
+            &[
+                (&[Action::Filter], "Today/Week/All"),
+                (&[Action::Sort], "Sort"),
+                (&[Action::Archive], "Archive"),
+                (&[Action::ShowArchived], "Show archived"),
+            ],
         ),
         AppView::ProjectTickrs => (
-            "Up/Down: Select  Enter: Detail  space: Start/End  n: New task  d: Delete",
-            "esc: Back  r: Refresh  ?: Help  q: Quit",
+            &[
+                (&[Action::Up, Action::Down], "Select"),
+                (&[Action::Open], "Detail"),
+                (&[Action::StartStop], "Start/End"),
+                (&[Action::New], "New task"),
+                (&[Action::Delete], "Delete"),
+            ],
+            &[
+                (&[Action::Search], "Search"),
+                (&[Action::Filter], "Category"),
+                (&[Action::Sort], "Sort"),
+                (&[Action::NewFromGit], "Task from branch"),
+                (&[Action::Archive], "Archive"),
+                (&[Action::ShowArchived], "Show archived"),
+            ],
         ),
         AppView::WorkedProjects => (
-            "Up/Down: Select  Enter: Open  Shift+Tab: Adjust Range",
-            "r: Refresh  ?: Help  q: Quit",
+            &[
+                (&[Action::Up, Action::Down], "Select"),
+                (&[Action::Open], "Open"),
+                (&[Action::PreviousPage, Action::NextPage], "Range"),
+                (&[Action::GoTo], "Custom range"),
+                (&[Action::Agenda], "Today's intervals"),
+            ],
+            &[
+                (&[Action::Left, Action::Right], "Collapse/Expand"),
+                (&[Action::Collapse, Action::Expand], "All"),
+            ],
         ),
         AppView::Timeline => (
-            "Shift+Tab: Day/Week  h/p/t/w/l/c: Quick nav",
-            "r: Refresh  ?: Help  q: Quit",
+            &[
+                (&[Action::PreviousPage, Action::NextPage], "Range"),
+                (&[Action::Left, Action::Right], "Previous/Next"),
+                (&[Action::Up, Action::Down], "Select day"),
+                (&[Action::GoTo], "Custom range"),
+            ],
+            &[(&[Action::Open], "Intervals of the day")],
+        ),
+        AppView::Calendar => (
+            &[
+                (
+                    &[Action::Left, Action::Right, Action::Up, Action::Down],
+                    "Select day",
+                ),
+                (
+                    &[Action::PreviousPage, Action::NextPage],
+                    "Previous/Next month",
+                ),
+                (&[Action::Open], "Tasks of the day"),
+            ],
+            &[(&[Action::Agenda], "Intervals of the day")],
+        ),
+        AppView::DayAgenda => (
+            &[
+                (&[Action::Up, Action::Down], "Select"),
+                (&[Action::Open], "Task detail"),
+                (
+                    &[Action::PreviousPage, Action::NextPage],
+                    "Previous/Next day",
+                ),
+                (&[Action::Delete], "Delete interval"),
+            ],
+            &[],
         ),
         AppView::Categories => (
-            "Up/Down: Select  n: New",
-            "esc: Back  r: Refresh  ?: Help  q: Quit",
+            &[
+                (&[Action::Up, Action::Down], "Select"),
+                (&[Action::New], "New"),
+                (&[Action::Edit], "Edit"),
+                (&[Action::Delete], "Delete"),
+            ],
+            &[],
         ),
+        AppView::Stats => (&[], &[]),
         AppView::TickrDetail => (
-            "space: Start/End  s: Stop  g: Project  e: Edit  d: Delete",
-            "esc: Back  ?: Help  q: Quit",
-        ),
-        AppView::Help => (
-            "?: Back  esc: Back  h/p/t/w/l/c: Quick nav",
-            "q: Quit",
+            &[
+                (&[Action::StartStop], "Start/End"),
+                (&[Action::Stop], "Stop"),
+                (&[Action::GoTo], "Project"),
+                (&[Action::Edit], "Edit"),
+                (&[Action::AddInterval], "Add interval"),
+                (&[Action::ToggleBillable], "Billable"),
+                (&[Action::Delete], "Delete"),
+            ],
+            &[
+                (&[Action::PreviousPage, Action::NextPage], "Older/Newer"),
+                (&[Action::Left, Action::Right], "Intervals/By month"),
+                (&[Action::ToggleDone], "Done"),
+                (&[Action::Notes], "Notes"),
+                (&[Action::BlockedBy], "Blocked by"),
+                (&[Action::OpenIssue], "Open issue"),
+                (&[Action::Archive], "Archive"),
+            ],
         ),
+        AppView::Help => (&[], &[]),
+    }
+}
+
+/// `hints` as "key: label" pairs, leaving out actions that do nothing here or have no key.
+fn hint_text(app: &App, hints: &[Hint]) -> String {
+    hints
+        .iter()
+        .filter_map(|(actions, label)| {
+            let keys: Vec<String> = actions
+                .iter()
+                .filter(|action| app.action_available(**action))
+                .filter_map(|action| app.keymap.key(*action))
+                .map(key_label)
+                .collect();
+            (!keys.is_empty()).then(|| format!("{}: {label}", keys.join("/")))
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Footer hints for the view and focus mode, with the keys as currently bound.
+fn keybinds_lines(app: &App) -> Vec<Line<'static>> {
+    let focus_hints = if app.focus_mode == crate::app::FocusMode::TabBar {
+        TAB_BAR_HINTS
+    } else {
+        CONTENT_HINTS
     };
+    let (primary, secondary) = view_hints(&app.view);
+    let secondary = [hint_text(app, secondary), hint_text(app, COMMON_HINTS)]
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("  ");
     vec![
         Line::from(Span::styled(
-            focus_hint,
+            hint_text(app, focus_hints),
             Style::default().fg(Theme::highlight()),
         )),
-        Line::from(Span::styled(primary, Style::default().fg(Theme::dim()))),
+        Line::from(Span::styled(
+            hint_text(app, primary),
+            Style::default().fg(Theme::dim()),
+        )),
         Line::from(Span::styled(secondary, Style::default().fg(Theme::dim()))),
     ]
 }
@@ -1,27 +1,75 @@
-use chrono::Duration;
+use chrono::{Duration, Local};
 use ratatui::{
-    style::{Modifier, Style},
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
+    widgets::{Cell, Paragraph, Row, Table},
 };
 
-use super::helpers::{clamp_name, format_duration};
+use super::helpers::format_duration;
 use super::theme::Theme;
-use crate::app::{App, WorkedRange};
+use super::view_model::{self, ProjectName, ProjectTree, TreeMarker};
+use super::{render_scrolling_table, right_cell, table_header};
+use crate::app::App;
+use crate::config::ProjectColumn;
+use crate::goal::GoalState;
 
-pub fn build_projects_text(app: &App) -> Text<'_> {
+/// Characters of the goal progress bars.
+const GOAL_BAR_WIDTH: usize = 10;
+
+/// Color of a goal's progress: a warning while behind, an alert once over.
+pub(super) fn goal_color(state: GoalState) -> Color {
+    match state {
+        GoalState::Behind => Theme::warn(),
+        GoalState::Over => Theme::danger(),
+        GoalState::OnTrack | GoalState::Reached => Theme::success(),
+    }
+}
+
+/// How the project lists of `app` group sub-projects.
+pub(super) fn project_tree(app: &App) -> ProjectTree<'_> {
+    ProjectTree {
+        sub_projects: &app.sub_projects,
+        collapsed: &app.collapsed_projects,
+    }
+}
+
+pub fn render_projects(frame: &mut Frame, area: Rect, app: &mut App) {
+    if let Some(text) = projects_placeholder(app) {
+        frame.render_widget(Paragraph::new(text), area);
+        return;
+    }
+    let mut state = std::mem::take(&mut app.projects_table_state);
+    state.select(Some(app.selected_project_index));
+    render_scrolling_table(
+        frame,
+        area,
+        projects_heading(app),
+        projects_table(app),
+        app.projects.len(),
+        &mut state,
+    );
+    app.projects_table_state = state;
+}
+
+fn projects_placeholder(app: &App) -> Option<Text<'_>> {
     if let Some(status) = &app.status {
-        return Text::from(status.as_str());
+        return Some(Text::from(status.as_str()));
     }
     if app.projects.is_empty() {
         if app.projects_search_query.trim().is_empty() {
-            return Text::from("No projects found. Press 'r' to refresh.");
+            return Some(Text::from("No projects found. Press 'r' to refresh."));
         }
-        return Text::from(format!(
+        return Some(Text::from(format!(
             "No projects match \"{}\".",
             app.projects_search_query.trim()
-        ));
+        )));
     }
-    let mut lines = Vec::new();
+    None
+}
+
+fn projects_heading(app: &App) -> Vec<Line<'_>> {
     let search_style = if app.projects_search_active {
         Style::default()
             .fg(Theme::highlight())
@@ -34,65 +82,90 @@ pub fn build_projects_text(app: &App) -> Text<'_> {
     } else {
         app.projects_search_query.trim()
     };
-    lines.push(Line::from(vec![
-        Span::styled("  Search: ", Style::default().fg(Theme::dim())),
-        Span::styled(search_value, search_style),
-    ]));
-    lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(
-        format!(
-            "  {:<24} {:>8} {:>5} {:>5}",
-            "Project", "Total", "End", "Open"
-        ),
-        Style::default()
-            .fg(Theme::secondary())
-            .add_modifier(Modifier::BOLD),
-    )));
-    lines.push(Line::from(Span::styled(
-        format!(
-            "  {:<24} {:>8} {:>5} {:>5}",
-            "------------------------", "--------", "-----", "-----"
-        ),
-        Style::default().fg(Theme::dim()),
-    )));
-    let project_lines = app
-        .projects
-        .iter()
-        .enumerate()
-        .map(|(index, project)| {
-            let summary = app.project_summary_for(project);
-            let name = clamp_name(project.name.as_str(), 24);
-            let total = format_duration(Duration::seconds(summary.total_seconds.max(0)));
-            let total_text = format!("{:>8}", total);
-            let ended_text = format!("{:>5}", summary.ended);
-            let open_text = format!("{:>5}", summary.open);
-            let selected = index == app.selected_project_index;
-            let name_style = if selected {
-                Style::default()
-                    .fg(Theme::highlight())
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-            };
-            let marker_style = if selected {
-                Style::default().fg(Theme::selection_marker())
-            } else {
-                Style::default().fg(Theme::dim())
-            };
-            Line::from(vec![
-                Span::styled(if selected { "> " } else { "  " }, marker_style),
-                Span::styled(name, name_style),
-                Span::raw(" "),
-                Span::styled(total_text, Style::default().fg(Theme::accent())),
-                Span::raw(" "),
-                Span::styled(ended_text, Style::default().fg(Theme::success())),
-                Span::raw(" "),
-                Span::styled(open_text, Style::default().fg(Theme::warn())),
-            ])
+    vec![
+        Line::from(vec![
+            Span::styled("  Search: ", Style::default().fg(Theme::dim())),
+            Span::styled(search_value, search_style),
+        ]),
+        Line::from(""),
+    ]
+}
+
+fn projects_table(app: &App) -> Table<'_> {
+    let config = &app.config.columns.projects;
+    let columns: Vec<ProjectColumn> = ProjectColumn::ALL
+        .into_iter()
+        .filter(|column| config.shows(*column))
+        .filter(|column| *column != ProjectColumn::Goal || !app.project_goals.is_empty())
+        .filter(|column| {
+            *column != ProjectColumn::Client
+                || app
+                    .projects
+                    .iter()
+                    .any(|project| project.client_id.is_some())
         })
-        .collect::<Vec<_>>();
-    lines.extend(project_lines);
-    Text::from(lines)
+        .collect();
+
+    let rows = view_model::project_rows(
+        &app.projects,
+        project_tree(app),
+        &app.project_summaries,
+        &app.project_goals,
+        &app.clients,
+    );
+    let rows = rows.into_iter().map(|row| {
+        let summary = row.summary;
+        let cells = columns.iter().map(|column| match column {
+            ProjectColumn::Project => project_name_cell(&row.name),
+            ProjectColumn::Total => right_cell(Span::styled(
+                format_duration(Duration::seconds(summary.total_seconds.max(0))),
+                Style::default().fg(Theme::accent()),
+            )),
+            ProjectColumn::End => right_cell(Span::styled(
+                summary.ended.to_string(),
+                Style::default().fg(Theme::success()),
+            )),
+            ProjectColumn::Open => right_cell(Span::styled(
+                summary.open.to_string(),
+                Style::default().fg(Theme::warn()),
+            )),
+            ProjectColumn::Goal => match row.goal {
+                Some(progress) => Cell::from(Span::styled(
+                    format!(
+                        "{} {:>3.0}%",
+                        progress.bar(GOAL_BAR_WIDTH),
+                        progress.fraction() * 100.0
+                    ),
+                    Style::default().fg(goal_color(progress.state)),
+                )),
+                None => Cell::from(""),
+            },
+            ProjectColumn::Client => Cell::from(Span::styled(
+                row.client.unwrap_or_default(),
+                Style::default().fg(Theme::secondary()),
+            )),
+        });
+        Row::new(cells.collect::<Vec<_>>())
+    });
+    let widths = columns.iter().map(|column| match config.width(*column) {
+        Some(width) => Constraint::Length(width),
+        None => match column {
+            ProjectColumn::Project => Constraint::Fill(1),
+            ProjectColumn::Total => Constraint::Length(8),
+            ProjectColumn::End | ProjectColumn::Open => Constraint::Length(5),
+            ProjectColumn::Goal => Constraint::Length(GOAL_BAR_WIDTH as u16 + 5),
+            ProjectColumn::Client => Constraint::Length(16),
+        },
+    });
+    let titles = columns.iter().map(|column| match column {
+        ProjectColumn::Project => Cell::from("Project"),
+        ProjectColumn::Total => right_cell("Total"),
+        ProjectColumn::End => right_cell("End"),
+        ProjectColumn::Open => right_cell("Open"),
+        ProjectColumn::Goal => Cell::from("Goal"),
+        ProjectColumn::Client => Cell::from("Client"),
+    });
+    Table::new(rows, widths).header(table_header(titles))
 }
 
 pub fn build_project_tickr_title(app: &App) -> &str {
@@ -102,65 +175,105 @@ pub fn build_project_tickr_title(app: &App) -> &str {
     &project.name
 }
 
-pub fn build_worked_projects_text(app: &App) -> Text<'_> {
+pub fn render_worked_projects(frame: &mut Frame, area: Rect, app: &mut App) {
+    if let Some(text) = worked_projects_placeholder(app) {
+        frame.render_widget(Paragraph::new(text), area);
+        return;
+    }
+    let mut state = std::mem::take(&mut app.worked_projects_table_state);
+    state.select(Some(app.selected_worked_project_index));
+    render_scrolling_table(
+        frame,
+        area,
+        worked_projects_heading(app),
+        worked_projects_table(app),
+        app.worked_projects.len(),
+        &mut state,
+    );
+    app.worked_projects_table_state = state;
+}
+
+fn worked_projects_placeholder(app: &App) -> Option<Text<'_>> {
     if let Some(status) = &app.status {
-        return Text::from(status.as_str());
+        return Some(Text::from(status.as_str()));
     }
     if app.worked_projects.is_empty() {
-        let label = worked_range_label(app.worked_range);
-        return Text::from(format!("No projects worked on {label}."));
+        let mut lines = worked_projects_heading(app);
+        lines.push(Line::from(format!(
+            "  No projects worked on {}.",
+            worked_range_label(app)
+        )));
+        return Some(Text::from(lines));
     }
+    None
+}
 
-    let mut lines = Vec::new();
-    lines.push(Line::from(Span::styled(
-        format!("  Worked on: {}", worked_range_label(app.worked_range)),
-        Style::default()
-            .fg(Theme::secondary())
-            .add_modifier(Modifier::BOLD),
-    )));
-    lines.push(Line::from(Span::styled(
-        format!("  {:<28}", "Project"),
-        Style::default()
-            .fg(Theme::secondary())
-            .add_modifier(Modifier::BOLD),
-    )));
-    lines.push(Line::from(Span::styled(
-        format!("  {:<28}", "----------------------------"),
-        Style::default().fg(Theme::dim()),
-    )));
-
-    let project_lines = app
-        .worked_projects
-        .iter()
-        .enumerate()
-        .map(|(index, project)| {
-            let name = clamp_name(project.name.as_str(), 28);
-            let selected = index == app.selected_worked_project_index;
-            let name_style = if selected {
-                Style::default()
-                    .fg(Theme::highlight())
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-            };
-            let marker_style = if selected {
-                Style::default().fg(Theme::selection_marker())
-            } else {
-                Style::default().fg(Theme::dim())
-            };
-            Line::from(vec![
-                Span::styled(if selected { "> " } else { "  " }, marker_style),
-                Span::styled(name, name_style),
-            ])
-        })
-        .collect::<Vec<_>>();
-    lines.extend(project_lines);
-    Text::from(lines)
+fn worked_projects_heading(app: &App) -> Vec<Line<'static>> {
+    vec![
+        super::range_selector_line(&app.worked_range),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("  Worked on: {}", worked_range_label(app)),
+            Style::default()
+                .fg(Theme::secondary())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ]
 }
 
-fn worked_range_label(range: WorkedRange) -> &'static str {
-    match range {
-        WorkedRange::Today => "today",
-        WorkedRange::Week => "this week",
+fn worked_projects_table(app: &App) -> Table<'_> {
+    let rows = view_model::worked_project_rows(
+        &app.worked_projects,
+        project_tree(app),
+        &app.worked_seconds,
+    );
+    let rows = rows.into_iter().map(|row| {
+        Row::new(vec![
+            project_name_cell(&row.name),
+            right_cell(Span::styled(
+                format_duration(row.worked),
+                Style::default().fg(Theme::accent()),
+            )),
+        ])
+    });
+    Table::new(rows, [Constraint::Fill(1), Constraint::Length(8)])
+        .header(table_header([Cell::from("Project"), right_cell("Time")]))
+}
+
+/// Project name with the tree marker of a parent project, indented for sub-projects.
+fn project_name_cell<'a>(name: &ProjectName<'a>) -> Cell<'a> {
+    let dim = Style::default().fg(Theme::dim());
+    let mut spans = Vec::new();
+    match name.marker {
+        TreeMarker::Child => spans.push(Span::raw("    ")),
+        TreeMarker::Leaf => spans.push(Span::raw("  ")),
+        TreeMarker::Parent { collapsed, .. } => {
+            let marker = if collapsed { "▸ " } else { "▾ " };
+            spans.push(Span::styled(
+                marker,
+                Style::default().fg(Theme::secondary()),
+            ));
+        }
+        TreeMarker::Flat => {}
+    }
+    if name.archived {
+        spans.push(Span::styled(name.name, dim));
+        spans.push(Span::styled("  archived", dim));
+    } else {
+        spans.push(Span::raw(name.name));
     }
+    if let TreeMarker::Parent {
+        collapsed: true,
+        children,
+    } = name.marker
+    {
+        spans.push(Span::styled(format!("  +{children}"), dim));
+    }
+    Cell::from(Line::from(spans))
+}
+
+fn worked_range_label(app: &App) -> String {
+    let today = Local::now().date_naive();
+    app.worked_range.describe(today, &app.config)
 }
@@ -0,0 +1,74 @@
+/// Humanized times like "5m ago", "yesterday" or "last Tue". The distance is worked out once as a
+/// [`RelativeTime`] and only then put into words, so another language needs nothing but another
+/// [`RelativeWords`].
+use chrono::{DateTime, Datelike, Local, NaiveDate, Weekday};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelativeTime {
+    /// Less than a minute ago, or in the future when clocks disagree.
+    JustNow,
+    MinutesAgo(i64),
+    /// Earlier today.
+    HoursAgo(i64),
+    Yesterday,
+    /// Within the last week.
+    LastWeekday(Weekday),
+    /// A week or more ago.
+    OnDate(NaiveDate),
+}
+
+/// Words of one language. Counts replace `{n}` and weekday names replace `{day}`.
+pub struct RelativeWords {
+    pub just_now: &'static str,
+    pub minutes_ago: &'static str,
+    pub hours_ago: &'static str,
+    pub yesterday: &'static str,
+    pub last_weekday: &'static str,
+    /// Monday first.
+    pub weekdays: [&'static str; 7],
+}
+
+impl RelativeWords {
+    pub const ENGLISH: Self = Self {
+        just_now: "just now",
+        minutes_ago: "{n}m ago",
+        hours_ago: "{n}h ago",
+        yesterday: "yesterday",
+        last_weekday: "last {day}",
+        weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    };
+}
+
+impl RelativeTime {
+    pub fn between(then: DateTime<Local>, now: DateTime<Local>) -> Self {
+        let minutes = (now - then).num_minutes();
+        let days = (now.date_naive() - then.date_naive()).num_days();
+        match days {
+            _ if minutes < 1 => Self::JustNow,
+            _ if minutes < 60 => Self::MinutesAgo(minutes),
+            0 => Self::HoursAgo(minutes / 60),
+            1 => Self::Yesterday,
+            2..=6 => Self::LastWeekday(then.weekday()),
+            _ => Self::OnDate(then.date_naive()),
+        }
+    }
+
+    /// Puts the time into `words`, dates in `date_format`.
+    pub fn label(self, words: &RelativeWords, date_format: &str) -> String {
+        match self {
+            Self::JustNow => words.just_now.to_string(),
+            Self::MinutesAgo(n) => words.minutes_ago.replace("{n}", &n.to_string()),
+            Self::HoursAgo(n) => words.hours_ago.replace("{n}", &n.to_string()),
+            Self::Yesterday => words.yesterday.to_string(),
+            Self::LastWeekday(day) => words
+                .last_weekday
+                .replace("{day}", words.weekdays[day.num_days_from_monday() as usize]),
+            Self::OnDate(date) => date.format(date_format).to_string(),
+        }
+    }
+}
+
+/// `then` relative to now in English, e.g. "2h ago".
+pub fn format_relative(then: DateTime<Local>, date_format: &str) -> String {
+    RelativeTime::between(then, Local::now()).label(&RelativeWords::ENGLISH, date_format)
+}
@@ -0,0 +1,315 @@
+use chrono::{Duration, Local};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Bar, BarChart, BarGroup},
+};
+
+use super::helpers::{clamp_name, format_duration};
+use super::render_text;
+use super::theme::Theme;
+use super::view_model::{self, MonthForecast};
+use crate::app::{App, WeekSummary};
+
+/// Rows of the weekly bar chart, including the day and total labels below the bars.
+const CHART_HEIGHT: u16 = 12;
+/// Fewer rows than this leave too little room for the bars to compare days.
+const MIN_CHART_HEIGHT: u16 = 6;
+/// Columns between the bars of two days.
+const DAY_GAP: u16 = 2;
+
+/// This week's bars above the month forecast, or only the forecast when space is short.
+pub fn render_stats(frame: &mut Frame, area: Rect, app: &App) {
+    let week = &app.week_summary;
+    let Some(week_start) = week.start.filter(|_| app.status.is_none()) else {
+        render_text(frame, area, build_stats_text(app));
+        return;
+    };
+    let chart_height = (area.height / 2).min(CHART_HEIGHT);
+    if chart_height < MIN_CHART_HEIGHT {
+        render_text(frame, area, build_stats_text(app));
+        return;
+    }
+    let [heading_area, chart_area, legend_area, month_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Length(chart_height),
+        Constraint::Length(3),
+        Constraint::Min(0),
+    ])
+    .areas(area);
+
+    let average = view_model::week_average(week, week_start, Local::now().date_naive());
+
+    render_text(
+        frame,
+        heading_area,
+        Text::from(Line::from(vec![
+            Span::styled(
+                format!("  Week of {}", week_start.format("%B %-d")),
+                Style::default()
+                    .fg(Theme::secondary())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" to {}", (week_start + Duration::days(6)).format("%B %-d")),
+                Style::default().fg(Theme::dim()),
+            ),
+        ])),
+    );
+    let chart_area = Rect {
+        x: chart_area.x + 2,
+        width: chart_area.width.saturating_sub(2),
+        ..chart_area
+    };
+    render_week_chart(frame, chart_area, week, average);
+    render_text(frame, legend_area, week_legend(week, average));
+    render_text(frame, month_area, build_stats_text(app));
+}
+
+/// One bar per day with the total below it, colored by project from the bottom up, and a
+/// dashed line at the daily average.
+fn render_week_chart(frame: &mut Frame, area: Rect, week: &WeekSummary, average: i64) {
+    let Some(week_start) = week.start else {
+        return;
+    };
+    let bar_width = (area.width.saturating_sub(6 * DAY_GAP) / 7).clamp(1, 10);
+    let day_format = if bar_width >= 6 { "%a %d" } else { "%a" };
+    // Whole minutes keep the values small and the bar heights in step with the segments.
+    let minutes = |seconds: i64| (seconds.max(0) / 60) as u64;
+    let max = (0..7)
+        .map(|day| minutes(week.day_total(day)))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut chart = BarChart::default()
+        .bar_width(bar_width)
+        .bar_gap(0)
+        .group_gap(DAY_GAP)
+        .bar_style(Style::default().fg(Theme::dim()))
+        .label_style(Style::default().fg(Theme::dim()))
+        .max(max);
+    for day in 0..7 {
+        let seconds = week.day_total(day);
+        let date = week_start + Duration::days(day as i64);
+        let bar = Bar::default()
+            .value(minutes(seconds))
+            .text_value(String::new())
+            .label(Line::from(short_duration(seconds)));
+        chart = chart.data(
+            BarGroup::default()
+                .label(Line::from(date.format(day_format).to_string()))
+                .bars(&[bar]),
+        );
+    }
+    frame.render_widget(chart, area);
+
+    // The chart keeps two rows for the day and total labels below the bars.
+    let bars_height = area.height.saturating_sub(2);
+    if bars_height == 0 {
+        return;
+    }
+    let ticks = |value: u64| value * u64::from(bars_height) * 8 / max;
+    let bottom = area.y + bars_height - 1;
+    let buf = frame.buffer_mut();
+    for (day, seconds) in week.days.iter().enumerate() {
+        let bar_x = area.x + day as u16 * (bar_width + DAY_GAP);
+        let mut upper = Vec::with_capacity(seconds.len());
+        let mut sum = 0;
+        for (project, seconds) in week.projects.iter().zip(seconds) {
+            sum += seconds;
+            if *seconds > 0 {
+                upper.push((ticks(minutes(sum)), Theme::project(project.0)));
+            }
+        }
+        let Some(&(top, top_color)) = upper.last() else {
+            continue;
+        };
+        for row in 0..bars_height {
+            let cell_start = u64::from(row) * 8;
+            if cell_start >= top {
+                break;
+            }
+            let color = upper
+                .iter()
+                .find(|(upper, _)| *upper > cell_start + 4)
+                .map_or(top_color, |(_, color)| *color);
+            for x in bar_x..(bar_x + bar_width).min(area.right()) {
+                buf[(x, bottom - row)].set_fg(color);
+            }
+        }
+    }
+
+    let average = minutes(average);
+    if average > 0 {
+        let row = (ticks(average) / 8).min(u64::from(bars_height) - 1) as u16;
+        let width = (7 * bar_width + 6 * DAY_GAP).min(area.width);
+        for x in area.x..area.x + width {
+            let cell = &mut buf[(x, bottom - row)];
+            if cell.symbol() == " " {
+                cell.set_symbol("╌").set_fg(Theme::warn());
+            }
+        }
+    }
+}
+
+/// Project colors with their weekly totals, then the week's total and daily average.
+fn week_legend(week: &WeekSummary, average: i64) -> Text<'_> {
+    let mut projects = vec![Span::raw("  ")];
+    let totals = view_model::week_project_totals(week);
+    for ((id, name), seconds) in week.projects.iter().zip(totals) {
+        projects.push(Span::styled("■ ", Style::default().fg(Theme::project(*id))));
+        projects.push(Span::styled(
+            format!("{} ", clamp_name(name, 20).trim_end()),
+            Style::default().fg(Theme::text()),
+        ));
+        projects.push(Span::styled(
+            format!("{}   ", short_duration(seconds)),
+            Style::default().fg(Theme::dim()),
+        ));
+    }
+    if week.projects.is_empty() {
+        projects.push(Span::styled(
+            "No time tracked this week.",
+            Style::default().fg(Theme::dim()),
+        ));
+    }
+    let value = |seconds: i64| {
+        Span::styled(
+            format_duration(Duration::seconds(seconds)),
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    Text::from(vec![
+        Line::from(projects),
+        Line::from(vec![
+            Span::styled("  Week total  ", Style::default().fg(Theme::dim())),
+            value(week.total()),
+            Span::styled("   Daily average  ", Style::default().fg(Theme::dim())),
+            value(average),
+            Span::styled(" ╌", Style::default().fg(Theme::warn())),
+        ]),
+    ])
+}
+
+/// `3:05` for three hours and five minutes, empty for no time.
+fn short_duration(seconds: i64) -> String {
+    if seconds <= 0 {
+        return String::new();
+    }
+    format!("{}:{:02}", seconds / 3600, seconds % 3600 / 60)
+}
+
+pub fn build_stats_text(app: &App) -> Text<'_> {
+    if let Some(status) = &app.status {
+        return Text::from(status.as_str());
+    }
+    let forecast = MonthForecast::new(&app.month_report, Local::now());
+    let label =
+        |name: &str| Span::styled(format!("  {name:<16}"), Style::default().fg(Theme::dim()));
+    let value = |text: String| {
+        Span::styled(
+            text,
+            Style::default()
+                .fg(Theme::accent())
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "  {} (day {} of {})",
+                forecast.month_start.format("%B %Y"),
+                forecast.day,
+                forecast.days_in_month
+            ),
+            Style::default()
+                .fg(Theme::secondary())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            label("Tracked so far"),
+            value(format_duration(Duration::seconds(forecast.tracked_seconds))),
+        ]),
+    ];
+    if !forecast.amounts.is_empty() {
+        lines.push(Line::from(vec![
+            label("Earned so far"),
+            value(forecast.amounts.to_string()),
+        ]));
+    }
+    lines.push(Line::from(""));
+
+    if !forecast.has_pace() {
+        lines.push(Line::from(Span::styled(
+            "  Too early in the month for a forecast.",
+            Style::default().fg(Theme::dim()),
+        )));
+    } else {
+        let projected = forecast.projected_hours();
+        lines.push(Line::from(vec![
+            label("Forecast hours"),
+            value(format!("{projected:.1} h")),
+        ]));
+        if !forecast.amounts.is_empty() {
+            lines.push(Line::from(vec![
+                label("Forecast earned"),
+                value(forecast.projected_amounts().to_string()),
+            ]));
+        }
+        if let Some(goal) = forecast.goal(app.config.monthly_goal_hours) {
+            let (difference, hours) = (goal.difference, goal.goal_hours);
+            let (text, color) = if difference >= 0.0 {
+                (
+                    format!("{hours:.1} h, on track ({difference:+.1} h)"),
+                    Theme::success(),
+                )
+            } else {
+                (
+                    format!("{hours:.1} h, behind ({difference:+.1} h)"),
+                    Theme::warn(),
+                )
+            };
+            lines.push(Line::from(vec![
+                label("Monthly goal"),
+                Span::styled(text, Style::default().fg(color)),
+            ]));
+            if let Some((per_day, days)) = goal.needed_per_day {
+                lines.push(Line::from(vec![
+                    label("Needed per day"),
+                    value(format!("{per_day:.1} h for the remaining {days:.0} days")),
+                ]));
+            }
+        }
+    }
+
+    if !app.month_report.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "  Projects this month",
+            Style::default()
+                .fg(Theme::secondary())
+                .add_modifier(Modifier::BOLD),
+        )));
+        for row in &app.month_report {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {}  ", clamp_name(&row.label, 24)),
+                    Style::default().fg(Theme::text()),
+                ),
+                Span::styled(
+                    format_duration(Duration::seconds(row.seconds)),
+                    Style::default().fg(Theme::accent()),
+                ),
+            ]));
+        }
+    }
+
+    Text::from(lines)
+}
@@ -1,66 +1,212 @@
+use std::sync::OnceLock;
+
 use ratatui::style::Color;
 
+use crate::config::ThemeName;
+use crate::types::ProjectId;
+
+/// Colors of one theme, picked once at startup with [`Theme::init`].
+struct Palette {
+    primary: Color,
+    secondary: Color,
+    success: Color,
+    active: Color,
+    warn: Color,
+    danger: Color,
+    ended: Color,
+    highlight: Color,
+    selection_marker: Color,
+    dim: Color,
+    text: Color,
+    accent: Color,
+    /// Text on the colored title, badges and selected tabs.
+    badge_text: Color,
+    /// Colors told apart in charts, one per project.
+    projects: [Color; 6],
+    /// Background and text of the calendar heatmap, from little to much tracked time.
+    heat: [(Color, Color); 4],
+}
+
+const DARK: Palette = Palette {
+    primary: Color::Magenta,
+    secondary: Color::Cyan,
+    success: Color::Green,
+    active: Color::LightGreen,
+    warn: Color::Yellow,
+    danger: Color::Red,
+    ended: Color::Blue,
+    highlight: Color::Cyan,
+    selection_marker: Color::Green,
+    dim: Color::DarkGray,
+    text: Color::White,
+    accent: Color::LightBlue,
+    badge_text: Color::Black,
+    projects: [
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightGreen,
+        Color::Yellow,
+        Color::LightCyan,
+        Color::LightRed,
+    ],
+    heat: [
+        (Color::Rgb(14, 68, 41), Color::White),
+        (Color::Rgb(0, 109, 50), Color::White),
+        (Color::Rgb(38, 166, 65), Color::Black),
+        (Color::Rgb(57, 211, 83), Color::Black),
+    ],
+};
+
+/// The dark theme's colors, with text in the terminal's own foreground color and dimmed text in
+/// a gray that reads on dark and light backgrounds alike.
+const TERMINAL: Palette = Palette {
+    dim: Color::Rgb(128, 128, 128),
+    text: Color::Reset,
+    ..DARK
+};
+
+const LIGHT: Palette = Palette {
+    primary: Color::Magenta,
+    secondary: Color::Blue,
+    success: Color::Green,
+    active: Color::Green,
+    warn: Color::Rgb(176, 112, 0),
+    danger: Color::Red,
+    ended: Color::Blue,
+    highlight: Color::Blue,
+    selection_marker: Color::Green,
+    dim: Color::Gray,
+    text: Color::Black,
+    accent: Color::Blue,
+    badge_text: Color::Black,
+    projects: [
+        Color::Blue,
+        Color::Magenta,
+        Color::Green,
+        Color::Rgb(176, 112, 0),
+        Color::Cyan,
+        Color::Red,
+    ],
+    heat: [
+        (Color::Rgb(155, 233, 168), Color::Black),
+        (Color::Rgb(64, 196, 99), Color::Black),
+        (Color::Rgb(48, 161, 78), Color::White),
+        (Color::Rgb(33, 110, 57), Color::White),
+    ],
+};
+
+const MONO: Palette = Palette {
+    primary: Color::Reset,
+    secondary: Color::Reset,
+    success: Color::Reset,
+    active: Color::Reset,
+    warn: Color::Reset,
+    danger: Color::Reset,
+    ended: Color::Reset,
+    highlight: Color::Reset,
+    selection_marker: Color::Reset,
+    dim: Color::Reset,
+    text: Color::Reset,
+    accent: Color::Reset,
+    badge_text: Color::Reset,
+    projects: [Color::Reset; 6],
+    heat: [(Color::Reset, Color::Reset); 4],
+};
+
+static PALETTE: OnceLock<&'static Palette> = OnceLock::new();
+
 /// Unified color theme for the application
 pub struct Theme;
 
 impl Theme {
+    /// Selects the palette; later calls are ignored.
+    pub fn init(name: ThemeName) {
+        PALETTE.get_or_init(|| match name {
+            ThemeName::Terminal => &TERMINAL,
+            ThemeName::Dark => &DARK,
+            ThemeName::Light => &LIGHT,
+            ThemeName::Mono => &MONO,
+        });
+    }
+
+    fn palette() -> &'static Palette {
+        PALETTE.get().copied().unwrap_or(&TERMINAL)
+    }
+
     /// Primary branding color
     pub fn primary() -> Color {
-        Color::Magenta
+        Self::palette().primary
     }
 
     /// Secondary/border color
     pub fn secondary() -> Color {
-        Color::Cyan
+        Self::palette().secondary
     }
 
     /// Success/completed status
     pub fn success() -> Color {
-        Color::Green
+        Self::palette().success
     }
 
     /// Running/active status
     pub fn active() -> Color {
-        Color::LightGreen
+        Self::palette().active
     }
 
     /// Warning/pending status
     pub fn warn() -> Color {
-        Color::Yellow
+        Self::palette().warn
     }
 
     /// Destructive/danger status
     pub fn danger() -> Color {
-        Color::Red
+        Self::palette().danger
     }
 
     /// Error/ended status
     pub fn ended() -> Color {
-        Color::Blue
+        Self::palette().ended
     }
 
     /// Selection/highlight
     pub fn highlight() -> Color {
-        Color::Cyan
+        Self::palette().highlight
     }
 
     /// Selection marker/arrow
     pub fn selection_marker() -> Color {
-        Color::Green
+        Self::palette().selection_marker
     }
 
     /// Dimmed/inactive text
     pub fn dim() -> Color {
-        Color::DarkGray
+        Self::palette().dim
     }
 
     /// Normal text
     pub fn text() -> Color {
-        Color::White
+        Self::palette().text
     }
 
     /// Accent for numbers/counts
     pub fn accent() -> Color {
-        Color::LightBlue
+        Self::palette().accent
+    }
+
+    /// Text on a colored background
+    pub fn badge_text() -> Color {
+        Self::palette().badge_text
+    }
+
+    /// Chart color of a project, repeating after a few projects
+    pub fn project(id: ProjectId) -> Color {
+        let colors = &Self::palette().projects;
+        colors[id as usize % colors.len()]
+    }
+
+    /// Heatmap background and text for `level` 1 (little) to 4 (much)
+    pub fn heat(level: usize) -> (Color, Color) {
+        let heat = &Self::palette().heat;
+        heat[level.clamp(1, heat.len()) - 1]
     }
 }
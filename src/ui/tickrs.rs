@@ -1,69 +1,193 @@
-use chrono::{Duration, Local};
 use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
+    widgets::{Cell, Paragraph, Row, Table},
 };
+use unicode_width::UnicodeWidthStr;
 
 use super::helpers::{format_duration, hex_to_color};
+use super::relative::format_relative;
 use super::theme::Theme;
-use crate::app::App;
+use super::view_model::{self, TaskState};
+use super::{render_scrolling_table, right_cell, table_header};
+use crate::app::{App, AppView, TickrFilter};
+use crate::config::TickrColumn;
 
-pub fn build_tickrs_text(app: &App, show_selection: bool) -> Text<'_> {
+/// Below this width the category, interval and last worked columns are left out.
+const WIDE_LAYOUT_WIDTH: u16 = 72;
+const MAX_CATEGORY_WIDTH: usize = 16;
+
+pub fn render_tickrs(frame: &mut Frame, area: Rect, app: &mut App) {
+    if let Some(text) = tickrs_placeholder(app) {
+        frame.render_widget(Paragraph::new(text), area);
+        return;
+    }
+    let mut state = std::mem::take(&mut app.tickrs_table_state);
+    state.select(Some(app.selected_tickr_index));
+    let heading = if app.view == AppView::ProjectTickrs {
+        project_tickrs_heading(app)
+    } else {
+        Vec::new()
+    };
+    let table = tickrs_table(app, area.width >= WIDE_LAYOUT_WIDTH);
+    render_scrolling_table(frame, area, heading, table, app.tickrs.len(), &mut state);
+    app.tickrs_table_state = state;
+}
+
+fn tickrs_placeholder(app: &App) -> Option<Text<'_>> {
     if let Some(status) = &app.status {
-        return Text::from(status.as_str());
+        return Some(Text::from(status.as_str()));
     }
     if app.tickrs.is_empty() {
-        return Text::from("No tickrs found. Press 'r' to refresh.");
+        if app.view == AppView::Tickrs && app.tickr_filter != TickrFilter::All {
+            return Some(Text::from(format!(
+                "No time tracked {}. Press 'f' to show more.",
+                app.tickr_filter.label()
+            )));
+        }
+        if app.view == AppView::ProjectTickrs && project_tickrs_filtered(app) {
+            let mut text = project_tickrs_heading(app);
+            text.push(Line::from(
+                "No tasks of this project match. Press '/' or 'f' to change the filter.",
+            ));
+            return Some(Text::from(text));
+        }
+        return Some(Text::from("No tickrs found. Press 'r' to refresh."));
     }
-    let lines = app
-        .tickrs
-        .iter()
-        .enumerate()
-        .map(|(index, tickr)| {
-            let intervals = &tickr.intervals;
-            let interval_text = if intervals.is_empty() {
-                "0 intervals, --:--:--".to_string()
-            } else {
-                let now = Local::now();
-                let total_duration =
-                    intervals
-                        .iter()
-                        .fold(Duration::seconds(0), |acc, interval| {
-                            let end_time = interval.end_time.unwrap_or(now);
-                            acc + end_time.signed_duration_since(interval.start_time)
-                        });
-                let elapsed = format_duration(total_duration);
-                let count = intervals.len();
-                let label = if count == 1 { "interval" } else { "intervals" };
-                format!("{count} {label}, {elapsed}")
-            };
-            let selected = show_selection && index == app.selected_tickr_index;
-            let line_style = if selected {
-                Style::default()
-                    .fg(Theme::highlight())
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-            };
-            let marker_style = if selected {
-                Style::default().fg(Theme::selection_marker())
-            } else {
-                Style::default().fg(Theme::dim())
-            };
-            let mut spans = vec![
-                Span::styled(if selected { "> " } else { "  " }, marker_style),
-                Span::styled(format!("[{interval_text}] "), line_style),
-            ];
-            if let Some(category) = app.category_for_tickr(tickr) {
-                let cat_color = hex_to_color(&category.color).unwrap_or(Color::Magenta);
-                spans.push(Span::styled(
-                    format!("[{}] ", category.name),
-                    Style::default().fg(cat_color).add_modifier(Modifier::BOLD),
-                ));
-            }
-            spans.push(Span::styled(&tickr.description, line_style));
-            Line::from(spans)
+    None
+}
+
+fn project_tickrs_filtered(app: &App) -> bool {
+    !app.project_tickrs_search_query.trim().is_empty() || app.project_tickrs_category.is_some()
+}
+
+/// The search and the category filter above the tasks of a project.
+fn project_tickrs_heading(app: &App) -> Vec<Line<'_>> {
+    let search_style = if app.project_tickrs_search_active {
+        Style::default()
+            .fg(Theme::highlight())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Theme::dim())
+    };
+    let query = app.project_tickrs_search_query.trim();
+    let search_value = if query.is_empty() { "(none)" } else { query };
+    let category = app
+        .project_tickrs_category
+        .and_then(|id| app.categories.get(&id));
+    let category_span = match category {
+        Some(category) => Span::styled(
+            category.name.as_str(),
+            Style::default()
+                .fg(hex_to_color(&category.color).unwrap_or(Color::Magenta))
+                .add_modifier(Modifier::BOLD),
+        ),
+        None => Span::styled("all", Style::default().fg(Theme::dim())),
+    };
+    vec![
+        Line::from(vec![
+            Span::styled("  Search: ", Style::default().fg(Theme::dim())),
+            Span::styled(search_value, search_style),
+            Span::styled("  Category: ", Style::default().fg(Theme::dim())),
+            category_span,
+        ]),
+        Line::from(""),
+    ]
+}
+
+fn tickrs_table(app: &App, wide: bool) -> Table<'_> {
+    let config = &app.config.columns.tickrs;
+    let columns: Vec<TickrColumn> = TickrColumn::ALL
+        .into_iter()
+        .filter(|column| config.shows(*column))
+        .filter(|column| {
+            wide || !matches!(
+                column,
+                TickrColumn::Category | TickrColumn::Intervals | TickrColumn::Last
+            )
         })
-        .collect::<Vec<_>>();
-    Text::from(lines)
+        .collect();
+    let rows = view_model::task_rows(&app.tickrs, &app.categories, app.now());
+    let category_width = rows
+        .iter()
+        .filter_map(|row| row.category)
+        .map(|category| category.name.width())
+        .max()
+        .unwrap_or(0)
+        .clamp("Category".len(), MAX_CATEGORY_WIDTH) as u16;
+
+    let titles = columns.iter().map(|column| match column {
+        TickrColumn::Task => Cell::from("Task"),
+        TickrColumn::State => Cell::from("State"),
+        TickrColumn::Category => Cell::from("Category"),
+        TickrColumn::Intervals => right_cell("Intervals"),
+        TickrColumn::Last => right_cell("Last"),
+        TickrColumn::Time => right_cell("Time"),
+    });
+    let widths = columns.iter().map(|column| match config.width(*column) {
+        Some(width) => Constraint::Length(width),
+        None => match column {
+            TickrColumn::Task => Constraint::Fill(1),
+            TickrColumn::State | TickrColumn::Time => Constraint::Length(8),
+            TickrColumn::Category => Constraint::Length(category_width),
+            TickrColumn::Intervals => Constraint::Length(9),
+            TickrColumn::Last => Constraint::Length(10),
+        },
+    });
+
+    let rows = rows.into_iter().map(|row| {
+        let cells = columns.iter().map(|column| match column {
+            TickrColumn::Task => Cell::from(row.description),
+            TickrColumn::State => state_cell(row.state),
+            TickrColumn::Category => match row.category {
+                Some(category) => {
+                    let color = hex_to_color(&category.color).unwrap_or(Color::Magenta);
+                    Cell::from(Span::styled(
+                        category.name.as_str(),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ))
+                }
+                None => Cell::from(""),
+            },
+            TickrColumn::Intervals => right_cell(Span::styled(
+                row.intervals.to_string(),
+                Style::default().fg(Theme::dim()),
+            )),
+            TickrColumn::Last => right_cell(Span::styled(
+                row.last_worked
+                    .map(|last| format_relative(last, &app.config.date_format))
+                    .unwrap_or_default(),
+                Style::default().fg(Theme::dim()),
+            )),
+            TickrColumn::Time => right_cell(Span::styled(
+                row.total
+                    .map_or_else(|| "--:--:--".to_string(), format_duration),
+                Style::default().fg(Theme::accent()),
+            )),
+        });
+        Row::new(cells.collect::<Vec<_>>())
+    });
+    Table::new(rows, widths).header(table_header(titles))
+}
+
+fn state_cell(state: TaskState) -> Cell<'static> {
+    match state {
+        TaskState::Running => Cell::from(Span::styled(
+            "running",
+            Style::default().fg(Theme::active()),
+        )),
+        TaskState::Archived => {
+            Cell::from(Span::styled("archived", Style::default().fg(Theme::dim())))
+        }
+        TaskState::Done => Cell::from(Span::styled("done", Style::default().fg(Theme::success()))),
+        TaskState::Blocked => Cell::from(Span::styled(
+            "blocked",
+            Style::default()
+                .fg(Theme::warn())
+                .add_modifier(Modifier::BOLD),
+        )),
+        TaskState::Open => Cell::from(""),
+    }
 }
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use chrono::{Duration, Local};
 use ratatui::{
     style::{Modifier, Style},
     text::{Line, Span, Text},
@@ -6,191 +6,218 @@ use ratatui::{
 
 use super::helpers::format_duration;
 use super::theme::Theme;
-use crate::app::{App, TimelineRange};
+use super::view_model::{self, day_timelines};
+use crate::app::App;
+use crate::types::Tickr;
+
+/// Width of the `  Work : ` prefix in the day view.
+const DAY_PREFIX_WIDTH: usize = 9;
+/// Width of the `  Mon 01-01  ` prefix and `  00:00:00` suffix in the week view.
+const WEEK_DECORATION_WIDTH: usize = 23;
+
+/// Size of a single cell in the timeline bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Resolution {
+    Hour,
+    QuarterHour,
+}
+
+impl Resolution {
+    /// Picks quarter-hour slots when the bar fits next to `decoration` columns.
+    fn for_width(width: u16, decoration: usize) -> Self {
+        if width as usize >= Resolution::QuarterHour.slots() + decoration {
+            Resolution::QuarterHour
+        } else {
+            Resolution::Hour
+        }
+    }
+
+    fn slots(self) -> usize {
+        match self {
+            Resolution::Hour => 24,
+            Resolution::QuarterHour => 96,
+        }
+    }
 
-struct DayTimeline {
-    date: NaiveDate,
-    hours: [u32; 24],
-    total_seconds: i64,
+    fn slot_minutes(self) -> i64 {
+        match self {
+            Resolution::Hour => 60,
+            Resolution::QuarterHour => 15,
+        }
+    }
+
+    fn slots_per_hour(self) -> usize {
+        (60 / self.slot_minutes()) as usize
+    }
+
+    fn legend(self) -> &'static str {
+        match self {
+            Resolution::Hour => "  Legend: . none  : <15m  = <30m  + <45m  # 45m+",
+            Resolution::QuarterHour => {
+                "  Legend (15m slots): . none  : <25%  = <50%  + <75%  # 75%+"
+            }
+        }
+    }
 }
 
-pub fn build_timeline_text(app: &App) -> Text<'_> {
+/// Builds the timeline body; `width` is the inner width of the content area and
+/// decides whether the bars are drawn in hourly or 15-minute slots.
+pub fn build_timeline_text(app: &App, width: u16) -> Text<'_> {
     let now = Local::now();
     let mut lines = Vec::new();
 
-    let (title, days) = match app.timeline_range {
-        TimelineRange::Day => ("Day", vec![now.date_naive()]),
-        TimelineRange::Week => {
-            let start = now.date_naive() - Duration::days(6);
-            let days = (0..7)
-                .map(|offset| start + Duration::days(offset))
-                .collect::<Vec<_>>();
-            ("Week", days)
-        }
-    };
+    let date_format = app.config.date_format.as_str();
+    let range = view_model::timeline_range(
+        app.timeline_range.preset,
+        app.timeline_days(),
+        app.selected_timeline_day(),
+        &app.config.date_format,
+    );
+    let single_day = range.single_day();
 
+    lines.push(super::range_selector_line(&app.timeline_range));
+    lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        format!("  Timeline ({title})"),
+        format!("  Timeline ({})", range.title),
         Style::default()
             .fg(Theme::accent())
             .add_modifier(Modifier::BOLD),
     )));
     lines.push(Line::from(""));
 
-    let timelines = build_day_timelines(&days, app, now);
-
-    match app.timeline_range {
-        TimelineRange::Day => {
-            let timeline = timelines.first();
-            if let Some(timeline) = timeline {
-                lines.push(Line::from(Span::styled(
-                    format!("  Date: {}", timeline.date.format("%Y-%m-%d")),
-                    Style::default().fg(Theme::secondary()),
-                )));
-                lines.push(Line::from(Span::styled(
-                    format!(
-                        "  Total: {}",
-                        format_duration(Duration::seconds(timeline.total_seconds.max(0)))
-                    ),
-                    Style::default().fg(Theme::text()),
-                )));
-                lines.push(Line::from(""));
-                lines.push(Line::from(Span::styled(
-                    format!("  Hours: {}", hour_markers()),
-                    Style::default().fg(Theme::dim()),
-                )));
-                lines.push(Line::from(Span::styled(
-                    format!("  Work : {}", bar_for_hours(&timeline.hours)),
-                    Style::default().fg(Theme::text()),
-                )));
-                lines.push(Line::from(""));
-                lines.push(Line::from(Span::styled(
-                    "  Legend: . none  : <15m  = <30m  + <45m  # 45m+",
-                    Style::default().fg(Theme::dim()),
-                )));
-            } else {
-                lines.push(Line::from("  No data."));
-            }
-        }
-        TimelineRange::Week => {
+    let resolution = if single_day {
+        Resolution::for_width(width, DAY_PREFIX_WIDTH)
+    } else {
+        Resolution::for_width(width, WEEK_DECORATION_WIDTH)
+    };
+    let intervals = app.tickrs.iter().flat_map(|tickr| &tickr.intervals);
+    let timelines = day_timelines(&range.days, intervals, now, resolution.slot_minutes());
+
+    if single_day {
+        let timeline = timelines.first();
+        if let Some(timeline) = timeline {
             lines.push(Line::from(Span::styled(
-                "  Hours: |   |   |   |   |   |",
-                Style::default().fg(Theme::dim()),
+                format!("  Date: {}", timeline.date.format(date_format)),
+                Style::default().fg(Theme::secondary()),
+            )));
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "  Total: {}",
+                    format_duration(Duration::seconds(timeline.total_seconds.max(0)))
+                ),
+                Style::default().fg(Theme::text()),
             )));
             lines.push(Line::from(""));
-            for timeline in timelines {
-                let label = timeline.date.format("%a %m-%d").to_string();
-                let total = format_duration(Duration::seconds(timeline.total_seconds.max(0)));
-                lines.push(Line::from(Span::styled(
-                    format!("  {label}  {}  {total}", bar_for_hours(&timeline.hours)),
-                    Style::default().fg(Theme::text()),
-                )));
-            }
+            lines.push(Line::from(Span::styled(
+                format!("  Hours: {}", slot_markers(resolution)),
+                Style::default().fg(Theme::dim()),
+            )));
+            lines.push(Line::from(Span::styled(
+                format!("  Work : {}", bar_for_slots(&timeline.slots, resolution)),
+                Style::default().fg(Theme::text()),
+            )));
             lines.push(Line::from(""));
             lines.push(Line::from(Span::styled(
-                "  Legend: . none  : <15m  = <30m  + <45m  # 45m+",
+                resolution.legend(),
                 Style::default().fg(Theme::dim()),
             )));
+        } else {
+            lines.push(Line::from("  No data."));
         }
-    }
-
-    Text::from(lines)
-}
-
-fn build_day_timelines(days: &[NaiveDate], app: &App, now: DateTime<Local>) -> Vec<DayTimeline> {
-    let mut timelines = Vec::new();
-
-    for day in days {
-        let mut timeline = DayTimeline {
-            date: *day,
-            hours: [0; 24],
-            total_seconds: 0,
-        };
-        let day_start = local_start_of_day(*day);
-        let day_end = day_start + Duration::days(1);
-
-        for tickr in &app.tickrs {
-            for interval in &tickr.intervals {
-                let start = interval.start_time;
-                let end = interval.end_time.unwrap_or(now);
-                add_interval_to_day(&mut timeline, start, end, day_start, day_end);
-            }
+    } else {
+        lines.push(Line::from(Span::styled(
+            format!("  {:<11}{}", "Hours:", slot_markers(resolution)),
+            Style::default().fg(Theme::dim()),
+        )));
+        lines.push(Line::from(""));
+        for timeline in timelines {
+            let label = timeline.date.format("%a %m-%d").to_string();
+            let total = format_duration(Duration::seconds(timeline.total_seconds.max(0)));
+            let (marker, style) = if timeline.date == range.selected {
+                (
+                    ">",
+                    Style::default()
+                        .fg(Theme::highlight())
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                (" ", Style::default().fg(Theme::text()))
+            };
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "{marker} {label}  {}  {total}",
+                    bar_for_slots(&timeline.slots, resolution)
+                ),
+                style,
+            )));
         }
-        timelines.push(timeline);
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            resolution.legend(),
+            Style::default().fg(Theme::dim()),
+        )));
     }
 
-    timelines
+    Text::from(lines)
 }
 
-fn add_interval_to_day(
-    timeline: &mut DayTimeline,
-    start: DateTime<Local>,
-    end: DateTime<Local>,
-    day_start: DateTime<Local>,
-    day_end: DateTime<Local>,
-) {
-    if end <= day_start || start >= day_end {
-        return;
-    }
-
-    let overlap_start = if start > day_start { start } else { day_start };
-    let overlap_end = if end < day_end { end } else { day_end };
-    let overlap_seconds = overlap_end.signed_duration_since(overlap_start).num_seconds();
-    if overlap_seconds <= 0 {
-        return;
-    }
-    timeline.total_seconds += overlap_seconds;
-
-    for hour in 0..24 {
-        let hour_start = day_start + Duration::hours(hour);
-        let hour_end = hour_start + Duration::hours(1);
-        if overlap_end > hour_start && overlap_start < hour_end {
-            let segment_start = if overlap_start > hour_start {
-                overlap_start
-            } else {
-                hour_start
-            };
-            let segment_end = if overlap_end < hour_end { overlap_end } else { hour_end };
-            let seconds = segment_end
-                .signed_duration_since(segment_start)
-                .num_seconds()
-                .max(0) as u32;
-            timeline.hours[hour as usize] = timeline.hours[hour as usize].saturating_add(seconds);
-        }
+/// The bars of the last `max_days` days `tickr` was worked on, oldest first, laid out like the
+/// week view, for the task detail.
+pub fn task_day_lines(tickr: &Tickr, width: u16, max_days: usize) -> Vec<Line<'static>> {
+    let now = Local::now();
+    let days = view_model::task_days(tickr, now, max_days);
+
+    let resolution = Resolution::for_width(width, WEEK_DECORATION_WIDTH);
+    let mut lines = vec![Line::from(Span::styled(
+        format!("  {:<11}{}", "Hours:", slot_markers(resolution)),
+        Style::default().fg(Theme::dim()),
+    ))];
+    for timeline in day_timelines(
+        &days,
+        tickr.intervals.iter(),
+        now,
+        resolution.slot_minutes(),
+    ) {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  {}  {}  {}",
+                timeline.date.format("%a %m-%d"),
+                bar_for_slots(&timeline.slots, resolution),
+                format_duration(Duration::seconds(timeline.total_seconds.max(0)))
+            ),
+            Style::default().fg(Theme::text()),
+        )));
     }
+    lines.push(Line::from(Span::styled(
+        resolution.legend(),
+        Style::default().fg(Theme::dim()),
+    )));
+    lines
 }
 
-fn bar_for_hours(hours: &[u32; 24]) -> String {
-    hours.iter().map(|&secs| hour_fill(secs)).collect()
+fn bar_for_slots(slots: &[u32], resolution: Resolution) -> String {
+    let slot_seconds = (resolution.slot_minutes() * 60) as u32;
+    slots
+        .iter()
+        .map(|&secs| slot_fill(secs, slot_seconds))
+        .collect()
 }
 
-fn hour_fill(seconds: u32) -> char {
+/// Maps the tracked seconds of a slot to a fill character by quarter of the slot.
+fn slot_fill(seconds: u32, slot_seconds: u32) -> char {
+    let quarter = slot_seconds / 4;
     match seconds {
         0 => '.',
-        1..=899 => ':',
-        900..=1799 => '=',
-        1800..=2699 => '+',
+        s if s < quarter => ':',
+        s if s < quarter * 2 => '=',
+        s if s < quarter * 3 => '+',
         _ => '#',
     }
 }
 
-fn hour_markers() -> String {
-    let mut marker = String::new();
-    for hour in 0..24 {
-        if hour % 4 == 0 {
-            marker.push('|');
-        } else {
-            marker.push(' ');
-        }
-    }
-    marker
-}
-
-fn local_start_of_day(date: NaiveDate) -> DateTime<Local> {
-    let naive = date.and_hms_opt(0, 0, 0).expect("valid time");
-    match Local.from_local_datetime(&naive) {
-        chrono::LocalResult::Single(dt) => dt,
-        chrono::LocalResult::Ambiguous(dt, _) => dt,
-        chrono::LocalResult::None => Local.from_utc_datetime(&naive),
-    }
+fn slot_markers(resolution: Resolution) -> String {
+    let every = 4 * resolution.slots_per_hour();
+    (0..resolution.slots())
+        .map(|slot| if slot % every == 0 { '|' } else { ' ' })
+        .collect()
 }
@@ -0,0 +1,1210 @@
+//! What the views show, worked out from the app state: durations, totals, forecasts, the rows
+//! of the task and project lists and the cells of the calendar and timeline. Nothing here knows
+//! about ratatui, so the view modules only lay out and color these structs. Builders take the
+//! data they need and `now` instead of the app or the clock.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+
+use super::helpers::{days_in_month, local_start_of_day};
+use crate::app::{ProjectSummary, RangePreset, WeekSummary};
+use crate::billing::Amounts;
+use crate::config::Config;
+use crate::goal::GoalProgress;
+use crate::types::{
+    CategoryId, ClientId, Interval, Project, ProjectId, ReportRow, Tickr, TickrCategory,
+};
+
+/// How many projects and tasks the dashboard lists as recent.
+const RECENT_COUNT: usize = 5;
+
+pub struct Dashboard<'a> {
+    pub now: DateTime<Local>,
+    pub running: Option<RunningTask<'a>>,
+    pub today: TodaySummary,
+    /// Projects with a goal, in the order of the project list.
+    pub goals: Vec<(&'a str, &'a GoalProgress)>,
+    pub project_count: usize,
+    pub task_count: usize,
+    pub category_count: usize,
+    pub recent_projects: Vec<RecentProject<'a>>,
+    pub recent_tasks: Vec<RecentTask<'a>>,
+}
+
+pub struct RunningTask<'a> {
+    pub task: &'a str,
+    pub project: &'a str,
+    pub elapsed: Duration,
+}
+
+/// Time tracked today, with the part after midnight of intervals started the day before.
+pub struct TodaySummary {
+    pub billable: Duration,
+    pub non_billable: Duration,
+    pub tasks: usize,
+    pub projects: usize,
+    pub budget: Option<Duration>,
+}
+
+impl TodaySummary {
+    pub fn total(&self) -> Duration {
+        self.billable + self.non_billable
+    }
+
+    /// What is left of the daily budget, negative for overtime.
+    pub fn budget_left(&self) -> Option<Duration> {
+        self.budget.map(|budget| budget - self.total())
+    }
+}
+
+pub struct RecentProject<'a> {
+    pub name: &'a str,
+    pub total: Duration,
+    pub last_worked: Option<DateTime<Local>>,
+}
+
+pub struct RecentTask<'a> {
+    pub description: &'a str,
+    pub category: Option<&'a TickrCategory>,
+    pub total: Duration,
+    pub last_worked: Option<DateTime<Local>>,
+}
+
+/// What the dashboard is made of.
+pub struct DashboardData<'a> {
+    /// In the order of the project list, the first ones counting as recent.
+    pub projects: &'a [Project],
+    pub tree: ProjectTree<'a>,
+    /// Totals of each project on its own.
+    pub project_summaries: &'a HashMap<ProjectId, ProjectSummary>,
+    pub project_goals: &'a HashMap<ProjectId, GoalProgress>,
+    /// Most recently worked first.
+    pub tickrs: &'a [Tickr],
+    pub categories: &'a HashMap<CategoryId, TickrCategory>,
+    pub daily_budget: Option<Duration>,
+}
+
+pub fn dashboard(data: DashboardData<'_>, now: DateTime<Local>) -> Dashboard<'_> {
+    let DashboardData {
+        projects,
+        tree,
+        project_summaries,
+        project_goals,
+        tickrs,
+        categories,
+        daily_budget,
+    } = data;
+    let project_name = |tickr: &Tickr| {
+        projects
+            .iter()
+            .find(|project| project.id == Some(tickr.project_id))
+            .map_or("Unknown", |project| project.name.as_str())
+    };
+    let running = tickrs.iter().find_map(|tickr| {
+        let interval = tickr.intervals.iter().find(|i| i.end_time.is_none())?;
+        Some(RunningTask {
+            task: &tickr.description,
+            project: project_name(tickr),
+            elapsed: interval.duration(now),
+        })
+    });
+
+    let today_start = local_start_of_day(now.date_naive());
+    let today_end = today_start + Duration::days(1);
+    let mut today = TodaySummary {
+        billable: Duration::zero(),
+        non_billable: Duration::zero(),
+        tasks: 0,
+        projects: 0,
+        budget: daily_budget,
+    };
+    let mut today_projects = HashSet::new();
+    for tickr in tickrs {
+        let duration = tickr
+            .intervals
+            .iter()
+            .map(|interval| interval.duration_within(today_start, today_end, now))
+            .fold(Duration::zero(), |sum, duration| sum + duration);
+        if duration <= Duration::zero() {
+            continue;
+        }
+        if tickr.billable {
+            today.billable += duration;
+        } else {
+            today.non_billable += duration;
+        }
+        today.tasks += 1;
+        today_projects.insert(tickr.project_id);
+    }
+    today.projects = today_projects.len();
+
+    let goals = projects
+        .iter()
+        .filter_map(|project| {
+            let progress = project_goals.get(&project.id?)?;
+            Some((project.name.as_str(), progress))
+        })
+        .collect();
+    let recent_projects = projects
+        .iter()
+        .take(RECENT_COUNT)
+        .map(|project| {
+            let summary = tree.summary(project, project_summaries);
+            RecentProject {
+                name: &project.name,
+                total: Duration::seconds(summary.total_seconds.max(0)),
+                last_worked: summary.last_worked,
+            }
+        })
+        .collect();
+    let recent_tasks = tickrs
+        .iter()
+        .take(RECENT_COUNT)
+        .map(|tickr| RecentTask {
+            description: &tickr.description,
+            category: category_of(tickr, categories),
+            total: total_duration(tickr.intervals.iter(), now),
+            last_worked: last_worked(tickr, now),
+        })
+        .collect();
+
+    Dashboard {
+        now,
+        running,
+        today,
+        goals,
+        project_count: projects.len(),
+        task_count: tickrs.len(),
+        category_count: categories.len(),
+        recent_projects,
+        recent_tasks,
+    }
+}
+
+/// Time tracked today on the tasks of one category.
+pub struct CategoryShare<'a> {
+    /// `None` for tasks without a category.
+    pub category: Option<&'a TickrCategory>,
+    pub duration: Duration,
+}
+
+/// Today's time per category for the header strip, most first and tasks without a category
+/// last. Like the dashboard, it counts the part after midnight of intervals started yesterday.
+pub fn today_by_category<'a>(
+    tickrs: &'a [Tickr],
+    categories: &'a HashMap<CategoryId, TickrCategory>,
+    now: DateTime<Local>,
+) -> Vec<CategoryShare<'a>> {
+    let today_start = local_start_of_day(now.date_naive());
+    let today_end = today_start + Duration::days(1);
+    let mut shares: Vec<CategoryShare> = Vec::new();
+    for tickr in tickrs {
+        let duration = tickr
+            .intervals
+            .iter()
+            .map(|interval| interval.duration_within(today_start, today_end, now))
+            .fold(Duration::zero(), |sum, duration| sum + duration);
+        if duration <= Duration::zero() {
+            continue;
+        }
+        let category = category_of(tickr, categories);
+        let id = category.map(|category| category.id);
+        match shares
+            .iter_mut()
+            .find(|share| share.category.map(|category| category.id) == id)
+        {
+            Some(share) => share.duration += duration,
+            None => shares.push(CategoryShare { category, duration }),
+        }
+    }
+    shares.sort_by_key(|share| (share.category.is_none(), -share.duration));
+    shares
+}
+
+/// How many of `width` cells each share gets, in proportion to its time. The cells add up to
+/// `width`; a share too small for a cell of its own gets none.
+pub fn strip_cells(shares: &[CategoryShare], width: usize) -> Vec<usize> {
+    let total: i64 = shares
+        .iter()
+        .map(|share| share.duration.num_seconds())
+        .sum();
+    if total <= 0 {
+        return vec![0; shares.len()];
+    }
+    // Rounding the running total instead of each share keeps rounding errors from adding up.
+    let mut seconds = 0;
+    let mut filled = 0;
+    shares
+        .iter()
+        .map(|share| {
+            seconds += share.duration.num_seconds();
+            let end = (seconds as f64 / total as f64 * width as f64).round() as usize;
+            let cells = end - filled;
+            filled = end;
+            cells
+        })
+        .collect()
+}
+
+/// What a task is up to, for the State column of the task lists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    Archived,
+    Done,
+    Blocked,
+    Open,
+}
+
+impl TaskState {
+    pub fn of(tickr: &Tickr) -> Self {
+        let running = tickr
+            .intervals
+            .last()
+            .is_some_and(|interval| interval.end_time.is_none());
+        if running {
+            Self::Running
+        } else if tickr.archived {
+            Self::Archived
+        } else if tickr.done {
+            Self::Done
+        } else if tickr.blocked_by.is_some() {
+            Self::Blocked
+        } else {
+            Self::Open
+        }
+    }
+}
+
+/// A row of the task lists.
+pub struct TaskRow<'a> {
+    pub description: &'a str,
+    pub state: TaskState,
+    pub category: Option<&'a TickrCategory>,
+    pub intervals: usize,
+    pub last_worked: Option<DateTime<Local>>,
+    /// `None` for a task that was never started.
+    pub total: Option<Duration>,
+}
+
+pub fn task_rows<'a>(
+    tickrs: &'a [Tickr],
+    categories: &'a HashMap<CategoryId, TickrCategory>,
+    now: DateTime<Local>,
+) -> Vec<TaskRow<'a>> {
+    tickrs
+        .iter()
+        .map(|tickr| TaskRow {
+            description: &tickr.description,
+            state: TaskState::of(tickr),
+            category: category_of(tickr, categories),
+            intervals: tickr.intervals.len(),
+            last_worked: last_worked(tickr, now),
+            total: (!tickr.intervals.is_empty())
+                .then(|| total_duration(tickr.intervals.iter(), now)),
+        })
+        .collect()
+}
+
+/// Where a task stands, for the detail view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskStatus {
+    NotStarted,
+    Running,
+    Done,
+    Ended,
+}
+
+impl TaskStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::NotStarted => "Not started",
+            Self::Running => "Running",
+            Self::Done => "Done",
+            Self::Ended => "Ended",
+        }
+    }
+}
+
+/// What the detail view works out from a task's intervals.
+pub struct TaskDetail {
+    pub status: TaskStatus,
+    pub first_start: Option<DateTime<Local>>,
+    /// End of the last interval, `None` while it runs or before the first.
+    pub last_end: Option<DateTime<Local>>,
+    /// `None` for a task that was never started.
+    pub total: Option<Duration>,
+}
+
+pub fn task_detail(tickr: &Tickr, now: DateTime<Local>) -> TaskDetail {
+    let last = tickr.intervals.last();
+    let status = match last {
+        None => TaskStatus::NotStarted,
+        Some(interval) if interval.end_time.is_none() => TaskStatus::Running,
+        Some(_) if tickr.done => TaskStatus::Done,
+        Some(_) => TaskStatus::Ended,
+    };
+    TaskDetail {
+        status,
+        first_start: tickr.intervals.first().map(|interval| interval.start_time),
+        last_end: last.and_then(|interval| interval.end_time),
+        total: last.map(|_| total_duration(tickr.intervals.iter(), now)),
+    }
+}
+
+/// Time tracked on a task in one month.
+pub struct MonthTotal {
+    /// First day of the month.
+    pub month: NaiveDate,
+    pub intervals: usize,
+    pub duration: Duration,
+}
+
+/// Totals per month of `tickr`, newest first. An interval counts toward the month it started in.
+pub fn month_totals(tickr: &Tickr, now: DateTime<Local>) -> Vec<MonthTotal> {
+    let mut months: Vec<MonthTotal> = Vec::new();
+    for interval in &tickr.intervals {
+        let start = interval.start_time.date_naive();
+        let month = start.with_day(1).unwrap_or(start);
+        let duration = interval.duration(now);
+        match months.iter_mut().find(|total| total.month == month) {
+            Some(total) => {
+                total.intervals += 1;
+                total.duration += duration;
+            }
+            None => months.push(MonthTotal {
+                month,
+                intervals: 1,
+                duration,
+            }),
+        }
+    }
+    months.sort_by_key(|total| std::cmp::Reverse(total.month));
+    months
+}
+
+/// Indices of the rows on `page` of a list of `len` rows read oldest first, when page 0 holds
+/// the newest `per_page` of them.
+pub fn newest_first_page(len: usize, page: usize, per_page: usize) -> std::ops::Range<usize> {
+    let end = len.saturating_sub(page * per_page);
+    end.saturating_sub(per_page)..end
+}
+
+/// How the project lists group sub-projects below their parents.
+#[derive(Clone, Copy)]
+pub struct ProjectTree<'a> {
+    pub sub_projects: &'a HashMap<ProjectId, Vec<ProjectId>>,
+    /// Parents shown as one row with the totals of their sub-projects.
+    pub collapsed: &'a HashSet<ProjectId>,
+}
+
+impl<'a> ProjectTree<'a> {
+    /// The project itself, followed by its sub-projects while it is collapsed.
+    fn rolled_up(&self, project: &Project) -> impl Iterator<Item = ProjectId> + 'a {
+        let children = project
+            .id
+            .filter(|id| self.collapsed.contains(id))
+            .and_then(|id| self.sub_projects.get(&id))
+            .map(|children| children.as_slice())
+            .unwrap_or_default();
+        project.id.into_iter().chain(children.iter().copied())
+    }
+
+    /// Totals of `project`, including its sub-projects while it is collapsed.
+    pub fn summary(
+        &self,
+        project: &Project,
+        summaries: &HashMap<ProjectId, ProjectSummary>,
+    ) -> ProjectSummary {
+        let mut summary = ProjectSummary::default();
+        for part in self.rolled_up(project).filter_map(|id| summaries.get(&id)) {
+            summary.total_seconds += part.total_seconds;
+            summary.ended += part.ended;
+            summary.open += part.open;
+            summary.last_worked = summary.last_worked.max(part.last_worked);
+        }
+        summary
+    }
+
+    fn name<'p>(&self, project: &'p Project) -> ProjectName<'p> {
+        let children = project.id.and_then(|id| self.sub_projects.get(&id));
+        let marker = match children {
+            _ if project.parent_id.is_some() => TreeMarker::Child,
+            Some(children) => TreeMarker::Parent {
+                collapsed: project.id.is_some_and(|id| self.collapsed.contains(&id)),
+                children: children.len(),
+            },
+            None if self.sub_projects.is_empty() => TreeMarker::Flat,
+            None => TreeMarker::Leaf,
+        };
+        ProjectName {
+            name: &project.name,
+            archived: project.archived,
+            marker,
+        }
+    }
+}
+
+/// Where a project's name sits in the tree of parents and sub-projects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeMarker {
+    /// In a list without sub-projects.
+    Flat,
+    /// A top-level project without sub-projects, lined up with the parents.
+    Leaf,
+    Parent {
+        collapsed: bool,
+        children: usize,
+    },
+    /// Below its parent.
+    Child,
+}
+
+/// The name column of the project lists.
+pub struct ProjectName<'a> {
+    pub name: &'a str,
+    pub archived: bool,
+    pub marker: TreeMarker,
+}
+
+/// A row of the Projects tab.
+pub struct ProjectRow<'a> {
+    pub name: ProjectName<'a>,
+    /// With the sub-projects of a collapsed parent.
+    pub summary: ProjectSummary,
+    pub goal: Option<&'a GoalProgress>,
+    pub client: Option<&'a str>,
+}
+
+pub fn project_rows<'a>(
+    projects: &'a [Project],
+    tree: ProjectTree<'_>,
+    summaries: &HashMap<ProjectId, ProjectSummary>,
+    goals: &'a HashMap<ProjectId, GoalProgress>,
+    clients: &'a HashMap<ClientId, String>,
+) -> Vec<ProjectRow<'a>> {
+    projects
+        .iter()
+        .map(|project| ProjectRow {
+            name: tree.name(project),
+            summary: tree.summary(project, summaries),
+            goal: project.id.and_then(|id| goals.get(&id)),
+            client: project
+                .client_id
+                .and_then(|id| clients.get(&id))
+                .map(String::as_str),
+        })
+        .collect()
+}
+
+/// A row of the Worked tab.
+pub struct WorkedProjectRow<'a> {
+    pub name: ProjectName<'a>,
+    /// In the range of the tab, with the sub-projects of a collapsed parent.
+    pub worked: Duration,
+}
+
+pub fn worked_project_rows<'a>(
+    projects: &'a [Project],
+    tree: ProjectTree<'_>,
+    worked_seconds: &HashMap<ProjectId, i64>,
+) -> Vec<WorkedProjectRow<'a>> {
+    projects
+        .iter()
+        .map(|project| WorkedProjectRow {
+            name: tree.name(project),
+            worked: Duration::seconds(
+                tree.rolled_up(project)
+                    .filter_map(|id| worked_seconds.get(&id))
+                    .sum(),
+            ),
+        })
+        .collect()
+}
+
+fn category_of<'a>(
+    tickr: &Tickr,
+    categories: &'a HashMap<CategoryId, TickrCategory>,
+) -> Option<&'a TickrCategory> {
+    tickr.category_id.and_then(|id| categories.get(&id))
+}
+
+/// End of the latest interval of `tickr`, `now` while one runs.
+fn last_worked(tickr: &Tickr, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    tickr
+        .intervals
+        .iter()
+        .map(|interval| interval.end_time.unwrap_or(now))
+        .max()
+}
+
+fn total_duration<'a>(
+    intervals: impl Iterator<Item = &'a Interval>,
+    now: DateTime<Local>,
+) -> Duration {
+    intervals.fold(Duration::zero(), |sum, interval| {
+        sum + interval.duration(now)
+    })
+}
+
+/// Extrapolation of the current month from the pace so far.
+pub struct MonthForecast {
+    pub month_start: NaiveDate,
+    pub day: u32,
+    pub days_in_month: i64,
+    /// Share of the month that has passed, between 0 and 1.
+    pub elapsed: f64,
+    pub tracked_seconds: i64,
+    pub amounts: Amounts,
+}
+
+/// How the forecast compares with the monthly goal.
+pub struct GoalForecast {
+    pub goal_hours: f64,
+    /// Forecast minus goal, negative while behind.
+    pub difference: f64,
+    /// Hours a day still needed and the whole days left, while the goal isn't reached and a day
+    /// or more remains.
+    pub needed_per_day: Option<(f64, f64)>,
+}
+
+impl MonthForecast {
+    pub fn new(month_report: &[ReportRow], now: DateTime<Local>) -> Self {
+        let today = now.date_naive();
+        let month_start = today.with_day(1).unwrap_or(today);
+        let next_month = if month_start.month() == 12 {
+            NaiveDate::from_ymd_opt(month_start.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(month_start.year(), month_start.month() + 1, 1)
+        }
+        .unwrap_or(month_start + Duration::days(31));
+        let start = local_start_of_day(month_start);
+        let length = local_start_of_day(next_month).signed_duration_since(start);
+        let passed = now.signed_duration_since(start);
+        let mut amounts = Amounts::default();
+        for row in month_report {
+            amounts.merge(&row.amounts);
+        }
+        Self {
+            month_start,
+            day: today.day(),
+            days_in_month: (next_month - month_start).num_days(),
+            elapsed: (passed.num_seconds() as f64 / length.num_seconds().max(1) as f64)
+                .clamp(0.0, 1.0),
+            tracked_seconds: month_report.iter().map(|row| row.seconds).sum(),
+            amounts,
+        }
+    }
+
+    /// Less than a day of data makes the extrapolation meaningless.
+    pub fn has_pace(&self) -> bool {
+        self.elapsed * self.days_in_month as f64 >= 1.0
+    }
+
+    pub fn tracked_hours(&self) -> f64 {
+        self.tracked_seconds as f64 / 3600.0
+    }
+
+    pub fn projected_hours(&self) -> f64 {
+        self.tracked_hours() / self.elapsed
+    }
+
+    pub fn projected_amounts(&self) -> Amounts {
+        self.amounts.scaled(1.0 / self.elapsed)
+    }
+
+    fn remaining_days(&self) -> f64 {
+        (1.0 - self.elapsed) * self.days_in_month as f64
+    }
+
+    /// Comparison with `goal_hours`, `None` without a positive goal.
+    pub fn goal(&self, goal_hours: Option<f64>) -> Option<GoalForecast> {
+        let goal_hours = goal_hours.filter(|goal| *goal > 0.0)?;
+        let missing = goal_hours - self.tracked_hours();
+        let remaining_days = self.remaining_days();
+        Some(GoalForecast {
+            goal_hours,
+            difference: self.projected_hours() - goal_hours,
+            needed_per_day: (missing > 0.0 && remaining_days >= 1.0)
+                .then(|| (missing / remaining_days, remaining_days.floor())),
+        })
+    }
+}
+
+/// Average time per day of `week` up to `today`.
+pub fn week_average(week: &WeekSummary, week_start: NaiveDate, today: NaiveDate) -> i64 {
+    let days_so_far = ((today - week_start).num_days() + 1).clamp(1, 7);
+    week.total() / days_so_far
+}
+
+/// Seconds of each of `week`'s projects over the whole week, in the order of its projects.
+pub fn week_project_totals(week: &WeekSummary) -> Vec<i64> {
+    (0..week.projects.len())
+        .map(|index| week.days.iter().filter_map(|day| day.get(index)).sum())
+        .collect()
+}
+
+/// A month grid starting on the configured first day of the week.
+pub struct CalendarMonth {
+    pub first: NaiveDate,
+    pub selected: NaiveDate,
+    pub days_in_month: u32,
+    /// Short weekday names of the columns.
+    pub weekdays: Vec<String>,
+    /// Rows of seven cells, `None` for days of the neighboring months.
+    pub weeks: Vec<Vec<Option<CalendarDay>>>,
+    pub selected_seconds: i64,
+    pub total_seconds: i64,
+    pub tracked_days: usize,
+}
+
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub seconds: i64,
+    /// 1 to 4 relative to the busiest day, 0 without time tracked.
+    pub heat: usize,
+    pub today: bool,
+    pub selected: bool,
+}
+
+/// The month of `selected` with the seconds `tracked` per day.
+pub fn calendar_month(
+    selected: NaiveDate,
+    tracked: &HashMap<NaiveDate, i64>,
+    config: &Config,
+    today: NaiveDate,
+) -> CalendarMonth {
+    let first = selected.with_day(1).unwrap_or(selected);
+    let grid_start = config.calendar_week_start_for(first);
+    let seconds = |date| tracked.get(&date).copied().unwrap_or(0);
+    let max = tracked.values().copied().max().unwrap_or(0);
+
+    let mut weeks = Vec::new();
+    let mut week_start = grid_start;
+    while week_start.month() == first.month() || week_start < first {
+        let week = (0..7)
+            .map(|day| {
+                let date = week_start + Duration::days(day);
+                (date.month() == first.month()).then(|| {
+                    let seconds = seconds(date);
+                    CalendarDay {
+                        date,
+                        seconds,
+                        heat: if seconds > 0 {
+                            heat_level(seconds, max)
+                        } else {
+                            0
+                        },
+                        today: date == today,
+                        selected: date == selected,
+                    }
+                })
+            })
+            .collect();
+        weeks.push(week);
+        week_start += Duration::days(7);
+    }
+
+    CalendarMonth {
+        first,
+        selected,
+        days_in_month: days_in_month(first),
+        weekdays: (0..7)
+            .map(|day| (grid_start + Duration::days(day)).format("%a").to_string())
+            .collect(),
+        weeks,
+        selected_seconds: seconds(selected),
+        total_seconds: tracked.values().sum(),
+        tracked_days: tracked.values().filter(|seconds| **seconds > 0).count(),
+    }
+}
+
+/// 1 to 4, the share of `max` in quarters rounded up.
+fn heat_level(seconds: i64, max: i64) -> usize {
+    if max <= 0 {
+        return 1;
+    }
+    ((seconds * 4 + max - 1) / max).clamp(1, 4) as usize
+}
+
+/// The days of the Timeline range and the heading above them.
+pub struct TimelineRange {
+    pub title: String,
+    pub days: Vec<NaiveDate>,
+    pub selected: NaiveDate,
+}
+
+impl TimelineRange {
+    /// A single day gets the detailed layout, longer ranges a bar per day.
+    pub fn single_day(&self) -> bool {
+        self.days.len() == 1
+    }
+}
+
+/// The days from `first` to `last`, picked with `preset`, with `selected` among them.
+pub fn timeline_range(
+    preset: RangePreset,
+    (first, last): (NaiveDate, NaiveDate),
+    selected: NaiveDate,
+    date_format: &str,
+) -> TimelineRange {
+    let days: Vec<NaiveDate> = (0..=(last - first).num_days())
+        .map(|offset| first + Duration::days(offset))
+        .collect();
+    let single_day = days.len() == 1;
+    let title = match preset {
+        RangePreset::Custom if single_day => first.format(date_format).to_string(),
+        RangePreset::Custom => format!(
+            "{} - {}",
+            first.format(date_format),
+            last.format(date_format)
+        ),
+        preset if single_day => preset.label().to_string(),
+        preset => format!(
+            "{} {} - {}",
+            preset.label(),
+            first.format(date_format),
+            last.format(date_format)
+        ),
+    };
+    TimelineRange {
+        title,
+        days,
+        selected,
+    }
+}
+
+/// Seconds tracked in each slot of a day.
+pub struct DayTimeline {
+    pub date: NaiveDate,
+    pub slots: Vec<u32>,
+    pub total_seconds: i64,
+}
+
+/// The time of `intervals` on each of `days`, in slots of `slot_minutes`.
+pub fn day_timelines<'a>(
+    days: &[NaiveDate],
+    intervals: impl Iterator<Item = &'a Interval> + Clone,
+    now: DateTime<Local>,
+    slot_minutes: i64,
+) -> Vec<DayTimeline> {
+    let slot_count = (24 * 60 / slot_minutes) as usize;
+    days.iter()
+        .map(|day| {
+            let mut timeline = DayTimeline {
+                date: *day,
+                slots: vec![0; slot_count],
+                total_seconds: 0,
+            };
+            let day_start = local_start_of_day(*day);
+            let day_end = day_start + Duration::days(1);
+            for interval in intervals.clone() {
+                let start = interval.start_time;
+                let end = interval.end_time.unwrap_or(now);
+                add_interval_to_day(&mut timeline, start, end, day_start, day_end, slot_minutes);
+            }
+            timeline
+        })
+        .collect()
+}
+
+fn add_interval_to_day(
+    timeline: &mut DayTimeline,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    day_start: DateTime<Local>,
+    day_end: DateTime<Local>,
+    slot_minutes: i64,
+) {
+    if end <= day_start || start >= day_end {
+        return;
+    }
+
+    let overlap_start = start.max(day_start);
+    let overlap_end = end.min(day_end);
+    let overlap_seconds = overlap_end
+        .signed_duration_since(overlap_start)
+        .num_seconds();
+    if overlap_seconds <= 0 {
+        return;
+    }
+    timeline.total_seconds += overlap_seconds;
+
+    let slot_length = Duration::minutes(slot_minutes);
+    for (slot, seconds) in timeline.slots.iter_mut().enumerate() {
+        let slot_start = day_start + slot_length * slot as i32;
+        let slot_end = slot_start + slot_length;
+        if overlap_end > slot_start && overlap_start < slot_end {
+            let segment = overlap_end
+                .min(slot_end)
+                .signed_duration_since(overlap_start.max(slot_start));
+            *seconds = seconds.saturating_add(segment.num_seconds().max(0) as u32);
+        }
+    }
+}
+
+/// The last `max_days` days `tickr` was worked on, oldest first.
+pub fn task_days(tickr: &Tickr, now: DateTime<Local>, max_days: usize) -> Vec<NaiveDate> {
+    let mut days: Vec<NaiveDate> = tickr
+        .intervals
+        .iter()
+        .flat_map(|interval| {
+            let first = interval.start_time.date_naive();
+            let last = interval.end_time.unwrap_or(now).date_naive();
+            first.iter_days().take_while(move |day| *day <= last)
+        })
+        .collect();
+    days.sort_unstable();
+    days.dedup();
+    days.split_off(days.len().saturating_sub(max_days))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 6, day, hour, minute, 0)
+            .single()
+            .expect("unambiguous time")
+    }
+
+    fn interval(start: DateTime<Local>, end: Option<DateTime<Local>>) -> Interval {
+        Interval {
+            id: None,
+            entry_id: 1,
+            start_time: start,
+            end_time: end,
+            notes: None,
+        }
+    }
+
+    fn tickr(description: &str, project_id: ProjectId, intervals: Vec<Interval>) -> Tickr {
+        Tickr {
+            id: Some(1),
+            project_id,
+            description: description.to_string(),
+            category_id: None,
+            billable: true,
+            done: false,
+            blocked_by: None,
+            notes: None,
+            estimate_minutes: None,
+            archived: false,
+            issue: None,
+            intervals,
+        }
+    }
+
+    fn project(id: ProjectId, name: &str, parent_id: Option<ProjectId>) -> Project {
+        Project {
+            id: Some(id),
+            name: name.to_string(),
+            created_at: at(1, 9, 0),
+            archived: false,
+            parent_id,
+            client_id: None,
+        }
+    }
+
+    fn category(id: CategoryId, name: &str) -> TickrCategory {
+        TickrCategory {
+            name: name.to_string(),
+            id,
+            color: "#ffffff".to_string(),
+        }
+    }
+
+    #[test]
+    fn dashboard_counts_today_from_midnight_and_the_running_task_up_to_now() {
+        let now = at(12, 10, 0);
+        let mut meeting = tickr(
+            "Meeting",
+            2,
+            vec![interval(at(11, 23, 0), Some(at(12, 1, 0)))],
+        );
+        meeting.billable = false;
+        let tickrs = vec![
+            tickr("Build", 1, vec![interval(at(12, 9, 0), None)]),
+            meeting,
+            tickr("Old", 1, vec![interval(at(10, 9, 0), Some(at(10, 10, 0)))]),
+        ];
+        let projects = vec![project(1, "Web", None), project(2, "Admin", None)];
+        let (sub_projects, collapsed) = (HashMap::new(), HashSet::new());
+        let (summaries, goals, categories) = (HashMap::new(), HashMap::new(), HashMap::new());
+        let dashboard = dashboard(
+            DashboardData {
+                projects: &projects,
+                tree: ProjectTree {
+                    sub_projects: &sub_projects,
+                    collapsed: &collapsed,
+                },
+                project_summaries: &summaries,
+                project_goals: &goals,
+                tickrs: &tickrs,
+                categories: &categories,
+                daily_budget: Some(Duration::hours(3)),
+            },
+            now,
+        );
+
+        let running = dashboard.running.expect("a running task");
+        assert_eq!((running.task, running.project), ("Build", "Web"));
+        assert_eq!(running.elapsed, Duration::hours(1));
+        assert_eq!(dashboard.today.billable, Duration::hours(1));
+        assert_eq!(dashboard.today.non_billable, Duration::hours(1));
+        assert_eq!((dashboard.today.tasks, dashboard.today.projects), (2, 2));
+        assert_eq!(dashboard.today.budget_left(), Some(Duration::hours(1)));
+        assert_eq!(dashboard.recent_tasks[2].last_worked, Some(at(10, 10, 0)));
+    }
+
+    #[test]
+    fn collapsed_parents_roll_up_their_sub_projects() {
+        let projects = vec![project(1, "Client", None), project(3, "Other", None)];
+        let sub_projects = HashMap::from([(1, vec![2])]);
+        let summary = |total_seconds, open| ProjectSummary {
+            total_seconds,
+            open,
+            ..ProjectSummary::default()
+        };
+        let summaries = HashMap::from([(1, summary(60, 1)), (2, summary(120, 2))]);
+        let collapsed = HashSet::from([1]);
+        let tree = ProjectTree {
+            sub_projects: &sub_projects,
+            collapsed: &collapsed,
+        };
+
+        let (goals, clients) = (HashMap::new(), HashMap::new());
+        let rows = project_rows(&projects, tree, &summaries, &goals, &clients);
+        assert_eq!(rows[0].summary.total_seconds, 180);
+        assert_eq!(rows[0].summary.open, 3);
+        assert_eq!(
+            rows[0].name.marker,
+            TreeMarker::Parent {
+                collapsed: true,
+                children: 1
+            }
+        );
+        assert_eq!(rows[1].name.marker, TreeMarker::Leaf);
+
+        let expanded = HashSet::new();
+        let tree = ProjectTree {
+            sub_projects: &sub_projects,
+            collapsed: &expanded,
+        };
+        let worked = HashMap::from([(1, 60), (2, 120)]);
+        let rows = worked_project_rows(&projects[..1], tree, &worked);
+        assert_eq!(rows[0].worked, Duration::seconds(60));
+    }
+
+    #[test]
+    fn sub_projects_are_indented_below_their_parent() {
+        let projects = vec![project(1, "Client", None), project(2, "Site", Some(1))];
+        let sub_projects = HashMap::from([(1, vec![2])]);
+        let tree = ProjectTree {
+            sub_projects: &sub_projects,
+            collapsed: &HashSet::new(),
+        };
+        let rows = worked_project_rows(&projects, tree, &HashMap::new());
+        assert_eq!(rows[1].name.marker, TreeMarker::Child);
+
+        let flat = HashMap::new();
+        let tree = ProjectTree {
+            sub_projects: &flat,
+            collapsed: &HashSet::new(),
+        };
+        assert_eq!(tree.name(&projects[0]).marker, TreeMarker::Flat);
+    }
+
+    #[test]
+    fn task_rows_show_state_and_no_total_before_the_first_interval() {
+        let now = at(12, 10, 0);
+        let mut archived = tickr("Archived", 1, vec![interval(at(12, 9, 0), None)]);
+        archived.archived = true;
+        let mut blocked = tickr("Blocked", 1, Vec::new());
+        blocked.blocked_by = Some(7);
+        blocked.category_id = Some(4);
+        let tickrs = vec![archived, blocked];
+        let categories = HashMap::from([(4, category(4, "Dev"))]);
+
+        let rows = task_rows(&tickrs, &categories, now);
+        // A running task shows as running even when archived.
+        assert_eq!(rows[0].state, TaskState::Running);
+        assert_eq!(rows[0].total, Some(Duration::hours(1)));
+        assert_eq!(rows[0].last_worked, Some(now));
+        assert_eq!(rows[1].state, TaskState::Blocked);
+        assert_eq!(rows[1].total, None);
+        assert_eq!(rows[1].category.map(|category| category.id), Some(4));
+    }
+
+    #[test]
+    fn task_detail_and_month_totals() {
+        let now = at(12, 10, 0);
+        let mut done = tickr(
+            "Report",
+            1,
+            vec![
+                interval(at(12, 8, 0), Some(at(12, 9, 0))),
+                interval(at(13, 8, 0), Some(at(13, 8, 30))),
+            ],
+        );
+        done.done = true;
+        let detail = task_detail(&done, now);
+        assert_eq!(detail.status, TaskStatus::Done);
+        assert_eq!(detail.first_start, Some(at(12, 8, 0)));
+        assert_eq!(detail.last_end, Some(at(13, 8, 30)));
+        assert_eq!(detail.total, Some(Duration::minutes(90)));
+        assert_eq!(
+            task_detail(&tickr("New", 1, Vec::new()), now).status,
+            TaskStatus::NotStarted
+        );
+
+        let months = month_totals(&done, now);
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].intervals, 2);
+        assert_eq!(months[0].duration, Duration::minutes(90));
+    }
+
+    #[test]
+    fn pages_start_with_the_newest_rows() {
+        assert_eq!(newest_first_page(25, 0, 10), 15..25);
+        assert_eq!(newest_first_page(25, 2, 10), 0..5);
+        assert_eq!(newest_first_page(0, 0, 10), 0..0);
+    }
+
+    #[test]
+    fn categories_are_sorted_by_time_with_uncategorized_last() {
+        let now = at(12, 12, 0);
+        let mut dev = tickr("Dev", 1, vec![interval(at(12, 8, 0), Some(at(12, 9, 0)))]);
+        dev.category_id = Some(1);
+        let mut meetings = tickr(
+            "Standup",
+            1,
+            vec![interval(at(12, 9, 0), Some(at(12, 11, 0)))],
+        );
+        meetings.category_id = Some(2);
+        let other = tickr("Other", 1, vec![interval(at(12, 11, 0), None)]);
+        let tickrs = vec![dev, other, meetings];
+        let categories = HashMap::from([(1, category(1, "Dev")), (2, category(2, "Meetings"))]);
+
+        let shares = today_by_category(&tickrs, &categories, now);
+        let names: Vec<_> = shares
+            .iter()
+            .map(|share| share.category.map(|category| category.name.as_str()))
+            .collect();
+        assert_eq!(names, [Some("Meetings"), Some("Dev"), None]);
+        assert_eq!(strip_cells(&shares, 8), [4, 2, 2]);
+    }
+
+    #[test]
+    fn strip_cells_fill_the_width_and_skip_tiny_shares() {
+        let shares =
+            [Duration::seconds(1000), Duration::seconds(1)].map(|duration| CategoryShare {
+                category: None,
+                duration,
+            });
+        assert_eq!(strip_cells(&shares, 10), [10, 0]);
+        assert_eq!(strip_cells(&[], 10), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn month_forecast_extrapolates_the_pace() {
+        let row = ReportRow {
+            seconds: 10 * 3600,
+            ..ReportRow::default()
+        };
+        // Half of June has passed when the 16th begins.
+        let forecast = MonthForecast::new(&[row], at(16, 0, 0));
+        assert!(forecast.has_pace());
+        assert!((forecast.projected_hours() - 20.0).abs() < 0.01);
+        let goal = forecast.goal(Some(30.0)).expect("a goal");
+        assert!((goal.difference + 10.0).abs() < 0.01);
+        let (per_day, days) = goal.needed_per_day.expect("time left");
+        assert_eq!(days, 15.0);
+        assert!((per_day - 20.0 / 15.0).abs() < 0.01);
+        assert!(forecast.goal(Some(0.0)).is_none());
+    }
+
+    #[test]
+    fn week_totals_and_average() {
+        let week = WeekSummary {
+            start: NaiveDate::from_ymd_opt(2024, 6, 10),
+            projects: vec![(1, "Web".to_string()), (2, "Admin".to_string())],
+            days: vec![vec![3600, 600], vec![1800, 0], vec![0, 0]],
+        };
+        assert_eq!(week_project_totals(&week), [5400, 600]);
+        let monday = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        assert_eq!(
+            week_average(&week, monday, monday + Duration::days(1)),
+            3000
+        );
+    }
+
+    #[test]
+    fn calendar_month_marks_today_and_heat() {
+        let selected = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap();
+        let tracked = HashMap::from([(selected, 4 * 3600), (selected + Duration::days(1), 3600)]);
+        let month = calendar_month(selected, &tracked, &Config::default(), selected);
+
+        // June 2024 starts on a Saturday, so the first Monday row holds five other days.
+        assert_eq!(month.weeks[0].iter().filter(|day| day.is_none()).count(), 5);
+        assert_eq!(month.weekdays[0], "Mon");
+        assert_eq!(month.days_in_month, 30);
+        assert_eq!((month.total_seconds, month.tracked_days), (5 * 3600, 2));
+        let days: Vec<&CalendarDay> = month.weeks.iter().flatten().flatten().collect();
+        let day = |date: NaiveDate| days.iter().find(|day| day.date == date).unwrap();
+        assert!(day(selected).today && day(selected).selected);
+        assert_eq!(day(selected).heat, 4);
+        assert_eq!(day(selected + Duration::days(1)).heat, 1);
+        assert_eq!(day(selected + Duration::days(2)).heat, 0);
+    }
+
+    #[test]
+    fn timeline_titles() {
+        let first = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let last = first + Duration::days(6);
+        let week = timeline_range(RangePreset::ThisWeek, (first, last), first, "%d.%m.");
+        assert_eq!(week.title, "This week 10.06. - 16.06.");
+        assert_eq!(week.days.len(), 7);
+        assert!(!week.single_day());
+        let day = timeline_range(RangePreset::Custom, (first, first), first, "%d.%m.");
+        assert_eq!(day.title, "10.06.");
+        assert!(day.single_day());
+    }
+
+    #[test]
+    fn day_timelines_split_intervals_at_midnight_and_into_slots() {
+        let intervals = [interval(at(11, 23, 30), Some(at(12, 0, 45)))];
+        let days = [
+            NaiveDate::from_ymd_opt(2024, 6, 11).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 6, 12).unwrap(),
+        ];
+        let timelines = day_timelines(&days, intervals.iter(), at(12, 12, 0), 60);
+        assert_eq!(timelines[0].total_seconds, 30 * 60);
+        assert_eq!(timelines[0].slots[23], 30 * 60);
+        assert_eq!(timelines[1].total_seconds, 45 * 60);
+        assert_eq!(timelines[1].slots[0], 45 * 60);
+    }
+
+    #[test]
+    fn task_days_keep_the_latest() {
+        let task = tickr(
+            "Build",
+            1,
+            vec![
+                interval(at(3, 9, 0), Some(at(3, 10, 0))),
+                interval(at(5, 23, 0), Some(at(6, 1, 0))),
+                interval(at(8, 9, 0), None),
+            ],
+        );
+        let days = task_days(&task, at(9, 9, 0), 3);
+        let expected: Vec<NaiveDate> = [6, 8, 9]
+            .into_iter()
+            .map(|day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap())
+            .collect();
+        assert_eq!(days, expected);
+    }
+}
@@ -1,34 +1,157 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{StatusCode, header};
 use self_update::cargo_crate_version;
+use serde::{Deserialize, Serialize};
 
 const REPO_OWNER: &str = "loknopf";
 const REPO_NAME: &str = "Tickr";
 
-/// Check if a newer version is available on GitHub releases
+/// Minimum time between two requests to the GitHub API.
+const CHECK_INTERVAL_HOURS: i64 = 12;
+/// Keep startup snappy when GitHub is slow or unreachable.
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Last response of the releases API, stored in the user's cache directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UpdateCache {
+    etag: Option<String>,
+    latest_version: Option<String>,
+    checked_at: Option<DateTime<Utc>>,
+    /// Set when GitHub rate limited us; no request is made before this time.
+    retry_after: Option<DateTime<Utc>>,
+}
+
+/// Check if a newer version is available on GitHub releases.
+///
+/// The API is asked at most every `CHECK_INTERVAL_HOURS` and with the cached ETag, so an
+/// unchanged release costs no rate limit. Rate limiting and network problems fall back to the
+/// cached release instead of failing.
 pub fn check_for_updates() -> Result<Option<String>> {
     let current_version = cargo_crate_version!();
-    
-    let releases = self_update::backends::github::ReleaseList::configure()
-        .repo_owner(REPO_OWNER)
-        .repo_name(REPO_NAME)
-        .build()?
-        .fetch()?;
-    
-    if let Some(latest_release) = releases.first() {
-        let latest_version = latest_release.version.trim_start_matches('v');
-        
-        if latest_version != current_version {
-            return Ok(Some(latest_version.to_string()));
+    let mut cache = load_cache().unwrap_or_default();
+    let now = Utc::now();
+
+    let fresh = cache
+        .checked_at
+        .is_some_and(|checked| now - checked < Duration::hours(CHECK_INTERVAL_HOURS));
+    let limited = cache.retry_after.is_some_and(|retry| now < retry);
+    if !fresh && !limited {
+        if let Ok(response) = fetch_latest_release(&cache) {
+            apply_response(&mut cache, response, now);
         }
+        // Failed requests are retried on the next launch.
+        save_cache(&cache).ok();
     }
-    
-    Ok(None)
+
+    Ok(cache
+        .latest_version
+        .filter(|latest| is_newer(current_version, latest)))
+}
+
+enum ReleaseResponse {
+    Changed {
+        etag: Option<String>,
+        version: String,
+    },
+    NotModified,
+    RateLimited {
+        reset: Option<DateTime<Utc>>,
+    },
+}
+
+fn fetch_latest_release(cache: &UpdateCache) -> Result<ReleaseResponse> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("tickr/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let mut request = client
+        .get(format!(
+            "https://api.github.com/repos/{REPO_OWNER}/{REPO_NAME}/releases/latest"
+        ))
+        .header(header::ACCEPT, "application/vnd.github+json");
+    if let Some(etag) = &cache.etag {
+        request = request.header(header::IF_NONE_MATCH, etag);
+    }
+    let response = request.send()?;
+
+    match response.status() {
+        StatusCode::NOT_MODIFIED => Ok(ReleaseResponse::NotModified),
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => {
+            let reset = response
+                .headers()
+                .get("x-ratelimit-reset")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<i64>().ok())
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0));
+            Ok(ReleaseResponse::RateLimited { reset })
+        }
+        status if status.is_success() => {
+            let etag = response
+                .headers()
+                .get(header::ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let release: serde_json::Value = serde_json::from_str(&response.text()?)?;
+            let tag = release
+                .get("tag_name")
+                .and_then(|tag| tag.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Release without tag_name"))?;
+            Ok(ReleaseResponse::Changed {
+                etag,
+                version: tag.trim_start_matches('v').to_string(),
+            })
+        }
+        status => anyhow::bail!("GitHub responded with {status}"),
+    }
+}
+
+fn apply_response(cache: &mut UpdateCache, response: ReleaseResponse, now: DateTime<Utc>) {
+    match response {
+        ReleaseResponse::Changed { etag, version } => {
+            cache.etag = etag;
+            cache.latest_version = Some(version);
+            cache.checked_at = Some(now);
+            cache.retry_after = None;
+        }
+        ReleaseResponse::NotModified => {
+            cache.checked_at = Some(now);
+            cache.retry_after = None;
+        }
+        ReleaseResponse::RateLimited { reset } => {
+            cache.retry_after = Some(reset.unwrap_or(now + Duration::hours(1)));
+        }
+    }
+}
+
+fn is_newer(current: &str, latest: &str) -> bool {
+    self_update::version::bump_is_greater(current, latest).unwrap_or(latest != current)
+}
+
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("tickr").join("update-check.json"))
+}
+
+fn load_cache() -> Option<UpdateCache> {
+    let raw = std::fs::read_to_string(cache_path()?).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+fn save_cache(cache: &UpdateCache) -> Result<()> {
+    let path = cache_path().ok_or_else(|| anyhow::anyhow!("No cache directory"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(cache)?)?;
+    Ok(())
 }
 
 /// Perform the self-update by downloading and replacing the current binary
 pub fn perform_update() -> Result<()> {
     let current_version = cargo_crate_version!();
-    
+
     let status = self_update::backends::github::Update::configure()
         .repo_owner(REPO_OWNER)
         .repo_name(REPO_NAME)
@@ -37,12 +160,12 @@ pub fn perform_update() -> Result<()> {
         .current_version(current_version)
         .build()?
         .update()?;
-    
+
     if status.updated() {
         println!("Updated to version: {}", status.version());
     } else {
         println!("Already up to date");
     }
-    
+
     Ok(())
 }